@@ -0,0 +1,53 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, adaptBitrate, type BitrateChange } from '../index.js';
+
+describe('setTargetBitrate', () => {
+  it('should convert bit/s to the unit of the encoder', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! vp8enc name=vp8 ! fakesink x264enc name=h264 ! fakesink');
+    expect(kit.setTargetBitrate('vp8', 1_500_000)).toBe(1_500_000);
+    expect(kit.getProperty('vp8', 'target-bitrate')).toContain('1500000');
+    // x264enc takes kbit/s
+    expect(kit.setTargetBitrate('h264', 1_500_000)).toBe(1_500_000);
+    expect(kit.getProperty('h264', 'bitrate')).toContain('1500');
+    kit.cleanup();
+  });
+
+  it('should reject elements without a bitrate', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! identity name=id ! fakesink');
+    expect(() => kit.setTargetBitrate('id', 1_000_000)).toThrow('has no bitrate property');
+    expect(() => kit.setTargetBitrate('missing', 1_000_000)).toThrow('not found');
+    kit.cleanup();
+  });
+});
+
+describe('adaptBitrate', () => {
+  it('should lower the bitrate while the output queue backs up', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'videotestsrc is-live=true ! video/x-raw,width=320,height=240,framerate=30/1 ! vp8enc name=enc deadline=1 target-bitrate=2000000 ' +
+        '! queue name=out max-size-time=0 max-size-buffers=0 max-size-bytes=0 ! identity sleep-time=100000 ! fakesink'
+    );
+    kit.play();
+    const adapter = adaptBitrate(kit, 'enc', { minBitrate: 500_000, intervalMs: 300, queue: 'out', maxQueueMs: 200 });
+    const changes: BitrateChange[] = [];
+    adapter.onChange(change => changes.push(change));
+    await new Promise(resolve => setTimeout(resolve, 2500));
+    adapter.stop();
+    kit.cleanup();
+
+    expect(changes.length).toBeGreaterThan(0);
+    expect(changes[0].reason).toBe('congestion');
+    expect(changes[0].bitrate).toBeLessThan(changes[0].previous);
+    expect(changes[0].queueMs).toBeGreaterThan(200);
+    expect(adapter.bitrate).toBeGreaterThanOrEqual(500_000);
+  });
+
+  it('should fail for encoders that are not in the pipeline', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink');
+    expect(() => adaptBitrate(kit, 'enc')).toThrow('Element not found: enc');
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,37 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, PipelineManager } from '../index.js';
+
+describe('adoptPipeline', () => {
+  it('should take over a managed pipeline in its current state', () => {
+    const manager = new PipelineManager();
+    manager.addPipeline('cam', 'videotestsrc ! video/x-raw,format=RGBA,width=32,height=32 ! appsink name=sink');
+    manager.play('cam');
+
+    const kit = new GstKit();
+    kit.adoptPipeline(manager, 'cam');
+    expect(manager.listPipelines()).toEqual([]);
+    const frame = kit.pullFrame('sink', { timeoutMs: 2000 });
+    expect(frame?.width).toBe(32);
+    kit.cleanup();
+  });
+
+  it('should reject unknown ids', () => {
+    expect(() => new GstKit().adoptPipeline(new PipelineManager(), 'nope')).toThrow('Pipeline not found: nope');
+  });
+});
+
+describe('setPipeline', () => {
+  it('should reject a single element that is not a pipeline', () => {
+    const kit = new GstKit();
+    expect(() => kit.setPipeline('fakesrc name=src num-buffers=5')).toThrow('Provided string is not a valid pipeline');
+    kit.cleanup();
+  });
+
+  it('should forget the playlist of the previous pipeline', () => {
+    const kit = new GstKit();
+    kit.setPlaylist(['file:///a.ogg', 'file:///b.ogg']);
+    kit.setPipeline('fakesrc num-buffers=5 ! fakesink');
+    expect(() => kit.next()).toThrow('No playlist entry to move to');
+    kit.cleanup();
+  });
+});
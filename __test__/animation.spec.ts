@@ -0,0 +1,44 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+describe('animateProperty', () => {
+  it('should apply keyframes on the pipeline clock', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'audiotestsrc num-buffers=50 samplesperbuffer=960 ! audio/x-raw,rate=48000 ! volume name=vol ! fakesink sync=false'
+    );
+    kit.animateProperty('vol', 'volume', [
+      { timeMs: 0, value: 0 },
+      { timeMs: 500, value: 0.5 },
+      { timeMs: 1000, value: 0.5 },
+    ]);
+    kit.play();
+    await new Promise(resolve => setTimeout(resolve, 500));
+    expect(Number(kit.getProperty('vol', 'volume').replace(/^\(\w+\) /, ''))).toBeCloseTo(0.5, 2);
+    expect(kit.clearAnimation('vol', 'volume')).toBe(true);
+    expect(kit.clearAnimation('vol', 'volume')).toBe(false);
+    kit.cleanup();
+  });
+
+  it('should animate pad properties', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! compositor name=mix ! fakesink');
+    expect(() =>
+      kit.animateProperty('mix.sink_0', 'xpos', [{ timeMs: 0, value: 0 }, { timeMs: 1000, value: 100 }], {
+        interpolation: 'cubic-monotonic',
+      })
+    ).not.toThrow();
+    kit.cleanup();
+  });
+
+  it('should reject properties that cannot be animated', () => {
+    const kit = new GstKit();
+    kit.setPipeline('audiotestsrc ! volume name=vol ! fakesink');
+    expect(() => kit.animateProperty('vol', 'nope', [{ timeMs: 0, value: 1 }])).toThrow();
+    expect(() => kit.animateProperty('vol', 'name', [{ timeMs: 0, value: 1 }])).toThrow();
+    expect(() => kit.animateProperty('vol', 'volume', [])).toThrow();
+    expect(() => kit.animateProperty('vol', 'volume', [{ timeMs: 0, value: 1 }], { interpolation: 'bounce' })).toThrow();
+    expect(() => kit.animateProperty('missing', 'volume', [{ timeMs: 0, value: 1 }])).toThrow();
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,68 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, getAttachments, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('attachments', () => {
+  let inputFile: string;
+  let coverFile: string;
+  let notesFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('attachments_input.avi', 'smpte', { numBuffers: 10 });
+    coverFile = path.join(TEST_DIR, 'poster.png');
+    const kit = new GstKit();
+    kit.setPipeline(
+      `videotestsrc num-buffers=1 ! video/x-raw,width=64,height=64 ! pngenc ! filesink location="${coverFile}"`
+    );
+    kit.play();
+    await new Promise(resolve => setTimeout(resolve, 500));
+    kit.stop();
+    kit.cleanup();
+    notesFile = path.join(TEST_DIR, 'notes.txt');
+    fs.writeFileSync(notesFile, 'Shot on location');
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should embed attachments in Matroska output and read them back', () => {
+    const output = path.join(TEST_DIR, 'attachments.mkv');
+    fs.writeFileSync(
+      output,
+      transcodeBuffer(fs.readFileSync(inputFile), {
+        container: 'mkv',
+        attachments: [
+          { path: coverFile, name: 'cover.png', description: 'Poster' },
+          { path: notesFile, mimeType: 'text/markdown' },
+        ],
+      })
+    );
+
+    const attachments = getAttachments(output);
+    expect(attachments.map(a => a.name)).toEqual(['cover.png', 'notes.txt']);
+    expect(attachments[0].mimeType).toBe('image/png');
+    expect(attachments[0].description).toBe('Poster');
+    expect(Buffer.from(attachments[0].data).equals(fs.readFileSync(coverFile))).toBe(true);
+    expect(attachments[1].mimeType).toBe('text/markdown');
+    expect(Buffer.from(attachments[1].data).toString()).toBe('Shot on location');
+  });
+
+  it('should return no attachments for files without any', () => {
+    const output = path.join(TEST_DIR, 'plain.mkv');
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv' }));
+    expect(getAttachments(output)).toEqual([]);
+  });
+
+  it('should reject attachments for other containers and missing files', () => {
+    const input = fs.readFileSync(inputFile);
+    expect(() => transcodeBuffer(input, { container: 'webm', attachments: [{ path: notesFile }] })).toThrow(/mkv/);
+    expect(() =>
+      transcodeBuffer(input, { container: 'mkv', attachments: [{ path: path.join(TEST_DIR, 'missing.png') }] })
+    ).toThrow();
+    expect(() => getAttachments(inputFile)).toThrow();
+  });
+});
@@ -0,0 +1,55 @@
+import { describe, it, expect } from 'bun:test';
+import { AudioMixer } from '../index.js';
+
+describe('AudioMixer', () => {
+  it('should add, list and remove inputs', () => {
+    const mixer = new AudioMixer({ output: 'fakesink' });
+    mixer.addInput('b', 'audiotestsrc');
+    mixer.addInput('a', 'audiotestsrc wave=square');
+    expect(mixer.listInputs()).toEqual(['a', 'b']);
+    expect(() => mixer.addInput('a', 'audiotestsrc')).toThrow();
+    expect(mixer.removeInput('a')).toBe(true);
+    expect(mixer.removeInput('a')).toBe(false);
+    expect(mixer.listInputs()).toEqual(['b']);
+  });
+
+  it('should set volume and mute per input', () => {
+    const mixer = new AudioMixer({ output: 'fakesink' });
+    mixer.addInput('music', 'audiotestsrc', { volume: 0.5, mute: true });
+    expect(mixer.getVolume('music')).toBeCloseTo(0.5);
+    expect(mixer.isMuted('music')).toBe(true);
+
+    mixer.setVolume('music', 2);
+    mixer.setMute('music', false);
+    expect(mixer.getVolume('music')).toBeCloseTo(2);
+    expect(mixer.isMuted('music')).toBe(false);
+    expect(() => mixer.setVolume('missing', 1)).toThrow();
+  });
+
+  it('should report levels per input', async () => {
+    const mixer = new AudioMixer({ output: 'fakesink' });
+    mixer.addInput('loud', 'audiotestsrc volume=1.0');
+    mixer.addInput('quiet', 'audiotestsrc volume=0.01');
+    mixer.play();
+    await new Promise(r => setTimeout(r, 500));
+
+    const levels = mixer.getLevels();
+    expect(levels.map(l => l.id)).toEqual(['loud', 'quiet']);
+    expect(levels[0].peak.length).toBeGreaterThan(0);
+    expect(Math.max(...levels[0].peak)).toBeGreaterThan(Math.max(...levels[1].peak));
+    mixer.stop();
+  });
+
+  it('should apply audio filters before measuring an input', async () => {
+    const mixer = new AudioMixer({ output: 'fakesink' });
+    mixer.addInput('plain', 'audiotestsrc');
+    mixer.addInput('filtered', 'audiotestsrc', { filters: ['gain=-40', 'channels=mono'] });
+    expect(() => mixer.addInput('bad', 'audiotestsrc', { filters: ['gain'] })).toThrow();
+    mixer.play();
+    await new Promise(r => setTimeout(r, 500));
+
+    const [filtered, plain] = mixer.getLevels();
+    expect(Math.max(...plain.peak) - Math.max(...filtered.peak)).toBeGreaterThan(30);
+    mixer.stop();
+  });
+});
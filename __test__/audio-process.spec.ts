@@ -0,0 +1,105 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, extractAudioForASR, processAudio, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo, generateTestVideoWithAudio } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+// Writes `seconds` of a 440 Hz tone at the given volume as 44.1 kHz stereo WAV
+const generateTone = async (filename: string, seconds: number, volume = 0.8) => {
+  const outputPath = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    `audiotestsrc volume=${volume} samplesperbuffer=4410 num-buffers=${seconds * 10} ! ` +
+      `audio/x-raw,rate=44100,channels=2 ! wavenc ! filesink location="${outputPath}"`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 500));
+  kit.stop();
+  kit.cleanup();
+  return outputPath;
+};
+
+describe('processAudio', () => {
+  let first: string;
+  let second: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    first = await generateTone('process_first.wav', 2);
+    second = await generateTone('process_second.wav', 1, 0.1);
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should trim and fade a WAV file', () => {
+    const output = path.join(TEST_DIR, 'process_trim.wav');
+    const result = processAudio(first, output, {
+      trim: { startMs: 500, endMs: 1500 },
+      fade: { inMs: 200, outMs: 200 },
+    });
+    expect(result.durationMs).toBeCloseTo(1000, 0);
+    expect(result.sampleRate).toBe(44100);
+    expect(result.channels).toBe(2);
+    expect(result.gainDb).toBe(0);
+    expect(fs.readFileSync(output).subarray(0, 4).toString()).toBe('RIFF');
+  });
+
+  it('should concatenate inputs in order', () => {
+    const output = path.join(TEST_DIR, 'process_concat.wav');
+    const result = processAudio([first, second], output);
+    expect(result.durationMs).toBeCloseTo(3000, 0);
+    expect(probeWithGStreamer(output).duration / 1e6).toBeCloseTo(3000, -2);
+  });
+
+  it('should normalize the peak level', () => {
+    const result = processAudio(second, path.join(TEST_DIR, 'process_normalized.wav'), { normalize: -1 });
+    // A tone at volume 0.1 peaks at -20 dBFS
+    expect(result.gainDb).toBeCloseTo(19, 0);
+  });
+
+  it('should encode to the format of the output extension', () => {
+    const output = path.join(TEST_DIR, 'process_encoded.ogg');
+    processAudio(first, output, { trim: { endMs: 1000 } });
+    expect(fs.readFileSync(output).subarray(0, 4).toString()).toBe('OggS');
+    expect(probeWithGStreamer(output).audio[0].codec.toLowerCase()).toContain('vorbis');
+  });
+
+  it('should reject invalid arguments', () => {
+    const output = path.join(TEST_DIR, 'process_invalid.wav');
+    expect(() => processAudio(first, path.join(TEST_DIR, 'process.xyz'))).toThrow();
+    expect(() => processAudio(first, output, { trim: { startMs: 1000, endMs: 500 } })).toThrow();
+    expect(() => processAudio(path.join(TEST_DIR, 'missing.wav'), output)).toThrow();
+    expect(() => processAudio([], output)).toThrow();
+  });
+});
+
+describe('extractAudioForASR', () => {
+  let withAudio: string;
+  let withoutAudio: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    withAudio = await generateTestVideoWithAudio('asr_input.avi', 'smpte', 'sine', { numBuffers: 30 });
+    withoutAudio = await generateTestVideo('asr_silent.avi', 'smpte', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should write 16 kHz mono 16-bit WAV', () => {
+    const output = path.join(TEST_DIR, 'asr_output.wav');
+    extractAudioForASR(withAudio, output);
+    const wav = fs.readFileSync(output);
+    expect(wav.subarray(0, 4).toString()).toBe('RIFF');
+    expect(wav.readUInt16LE(22)).toBe(1);
+    expect(wav.readUInt32LE(24)).toBe(16000);
+    expect(wav.readUInt16LE(34)).toBe(16);
+  });
+
+  it('should reject inputs without audio', () => {
+    expect(() => extractAudioForASR(withoutAudio, path.join(TEST_DIR, 'asr_none.wav'))).toThrow();
+  });
+});
@@ -0,0 +1,102 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { diagnosePipeline, GstKit } from '../index.js';
+import setup, { generateTestVideoWithAudio } from './setup.js';
+
+async function playing(): Promise<GstKit> {
+  const kit = new GstKit();
+  kit.setPipeline('audiotestsrc ! audio/x-raw,format=S16LE,rate=48000 ! appsink name=sink sync=false max-buffers=4');
+  await kit.playAsync(5000);
+  return kit;
+}
+
+describe('audio tempo and pitch', () => {
+  it('should insert scaletempo in front of the audio sink', async () => {
+    const kit = await playing();
+    kit.setTempo(1.5);
+    kit.pullSample('sink', 2000);
+    const elements = kit.getElements();
+    kit.cleanup();
+
+    expect(elements).toContain('gstkit-scaletempo');
+  });
+
+  it('should keep samples flowing after the tempo changes', async () => {
+    const kit = await playing();
+    kit.setTempo(2);
+    const samples = [1, 2, 3, 4, 5].map(() => kit.pullAudioSample('sink', { timeoutMs: 2000 }));
+    kit.cleanup();
+
+    expect(samples.every((sample) => sample !== null)).toBe(true);
+    const timestamps = samples.map((sample) => sample!.timestamp);
+    expect(timestamps).toEqual([...timestamps].sort((a, b) => a - b));
+  });
+
+  it('should reuse the element on later changes', async () => {
+    const kit = await playing();
+    kit.setTempo(0.5);
+    kit.setTempo(1);
+    const elements = kit.getElements().filter((name) => name === 'gstkit-scaletempo');
+    kit.cleanup();
+
+    expect(elements).toHaveLength(1);
+  });
+
+  it('should reject tempos that are not positive', async () => {
+    const kit = await playing();
+    expect(() => kit.setTempo(0)).toThrow('Tempo must be positive');
+    expect(() => kit.setTempo(-1)).toThrow('Tempo must be positive');
+    kit.cleanup();
+  });
+
+  it('should reject pitch shifts over two octaves', async () => {
+    const kit = await playing();
+    expect(() => kit.setPitch(25)).toThrow('within two octaves');
+    kit.cleanup();
+  });
+
+  const hasPitch = diagnosePipeline('pitch').missing.length === 0;
+
+  it.skipIf(!hasPitch)('should shift the pitch with the SoundTouch plugin', async () => {
+    const kit = await playing();
+    kit.setPitch(-2);
+    expect(kit.pullSample('sink', 2000)).not.toBeNull();
+    const elements = kit.getElements();
+    kit.cleanup();
+
+    expect(elements).toContain('gstkit-pitch');
+  });
+
+  it('should need an audio sink', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink');
+    await kit.playAsync(5000);
+    expect(() => kit.setTempo(2)).toThrow('No playing audio sink found');
+    kit.cleanup();
+  });
+
+  describe('in a playbin', () => {
+    let media: string;
+
+    beforeAll(async () => {
+      setup.setupTestDirectories();
+      media = await generateTestVideoWithAudio('tempo_input.avi', 'smpte', 'sine', { numBuffers: 200 });
+    });
+
+    afterAll(() => {
+      setup.cleanupTestDirectories();
+    });
+
+    it('should change the tempo of the audio sink inside playsink', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        `playbin uri=file://${media} video-sink=fakesink audio-sink="appsink name=audio sync=false max-buffers=4"`,
+      );
+      await kit.playAsync(5000);
+      kit.setTempo(1.5);
+      const samples = [1, 2, 3].map(() => kit.pullSample('audio', 2000));
+      kit.cleanup();
+
+      expect(samples.every((sample) => sample !== null)).toBe(true);
+    });
+  });
+});
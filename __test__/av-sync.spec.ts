@@ -0,0 +1,73 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, measureAvSync, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo, generateTestVideoWithAudio } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+/**
+ * Records 10 seconds of raw audio and video in Matroska. Caps claiming a 1%
+ * higher sample rate than the samples were timestamped at make the audio
+ * run 100 ms short of its timestamps by the end.
+ */
+async function generateDriftingVideo(filename: string): Promise<string> {
+  const output = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    `videotestsrc num-buffers=300 ! video/x-raw,width=320,height=240,framerate=30/1 ! jpegenc ! queue ! ` +
+      `matroskamux name=mux ! filesink location="${output}" ` +
+      `audiotestsrc samplesperbuffer=960 num-buffers=500 ! audio/x-raw,format=S16LE,rate=48000,channels=1 ! ` +
+      `capssetter caps="audio/x-raw,rate=48480" ! queue ! mux.`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 5000));
+  kit.stop();
+  kit.cleanup();
+  return output;
+}
+
+describe('measureAvSync', () => {
+  let drifting: string;
+  let steady: string;
+  let silent: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    drifting = await generateDriftingVideo('drifting.mkv');
+    steady = await generateTestVideoWithAudio('steady.avi', 'ball', 'sine', { numBuffers: 60 });
+    silent = await generateTestVideo('silent.avi', 'ball', { numBuffers: 30 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should report no drift for audio that matches its timestamps', () => {
+    const report = measureAvSync(steady);
+    expect(Math.abs(report.maxDrift)).toBeLessThan(5_000_000);
+    expect(report.offsets[0].minute).toBe(0);
+  });
+
+  it('should report audio running ahead of its timestamps', () => {
+    const report = measureAvSync(drifting);
+    expect(report.maxDrift).toBeGreaterThan(80_000_000);
+    expect(report.finalDrift).toBe(report.maxDrift);
+    expect(report.offsets).toEqual([{ minute: 0, offset: 0 }]);
+    expect(Math.abs(report.startOffset)).toBeLessThan(50_000_000);
+  });
+
+  it('should reject files without audio', () => {
+    expect(() => measureAvSync(silent)).toThrow();
+  });
+
+  it('should remove the drift with asyncCorrect', () => {
+    const output = path.join(TEST_DIR, 'corrected.mkv');
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(drifting), { container: 'mkv', asyncCorrect: true }));
+    expect(Math.abs(measureAvSync(output).maxDrift)).toBeLessThan(45_000_000);
+  });
+
+  it('should require re-encoding the audio for asyncCorrect', () => {
+    expect(() =>
+      transcodeBuffer(fs.readFileSync(drifting), { container: 'mkv', audioCodec: 'copy', asyncCorrect: true })
+    ).toThrow();
+  });
+});
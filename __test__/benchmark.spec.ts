@@ -0,0 +1,28 @@
+import { describe, it, expect } from 'bun:test';
+import { runBenchmark } from '../index.js';
+
+describe('runBenchmark', () => {
+  it('should report encode, decode and filter results', () => {
+    const results = runBenchmark({ resolution: '320x240', seconds: 0.5, codecs: ['mjpeg'], threadCounts: [1] });
+
+    const encode = results.find(r => r.name === 'mjpeg' && r.operation === 'encode');
+    expect(encode).toBeDefined();
+    expect(encode!.threads).toBe(1);
+    expect(encode!.frames).toBe(15);
+    expect(encode!.fps).toBeGreaterThan(0);
+
+    expect(results.some(r => r.operation === 'decode')).toBe(true);
+    expect(results.filter(r => r.operation === 'filter').length).toBeGreaterThan(0);
+  });
+
+  it('should report unknown codecs as failed runs', () => {
+    const results = runBenchmark({ resolution: '160x120', seconds: 0.1, codecs: ['nope'], threadCounts: [1] });
+    const encode = results.find(r => r.name === 'nope' && r.operation === 'encode');
+    expect(encode!.fps).toBe(0);
+    expect(encode!.error).toBeDefined();
+  });
+
+  it('should throw on an invalid resolution', () => {
+    expect(() => runBenchmark({ resolution: 'big' })).toThrow();
+  });
+});
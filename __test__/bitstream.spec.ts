@@ -0,0 +1,87 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { exportBitstream, generateTestMedia, getSupportedCodecs, inspectBitstream } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('inspectBitstream', () => {
+  const webm = path.join(TEST_DIR, 'bitstream.webm');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(webm, { format: 'webm', width: 160, height: 120, fps: 10, duration: 1, audio: true });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should list the frames of every stream', () => {
+    const frames = inspectBitstream(webm);
+    const video = frames.filter(f => f.codec === 'video/x-vp8');
+    const audio = frames.filter(f => f.codec.startsWith('audio/'));
+    expect(video.length).toBe(10);
+    expect(audio.length).toBeGreaterThan(0);
+    expect(video[0].keyframe).toBe(true);
+    expect(video[0].pts).toBe(0);
+    expect(video[1].pts).toBeGreaterThan(video[0].pts);
+    expect(video.every(f => f.size > 0 && f.units.length === 0)).toBe(true);
+    expect(new Set(frames.map(f => f.stream)).size).toBe(2);
+  });
+
+  it('should list the NAL unit types of H.264 frames', () => {
+    if (!getSupportedCodecs().some(c => c.name === 'h264')) {
+      console.log('Skipping: no H.264 encoder available');
+      return;
+    }
+    const mp4 = path.join(TEST_DIR, 'bitstream.mp4');
+    generateTestMedia(mp4, { format: 'mp4', videoCodec: 'h264', width: 160, height: 120, fps: 10, duration: 1 });
+    const frames = inspectBitstream(mp4);
+    expect(frames[0].codec).toBe('video/x-h264');
+    expect(frames[0].keyframe).toBe(true);
+    expect(frames[0].units).toContain('IDR');
+    expect(frames.filter(f => !f.keyframe).every(f => !f.units.includes('IDR'))).toBe(true);
+  });
+
+  it('should reject missing files', () => {
+    expect(() => inspectBitstream(path.join(TEST_DIR, 'missing.webm'))).toThrow();
+  });
+});
+
+describe('exportBitstream', () => {
+  const webm = path.join(TEST_DIR, 'export.webm');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(webm, { format: 'webm', width: 160, height: 120, fps: 10, duration: 1 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should write one JSON object per frame', () => {
+    const output = path.join(TEST_DIR, 'frames.ndjson');
+    const count = exportBitstream(webm, output);
+    const lines = fs.readFileSync(output, 'utf8').trim().split('\n');
+    expect(count).toBe(10);
+    expect(lines.length).toBe(10);
+    expect(lines.map(line => JSON.parse(line))).toEqual(inspectBitstream(webm));
+  });
+
+  it('should write CSV with a header row', () => {
+    const output = path.join(TEST_DIR, 'frames.csv');
+    expect(exportBitstream(webm, output)).toBe(10);
+    const [header, first] = fs.readFileSync(output, 'utf8').split('\n');
+    expect(header).toBe('stream,codec,pts,dts,duration,size,keyframe,units');
+    expect(first.startsWith('0,video/x-vp8,0,')).toBe(true);
+    expect(first.split(',')[6]).toBe('true');
+  });
+
+  it('should honour an explicit format', () => {
+    const output = path.join(TEST_DIR, 'frames.txt');
+    exportBitstream(webm, output, 'csv');
+    expect(fs.readFileSync(output, 'utf8').startsWith('stream,codec')).toBe(true);
+    expect(() => exportBitstream(webm, output, 'xml')).toThrow(/Unsupported export format/);
+  });
+});
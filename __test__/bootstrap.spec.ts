@@ -0,0 +1,26 @@
+import { describe, it, expect } from 'bun:test';
+import { configureGStreamer, verifyPlugins } from '../index.js';
+
+describe('verifyPlugins', () => {
+  it('should report available and missing elements', () => {
+    const report = verifyPlugins(['videotestsrc', 'appsink', 'no_such_element_1234']);
+    expect(report.available).toEqual(['videotestsrc', 'appsink']);
+    expect(report.missing).toEqual(['no_such_element_1234']);
+    expect(report.ok).toBe(false);
+    expect(verifyPlugins(['videotestsrc']).ok).toBe(true);
+  });
+});
+
+describe('configureGStreamer', () => {
+  it('should scan plugin paths into a running registry', () => {
+    expect(() => configureGStreamer({ pluginPaths: ['/nonexistent/gstreamer-1.0'] })).not.toThrow();
+    expect(verifyPlugins(['videotestsrc']).ok).toBe(true);
+  });
+
+  it('should reject settings that only apply before initialization', () => {
+    verifyPlugins([]);
+    const message = 'registryPath and disableSegtrap must be set before GStreamer is initialized';
+    expect(() => configureGStreamer({ registryPath: '/tmp/registry.bin' })).toThrow(message);
+    expect(() => configureGStreamer({ disableSegtrap: true })).toThrow(message);
+  });
+});
@@ -0,0 +1,17 @@
+import { describe, it, expect } from 'bun:test';
+import { getBuildInfo } from '../index.js';
+
+describe('getBuildInfo', () => {
+  it('should report versions, features and CPU details', () => {
+    const info = getBuildInfo();
+    expect(info.version).toMatch(/^\d+\.\d+\.\d+/);
+    expect(info.gstreamerVersion).toMatch(/^1\.\d+/);
+    expect(Array.isArray(info.features)).toBe(true);
+    expect(['avx512', 'avx2', 'sse4.2', 'sse2', 'neon', 'none']).toContain(info.simd);
+    expect(info.target.length).toBeGreaterThan(0);
+    expect(typeof info.debug).toBe('boolean');
+    if (info.gitHash) {
+      expect(info.gitHash).toMatch(/^[0-9a-f]+$/);
+    }
+  });
+});
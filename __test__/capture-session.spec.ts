@@ -0,0 +1,68 @@
+import { describe, it, expect } from 'bun:test';
+import { CaptureSession, type CaptureFrame } from '../index.js';
+
+const CAMERA = 'videotestsrc is-live=true ! video/x-raw,width=160,height=120,framerate=30/1';
+
+describe('CaptureSession', () => {
+  it('should add, list and remove sources', () => {
+    const session = new CaptureSession();
+    session.addSource('right', CAMERA);
+    session.addSource('left', CAMERA);
+    expect(session.listSources()).toEqual(['left', 'right']);
+    expect(session.removeSource('left')).toBe(true);
+    expect(session.removeSource('left')).toBe(false);
+    expect(session.listSources()).toEqual(['right']);
+  });
+
+  it('should reject duplicate ids, invalid sources and unknown formats', () => {
+    const session = new CaptureSession();
+    session.addSource('cam', CAMERA);
+    expect(() => session.addSource('cam', CAMERA)).toThrow();
+    expect(() => session.addSource('bad', 'not_an_element')).toThrow();
+    expect(() => new CaptureSession({ format: 'NOPE' })).toThrow();
+    expect(() => new CaptureSession().start()).toThrow();
+  });
+
+  it('should tag the frames of all sources with a shared session timestamp', async () => {
+    const session = new CaptureSession({ format: 'I420', startDelayMs: 100 });
+    session.addSource('a', CAMERA);
+    session.addSource('b', CAMERA);
+    const frames: CaptureFrame[] = [];
+    session.onFrame(frame => frames.push(frame));
+    expect(session.getSessionTime()).toBeNull();
+    session.start();
+    expect(() => session.start()).toThrow();
+    await new Promise(resolve => setTimeout(resolve, 1000));
+    expect(session.getSessionTime()).toBeGreaterThan(0);
+    const stats = session.stats();
+    session.stop();
+    expect(session.getSessionTime()).toBeNull();
+
+    expect(stats.map(s => s.state)).toEqual(['Playing', 'Playing']);
+    expect(stats.every(s => s.frames > 10)).toBe(true);
+    const a = frames.filter(frame => frame.sourceId === 'a');
+    const b = frames.filter(frame => frame.sourceId === 'b');
+    expect(a[0].sequence).toBe(0);
+    expect(a[0].frame.format).toBe('I420');
+    expect(a[0].frame.width).toBe(160);
+    // Frames captured together carry session timestamps within a frame of each other
+    const count = Math.min(a.length, b.length);
+    for (let i = 0; i < count; i++) {
+      expect(Math.abs(a[i].sessionTimestamp - b[i].sessionTimestamp)).toBeLessThan(1e9 / 30);
+    }
+  });
+
+  it('should put sources added while running on the session timeline', async () => {
+    const session = new CaptureSession();
+    session.addSource('first', CAMERA);
+    session.start();
+    await new Promise(resolve => setTimeout(resolve, 500));
+    session.addSource('late', CAMERA);
+    await new Promise(resolve => setTimeout(resolve, 300));
+    const late = session.stats().find(s => s.id === 'late')!;
+    session.stop();
+
+    expect(late.frames).toBeGreaterThan(0);
+    expect(late.lastTimestamp).toBeGreaterThan(500_000_000);
+  });
+});
@@ -0,0 +1,38 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { extractClip, generateTestMedia } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('extractClip', () => {
+  const inputFile = path.join(TEST_DIR, 'clip_input.mkv');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(inputFile, { format: 'mkv', pattern: 'smpte', width: 160, height: 120, fps: 10, duration: 3 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it.each(['copy', 'reencode', 'smart'])('should extract a clip in %s mode', mode => {
+    const outputFile = path.join(TEST_DIR, `clip_${mode}.mkv`);
+    extractClip(inputFile, outputFile, 1000, 2000, { mode });
+
+    expect(fs.existsSync(outputFile)).toBe(true);
+    const size = fs.statSync(outputFile).size;
+    expect(size).toBeGreaterThan(0);
+    expect(size).toBeLessThan(fs.statSync(inputFile).size);
+  });
+
+  it('should throw on an empty range', () => {
+    expect(() => extractClip(inputFile, path.join(TEST_DIR, 'empty.mkv'), 2000, 1000)).toThrow();
+  });
+
+  it('should reject an unknown mode', () => {
+    expect(() => extractClip(inputFile, path.join(TEST_DIR, 'bad.mkv'), 0, 1000, { mode: 'fast' })).toThrow(
+      'Unsupported clip mode: fast',
+    );
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, expect } from 'bun:test';
+import { getCapabilities, getSupportedCodecs, registerCodecBackend } from '../index.js';
+
+describe('Codec registry', () => {
+  it('should only report codecs with an installed encoder', () => {
+    const codecs = getSupportedCodecs();
+    const mjpeg = codecs.find(c => c.name === 'mjpeg');
+    expect(mjpeg).toBeDefined();
+    expect(mjpeg!.kind).toBe('video');
+    expect(mjpeg!.encoder).toBe('jpegenc');
+    expect(mjpeg!.hardware).toBe(false);
+    for (const codec of codecs) {
+      expect(codec.backends[0]).toBe(codec.encoder);
+    }
+  });
+
+  it('should skip registered backends that are not installed', () => {
+    registerCodecBackend('mjpeg', { kind: 'video', encoder: 'missingjpegenc' });
+    const mjpeg = getSupportedCodecs().find(c => c.name === 'mjpeg');
+    expect(mjpeg!.encoder).toBe('jpegenc');
+    expect(mjpeg!.backends).not.toContain('missingjpegenc');
+  });
+
+  it('should make newly registered codecs available', () => {
+    registerCodecBackend('png', { kind: 'video', encoder: 'pngenc' });
+    const png = getSupportedCodecs().find(c => c.name === 'png');
+    expect(png).toBeDefined();
+    expect(png!.encoder).toBe('pngenc');
+  });
+
+  it('should reject invalid kinds', () => {
+    expect(() => registerCodecBackend('x', { kind: 'subtitle', encoder: 'x' })).toThrow();
+  });
+});
+
+describe('getCapabilities', () => {
+  it('should describe codecs by direction', () => {
+    const caps = getCapabilities();
+    expect(caps.gstreamerVersion).toMatch(/^\d+\.\d+/);
+    const mjpeg = caps.codecs.find(c => c.name === 'mjpeg')!;
+    expect(mjpeg.encode).toBe(true);
+    expect(mjpeg.encoders).toContain('jpegenc');
+    expect(mjpeg.decode).toBe(true);
+    expect(mjpeg.decoders.length).toBeGreaterThan(0);
+    for (const codec of caps.codecs) {
+      expect(codec.encode).toBe(codec.encoders.length > 0);
+      expect(codec.decode).toBe(codec.decoders.length > 0);
+    }
+  });
+
+  it('should describe containers by direction', () => {
+    const avi = getCapabilities().containers.find(c => c.name === 'avi')!;
+    expect(avi.muxer).toBe('avimux');
+    expect(avi.mux).toBe(true);
+    expect(avi.demux).toBe(true);
+    expect(avi.demuxers).toContain('avidemux');
+  });
+
+  it('should list the convertible pixel formats', () => {
+    const { pixelFormats } = getCapabilities();
+    expect(pixelFormats).toContain('I420');
+    expect(pixelFormats).toContain('RGBA');
+  });
+});
@@ -0,0 +1,45 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { composeComparison, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as path from 'node:path';
+
+describe('composeComparison', () => {
+  let inputA: string;
+  let inputB: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputA = await generateTestVideo('compare_a.avi', 'smpte', { numBuffers: 15, width: 160, height: 120 });
+    inputB = await generateTestVideo('compare_b.avi', 'ball', { numBuffers: 15, width: 320, height: 240 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it.each([
+    ['hstack', 320, 120],
+    ['vstack', 160, 240],
+    ['wipe', 160, 120],
+  ])('should render the %s layout', (layout, width, height) => {
+    const output = path.join(TEST_DIR, `compare_${layout}.mkv`);
+    composeComparison(inputA, inputB, output, { layout });
+    const [video] = probeWithGStreamer(output).video;
+    expect(video.width).toBe(width);
+    expect(video.height).toBe(height);
+  });
+
+  it('should scale both inputs to the requested size', () => {
+    const output = path.join(TEST_DIR, 'compare_sized.webm');
+    composeComparison(inputA, inputB, output, { width: 64, height: 48 });
+    const [video] = probeWithGStreamer(output).video;
+    expect(video.width).toBe(128);
+    expect(video.height).toBe(48);
+  });
+
+  it('should reject unknown layouts and missing inputs', () => {
+    const output = path.join(TEST_DIR, 'compare_invalid.mkv');
+    expect(() => composeComparison(inputA, inputB, output, { layout: 'diagonal' })).toThrow();
+    expect(() => composeComparison(inputA, path.join(TEST_DIR, 'missing.avi'), output)).toThrow();
+  });
+});
@@ -0,0 +1,67 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { analyzeComplexity } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('analyzeComplexity', () => {
+  let flat: string;
+  let noise: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    flat = await generateTestVideo('complexity_flat.avi', 'black', { numBuffers: 20, width: 640, height: 480 });
+    noise = await generateTestVideo('complexity_noise.avi', 'snow', { numBuffers: 20, width: 640, height: 480 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should score static flat content as simple', () => {
+    const report = analyzeComplexity(flat);
+    expect(report.width).toBe(640);
+    expect(report.height).toBe(480);
+    expect(report.framesAnalyzed).toBe(20);
+    expect(report.spatialInformation).toBeLessThan(5);
+    expect(report.temporalInformation).toBeLessThan(5);
+    expect(report.score).toBeLessThan(0.1);
+  });
+
+  it('should give busy content more bits per rung', () => {
+    const simple = analyzeComplexity(flat);
+    const busy = analyzeComplexity(noise);
+    expect(busy.score).toBeGreaterThan(simple.score);
+    expect(busy.ladder.map(r => r.height)).toEqual([480, 360, 240]);
+    expect(busy.ladder[0].bitrate).toBeGreaterThan(simple.ladder[0].bitrate);
+    expect(busy.ladder[0].crf).toBeLessThan(simple.ladder[0].crf);
+  });
+
+  it('should sample the requested number of frames and scale by codec', () => {
+    const h264 = analyzeComplexity(noise, { samples: 5 });
+    const av1 = analyzeComplexity(noise, { samples: 5, codec: 'av1' });
+    expect(h264.framesAnalyzed).toBe(5);
+    expect(av1.ladder[0].bitrate).toBeLessThan(h264.ladder[0].bitrate);
+  });
+
+  it('should reject unknown codecs and missing files', () => {
+    expect(() => analyzeComplexity(flat, { codec: 'realvideo' })).toThrow();
+    expect(() => analyzeComplexity(path.join(TEST_DIR, 'missing.avi'))).toThrow();
+  });
+
+  it('should stream per-frame scores while analysing', () => {
+    const output = path.join(TEST_DIR, 'complexity.csv');
+    const report = analyzeComplexity(noise, { exportPath: output });
+    const [header, ...rows] = fs.readFileSync(output, 'utf8').trim().split('\n');
+    expect(header).toBe('frame,pts,spatialInformation,temporalInformation');
+    expect(rows.length).toBe(report.framesAnalyzed);
+    // The first frame has no predecessor to measure motion against
+    expect(rows[0].endsWith(',')).toBe(true);
+    expect(Math.max(...rows.map(r => Number(r.split(',')[2])))).toBeCloseTo(report.spatialInformation, 6);
+
+    const ndjson = path.join(TEST_DIR, 'complexity.ndjson');
+    analyzeComplexity(noise, { exportPath: ndjson });
+    const records = fs.readFileSync(ndjson, 'utf8').trim().split('\n').map(line => JSON.parse(line));
+    expect(records[1].temporalInformation).toBeGreaterThan(0);
+  });
+});
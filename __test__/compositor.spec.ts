@@ -0,0 +1,63 @@
+import { describe, it, expect } from 'bun:test';
+import { Compositor } from '../index.js';
+
+const output = 'video/x-raw,format=RGBA ! appsink name=out sync=false';
+
+describe('Compositor', () => {
+  it('should add, list and remove inputs', () => {
+    const comp = new Compositor({ width: 320, height: 240, output: 'fakesink' });
+    comp.addInput('b', 'videotestsrc');
+    comp.addInput('a', 'videotestsrc pattern=ball');
+    expect(comp.listInputs()).toEqual(['a', 'b']);
+    expect(() => comp.addInput('a', 'videotestsrc')).toThrow();
+    expect(comp.removeInput('a')).toBe(true);
+    expect(comp.removeInput('a')).toBe(false);
+    expect(comp.listInputs()).toEqual(['b']);
+  });
+
+  it('should apply and update input layouts', () => {
+    const comp = new Compositor({ width: 320, height: 240, output: 'fakesink' });
+    comp.addInput('main', 'videotestsrc');
+    comp.addInput('pip', 'videotestsrc pattern=ball', { x: 200, y: 150, width: 100, height: 75 });
+
+    expect(comp.getLayout('main').zorder).toBe(0);
+    const pip = comp.getLayout('pip');
+    expect(pip).toMatchObject({ x: 200, y: 150, width: 100, height: 75, zorder: 1 });
+
+    comp.setLayout('pip', { alpha: 0.5, zorder: 5 });
+    expect(comp.getLayout('pip')).toMatchObject({ x: 200, zorder: 5, alpha: 0.5 });
+    expect(() => comp.setLayout('missing', { x: 0 })).toThrow();
+  });
+
+  it('should output frames of the configured size', async () => {
+    const comp = new Compositor({ width: 160, height: 120, output });
+    comp.addInput('main', 'videotestsrc pattern=red');
+    comp.addInput('pip', 'videotestsrc pattern=blue', { x: 80, y: 60, width: 80, height: 60 });
+    comp.play();
+
+    const frame = comp.pullFrame(2000);
+    expect(frame).not.toBeNull();
+    expect(frame!.length).toBe(160 * 120 * 4);
+    // Top-left pixel is red, bottom-right pixel is blue
+    expect(Array.from(frame!.subarray(0, 3))).toEqual([255, 0, 0]);
+    const last = frame!.length - 4;
+    expect(Array.from(frame!.subarray(last, last + 3))).toEqual([0, 0, 255]);
+    comp.stop();
+  });
+
+  it('should mix frames pushed to an appsrc input', async () => {
+    const comp = new Compositor({ width: 64, height: 64, output });
+    comp.addInput(
+      'feed',
+      'appsrc format=time is-live=true do-timestamp=true caps=video/x-raw,format=RGBA,width=64,height=64,framerate=30/1'
+    );
+    comp.play();
+    for (let i = 0; i < 5; i++) {
+      comp.pushFrame('feed', Buffer.alloc(64 * 64 * 4, 255));
+    }
+    const frame = comp.pullFrame(2000);
+    expect(frame).not.toBeNull();
+    expect(frame![0]).toBe(255);
+    comp.stop();
+  });
+});
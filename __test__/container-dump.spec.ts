@@ -0,0 +1,85 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { dumpContainer, generateTestMedia, type ContainerElement } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const find = (elements: ContainerElement[], name: string): ContainerElement | undefined => {
+  for (const element of elements) {
+    if (element.name === name) return element;
+    const child = find(element.children, name);
+    if (child) return child;
+  }
+  return undefined;
+};
+
+describe('dumpContainer', () => {
+  const webm = path.join(TEST_DIR, 'dump.webm');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(webm, { format: 'webm', width: 160, height: 120, fps: 10, duration: 1 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should dump the EBML tree of a WebM file', () => {
+    const dump = dumpContainer(webm);
+    expect(dump.format).toBe('webm');
+    expect(dump.size).toBe(fs.statSync(webm).size);
+    expect(dump.elements.map(e => e.name)).toEqual(['EBML', 'Segment']);
+    expect(dump.elements[0].id).toBe('0x1A45DFA3');
+    expect(dump.elements[0].offset).toBe(0);
+
+    expect(find(dump.elements, 'CodecID')?.value).toBe('V_VP8');
+    expect(find(dump.elements, 'PixelWidth')?.value).toBe('160');
+    expect(find(dump.elements, 'PixelHeight')?.value).toBe('120');
+    expect(find(dump.elements, 'SimpleBlock')?.value).toContain('track 1, timecode 0, keyframe');
+  });
+
+  it('should stop at the requested depth', () => {
+    const dump = dumpContainer(webm, { maxDepth: 2 });
+    const segment = dump.elements.find(e => e.name === 'Segment')!;
+    expect(segment.children.map(e => e.name)).toContain('Cluster');
+    expect(segment.children.every(e => e.children.length === 0)).toBe(true);
+    expect(dumpContainer(webm, { maxDepth: 1 }).format).toBe('webm');
+  });
+
+  it('should list the frames of an IVF file', () => {
+    const header = Buffer.alloc(32);
+    header.write('DKIF', 0);
+    header.writeUInt16LE(0, 4);
+    header.writeUInt16LE(32, 6);
+    header.write('VP80', 8);
+    header.writeUInt16LE(64, 12);
+    header.writeUInt16LE(48, 14);
+    header.writeUInt32LE(30, 16);
+    header.writeUInt32LE(1, 20);
+    header.writeUInt32LE(2, 24);
+    const frame = (pts: number, size: number) => {
+      const record = Buffer.alloc(12 + size);
+      record.writeUInt32LE(size, 0);
+      record.writeBigUInt64LE(BigInt(pts), 4);
+      return record;
+    };
+    const ivf = path.join(TEST_DIR, 'dump.ivf');
+    fs.writeFileSync(ivf, Buffer.concat([header, frame(0, 100), frame(1, 20)]));
+
+    const dump = dumpContainer(ivf);
+    expect(dump.format).toBe('ivf');
+    expect(dump.elements[0].value).toBe('version 0, codec VP80, 64x48, time base 1/30, 2 frames');
+    expect(dump.elements.slice(1).map(e => [e.offset, e.size, e.value])).toEqual([
+      [32, 100, 'pts 0'],
+      [144, 20, 'pts 1'],
+    ]);
+  });
+
+  it('should reject other files', () => {
+    const text = path.join(TEST_DIR, 'dump.txt');
+    fs.writeFileSync(text, 'not a container');
+    expect(() => dumpContainer(text)).toThrow();
+    expect(() => dumpContainer(path.join(TEST_DIR, 'missing.webm'))).toThrow();
+  });
+});
@@ -0,0 +1,86 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, detectCropRegion, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+/** Renders a 320x240 video whose picture is surrounded by black bars */
+async function generateBoxedVideo(filename: string, box: string): Promise<string> {
+  const output = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    `videotestsrc pattern=smpte num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! ` +
+      `videobox ${box} fill=black ! videoscale ! video/x-raw,width=320,height=240 ! ` +
+      `jpegenc quality=95 ! avimux ! filesink location="${output}"`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 1500));
+  kit.stop();
+  kit.cleanup();
+  return output;
+}
+
+describe('detectCropRegion', () => {
+  let letterboxed: string;
+  let pillarboxed: string;
+  let plain: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    // Bars added around the picture, scaled back to 320x240: 30 rows above and
+    // below, or about 44 columns left and right
+    letterboxed = await generateBoxedVideo('letterboxed.avi', 'top=-40 bottom=-40');
+    pillarboxed = await generateBoxedVideo('pillarboxed.avi', 'left=-60 right=-60');
+    plain = await generateTestVideo('crop_plain.avi', 'smpte', { numBuffers: 30 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should find letterboxing', () => {
+    const region = detectCropRegion(letterboxed, { samples: 5 });
+    expect(region.cropped).toBe(true);
+    expect(region.sourceWidth).toBe(320);
+    expect(region.sourceHeight).toBe(240);
+    expect(region.x).toBe(0);
+    expect(region.width).toBe(320);
+    expect(Math.abs(region.y - 30)).toBeLessThanOrEqual(2);
+    expect(Math.abs(region.height - 180)).toBeLessThanOrEqual(4);
+    expect(region.filter).toBe(`crop=${region.x},${region.y},${region.width},${region.height}`);
+  });
+
+  it('should find pillarboxing and honor rounding', () => {
+    const region = detectCropRegion(pillarboxed, { round: 16 });
+    expect(region.cropped).toBe(true);
+    expect(region.width % 16).toBe(0);
+    expect(region.x % 2).toBe(0);
+    expect(Math.abs(region.width - 232)).toBeLessThanOrEqual(16);
+    expect(region.height).toBe(240);
+  });
+
+  it('should keep the full frame without bars', () => {
+    const region = detectCropRegion(plain);
+    expect(region.cropped).toBe(false);
+    expect(region.filter).toBe('crop=0,0,320,240');
+  });
+
+  it('should feed the crop filter', () => {
+    const region = detectCropRegion(letterboxed);
+    const output = path.join(TEST_DIR, 'cropped.mkv');
+    fs.writeFileSync(
+      output,
+      transcodeBuffer(fs.readFileSync(letterboxed), { container: 'mkv', videoFilters: [region.filter] })
+    );
+    const cropped = detectCropRegion(output);
+    expect(cropped.sourceWidth).toBe(region.width);
+    expect(cropped.sourceHeight).toBe(region.height);
+    expect(cropped.cropped).toBe(false);
+  });
+
+  it('should reject invalid crop filters', () => {
+    for (const filter of ['crop=0,0,0,10', 'crop=1.5,0,10,10', 'crop=-2,0,10,10', 'crop=0,0,10']) {
+      expect(() => transcodeBuffer(fs.readFileSync(plain), { container: 'mkv', videoFilters: [filter] })).toThrow();
+    }
+  });
+});
@@ -0,0 +1,36 @@
+import { describe, it, expect } from 'bun:test';
+import { capturePermission, listCaptureDevices, watchDevices } from '../index.js';
+
+const PERMISSIONS = ['granted', 'denied', 'restricted', 'not-determined', 'unknown'];
+
+describe('capture devices', () => {
+  it('should list devices of the requested kind', () => {
+    for (const device of listCaptureDevices('video')) {
+      expect(device.kind).toBe('video');
+      expect(device.deviceClass).toContain('Video');
+      expect(device.name.length).toBeGreaterThan(0);
+    }
+    const all = listCaptureDevices();
+    expect(all.length).toBeGreaterThanOrEqual(listCaptureDevices('audio').length);
+  });
+
+  it('should report the capture permission of each kind', () => {
+    expect(PERMISSIONS).toContain(capturePermission('video'));
+    expect(PERMISSIONS).toContain(capturePermission('audio'));
+  });
+
+  it('should reject unknown kinds', () => {
+    expect(() => capturePermission('screen')).toThrow('Unknown device kind: screen');
+    expect(() => listCaptureDevices('screen')).toThrow('Unknown device kind');
+    expect(() => watchDevices({ kind: 'screen' })).toThrow('Unknown device kind');
+  });
+
+  it('should watch devices until stopped', () => {
+    const watcher = watchDevices({ kind: 'video', permissionPollMs: 100 });
+    const events: unknown[] = [];
+    watcher.onEvent(event => events.push(event));
+    expect(Array.isArray(watcher.devices())).toBe(true);
+    watcher.stop();
+    watcher.stop();
+  });
+});
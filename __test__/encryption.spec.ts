@@ -0,0 +1,78 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, probeWithGStreamer, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideoWithAudio } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const EBML_MAGIC = Buffer.from([0x1a, 0x45, 0xdf, 0xa3]);
+const KEY = '000102030405060708090a0b0c0d0e0f';
+
+/** IVs of the encrypted frames of a Matroska file, in order */
+function frameIvs(file: Buffer): string[] {
+  const location = path.join(TEST_DIR, 'ivs.mkv');
+  fs.writeFileSync(location, file);
+  const kit = new GstKit();
+  kit.setPipeline(`filesrc location=${location} ! matroskademux ! appsink name=sink sync=false`);
+  kit.play();
+  const ivs: string[] = [];
+  for (let frame = kit.pullSample('sink', 2000); frame; frame = kit.pullSample('sink', 2000)) {
+    // A signal byte, then the IV
+    ivs.push(frame.subarray(1, 9).toString('hex'));
+  }
+  kit.cleanup();
+  return ivs;
+}
+
+describe('encryption', () => {
+  let input: Buffer;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    input = fs.readFileSync(await generateTestVideoWithAudio('encryption_input.avi', 'smpte', 'sine', { numBuffers: 10 }));
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should write encrypted frames that decrypt with the key', () => {
+    const encrypted = transcodeBuffer(input, { container: 'mkv', encryption: { key: KEY } });
+    expect(encrypted.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+
+    const decrypted = path.join(TEST_DIR, 'decrypted.webm');
+    fs.writeFileSync(decrypted, transcodeBuffer(encrypted, { container: 'webm', decryptionKey: KEY }));
+    const info = probeWithGStreamer(decrypted);
+    expect(info.video[0].width).toBe(320);
+    expect(info.audio.length).toBe(1);
+  });
+
+  it('should give every frame its own IV', () => {
+    const options = { container: 'mkv', deterministic: true, audioCodec: 'none' };
+    const plain = transcodeBuffer(input, options);
+    const first = transcodeBuffer(input, { ...options, encryption: { key: KEY, iv: '0000000000000000' } });
+    const second = transcodeBuffer(input, { ...options, encryption: { key: KEY, iv: '0000000000000000' } });
+    // A signal byte and an IV are added to each of the 10 frames
+    expect(first.length).toBeGreaterThanOrEqual(plain.length + 10 * 9);
+    expect(first.equals(second)).toBe(true);
+
+    const ivs = frameIvs(first);
+    expect(ivs).toHaveLength(10);
+    expect(new Set(ivs).size).toBe(10);
+    expect(ivs.slice(0, 2)).toEqual(['0000000000000000', '0000000000000001']);
+  });
+
+  it('should draw a fresh IV for every deterministic transcode', () => {
+    const options = { container: 'mkv', deterministic: true, audioCodec: 'none', encryption: { key: KEY } };
+    const first = frameIvs(transcodeBuffer(input, options));
+    const second = frameIvs(transcodeBuffer(input, options));
+    expect(new Set(first).size).toBe(first.length);
+    expect(first[0]).not.toBe(second[0]);
+  });
+
+  it('should reject invalid keys, IVs and containers', () => {
+    expect(() => transcodeBuffer(input, { container: 'mkv', encryption: { key: 'abcd' } })).toThrow(/key/);
+    expect(() => transcodeBuffer(input, { container: 'mkv', encryption: { key: KEY, iv: 'zz' } })).toThrow(/IV/);
+    expect(() => transcodeBuffer(input, { container: 'mp4', encryption: { key: KEY } })).toThrow(/mkv or webm/);
+    expect(() => transcodeBuffer(input, { container: 'webm', decryptionKey: 'not a key' })).toThrow(/key/);
+  });
+});
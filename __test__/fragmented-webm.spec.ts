@@ -0,0 +1,72 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, fragmentFromPipeline, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const RAW = 'videotestsrc num-buffers=90 ! video/x-raw,width=320,height=240,framerate=30/1 ! appsink name=sink';
+
+/** Runs `kit` long enough for its 90 frames to reach the sink */
+async function run(kit: GstKit): Promise<void> {
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 3000));
+}
+
+describe('fragmentFromPipeline', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should split the stream into an init segment and clusters', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    const fragmenter = fragmentFromPipeline(kit, 'sink', { fragmentDurationMs: 1000 });
+    expect(fragmenter.initSegment()).toBeNull();
+    await run(kit);
+    fragmenter.stop();
+    kit.cleanup();
+
+    const init = fragmenter.initSegment()!;
+    expect([...init.subarray(0, 4)]).toEqual([0x1a, 0x45, 0xdf, 0xa3]);
+    const fragments = [];
+    let fragment;
+    while ((fragment = fragmenter.nextFragment())) fragments.push(fragment);
+    expect(fragmenter.frames).toBe(90);
+    expect(fragments.length).toBeGreaterThanOrEqual(3);
+    expect(fragments.map(f => f.sequence)).toEqual(fragments.map((_, i) => i));
+    for (const f of fragments) {
+      expect([...f.data.subarray(0, 4)]).toEqual([0x1f, 0x43, 0xb6, 0x75]);
+    }
+    expect(fragments[0].timestamp).toBe(0);
+
+    const output = path.join(TEST_DIR, 'fragmented.webm');
+    fs.writeFileSync(output, Buffer.concat([init, ...fragments.map(f => f.data)]));
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].codec.toLowerCase()).toContain('vp8');
+    expect(info.video[0].width).toBe(320);
+  });
+
+  it('should drop the oldest fragments beyond maxQueued', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    const fragmenter = fragmentFromPipeline(kit, 'sink', { fragmentDurationMs: 250, maxQueued: 2 });
+    await run(kit);
+    fragmenter.stop();
+    kit.cleanup();
+
+    expect(fragmenter.dropped).toBeGreaterThan(0);
+    const first = fragmenter.nextFragment()!;
+    expect(first.sequence).toBe(fragmenter.dropped);
+  });
+
+  it('should reject unsupported codecs', () => {
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    expect(() => fragmentFromPipeline(kit, 'sink', { videoCodec: 'h264' })).toThrow('Unsupported fragment codec');
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,52 @@
+import { describe, it, expect } from 'bun:test';
+import { FrameClock, GstKit, type FrameTick } from '../index.js';
+
+describe('FrameClock', () => {
+  it('should take NTSC rates as exact fractions', () => {
+    expect(new FrameClock({ fps: 29.97 }).frameRate).toBe('30000/1001');
+    expect(new FrameClock({ fps: 60 }).frameRate).toBe('60/1');
+    expect(() => new FrameClock({ fps: 0 })).toThrow();
+    expect(() => new FrameClock({ fps: Number.NaN })).toThrow();
+  });
+
+  it('should tick at frame boundaries and report jitter', async () => {
+    const clock = new FrameClock({ fps: 50 });
+    const ticks: FrameTick[] = [];
+    clock.start(tick => ticks.push(tick));
+    expect(clock.running).toBe(true);
+    expect(() => clock.start(() => {})).toThrow();
+    await new Promise(resolve => setTimeout(resolve, 500));
+    clock.stop();
+    expect(clock.running).toBe(false);
+    await new Promise(resolve => setTimeout(resolve, 50));
+
+    const stats = clock.stats();
+    expect(stats.ticks + stats.skipped).toBeGreaterThanOrEqual(20);
+    expect(stats.ticks + stats.skipped).toBeLessThanOrEqual(27);
+    expect(stats.maxJitter).toBeGreaterThanOrEqual(0);
+    expect(stats.meanJitter).toBeLessThan(20_000_000);
+    expect(ticks[0].index).toBe(0);
+    expect(ticks[0].timestamp).toBe(0);
+    expect(ticks[1].timestamp - ticks[0].timestamp).toBe(20_000_000);
+    expect(ticks.every(tick => tick.duration === 20_000_000)).toBe(true);
+  });
+
+  it('should push timestamped frames into an AppSrc', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'appsrc name=src is-live=true format=time caps=video/x-raw,format=GRAY8,width=16,height=16,framerate=30/1 ! appsink name=sink sync=false'
+    );
+    kit.play();
+    const clock = new FrameClock({ fps: 30 });
+    clock.start(tick => clock.pushFrame(kit, 'src', tick.index, Buffer.alloc(256, tick.index)));
+    await new Promise(resolve => setTimeout(resolve, 300));
+    clock.stop();
+    await new Promise(resolve => setTimeout(resolve, 50));
+
+    const frame = kit.pullFrame('sink');
+    expect(frame).not.toBeNull();
+    const index = Math.round((frame!.timestamp * 30) / 1e9);
+    expect(frame!.timestamp).toBe(Math.floor((index * 1e9) / 30));
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,77 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { extractFramesToImages } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('extractFramesToImages', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('frame_export_input.avi', 'smpte', { numBuffers: 30 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should write every frame and an index to a directory', () => {
+    const dir = path.join(TEST_DIR, 'frames');
+    const frames = extractFramesToImages(inputFile, dir);
+    expect(frames.length).toBe(30);
+    expect(frames[0].name).toBe('frame-000001.jpg');
+    expect(frames[1].pts).toBeGreaterThan(frames[0].pts);
+
+    const image = fs.readFileSync(path.join(dir, frames[0].name));
+    expect(image.length).toBe(frames[0].size);
+    expect(image.subarray(0, 2).equals(Buffer.from([0xff, 0xd8]))).toBe(true);
+    const index = JSON.parse(fs.readFileSync(path.join(dir, 'index.json'), 'utf8'));
+    expect(index.format).toBe('jpg');
+    expect(index.frames).toEqual(frames);
+  });
+
+  it('should honor format, fps and maxFrames', () => {
+    const dir = path.join(TEST_DIR, 'frames_png');
+    const frames = extractFramesToImages(inputFile, dir, { format: 'png', fps: 5, maxFrames: 3 });
+    expect(frames.map(f => f.name)).toEqual(['frame-000001.png', 'frame-000002.png', 'frame-000003.png']);
+    const image = fs.readFileSync(path.join(dir, frames[2].name));
+    expect(image.subarray(1, 4).toString()).toBe('PNG');
+  });
+
+  it('should stream the frames into a zip archive', () => {
+    const output = path.join(TEST_DIR, 'frames.zip');
+    const frames = extractFramesToImages(inputFile, output, { archive: true, maxFrames: 5 });
+    expect(frames.length).toBe(5);
+
+    const zip = fs.readFileSync(output);
+    expect(zip.subarray(0, 4).equals(Buffer.from('PK\x03\x04', 'latin1'))).toBe(true);
+    // Stored entries keep the images readable in the archive
+    expect(zip.includes(Buffer.from('frame-000005.jpg'))).toBe(true);
+    expect(zip.includes(Buffer.from('index.json'))).toBe(true);
+    expect(zip.includes(Buffer.from('"name": "frame-000001.jpg"'))).toBe(true);
+  });
+
+  it('should encrypt the archive with a password', () => {
+    const output = path.join(TEST_DIR, 'frames_encrypted.zip');
+    extractFramesToImages(inputFile, output, { archive: true, password: 'hunter2', maxFrames: 2 });
+
+    const zip = fs.readFileSync(output);
+    expect(zip.includes(Buffer.from('index.json'))).toBe(true);
+    expect(zip.includes(Buffer.from('"name": "frame-000001.jpg"'))).toBe(false);
+    // General purpose flag bit 0 marks encrypted entries
+    expect(zip.readUInt16LE(6) & 1).toBe(1);
+  });
+
+  it('should reject invalid options', () => {
+    expect(() => extractFramesToImages(inputFile, path.join(TEST_DIR, 'bad'), { format: 'gif' })).toThrow(
+      /Unsupported image format/
+    );
+    expect(() => extractFramesToImages(inputFile, path.join(TEST_DIR, 'bad'), { password: 'x' })).toThrow(
+      /password requires archive/
+    );
+    expect(() => extractFramesToImages(inputFile, path.join(TEST_DIR, 'bad'), { fps: 0 })).toThrow(
+      /Invalid frame rate/
+    );
+  });
+});
@@ -0,0 +1,41 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+const CAPS = 'video/x-raw,format=RGBA,width=4,height=4,framerate=30/1';
+const FRAME = Buffer.alloc(4 * 4 * 4, 128);
+
+function pipeline(middle: string): GstKit {
+  const kit = new GstKit();
+  kit.setPipeline(`appsrc name=src format=time caps=${CAPS} ! ${middle} ! appsink name=sink sync=false`);
+  kit.play();
+  return kit;
+}
+
+describe('frame metadata', () => {
+  it('should carry string metadata from AppSrc to AppSink', () => {
+    const kit = pipeline('queue');
+    kit.pushSample('src', FRAME, JSON.stringify({ sequence: 1 }));
+    const frame = kit.pullFrame('sink', { timeoutMs: 2000 })!;
+    kit.cleanup();
+
+    expect(JSON.parse(frame.metadata!.toString())).toEqual({ sequence: 1 });
+  });
+
+  it('should keep byte metadata through conversion and scaling', () => {
+    const kit = pipeline('videoconvert ! videoscale ! video/x-raw,format=I420,width=8,height=8');
+    kit.pushSample('src', FRAME, Buffer.from([1, 2, 3]));
+    kit.pushSample('src', FRAME);
+    const first = kit.pullFrame('sink', { timeoutMs: 2000 })!;
+    const second = kit.pullFrame('sink', { timeoutMs: 2000 })!;
+    kit.cleanup();
+
+    expect([...first.metadata!]).toEqual([1, 2, 3]);
+    expect(second.metadata).toBeUndefined();
+  });
+
+  it('should reject metadata over 64 KiB', () => {
+    const kit = pipeline('queue');
+    expect(() => kit.pushSample('src', FRAME, Buffer.alloc(64 * 1024 + 1))).toThrow('more than the 65536 allowed');
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,165 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+describe('Frame Streams', () => {
+  it('should iterate frames until end of stream', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=10 ! video/x-raw,format=I420,width=32,height=16 ! appsink name=sink sync=false');
+    const stream = kit.frames('sink', { maxQueueLength: 16 });
+    kit.play();
+
+    const frames = [];
+    for await (const frame of stream) {
+      frames.push(frame);
+    }
+    kit.stop();
+    kit.cleanup();
+
+    expect(frames.length).toBe(10);
+    expect(frames[0].format).toBe('I420');
+    expect(frames[0].sinkName).toBe('sink');
+    expect(frames[9].timestamp).toBeGreaterThan(frames[0].timestamp);
+    expect(stream.dropped).toBe(0);
+  });
+
+  it('should drop the oldest frames when the consumer is slow', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=30 ! video/x-raw,width=16,height=16 ! appsink name=sink sync=false');
+    const stream = kit.frames('sink', { maxQueueLength: 2 });
+    kit.play();
+    await new Promise(r => setTimeout(r, 500));
+
+    const timestamps = [];
+    for await (const frame of stream) {
+      timestamps.push(frame.timestamp);
+    }
+    kit.stop();
+    kit.cleanup();
+
+    expect(timestamps.length).toBe(2);
+    expect(stream.dropped).toBe(28);
+    // The two most recent frames survive
+    expect(timestamps[1]).toBeGreaterThan(timestamps[0]);
+  });
+
+  it('should hand the sink back when the loop is left', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc is-live=true ! video/x-raw,width=16,height=16,framerate=30/1 ! appsink name=sink');
+    kit.play();
+    for await (const frame of kit.frames('sink')) {
+      expect(frame.width).toBe(16);
+      break;
+    }
+    expect(kit.pullSample('sink', 1000)).not.toBeNull();
+
+    const stream = kit.frames('sink');
+    stream.close();
+    const next = await stream[Symbol.asyncIterator]().next();
+    expect(next.done).toBe(true);
+    kit.stop();
+    kit.cleanup();
+  });
+
+  it('should deliver only the cropped region', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=3 ! video/x-raw,format=NV12,width=64,height=48 ! appsink name=sink sync=false');
+    const stream = kit.frames('sink', { maxQueueLength: 4, crop: { x: 48, y: 0, width: 16, height: 8 } });
+    kit.play();
+
+    const frames = [];
+    for await (const frame of stream) {
+      frames.push(frame);
+    }
+    kit.stop();
+    kit.cleanup();
+
+    expect(frames.length).toBe(3);
+    expect([frames[0].width, frames[0].height]).toEqual([16, 8]);
+    expect(frames[0].planes.map(p => p.stride)).toEqual([16, 16]);
+    expect(frames[0].data.length).toBe(16 * 8 + 16 * 4);
+  });
+
+  it('should reject elements that are not AppSinks', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc name=src ! fakesink');
+    expect(() => kit.frames('src')).toThrow();
+    expect(() => kit.frames('missing')).toThrow();
+    kit.cleanup();
+  });
+
+  describe('Backpressure policies', () => {
+    async function collect(kit: GstKit, stream: any, delayMs = 500) {
+      kit.play();
+      await new Promise(r => setTimeout(r, delayMs));
+      const frames = [];
+      for await (const frame of stream) {
+        frames.push(frame);
+      }
+      kit.stop();
+      kit.cleanup();
+      return frames;
+    }
+
+    const pipeline = 'videotestsrc num-buffers=30 ! video/x-raw,width=16,height=16,framerate=30/1 ! appsink name=sink sync=false';
+
+    it('should keep the first frames with dropNewest', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      const stream = kit.frames('sink', { policy: 'dropNewest', maxQueueLength: 3 });
+      const frames = await collect(kit, stream);
+
+      expect(frames.map(f => f.timestamp)).toEqual([0, 33_333_333, 66_666_666]);
+      expect(stream.dropped).toBe(27);
+    });
+
+    it('should deliver every frame with block', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      const stream = kit.frames('sink', { policy: 'block', maxQueueLength: 1 });
+      const frames = await collect(kit, stream, 100);
+
+      expect(frames.length).toBe(30);
+      expect(stream.dropped).toBe(0);
+    });
+
+    it('should sample every Nth frame', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      const stream = kit.frames('sink', { policy: 'sampleEveryN', sampleEvery: 10, maxQueueLength: 8 });
+      const frames = await collect(kit, stream);
+
+      expect(frames.map(f => f.timestamp)).toEqual([0, 333_333_333, 666_666_666]);
+      expect(stream.dropped).toBe(0);
+    });
+
+    it('should use the settings given to startFrameEmission', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      kit.startFrameEmission(['sink'], null, { policy: 'dropNewest', maxQueueLength: 2 });
+      expect(kit.getProperty('sink', 'max-buffers')).toContain('2');
+      expect(kit.getProperty('sink', 'drop')).toContain('true');
+      const stream = kit.frames('sink');
+      const frames = await collect(kit, stream);
+
+      expect(frames.length).toBe(2);
+      expect(frames[0].timestamp).toBe(0);
+    });
+
+    it('should let blocking sinks stall instead of dropping', () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      kit.startFrameEmission(['sink'], null, { policy: 'block', maxQueueLength: 5 });
+      expect(kit.getProperty('sink', 'drop')).toContain('false');
+      kit.cleanup();
+    });
+
+    it('should reject invalid policies', () => {
+      const kit = new GstKit();
+      kit.setPipeline(pipeline);
+      expect(() => kit.frames('sink', { policy: 'dropRandom' })).toThrow();
+      expect(() => kit.frames('sink', { policy: 'sampleEveryN', sampleEvery: 0 })).toThrow();
+      expect(() => kit.startFrameEmission(['sink'], null, { policy: 'block', sampleEvery: 2 })).toThrow();
+      kit.cleanup();
+    });
+  });
+});
@@ -351,4 +351,406 @@ describe('GstKit Functional Tests', () => {
       expect(stats.size).toBeGreaterThan(100);
     });
   });
+
+  describe('Pipeline Statistics', () => {
+    it('should report queue levels, caps and sink counters', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        'videotestsrc is-live=true ! video/x-raw,width=160,height=120 ! queue name=q ! fakesink name=sink sync=true'
+      );
+      kit.play();
+      await new Promise(r => setTimeout(r, 500));
+
+      const stats = kit.getStats();
+      expect(stats.state).toBe('Playing');
+
+      const queue = stats.elements.find(e => e.name === 'q')!;
+      expect(queue.factory).toBe('queue');
+      expect(queue.queueBuffers).toBeGreaterThanOrEqual(0);
+      expect(queue.caps[0]).toContain('width=(int)160');
+
+      const sink = stats.elements.find(e => e.name === 'sink')!;
+      expect(sink.rendered).toBeGreaterThan(0);
+      expect(stats.renderedFrames).toBe(sink.rendered!);
+
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should throw when no pipeline is set', () => {
+      const kit = new GstKit();
+      expect(() => kit.getStats()).toThrow();
+    });
+
+    it('should leave bus errors to the state waits', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=RGBA ! video/x-raw,format=I420 ! fakesink');
+      const playing = kit.playAsync(5000);
+      const poll = setInterval(() => kit.getStats(), 1);
+      await expect(playing).rejects.toThrow(/not-negotiated|negotiat/i);
+      clearInterval(poll);
+      kit.cleanup();
+    });
+  });
+
+  describe('Clock and Latency Control', () => {
+    it('should set and report a fixed latency', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! fakesink');
+      kit.setLatency(150);
+      expect(kit.getLatency()).toBeCloseTo(150);
+      expect(() => kit.setLatency(-1)).toThrow();
+      kit.cleanup();
+    });
+
+    it('should select the system clock and set the base time', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc is-live=true ! fakesink');
+      await kit.useClock({ kind: 'system' });
+      const now = kit.getClockTime();
+      expect(now).toBeGreaterThan(0);
+      kit.setBaseTime(now);
+      expect(kit.getBaseTime()).toBe(now);
+      kit.cleanup();
+    });
+
+    it('should follow a published network clock', async () => {
+      const server = new GstKit();
+      server.setPipeline('videotestsrc is-live=true ! fakesink');
+      const port = server.publishClock(0);
+      expect(port).toBeGreaterThan(0);
+
+      const client = new GstKit();
+      client.setPipeline('videotestsrc is-live=true ! fakesink');
+      await client.useClock({ kind: 'net', address: '127.0.0.1', port });
+      expect(client.getClockTime()).toBeGreaterThan(0);
+
+      client.cleanup();
+      server.cleanup();
+    });
+
+    it('should reject unknown clocks', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! fakesink');
+      expect(() => kit.useClock({ kind: 'sundial' })).toThrow();
+      expect(() => kit.useClock({ kind: 'ntp' })).toThrow();
+      kit.cleanup();
+    });
+  });
+
+  describe('Frame Taps', () => {
+    it('should deliver buffers from a tapped element and stop after removal', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        'videotestsrc is-live=true ! video/x-raw,format=RGBA,width=32,height=32,framerate=30/1 ! identity name=tap ! fakesink'
+      );
+      const frames: Buffer[] = [];
+      kit.play();
+      kit.addFrameTap('tap', frame => frames.push(frame));
+      await new Promise(r => setTimeout(r, 500));
+
+      expect(frames.length).toBeGreaterThan(0);
+      expect(frames[0].length).toBe(32 * 32 * 4);
+      expect(() => kit.addFrameTap('tap', () => {})).toThrow();
+
+      expect(kit.removeFrameTap('tap')).toBe(true);
+      expect(kit.removeFrameTap('tap')).toBe(false);
+      await new Promise(r => setTimeout(r, 100));
+      const count = frames.length;
+      await new Promise(r => setTimeout(r, 300));
+      expect(frames.length).toBe(count);
+      expect(kit.getState()).toBe('Playing');
+
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should throw for unknown elements', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! fakesink');
+      expect(() => kit.addFrameTap('missing', () => {})).toThrow();
+      kit.cleanup();
+    });
+
+    it('should deliver planar YUV frames with their strides', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        'videotestsrc is-live=true ! video/x-raw,format=RGBA,width=30,height=20,framerate=30/1 ! identity name=tap ! fakesink'
+      );
+      const frames: any[] = [];
+      kit.play();
+      kit.addPlanarFrameTap('tap', 'I420', frame => frames.push(frame));
+      await new Promise(r => setTimeout(r, 500));
+      kit.stop();
+      kit.cleanup();
+
+      expect(frames.length).toBeGreaterThan(0);
+      const [y, u, v] = frames[0].planes;
+      expect(frames[0].format).toBe('I420');
+      expect(frames[0].planes.length).toBe(3);
+      expect([y.width, y.height, u.width, u.height, v.width, v.height]).toEqual([30, 20, 15, 10, 15, 10]);
+      expect(y.stride).toBeGreaterThanOrEqual(30);
+      expect(y.data.length).toBe(y.stride * y.height);
+      expect(u.offset).toBe(y.stride * y.height);
+    });
+
+    it('should reject unknown tap formats', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! identity name=tap ! fakesink');
+      expect(() => kit.addPlanarFrameTap('tap', 'YUV9000', () => {})).toThrow();
+      kit.cleanup();
+    });
+  });
+
+  describe('GPU Handles', () => {
+    it('should reject frames in system memory', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,width=16,height=16 ! appsink name=sink');
+      kit.play();
+      await new Promise(r => setTimeout(r, 200));
+      expect(() => kit.pullSampleAsGpuHandle('sink', 2000)).toThrow(/DMABuf or GL memory/);
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should return null when no frame is available', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! appsink name=sink');
+      expect(kit.pullSampleAsGpuHandle('sink', 0)).toBeNull();
+      expect(() => kit.pullSampleAsGpuHandle('missing')).toThrow();
+      kit.cleanup();
+    });
+  });
+
+  describe('Planar Frame Extraction', () => {
+    it('should pull NV12 frames as two planes', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=NV12,width=64,height=48 ! appsink name=sink');
+      kit.play();
+      const frame = kit.pullFrame('sink', { timeoutMs: 2000 });
+      kit.stop();
+      kit.cleanup();
+
+      expect(frame).not.toBeNull();
+      expect(frame!.format).toBe('NV12');
+      expect([frame!.width, frame!.height]).toEqual([64, 48]);
+      expect(frame!.planes.length).toBe(2);
+      expect(frame!.planes[1].width).toBe(32);
+      expect(frame!.planes[1].height).toBe(24);
+      expect(frame!.timestamp).toBeGreaterThanOrEqual(0);
+    });
+
+    it('should keep RGBA as a single plane', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=RGBA,width=16,height=16 ! appsink name=sink');
+      kit.play();
+      const frame = kit.pullFrame('sink', { timeoutMs: 2000 });
+      kit.stop();
+      kit.cleanup();
+
+      expect(frame!.planes.length).toBe(1);
+      expect(frame!.planes[0].stride).toBe(64);
+      expect(frame!.data.length).toBe(16 * 16 * 4);
+    });
+  });
+
+  describe('Caps Negotiation Helpers', () => {
+    it('should force a known pixel format on an appsink', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=I420,width=16,height=16 ! appsink name=sink');
+      kit.startFrameEmission(['sink'], 'RGBA');
+      kit.play();
+      await new Promise(r => setTimeout(r, 300));
+
+      const frame = kit.pullSample('sink', 1000);
+      expect(frame?.length).toBe(16 * 16 * 4);
+      expect(kit.getElements()).toContain('sink_convert');
+
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should reject unknown formats', () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! appsink name=sink');
+      expect(() => kit.startFrameEmission(['sink'], 'NOPE')).toThrow();
+      kit.cleanup();
+    });
+
+    it('should update a capsfilter at runtime', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        'videotestsrc ! video/x-raw,format=RGBA ! videoscale ! capsfilter name=size caps=video/x-raw,width=16,height=16 ! appsink name=sink'
+      );
+      kit.setCapsFilter('size', 'video/x-raw,width=8,height=8');
+      kit.play();
+      await new Promise(r => setTimeout(r, 300));
+
+      expect(kit.pullSample('sink', 1000)?.length).toBe(8 * 8 * 4);
+      expect(() => kit.setCapsFilter('size', 'video/x-raw,width=(int)abc')).toThrow();
+      expect(() => kit.setCapsFilter('sink_missing', 'video/x-raw')).toThrow();
+
+      kit.stop();
+      kit.cleanup();
+    });
+  });
+
+  describe('Audio Sample Pulling', () => {
+    it('should pull audio samples with parsed caps', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        'audiotestsrc samplesperbuffer=1024 ! audio/x-raw,format=S16LE,rate=48000,channels=2,layout=interleaved ! appsink name=audio'
+      );
+      kit.play();
+      await new Promise(r => setTimeout(r, 300));
+
+      const sample = kit.pullAudioSample('audio', { timeoutMs: 1000, deinterleave: true });
+      expect(sample).not.toBeNull();
+      expect(sample!.format).toBe('S16LE');
+      expect(sample!.rate).toBe(48000);
+      expect(sample!.channels).toBe(2);
+      expect(sample!.layout).toBe('interleaved');
+      expect(sample!.data.length).toBe(1024 * 2 * 2);
+      expect(sample!.channelData!.length).toBe(2);
+      expect(sample!.channelData![0].length).toBe(1024);
+      expect(sample!.channelData![0].every(v => v >= -1 && v <= 1)).toBe(true);
+
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should reject sinks that do not receive raw audio', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! appsink name=video');
+      kit.play();
+      await new Promise(r => setTimeout(r, 200));
+      expect(() => kit.pullAudioSample('video', { timeoutMs: 1000 })).toThrow();
+      kit.stop();
+      kit.cleanup();
+    });
+  });
+
+  describe('Playlist Playback', () => {
+    const tracks = ['playlist_a.wav', 'playlist_b.wav'].map(f => path.join(TEST_DIR, f));
+
+    beforeAll(async () => {
+      for (const track of tracks) {
+        const kit = new GstKit();
+        kit.setPipeline(
+          `audiotestsrc num-buffers=10 ! wavenc ! filesink location="${track}"`
+        );
+        kit.play();
+        await new Promise(r => setTimeout(r, 500));
+        kit.stop();
+      }
+    });
+
+    it('should play entries in order and report track changes', async () => {
+      const kit = new GstKit();
+      const changes: number[] = [];
+      kit.setPlaylist(tracks, { audioSink: 'fakesink', videoSink: 'fakesink' });
+      kit.onTrackChange(change => changes.push(change.index));
+      kit.play();
+      await new Promise(r => setTimeout(r, 1500));
+
+      expect(changes).toContain(0);
+      expect(changes).toContain(1);
+      expect(kit.getPlaylistIndex()).toBe(1);
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should move with next and previous', () => {
+      const kit = new GstKit();
+      kit.setPlaylist(tracks, { audioSink: 'fakesink', videoSink: 'fakesink' });
+      expect(kit.next()).toBe(1);
+      expect(() => kit.next()).toThrow();
+      expect(kit.previous()).toBe(0);
+      expect(() => kit.previous()).toThrow();
+      kit.cleanup();
+    });
+
+    it('should reject an empty playlist', () => {
+      const kit = new GstKit();
+      expect(() => kit.setPlaylist([])).toThrow();
+    });
+  });
+
+  describe('QR Detection', () => {
+    // Version 1 QR code encoding "gstkit"
+    const QR = [
+      '111111101111101111111',
+      '100000101101101000001',
+      '101110100111001011101',
+      '101110100101101011101',
+      '101110101000101011101',
+      '100000101010001000001',
+      '111111101010101111111',
+      '000000001111000000000',
+      '111001101111111110011',
+      '100110010000000000011',
+      '001001111010001000001',
+      '110010011110100011011',
+      '010111111110001000001',
+      '000000001011011001001',
+      '111111100111110111101',
+      '100000101101011001001',
+      '101110100101111011001',
+      '101110100010000110100',
+      '101110101010001010011',
+      '100000101010100101000',
+      '111111101110001101001',
+    ];
+    const MODULE = 8;
+    const SIZE = (QR.length + 8) * MODULE;
+
+    const renderQr = () => {
+      const frame = Buffer.alloc(SIZE * SIZE, 255);
+      for (let y = 0; y < SIZE; y++) {
+        for (let x = 0; x < SIZE; x++) {
+          const row = QR[Math.floor(y / MODULE) - 4];
+          if (row?.[Math.floor(x / MODULE) - 4] === '1') {
+            frame[y * SIZE + x] = 0;
+          }
+        }
+      }
+      return frame;
+    };
+
+    it('should report payload and bounding box of QR codes', async () => {
+      const kit = new GstKit();
+      kit.setPipeline(
+        `appsrc name=src format=time do-timestamp=true caps=video/x-raw,format=GRAY8,width=${SIZE},height=${SIZE},framerate=10/1 ! videoconvert name=conv ! fakesink`
+      );
+      const detections: { payload: string; x: number; y: number; width: number }[] = [];
+      kit.addQrDetector('conv', detection => detections.push(detection));
+      kit.play();
+      const frame = renderQr();
+      for (let i = 0; i < 5; i++) {
+        kit.pushSample('src', frame);
+        await new Promise(r => setTimeout(r, 50));
+      }
+      await new Promise(r => setTimeout(r, 300));
+
+      expect(detections.length).toBeGreaterThan(0);
+      expect(detections[0].payload).toBe('gstkit');
+      expect(Math.abs(detections[0].x - 4 * MODULE)).toBeLessThanOrEqual(MODULE);
+      expect(Math.abs(detections[0].width - QR.length * MODULE)).toBeLessThanOrEqual(2 * MODULE);
+      expect(kit.removeFrameTap('conv')).toBe(true);
+      kit.stop();
+      kit.cleanup();
+    });
+
+    it('should not report anything for frames without codes', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc num-buffers=10 ! videoconvert name=conv ! fakesink');
+      let count = 0;
+      kit.addQrDetector('conv', () => count++);
+      kit.play();
+      await new Promise(r => setTimeout(r, 500));
+      expect(count).toBe(0);
+      kit.stop();
+      kit.cleanup();
+    });
+  });
 });
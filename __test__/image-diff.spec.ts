@@ -0,0 +1,41 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, diffImages } from '../index.js';
+
+const PNG_MAGIC = Buffer.from([0x89, 0x50, 0x4e, 0x47]);
+
+async function renderPng(pattern: string): Promise<Buffer> {
+  const kit = new GstKit();
+  kit.setPipeline(
+    `videotestsrc pattern=${pattern} num-buffers=1 ! video/x-raw,width=64,height=64 ! pngenc ! appsink name=sink`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 300));
+  const png = kit.pullSample('sink', 1000);
+  kit.cleanup();
+  if (!png) {
+    throw new Error('Failed to render test image');
+  }
+  return png;
+}
+
+describe('diffImages', () => {
+  it('should report identical images as fully similar', async () => {
+    const png = await renderPng('smpte');
+    const diff = diffImages(png, png);
+    expect(diff.similarity).toBe(1);
+    expect(diff.differentPixels).toBe(0);
+    expect(diff.totalPixels).toBe(64 * 64);
+    expect(diff.heatmap).toBeUndefined();
+  });
+
+  it('should detect differences and render a heatmap', async () => {
+    const diff = diffImages(await renderPng('black'), await renderPng('white'), { heatmap: true });
+    expect(diff.similarity).toBe(0);
+    expect(diff.meanDifference).toBeGreaterThan(0.5);
+    expect(diff.heatmap!.subarray(0, 4).equals(PNG_MAGIC)).toBe(true);
+  });
+
+  it('should throw on data that is not an image', () => {
+    expect(() => diffImages(Buffer.from('not an image'), Buffer.from('nope'))).toThrow();
+  });
+});
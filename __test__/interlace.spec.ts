@@ -0,0 +1,84 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, detectInterlacing, probeWithGStreamer, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+/** Renders a moving ball through GStreamer's `interlace` element */
+async function generateFieldVideo(filename: string, framerate: string, pattern: string): Promise<string> {
+  const output = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    `videotestsrc pattern=ball num-buffers=120 ! video/x-raw,width=320,height=240,framerate=${framerate} ! ` +
+      `interlace field-pattern=${pattern} top-field-first=true ! videoconvert ! jpegenc quality=100 ! avimux ! ` +
+      `filesink location="${output}"`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  kit.stop();
+  kit.cleanup();
+  return output;
+}
+
+describe('detectInterlacing', () => {
+  let progressive: string;
+  let interlaced: string;
+  let telecined: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    progressive = await generateTestVideo('progressive.avi', 'ball', { numBuffers: 60 });
+    // Each field from its own frame, and film frames spread over 2 then 3 fields
+    interlaced = await generateFieldVideo('interlaced.avi', '60/1', '1:1');
+    telecined = await generateFieldVideo('telecined.avi', '24000/1001', '2:3');
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should report progressive video', () => {
+    const report = detectInterlacing(progressive);
+    expect(report.verdict).toBe('progressive');
+    expect(report.framesAnalyzed).toBe(60);
+    expect(report.recommendedFilter).toBeNull();
+  });
+
+  it('should report interlaced video', () => {
+    const report = detectInterlacing(interlaced);
+    expect(report.verdict).toBe('interlaced');
+    expect(report.combedRatio).toBeGreaterThanOrEqual(0.5);
+    expect(report.recommendedFilter).toBe('deinterlace');
+  });
+
+  it('should report telecined video and its pulldown phase', () => {
+    const report = detectInterlacing(telecined, { frames: 100 });
+    expect(report.verdict).toBe('telecined');
+    expect(report.framesAnalyzed).toBe(100);
+    expect(report.combedRatio).toBeCloseTo(0.4, 1);
+    expect(report.pulldownPhase).toBeGreaterThanOrEqual(0);
+    expect(report.recommendedFilter).toBe('ivtc');
+  });
+
+  it('should restore progressive film frames with the ivtc filter', () => {
+    const output = path.join(TEST_DIR, 'ivtc.mkv');
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(telecined), { container: 'mkv', videoFilters: ['ivtc'] }));
+    expect(probeWithGStreamer(output).video[0].frameRate).toBeCloseTo(23.976, 2);
+    expect(detectInterlacing(output).verdict).toBe('progressive');
+  });
+
+  it('should deinterlace with the deinterlace filter', () => {
+    const output = path.join(TEST_DIR, 'deinterlaced.mkv');
+    fs.writeFileSync(
+      output,
+      transcodeBuffer(fs.readFileSync(interlaced), { container: 'mkv', videoFilters: ['deinterlace'] })
+    );
+    expect(detectInterlacing(output).verdict).not.toBe('interlaced');
+  });
+
+  it('should reject arguments to ivtc and deinterlace', () => {
+    for (const filter of ['ivtc=1', 'deinterlace=yadif']) {
+      expect(() => transcodeBuffer(fs.readFileSync(progressive), { container: 'mkv', videoFilters: [filter] })).toThrow();
+    }
+  });
+});
@@ -0,0 +1,36 @@
+import { describe, it, expect } from 'bun:test';
+import { measureLatency } from '../index.js';
+
+describe('measureLatency', () => {
+  it('should recognize overlay frames through an encoder', () => {
+    const report = measureLatency('jpegenc ! jpegdec', { resolution: '160x120', frames: 20, framerate: 60 });
+    expect(report.sent).toBe(20);
+    expect(report.received).toBeGreaterThan(15);
+    expect(report.lost).toBe(report.sent - report.received);
+    expect(report.samples.length).toBe(report.received);
+    expect(report.min).toBeGreaterThanOrEqual(0);
+    expect(report.min).toBeLessThanOrEqual(report.median);
+    expect(report.median).toBeLessThanOrEqual(report.p95);
+    expect(report.p99).toBeLessThanOrEqual(report.max);
+  });
+
+  it('should match frames by timestamp with the pts pattern', () => {
+    const report = measureLatency('queue', { pattern: 'pts', resolution: '64x64', frames: 10, framerate: 60 });
+    expect(report.received).toBe(10);
+    expect(report.lost).toBe(0);
+  });
+
+  it('should include the delay added by the pipeline', () => {
+    const report = measureLatency('queue min-threshold-time=100000000', {
+      resolution: '64x64',
+      frames: 10,
+      framerate: 30,
+    });
+    expect(report.received).toBeGreaterThan(0);
+    expect(report.median).toBeGreaterThanOrEqual(50);
+  });
+
+  it('should reject unknown patterns', () => {
+    expect(() => measureLatency('queue', { pattern: 'smpte' })).toThrow();
+  });
+});
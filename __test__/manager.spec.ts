@@ -0,0 +1,45 @@
+import { describe, it, expect } from 'bun:test';
+import { PipelineManager, type ManagedPipelineEvent } from '../index.js';
+
+describe('PipelineManager', () => {
+  it('should add, list and remove named pipelines', () => {
+    const manager = new PipelineManager();
+    manager.addPipeline('b', 'videotestsrc ! fakesink');
+    manager.addPipeline('a', 'audiotestsrc ! fakesink');
+    expect(manager.listPipelines()).toEqual(['a', 'b']);
+    expect(manager.removePipeline('a')).toBe(true);
+    expect(manager.removePipeline('a')).toBe(false);
+    expect(manager.listPipelines()).toEqual(['b']);
+  });
+
+  it('should reject duplicate ids and unknown pipelines', () => {
+    const manager = new PipelineManager();
+    manager.addPipeline('main', 'videotestsrc ! fakesink');
+    expect(() => manager.addPipeline('main', 'videotestsrc ! fakesink')).toThrow();
+    expect(() => manager.play('missing')).toThrow();
+    expect(() => manager.addPipeline('bad', 'not_an_element')).toThrow();
+  });
+
+  it('should play and stop all pipelines', async () => {
+    const manager = new PipelineManager();
+    manager.addPipeline('one', 'videotestsrc ! fakesink');
+    manager.addPipeline('two', 'videotestsrc ! fakesink');
+    manager.playAll();
+    await new Promise(resolve => setTimeout(resolve, 200));
+    const stats = manager.statsAll();
+    expect(stats.map(s => s.id)).toEqual(['one', 'two']);
+    expect(stats.every(s => s.state === 'Playing')).toBe(true);
+    manager.stopAll();
+    expect(manager.statsAll().every(s => s.state === 'Null')).toBe(true);
+  });
+
+  it('should route events with the pipeline id', async () => {
+    const manager = new PipelineManager();
+    const events: ManagedPipelineEvent[] = [];
+    manager.onEvent(event => events.push(event));
+    manager.addPipeline('short', 'videotestsrc num-buffers=5 ! fakesink');
+    manager.play('short');
+    await new Promise(resolve => setTimeout(resolve, 500));
+    expect(events.some(e => e.pipelineId === 'short' && e.eventType === 'eos')).toBe(true);
+  });
+});
@@ -0,0 +1,27 @@
+import { describe, it, expect } from 'bun:test';
+import { diagnosePipeline, GstKit } from '../index.js';
+
+describe('diagnosePipeline', () => {
+  it('should accept pipelines whose elements are installed', () => {
+    const diagnosis = diagnosePipeline('videotestsrc ! fakesink');
+    expect(diagnosis.valid).toBe(true);
+    expect(diagnosis.missing).toEqual([]);
+  });
+
+  it('should report missing elements with package hints', () => {
+    const diagnosis = diagnosePipeline('videotestsrc ! no_such_element_1234 ! fakesink');
+    expect(diagnosis.valid).toBe(false);
+    expect(diagnosis.error).toBeDefined();
+    expect(diagnosis.missing.map(element => element.factory)).toEqual(['no_such_element_1234']);
+    expect(diagnosis.missing[0].packages).toEqual([]);
+  });
+});
+
+describe('setPipeline', () => {
+  it('should list missing elements in the parse error', () => {
+    const kit = new GstKit();
+    expect(() => kit.setPipeline('videotestsrc ! no_such_element_1234 ! fakesink')).toThrow(
+      /Missing elements: no_such_element_1234 \(unknown plugin\)/
+    );
+  });
+});
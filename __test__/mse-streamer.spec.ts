@@ -0,0 +1,77 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, MseStreamer, type MseSegment } from '../index.js';
+
+const CLUSTER = [0x1f, 0x43, 0xb6, 0x75];
+
+/** Pulls 30 VP8 frames with a keyframe every 10 */
+function encodedFrames(): Buffer[] {
+  const kit = new GstKit();
+  kit.setPipeline(
+    'videotestsrc num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! vp8enc keyframe-max-dist=10 deadline=1 ! appsink name=sink sync=false'
+  );
+  kit.play();
+  const frames: Buffer[] = [];
+  for (let i = 0; i < 30; i++) {
+    const frame = kit.pullSample('sink', 2000);
+    if (frame) frames.push(frame);
+  }
+  kit.cleanup();
+  return frames;
+}
+
+/** Whether a VP8 frame is a keyframe */
+const isKeyframe = (frame: Buffer) => (frame[0] & 1) === 0;
+
+describe('MseStreamer', () => {
+  it('should report the MIME type of its codec', () => {
+    expect(new MseStreamer().mimeType).toBe('video/webm; codecs="vp8"');
+    expect(new MseStreamer({ codec: 'vp9' }).mimeType).toBe('video/webm; codecs="vp9"');
+  });
+
+  it('should turn pushed frames into init and numbered media segments', async () => {
+    const streamer = new MseStreamer({ width: 320, height: 240, framerate: 30 });
+    const segments: MseSegment[] = [];
+    streamer.onSegment(segment => segments.push(segment));
+
+    const frames = encodedFrames();
+    expect(streamer.pushFrame(frames[1], undefined, false)).toBe(false);
+    frames.forEach((frame, i) => streamer.pushFrame(frame, Math.round(i * 1e9 / 30), isKeyframe(frame)));
+    streamer.end();
+    await new Promise(resolve => setTimeout(resolve, 100));
+
+    expect(segments[0].segmentType).toBe('init');
+    expect([...segments[0].data.subarray(0, 4)]).toEqual([0x1a, 0x45, 0xdf, 0xa3]);
+    expect(streamer.initSegment()).toEqual(segments[0].data);
+    const media = segments.slice(1);
+    expect(media.length).toBe(3);
+    expect(streamer.segments).toBe(3);
+    expect(media.map(segment => segment.sequence)).toEqual([0, 1, 2]);
+    for (const segment of media) {
+      expect(segment.segmentType).toBe('media');
+      expect([...segment.data.subarray(0, 4)]).toEqual(CLUSTER);
+    }
+    expect(media[1].timestamp).toBeCloseTo(10 / 30 * 1e9, -6);
+  });
+
+  it('should encode the raw frames of an attached sink', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=60 ! video/x-raw,width=320,height=240,framerate=30/1 ! appsink name=sink');
+    const streamer = new MseStreamer({ fragmentDurationMs: 500 });
+    const segments: MseSegment[] = [];
+    streamer.onSegment(segment => segments.push(segment));
+    streamer.attach(kit, 'sink');
+    kit.play();
+    await new Promise(resolve => setTimeout(resolve, 3000));
+    streamer.end();
+    kit.cleanup();
+    await new Promise(resolve => setTimeout(resolve, 100));
+
+    expect(segments[0].segmentType).toBe('init');
+    expect(streamer.segments).toBeGreaterThanOrEqual(4);
+  });
+
+  it('should require the frame size for pushed frames', () => {
+    const streamer = new MseStreamer();
+    expect(() => streamer.pushFrame(Buffer.alloc(16), 0, true)).toThrow('width and height');
+  });
+});
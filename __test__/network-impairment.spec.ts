@@ -0,0 +1,55 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+const LIVE = 'videotestsrc is-live=true ! video/x-raw,width=160,height=120,framerate=30/1 ! appsink name=out sync=false';
+
+describe('impairNetwork', () => {
+  it('should insert a netsim element in front of the sink', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink name=out');
+    kit.impairNetwork('out', { loss: 0.1, delayMs: 50, jitterMs: 20, bandwidthKbps: 500 });
+
+    expect(kit.getElements()).toContain('out-netsim');
+    expect(kit.getProperty('out-netsim', 'min-delay')).toContain('30');
+    expect(kit.getProperty('out-netsim', 'max-delay')).toContain('70');
+    expect(kit.getProperty('out-netsim', 'max-kbps')).toContain('500');
+    kit.cleanup();
+  });
+
+  it('should drop every packet of a running stream and recover once cleared', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(LIVE);
+    kit.play();
+    expect(kit.pullSample('out', 2000)).not.toBeNull();
+
+    kit.impairNetwork('out', { loss: 1 });
+    await new Promise(resolve => setTimeout(resolve, 200));
+    while (kit.pullSample('out', 0)) {}
+    expect(kit.pullSample('out', 500)).toBeNull();
+
+    expect(kit.clearNetworkImpairment('out')).toBe(true);
+    expect(kit.pullSample('out', 2000)).not.toBeNull();
+    kit.cleanup();
+  });
+
+  it('should update an existing impairment instead of adding another', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink name=out');
+    kit.impairNetwork('out', { loss: 0.5 });
+    kit.impairNetwork('out', { delayMs: 10 });
+
+    expect(kit.getElements().filter(name => name.endsWith('netsim'))).toHaveLength(1);
+    expect(kit.getProperty('out-netsim', 'drop-probability')).toContain('0');
+    expect(kit.getProperty('out-netsim', 'min-delay')).toContain('10');
+    kit.cleanup();
+  });
+
+  it('should validate the impairment and the sink', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink name=out');
+    expect(() => kit.impairNetwork('out', { loss: 2 })).toThrow('loss must be between 0 and 1');
+    expect(() => kit.impairNetwork('missing', {})).toThrow('Element missing not found');
+    expect(kit.clearNetworkImpairment('out')).toBe(false);
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,36 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, watchNetwork, type NetworkEvent } from '../index.js';
+
+describe('watchNetwork', () => {
+  it('should start connected with a full buffer', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink');
+    const watcher = watchNetwork(kit);
+    expect(watcher.connected).toBe(true);
+    expect(watcher.buffering).toBe(100);
+    watcher.stop();
+    kit.cleanup();
+  });
+
+  it('should report a lost connection and retry it', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('souphttpsrc location=http://127.0.0.1:9/stream retries=0 ! fakesink');
+    const watcher = watchNetwork(kit, { reconnectAttempts: 1, reconnectDelayMs: 50 });
+    const events: NetworkEvent[] = [];
+    watcher.onEvent(event => events.push(event));
+    kit.play();
+    await new Promise(resolve => setTimeout(resolve, 1500));
+    watcher.stop();
+    kit.cleanup();
+
+    expect(watcher.connected).toBe(false);
+    const lost = events.find(event => event.eventType === 'connection-lost');
+    expect(lost?.message).toBeDefined();
+    expect(events.filter(event => event.eventType === 'connection-lost')).toHaveLength(1);
+    expect(events.filter(event => event.eventType === 'reconnecting').map(event => event.attempt)).toEqual([1]);
+  });
+
+  it('should require a pipeline', () => {
+    expect(() => watchNetwork(new GstKit())).toThrow('Pipeline not initialized');
+  });
+});
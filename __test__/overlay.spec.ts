@@ -0,0 +1,103 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, overlayVideo, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as path from 'node:path';
+
+// Runs a pipeline writing `filename` to completion
+const generate = async (filename: string, pipeline: string) => {
+  const outputPath = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(`${pipeline} ! filesink location="${outputPath}"`);
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 500));
+  kit.stop();
+  kit.cleanup();
+  return outputPath;
+};
+
+function firstFrame(file: string) {
+  const kit = new GstKit();
+  kit.setPipeline(
+    `filesrc location="${file}" ! decodebin ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink sync=false`
+  );
+  kit.play();
+  const frame = kit.pullSampleAsImageData('sink', { timeoutMs: 5000 });
+  kit.stop();
+  kit.cleanup();
+  return frame!;
+}
+
+describe('overlayVideo', () => {
+  let greenScreen: string;
+  let redImage: string;
+  let blueVideo: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    // A green screen with white bands along its top and left edges
+    greenScreen = await generate(
+      'overlay_green.avi',
+      'videotestsrc pattern=solid-color foreground-color=0xff00ff00 num-buffers=10 ! ' +
+        'video/x-raw,width=160,height=120,framerate=10/1 ! ' +
+        'compositor background=white sink_0::xpos=40 sink_0::ypos=40 ! ' +
+        'video/x-raw,width=160,height=120 ! videoconvert ! jpegenc ! avimux'
+    );
+    redImage = await generate(
+      'overlay_red.png',
+      'videotestsrc pattern=solid-color foreground-color=0xffff0000 num-buffers=1 ! ' +
+        'video/x-raw,width=320,height=240 ! videoconvert ! pngenc'
+    );
+    blueVideo = await generate(
+      'overlay_blue.avi',
+      'videotestsrc pattern=solid-color foreground-color=0xff0000ff num-buffers=10 ! ' +
+        'video/x-raw,width=320,height=240,framerate=10/1 ! videoconvert ! jpegenc ! avimux'
+    );
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  const pixel = (frame: { data: Uint8ClampedArray | Uint8Array; width: number }, x: number, y: number) =>
+    Array.from(frame.data.subarray((y * frame.width + x) * 4, (y * frame.width + x) * 4 + 3));
+
+  it('should key a green screen over a still image', () => {
+    const output = path.join(TEST_DIR, 'overlay_image.mkv');
+    overlayVideo(greenScreen, redImage, output, { filters: ['chromakey=#00FF00:0.1:0.05'], fps: 10 });
+
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(320);
+    expect(info.video[0].height).toBe(240);
+    expect(info.duration / 1e9).toBeCloseTo(1, 0);
+
+    const frame = firstFrame(output);
+    // The keyed green shows the red background; the white box stays
+    const [r, g, b] = pixel(frame, 60, 100);
+    expect(r).toBeGreaterThan(200);
+    expect(g + b).toBeLessThan(80);
+    const [wr, wg, wb] = pixel(frame, 5, 5);
+    expect(Math.min(wr, wg, wb)).toBeGreaterThan(200);
+  });
+
+  it('should place the foreground over a video background', () => {
+    const output = path.join(TEST_DIR, 'overlay_video.webm');
+    overlayVideo(greenScreen, blueVideo, output, { x: 160, y: 120, fps: 10 });
+
+    const frame = firstFrame(output);
+    const [r, g, b] = pixel(frame, 20, 20);
+    expect(b).toBeGreaterThan(200);
+    expect(r + g).toBeLessThan(80);
+    // Without a key the green screen is drawn as is
+    const [gr, gg, gb] = pixel(frame, 300, 230);
+    expect(gg).toBeGreaterThan(200);
+    expect(gr + gb).toBeLessThan(80);
+  });
+
+  it('should reject invalid filters and inputs', () => {
+    const output = path.join(TEST_DIR, 'overlay_invalid.webm');
+    expect(() => overlayVideo(greenScreen, redImage, output, { filters: ['chromakey=#00FF00:5'] })).toThrow(
+      /Invalid video filter/
+    );
+    expect(() => overlayVideo(path.join(TEST_DIR, 'missing.avi'), redImage, output)).toThrow();
+  });
+});
@@ -0,0 +1,58 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { computePerceptualHashes, findDuplicateSegments, perceptualHashDistance } from '../index.js';
+import setup, { generateTestVideo } from './setup.js';
+
+describe('perceptual hashing', () => {
+  let smallFile: string;
+  let largeFile: string;
+  let noiseFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    // The same moving ball rendered at two sizes
+    smallFile = await generateTestVideo('phash_small.avi', 'ball', { numBuffers: 90, width: 320, height: 240 });
+    largeFile = await generateTestVideo('phash_large.avi', 'ball', { numBuffers: 90, width: 640, height: 480 });
+    noiseFile = await generateTestVideo('phash_noise.avi', 'snow', { numBuffers: 90 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should hash every frame as 16 hexadecimal digits', () => {
+    const hashes = computePerceptualHashes(smallFile);
+    expect(hashes.length).toBe(90);
+    expect(hashes[0].frame).toBe(0);
+    expect(hashes[0].phash).toMatch(/^[0-9a-f]{16}$/);
+    expect(hashes[0].dhash).toMatch(/^[0-9a-f]{16}$/);
+    expect(hashes[1].pts).toBeGreaterThan(hashes[0].pts);
+  });
+
+  it('should honor fps and interval', () => {
+    expect(computePerceptualHashes(smallFile, { fps: 10 }).length).toBe(30);
+    const hashes = computePerceptualHashes(smallFile, { interval: 10 });
+    expect(hashes.map(h => h.frame)).toEqual([0, 10, 20, 30, 40, 50, 60, 70, 80]);
+  });
+
+  it('should give close hashes to scaled copies and distant ones to other content', () => {
+    const [small, large, noise] = [smallFile, largeFile, noiseFile].map(f => computePerceptualHashes(f, { interval: 30 }));
+    for (let i = 0; i < small.length; i++) {
+      expect(perceptualHashDistance(small[i].phash, large[i].phash)).toBeLessThanOrEqual(6);
+      expect(perceptualHashDistance(small[i].dhash, large[i].dhash)).toBeLessThanOrEqual(6);
+      expect(perceptualHashDistance(small[i].phash, noise[i].phash)).toBeGreaterThan(10);
+    }
+    expect(perceptualHashDistance('0000000000000000', 'ffffffffffffffff')).toBe(64);
+    expect(() => perceptualHashDistance('xyz', '0000000000000000')).toThrow(/Invalid perceptual hash/);
+  });
+
+  it('should find duplicate segments between two files', () => {
+    const segments = findDuplicateSegments(smallFile, largeFile, { minDuration: 1 });
+    expect(segments.length).toBeGreaterThan(0);
+    const [longest] = segments;
+    expect(longest.firstStart).toBe(longest.secondStart);
+    expect(longest.firstEnd - longest.firstStart).toBeGreaterThanOrEqual(2.5e9);
+    expect(longest.distance).toBeLessThanOrEqual(10);
+
+    expect(findDuplicateSegments(smallFile, noiseFile, { minDuration: 1 })).toEqual([]);
+  });
+});
@@ -0,0 +1,47 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, PipelineBuilder } from '../index.js';
+
+describe('PipelineBuilder', () => {
+  it('should generate a launch string', () => {
+    const description = new PipelineBuilder()
+      .testSource({ numBuffers: 3 })
+      .convert('RGBA')
+      .scale(64, 48)
+      .appsink('out', { sync: false })
+      .build();
+    expect(description).toBe(
+      'videotestsrc num-buffers=3 ! videoconvert ! video/x-raw,format=RGBA ! videoscale ! video/x-raw,width=64,height=48 ! appsink name=out sync=false'
+    );
+  });
+
+  it('should quote file paths', () => {
+    expect(new PipelineBuilder().source('/tmp/my clip.mp4').decode().sink('fakesink').build()).toBe(
+      'filesrc location="/tmp/my clip.mp4" ! decodebin ! fakesink'
+    );
+  });
+
+  it('should reject steps out of order', () => {
+    expect(() => new PipelineBuilder().decode()).toThrow('decode() needs a source before it');
+    expect(() => new PipelineBuilder().testSource().testSource()).toThrow();
+    expect(() => new PipelineBuilder().testSource().sink('fakesink').convert()).toThrow();
+    expect(() => new PipelineBuilder().testSource().build()).toThrow('The pipeline needs a source and a sink');
+  });
+
+  it('should reject invalid formats, caps and elements', () => {
+    expect(() => new PipelineBuilder().testSource().convert('NOT_A_FORMAT')).toThrow();
+    expect(() => new PipelineBuilder().testSource().caps('video/x-raw,width=(int)abc')).toThrow();
+    expect(() => new PipelineBuilder().testSource().element('no_such_element_1234').sink('fakesink').build()).toThrow(
+      /no_such_element_1234/
+    );
+  });
+
+  it('should build pipelines GstKit can run', () => {
+    const kit = new GstKit();
+    kit.setPipeline(new PipelineBuilder().testSource({ numBuffers: 1 }).convert('RGBA').appsink('out').build());
+    kit.play();
+    const frame = kit.pullFrame('out', { timeoutMs: 2000 });
+    expect(frame).not.toBeNull();
+    expect(frame!.format).toBe('RGBA');
+    kit.cleanup();
+  });
+});
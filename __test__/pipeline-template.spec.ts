@@ -0,0 +1,43 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, renderPipelineTemplate } from '../index.js';
+
+describe('renderPipelineTemplate', () => {
+  it('should substitute strings, numbers and booleans', () => {
+    expect(
+      renderPipelineTemplate('videotestsrc is-live={live} ! video/x-raw,width={width},framerate={fps} ! {sink}', {
+        live: true,
+        width: 640,
+        fps: '30/1',
+        sink: 'fakesink',
+      })
+    ).toBe('videotestsrc is-live=true ! video/x-raw,width=640,framerate=30/1 ! fakesink');
+  });
+
+  it('should quote values that are not a single token', () => {
+    expect(
+      renderPipelineTemplate('filesrc location={path} ! fakesink', { path: 'a b" ! filesink location=/tmp/x' })
+    ).toBe('filesrc location="a b\\" ! filesink location=/tmp/x" ! fakesink');
+  });
+
+  it('should keep caps lists and escaped braces', () => {
+    expect(renderPipelineTemplate('video/x-raw,format={ RGBA, BGRA } {{name}}', {})).toBe(
+      'video/x-raw,format={ RGBA, BGRA } {name}'
+    );
+  });
+
+  it('should reject missing variables', () => {
+    expect(() => renderPipelineTemplate('{src} ! {sink}', { src: 'videotestsrc' })).toThrow(
+      'Missing template variables: sink'
+    );
+  });
+});
+
+describe('setPipelineTemplate', () => {
+  it('should set the rendered pipeline', () => {
+    const kit = new GstKit();
+    kit.setPipelineTemplate('videotestsrc num-buffers={count} ! appsink name={sink}', { count: 1, sink: 'out' });
+    kit.play();
+    expect(kit.pullFrame('out', { timeoutMs: 2000 })).not.toBeNull();
+    kit.cleanup();
+  });
+});
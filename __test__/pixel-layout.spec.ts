@@ -0,0 +1,208 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, i420ToNv12, nv12ToI420 } from '../index.js';
+
+describe('Pixel Layout', () => {
+  describe('NV12 and I420 conversion', () => {
+    // 4x2 frame: 8 luma samples, 2x1 chroma samples per plane
+    const i420 = Buffer.from([0, 1, 2, 3, 4, 5, 6, 7, 10, 11, 20, 21]);
+    const nv12 = Buffer.from([0, 1, 2, 3, 4, 5, 6, 7, 10, 20, 11, 21]);
+
+    it('should interleave the chroma planes', () => {
+      expect(i420ToNv12(i420, 4, 2)).toEqual(nv12);
+    });
+
+    it('should split interleaved chroma', () => {
+      expect(nv12ToI420(nv12, 4, 2)).toEqual(i420);
+    });
+
+    it('should round odd sizes up for chroma', () => {
+      // 3x3 frame: 9 luma samples, 2x2 chroma samples per plane
+      const frame = Buffer.alloc(9 + 2 * 4, 7);
+      expect(nv12ToI420(i420ToNv12(frame, 3, 3), 3, 3)).toEqual(frame);
+    });
+
+    it('should reject buffers of the wrong size', () => {
+      expect(() => nv12ToI420(Buffer.alloc(10), 4, 2)).toThrow();
+      expect(() => i420ToNv12(i420, 0, 2)).toThrow();
+    });
+  });
+
+  describe('Packed frames', () => {
+    it('should remove row padding from pulled frames', async () => {
+      // 30 pixel wide I420 rows are padded to a stride of 32
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=I420,width=30,height=20 ! appsink name=sink');
+      kit.play();
+      const padded = kit.pullFrame('sink', { timeoutMs: 2000 });
+      const packed = kit.pullFrame('sink', { timeoutMs: 2000, packed: true });
+      kit.stop();
+      kit.cleanup();
+
+      expect(padded!.planes[0].stride).toBe(32);
+      expect(packed!.planes.map(p => p.stride)).toEqual([30, 15, 15]);
+      expect(packed!.planes.map(p => p.offset)).toEqual([0, 600, 750]);
+      expect(packed!.data.length).toBe(30 * 20 + 2 * 15 * 10);
+      expect(packed!.planes[0].data.subarray(0, 30)).toEqual(padded!.planes[0].data.subarray(0, 30));
+    });
+
+    it('should convert packed NV12 frames to I420', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=NV12,width=30,height=20 ! appsink name=sink');
+      kit.play();
+      const frame = kit.pullFrame('sink', { timeoutMs: 2000, packed: true });
+      kit.stop();
+      kit.cleanup();
+
+      const i420 = nv12ToI420(frame!.data, frame!.width, frame!.height);
+      expect(i420.length).toBe(30 * 20 + 2 * 15 * 10);
+      expect(i420ToNv12(i420, 30, 20)).toEqual(frame!.data);
+    });
+  });
+
+  describe('Cropped frames', () => {
+    it('should align the crop to the chroma grid of I420', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=I420,width=64,height=48 ! appsink name=sink');
+      kit.play();
+      const full = kit.pullFrame('sink', { timeoutMs: 2000, packed: true });
+      const crop = kit.pullFrame('sink', {
+        timeoutMs: 2000,
+        crop: { x: 9, y: 5, width: 20, height: 10 },
+      });
+      kit.stop();
+      kit.cleanup();
+
+      // The origin moves to 8,4 and the right and bottom edges stay put
+      expect([crop!.width, crop!.height]).toEqual([21, 11]);
+      expect(crop!.planes.map(p => [p.width, p.height])).toEqual([[21, 11], [11, 6], [11, 6]]);
+      expect(crop!.planes.map(p => p.offset)).toEqual([0, 231, 297]);
+      expect(crop!.data.length).toBe(21 * 11 + 2 * 11 * 6);
+      for (let row = 0; row < 11; row++) {
+        const start = (4 + row) * 64 + 8;
+        expect(crop!.planes[0].data.subarray(row * 21, row * 21 + 21)).toEqual(
+          full!.planes[0].data.subarray(start, start + 21),
+        );
+      }
+      const u = full!.planes[1].data;
+      expect(crop!.planes[1].data.subarray(0, 11)).toEqual(u.subarray(2 * 32 + 4, 2 * 32 + 15));
+    });
+
+    it('should crop RGBA frames at any pixel', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=RGBA,width=32,height=16 ! appsink name=sink');
+      kit.play();
+      const full = kit.pullFrame('sink', { timeoutMs: 2000 });
+      const crop = kit.pullFrame('sink', {
+        timeoutMs: 2000,
+        crop: { x: 27, y: 3, width: 10, height: 2 },
+      });
+      kit.stop();
+      kit.cleanup();
+
+      expect([crop!.width, crop!.height]).toEqual([5, 2]);
+      expect(crop!.planes[0].stride).toBe(20);
+      expect(crop!.data).toEqual(
+        Buffer.concat([
+          full!.data.subarray(3 * 128 + 27 * 4, 4 * 128),
+          full!.data.subarray(4 * 128 + 27 * 4, 5 * 128),
+        ]),
+      );
+    });
+
+    it('should reject crops outside the frame', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=NV12,width=32,height=16 ! appsink name=sink');
+      kit.play();
+      expect(() => kit.pullFrame('sink', { timeoutMs: 2000, crop: { x: 32, y: 0, width: 4, height: 4 } })).toThrow(
+        /outside the 32x16 frame/,
+      );
+      kit.stop();
+      kit.cleanup();
+    });
+  });
+
+  describe('ImageData frames', () => {
+    async function pull(caps: string, premultiplied?: boolean) {
+      const kit = new GstKit();
+      kit.setPipeline(
+        `videotestsrc pattern=solid-color foreground-color=0x80ff0000 ! ${caps} ! appsink name=sink`
+      );
+      kit.play();
+      const frame = kit.pullSampleAsImageData('sink', { timeoutMs: 2000, premultiplied });
+      kit.stop();
+      kit.cleanup();
+      return frame!;
+    }
+
+    it('should reorder BGRA into straight RGBA', async () => {
+      const frame = await pull('video/x-raw,format=BGRA,width=8,height=4');
+      expect(frame.data).toBeInstanceOf(Uint8ClampedArray);
+      expect(frame.data.length).toBe(8 * 4 * 4);
+      expect(Array.from(frame.data.subarray(0, 4))).toEqual([255, 0, 0, 128]);
+      expect(frame.premultiplied).toBe(false);
+    });
+
+    it('should premultiply alpha on request', async () => {
+      const frame = await pull('video/x-raw,format=BGRA,width=8,height=4', true);
+      expect(Array.from(frame.data.subarray(0, 4))).toEqual([128, 0, 0, 128]);
+      expect(frame.premultiplied).toBe(true);
+    });
+
+    it('should pack padded RGB rows with opaque alpha', async () => {
+      // 30 RGB pixels are 90 bytes, padded to a stride of 92
+      const frame = await pull('video/x-raw,format=RGB,width=30,height=2');
+      expect(frame.data.length).toBe(30 * 2 * 4);
+      expect(Array.from(frame.data.subarray(30 * 4, 30 * 4 + 4))).toEqual([255, 0, 0, 255]);
+    });
+
+    it('should reject YUV frames', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=I420,width=8,height=8 ! appsink name=sink');
+      kit.play();
+      expect(() => kit.pullSampleAsImageData('sink', { timeoutMs: 2000 })).toThrow(/ImageData/);
+      kit.stop();
+      kit.cleanup();
+    });
+  });
+
+  describe('Matrix frames', () => {
+    async function pull(caps: string, rgb?: boolean) {
+      const kit = new GstKit();
+      kit.setPipeline(
+        `videotestsrc pattern=solid-color foreground-color=0xff102030 ! ${caps} ! appsink name=sink`
+      );
+      kit.play();
+      const frame = kit.pullSampleAsMat('sink', { timeoutMs: 2000, rgb });
+      kit.stop();
+      kit.cleanup();
+      return frame!;
+    }
+
+    it('should lay out RGBA frames as packed BGR', async () => {
+      const frame = await pull('video/x-raw,format=RGBA,width=30,height=2');
+      expect([frame.rows, frame.cols, frame.channels]).toEqual([2, 30, 3]);
+      expect(frame.shape).toEqual([2, 30, 3]);
+      expect(frame.strides).toEqual([90, 3, 1]);
+      expect(frame.dtype).toBe('uint8');
+      expect(frame.matType).toBe(16);
+      expect(frame.channelOrder).toBe('BGR');
+      expect(frame.data.length).toBe(30 * 2 * 3);
+      expect(Array.from(frame.data.subarray(0, 3))).toEqual([0x30, 0x20, 0x10]);
+    });
+
+    it('should keep RGB order on request', async () => {
+      const frame = await pull('video/x-raw,format=BGRx,width=8,height=4', true);
+      expect(frame.channelOrder).toBe('RGB');
+      expect(Array.from(frame.data.subarray(0, 3))).toEqual([0x10, 0x20, 0x30]);
+    });
+
+    it('should reject YUV frames', async () => {
+      const kit = new GstKit();
+      kit.setPipeline('videotestsrc ! video/x-raw,format=NV12,width=8,height=8 ! appsink name=sink');
+      kit.play();
+      expect(() => kit.pullSampleAsMat('sink', { timeoutMs: 2000 })).toThrow(/matrix/);
+      kit.stop();
+      kit.cleanup();
+    });
+  });
+});
@@ -0,0 +1,92 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, generateTestMedia, playTranscodedInto } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as path from 'node:path';
+
+/** Pulls frames from `sink` until end of stream, or `timeoutMs` without one */
+function drain(kit: GstKit, sink: string, timeoutMs = 2000): Buffer[] {
+  const frames: Buffer[] = [];
+  for (let frame = kit.pullSample(sink, timeoutMs); frame; frame = kit.pullSample(sink, timeoutMs)) {
+    frames.push(frame);
+  }
+  return frames;
+}
+
+describe('playTranscodedInto', () => {
+  const input = path.join(TEST_DIR, 'feed.mkv');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(input, { format: 'mkv', width: 160, height: 120, fps: 10, duration: 1, audio: true });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should push every decoded frame into the appsrc', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=src format=time ! videoconvert ! video/x-raw,format=RGBA ! appsink name=out');
+    kit.play();
+    const feed = playTranscodedInto(kit, 'src', input, { realtime: false });
+    const frames = drain(kit, 'out');
+    feed.stop();
+    kit.cleanup();
+
+    expect(frames.length).toBe(10);
+    expect(frames[0].length).toBe(160 * 120 * 4);
+    expect(feed.frames).toBe(10);
+    expect(feed.finished).toBe(true);
+  });
+
+  it('should convert frames to the caps of the appsrc', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=src format=time caps=video/x-raw,format=GRAY8,width=64,height=48 ! appsink name=out');
+    kit.play();
+    const feed = playTranscodedInto(kit, 'src', input, { realtime: false });
+    const frames = drain(kit, 'out');
+    feed.stop();
+    kit.cleanup();
+
+    expect(frames.length).toBe(10);
+    expect(frames.every(frame => frame.length === 64 * 48)).toBe(true);
+  });
+
+  it('should feed audio into an audio appsrc', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=src caps=audio/x-raw,format=S16LE,rate=8000,channels=1,layout=interleaved ! appsink name=out');
+    kit.play();
+    const feed = playTranscodedInto(kit, 'src', input, { realtime: false });
+    const bytes = drain(kit, 'out').reduce((total, buffer) => total + buffer.length, 0);
+    feed.stop();
+    kit.cleanup();
+
+    // One second of 16 bit mono at 8 kHz
+    expect(bytes).toBeGreaterThan(15_000);
+    expect(bytes).toBeLessThan(17_000);
+  });
+
+  it('should pace frames by their timestamps in realtime mode', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=src format=time ! fakesink');
+    kit.play();
+    const started = Date.now();
+    const feed = playTranscodedInto(kit, 'src', input, { realtime: true });
+    while (!feed.finished && Date.now() - started < 5000) {
+      Bun.sleepSync(20);
+    }
+    feed.stop();
+    kit.cleanup();
+
+    expect(feed.finished).toBe(true);
+    expect(Date.now() - started).toBeGreaterThanOrEqual(800);
+  });
+
+  it('should reject unknown appsrcs and media', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=src format=time ! fakesink');
+    expect(() => playTranscodedInto(kit, 'missing', input)).toThrow();
+    expect(() => playTranscodedInto(kit, 'src', input, { media: 'text' })).toThrow();
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,63 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+const MS = 1_000_000;
+
+async function paused(): Promise<GstKit> {
+  const kit = new GstKit();
+  kit.setPipeline(
+    'videotestsrc ! video/x-raw,width=16,height=16,framerate=100/1 ! appsink name=sink sync=false max-buffers=2',
+  );
+  await kit.pauseAsync(5000);
+  return kit;
+}
+
+describe('playback loop', () => {
+  it('should repeat the region', async () => {
+    const kit = await paused();
+    kit.setLoopRegion(200 * MS, 300 * MS);
+    kit.play();
+    const timestamps: number[] = [];
+    for (let i = 0; i < 40; i++) {
+      const frame = kit.pullFrame('sink', { timeoutMs: 2000 });
+      if (frame) timestamps.push(frame.timestamp);
+    }
+    kit.cleanup();
+
+    expect(timestamps.length).toBe(40);
+    for (const timestamp of timestamps) {
+      expect(timestamp).toBeGreaterThanOrEqual(200 * MS);
+      expect(timestamp).toBeLessThan(300 * MS);
+    }
+    // Wrapped around at least once
+    expect(timestamps.some((timestamp, i) => i > 0 && timestamp < timestamps[i - 1])).toBe(true);
+  });
+
+  it('should continue past the region once cleared', async () => {
+    const kit = await paused();
+    kit.setLoopRegion(200 * MS, 300 * MS);
+    kit.play();
+    kit.pullFrame('sink', { timeoutMs: 2000 });
+    expect(kit.clearLoop()).toBe(true);
+    let last = 0;
+    for (let i = 0; i < 30; i++) {
+      const frame = kit.pullFrame('sink', { timeoutMs: 2000 });
+      if (frame) last = frame.timestamp;
+    }
+    kit.cleanup();
+
+    expect(last).toBeGreaterThanOrEqual(300 * MS);
+  });
+
+  it('should report whether a loop was cleared', async () => {
+    const kit = await paused();
+    expect(kit.clearLoop()).toBe(false);
+    kit.cleanup();
+  });
+
+  it('should reject empty regions', async () => {
+    const kit = await paused();
+    expect(() => kit.setLoopRegion(300 * MS, 300 * MS)).toThrow('The loop must end after it starts');
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,26 @@
+import { describe, it, expect } from 'bun:test';
+import { listPresets, transcodeBuffer } from '../index.js';
+
+describe('listPresets', () => {
+  it('should list named and numeric presets for h264', () => {
+    const { presets, tunes } = listPresets('h264');
+    expect(presets).toContain('ultrafast');
+    expect(presets).toContain('placebo');
+    expect(presets).toContain('10');
+    expect(tunes).toContain('zerolatency');
+  });
+
+  it('should list no presets for mjpeg', () => {
+    const { presets, tunes } = listPresets('mjpeg');
+    expect(presets.length).toBe(0);
+    expect(tunes.length).toBe(0);
+  });
+
+  it('should throw on unsupported codec', () => {
+    expect(() => listPresets('nope')).toThrow();
+  });
+
+  it('should reject an invalid preset', () => {
+    expect(() => transcodeBuffer(Buffer.alloc(0), { container: 'webm', preset: 'warp' })).toThrow();
+  });
+});
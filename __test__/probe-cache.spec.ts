@@ -0,0 +1,68 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { probeDirectory } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('probeDirectory', () => {
+  const libraryDir = path.join(TEST_DIR, 'library');
+  const cachePath = path.join(TEST_DIR, 'probe-cache.json');
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    fs.mkdirSync(libraryDir, { recursive: true });
+    await generateTestVideo('library/a.avi', 'smpte', { numBuffers: 10, width: 160, height: 120 });
+    await generateTestVideo('library/b.avi', 'ball', { numBuffers: 10, width: 320, height: 240 });
+    fs.writeFileSync(path.join(libraryDir, 'notes.txt'), 'not media');
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should probe every file and report failures per file', () => {
+    const files = probeDirectory(libraryDir);
+    expect(files.map(f => path.basename(f.path))).toEqual(['a.avi', 'b.avi', 'notes.txt']);
+    expect(files[0].info!.video[0].width).toBe(160);
+    expect(files[1].info!.video[0].width).toBe(320);
+    expect(files[2].info).toBeNull();
+    expect(files[2].error).toBeTruthy();
+    expect(files.every(f => !f.cached)).toBe(true);
+  });
+
+  it('should filter by extension', () => {
+    const files = probeDirectory(libraryDir, { extensions: ['.AVI'] });
+    expect(files.length).toBe(2);
+  });
+
+  it('should serve unchanged files from the cache', () => {
+    const first = probeDirectory(libraryDir, { cachePath, extensions: ['avi'] });
+    expect(first.every(f => !f.cached)).toBe(true);
+    expect(fs.existsSync(cachePath)).toBe(true);
+
+    const second = probeDirectory(libraryDir, { cachePath, extensions: ['avi'] });
+    expect(second.every(f => f.cached)).toBe(true);
+    expect(second[0].info!.duration).toBe(first[0].info!.duration);
+    expect(second[1].info!.uri).toBe(first[1].info!.uri);
+  });
+
+  it('should probe a file again once it changes', async () => {
+    probeDirectory(libraryDir, { cachePath, extensions: ['avi'] });
+    await generateTestVideo('library/a.avi', 'smpte', { numBuffers: 10, width: 64, height: 48 });
+
+    const files = probeDirectory(libraryDir, { cachePath, extensions: ['avi'] });
+    expect(files[0].cached).toBe(false);
+    expect(files[0].info!.video[0].width).toBe(64);
+    expect(files[1].cached).toBe(true);
+  });
+
+  it('should ignore a corrupt cache file', () => {
+    fs.writeFileSync(cachePath, '{ not json');
+    const files = probeDirectory(libraryDir, { cachePath, extensions: ['avi'] });
+    expect(files.every(f => !f.cached && f.info)).toBe(true);
+  });
+
+  it('should fail for missing directories', () => {
+    expect(() => probeDirectory(path.join(TEST_DIR, 'missing'))).toThrow();
+  });
+});
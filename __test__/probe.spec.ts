@@ -0,0 +1,121 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { getMediaInfoBatch, probeAsFfprobe, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+import { pathToFileURL } from 'node:url';
+
+describe('probeWithGStreamer', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('probe_input.avi', 'smpte', { numBuffers: 30, width: 320, height: 240 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should describe container, duration and video stream', () => {
+    const info = probeWithGStreamer(inputFile);
+    expect(info.container).toBeTruthy();
+    expect(info.duration).toBeGreaterThan(0);
+    expect(info.seekable).toBe(true);
+    expect(info.live).toBe(false);
+    expect(info.video.length).toBe(1);
+    expect(info.video[0].width).toBe(320);
+    expect(info.video[0].height).toBe(240);
+    expect(info.video[0].codec.toLowerCase()).toContain('jpeg');
+    expect(info.audio.length).toBe(0);
+  });
+
+  it('should accept file URIs', () => {
+    const info = probeWithGStreamer(pathToFileURL(inputFile).href);
+    expect(info.uri.startsWith('file://')).toBe(true);
+    expect(info.video.length).toBe(1);
+  });
+
+  it('should fail for missing files', () => {
+    expect(() => probeWithGStreamer(path.join(TEST_DIR, 'missing.mkv'))).toThrow();
+  });
+});
+
+describe('getMediaInfoBatch', () => {
+  const inputs: string[] = [];
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    for (const [i, width] of [160, 320, 64].entries()) {
+      inputs.push(await generateTestVideo(`batch_${i}.avi`, 'smpte', { numBuffers: 10, width, height: 48 }));
+    }
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should probe a list of files in order', () => {
+    const missing = path.join(TEST_DIR, 'missing.avi');
+    const results = getMediaInfoBatch([...inputs, missing], { concurrency: 2 });
+    expect(results.map(r => r.path)).toEqual([...inputs, missing]);
+    expect(results.slice(0, 3).map(r => r.info!.video[0].width)).toEqual([160, 320, 64]);
+    expect(results[3].info).toBeNull();
+    expect(results[3].error).toBeTruthy();
+  });
+
+  it('should expand glob patterns', () => {
+    const results = getMediaInfoBatch(path.join(TEST_DIR, 'batch_*.avi'));
+    expect(results.map(r => path.basename(r.path))).toEqual(['batch_0.avi', 'batch_1.avi', 'batch_2.avi']);
+    expect(results.every(r => r.info && !r.error)).toBe(true);
+  });
+
+  it('should return an empty array when nothing matches', () => {
+    expect(getMediaInfoBatch(path.join(TEST_DIR, '*.none'))).toEqual([]);
+    expect(getMediaInfoBatch([])).toEqual([]);
+  });
+
+  it('should reject invalid patterns', () => {
+    expect(() => getMediaInfoBatch('[')).toThrow(/glob/);
+  });
+});
+
+describe('probeAsFfprobe', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('ffprobe_input.avi', 'smpte', { numBuffers: 30, width: 320, height: 240 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should follow the ffprobe -show_format -show_streams schema', () => {
+    const probe = JSON.parse(probeAsFfprobe(inputFile));
+    expect(probe.streams.length).toBe(1);
+    const [video] = probe.streams;
+    expect(video.index).toBe(0);
+    expect(video.codec_type).toBe('video');
+    expect(video.codec_name).toBe('mjpeg');
+    expect([video.width, video.height]).toEqual([320, 240]);
+    expect(video.r_frame_rate).toBe('30/1');
+    expect(video.display_aspect_ratio).toBe('4:3');
+
+    expect(probe.format.filename).toBe(inputFile);
+    expect(probe.format.nb_streams).toBe(1);
+    expect(probe.format.format_name).toBe('avi');
+    expect(Number(probe.format.duration)).toBeCloseTo(1, 1);
+    expect(probe.format.size).toBe(String(fs.statSync(inputFile).size));
+    expect(typeof probe.format.bit_rate).toBe('string');
+  });
+
+  it('should indent like ffprobe', () => {
+    expect(probeAsFfprobe(inputFile)).toMatch(/^\{\n    "streams": \[/);
+  });
+
+  it('should fail for missing files', () => {
+    expect(() => probeAsFfprobe(path.join(TEST_DIR, 'missing.mkv'))).toThrow();
+  });
+});
@@ -0,0 +1,90 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, dumpContainer, probeWithGStreamer, recordFromPipeline } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const RAW = 'videotestsrc num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! appsink name=sink';
+
+/** Runs `kit` long enough for its 30 frames to reach the sink */
+async function run(kit: GstKit): Promise<void> {
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 2000));
+}
+
+describe('recordFromPipeline', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should encode raw frames into WebM', async () => {
+    const output = path.join(TEST_DIR, 'recording.webm');
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    const recorder = recordFromPipeline(kit, 'sink', output);
+    await run(kit);
+    const stats = recorder.stop();
+    kit.cleanup();
+
+    expect(stats.frames).toBe(30);
+    expect(stats.duration).toBeCloseTo(29 / 30 * 1e9, -6);
+    expect(stats.bytes).toBe(fs.statSync(output).size);
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].codec.toLowerCase()).toContain('vp8');
+    expect(info.video[0].width).toBe(320);
+  });
+
+  it('should write already encoded frames into IVF without re-encoding', async () => {
+    const output = path.join(TEST_DIR, 'recording.ivf');
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! vp8enc ! appsink name=sink');
+    const recorder = recordFromPipeline(kit, 'sink', output);
+    await run(kit);
+    expect(recorder.frames).toBe(30);
+    const stats = recorder.stop();
+    kit.cleanup();
+
+    const dump = dumpContainer(output);
+    expect(dump.format).toBe('ivf');
+    expect(dump.elements[0].value).toBe('version 0, codec VP80, 320x240, time base 1/30, 30 frames');
+    expect(dump.elements.filter(element => element.name === 'Frame').map(frame => frame.value)).toEqual(
+      Array.from({ length: 30 }, (_, i) => `pts ${i}`)
+    );
+    expect(stats.bytes).toBe(fs.statSync(output).size);
+  });
+
+  it('should encode raw frames into IVF with the requested codec', async () => {
+    const output = path.join(TEST_DIR, 'recording-vp9.ivf');
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    const recorder = recordFromPipeline(kit, 'sink', output, { videoCodec: 'vp9', videoBitrate: 500 });
+    await run(kit);
+    recorder.stop();
+    kit.cleanup();
+
+    expect(dumpContainer(output).elements[0].value).toContain('codec VP90');
+  });
+
+  it('should reject unsupported containers, codecs and sinks', () => {
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    expect(() => recordFromPipeline(kit, 'sink', path.join(TEST_DIR, 'out.mp4'))).toThrow();
+    expect(() => recordFromPipeline(kit, 'sink', path.join(TEST_DIR, 'out.webm'), { videoCodec: 'h264' })).toThrow();
+    expect(() => recordFromPipeline(kit, 'nope', path.join(TEST_DIR, 'out.webm'))).toThrow();
+    kit.cleanup();
+  });
+
+  it('should fail to stop a recording without frames and remove its file', () => {
+    const output = path.join(TEST_DIR, 'empty.webm');
+    const kit = new GstKit();
+    kit.setPipeline(RAW);
+    const recorder = recordFromPipeline(kit, 'sink', output);
+    expect(() => recorder.stop()).toThrow();
+    expect(fs.existsSync(output)).toBe(false);
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,66 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, probeWithGStreamer, transcodeBuffer } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+/** Records a 320x240 MP4 whose track matrix asks players to rotate it */
+async function generateRotatedVideo(filename: string, orientation: string): Promise<string> {
+  const output = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    `videotestsrc pattern=smpte num-buffers=15 ! video/x-raw,width=320,height=240,framerate=30/1 ! ` +
+      `taginject tags="image-orientation=${orientation}" ! x264enc ! mp4mux ! filesink location="${output}"`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 1500));
+  kit.stop();
+  kit.cleanup();
+  return output;
+}
+
+describe('rotation', () => {
+  let portrait: string;
+  let upsideDown: string;
+  let plain: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    portrait = await generateRotatedVideo('rotate_90.mp4', 'rotate-90');
+    upsideDown = await generateRotatedVideo('rotate_180.mp4', 'rotate-180');
+    plain = await generateTestVideo('rotate_none.avi', 'smpte', { numBuffers: 15 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should report the rotation of video streams', () => {
+    expect(probeWithGStreamer(portrait).video[0].rotation).toBe(90);
+    expect(probeWithGStreamer(upsideDown).video[0].rotation).toBe(180);
+    expect(probeWithGStreamer(plain).video[0].rotation).toBe(0);
+  });
+
+  it('should turn the video upright with autorotate', () => {
+    const output = path.join(TEST_DIR, 'upright.mp4');
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(portrait), { container: 'mp4', autorotate: true }));
+    const [video] = probeWithGStreamer(output).video;
+    expect(video.width).toBe(240);
+    expect(video.height).toBe(320);
+    expect(video.rotation).toBe(0);
+  });
+
+  it('should keep the frames and the rotation without autorotate', () => {
+    const output = path.join(TEST_DIR, 'kept.mkv');
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(upsideDown), { container: 'mkv' }));
+    const [video] = probeWithGStreamer(output).video;
+    expect(video.width).toBe(320);
+    expect(video.height).toBe(240);
+  });
+
+  it('should reject autorotate when copying video', () => {
+    expect(() =>
+      transcodeBuffer(fs.readFileSync(portrait), { container: 'mkv', videoCodec: 'copy', autorotate: true })
+    ).toThrow(/autorotate/);
+  });
+});
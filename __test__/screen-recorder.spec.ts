@@ -0,0 +1,74 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { ScreenRecorder, probeWithGStreamer } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+// Live test sources stand in for the screen, the webcam and the microphone
+const SCREEN = 'videotestsrc is-live=true ! video/x-raw,width=640,height=480';
+const WEBCAM = 'videotestsrc is-live=true pattern=ball';
+const MICROPHONE = 'audiotestsrc is-live=true';
+
+/** Records for `ms` milliseconds */
+async function record(recorder: ScreenRecorder, ms = 1000): Promise<void> {
+  recorder.start();
+  await new Promise(resolve => setTimeout(resolve, ms));
+}
+
+describe('ScreenRecorder', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should record a region of the screen with the webcam and microphone', async () => {
+    const output = path.join(TEST_DIR, 'screen.webm');
+    const recorder = new ScreenRecorder({
+      output,
+      screen: SCREEN,
+      region: { x: 100, y: 50, width: 320, height: 240 },
+      fps: 15,
+      videoBitrate: 800,
+      webcam: { source: WEBCAM, width: 96, corner: 'top-left' },
+      microphone: MICROPHONE,
+    });
+    await record(recorder);
+    expect(recorder.frames).toBeGreaterThan(0);
+    recorder.setWebcamVisible(false);
+    recorder.setMicrophoneMuted(true);
+    const stats = recorder.stop();
+
+    expect(stats.frames).toBeGreaterThan(5);
+    expect(stats.duration).toBeGreaterThan(0);
+    expect(stats.bytes).toBe(fs.statSync(output).size);
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(320);
+    expect(info.video[0].height).toBe(240);
+    expect(info.audio).toHaveLength(1);
+  });
+
+  it('should record the screen alone', async () => {
+    const output = path.join(TEST_DIR, 'screen-only.mkv');
+    const recorder = new ScreenRecorder({ output, screen: SCREEN });
+    await record(recorder, 500);
+    recorder.stop();
+
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(640);
+    expect(info.audio).toHaveLength(0);
+    expect(() => recorder.setWebcamVisible(true)).toThrow();
+    expect(() => recorder.setMicrophoneMuted(true)).toThrow();
+    expect(() => recorder.stop()).toThrow();
+  });
+
+  it('should reject invalid options', () => {
+    const output = path.join(TEST_DIR, 'invalid.webm');
+    expect(() => new ScreenRecorder({ output: path.join(TEST_DIR, 'noextension'), screen: SCREEN })).toThrow();
+    expect(() => new ScreenRecorder({ output, screen: SCREEN, fps: 0 })).toThrow();
+    expect(() => new ScreenRecorder({ output, screen: SCREEN, region: { x: 0, y: 0, width: 0, height: 10 } })).toThrow();
+    expect(() => new ScreenRecorder({ output, screen: SCREEN, webcam: { source: WEBCAM, corner: 'middle' } })).toThrow();
+  });
+});
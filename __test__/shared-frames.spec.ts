@@ -0,0 +1,69 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+import * as fs from 'node:fs';
+
+const HEADER_SIZE = 64;
+const SLOT_HEADER_SIZE = 128;
+
+describe('Shared-Memory Frames', () => {
+  it('should publish frames into the ring', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'videotestsrc is-live=true ! video/x-raw,format=NV12,width=32,height=16,framerate=30/1 ! appsink name=sink'
+    );
+    const ring = kit.publishSharedFrames('sink', `gst-test-${process.pid}`, {
+      slots: 3,
+      maxFrameSize: 4096,
+    });
+    expect(ring.slots).toBe(3);
+    expect(ring.headerSize).toBe(HEADER_SIZE);
+    expect(ring.size).toBe(HEADER_SIZE + 3 * ring.slotSize);
+
+    kit.play();
+    await new Promise(r => setTimeout(r, 500));
+    const file = fs.readFileSync(ring.path);
+
+    expect(file.subarray(0, 8).toString()).toBe('GSTSHMR1');
+    expect(file.readUInt32LE(12)).toBe(3);
+    const published = Number(file.readBigUInt64LE(24));
+    expect(published).toBeGreaterThan(0);
+
+    const slot = HEADER_SIZE + ((published - 1) % 3) * ring.slotSize;
+    expect(Number(file.readBigUInt64LE(slot))).toBe(published);
+    expect(Number(file.readBigUInt64LE(slot + 8))).toBe(32 * 16 * 3 / 2);
+    expect(file.readUInt32LE(slot + 24)).toBe(32);
+    expect(file.readUInt32LE(slot + 28)).toBe(16);
+    expect(file.subarray(slot + 32, slot + 36).toString()).toBe('NV12');
+    expect(file.readUInt32LE(slot + 48)).toBe(2);
+    expect(file.readUInt32LE(slot + 56)).toBe(32);
+    expect(file.readUInt32LE(slot + 76)).toBe(32 * 16);
+    expect(file.length).toBeGreaterThan(slot + SLOT_HEADER_SIZE);
+
+    kit.stopSharedFrames('sink');
+    expect(fs.existsSync(ring.path)).toBe(false);
+    kit.stop();
+    kit.cleanup();
+  });
+
+  it('should count frames too large for a slot as dropped', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc num-buffers=5 ! video/x-raw,format=RGBA,width=64,height=64 ! appsink name=sink');
+    const ring = kit.publishSharedFrames('sink', `gst-test-small-${process.pid}`, { maxFrameSize: 1024 });
+    kit.play();
+    await new Promise(r => setTimeout(r, 500));
+    const file = fs.readFileSync(ring.path);
+
+    expect(Number(file.readBigUInt64LE(24))).toBe(0);
+    expect(Number(file.readBigUInt64LE(32))).toBe(5);
+    kit.stop();
+    kit.cleanup();
+  });
+
+  it('should reject invalid names and unknown sinks', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! appsink name=sink');
+    expect(() => kit.publishSharedFrames('sink', '../escape')).toThrow();
+    expect(() => kit.publishSharedFrames('missing', 'ring')).toThrow();
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, probeWithGStreamer, renderSlate } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as path from 'node:path';
+
+function firstFrame(file: string) {
+  const kit = new GstKit();
+  kit.setPipeline(
+    `filesrc location="${file}" ! decodebin ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink sync=false`
+  );
+  kit.play();
+  const frame = kit.pullSampleAsImageData('sink', { timeoutMs: 5000 });
+  kit.stop();
+  kit.cleanup();
+  return frame!;
+}
+
+describe('renderSlate', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should encode a slate of the requested size and length', () => {
+    const output = path.join(TEST_DIR, 'slate.webm');
+    renderSlate(output, { text: 'Episode 12\nThe Return', durationSec: 2, resolution: '320x180', fps: 10 });
+
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(320);
+    expect(info.video[0].height).toBe(180);
+    expect(info.duration / 1e9).toBeCloseTo(2, 0);
+  });
+
+  it('should draw the text over the background color', () => {
+    const output = path.join(TEST_DIR, 'slate_colors.mkv');
+    renderSlate(output, {
+      text: 'HHHHHHHH',
+      background: '#0000ff',
+      color: 'white',
+      durationSec: 0.5,
+      resolution: '160x90',
+      fps: 10,
+    });
+
+    const frame = firstFrame(output);
+    const pixel = (x: number, y: number) => Array.from(frame.data.subarray((y * 160 + x) * 4, (y * 160 + x) * 4 + 3));
+    const [r, g, b] = pixel(2, 2);
+    expect(b).toBeGreaterThan(200);
+    expect(r + g).toBeLessThan(60);
+    // The middle of the frame crosses the bars of the H glyphs
+    const row = Array.from({ length: 160 }, (_, x) => pixel(x, 45));
+    expect(row.some(([r, g, b]) => r > 200 && g > 200 && b > 200)).toBe(true);
+  });
+
+  it('should reject invalid colors, sizes and containers', () => {
+    const output = path.join(TEST_DIR, 'slate_invalid.webm');
+    expect(() => renderSlate(output, { text: 'x', background: 'chartreuse-ish' })).toThrow(/Invalid color/);
+    expect(() => renderSlate(output, { text: 'x', resolution: '0x0' })).toThrow(/Invalid resolution/);
+    expect(() => renderSlate(path.join(TEST_DIR, 'slate'), { text: 'x' })).toThrow(/container/);
+  });
+});
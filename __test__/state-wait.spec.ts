@@ -0,0 +1,48 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit } from '../index.js';
+
+describe('state change await helpers', () => {
+  it('should resolve once the pipeline is playing or paused', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc is-live=true ! fakesink');
+    await kit.playAsync(5000);
+    expect(kit.getState()).toBe('Playing');
+    await kit.pauseAsync(5000);
+    expect(kit.getState()).toBe('Paused');
+    kit.cleanup();
+  });
+
+  it('should wait for a state without changing it', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! fakesink');
+    const playing = kit.waitForState('playing', 5000);
+    kit.play();
+    await playing;
+    expect(kit.getState()).toBe('Playing');
+    await expect(kit.waitForState('Ready', 100)).rejects.toThrow(/Timed out/);
+    expect(() => kit.waitForState('running')).toThrow('Unknown state: running');
+    kit.cleanup();
+  });
+
+  it('should reject with the bus error when the change fails', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('filesrc location=/nonexistent/file.mp4 ! fakesink');
+    await expect(kit.playAsync(5000)).rejects.toThrow(/Failed to set state to Playing/);
+    kit.cleanup();
+  });
+
+  it('should not report errors left by an earlier change', async () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! capsfilter name=f caps=video/x-raw,format=RGBA ! video/x-raw,format=I420 ! fakesink');
+    await expect(kit.playAsync(5000)).rejects.toThrow(/Failed to set state to Playing/);
+    kit.stop();
+    kit.setProperty('f', 'caps', 'video/x-raw,format=I420');
+    await kit.playAsync(5000);
+    expect(kit.getState()).toBe('Playing');
+    kit.cleanup();
+  });
+
+  it('should reject without a pipeline', () => {
+    expect(() => new GstKit().playAsync()).toThrow('Pipeline not initialized');
+  });
+});
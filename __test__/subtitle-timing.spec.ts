@@ -0,0 +1,104 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { extractClip, generateTestMedia, rescaleSubtitles, shiftSubtitles } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const SRT = `1
+00:00:00,500 --> 00:00:01,200
+First
+
+2
+00:00:01,500 --> 00:00:02,500
+<i>Second</i>
+
+3
+00:00:02,800 --> 00:00:03,000
+Third
+`;
+
+const VTT = `WEBVTT
+
+NOTE Timed for the director's cut
+
+intro
+00:01.000 --> 00:02.000 align:start line:0
+Hello
+`;
+
+describe('subtitle timing', () => {
+  const srtFile = path.join(TEST_DIR, 'movie.en.srt');
+  const vttFile = path.join(TEST_DIR, 'movie.vtt');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    fs.writeFileSync(srtFile, SRT);
+    fs.writeFileSync(vttFile, VTT);
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should shift SubRip cues and renumber them', () => {
+    const shifted = shiftSubtitles(srtFile, -1300);
+    expect(shifted).toBe(`1
+00:00:00,200 --> 00:00:01,200
+<i>Second</i>
+
+2
+00:00:01,500 --> 00:00:01,700
+Third
+`);
+    // The input is left alone without an output path
+    expect(fs.readFileSync(srtFile, 'utf8')).toBe(SRT);
+  });
+
+  it('should clamp cues shifted partly before zero', () => {
+    expect(shiftSubtitles(srtFile, -1000)).toContain('00:00:00,000 --> 00:00:00,200\nFirst');
+  });
+
+  it('should keep WebVTT headers, notes, identifiers and settings', () => {
+    const output = path.join(TEST_DIR, 'shifted.vtt');
+    shiftSubtitles(vttFile, 61_500, output);
+    expect(fs.readFileSync(output, 'utf8')).toBe(`WEBVTT
+
+NOTE Timed for the director's cut
+
+intro
+00:01:02.500 --> 00:01:03.500 align:start line:0
+Hello
+`);
+  });
+
+  it('should rescale the timeline', () => {
+    const rescaled = rescaleSubtitles(srtFile, 2);
+    expect(rescaled).toContain('00:00:01,000 --> 00:00:02,400');
+    expect(rescaled).toContain('00:00:05,600 --> 00:00:06,000');
+  });
+
+  it('should reject invalid arguments and files', () => {
+    expect(() => rescaleSubtitles(srtFile, 0)).toThrow();
+    expect(() => shiftSubtitles(srtFile, Number.NaN)).toThrow();
+    const broken = path.join(TEST_DIR, 'broken.srt');
+    fs.writeFileSync(broken, '1\n00:00:xx,000 --> 00:00:01,000\nText\n');
+    expect(() => shiftSubtitles(broken, 100)).toThrow(/line 2/);
+  });
+
+  it('should trim sidecar subtitles to an extracted clip', () => {
+    const input = path.join(TEST_DIR, 'movie.mkv');
+    generateTestMedia(input, { format: 'mkv', pattern: 'smpte', width: 160, height: 120, fps: 10, duration: 3 });
+    const clip = path.join(TEST_DIR, 'clip.mkv');
+    extractClip(input, clip, 1000, 2000, { mode: 'reencode', subtitles: [srtFile, vttFile] });
+
+    expect(fs.readFileSync(path.join(TEST_DIR, 'clip.en.srt'), 'utf8')).toBe(`1
+00:00:00,000 --> 00:00:00,200
+First
+
+2
+00:00:00,500 --> 00:00:01,000
+<i>Second</i>
+`);
+    expect(fs.readFileSync(path.join(TEST_DIR, 'clip.vtt'), 'utf8')).toContain('00:00:00.000 --> 00:00:01.000');
+  });
+});
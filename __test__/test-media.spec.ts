@@ -0,0 +1,53 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { generateTestMedia } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('generateTestMedia', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  const cases = [
+    { format: 'webm', pattern: 'smpte', file: 'media_smpte.webm' },
+    { format: 'avi', pattern: 'gradient', file: 'media_gradient.avi' },
+    { format: 'y4m', pattern: 'noise', file: 'media_noise.y4m' },
+  ];
+
+  it.each(cases)('should write a $pattern $format clip', ({ format, pattern, file }) => {
+    const outputFile = path.join(TEST_DIR, file);
+    generateTestMedia(outputFile, { format, pattern, width: 160, height: 120, fps: 10, duration: 0.5 });
+
+    expect(fs.existsSync(outputFile)).toBe(true);
+    expect(fs.statSync(outputFile).size).toBeGreaterThan(100);
+  });
+
+  it('should write a valid y4m header', () => {
+    const outputFile = path.join(TEST_DIR, 'media_header.y4m');
+    generateTestMedia(outputFile, { format: 'y4m', width: 64, height: 48, duration: 0.1 });
+
+    const header = fs.readFileSync(outputFile).subarray(0, 32).toString('ascii');
+    expect(header.startsWith('YUV4MPEG2 W64 H48')).toBe(true);
+  });
+
+  it('should include an audio track when requested', () => {
+    const outputFile = path.join(TEST_DIR, 'media_audio.webm');
+    generateTestMedia(outputFile, { format: 'webm', duration: 0.5, audio: true });
+    expect(fs.statSync(outputFile).size).toBeGreaterThan(100);
+  });
+
+  it('should throw on unsupported formats', () => {
+    expect(() => generateTestMedia(path.join(TEST_DIR, 'bad.xyz'), { format: 'xyz' })).toThrow();
+  });
+
+  it('should reject unknown patterns', () => {
+    expect(() =>
+      generateTestMedia(path.join(TEST_DIR, 'bad_pattern.y4m'), { format: 'y4m', pattern: 'smpte ! fakesink' }),
+    ).toThrow('Unknown test pattern');
+  });
+});
@@ -0,0 +1,96 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, encodeKlv, parseKlv, injectSei, extractSei } from '../index.js';
+
+const KEY = Buffer.from('060e2b34020b01010e01030101000000', 'hex');
+
+describe('KLV packets', () => {
+  it('should round-trip short and long values', () => {
+    const data = Buffer.concat([encodeKlv(KEY, Buffer.from([1, 2, 3])), encodeKlv(KEY, Buffer.alloc(300, 7))]);
+    const packets = parseKlv(data);
+
+    expect(packets).toHaveLength(2);
+    expect([...packets[0].value]).toEqual([1, 2, 3]);
+    expect(packets[1].value.length).toBe(300);
+    expect(packets[1].key.equals(KEY)).toBe(true);
+  });
+
+  it('should reject keys that are not 16 bytes and truncated packets', () => {
+    expect(() => encodeKlv(Buffer.alloc(4), Buffer.alloc(1))).toThrow('16 bytes');
+    expect(() => parseKlv(encodeKlv(KEY, Buffer.alloc(10)).subarray(0, 20))).toThrow('Truncated');
+  });
+
+  it('should pass pushed metadata to an AppSink with its timestamp', () => {
+    const kit = new GstKit();
+    kit.setPipeline('appsrc name=klv format=time caps=meta/x-klv,parsed=true ! queue ! appsink name=out sync=false');
+    kit.play();
+    kit.pushTimedMetadata('klv', encodeKlv(KEY, Buffer.from('fix')), 40_000_000);
+    const metadata = kit.pullTimedMetadata('out', 2000)!;
+    kit.cleanup();
+
+    expect(metadata.timestamp).toBe(40_000_000);
+    expect(parseKlv(metadata.data)[0].value.toString()).toBe('fix');
+    expect(metadata.uuid).toBeUndefined();
+  });
+});
+
+describe('SEI user data', () => {
+  it('should carry metadata from an encoder to a parser', () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'videotestsrc num-buffers=10 ! video/x-raw,width=64,height=64 ! x264enc name=enc ! h264parse name=parse ! fakesink',
+    );
+    const injector = injectSei(kit, 'enc');
+    const extractor = extractSei(kit, 'parse');
+    injector.push(JSON.stringify({ lat: 48.85 }));
+    kit.play();
+    const metadata = extractor.next(5000)!;
+    kit.cleanup();
+
+    expect(JSON.parse(metadata.data.toString())).toEqual({ lat: 48.85 });
+    expect(metadata.uuid).toBe('6773746b-6974-2d6d-6574-616461746131');
+    expect(injector.injected).toBe(1);
+    expect(injector.pending).toBe(0);
+  });
+
+  it('should hold timestamped metadata until its frame', () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'videotestsrc num-buffers=10 ! video/x-raw,width=64,height=64,framerate=10/1 ! x264enc name=enc ! h264parse name=parse ! fakesink',
+    );
+    const injector = injectSei(kit, 'enc');
+    const extractor = extractSei(kit, 'parse');
+    injector.push('late', 500_000_000);
+    injector.push('never', 5_000_000_000);
+    kit.play();
+    const metadata = extractor.next(5000)!;
+    const next = extractor.next(1000);
+    kit.cleanup();
+
+    expect(metadata.data.toString()).toBe('late');
+    expect(metadata.timestamp).toBeGreaterThanOrEqual(500_000_000);
+    expect(next).toBeNull();
+    expect(injector.pending).toBe(1);
+  });
+
+  it('should filter messages by UUID', () => {
+    const kit = new GstKit();
+    kit.setPipeline(
+      'videotestsrc num-buffers=10 ! video/x-raw,width=64,height=64 ! x264enc name=enc ! h264parse name=parse ! fakesink',
+    );
+    const injector = injectSei(kit, 'enc', { uuid: '00112233-4455-6677-8899-aabbccddeeff' });
+    const extractor = extractSei(kit, 'parse', { uuid: 'ffeeddcc-bbaa-9988-7766-554433221100' });
+    injector.push('ignored');
+    kit.play();
+    const metadata = extractor.next(1000);
+    kit.cleanup();
+
+    expect(metadata).toBeNull();
+  });
+
+  it('should reject malformed UUIDs', () => {
+    const kit = new GstKit();
+    kit.setPipeline('videotestsrc ! x264enc name=enc ! fakesink');
+    expect(() => injectSei(kit, 'enc', { uuid: 'not-a-uuid' })).toThrow('Invalid UUID');
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,117 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { TranscodeJob, type TranscodeProgress } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const waitFor = async (job: TranscodeJob, timeoutMs = 10000) => {
+  const deadline = Date.now() + timeoutMs;
+  while (!job.isFinished() && Date.now() < deadline) {
+    await new Promise(r => setTimeout(r, 50));
+  }
+};
+
+describe('TranscodeJob', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('job_input.avi', 'smpte', { numBuffers: 30 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should transcode a file and report progress', async () => {
+    const output = path.join(TEST_DIR, 'job_output.webm');
+    const job = new TranscodeJob(inputFile, output, { container: 'webm', audioCodec: 'none' });
+    const updates: TranscodeProgress[] = [];
+    job.onProgress(progress => updates.push(progress));
+    expect(job.getProgress().state).toBe('pending');
+
+    job.start();
+    await waitFor(job);
+    await new Promise(r => setTimeout(r, 50));
+
+    const progress = job.getProgress();
+    expect(progress.state).toBe('done');
+    expect(progress.percent).toBe(100);
+    expect(updates.at(-1)?.state).toBe('done');
+    expect(progress.framesProcessed).toBe(30);
+    expect(progress.etaSeconds).toBe(0);
+    expect(updates.some(update => update.speed > 0)).toBe(true);
+    const data = fs.readFileSync(output);
+    expect(data.subarray(0, 4).equals(Buffer.from([0x1a, 0x45, 0xdf, 0xa3]))).toBe(true);
+    expect(fs.existsSync(`${output}.tmp`)).toBe(false);
+  });
+
+  it('should cancel a running job and delete its output', () => {
+    const output = path.join(TEST_DIR, 'job_cancelled.webm');
+    const job = new TranscodeJob(inputFile, output, {
+      container: 'webm',
+      audioCodec: 'none',
+    });
+    job.start();
+    expect(job.cancel()).toBe(true);
+    expect(job.getProgress().state).toBe('cancelled');
+    expect(job.isFinished()).toBe(true);
+    expect(job.cancel()).toBe(false);
+    expect(fs.existsSync(output)).toBe(false);
+    expect(fs.existsSync(`${output}.tmp`)).toBe(false);
+  });
+
+  it('should not start once cancelled', async () => {
+    const output = path.join(TEST_DIR, 'job_cancelled_first.webm');
+    const job = new TranscodeJob(inputFile, output, { container: 'webm', audioCodec: 'none' });
+    expect(job.cancel()).toBe(true);
+    expect(() => job.start()).toThrow('Cannot start a cancelled transcode job');
+    await new Promise(r => setTimeout(r, 500));
+    expect(job.getProgress().state).toBe('cancelled');
+    expect(fs.existsSync(`${output}.tmp`)).toBe(false);
+  });
+
+  it('should refuse to overwrite an existing output by default', () => {
+    const output = path.join(TEST_DIR, 'job_existing.webm');
+    fs.writeFileSync(output, 'keep');
+    expect(() => new TranscodeJob(inputFile, output, { container: 'webm' })).toThrow('already exists');
+    expect(() => new TranscodeJob(inputFile, output, { container: 'webm', overwrite: 'skip' })).toThrow(
+      'Unsupported overwrite policy',
+    );
+    expect(new TranscodeJob(inputFile, output, { container: 'webm', overwrite: 'replace' }).outputPath).toBe(output);
+  });
+
+  it('should write next to an existing output in rename mode', async () => {
+    const output = path.join(TEST_DIR, 'job_renamed.webm');
+    fs.writeFileSync(output, 'keep');
+    const job = new TranscodeJob(inputFile, output, { container: 'webm', audioCodec: 'none', overwrite: 'rename' });
+    expect(job.outputPath).toBe(path.join(TEST_DIR, 'job_renamed (1).webm'));
+    job.start();
+    await waitFor(job);
+
+    expect(job.getProgress().state).toBe('done');
+    expect(fs.readFileSync(output, 'utf8')).toBe('keep');
+    expect(fs.existsSync(job.outputPath)).toBe(true);
+  });
+
+  it('should report failures', async () => {
+    const job = new TranscodeJob(path.join(TEST_DIR, 'missing.avi'), path.join(TEST_DIR, 'never.webm'), {
+      container: 'webm',
+    });
+    try {
+      job.start();
+    } catch {
+      return;
+    }
+    await waitFor(job);
+    expect(job.getProgress().state).toBe('failed');
+    expect(job.getProgress().error).toBeDefined();
+  });
+
+  it('should not start twice', () => {
+    const job = new TranscodeJob(inputFile, path.join(TEST_DIR, 'job_twice.webm'), { container: 'webm' });
+    job.start();
+    expect(() => job.start()).toThrow();
+    job.cancel();
+  });
+});
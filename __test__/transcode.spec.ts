@@ -0,0 +1,552 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, TranscodeStream, probeWithGStreamer, transcodeBuffer, transcodeBufferWithReport } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo, generateTestVideoWithAudio } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+const EBML_MAGIC = Buffer.from([0x1a, 0x45, 0xdf, 0xa3]);
+
+// Decodes the first frame of a file as RGBA
+function firstFrame(file: string) {
+  const kit = new GstKit();
+  kit.setPipeline(
+    `filesrc location="${file}" ! decodebin ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink sync=false`
+  );
+  kit.play();
+  const frame = kit.pullSampleAsImageData('sink', { timeoutMs: 5000 });
+  kit.stop();
+  kit.cleanup();
+  return frame!;
+}
+
+describe('TranscodeStream', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('transcode_input.avi', 'smpte', { numBuffers: 15 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should throw on unsupported container', () => {
+    expect(() => new TranscodeStream({ container: 'nope' })).toThrow();
+  });
+
+  it('should throw on unsupported codec', () => {
+    expect(() => new TranscodeStream({ container: 'webm', videoCodec: 'nope' })).toThrow();
+  });
+
+  it('should transcode written chunks to webm', async () => {
+    const stream = new TranscodeStream({ container: 'webm', videoCodec: 'vp8', audioCodec: 'none' });
+
+    const input = fs.readFileSync(inputFile);
+    for (let offset = 0; offset < input.length; offset += 4096) {
+      stream.write(input.subarray(offset, offset + 4096));
+    }
+    stream.end();
+
+    const chunks: Buffer[] = [];
+    const deadline = Date.now() + 10000;
+    while (!stream.isFinished() && Date.now() < deadline) {
+      const chunk = stream.read(100);
+      if (chunk) {
+        chunks.push(chunk);
+      }
+    }
+
+    const output = Buffer.concat(chunks);
+    expect(output.length).toBeGreaterThan(0);
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+  });
+
+  it('should deliver chunks to the data callback', async () => {
+    const stream = new TranscodeStream({ container: 'webm', audioCodec: 'none' });
+    const chunks: Buffer[] = [];
+    stream.onData(chunk => chunks.push(chunk));
+
+    stream.write(fs.readFileSync(inputFile));
+    stream.end();
+
+    await new Promise(resolve => setTimeout(resolve, 3000));
+    expect(chunks.length).toBeGreaterThan(0);
+  });
+});
+
+describe('transcodeBuffer', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('buffer_input.avi', 'ball', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should transcode a buffer to webm in memory', () => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', audioCodec: 'none' });
+    expect(output.length).toBeGreaterThan(0);
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+  });
+
+  it('should copy the video stream without re-encoding', () => {
+    const input = fs.readFileSync(inputFile);
+    const output = transcodeBuffer(input, { container: 'mkv', videoCodec: 'copy', audioCodec: 'none' });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+    expect(output.includes(Buffer.from('V_MJPEG'))).toBe(true);
+  });
+
+  it('should resample to a constant frame rate', () => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', audioCodec: 'none', frameRate: 10 });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+  });
+
+  it('should reject a frame rate when copying video', () => {
+    expect(() =>
+      transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', videoCodec: 'copy', frameRate: 10 })
+    ).toThrow();
+  });
+
+  it('should accept regions of interest', () => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), {
+      container: 'webm',
+      audioCodec: 'none',
+      regions: [{ x: 0, y: 0, width: 64, height: 64, deltaQp: -10 }],
+    });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+  });
+
+  it.each(['webm', 'ogg'])('should produce identical %s output in deterministic mode', container => {
+    const input = fs.readFileSync(inputFile);
+    const options = { container, audioCodec: 'none', deterministic: true };
+    const first = transcodeBuffer(input, options);
+    const second = transcodeBuffer(input, options);
+    expect(first.length).toBeGreaterThan(0);
+    expect(first.equals(second)).toBe(true);
+  });
+
+  it('should throw on data that is not media', () => {
+    expect(() => transcodeBuffer(Buffer.from('not a video'), { container: 'webm' })).toThrow();
+  });
+
+  it('should write a complete, seekable mp4', () => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), { container: 'mp4', audioCodec: 'none' });
+    const file = path.join(TEST_DIR, 'buffer_output.mp4');
+    fs.writeFileSync(file, output);
+    const info = probeWithGStreamer(file);
+
+    expect(output.includes(Buffer.from('moov'))).toBe(true);
+    expect(output.includes(Buffer.from('moof'))).toBe(false);
+    expect(info.duration / 1e9).toBeCloseTo(10 / 30, 1);
+    expect(info.seekable).toBe(true);
+  });
+
+  it('should give up after the timeout', () => {
+    expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', audioCodec: 'none' }, 1)).toThrow(
+      'did not finish within 1 ms',
+    );
+  });
+});
+
+describe('frame interpolation', () => {
+  let inputFile: string;
+
+  const outputDuration = (name: string, output: Buffer) => {
+    const file = path.join(TEST_DIR, name);
+    fs.writeFileSync(file, output);
+    return probeWithGStreamer(file).duration / 1e9;
+  };
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('interpolate_input.avi', 'ball', { numBuffers: 15 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it.each(['duplicate', 'blend', 'motion'])('should double the frame rate in %s mode', interpolation => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), {
+      container: 'mkv',
+      audioCodec: 'none',
+      frameRate: 60,
+      interpolation,
+    });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+    expect(outputDuration(`interpolate_${interpolation}.mkv`, output)).toBeCloseTo(0.5, 1);
+  });
+
+  it('should stretch the video for slow motion', () => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), {
+      container: 'mkv',
+      audioCodec: 'none',
+      slowMotion: 2,
+      interpolation: 'motion',
+    });
+    expect(outputDuration('interpolate_slow.mkv', output)).toBeCloseTo(1, 1);
+  });
+
+  it('should reject invalid interpolation settings', () => {
+    const input = fs.readFileSync(inputFile);
+    expect(() => transcodeBuffer(input, { container: 'mkv', audioCodec: 'none', interpolation: 'optical' })).toThrow();
+    expect(() => transcodeBuffer(input, { container: 'mkv', audioCodec: 'none', slowMotion: 0 })).toThrow();
+    expect(() => transcodeBuffer(input, { container: 'mkv', audioCodec: 'none', slowMotion: 2, speed: 0.5 })).toThrow();
+    expect(() => transcodeBuffer(input, { container: 'mkv', videoCodec: 'copy', interpolation: 'blend' })).toThrow();
+  });
+});
+
+describe('speed', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideoWithAudio('speed_input.avi', 'ball', 'sine', { numBuffers: 30 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it.each([2, 0.5])('should scale video and audio duration at speed %d', speed => {
+    const output = transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', speed });
+    const file = path.join(TEST_DIR, `speed_${speed}.mkv`);
+    fs.writeFileSync(file, output);
+    const info = probeWithGStreamer(file);
+    expect(info.video.length).toBe(1);
+    expect(info.audio.length).toBe(1);
+    expect(info.duration / 1e9).toBeCloseTo(1 / speed, 1);
+  });
+
+  it('should reject invalid speeds and copied streams', () => {
+    const input = fs.readFileSync(inputFile);
+    expect(() => transcodeBuffer(input, { container: 'mkv', speed: 0 })).toThrow();
+    expect(() => transcodeBuffer(input, { container: 'mkv', speed: -1 })).toThrow();
+    expect(() => transcodeBuffer(input, { container: 'mkv', audioCodec: 'copy', speed: 2 })).toThrow();
+  });
+});
+
+describe('audioFilters', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideoWithAudio('filters_input.avi', 'smpte', 'sine', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should resample, downmix, amplify and fade the audio stream', () => {
+    const { output, report } = transcodeBufferWithReport(fs.readFileSync(inputFile), {
+      container: 'webm',
+      audioFilters: ['aresample=48000', 'channels=mono', 'gain=-6dB', 'fadein=0.1', 'fadeout=0.2:0.1'],
+    });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+    const audio = report.streams.find(stream => stream.media === 'audio');
+    expect(audio?.filters).toContain('volume');
+    expect(audio?.outputCaps).toContain('channels=(int)1');
+    expect(audio?.outputCaps).toContain('rate=(int)48000');
+  });
+
+  it.each(['channels=surround', 'gain=+30', 'fadeout=2', 'reverb=1'])('should reject the filter %s', filter => {
+    expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', audioFilters: [filter] })).toThrow();
+  });
+
+  it('should reject audio filters when copying audio', () => {
+    expect(() =>
+      transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', audioCodec: 'copy', audioFilters: ['gain=3'] })
+    ).toThrow();
+  });
+});
+
+describe('videoFilters', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('video_filters_input.avi', 'smpte', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should key the video stream before encoding', () => {
+    const { output, report } = transcodeBufferWithReport(fs.readFileSync(inputFile), {
+      container: 'webm',
+      videoFilters: ['chromakey=0x00FF00:0.1:0.05'],
+    });
+    expect(output.subarray(0, 4).equals(EBML_MAGIC)).toBe(true);
+    const video = report.streams.find(stream => stream.media === 'video');
+    expect(video?.filters.some(filter => filter.includes('AYUV'))).toBe(true);
+  });
+
+  it.each(['chromakey=chartreuse-ish', 'chromakey=#00FF00:2', 'chromakey=#00FF00:0.1:0.1:1', 'lutrgb=1'])(
+    'should reject the filter %s',
+    filter => {
+      expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', videoFilters: [filter] })).toThrow();
+    }
+  );
+
+  it('should reject video filters when copying video', () => {
+    expect(() =>
+      transcodeBuffer(fs.readFileSync(inputFile), {
+        container: 'mkv',
+        videoCodec: 'copy',
+        videoFilters: ['chromakey=#00FF00'],
+      })
+    ).toThrow();
+  });
+});
+
+describe('lut3d', () => {
+  let inputFile: string;
+  let invertLut: string;
+
+  // A 2x2x2 LUT is exact for a linear mapping such as inversion
+  const cube = (size: number, entry: (r: number, g: number, b: number) => number[], header = '') => {
+    const lines = [`TITLE "test"`, `LUT_3D_SIZE ${size}`, header];
+    for (let b = 0; b < size; b++)
+      for (let g = 0; g < size; g++)
+        for (let r = 0; r < size; r++) lines.push(entry(r / (size - 1), g / (size - 1), b / (size - 1)).join(' '));
+    return lines.join('\n');
+  };
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('lut_input.avi', 'red', { numBuffers: 10 });
+    invertLut = path.join(TEST_DIR, 'invert.cube');
+    fs.writeFileSync(invertLut, cube(2, (r, g, b) => [1 - r, 1 - g, 1 - b]));
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should grade the video through the LUT', () => {
+    const output = path.join(TEST_DIR, 'lut_output.mkv');
+    fs.writeFileSync(
+      output,
+      transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', videoFilters: [`lut3d=${invertLut}`] })
+    );
+
+    const frame = firstFrame(output);
+    // Red inverts to cyan
+    const [r, g, b] = Array.from(frame.data.subarray(0, 3));
+    expect(r).toBeLessThan(40);
+    expect(g).toBeGreaterThan(200);
+    expect(b).toBeGreaterThan(200);
+  });
+
+  it('should reject malformed LUT files', () => {
+    const write = (name: string, text: string) => {
+      const file = path.join(TEST_DIR, name);
+      fs.writeFileSync(file, text);
+      return `lut3d=${file}`;
+    };
+    const filters = [
+      write('short.cube', 'LUT_3D_SIZE 2\n0 0 0\n1 1 1\n'),
+      write('nosize.cube', '0 0 0\n'),
+      write('oned.cube', 'LUT_1D_SIZE 2\n0 0 0\n1 1 1\n'),
+      write('words.cube', cube(2, () => [0, 0, 0]).replace('0 0 0', 'zero 0 0')),
+      `lut3d=${path.join(TEST_DIR, 'missing.cube')}`,
+    ];
+    for (const filter of filters) {
+      expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', videoFilters: [filter] })).toThrow();
+    }
+  });
+});
+
+describe('boxblur and pixelate', () => {
+  let inputFile: string;
+
+  // Largest difference between neighbouring pixels of a row, in the red channel
+  const contrast = (frame: { data: Uint8ClampedArray; width: number }, y: number, x0: number, x1: number) => {
+    let max = 0;
+    for (let x = x0 + 1; x < x1; x++) {
+      max = Math.max(max, Math.abs(frame.data[(y * frame.width + x) * 4] - frame.data[(y * frame.width + x - 1) * 4]));
+    }
+    return max;
+  };
+
+  const transcodeToFile = (name: string, videoFilters: string[]) => {
+    const output = path.join(TEST_DIR, name);
+    fs.writeFileSync(output, transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', videoFilters }));
+    return firstFrame(output);
+  };
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    // 8 pixel black and white checkers, 320x240
+    inputFile = await generateTestVideo('mask_input.avi', 'checkers-8', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should pixelate and blur the whole frame', () => {
+    for (const filter of ['pixelate=16', 'boxblur=8']) {
+      const frame = transcodeToFile('mask_whole.mkv', [filter]);
+      expect(contrast(frame, 100, 0, 320)).toBeLessThan(60);
+    }
+  });
+
+  it('should only filter inside the regions', () => {
+    const frame = transcodeToFile('mask_region.mkv', ['pixelate=16:0,0,160,240']);
+    expect(contrast(frame, 100, 0, 150)).toBeLessThan(60);
+    expect(contrast(frame, 100, 170, 320)).toBeGreaterThan(150);
+  });
+
+  it('should only filter regions active at the frame time', () => {
+    const frame = transcodeToFile('mask_later.mkv', ['boxblur=8:0,0,320,240@1-2']);
+    expect(contrast(frame, 100, 0, 320)).toBeGreaterThan(150);
+  });
+
+  it('should move regions between keyframes', () => {
+    const frame = transcodeToFile('mask_moving.mkv', ['pixelate=16:0,0,64,240@0>256,0,64,240@1']);
+    expect(contrast(frame, 100, 0, 60)).toBeLessThan(60);
+    expect(contrast(frame, 100, 80, 320)).toBeGreaterThan(150);
+  });
+
+  it.each(['pixelate=1', 'boxblur=0', 'boxblur=4:0,0,10', 'pixelate=8:0,0,10,10@2-1', 'pixelate=8:0,0,8,8@1>8,8,8,8@0'])(
+    'should reject the filter %s',
+    filter => {
+      expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', videoFilters: [filter] })).toThrow();
+    }
+  );
+});
+
+describe('subtitles', () => {
+  let inputFile: string;
+
+  const writeSrt = (name: string, text: string) => {
+    const file = path.join(TEST_DIR, name);
+    fs.writeFileSync(file, text);
+    return file;
+  };
+
+  // Rows of the first frame containing near-white pixels
+  const textRows = (name: string, srt: string) => {
+    const output = path.join(TEST_DIR, name);
+    fs.writeFileSync(
+      output,
+      transcodeBuffer(fs.readFileSync(inputFile), { container: 'mkv', videoFilters: [`subtitles=${srt}`] })
+    );
+    const frame = firstFrame(output);
+    const rows = new Set<number>();
+    for (let i = 0; i < frame.data.length; i += 4) {
+      if (frame.data[i] > 200 && frame.data[i + 1] > 200 && frame.data[i + 2] > 200) rows.add(Math.floor(i / 4 / frame.width));
+    }
+    return [...rows];
+  };
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('subtitles_input.avi', 'black', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should burn in the cue shown at the frame time at the bottom', () => {
+    const srt = writeSrt('bottom.srt', '1\n00:00:00,000 --> 00:00:01,000\n<i>HELLO WORLD</i>\n\n2\n00:00:05,000 --> 00:00:06,000\nLater\n');
+    const rows = textRows('subtitles_bottom.mkv', srt);
+    expect(rows.length).toBeGreaterThan(0);
+    expect(Math.min(...rows)).toBeGreaterThan(160);
+  });
+
+  it('should place cues with a position tag', () => {
+    const srt = writeSrt('top.srt', '00:00:00,000 --> 00:00:01,000\n{\\an8}HELLO\n');
+    const rows = textRows('subtitles_top.mkv', srt);
+    expect(rows.length).toBeGreaterThan(0);
+    expect(Math.max(...rows)).toBeLessThan(80);
+  });
+
+  it('should not draw cues outside their time range', () => {
+    const srt = writeSrt('later.srt', '1\n00:00:01,000 --> 00:00:02,000\nHELLO\n');
+    expect(textRows('subtitles_later.mkv', srt).length).toBe(0);
+  });
+
+  it('should reject malformed subtitle files', () => {
+    const filters = [
+      `subtitles=${writeSrt('bad_time.srt', '1\n00:00:xx,000 --> 00:00:01,000\nHELLO\n')}`,
+      `subtitles=${writeSrt('no_arrow.srt', '1\n00:00:00,000 00:00:01,000\nHELLO\n')}`,
+      `subtitles=${path.join(TEST_DIR, 'missing.srt')}`,
+    ];
+    for (const filter of filters) {
+      expect(() => transcodeBuffer(fs.readFileSync(inputFile), { container: 'webm', videoFilters: [filter] })).toThrow();
+    }
+  });
+});
+
+describe('transcodeBufferWithReport', () => {
+  let inputFile: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    inputFile = await generateTestVideo('report_input.avi', 'smpte', { numBuffers: 10 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should describe the encoded streams', () => {
+    const input = fs.readFileSync(inputFile);
+    const { output, report } = transcodeBufferWithReport(input, {
+      container: 'webm',
+      audioCodec: 'none',
+      videoBitrate: 500,
+      frameRate: 10,
+    });
+
+    expect(report.container).toBe('webm');
+    expect(report.muxer).toBe('webmmux');
+    expect(report.inputSize).toBe(input.length);
+    expect(report.outputSize).toBe(output.length);
+    expect(report.videoFrames).toBe(10);
+    expect(report.wallTimeMs).toBeGreaterThan(0);
+    expect(report.averageFps).toBeGreaterThan(0);
+
+    expect(report.streams.length).toBe(1);
+    const video = report.streams[0];
+    expect(video.media).toBe('video');
+    expect(video.encoder).toBe('vp8enc');
+    expect(video.inputCaps).toContain('video/x-raw');
+    expect(video.outputCaps).toContain('video/x-vp8');
+    expect(video.filters).toContain('videoconvert');
+    expect(video.filters).toContain('videorate');
+    expect(video.settings).toContainEqual({ name: 'target-bitrate', value: '500000' });
+  });
+
+  it('should report copied streams without an encoder', () => {
+    const { report } = transcodeBufferWithReport(fs.readFileSync(inputFile), {
+      container: 'mkv',
+      videoCodec: 'copy',
+      audioCodec: 'none',
+    });
+    expect(report.streams[0].encoder).toBeNull();
+    expect(report.streams[0].inputCaps).toContain('image/jpeg');
+  });
+
+  it('should write the report as JSON', () => {
+    const reportPath = path.join(TEST_DIR, 'report.json');
+    const { report } = transcodeBufferWithReport(
+      fs.readFileSync(inputFile),
+      { container: 'webm', audioCodec: 'none' },
+      reportPath
+    );
+    const written = JSON.parse(fs.readFileSync(reportPath, 'utf8'));
+    expect(written.container).toBe('webm');
+    expect(written.streams[0].encoder).toBe(report.streams[0].encoder);
+    expect(written.outputSize).toBe(report.outputSize);
+  });
+});
@@ -0,0 +1,109 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, probeWithGStreamer, watchTriggers, type TriggerEvent } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+/** A pipeline of 30 frames of `pattern` ending in an AppSink named "sink" */
+function pipeline(pattern: string): string {
+  return `videotestsrc pattern=${pattern} num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! appsink name=sink`;
+}
+
+/** Runs `kit` long enough for its frames to reach the sink */
+async function run(kit: GstKit): Promise<void> {
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 2000));
+}
+
+/** Lets the queued event callbacks run */
+async function settle(): Promise<void> {
+  await new Promise(resolve => setTimeout(resolve, 200));
+}
+
+describe('watchTriggers', () => {
+  beforeAll(() => {
+    setup.setupTestDirectories();
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should record while there is motion', async () => {
+    const outputDir = path.join(TEST_DIR, 'motion');
+    const kit = new GstKit();
+    kit.setPipeline(pipeline('ball'));
+    const watcher = watchTriggers(kit, 'sink', {
+      outputDir,
+      rules: [{ when: 'motion', action: 'record', threshold: 0.001, stopAfterIdle: 10 }],
+    });
+    const events: TriggerEvent[] = [];
+    watcher.onEvent(event => events.push(event));
+    await run(kit);
+    expect(watcher.recording).toBe(true);
+    watcher.stop();
+    await settle();
+    kit.cleanup();
+
+    expect(events.map(event => event.eventType)).toEqual(['recording-started', 'recording-stopped']);
+    const file = path.join(outputDir, 'recording-0001.webm');
+    expect(events[1].path).toBe(file);
+    expect(probeWithGStreamer(file).video[0].codec.toLowerCase()).toContain('vp8');
+  });
+
+  it('should save a snapshot on scene change and respect the cooldown', async () => {
+    const outputDir = path.join(TEST_DIR, 'scenes');
+    const kit = new GstKit();
+    kit.setPipeline(pipeline('snow'));
+    const watcher = watchTriggers(kit, 'sink', {
+      outputDir,
+      rules: [{ when: 'sceneChange', action: 'snapshot', threshold: 0, cooldown: 10 }],
+    });
+    const events: TriggerEvent[] = [];
+    watcher.onEvent(event => events.push(event));
+    await run(kit);
+    watcher.stop();
+    await settle();
+    kit.cleanup();
+
+    expect(events.map(event => event.eventType)).toEqual(['snapshot']);
+    const file = path.join(outputDir, 'snapshot-0001.jpg');
+    expect(events[0].path).toBe(file);
+    expect(events[0].score).toBeGreaterThan(0);
+    expect([...fs.readFileSync(file).subarray(0, 2)]).toEqual([0xff, 0xd8]);
+  });
+
+  it('should not fire on a still picture', async () => {
+    const outputDir = path.join(TEST_DIR, 'still');
+    const kit = new GstKit();
+    kit.setPipeline(pipeline('smpte'));
+    const watcher = watchTriggers(kit, 'sink', {
+      outputDir,
+      rules: [
+        { when: 'motion', action: 'record' },
+        { when: 'sceneChange', action: 'snapshot' },
+      ],
+    });
+    const events: TriggerEvent[] = [];
+    watcher.onEvent(event => events.push(event));
+    await run(kit);
+    watcher.stop();
+    await settle();
+    kit.cleanup();
+
+    expect(events).toEqual([]);
+    expect(fs.readdirSync(outputDir)).toEqual([]);
+  });
+
+  it('should reject invalid rules', () => {
+    const kit = new GstKit();
+    kit.setPipeline(pipeline('ball'));
+    const outputDir = path.join(TEST_DIR, 'invalid');
+    expect(() => watchTriggers(kit, 'sink', { outputDir, rules: [] })).toThrow();
+    expect(() => watchTriggers(kit, 'sink', { outputDir, rules: [{ when: 'sound', action: 'record' }] })).toThrow();
+    expect(() => watchTriggers(kit, 'sink', { outputDir, rules: [{ when: 'motion', action: 'email' }] })).toThrow();
+    expect(() => watchTriggers(kit, 'sink', { outputDir, rules: [{ when: 'motion', action: 'record', cooldown: -1 }] })).toThrow();
+    expect(() => watchTriggers(kit, 'nope', { outputDir, rules: [{ when: 'motion', action: 'record' }] })).toThrow();
+    kit.cleanup();
+  });
+});
@@ -0,0 +1,28 @@
+import { describe, it, expect } from 'bun:test';
+import { GstKit, publishVirtualCamera } from '../index.js';
+
+const PIPELINE = 'videotestsrc num-buffers=30 ! video/x-raw,width=320,height=240,framerate=30/1 ! appsink name=sink';
+
+describe('publishVirtualCamera', () => {
+  it('should publish every frame of the sink through a custom sink', async () => {
+    const kit = new GstKit();
+    kit.setPipeline(PIPELINE);
+    const camera = publishVirtualCamera(kit, 'sink', 'Test Cam', { sink: 'fakesink' });
+    expect(camera.device).toBe('Test Cam');
+    kit.play();
+    await new Promise(resolve => setTimeout(resolve, 1500));
+    expect(camera.frames).toBe(30);
+    camera.stop();
+    kit.cleanup();
+  });
+
+  it('should reject unknown devices, formats and sinks', () => {
+    const kit = new GstKit();
+    kit.setPipeline(PIPELINE);
+    expect(() => publishVirtualCamera(kit, 'sink', 'No Such Camera 1234')).toThrow();
+    expect(() => publishVirtualCamera(kit, 'sink', '/dev/video-missing')).toThrow();
+    expect(() => publishVirtualCamera(kit, 'sink', 'Test Cam', { sink: 'fakesink', format: 'NOPE' })).toThrow();
+    expect(() => publishVirtualCamera(kit, 'nope', 'Test Cam', { sink: 'fakesink' })).toThrow();
+    kit.cleanup();
+  });
+});
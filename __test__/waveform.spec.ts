@@ -0,0 +1,61 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { GstKit, probeWithGStreamer, renderWaveformVideo } from '../index.js';
+import setup, { TEST_DIR, generateTestVideo } from './setup.js';
+import * as path from 'node:path';
+
+// Writes two seconds of a 440 Hz tone as 44.1 kHz stereo WAV
+const generateTone = async (filename: string) => {
+  const outputPath = path.join(TEST_DIR, filename);
+  const kit = new GstKit();
+  kit.setPipeline(
+    'audiotestsrc samplesperbuffer=4410 num-buffers=20 ! ' +
+      `audio/x-raw,rate=44100,channels=2 ! wavenc ! filesink location="${outputPath}"`
+  );
+  kit.play();
+  await new Promise(resolve => setTimeout(resolve, 500));
+  kit.stop();
+  kit.cleanup();
+  return outputPath;
+};
+
+describe('renderWaveformVideo', () => {
+  let tone: string;
+
+  beforeAll(async () => {
+    setup.setupTestDirectories();
+    tone = await generateTone('waveform_tone.wav');
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should render a waveform video with the audio as soundtrack', () => {
+    const output = path.join(TEST_DIR, 'waveform.webm');
+    renderWaveformVideo(tone, output, { resolution: '320x180', fps: 10 });
+
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(320);
+    expect(info.video[0].height).toBe(180);
+    expect(info.audio.length).toBe(1);
+    expect(info.duration).toBeGreaterThan(1.5);
+  });
+
+  it('should render a spectrum without audio', () => {
+    const output = path.join(TEST_DIR, 'spectrum.mkv');
+    renderWaveformVideo(tone, output, { style: 'spectrum', resolution: '256x256', audio: false });
+
+    const info = probeWithGStreamer(output);
+    expect(info.video[0].width).toBe(256);
+    expect(info.audio.length).toBe(0);
+  });
+
+  it('should reject unknown styles', () => {
+    expect(() => renderWaveformVideo(tone, path.join(TEST_DIR, 'bad.webm'), { style: 'oscilloscope' })).toThrow();
+  });
+
+  it('should fail for inputs without audio', async () => {
+    const video = await generateTestVideo('waveform_silent.avi', 'smpte', { numBuffers: 10 });
+    expect(() => renderWaveformVideo(video, path.join(TEST_DIR, 'silent.webm'))).toThrow();
+  });
+});
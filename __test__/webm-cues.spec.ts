@@ -0,0 +1,48 @@
+import { describe, it, expect, beforeAll, afterAll } from 'bun:test';
+import { byteRangeForTime, generateTestMedia, getWebmCues } from '../index.js';
+import setup, { TEST_DIR } from './setup.js';
+import * as fs from 'node:fs';
+import * as path from 'node:path';
+
+describe('WebM cues', () => {
+  const file = path.join(TEST_DIR, 'cues.webm');
+
+  beforeAll(() => {
+    setup.setupTestDirectories();
+    generateTestMedia(file, { format: 'webm', width: 160, height: 120, fps: 10, duration: 2 });
+  });
+
+  afterAll(() => {
+    setup.cleanupTestDirectories();
+  });
+
+  it('should list the cue points and header size', () => {
+    const index = getWebmCues(file);
+    expect(index.fileSize).toBe(fs.statSync(file).size);
+    expect(index.cues.length).toBeGreaterThan(0);
+    expect(index.cues[0].timeMs).toBe(0);
+    expect(index.cues[0].clusterOffset).toBe(index.headerSize);
+    expect(index.durationMs).toBeCloseTo(2000, -2);
+
+    // Cluster element ID
+    const cluster = fs.readFileSync(file).subarray(index.headerSize, index.headerSize + 4);
+    expect([...cluster]).toEqual([0x1f, 0x43, 0xb6, 0x75]);
+  });
+
+  it('should map a time to the byte range of its cluster', () => {
+    const index = getWebmCues(file);
+    const range = byteRangeForTime(file, 1500);
+    const cue = index.cues.filter(c => c.timeMs <= 1500).at(-1)!;
+    expect(range.start).toBe(cue.clusterOffset);
+    expect(range.timeMs).toBe(cue.timeMs);
+    expect(range.end).toBeGreaterThan(range.start);
+    expect(range.end).toBeLessThan(index.fileSize);
+  });
+
+  it('should reject files that are not WebM', () => {
+    const other = path.join(TEST_DIR, 'cues.y4m');
+    generateTestMedia(other, { format: 'y4m', width: 16, height: 16, duration: 0.1 });
+    expect(() => getWebmCues(other)).toThrow(/not a WebM/);
+    expect(() => byteRangeForTime(file, -1)).toThrow('Invalid time: -1');
+  });
+});
@@ -1,305 +1,5507 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
 /**
- * Main GStreamer wrapper class for Node.js
+ * Mixer of several audio inputs with per-input volume, mute and levels
  *
- * `GstKit` provides a high-level interface for creating and controlling
- * GStreamer pipelines. It supports playback control, data extraction,
- * and property manipulation.
+ * # Example
+ * ```javascript
+ * const mixer = new AudioMixer();
+ * mixer.addInput("music", "music.mp3", { volume: 0.3 });
+ * mixer.addInput("mic", "pulsesrc");
+ * mixer.play();
+ * setInterval(() => console.log(mixer.getLevels()), 100);
+ * ```
  */
-export declare class GstKit {
+export declare class AudioMixer {
   /**
-   * Creates a new `GstKit` instance and initializes GStreamer
+   * Creates a mixer with no inputs
    *
-   * # Returns
-   * * `Result<Self>` - A new GstKit instance or an error if initialization fails
+   * # Arguments
+   * * `options` - Optional output format and sink
    *
    * # Example
    * ```javascript
-   * const kit = new GstKit();
+   * const mixer = new AudioMixer({ rate: 44100, output: "filesink location=mix.wav" });
    * ```
    */
-  constructor()
+  constructor(options?: AudioMixerOptions | undefined | null)
   /**
-   * Sets up a GStreamer pipeline from a launch string
+   * Adds an audio input
    *
    * # Arguments
-   * * `pipeline_string` - A valid GStreamer pipeline description
+   * * `id` - Unique name of the input
+   * * `source` - A file path, a URI, or a launch description producing audio
+   *   (e.g. "pulsesrc" or "appsrc caps=audio/x-raw,... format=time")
+   * * `options` - Optional initial volume, mute state and audio filters
    *
    * # Example
    * ```javascript
-   * kit.setPipeline("videotestsrc ! video/x-raw,format=RGBA ! appsink name=sink");
+   * mixer.addInput("tone", "audiotestsrc freq=440", { volume: 0.5 });
    * ```
    */
-  setPipeline(pipelineString: string): void
+  addInput(id: string, source: string, options?: AudioInputOptions | undefined | null): void
   /**
-   * Sets up a callback for pipeline events
+   * Removes an input
    *
-   * # Arguments
-   * * `callback` - A JavaScript function to call when pipeline events occur
+   * # Returns
+   * * `bool` - Whether an input with this id existed
+   */
+  removeInput(id: string): boolean
+  /** Returns the ids of all inputs */
+  listInputs(): Array<string>
+  /**
+   * Sets the linear gain of an input, from 0 to 10
    *
    * # Example
    * ```javascript
-   * kit.onEvent((event) => {
-   *   console.log("Event:", event.eventType, event.message);
-   * });
+   * mixer.setVolume("music", 0.2);
    * ```
    */
-  onEvent(callback: (arg?: unknown) => unknown): void
+  setVolume(id: string, volume: number): void
+  /** Returns the linear gain of an input */
+  getVolume(id: string): number
+  /** Silences or restores an input */
+  setMute(id: string, mute: boolean): void
+  /** Returns whether an input is muted */
+  isMuted(id: string): boolean
   /**
-   * Sets up a callback for frame events from AppSink elements
+   * Returns the latest measured levels of every input, sorted by id
    *
-   * # Arguments
-   * * `callback` - A JavaScript function to call when frames are available
+   * Levels are measured every 100 ms before volume and mute are applied.
+   * Inputs that have not produced audio yet are omitted.
    *
    * # Example
    * ```javascript
-   * kit.onFrame((frame) => {
-   *   console.log("Frame from", frame.sinkName, "size:", frame.data.length);
-   * });
+   * for (const { id, peak } of mixer.getLevels()) {
+   *   console.log(id, Math.max(...peak).toFixed(1), "dB");
+   * }
    * ```
    */
-  onFrame(callback: (arg?: unknown) => unknown): void
+  getLevels(): Array<InputLevel>
   /**
-   * Starts emitting frames from all AppSink elements in the pipeline
+   * Pushes raw samples to an input whose source is an AppSrc
    *
    * # Arguments
-   * * `sink_names` - Optional list of sink names to emit frames from. If empty, emits from all AppSinks.
+   * * `id` - The input id
+   * * `data` - Samples matching the caps of the input's AppSrc
+   */
+  pushSamples(id: string, data: Buffer): void
+  /**
+   * Returns the current state of the pipeline
+   *
+   * # Returns
+   * * `String` - The current state ("Null", "Ready", "Paused" or "Playing")
+   */
+  getState(): string
+  /** Starts mixing */
+  play(): void
+  /** Pauses mixing */
+  pause(): void
+  /** Stops mixing and resets the pipeline to the Null state */
+  stop(): void
+}
+
+/** Automatic bitrate adaptation of an encoder, created by `adaptBitrate` */
+export declare class BitrateAdapter {
+  /**
+   * Sets the callback announcing each bitrate change
+   *
+   * # Arguments
+   * * `callback` - Called with every change
    *
    * # Example
    * ```javascript
-   * // Emit frames from all sinks
-   * kit.startFrameEmission();
-   *
-   * // Emit frames from specific sink
-   * kit.startFrameEmission(["mysink"]);
+   * adapter.onChange((change) => console.log(change.reason, change.bitrate));
    * ```
    */
-  startFrameEmission(sinkNames?: Array<string> | undefined | null): void
+  onChange(callback: BitrateCallback): void
+  /** The current bitrate of the encoder in bit/s */
+  get bitrate(): number
+  /** Stops adapting; the encoder keeps its current bitrate */
+  stop(): void
+}
+
+/**
+ * Synchronized capture from several sources
+ *
+ * # Example
+ * ```javascript
+ * const session = new CaptureSession({ format: "I420" });
+ * session.addSource("left", "v4l2src device=/dev/video0");
+ * session.addSource("right", "v4l2src device=/dev/video1");
+ * session.onFrame(({ sourceId, sessionTimestamp, frame }) => {
+ *   writer.write(sourceId, sessionTimestamp, frame.data);
+ * });
+ * session.start();
+ * ```
+ */
+export declare class CaptureSession {
   /**
-   * Stops emitting frames from AppSink elements
+   * Creates an empty session
+   *
+   * # Arguments
+   * * `options` - Optional frame format and start delay
    *
    * # Example
    * ```javascript
-   * kit.stopFrameEmission();
+   * const session = new CaptureSession();
    * ```
    */
-  stopFrameEmission(): void
+  constructor(options?: CaptureSessionOptions | undefined | null)
   /**
-   * Starts monitoring the pipeline bus for events
+   * Sets the callback receiving the frames of every source
    *
-   * This will call the event callback for various pipeline events:
-   * - "eos": End of stream
-   * - "error": Pipeline error
-   * - "warning": Pipeline warning
-   * - "state-changed": Pipeline state changed
-   * - "element": Element message
+   * # Arguments
+   * * `callback` - Called with each frame, tagged with its source id and
+   *   session timestamp
    *
    * # Example
    * ```javascript
-   * kit.startBusMonitoring();
+   * session.onFrame((capture) => console.log(capture.sourceId, capture.sessionTimestamp));
    * ```
    */
-  startBusMonitoring(): void
+  onFrame(callback: ((arg: CaptureFrame) => void)): void
   /**
-   * Starts playback of the pipeline
+   * Adds a source under the given id; a source added while the session
+   * runs starts right away, on the session timeline
+   *
+   * # Arguments
+   * * `id` - Unique name of the source
+   * * `source` - A file path, a URI, or a launch description producing video
+   *   (e.g. "v4l2src device=/dev/video0")
    *
    * # Example
    * ```javascript
-   * kit.play();
+   * session.addSource("top", "v4l2src device=/dev/video2 ! video/x-raw,width=1280,height=720");
    * ```
    */
-  play(): void
+  addSource(id: string, source: string): void
   /**
-   * Pauses the pipeline
+   * Stops and removes a source
+   *
+   * # Returns
+   * * `bool` - Whether a source with this id existed
+   */
+  removeSource(id: string): boolean
+  /** Returns the ids of all sources */
+  listSources(): Array<string>
+  /**
+   * Starts every source on a shared clock and base time
+   *
+   * The session time 0 is `startDelayMs` after the call; frames captured
+   * before it are timestamped 0.
+   *
+   * # Returns
+   * * `Result<i64>` - The base time of the session on the system clock, in
+   *   nanoseconds
    *
    * # Example
    * ```javascript
-   * kit.pause();
+   * session.start();
+   * setTimeout(() => session.stop(), 60_000);
    * ```
    */
-  pause(): void
+  start(): number
+  /** Stops every source */
+  stop(): void
   /**
-   * Stops the pipeline and sets it to NULL state
+   * Returns the current session time in nanoseconds, negative before the
+   * session time 0 is reached, or null when the session is stopped
+   */
+  getSessionTime(): number | null
+  /**
+   * Returns the state and frame count of every source, sorted by id
    *
    * # Example
    * ```javascript
-   * kit.stop();
+   * for (const stats of session.stats()) {
+   *   console.log(stats.id, stats.frames, stats.lastTimestamp);
+   * }
    * ```
    */
-  stop(): void
+  stats(): Array<CaptureSourceStats>
+}
+
+/**
+ * Mixer of several video inputs into one picture, for picture-in-picture,
+ * side-by-side views and overlays
+ *
+ * # Example
+ * ```javascript
+ * const comp = new Compositor({ width: 1280, height: 720 });
+ * comp.addInput("main", "movie.mp4");
+ * comp.addInput("cam", "v4l2src", { x: 960, y: 540, width: 320, height: 180 });
+ * comp.play();
+ * comp.setLayout("cam", { alpha: 0.5 });
+ * ```
+ */
+export declare class Compositor {
   /**
-   * Pulls a sample from a named AppSink element with a configurable timeout
+   * Creates a compositor with no inputs
    *
    * # Arguments
-   * * `element_name` - The name of the AppSink element
-   * * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
-   *
-   * # Returns
-   * * `Result<Option<Buffer>>` - A Buffer containing the sample data, or null if no sample is available
+   * * `options` - Optional output size, frame rate, background and sink
    *
    * # Example
    * ```javascript
-   * const frame = kit.pullSample("mysink", 100);
-   * if (frame) {
-   *   console.log("Got frame of size:", frame.length);
-   * }
+   * const comp = new Compositor({ width: 640, height: 360, output: "appsink name=out" });
    * ```
    */
-  pullSample(elementName: string, timeoutMs?: number | undefined): Buffer | null
+  constructor(options?: CompositorOptions | undefined | null)
   /**
-   * Pushes a buffer to a named AppSrc element
+   * Adds a video input
    *
    * # Arguments
-   * * `element_name` - The name of the AppSrc element
-   * * `data` - The data to push as a Buffer
+   * * `id` - Unique name of the input
+   * * `source` - A file path, a URI, or a launch description producing video
+   *   (e.g. "v4l2src" or "appsrc caps=video/x-raw,... format=time")
+   * * `layout` - Optional initial placement; new inputs are stacked on top
    *
    * # Example
    * ```javascript
-   * kit.pushSample("mysrc", Buffer.from([0, 1, 2, 3]));
+   * comp.addInput("logo", "logo.png", { x: 16, y: 16, width: 128, height: 128, alpha: 0.8 });
    * ```
    */
-  pushSample(elementName: string, data: Buffer): void
+  addInput(id: string, source: string, layout?: InputLayout | undefined | null): void
   /**
-   * Returns the current state of the pipeline
+   * Removes an input
    *
    * # Returns
-   * * `Result<String>` - The current state as a string ("Playing", "Paused", "Null", "Ready")
+   * * `bool` - Whether an input with this id existed
+   */
+  removeInput(id: string): boolean
+  /** Returns the ids of all inputs */
+  listInputs(): Array<string>
+  /**
+   * Changes the placement of an input; omitted fields are left unchanged
    *
    * # Example
    * ```javascript
-   * const state = kit.getState();
-   * console.log("Current state:", state);
+   * comp.setLayout("cam", { x: 0, y: 0, zorder: 10 });
    * ```
    */
-  getState(): string
+  setLayout(id: string, layout: InputLayout): void
+  /** Returns the current placement of an input */
+  getLayout(id: string): InputLayout
   /**
-   * Returns the current position of the pipeline in nanoseconds
+   * Pushes a raw frame to an input whose source is an AppSrc
+   *
+   * # Arguments
+   * * `id` - The input id
+   * * `data` - Frame data matching the caps of the input's AppSrc
+   */
+  pushFrame(id: string, data: Buffer): void
+  /**
+   * Pulls a mixed frame when the output is an AppSink
+   *
+   * # Arguments
+   * * `timeout_ms` - Maximum time to wait for a frame (default: 1000)
    *
    * # Returns
-   * * `Result<i64>` - Current position in nanoseconds
+   * * `Result<Option<Buffer>>` - The frame data, or null on timeout
    *
    * # Example
    * ```javascript
-   * const position = kit.getPosition();
-   * console.log("Position (ns):", position);
+   * const comp = new Compositor({ output: "video/x-raw,format=RGBA ! appsink name=out" });
+   * const frame = comp.pullFrame();
    * ```
    */
-  getPosition(): number
+  pullFrame(timeoutMs?: number | undefined | null): Buffer | null
   /**
-   * Returns the duration of the pipeline in nanoseconds
+   * Returns the current state of the pipeline
    *
    * # Returns
-   * * `Result<i64>` - Duration in nanoseconds, or -1 if unknown
+   * * `String` - The current state ("Null", "Ready", "Paused" or "Playing")
+   */
+  getState(): string
+  /** Starts mixing */
+  play(): void
+  /** Pauses mixing */
+  pause(): void
+  /** Stops mixing and resets the pipeline to the Null state */
+  stop(): void
+}
+
+/** Device plug and permission events, created by `watchDevices` */
+export declare class DeviceWatcher {
+  /**
+   * Sets the callback receiving device and permission events
+   *
+   * # Arguments
+   * * `callback` - Called with every event
    *
    * # Example
    * ```javascript
-   * const duration = kit.getDuration();
-   * console.log("Duration (ns):", duration);
+   * watcher.onEvent((event) => {
+   *   if (event.eventType === "removed") console.log(`${event.device.name} was unplugged`);
+   * });
    * ```
    */
-  getDuration(): number
+  onEvent(callback: DeviceCallback): void
+  /** The devices currently available */
+  devices(): Array<DeviceInfo>
+  /** Stops watching */
+  stop(): void
+}
+
+/**
+ * Ticks at the frame boundaries of a frame rate
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("appsrc name=src is-live=true format=time caps=video/x-raw,format=RGBA,width=640,height=360,framerate=60/1 ! videoconvert ! autovideosink");
+ * kit.play();
+ * const clock = new FrameClock({ fps: 60 });
+ * clock.start((tick) => clock.pushFrame(kit, "src", tick.index, render(tick.timestamp)));
+ * ```
+ */
+export declare class FrameClock {
   /**
-   * Seeks to a specific position in the pipeline
+   * Creates a stopped clock
    *
    * # Arguments
-   * * `position_ns` - Position to seek to in nanoseconds
+   * * `options` - Frame rate and catch-up and spin settings
    *
    * # Example
    * ```javascript
-   * // Seek to 5 seconds (5 * 1_000_000_000 nanoseconds)
-   * kit.seek(5_000_000_000);
+   * const clock = new FrameClock({ fps: 29.97 });
    * ```
    */
-  seek(positionNs: number): void
+  constructor(options: FrameClockOptions)
   /**
-   * Sets a property on a named element in the pipeline
+   * Starts ticking; the statistics are reset
+   *
+   * The tick is taken on a dedicated thread and queued to JavaScript, so a
+   * busy event loop delays the callback but not the clock.
    *
    * # Arguments
-   * * `element_name` - The name of the element
-   * * `property_name` - The name of the property
-   * * `value` - The value to set (as a string)
+   * * `callback` - Called with every tick
    *
    * # Example
    * ```javascript
-   * kit.setProperty("mysrc", "is-live", "true");
+   * clock.start((tick) => draw(tick.index));
    * ```
    */
-  setProperty(elementName: string, propertyName: string, value: string): void
+  start(callback: ((arg: FrameTick) => void)): void
+  /** Stops ticking; the clock can be started again */
+  stop(): void
+  /** Whether the clock is ticking */
+  get running(): boolean
+  /** Frame rate of the clock as a fraction, e.g. "30000/1001" */
+  get frameRate(): string
   /**
-   * Gets a property value from a named element in the pipeline
+   * Returns the tick count and jitter since the clock started
+   *
+   * # Example
+   * ```javascript
+   * const { maxJitter, skipped } = clock.stats();
+   * ```
+   */
+  stats(): FrameClockStats
+  /**
+   * Pushes the data of a frame into an AppSrc, timestamped for its tick
    *
    * # Arguments
-   * * `element_name` - The name of the element
-   * * `property_name` - The name of the property
+   * * `kit` - The kit running the pipeline
+   * * `src_name` - The name of the AppSrc element
+   * * `index` - Index of the tick the frame was made for
+   * * `data` - The frame data, in the format of the AppSrc caps
    *
-   * # Returns
-   * * `Result<String>` - The property value as a string
+   * # Example
+   * ```javascript
+   * clock.start((tick) => clock.pushFrame(kit, "src", tick.index, canvas.toBuffer("raw")));
+   * ```
+   */
+  pushFrame(kit: GstKit, srcName: string, index: number, data: Buffer): void
+}
+
+/**
+ * Async iterable over the frames of an AppSink, created by `GstKit.frames`
+ *
+ * Iteration ends at end of stream, or when the loop is left with `break`,
+ * which also detaches the stream from the sink.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class FrameStream {
+  /**
+   * Number of frames dropped so far because the consumer fell behind (frames
+   * skipped by "sampleEveryN" are not counted)
+   */
+  get dropped(): number
+  /**
+   * Ends the stream and detaches it from the sink
    *
    * # Example
    * ```javascript
-   * const value = kit.getProperty("mysrc", "is-live");
-   * console.log("Property value:", value);
+   * const frames = kit.frames("sink");
+   * setTimeout(() => frames.close(), 5000);
+   * for await (const frame of frames) { ... }
    * ```
    */
-  getProperty(elementName: string, propertyName: string): string
+  close(): void
+  [Symbol.asyncIterator](): AsyncGenerator<FrameData, void, undefined>
+}
+
+/**
+ * Main GStreamer wrapper class for Node.js
+ *
+ * `GstKit` provides a high-level interface for creating and controlling
+ * GStreamer pipelines. It supports playback control, data extraction,
+ * and property manipulation.
+ */
+export declare class GstKit {
   /**
-   * Returns a list of all element names in the pipeline
+   * Creates a new `GstKit` instance and initializes GStreamer
    *
    * # Returns
-   * * `Result<Vec<String>>` - Array of element names
+   * * `Result<Self>` - A new GstKit instance or an error if initialization fails
    *
    * # Example
    * ```javascript
-   * const elements = kit.getElements();
-   * console.log("Elements:", elements);
+   * const kit = new GstKit();
    * ```
    */
-  getElements(): Array<string>
+  constructor()
   /**
-   * Checks if the pipeline has been initialized
+   * Sets up a GStreamer pipeline from a launch string
    *
-   * # Returns
-   * * `bool` - true if pipeline is initialized, false otherwise
+   * # Arguments
+   * * `pipeline_string` - A valid GStreamer pipeline description
    *
    * # Example
    * ```javascript
-   * if (kit.isInitialized()) {
-   *   console.log("Pipeline is ready");
-   * }
+   * kit.setPipeline("videotestsrc ! video/x-raw,format=RGBA ! appsink name=sink");
    * ```
    */
-  isInitialized(): boolean
+  setPipeline(pipelineString: string): void
   /**
-   * Cleans up and releases the pipeline
+   * Sets the pipeline from a launch string template with `{name}` placeholders
    *
-   * This method stops the pipeline and releases all resources.
-   * After calling this, you must call `setPipeline` again to use the kit.
+   * Every placeholder must have a value. Values are escaped so each stays
+   * one token of the launch string; `{{` and `}}` stand for literal braces.
+   *
+   * # Arguments
+   * * `template` - A pipeline description with `{name}` placeholders
+   * * `vars` - The value of every placeholder
    *
    * # Example
    * ```javascript
-   * kit.cleanup();
+   * kit.setPipelineTemplate(
+   *   "v4l2src device={device} ! video/x-raw,width={width},height={height} ! videoconvert ! appsink name=sink",
+   *   { device: "/dev/video0", width: 1280, height: 720 },
+   * );
    * ```
    */
-  cleanup(): void
-}
-
-/** Frame data emitted from AppSink */
-export interface FrameData {
-  /** The frame data as a buffer */
-  data: Buffer
-  /** The name of the sink element */
-  sinkName: string
-  /** Timestamp of the frame in nanoseconds */
-  timestamp: number
-}
-
-/** Event types that can be emitted by the pipeline */
-export interface PipelineEvent {
-  /** The type of event */
-  eventType: string
-  /** Optional message associated with the event */
-  message?: string
-  /** Optional error code (for error events) */
-  errorCode?: number
-}
+  setPipelineTemplate(template: string, vars: Record<string, string | number | boolean>): void
+  /**
+   * Takes over a pipeline of a `PipelineManager`, which stops managing it
+   *
+   * The pipeline keeps its state, so a playing pipeline keeps playing and
+   * can be controlled and tapped through the kit from then on.
+   *
+   * # Arguments
+   * * `manager` - The manager owning the pipeline
+   * * `id` - The id the pipeline was added under
+   *
+   * # Example
+   * ```javascript
+   * manager.addPipeline("cam1", "v4l2src ! videoconvert ! appsink name=sink");
+   * kit.adoptPipeline(manager, "cam1");
+   * const frame = kit.pullFrame("sink");
+   * ```
+   */
+  adoptPipeline(manager: PipelineManager, id: string): void
+  /**
+   * Sets up a callback for pipeline events
+   *
+   * # Arguments
+   * * `callback` - A JavaScript function to call when pipeline events occur
+   *
+   * # Example
+   * ```javascript
+   * kit.onEvent((event) => {
+   *   console.log("Event:", event.eventType, event.message);
+   * });
+   * ```
+   */
+  onEvent(callback: (arg?: unknown) => unknown): void
+  /**
+   * Sets up a callback for frame events from AppSink elements
+   *
+   * # Arguments
+   * * `callback` - A JavaScript function to call when frames are available
+   *
+   * # Example
+   * ```javascript
+   * kit.onFrame((frame) => {
+   *   console.log("Frame from", frame.sinkName, "size:", frame.data.length);
+   * });
+   * ```
+   */
+  onFrame(callback: (arg?: unknown) => unknown): void
+  /**
+   * Starts emitting frames from all AppSink elements in the pipeline
+   *
+   * # Arguments
+   * * `sink_names` - Optional list of sink names to emit frames from. If empty, emits from all AppSinks.
+   * * `preferred_output_format` - Optional raw video format (e.g. "RGBA") the sinks
+   *   are forced to receive; a `videoconvert` is inserted in front of them if needed.
+   * * `options` - Optional backpressure policy and queue length, used by the
+   *   `frames()` streams of the sinks. For `pullSample` callers the sinks
+   *   hold at most `maxQueueLength` frames: "block" stalls the pipeline when
+   *   they are full, the other policies drop the oldest frame.
+   *
+   * # Example
+   * ```javascript
+   * // Emit frames from all sinks
+   * kit.startFrameEmission();
+   *
+   * // Emit RGBA frames from specific sink
+   * kit.startFrameEmission(["mysink"], "RGBA");
+   *
+   * // Hand every third frame to a slow consumer, never stalling the pipeline
+   * kit.startFrameEmission(["mysink"], null, { policy: "sampleEveryN", sampleEvery: 3 });
+   * ```
+   */
+  startFrameEmission(sinkNames?: Array<string> | undefined | null, preferredOutputFormat?: string | undefined | null, options?: FrameEmissionOptions | undefined | null): void
+  /**
+   * Stops emitting frames from AppSink elements
+   *
+   * # Example
+   * ```javascript
+   * kit.stopFrameEmission();
+   * ```
+   */
+  stopFrameEmission(): void
+  /**
+   * Publishes the frames of an AppSink into a shared-memory ring buffer
+   *
+   * Every frame reaching the sink is copied into the next slot of the ring
+   * named `name`, where other processes read it without crossing into
+   * JavaScript; see the `shared_frames` module for the layout and the read
+   * protocol. The sink's callbacks are taken over, so `pullSample` no longer
+   * returns its frames. Use `startFrameEmission` first to pick the format.
+   *
+   * # Arguments
+   * * `sink_name` - The name of the AppSink element
+   * * `name` - Name of the shared memory object
+   * * `options` - Optional slot count and maximum frame size
+   *
+   * # Returns
+   * * `Result<SharedFramesInfo>` - Path and geometry of the ring
+   *
+   * # Example
+   * ```javascript
+   * kit.startFrameEmission(["sink"], "RGB");
+   * const ring = kit.publishSharedFrames("sink", "camera0", { slots: 8 });
+   * spawn("python3", ["infer.py", ring.name]);
+   * ```
+   */
+  publishSharedFrames(sinkName: string, name: string, options?: SharedFramesOptions | undefined | null): SharedFramesInfo
+  /**
+   * Stops publishing an AppSink's frames to shared memory and removes the ring
+   *
+   * Readers that still have the ring mapped keep their mapping, but no new
+   * frames arrive. The sink's frames can be pulled again afterwards.
+   *
+   * # Arguments
+   * * `sink_name` - The name of the AppSink element
+   *
+   * # Example
+   * ```javascript
+   * kit.stopSharedFrames("sink");
+   * ```
+   */
+  stopSharedFrames(sinkName: string): void
+  /**
+   * Starts monitoring the pipeline bus for events
+   *
+   * This will call the event callback for various pipeline events:
+   * - "eos": End of stream
+   * - "error": Pipeline error
+   * - "warning": Pipeline warning
+   * - "state-changed": Pipeline state changed
+   * - "element": Element message
+   *
+   * # Example
+   * ```javascript
+   * kit.startBusMonitoring();
+   * ```
+   */
+  startBusMonitoring(): void
+  /**
+   * Starts playback of the pipeline
+   *
+   * # Example
+   * ```javascript
+   * kit.play();
+   * ```
+   */
+  play(): void
+  /**
+   * Pauses the pipeline
+   *
+   * # Example
+   * ```javascript
+   * kit.pause();
+   * ```
+   */
+  pause(): void
+  /**
+   * Starts playback and resolves once the pipeline is playing
+   *
+   * Unlike `play`, which returns as soon as the change is started, this
+   * rejects with the error an element posted if it fails to start, e.g.
+   * because a device is busy or caps cannot be negotiated.
+   *
+   * # Arguments
+   * * `timeout_ms` - Reject if the pipeline is not playing by then
+   *   (default: wait indefinitely)
+   *
+   * # Example
+   * ```javascript
+   * await kit.playAsync(5000);
+   * ```
+   */
+  playAsync(timeoutMs?: number | undefined | null): Promise<void>
+  /**
+   * Pauses the pipeline and resolves once it is paused, which for
+   * non-live pipelines means the first frame has reached every sink
+   *
+   * # Arguments
+   * * `timeout_ms` - Reject if the pipeline is not paused by then
+   *   (default: wait indefinitely)
+   *
+   * # Example
+   * ```javascript
+   * await kit.pauseAsync();
+   * const duration = kit.getDuration();
+   * ```
+   */
+  pauseAsync(timeoutMs?: number | undefined | null): Promise<void>
+  /**
+   * Resolves once the pipeline reaches a state, without changing it
+   *
+   * # Arguments
+   * * `state` - "Null", "Ready", "Paused" or "Playing", as returned by
+   *   `getState`
+   * * `timeout_ms` - Reject if the state is not reached by then (default:
+   *   wait indefinitely)
+   *
+   * # Example
+   * ```javascript
+   * kit.play();
+   * await kit.waitForState("Playing", 2000);
+   * ```
+   */
+  waitForState(state: string, timeoutMs?: number | undefined | null): Promise<void>
+  /**
+   * Stops the pipeline and sets it to NULL state
+   *
+   * # Example
+   * ```javascript
+   * kit.stop();
+   * ```
+   */
+  stop(): void
+  /**
+   * Pulls a sample from a named AppSink element with a configurable timeout
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+   *
+   * # Returns
+   * * `Result<Option<Buffer>>` - A Buffer containing the sample data, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * const frame = kit.pullSample("mysink", 100);
+   * if (frame) {
+   *   console.log("Got frame of size:", frame.length);
+   * }
+   * ```
+   */
+  pullSample(elementName: string, timeoutMs?: number | undefined): Buffer | null
+  /**
+   * Pulls a raw video frame from a named AppSink element, split into its planes
+   *
+   * Unlike `pullSample`, the frame keeps its negotiated format and comes with
+   * the stride and offset of every plane, so planar YUV (e.g. I420 or NV12)
+   * can be consumed without converting to RGBA first. Use
+   * `startFrameEmission` to force a format on the sink, and the `packed`
+   * option to drop the row padding sinks often negotiate.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `options` - Optional timeout and row packing
+   *
+   * # Returns
+   * * `Result<Option<FrameData>>` - The frame, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("videotestsrc ! video/x-raw,format=I420 ! appsink name=sink");
+   * kit.play();
+   * const frame = kit.pullFrame("sink");
+   * if (frame) {
+   *   const [y, u, v] = frame.planes;
+   *   console.log(frame.format, y.stride, u.width, v.height);
+   * }
+   * ```
+   */
+  pullFrame(elementName: string, options?: PullFrameOptions | undefined | null): FrameData | null
+  /**
+   * Returns the frames of a named AppSink element as an async iterable
+   *
+   * Frames arrive as `FrameData`, like with `pullFrame`. They are queued
+   * until the loop asks for them; when it falls behind, the backpressure
+   * policy decides which frames are lost (by default the oldest queued
+   * one). The stream takes over the sink's callbacks, so use one stream per
+   * sink and no `pullSample` calls alongside it. Leaving the loop or
+   * calling `close()` on the stream hands the sink back.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `options` - Optional backpressure policy and queue length; defaults to
+   *   the settings given to `startFrameEmission` for the sink
+   *
+   * # Returns
+   * * `Result<FrameStream>` - An async iterable ending at end of stream
+   *
+   * # Example
+   * ```javascript
+   * kit.play();
+   * for await (const frame of kit.frames("sink", { maxQueueLength: 2 })) {
+   *   await detector.process(frame.data, frame.width, frame.height);
+   * }
+   * ```
+   */
+  frames(elementName: string, options?: FrameEmissionOptions | undefined | null): FrameStream
+  /**
+   * Pulls a frame from a named AppSink element laid out like `ImageData`
+   *
+   * The pixels come as a tightly packed RGBA `Uint8ClampedArray`, whatever
+   * the row padding and channel order of the sink (RGBA, BGRA, ARGB, RGBx,
+   * RGB, GRAY8, ...), ready for `new ImageData(data, width, height)` or
+   * `createImageBitmap` in Electron and other HTML canvas hosts.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `options` - Optional timeout and alpha premultiplication
+   *
+   * # Returns
+   * * `Result<Option<ImageDataFrame>>` - The frame, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * const frame = kit.pullSampleAsImageData("sink");
+   * if (frame) {
+   *   ctx.putImageData(new ImageData(frame.data, frame.width, frame.height), 0, 0);
+   * }
+   * ```
+   */
+  pullSampleAsImageData(elementName: string, options?: ImageDataOptions | undefined | null): ImageDataFrame | null
+  /**
+   * Pulls a frame from a named AppSink element laid out for OpenCV and numpy
+   *
+   * The pixels come tightly packed in BGR order, whatever the row padding and
+   * channel order of the sink, with the shape, strides and matrix type that
+   * `cv.Mat` and `ndarray` consumers expect, so no channel shuffle is needed
+   * in JavaScript.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `options` - Optional timeout and RGB channel order
+   *
+   * # Returns
+   * * `Result<Option<MatFrame>>` - The frame, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * const frame = kit.pullSampleAsMat("sink");
+   * if (frame) {
+   *   const mat = new cv.Mat(frame.rows, frame.cols, frame.matType);
+   *   mat.data.set(frame.data);
+   * }
+   * ```
+   */
+  pullSampleAsMat(elementName: string, options?: MatOptions | undefined | null): MatFrame | null
+  /**
+   * Pulls a frame from a named AppSink element as a GPU handle, without
+   * downloading it to system memory
+   *
+   * The sink must negotiate DMABuf, GL or D3D11 memory, e.g. with
+   * `appsink caps="video/x-raw(memory:DMABuf)"` after a hardware decoder or
+   * `glupload ! appsink caps="video/x-raw(memory:GLMemory)"`, or receive the
+   * IOSurface-backed buffers of an `applemedia` decoder such as `vtdec`.
+   * D3D11 textures are only exported when allocated as shared resources.
+   * Other memory types are rejected.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+   *
+   * # Returns
+   * * `Result<Option<GpuFrameHandle>>` - The handles and layout, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("filesrc location=in.mp4 ! parsebin ! vah264dec ! appsink name=sink caps=video/x-raw(memory:DMABuf)");
+   * kit.play();
+   * const frame = kit.pullSampleAsGpuHandle("sink", 1000);
+   * if (frame) {
+   *   renderer.importDmaBuf(frame.handles, frame.format, frame.strides, frame.offsets);
+   *   frame.handles.forEach((fd) => fs.closeSync(fd));
+   * }
+   * ```
+   */
+  pullSampleAsGpuHandle(elementName: string, timeoutMs?: number | undefined): GpuFrameHandle | null
+  /**
+   * Pulls an audio sample from a named AppSink element, with its format info
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element
+   * * `options` - Optional timeout and deinterleaving
+   *
+   * # Returns
+   * * `Result<Option<AudioSample>>` - The sample and its parsed caps, or null if no sample is available
+   *
+   * # Example
+   * ```javascript
+   * const sample = kit.pullAudioSample("audiosink", { deinterleave: true });
+   * if (sample) {
+   *   const left = sample.channelData[0];
+   *   console.log(sample.rate, sample.channels, left.length);
+   * }
+   * ```
+   */
+  pullAudioSample(elementName: string, options?: AudioPullOptions | undefined | null): AudioSample | null
+  /**
+   * Pushes a buffer to a named AppSrc element
+   *
+   * Metadata attached to the buffer travels with the frame and is returned
+   * as `metadata` by `pullFrame`, `frames` and planar frame taps.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSrc element
+   * * `data` - The data to push as a Buffer
+   * * `metadata` - Optional bytes or string (up to 64 KiB) to attach to the frame
+   *
+   * # Example
+   * ```javascript
+   * kit.pushSample("mysrc", Buffer.from([0, 1, 2, 3]));
+   * kit.pushSample("mysrc", frame, JSON.stringify({ sequence: 42 }));
+   * ```
+   */
+  pushSample(elementName: string, data: Buffer, metadata?: Buffer | string | undefined | null): void
+  /**
+   * Pushes timed metadata, such as a KLV packet, to a named AppSrc element
+   *
+   * Linked to `mpegtsmux`, an AppSrc with `meta/x-klv,parsed=true` caps and
+   * `format=time` becomes a KLV stream of the transport stream, each packet
+   * presented at its timestamp.
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSrc element
+   * * `data` - Bytes or a string (stored as UTF-8), up to 64 KiB
+   * * `timestamp_ns` - Presentation time in nanoseconds, in the running time of the pipeline
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline(
+   *   "v4l2src ! videoconvert ! x264enc tune=zerolatency ! h264parse ! mux. " +
+   *   "appsrc name=klv format=time is-live=true caps=meta/x-klv,parsed=true ! mux. " +
+   *   "mpegtsmux name=mux ! filesink location=flight.ts"
+   * );
+   * kit.play();
+   * kit.pushTimedMetadata("klv", encodeKlv(key, localSet), kit.getPosition());
+   * ```
+   */
+  pushTimedMetadata(elementName: string, data: Buffer | string, timestampNs: number): void
+  /**
+   * Pulls timed metadata, such as KLV packets, from a named AppSink element
+   *
+   * # Arguments
+   * * `element_name` - The name of the AppSink element, e.g. one linked to the `meta/x-klv` pad of `tsdemux`
+   * * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+   *
+   * # Returns
+   * * `Result<Option<TimedMetadata>>` - The metadata with its presentation time, or null if none is available
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("filesrc location=flight.ts ! tsdemux name=demux demux. ! meta/x-klv ! appsink name=klv sync=false");
+   * kit.play();
+   * const metadata = kit.pullTimedMetadata("klv", 1000);
+   * if (metadata) console.log(metadata.timestamp, parseKlv(metadata.data));
+   * ```
+   */
+  pullTimedMetadata(elementName: string, timeoutMs?: number | undefined): TimedMetadata | null
+  /**
+   * Returns the current state of the pipeline
+   *
+   * # Returns
+   * * `Result<String>` - The current state as a string ("Playing", "Paused", "Null", "Ready")
+   *
+   * # Example
+   * ```javascript
+   * const state = kit.getState();
+   * console.log("Current state:", state);
+   * ```
+   */
+  getState(): string
+  /**
+   * Returns the current position of the pipeline in nanoseconds
+   *
+   * # Returns
+   * * `Result<i64>` - Current position in nanoseconds
+   *
+   * # Example
+   * ```javascript
+   * const position = kit.getPosition();
+   * console.log("Position (ns):", position);
+   * ```
+   */
+  getPosition(): number
+  /**
+   * Returns the duration of the pipeline in nanoseconds
+   *
+   * # Returns
+   * * `Result<i64>` - Duration in nanoseconds, or -1 if unknown
+   *
+   * # Example
+   * ```javascript
+   * const duration = kit.getDuration();
+   * console.log("Duration (ns):", duration);
+   * ```
+   */
+  getDuration(): number
+  /**
+   * Seeks to a specific position in the pipeline
+   *
+   * # Arguments
+   * * `position_ns` - Position to seek to in nanoseconds
+   *
+   * # Example
+   * ```javascript
+   * // Seek to 5 seconds (5 * 1_000_000_000 nanoseconds)
+   * kit.seek(5_000_000_000);
+   * ```
+   */
+  seek(positionNs: number): void
+  /**
+   * Loops a region of the media, e.g. for A/B repeat in a review tool
+   *
+   * Playback jumps to the start of the region unless it is already inside
+   * it, and returns to the start at the end of every pass without a gap.
+   * The start and end are frame-accurate. Setting another region replaces
+   * the current one; seeking with `seek` leaves the loop.
+   *
+   * # Arguments
+   * * `start_ns` - Start of the region in nanoseconds
+   * * `end_ns` - End of the region in nanoseconds
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("playbin uri=file:///clip.mp4");
+   * await kit.playAsync();
+   * kit.setLoopRegion(2_000_000_000, 4_500_000_000);
+   * ```
+   */
+  setLoopRegion(startNs: number, endNs: number): void
+  /**
+   * Stops looping; playback continues from the current position
+   *
+   * # Returns
+   * * `Result<bool>` - Whether a region was looped
+   *
+   * # Example
+   * ```javascript
+   * kit.clearLoop();
+   * ```
+   */
+  clearLoop(): boolean
+  /**
+   * Changes the playback speed, keeping the pitch of the audio
+   *
+   * A `scaletempo` element is inserted in front of the audio sink on first
+   * use, so call it once the pipeline has prerolled. A looped region keeps
+   * looping at the new speed.
+   *
+   * # Arguments
+   * * `factor` - Speed relative to normal playback, e.g. 0.5 for half speed
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("playbin uri=file:///lecture.mp4");
+   * await kit.playAsync();
+   * kit.setTempo(1.5);
+   * ```
+   */
+  setTempo(factor: number): void
+  /**
+   * Shifts the pitch of the audio without changing the playback speed
+   *
+   * A `pitch` element (SoundTouch plugin) is inserted in front of the audio
+   * sink on first use, so call it once the pipeline has prerolled.
+   *
+   * # Arguments
+   * * `semitones` - Shift in semitones, from -24 to 24; 0 restores the original pitch
+   *
+   * # Example
+   * ```javascript
+   * kit.setPitch(-2);
+   * ```
+   */
+  setPitch(semitones: number): void
+  /**
+   * Sets a property on a named element in the pipeline
+   *
+   * # Arguments
+   * * `element_name` - The name of the element
+   * * `property_name` - The name of the property
+   * * `value` - The value to set (as a string)
+   *
+   * # Example
+   * ```javascript
+   * kit.setProperty("mysrc", "is-live", "true");
+   * ```
+   */
+  setProperty(elementName: string, propertyName: string, value: string): void
+  /**
+   * Changes the bitrate of a running encoder
+   *
+   * The value is converted to the unit of the encoder's bitrate property
+   * and clamped to its range.
+   *
+   * # Arguments
+   * * `element_name` - The name of the encoder element
+   * * `bps` - The target bitrate in bit/s
+   *
+   * # Returns
+   * * `Result<f64>` - The bitrate applied, in bit/s
+   *
+   * # Example
+   * ```javascript
+   * kit.setTargetBitrate("enc", 1_500_000);
+   * ```
+   */
+  setTargetBitrate(elementName: string, bps: number): number
+  /**
+   * Animates a numeric property of an element or pad through keyframes
+   *
+   * Values are computed for the timestamp of each buffer on the pipeline
+   * clock, so the animation follows pauses and seeks. Any earlier animation
+   * of the property is replaced.
+   *
+   * # Arguments
+   * * `element_name` - The name of the element, or "element.pad" for a pad
+   * * `property_name` - The name of a controllable numeric property
+   * * `keyframes` - Values of the property at stream times
+   * * `options` - Interpolation between keyframes
+   *
+   * # Example
+   * ```javascript
+   * // Fade in over two seconds, then slide the second input to the right
+   * kit.animateProperty("vol", "volume", [{ timeMs: 0, value: 0 }, { timeMs: 2000, value: 1 }]);
+   * kit.animateProperty("mix.sink_1", "xpos", [{ timeMs: 2000, value: 0 }, { timeMs: 4000, value: 640 }], {
+   *   interpolation: "cubic-monotonic",
+   * });
+   * ```
+   */
+  animateProperty(elementName: string, propertyName: string, keyframes: Array<Keyframe>, options?: AnimationOptions | undefined | null): void
+  /**
+   * Stops animating a property, leaving it at its current value
+   *
+   * # Arguments
+   * * `element_name` - The name of the element, or "element.pad" for a pad
+   * * `property_name` - The name of the animated property
+   *
+   * # Returns
+   * * `Result<bool>` - Whether the property was animated
+   */
+  clearAnimation(elementName: string, propertyName: string): boolean
+  /**
+   * Simulates a bad network in front of a sink, for testing
+   *
+   * A `netsim` element is inserted before the sink on first use, also
+   * while the pipeline plays; later calls replace its impairment. Naming a
+   * `netsim` element of the pipeline configures it instead.
+   *
+   * # Arguments
+   * * `element_name` - The name of the sink, e.g. an `udpsink` or `srtsink`
+   * * `impairment` - Loss, delay, jitter, bandwidth cap and duplication
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("videotestsrc is-live=true ! x264enc tune=zerolatency ! rtph264pay ! udpsink name=out port=5000");
+   * kit.play();
+   * kit.impairNetwork("out", { loss: 0.05, delayMs: 80, jitterMs: 30, bandwidthKbps: 2000 });
+   * ```
+   */
+  impairNetwork(elementName: string, impairment: NetworkImpairment): void
+  /**
+   * Lets packets reach a sink unimpaired again
+   *
+   * # Arguments
+   * * `element_name` - The name of the impaired sink
+   *
+   * # Returns
+   * * `Result<bool>` - Whether the sink was impaired
+   */
+  clearNetworkImpairment(elementName: string): boolean
+  /**
+   * Gets a property value from a named element in the pipeline
+   *
+   * # Arguments
+   * * `element_name` - The name of the element
+   * * `property_name` - The name of the property
+   *
+   * # Returns
+   * * `Result<String>` - The property value as a string
+   *
+   * # Example
+   * ```javascript
+   * const value = kit.getProperty("mysrc", "is-live");
+   * console.log("Property value:", value);
+   * ```
+   */
+  getProperty(elementName: string, propertyName: string): string
+  /**
+   * Returns a list of all element names in the pipeline
+   *
+   * # Returns
+   * * `Result<Vec<String>>` - Array of element names
+   *
+   * # Example
+   * ```javascript
+   * const elements = kit.getElements();
+   * console.log("Elements:", elements);
+   * ```
+   */
+  getElements(): Array<string>
+  /**
+   * Returns a snapshot of pipeline statistics
+   *
+   * Reports queue fill levels, encoder bitrates, negotiated caps and the
+   * rendered/dropped frame counts of sinks and of elements posting QoS
+   * messages. QoS messages are collected as they are posted and stay on
+   * the pipeline bus.
+   *
+   * # Returns
+   * * `Result<PipelineStats>` - Per-element statistics and frame totals
+   *
+   * # Example
+   * ```javascript
+   * const stats = kit.getStats();
+   * console.log("Dropped frames:", stats.droppedFrames);
+   * for (const el of stats.elements.filter((e) => e.queueBuffers !== undefined)) {
+   *   console.log(el.name, el.queueBuffers);
+   * }
+   * ```
+   */
+  getStats(): PipelineStats
+  /**
+   * Returns the pipeline latency in milliseconds
+   *
+   * This is the latency set with `setLatency`, or otherwise the minimum
+   * latency reported by the pipeline's live elements.
+   *
+   * # Returns
+   * * `Result<f64>` - Latency in milliseconds
+   *
+   * # Example
+   * ```javascript
+   * console.log("Latency (ms):", kit.getLatency());
+   * ```
+   */
+  getLatency(): number
+  /**
+   * Sets a fixed pipeline latency, overriding the latency computed from the
+   * live elements
+   *
+   * # Arguments
+   * * `latency_ms` - Latency in milliseconds
+   *
+   * # Example
+   * ```javascript
+   * kit.setLatency(200);
+   * ```
+   */
+  setLatency(latencyMs: number): void
+  /**
+   * Selects the clock driving the pipeline, resolving once it is in use
+   *
+   * Network clocks ("ntp", "ptp" and "net") are waited on until they are
+   * synchronized, so pipelines on several machines can share one timeline.
+   * The wait happens off the JavaScript thread.
+   *
+   * # Arguments
+   * * `options` - Clock type and, for network clocks, the server to follow
+   *
+   * # Example
+   * ```javascript
+   * await kit.useClock({ kind: "net", address: "192.168.1.10", port: 8554 });
+   * kit.setBaseTime(sharedBaseTime);
+   * kit.play();
+   * ```
+   */
+  useClock(options: ClockOptions): Promise<void>
+  /**
+   * Publishes the pipeline clock on the network, so other machines can
+   * follow it with `useClock({ kind: "net" })`
+   *
+   * # Arguments
+   * * `port` - UDP port to serve the clock on (0 picks a free port)
+   *
+   * # Returns
+   * * `Result<u32>` - The port the clock is served on
+   *
+   * # Example
+   * ```javascript
+   * const port = kit.publishClock(8554);
+   * ```
+   */
+  publishClock(port: number): number
+  /**
+   * Returns the current time of the pipeline clock in nanoseconds
+   *
+   * # Returns
+   * * `Result<i64>` - Clock time in nanoseconds
+   */
+  getClockTime(): number
+  /**
+   * Returns the base time of the pipeline in nanoseconds
+   *
+   * # Returns
+   * * `Result<i64>` - Base time in nanoseconds, or -1 if not set
+   */
+  getBaseTime(): number
+  /**
+   * Sets the base time of the pipeline in nanoseconds
+   *
+   * Automatic base time selection is disabled, so pipelines sharing a clock
+   * and a base time render the same running time at the same moment.
+   *
+   * # Arguments
+   * * `base_time_ns` - Base time in nanoseconds of the pipeline clock
+   *
+   * # Example
+   * ```javascript
+   * kit.setBaseTime(kit.getClockTime() + 500_000_000);
+   * ```
+   */
+  setBaseTime(baseTimeNs: number): void
+  /**
+   * Delivers the buffers leaving an element to a callback, without changing
+   * the launch string
+   *
+   * A `tee ! queue ! appsink` branch is spliced after the element's source
+   * pad, also while the pipeline is running. The tap queue is leaky, so a
+   * slow callback drops tapped buffers instead of stalling the pipeline.
+   *
+   * # Arguments
+   * * `element_name` - The name of the element to tap
+   * * `callback` - Called with the data of each buffer
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("videotestsrc ! videoconvert name=conv ! autovideosink");
+   * kit.addFrameTap("conv", (frame) => console.log("Frame of size:", frame.length));
+   * kit.play();
+   * ```
+   */
+  addFrameTap(elementName: string, callback: ((arg: Buffer) => void)): void
+  /**
+   * Taps the raw video frames leaving an element, split into their planes
+   *
+   * Works like `addFrameTap`, but delivers `FrameData` with the format,
+   * size and per-plane stride of each frame. When `format` is given the
+   * tapped frames are converted to it (e.g. "I420" or "NV12" for models
+   * fed with YUV); otherwise they keep the format they have in the pipeline
+   * and no conversion happens. Remove the tap with `removeFrameTap`.
+   *
+   * # Arguments
+   * * `element_name` - The name of an element producing raw video
+   * * `format` - Optional raw video format the tapped frames are converted to
+   * * `callback` - Called with each frame
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("videotestsrc ! videoconvert name=conv ! autovideosink");
+   * kit.addPlanarFrameTap("conv", "I420", (frame) => {
+   *   const luma = frame.planes[0];
+   *   model.feed(luma.data, luma.stride, frame.width, frame.height);
+   * });
+   * kit.play();
+   * ```
+   */
+  addPlanarFrameTap(elementName: string, format: string | undefined | null, callback: ((arg: FrameData) => void)): void
+  /**
+   * Scans the frames leaving an element for QR codes
+   *
+   * Frames are converted to grayscale in a tapped branch, as with
+   * `addFrameTap`, so the pipeline itself is not slowed down; frames are
+   * skipped while the previous one is still being scanned. Remove the
+   * detector with `removeFrameTap`.
+   *
+   * # Arguments
+   * * `element_name` - The name of an element producing raw video
+   * * `callback` - Called with each code found, once per frame it appears in
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("v4l2src ! videoconvert name=conv ! autovideosink");
+   * kit.addQrDetector("conv", ({ payload, x, y }) => console.log(payload, "at", x, y));
+   * kit.play();
+   * ```
+   */
+  addQrDetector(elementName: string, callback: ((arg: QrDetection) => void)): void
+  /**
+   * Removes a frame tap added with `addFrameTap` and restores the original link
+   *
+   * # Arguments
+   * * `element_name` - The name of the tapped element
+   *
+   * # Returns
+   * * `bool` - Whether the element was tapped
+   *
+   * # Example
+   * ```javascript
+   * kit.removeFrameTap("conv");
+   * ```
+   */
+  removeFrameTap(elementName: string): boolean
+  /**
+   * Sets the caps of a capsfilter (or any element with a "caps" property),
+   * renegotiating the running pipeline
+   *
+   * # Arguments
+   * * `element_name` - The name of the capsfilter element
+   * * `caps` - A caps string, e.g. "video/x-raw,width=640,height=360"
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("videotestsrc ! videoscale ! capsfilter name=size ! appsink name=sink");
+   * kit.setCapsFilter("size", "video/x-raw,width=640,height=360");
+   * ```
+   */
+  setCapsFilter(elementName: string, caps: string): void
+  /**
+   * Replaces the pipeline with a `playbin` playing a list of media gaplessly
+   *
+   * The next entry is queued when the current one is about to finish, so
+   * there is no gap between tracks. Use `onTrackChange` to follow playback.
+   *
+   * # Arguments
+   * * `uris` - Media URIs or file paths, in playback order
+   * * `options` - Optional video and audio sinks
+   *
+   * # Example
+   * ```javascript
+   * kit.setPlaylist(["intro.ogg", "song.ogg", "https://example.com/outro.ogg"]);
+   * kit.onTrackChange(({ index, uri }) => console.log("Now playing", index, uri));
+   * kit.play();
+   * ```
+   */
+  setPlaylist(uris: Array<string>, options?: PlaylistOptions | undefined | null): void
+  /**
+   * Sets the callback called when playback moves to another playlist entry
+   *
+   * # Arguments
+   * * `callback` - Called with the index and URI of the entry now playing
+   */
+  onTrackChange(callback: ((arg: TrackChange) => void)): void
+  /**
+   * Moves to the next playlist entry
+   *
+   * # Returns
+   * * `Result<u32>` - Index of the new entry
+   *
+   * # Example
+   * ```javascript
+   * kit.next();
+   * ```
+   */
+  next(): number
+  /**
+   * Moves to the previous playlist entry
+   *
+   * # Returns
+   * * `Result<u32>` - Index of the new entry
+   */
+  previous(): number
+  /** Returns the index of the playlist entry currently playing */
+  getPlaylistIndex(): number
+  /**
+   * Checks if the pipeline has been initialized
+   *
+   * # Returns
+   * * `bool` - true if pipeline is initialized, false otherwise
+   *
+   * # Example
+   * ```javascript
+   * if (kit.isInitialized()) {
+   *   console.log("Pipeline is ready");
+   * }
+   * ```
+   */
+  isInitialized(): boolean
+  /**
+   * Cleans up and releases the pipeline
+   *
+   * This method stops the pipeline and releases all resources.
+   * After calling this, you must call `setPipeline` again to use the kit.
+   *
+   * # Example
+   * ```javascript
+   * kit.cleanup();
+   * ```
+   */
+  cleanup(): void
+}
+
+/** Muxes live encoded frames into Media Source Extensions segments */
+export declare class MseStreamer {
+  /**
+   * Creates a streamer; nothing is muxed until the first frame
+   *
+   * # Arguments
+   * * `options` - Codec, frame size and encoder settings
+   *
+   * # Example
+   * ```javascript
+   * const streamer = new MseStreamer({ codec: "vp8", width: 1280, height: 720, framerate: 30 });
+   * ```
+   */
+  constructor(options?: MseStreamerOptions | undefined | null)
+  /** MIME type to pass to `MediaSource.addSourceBuffer` */
+  get mimeType(): string
+  /**
+   * Sets the callback receiving the initialization and media segments
+   *
+   * Segments produced before a callback is set are not delivered, apart
+   * from the initialization segment, which `initSegment` keeps.
+   *
+   * # Arguments
+   * * `callback` - Called with every segment, in order
+   *
+   * # Example
+   * ```javascript
+   * streamer.onSegment((segment) => {
+   *   for (const client of wss.clients) client.send(segment.data);
+   * });
+   * ```
+   */
+  onSegment(callback: SegmentCallback): void
+  /**
+   * Returns the initialization segment, or null until the first media
+   * segment has started; send it to each client before any media segment
+   *
+   * # Example
+   * ```javascript
+   * wss.on("connection", (ws) => ws.send(streamer.initSegment()));
+   * ```
+   */
+  initSegment(): Buffer | null
+  /** Number of media segments produced so far */
+  get segments(): number
+  /**
+   * Pushes an encoded frame
+   *
+   * Frames before the first keyframe are dropped, since a decoder cannot
+   * start with them.
+   *
+   * # Arguments
+   * * `data` - The frame in the streamer's codec
+   * * `timestamp_ns` - Presentation time of the frame in nanoseconds
+   *   (default: one frame after the previous one)
+   * * `keyframe` - Whether the frame is a keyframe (default: false)
+   *
+   * # Returns
+   * * `Result<bool>` - Whether the frame was muxed
+   *
+   * # Example
+   * ```javascript
+   * encoder.on("frame", (frame) => streamer.pushFrame(frame.data, frame.pts, frame.keyframe));
+   * ```
+   */
+  pushFrame(data: Buffer, timestampNs?: number | undefined | null, keyframe?: boolean | undefined | null): boolean
+  /**
+   * Streams the frames reaching an AppSink instead of pushed frames
+   *
+   * Raw frames are encoded to the streamer's codec; frames already in it
+   * are muxed as they are. The sink's callbacks are taken over until the
+   * streamer ends.
+   *
+   * # Arguments
+   * * `kit` - The kit running the pipeline
+   * * `sink_name` - The name of the AppSink element
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+   * streamer.attach(kit, "sink");
+   * kit.play();
+   * ```
+   */
+  attach(kit: GstKit, sinkName: string): void
+  /**
+   * Ends the stream, delivering the last media segment
+   *
+   * # Example
+   * ```javascript
+   * streamer.end();
+   * ```
+   */
+  end(): void
+}
+
+/** Pipeline buffering and connection events, created by `watchNetwork` */
+export declare class NetworkWatcher {
+  /**
+   * Sets the callback receiving buffering and connection events
+   *
+   * # Arguments
+   * * `callback` - Called with every event
+   *
+   * # Example
+   * ```javascript
+   * watcher.onEvent((event) => {
+   *   if (event.eventType === "buffering") spinner.show(event.percent < 100);
+   * });
+   * ```
+   */
+  onEvent(callback: NetworkCallback): void
+  /** Fill level of the buffer from 0 to 100 at the last buffering message */
+  get buffering(): number
+  /** Whether the network sources are connected, as far as known */
+  get connected(): boolean
+  /** Stops watching; playback paused for buffering is not resumed */
+  stop(): void
+}
+
+/**
+ * Composes a linear pipeline and generates its launch string
+ *
+ * # Example
+ * ```javascript
+ * const description = new PipelineBuilder()
+ *   .source("clip.mp4")
+ *   .decode()
+ *   .convert("RGBA")
+ *   .scale(640, 360)
+ *   .appsink("out", { maxBuffers: 2, drop: true })
+ *   .build();
+ * kit.setPipeline(description);
+ * ```
+ */
+export declare class PipelineBuilder {
+  /** Creates an empty builder */
+  constructor()
+  /**
+   * Starts the pipeline with a file or URI
+   *
+   * # Arguments
+   * * `location` - A file path, or a URI such as "https://..." or "rtsp://..."
+   *
+   * # Example
+   * ```javascript
+   * builder.source("/media/clip.mp4");
+   * ```
+   */
+  source(location: string): this
+  /**
+   * Starts the pipeline with a generated test pattern
+   *
+   * # Arguments
+   * * `options` - Pattern, frame count and live setting
+   *
+   * # Example
+   * ```javascript
+   * builder.testSource({ pattern: "ball", numBuffers: 100 });
+   * ```
+   */
+  testSource(options?: TestSourceOptions | undefined | null): this
+  /**
+   * Starts the pipeline with a camera
+   *
+   * # Arguments
+   * * `device` - A V4L2 device path such as "/dev/video0"; the default
+   *   camera of the system if omitted
+   *
+   * # Example
+   * ```javascript
+   * builder.camera("/dev/video2");
+   * ```
+   */
+  camera(device?: string | undefined | null): this
+  /**
+   * Starts the pipeline with an AppSrc fed by `pushSample`
+   *
+   * # Arguments
+   * * `name` - The name of the AppSrc element
+   * * `caps` - Caps of the pushed data, e.g. "video/x-raw,format=RGBA,width=640,height=360,framerate=30/1"
+   *
+   * # Example
+   * ```javascript
+   * builder.appsrc("src", "video/x-raw,format=RGBA,width=640,height=360,framerate=30/1");
+   * ```
+   */
+  appsrc(name: string, caps?: string | undefined | null): this
+  /** Decodes the source into raw video */
+  decode(): this
+  /**
+   * Converts raw video to another pixel format
+   *
+   * # Arguments
+   * * `format` - A raw video format such as "RGBA", "BGR" or "NV12"; any
+   *   format the next element accepts if omitted
+   *
+   * # Example
+   * ```javascript
+   * builder.convert("RGBA");
+   * ```
+   */
+  convert(format?: string | undefined | null): this
+  /**
+   * Scales raw video to a size
+   *
+   * # Arguments
+   * * `width` - Width in pixels
+   * * `height` - Height in pixels
+   */
+  scale(width: number, height: number): this
+  /**
+   * Changes the frame rate of raw video by dropping or duplicating frames
+   *
+   * # Arguments
+   * * `fps` - Frames per second; 29.97 and 59.94 are taken as 30000/1001 and 60000/1001
+   */
+  rate(fps: number): this
+  /**
+   * Restricts the stream to caps
+   *
+   * # Arguments
+   * * `caps` - A caps string, e.g. "video/x-raw,width=1280,height=720"
+   */
+  caps(caps: string): this
+  /**
+   * Adds a queue, decoupling the steps before and after it into threads
+   *
+   * # Arguments
+   * * `max_buffers` - Frames the queue holds before it blocks (default: 200)
+   */
+  queue(maxBuffers?: number | undefined | null): this
+  /**
+   * Adds any element, for steps the builder has no method for
+   *
+   * # Arguments
+   * * `factory` - The element factory name, e.g. "videoflip"
+   * * `properties` - Properties of the element
+   *
+   * # Example
+   * ```javascript
+   * builder.element("videoflip", { method: "horizontal-flip" });
+   * ```
+   */
+  element(factory: string, properties?: Record<string, string | number | boolean> | undefined | null): this
+  /**
+   * Ends the pipeline with an AppSink for `pullFrame` and `pullSample`
+   *
+   * # Arguments
+   * * `name` - The name of the AppSink element
+   * * `options` - Queue size, dropping and sync settings
+   *
+   * # Example
+   * ```javascript
+   * builder.appsink("out", { maxBuffers: 1, drop: true });
+   * ```
+   */
+  appsink(name: string, options?: BuilderSinkOptions | undefined | null): this
+  /**
+   * Ends the pipeline with a display window or another sink element
+   *
+   * # Arguments
+   * * `factory` - The sink element factory name (default: "autovideosink")
+   */
+  sink(factory?: string | undefined | null): this
+  /**
+   * Checks the pipeline and returns its launch string
+   *
+   * # Returns
+   * * `Result<String>` - The pipeline description, for `setPipeline`
+   *
+   * # Example
+   * ```javascript
+   * kit.setPipeline(new PipelineBuilder().testSource().convert("RGBA").appsink("out").build());
+   * ```
+   */
+  build(): string
+}
+
+/**
+ * Manager for multiple named GStreamer pipelines
+ *
+ * # Example
+ * ```javascript
+ * const manager = new PipelineManager();
+ * manager.onEvent((event) => console.log(event.pipelineId, event.eventType));
+ * manager.addPipeline("cam1", "v4l2src device=/dev/video0 ! autovideosink");
+ * manager.addPipeline("cam2", "v4l2src device=/dev/video1 ! autovideosink");
+ * manager.playAll();
+ * ```
+ */
+export declare class PipelineManager {
+  /**
+   * Creates an empty manager and starts its main loop thread
+   *
+   * # Example
+   * ```javascript
+   * const manager = new PipelineManager();
+   * ```
+   */
+  constructor()
+  /**
+   * Sets the callback receiving the bus messages of every pipeline
+   *
+   * # Arguments
+   * * `callback` - Called with each event, tagged with its pipeline id
+   *
+   * # Example
+   * ```javascript
+   * manager.onEvent((event) => {
+   *   if (event.eventType === "error") console.error(event.pipelineId, event.message);
+   * });
+   * ```
+   */
+  onEvent(callback: ((arg: ManagedPipelineEvent) => void)): void
+  /**
+   * Adds a pipeline from a launch string under the given id
+   *
+   * # Arguments
+   * * `id` - Unique name of the pipeline
+   * * `pipeline_string` - A valid GStreamer pipeline description
+   *
+   * # Example
+   * ```javascript
+   * manager.addPipeline("preview", "videotestsrc ! autovideosink");
+   * ```
+   */
+  addPipeline(id: string, pipelineString: string): void
+  /**
+   * Stops and removes a pipeline
+   *
+   * # Returns
+   * * `bool` - Whether a pipeline with this id existed
+   */
+  removePipeline(id: string): boolean
+  /** Returns the ids of all managed pipelines */
+  listPipelines(): Array<string>
+  /** Starts playback of a pipeline */
+  play(id: string): void
+  /** Pauses a pipeline */
+  pause(id: string): void
+  /** Stops a pipeline and resets it to the Null state */
+  stop(id: string): void
+  /** Starts playback of every pipeline */
+  playAll(): void
+  /** Stops every pipeline */
+  stopAll(): void
+  /**
+   * Returns the state and timing of every pipeline, sorted by id
+   *
+   * # Example
+   * ```javascript
+   * for (const stats of manager.statsAll()) {
+   *   console.log(stats.id, stats.state, stats.position);
+   * }
+   * ```
+   */
+  statsAll(): Array<ManagedPipelineStats>
+}
+
+/** A recording started by `recordFromPipeline` */
+export declare class PipelineRecorder {
+  /** Number of frames recorded so far */
+  get frames(): number
+  /**
+   * Stops recording and finalizes the output file
+   *
+   * # Returns
+   * * `Result<RecordingStats>` - Frames, duration and size of the recording
+   *
+   * # Example
+   * ```javascript
+   * const { frames, bytes } = recorder.stop();
+   * ```
+   */
+  stop(): RecordingStats
+}
+
+/**
+ * Screen, webcam and microphone recording into a file or stream
+ *
+ * # Example
+ * ```javascript
+ * const recorder = new ScreenRecorder({
+ *   output: "capture.mp4",
+ *   region: { x: 0, y: 0, width: 1920, height: 1080 },
+ *   fps: 30,
+ *   videoBitrate: 6000,
+ *   webcam: { corner: "bottom-right" },
+ *   microphone: "default",
+ * });
+ * recorder.start();
+ * // ...
+ * const { duration, bytes } = recorder.stop();
+ * ```
+ */
+export declare class ScreenRecorder {
+  /**
+   * Builds the recording pipeline; nothing is captured until `start`
+   *
+   * # Arguments
+   * * `options` - Output, region, frame rate, bitrate, webcam and microphone
+   *
+   * # Example
+   * ```javascript
+   * const recorder = new ScreenRecorder({ output: "rtmp://live.example.com/app/key" });
+   * ```
+   */
+  constructor(options: ScreenRecorderOptions)
+  /**
+   * Starts capturing and encoding
+   *
+   * # Example
+   * ```javascript
+   * recorder.start();
+   * ```
+   */
+  start(): void
+  /** Number of video frames encoded so far */
+  get frames(): number
+  /**
+   * Shows or hides the webcam picture
+   *
+   * # Arguments
+   * * `visible` - Whether the webcam is drawn over the screen
+   */
+  setWebcamVisible(visible: boolean): void
+  /**
+   * Mutes or unmutes the microphone
+   *
+   * # Arguments
+   * * `muted` - Whether silence is recorded instead of the microphone
+   */
+  setMicrophoneMuted(muted: boolean): void
+  /**
+   * Changes the video bitrate while recording
+   *
+   * # Arguments
+   * * `bps` - The target bitrate in bit/s
+   *
+   * # Returns
+   * * `Result<f64>` - The bitrate applied, in bit/s
+   *
+   * # Example
+   * ```javascript
+   * recorder.setTargetBitrate(2_500_000);
+   * ```
+   */
+  setTargetBitrate(bps: number): number
+  /**
+   * Adapts the video bitrate to the network of a live stream
+   *
+   * SRT outputs are adapted from their packet loss and bandwidth estimate,
+   * and RTMP and SRT outputs from the data waiting to be sent.
+   *
+   * # Arguments
+   * * `options` - Bitrate limits and congestion thresholds
+   *
+   * # Returns
+   * * `Result<BitrateAdapter>` - The running adapter
+   *
+   * # Example
+   * ```javascript
+   * const adapter = recorder.adaptBitrate({ minBitrate: 800_000 });
+   * adapter.onChange((change) => console.log(change.reason, change.bitrate));
+   * ```
+   */
+  adaptBitrate(options?: AdaptiveBitrateOptions | undefined | null): BitrateAdapter
+  /**
+   * Stops recording and finalizes the file or ends the stream
+   *
+   * # Returns
+   * * `Result<RecordingStats>` - Frames, duration and, for files, size of
+   *   the recording
+   *
+   * # Example
+   * ```javascript
+   * const { frames, duration, bytes } = recorder.stop();
+   * ```
+   */
+  stop(): RecordingStats
+}
+
+/** Reads SEI user data from an encoded stream, created by `extractSei` */
+export declare class SeiExtractor {
+  /**
+   * Sets the callback receiving each message as it passes; messages
+   * delivered to it are not returned by `next`
+   *
+   * # Arguments
+   * * `callback` - Called with every message
+   *
+   * # Example
+   * ```javascript
+   * extractor.onMetadata((metadata) => console.log(metadata.timestamp, metadata.data.toString()));
+   * ```
+   */
+  onMetadata(callback: MetadataCallback): void
+  /**
+   * Returns the next message, waiting for one up to `timeoutMs`
+   *
+   * # Arguments
+   * * `timeout_ms` - Timeout in milliseconds (default: 100, use 0 for non-blocking)
+   *
+   * # Returns
+   * * `Option<TimedMetadata>` - The message, or null if none arrived in time
+   *
+   * # Example
+   * ```javascript
+   * const metadata = extractor.next(1000);
+   * if (metadata) console.log(JSON.parse(metadata.data.toString()));
+   * ```
+   */
+  next(timeoutMs?: number | undefined | null): TimedMetadata | null
+  /** Stops reading metadata */
+  stop(): void
+}
+
+/** Inserts SEI user data into an encoded stream, created by `injectSei` */
+export declare class SeiInjector {
+  /**
+   * Queues metadata for the stream
+   *
+   * The metadata is carried by the first access unit presented at or
+   * after `timestampNs`, or by the next one to pass without a timestamp.
+   *
+   * # Arguments
+   * * `data` - Bytes or a string (stored as UTF-8), up to 64 KiB
+   * * `timestamp_ns` - Presentation time in nanoseconds, in stream time
+   *   as reported by `getPosition`
+   *
+   * # Example
+   * ```javascript
+   * injector.push(JSON.stringify({ lat: 48.85, lon: 2.35 }), kit.getPosition());
+   * ```
+   */
+  push(data: Buffer | string, timestampNs?: number | undefined | null): void
+  /** Number of messages waiting for their access unit */
+  get pending(): number
+  /** Number of messages inserted into the stream so far */
+  get injected(): number
+  /** Stops inserting metadata; pending messages are dropped */
+  stop(): void
+}
+
+/**
+ * Background file-to-file transcode with progress reporting and cancellation
+ *
+ * # Example
+ * ```javascript
+ * const job = new TranscodeJob("input.mp4", "output.webm", { container: "webm" });
+ * job.onProgress(({ state, percent }) => console.log(state, percent.toFixed(1)));
+ * job.start();
+ * process.on("SIGINT", () => job.cancel());
+ * ```
+ */
+export declare class TranscodeJob {
+  /**
+   * Creates a job transcoding one file into another
+   *
+   * # Arguments
+   * * `input` - Path of the input file
+   * * `output` - Path of the output file; see `overwrite` for when it exists
+   * * `options` - Output container and codec settings
+   *
+   * # Example
+   * ```javascript
+   * const job = new TranscodeJob("camera.mov", "camera.mp4", { container: "mp4", videoCodec: "copy" });
+   * ```
+   */
+  constructor(input: string, output: string, options: TranscodeOptions)
+  /**
+   * Sets the callback receiving progress updates, about four times a second
+   * while running and once when the job ends
+   *
+   * # Arguments
+   * * `callback` - Called with the job progress
+   */
+  onProgress(callback: ((arg: TranscodeProgress) => void)): void
+  /** Starts transcoding in the background */
+  start(): void
+  /**
+   * Stops the job and deletes the partially written output
+   *
+   * # Returns
+   * * `bool` - Whether the job was pending or running
+   */
+  cancel(): boolean
+  /**
+   * Path the output is written to, which differs from the one asked for
+   * when `overwrite` is "rename"
+   */
+  get outputPath(): string
+  /** Returns the current progress of the job */
+  getProgress(): TranscodeProgress
+  /** Checks if the job has ended, successfully or not */
+  isFinished(): boolean
+}
+
+/**
+ * Streaming transcoder fed from JavaScript
+ *
+ * `TranscodeStream` accepts encoded input chunks through `write` and produces
+ * chunks of the transcoded container, which are either pulled with `read` or
+ * delivered to a callback registered with `onData`.
+ */
+export declare class TranscodeStream {
+  /**
+   * Creates a new transcoder and starts its pipeline
+   *
+   * # Arguments
+   * * `options` - Output container and codec settings
+   *
+   * # Example
+   * ```javascript
+   * const stream = new TranscodeStream({ container: "webm", videoCodec: "vp8" });
+   * ```
+   */
+  constructor(options: TranscodeOptions)
+  /**
+   * Writes a chunk of input media
+   *
+   * # Arguments
+   * * `chunk` - The next chunk of the input file
+   *
+   * # Example
+   * ```javascript
+   * readable.on("data", (chunk) => stream.write(chunk));
+   * ```
+   */
+  write(chunk: Buffer): void
+  /**
+   * Signals that all input has been written
+   *
+   * # Example
+   * ```javascript
+   * readable.on("end", () => stream.end());
+   * ```
+   */
+  end(): void
+  /**
+   * Reads the next chunk of transcoded output
+   *
+   * # Arguments
+   * * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+   *
+   * # Returns
+   * * `Result<Option<Buffer>>` - The next output chunk, or null if none is available yet
+   *
+   * # Example
+   * ```javascript
+   * while (!stream.isFinished()) {
+   *   const chunk = stream.read(100);
+   *   if (chunk) writable.write(chunk);
+   * }
+   * ```
+   */
+  read(timeoutMs?: number | undefined): Buffer | null
+  /**
+   * Delivers every transcoded output chunk to a callback
+   *
+   * Once a callback is registered, chunks are no longer available through `read`.
+   *
+   * # Arguments
+   * * `callback` - A JavaScript function called with each output chunk
+   *
+   * # Example
+   * ```javascript
+   * stream.onData((chunk) => writable.write(chunk));
+   * ```
+   */
+  onData(callback: ((arg: Buffer) => void)): void
+  /**
+   * Checks if all transcoded output has been produced
+   *
+   * # Returns
+   * * `bool` - true once the output reached end of stream
+   */
+  isFinished(): boolean
+}
+
+/** A file being pushed into an AppSrc, started by `playTranscodedInto` */
+export declare class TranscodedFeed {
+  /** Number of frames (or audio buffers) pushed so far */
+  get frames(): number
+  /** Whether the whole file has been pushed */
+  get finished(): boolean
+  /**
+   * Stops pushing frames. End of stream is not sent to the AppSrc.
+   *
+   * # Returns
+   * * `Result<()>` - An error if decoding the file failed
+   *
+   * # Example
+   * ```javascript
+   * feed.stop();
+   * ```
+   */
+  stop(): void
+}
+
+/**
+ * Motion and scene change rules running on an AppSink, created by
+ * `watchTriggers`
+ */
+export declare class TriggerWatcher {
+  /**
+   * Sets the callback announcing recordings, snapshots and errors
+   *
+   * # Arguments
+   * * `callback` - Called with every event
+   *
+   * # Example
+   * ```javascript
+   * watcher.onEvent((event) => {
+   *   if (event.eventType === "recording-stopped") upload(event.path);
+   * });
+   * ```
+   */
+  onEvent(callback: ((arg: TriggerEvent) => void)): void
+  /** Whether a recording is in progress */
+  get recording(): boolean
+  /**
+   * Stops watching the sink, finishing the recording in progress and the
+   * pending snapshots
+   *
+   * # Example
+   * ```javascript
+   * watcher.stop();
+   * ```
+   */
+  stop(): void
+}
+
+/**
+ * Frames of an AppSink published as a webcam, created by
+ * `publishVirtualCamera`
+ */
+export declare class VirtualCamera {
+  /** Path of the device written to, or the name given with a custom sink */
+  get device(): string
+  /** Number of frames published so far */
+  get frames(): number
+  /**
+   * Stops publishing; the sink's frames can be pulled again afterwards
+   *
+   * # Returns
+   * * `Result<()>` - An error if writing to the device failed
+   *
+   * # Example
+   * ```javascript
+   * camera.stop();
+   * ```
+   */
+  stop(): void
+}
+
+/**
+ * Fragmented WebM of the frames reaching an AppSink, created by
+ * `fragmentFromPipeline`
+ */
+export declare class WebmFragmenter {
+  /**
+   * Returns the initialization segment, or null until the first fragment
+   * is complete
+   *
+   * # Example
+   * ```javascript
+   * sourceBuffer.appendBuffer(fragmenter.initSegment());
+   * ```
+   */
+  initSegment(): Buffer | null
+  /**
+   * Takes the oldest complete fragment
+   *
+   * # Arguments
+   * * `timeout_ms` - Time to wait for a fragment (default: 0, don't wait)
+   *
+   * # Returns
+   * * `Option<WebmFragment>` - The fragment, or null if none is ready
+   *
+   * # Example
+   * ```javascript
+   * let fragment;
+   * while ((fragment = fragmenter.nextFragment(2000))) socket.send(fragment.data);
+   * ```
+   */
+  nextFragment(timeoutMs?: number | undefined | null): WebmFragment | null
+  /** Number of fragments dropped because they were not pulled in time */
+  get dropped(): number
+  /** Number of frames fragmented so far */
+  get frames(): number
+  /**
+   * Detaches from the sink and ends the stream; the last fragment can
+   * still be pulled
+   *
+   * # Example
+   * ```javascript
+   * fragmenter.stop();
+   * const last = fragmenter.nextFragment();
+   * ```
+   */
+  stop(): void
+}
+
+/** Options for `adaptBitrate` */
+export interface AdaptiveBitrateOptions {
+  /** Lowest bitrate in bit/s (default: 300000) */
+  minBitrate?: number
+  /** Highest bitrate in bit/s (default: the bitrate when adaptation starts) */
+  maxBitrate?: number
+  /** Time between adjustments in milliseconds (default: 1000) */
+  intervalMs?: number
+  /**
+   * Share of lost packets above which the network counts as congested
+   * (default: 0.02)
+   */
+  maxLossRate?: number
+  /**
+   * Queued data in milliseconds above which the network counts as
+   * congested (default: 500)
+   */
+  maxQueueMs?: number
+  /**
+   * Name of the SRT sink to read statistics from (default: the first
+   * `srtsink` of the pipeline)
+   */
+  sink?: string
+  /**
+   * Name of the queue in front of the network sink whose fill level is
+   * watched (default: none)
+   */
+  queue?: string
+}
+
+/** Options for `animateProperty` */
+export interface AnimationOptions {
+  /**
+   * How values between keyframes are computed: "linear", "cubic",
+   * "cubic-monotonic" (cubic without overshoot) or "step" (default:
+   * "linear")
+   */
+  interpolation?: string
+}
+
+/** A file embedded in a media file */
+export interface AttachedFile {
+  /** Stored file name */
+  name: string
+  /** MIME type */
+  mimeType: string
+  /** Description, if one was stored */
+  description?: string
+  /** Contents of the file */
+  data: Buffer
+}
+
+/** A file to embed in the output */
+export interface Attachment {
+  /** Path of the file to embed */
+  path: string
+  /** File name stored in the container (default: the name of `path`) */
+  name?: string
+  /** MIME type (default: guessed from the file extension) */
+  mimeType?: string
+  /** Free-form description */
+  description?: string
+}
+
+/** Fade in and out lengths */
+export interface AudioFade {
+  /** Fade-in length in milliseconds from the start of the output */
+  inMs?: number
+  /** Fade-out length in milliseconds before the end of the output */
+  outMs?: number
+}
+
+/** Volume, mute state and filters of a mixer input */
+export interface AudioInputOptions {
+  /** Linear gain, where 1 keeps the input level (default: 1) */
+  volume?: number
+  /** Whether the input is silenced (default: false) */
+  mute?: boolean
+  /**
+   * Audio filters applied to the input before it is measured and mixed,
+   * e.g. `["gain=-6", "fadein=2"]`
+   */
+  filters?: Array<string>
+}
+
+/** Options for the `AudioMixer` constructor */
+export interface AudioMixerOptions {
+  /** Output sample rate in Hz (default: 48000) */
+  rate?: number
+  /** Output channel count (default: 2) */
+  channels?: number
+  /** Sink description receiving the mixed audio (default: "autoaudiosink") */
+  output?: string
+}
+
+/** Options for `processAudio` */
+export interface AudioProcessOptions {
+  /** Time range to keep */
+  trim?: AudioTrim
+  /** Fade in and out */
+  fade?: AudioFade
+  /**
+   * Peak level to normalize to, in dBFS (e.g. -1). The input is scanned once
+   * to measure its peak before it is written.
+   */
+  normalize?: number
+  /**
+   * Output format ("wav", "flac", "opus", "ogg", "mp3" or "m4a"); defaults to
+   * the output file extension
+   */
+  format?: string
+  /** Target bitrate in kbit/s, for lossy formats */
+  bitrate?: number
+}
+
+/** Summary of a `processAudio` run */
+export interface AudioProcessResult {
+  /** Length of the written audio in milliseconds */
+  durationMs: number
+  /** Sample rate of the written audio in Hz */
+  sampleRate: number
+  /** Channel count of the written audio */
+  channels: number
+  /** Gain applied by normalization in dB (0 without normalization) */
+  gainDb: number
+}
+
+/** Options for `pullAudioSample` */
+export interface AudioPullOptions {
+  /** Timeout in milliseconds (default: 100ms, use 0 for non-blocking) */
+  timeoutMs?: number
+  /** Also return the samples as one Float32Array per channel (default: false) */
+  deinterleave?: boolean
+}
+
+/** Audio sample pulled from an AppSink, with its parsed caps */
+export interface AudioSample {
+  /** The raw sample data as a buffer */
+  data: Buffer
+  /** Sample format (e.g. "S16LE", "F32LE") */
+  format: string
+  /** Sample rate in Hz */
+  rate: number
+  /** Number of channels */
+  channels: number
+  /** Channel layout ("interleaved" or "non-interleaved") */
+  layout: string
+  /** Timestamp of the sample in nanoseconds, or -1 if unknown */
+  timestamp: number
+  /** One array of samples in [-1, 1] per channel, if requested */
+  channelData?: Array<Float32Array>
+}
+
+/** An audio stream of a media file */
+export interface AudioStreamInfo {
+  /** Human readable codec name, e.g. "Opus" */
+  codec: string
+  /** Caps of the stream */
+  caps: string
+  /** Number of channels */
+  channels: number
+  /** Sample rate in Hz */
+  sampleRate: number
+  /** Bitrate in bit/s, or 0 if unknown */
+  bitrate: number
+  /** Language code, if tagged */
+  language?: string
+}
+
+/** Time range of the (concatenated) input to keep */
+export interface AudioTrim {
+  /** Start of the range in milliseconds (default: 0) */
+  startMs?: number
+  /** End of the range in milliseconds (default: end of the input) */
+  endMs?: number
+}
+
+/** Audio/video offset at one minute of a file */
+export interface AvSyncOffset {
+  /** Minute of the audio, from 0 */
+  minute: number
+  /**
+   * Drift in nanoseconds at the start of the minute, positive when the
+   * audio is heard ahead of the video
+   */
+  offset: number
+}
+
+/** Audio/video synchronization of a file */
+export interface AvSyncReport {
+  /** First audio timestamp minus first video timestamp, in nanoseconds */
+  startOffset: number
+  /**
+   * Drift of largest magnitude found, in nanoseconds, positive when the
+   * audio is heard ahead of the video
+   */
+  maxDrift: number
+  /** Drift at the end of the audio, in nanoseconds */
+  finalDrift: number
+  /** Drift at the start of every minute of the audio */
+  offsets: Array<AvSyncOffset>
+  /**
+   * Number of audio buffers whose timestamp did not follow on from the
+   * previous one
+   */
+  discontinuities: number
+}
+
+/** Options for `runBenchmark` */
+export interface BenchmarkOptions {
+  /** Frame size as "WIDTHxHEIGHT" (default: "1280x720") */
+  resolution?: string
+  /** Seconds of 30 fps test content to process per run (default: 2) */
+  seconds?: number
+  /** Video codecs to benchmark (default: every supported codec) */
+  codecs?: Array<string>
+  /** Encoder thread counts to try (default: 1 and the number of CPUs) */
+  threadCounts?: Array<number>
+}
+
+/** A single benchmark measurement */
+export interface BenchmarkResult {
+  /** Codec or filter name */
+  name: string
+  /** The measured operation ("encode", "decode" or "filter") */
+  operation: string
+  /** Encoder thread count, if the operation was run with a fixed count */
+  threads?: number
+  /** Frames processed */
+  frames: number
+  /** Achieved frames per second, or 0 if the run failed */
+  fps: number
+  /** Why the run failed (e.g. the encoder is not installed) */
+  error?: string
+}
+
+/** A bitrate change made by an adapter */
+export interface BitrateChange {
+  /** The new bitrate in bit/s */
+  bitrate: number
+  /** The bitrate before the change in bit/s */
+  previous: number
+  /** "congestion", "bandwidth" (above the estimated bandwidth) or "recovery" */
+  reason: string
+  /** Share of the packets lost over the last interval, from the SRT sink */
+  lossRate?: number
+  /** Round-trip time in milliseconds, from the SRT sink */
+  rttMs?: number
+  /** Estimated bandwidth in bit/s, from the SRT sink */
+  bandwidth?: number
+  /** Data waiting in the watched queue in milliseconds */
+  queueMs?: number
+}
+
+/** A compressed frame of a media file */
+export interface BitstreamFrame {
+  /** Index of the stream, in the order the demuxer exposed it */
+  stream: number
+  /** Media type of the stream, e.g. "video/x-h264" or "audio/x-opus" */
+  codec: string
+  /** Presentation timestamp in nanoseconds, or -1 if unknown */
+  pts: number
+  /** Decoding timestamp in nanoseconds, or -1 if unknown */
+  dts: number
+  /** Duration in nanoseconds, or -1 if unknown */
+  duration: number
+  /** Size in bytes */
+  size: number
+  /** Whether the frame can be decoded on its own */
+  keyframe: boolean
+  /** NAL unit types (H.264, H.265) or OBU types (AV1) in the frame, in order */
+  units: Array<string>
+}
+
+/** How the native module was built */
+export interface BuildInfo {
+  /** Version of the native crate */
+  version: string
+  /** Cargo features enabled at build time */
+  features: Array<string>
+  /** Version of the GStreamer library loaded at runtime, e.g. "1.24.2" */
+  gstreamerVersion: string
+  /** Best SIMD instruction set of the CPU ("avx512", "avx2", "sse4.2", "sse2", "neon" or "none") */
+  simd: string
+  /** Short git commit hash of the build, or null if unknown */
+  gitHash?: string
+  /** Rust target triple of the build */
+  target: string
+  /** Whether this is a debug build */
+  debug: boolean
+}
+
+/** Options for `PipelineBuilder.appsink` */
+export interface BuilderSinkOptions {
+  /** Frames queued in the sink before it blocks or drops (default: unlimited) */
+  maxBuffers?: number
+  /**
+   * Drop the oldest frame instead of blocking when the queue is full
+   * (default: false)
+   */
+  drop?: boolean
+  /**
+   * Deliver frames at their playback time instead of as fast as possible
+   * (default: true)
+   */
+  sync?: boolean
+}
+
+/** Capabilities of the GStreamer installation this module runs on */
+export interface Capabilities {
+  /** Runtime GStreamer version, e.g. "1.24.2" */
+  gstreamerVersion: string
+  /** Registered codecs, whether or not they are usable */
+  codecs: Array<CodecCapability>
+  /** Supported containers */
+  containers: Array<ContainerCapability>
+  /** Raw video formats `videoconvert` can convert between */
+  pixelFormats: Array<string>
+}
+
+/** A frame captured by a source of a session */
+export interface CaptureFrame {
+  /** Id of the source that captured the frame */
+  sourceId: string
+  /**
+   * Time of the capture since the session started, in nanoseconds, or -1
+   * if unknown; the same for all sources
+   */
+  sessionTimestamp: number
+  /** Number of the frame in its source, from 0 */
+  sequence: number
+  /** The frame; its `timestamp` is the source's own */
+  frame: FrameData
+}
+
+/** Options for the `CaptureSession` constructor */
+export interface CaptureSessionOptions {
+  /** Raw video format of the emitted frames (default: "RGBA") */
+  format?: string
+  /**
+   * Delay between `start` and the session time 0, in milliseconds, leaving
+   * the sources time to start (default: 200)
+   */
+  startDelayMs?: number
+}
+
+/** Frame count and timing of a session source */
+export interface CaptureSourceStats {
+  /** Id of the source */
+  id: string
+  /** Current state ("Null", "Ready", "Paused" or "Playing") */
+  state: string
+  /** Frames captured since the session started */
+  frames: number
+  /** Session timestamp of the last frame in nanoseconds, or -1 */
+  lastTimestamp: number
+}
+
+/** Options for `extractClip` */
+export interface ClipOptions {
+  /**
+   * "copy" (default) remuxes without re-encoding, cutting on keyframes;
+   * "reencode" decodes and re-encodes the range frame-accurately; "smart"
+   * re-encodes only the video before the first keyframe of the range
+   */
+  mode?: string
+  /** Output container; defaults to the output file extension */
+  container?: string
+  /**
+   * SubRip or WebVTT files of the input, written next to the clip trimmed
+   * to its range: "movie.en.srt" becomes "<clip name>.en.srt"
+   */
+  subtitles?: Array<string>
+}
+
+/** Clock used to drive a pipeline */
+export interface ClockOptions {
+  /** Clock type: "system", "ntp", "ptp" or "net" (a remote `publishClock` server) */
+  kind: string
+  /** Server address, for "ntp" and "net" clocks */
+  address?: string
+  /** Server port, for "ntp" (default: 123) and "net" clocks */
+  port?: number
+  /** PTP domain, for "ptp" clocks (default: 0) */
+  domain?: number
+  /** How long to wait for a network clock to synchronize (default: 5000) */
+  syncTimeoutMs?: number
+}
+
+/** Encoder backend registered with `registerCodecBackend` */
+export interface CodecBackend {
+  /** "video" or "audio" */
+  kind: string
+  /** Encoder element name, e.g. "qsvh264enc" */
+  encoder: string
+  /** Parser placed after the encoder, e.g. "h264parse" */
+  parser?: string
+  /** Name of the encoder's bitrate property */
+  bitrateProperty?: string
+  /** Factor converting kbit/s into the unit of the bitrate property (default: 1) */
+  bitrateScale?: number
+  /** Whether the encoder runs on dedicated hardware (default: false) */
+  hardware?: boolean
+}
+
+/** What the installed plugins can do with a codec */
+export interface CodecCapability {
+  /** Codec name, as accepted by `TranscodeOptions` */
+  name: string
+  /** "video" or "audio" */
+  kind: string
+  /** Whether the codec can be encoded */
+  encode: boolean
+  /** Whether the codec can be decoded */
+  decode: boolean
+  /** Installed encoder elements, in order of preference */
+  encoders: Array<string>
+  /** Installed decoder elements, best ranked first */
+  decoders: Array<string>
+  /** Whether a hardware encoder or decoder is installed */
+  hardware: boolean
+}
+
+/** Valid presets and tunes of a codec */
+export interface CodecPresets {
+  /** Accepted `preset` values */
+  presets: Array<string>
+  /** Accepted `tune` values (empty if the encoder has no tuning) */
+  tunes: Array<string>
+}
+
+/** Options for `composeComparison` */
+export interface ComparisonOptions {
+  /**
+   * "hstack" (side by side, default), "vstack" (A above B) or "wipe" (left
+   * half of A next to the right half of B)
+   */
+  layout?: string
+  /** Width each input is scaled to (default: width of the first input) */
+  width?: number
+  /** Height each input is scaled to (default: height of the first input) */
+  height?: number
+  /** Output container; defaults to the output file extension */
+  container?: string
+  /** Video codec, defaults to the usual codec for the container */
+  videoCodec?: string
+}
+
+/** Options for `analyzeComplexity` */
+export interface ComplexityOptions {
+  /** Number of frames to measure, spread over the whole input (default: 60) */
+  samples?: number
+  /** Codec the ladder is recommended for (default: "h264") */
+  codec?: string
+  /**
+   * File the scores of each measured frame are written to while the
+   * analysis runs
+   */
+  exportPath?: string
+  /** "ndjson" or "csv" (default: from the extension of `exportPath`, NDJSON unless `.csv`) */
+  exportFormat?: string
+}
+
+/** Measured complexity of a video and the ladder recommended for it */
+export interface ComplexityReport {
+  /** Source width in pixels */
+  width: number
+  /** Source height in pixels */
+  height: number
+  /** Number of frames measured */
+  framesAnalyzed: number
+  /** Spatial information: the maximum over the measured frames */
+  spatialInformation: number
+  /** Temporal information: the maximum over the measured frames */
+  temporalInformation: number
+  /** Mean spatial information of the measured frames */
+  meanSpatialInformation: number
+  /** Mean temporal information of the measured frames */
+  meanTemporalInformation: number
+  /** Overall complexity from 0 (static, flat) to 1 (busy, high motion) */
+  score: number
+  /** Recommended renditions, highest resolution first */
+  ladder: Array<LadderRung>
+}
+
+/** Options for the `Compositor` constructor */
+export interface CompositorOptions {
+  /** Output width in pixels (default: 1280) */
+  width?: number
+  /** Output height in pixels (default: 720) */
+  height?: number
+  /** Output frame rate (default: 30) */
+  framerate?: number
+  /** Background behind the inputs ("checker", "black", "white" or "transparent", default: "black") */
+  background?: string
+  /** Sink description receiving the mixed video (default: "autovideosink") */
+  output?: string
+}
+
+/** What the installed plugins can do with a container */
+export interface ContainerCapability {
+  /** Container name, as accepted by `TranscodeOptions` */
+  name: string
+  /** Whether the container can be written */
+  mux: boolean
+  /** Whether the container can be read */
+  demux: boolean
+  /** Muxer element */
+  muxer: string
+  /** Installed demuxer elements, best ranked first */
+  demuxers: Array<string>
+}
+
+/** Structure of a container file */
+export interface ContainerDump {
+  /** "matroska", "webm" or "ivf" */
+  format: string
+  /** File size in bytes */
+  size: number
+  /** Top-level elements */
+  elements: Array<ContainerElement>
+}
+
+/** An element of a container file */
+export interface ContainerElement {
+  /** Element ID in hex (e.g. "0x1A45DFA3"); empty for IVF records */
+  id: string
+  /** Element name, or "Unknown" */
+  name: string
+  /** Offset of the element header in the file */
+  offset: number
+  /** Size of the header (ID and size fields) */
+  headerSize: number
+  /** Size of the payload, or -1 if unknown (live-streamed Matroska) */
+  size: number
+  /** Decoded value of number, text and block elements */
+  value?: string
+  /** Child elements of master elements */
+  children: Array<ContainerElement>
+}
+
+/** Options for `detectCropRegion` */
+export interface CropDetectOptions {
+  /** Number of frames to scan, spread over the whole input (default: 20) */
+  samples?: number
+  /**
+   * Highest mean luma, from 0 to 255, of a row or column that is part of a
+   * bar (default: 24, black being 16 in limited range video)
+   */
+  threshold?: number
+  /** Round the size of the rectangle down to a multiple of this (default: 2) */
+  round?: number
+}
+
+/** Suggested crop of a video */
+export interface CropRegion {
+  /** Left edge of the picture */
+  x: number
+  /** Top edge of the picture */
+  y: number
+  /** Width of the picture */
+  width: number
+  /** Height of the picture */
+  height: number
+  /** Width of the frames */
+  sourceWidth: number
+  /** Height of the frames */
+  sourceHeight: number
+  /** Whether there are bars to remove */
+  cropped: boolean
+  /** The rectangle as a `crop` video filter, e.g. "crop=0,140,1920,800" */
+  filter: string
+}
+
+/** A device or permission change reported by a `DeviceWatcher` */
+export interface DeviceEvent {
+  /** "added", "removed" or "permission" */
+  eventType: string
+  /** The device, for device events */
+  device?: DeviceInfo
+  /** "video" or "audio" */
+  kind: string
+  /** The new permission, for "permission" events (see `capturePermission`) */
+  permission?: string
+}
+
+/** A camera or microphone */
+export interface DeviceInfo {
+  /** Name of the device for display */
+  name: string
+  /** GStreamer device class, e.g. "Video/Source" or "Audio/Source" */
+  deviceClass: string
+  /** "video" or "audio" */
+  kind: string
+  /** System API providing the device, e.g. "v4l2", "avf", "wasapi2" */
+  api?: string
+  /** Device path or identifier, e.g. "/dev/video0" */
+  path?: string
+  /** Caps the device can produce */
+  caps?: string
+}
+
+/** Options for `watchDevices` */
+export interface DeviceWatchOptions {
+  /** "video", "audio" or both if not set */
+  kind?: string
+  /**
+   * Interval at which permissions are checked for changes in milliseconds
+   * (default: 2000, 0 to not check)
+   */
+  permissionPollMs?: number
+}
+
+/** Options for `dumpContainer` */
+export interface DumpOptions {
+  /**
+   * Deepest nesting level of Matroska elements listed, top-level elements
+   * being level 1 (default: unlimited)
+   */
+  maxDepth?: number
+}
+
+/** Content found in both inputs of `findDuplicateSegments` */
+export interface DuplicateSegment {
+  /** Start in the first input in nanoseconds */
+  firstStart: number
+  /** End in the first input in nanoseconds */
+  firstEnd: number
+  /** Start in the second input in nanoseconds */
+  secondStart: number
+  /** End in the second input in nanoseconds */
+  secondEnd: number
+  /** Number of matching sampled frames */
+  frames: number
+  /** Mean pHash distance of the matching frames */
+  distance: number
+}
+
+/** Options for `findDuplicateSegments` */
+export interface DuplicateSegmentOptions {
+  /** Frame rate both inputs are sampled at for matching (default: 5) */
+  fps?: number
+  /** Maximum pHash distance of matching frames, 0 to 64 (default: 10) */
+  threshold?: number
+  /** Minimum length of a segment in seconds (default: 2) */
+  minDuration?: number
+}
+
+/** Statistics of a single pipeline element */
+export interface ElementStats {
+  /** The name of the element */
+  name: string
+  /** The factory the element was created from (e.g. "queue", "x264enc") */
+  factory?: string
+  /** Negotiated caps of each linked source pad */
+  caps: Array<string>
+  /** Buffers currently held, for queue elements */
+  queueBuffers?: number
+  /** Bytes currently held, for queue elements */
+  queueBytes?: number
+  /** Nanoseconds of data currently held, for queue elements */
+  queueTime?: number
+  /** Configured bitrate, for encoders (in the encoder's own unit) */
+  bitrate?: number
+  /** Frames or buffers rendered, for sinks and elements reporting QoS */
+  rendered?: number
+  /** Frames or buffers dropped, for sinks and elements reporting QoS */
+  dropped?: number
+}
+
+/** An encoder property whose value differs from its default */
+export interface EncoderSetting {
+  /** Property name */
+  name: string
+  /** Property value, serialized as in a launch string */
+  value: string
+}
+
+/** Encryption of the output frames */
+export interface EncryptionOptions {
+  /** AES-128 key as 32 hexadecimal digits */
+  key: string
+  /** IV of the first frame as 16 hexadecimal digits (default: random) */
+  iv?: string
+}
+
+/** An image written by `extractFramesToImages` */
+export interface ExportedFrame {
+  /** File name of the image, in the directory or archive */
+  name: string
+  /** Number of the frame among the exported ones, from 0 */
+  frame: number
+  /** Presentation timestamp in nanoseconds, or -1 if unknown */
+  pts: number
+  /** Size of the image in bytes */
+  size: number
+}
+
+/** Options for `playTranscodedInto` */
+export interface FeedOptions {
+  /**
+   * Push frames at the pace of their timestamps (default: true). Otherwise
+   * they are pushed as fast as the pipeline consumes them, the AppSrc
+   * being set to block when its queue is full.
+   */
+  realtime?: boolean
+  /**
+   * Stream of the file to push, "video" or "audio"; defaults to the media
+   * of the AppSrc caps, or "video"
+   */
+  media?: string
+  /** Send end of stream to the AppSrc when the file ends (default: true) */
+  endOfStream?: boolean
+}
+
+/** Options for `fragmentFromPipeline` */
+export interface FragmentOptions {
+  /**
+   * Codec raw frames are encoded to: "vp8" (default), "vp9" or "av1".
+   * Encoded frames are muxed as they are.
+   */
+  videoCodec?: string
+  /** Target video bitrate in kbit/s */
+  videoBitrate?: number
+  /** Encoder speed preset (see `listPresets`), e.g. "ultrafast" for live input */
+  preset?: string
+  /** Encoder tuning (see `listPresets`) */
+  tune?: string
+  /**
+   * Duration of each fragment of raw frames in milliseconds, i.e. the
+   * keyframe interval (default: 1000)
+   */
+  fragmentDurationMs?: number
+  /**
+   * Fragments kept for `nextFragment` before the oldest are dropped
+   * (default: 64)
+   */
+  maxQueued?: number
+}
+
+/** Options for the `FrameClock` constructor */
+export interface FrameClockOptions {
+  /** Frames per second; 29.97 and 59.94 are taken as 30000/1001 and 60000/1001 */
+  fps: number
+  /**
+   * Deliver ticks that were missed late instead of skipping them (default:
+   * false)
+   */
+  catchUp?: boolean
+  /**
+   * Microseconds before a deadline at which the clock stops sleeping and
+   * spins (default: 1000); 0 saves CPU at the cost of precision
+   */
+  spinUs?: number
+}
+
+/** Timing statistics of a `FrameClock` */
+export interface FrameClockStats {
+  /** Ticks delivered */
+  ticks: number
+  /** Ticks skipped because the clock fell more than a frame behind */
+  skipped: number
+  /** Average lateness of the ticks, in nanoseconds */
+  meanJitter: number
+  /** Standard deviation of the lateness, in nanoseconds */
+  jitterStdDev: number
+  /** Largest lateness, in nanoseconds */
+  maxJitter: number
+}
+
+/** A sub-rectangle of a frame, in pixels */
+export interface FrameCrop {
+  /** Left edge */
+  x: number
+  /** Top edge */
+  y: number
+  /** Width */
+  width: number
+  /** Height */
+  height: number
+}
+
+/** Frame data emitted from AppSink */
+export interface FrameData {
+  /** The frame data as a buffer */
+  data: Buffer
+  /** The name of the sink element */
+  sinkName: string
+  /** Timestamp of the frame in nanoseconds, or -1 if unknown */
+  timestamp: number
+  /** Raw video format of the frame, e.g. "RGBA", "I420" or "NV12" */
+  format: string
+  /** Width of the frame in pixels */
+  width: number
+  /** Height of the frame in pixels */
+  height: number
+  /**
+   * The planes of the frame, each with its own stride; a single plane for
+   * packed formats such as RGBA
+   */
+  planes: Array<FramePlane>
+  /** Metadata attached to the frame with `pushSample`, if it carries any */
+  metadata?: Buffer
+}
+
+/** Options for `startFrameEmission` and `frames` */
+export interface FrameEmissionOptions {
+  /**
+   * What to do when the consumer falls behind: "dropOldest" (default),
+   * "dropNewest", "block" or "sampleEveryN"
+   */
+  policy?: string
+  /** Frames held while the consumer is busy (default: 4) */
+  maxQueueLength?: number
+  /** Keep one frame out of this many with "sampleEveryN" (default: 2) */
+  sampleEvery?: number
+  /**
+   * Only deliver this part of each frame, packed; the origin is moved onto
+   * the chroma grid of subsampled formats (even coordinates for 4:2:0)
+   */
+  crop?: FrameCrop
+}
+
+/** Options for `extractFramesToImages` */
+export interface FrameExportOptions {
+  /** Image format: "jpeg" (default) or "png" */
+  format?: string
+  /** Frames per second to export; by default every frame is exported */
+  fps?: number
+  /** Stop after this many frames */
+  maxFrames?: number
+  /** JPEG quality from 1 to 100 (default: 85) */
+  quality?: number
+  /** Write a zip archive at the output path instead of files in a directory */
+  archive?: boolean
+  /** Encrypt the archive entries with AES-256 using this password */
+  password?: string
+}
+
+/** Perceptual hashes of one frame */
+export interface FrameHash {
+  /** Number of the frame, counting all frames after `fps` resampling */
+  frame: number
+  /** Presentation timestamp in nanoseconds, or -1 if unknown */
+  pts: number
+  /** DCT hash as 16 hexadecimal digits */
+  phash: string
+  /** Difference hash as 16 hexadecimal digits */
+  dhash: string
+}
+
+/** A plane of a raw video frame */
+export interface FramePlane {
+  /** The plane's rows, including any padding at the end of each row */
+  data: Buffer
+  /** Bytes from the start of one row to the start of the next */
+  stride: number
+  /** Width of the plane in samples (half the frame width for the chroma of I420) */
+  width: number
+  /** Number of rows of the plane */
+  height: number
+  /** Byte offset of the plane in `FrameData.data` */
+  offset: number
+}
+
+/** A frame boundary */
+export interface FrameTick {
+  /** Number of the frame since the clock started, from 0 */
+  index: number
+  /** Timestamp of the frame in nanoseconds since the clock started */
+  timestamp: number
+  /** Duration of the frame in nanoseconds */
+  duration: number
+  /** How late the tick fired after its deadline, in nanoseconds */
+  lateness: number
+}
+
+/** Options for `configureGStreamer` */
+export interface GStreamerConfig {
+  /** Directories searched for plugins before the ones in `GST_PLUGIN_PATH` */
+  pluginPaths?: Array<string>
+  /** File the plugin registry is cached in, e.g. in the app's data directory */
+  registryPath?: string
+  /**
+   * Let crashes in plugins reach the app's own crash handler instead of
+   * GStreamer's segfault trap (default: false)
+   */
+  disableSegtrap?: boolean
+}
+
+/** GPU handle of a video frame */
+export interface GpuFrameHandle {
+  /**
+   * Kind of handle: "dmabuf" (file descriptors), "gl" (texture names),
+   * "iosurface" (IOSurface IDs) or "d3d11" (shared NT handles)
+   */
+  kind: string
+  /**
+   * One handle per memory of the buffer, or a single IOSurface ID for the
+   * whole frame. DMABuf file descriptors are duplicated and owned by the
+   * caller, who must close them; GL texture names belong to the pipeline's
+   * GL context and are only valid while it renders the frame; IOSurface IDs
+   * are resolved with `IOSurfaceLookup`; D3D11 handles belong to the
+   * pipeline and are opened with `ID3D11Device1::OpenSharedResource1`
+   */
+  handles: Array<number>
+  /**
+   * Raw video format, or the DRM fourcc and modifier (e.g. "NV12:0x0100000000000002")
+   * for DMA_DRM caps
+   */
+  format: string
+  /** Width of the frame in pixels */
+  width: number
+  /** Height of the frame in pixels */
+  height: number
+  /** Byte offset of each plane within its memory */
+  offsets: Array<number>
+  /** Stride of each plane in bytes */
+  strides: Array<number>
+  /** Timestamp of the frame in nanoseconds, or -1 if unknown */
+  timestamp: number
+  /** The negotiated caps, with their memory feature */
+  caps: string
+}
+
+/** A frame laid out like the web's `ImageData` */
+export interface ImageDataFrame {
+  /** Width in pixels */
+  width: number
+  /** Height in pixels */
+  height: number
+  /** Tightly packed RGBA pixels, `width * height * 4` bytes */
+  data: Uint8ClampedArray
+  /** Whether the color channels are multiplied by alpha */
+  premultiplied: boolean
+  /** Timestamp of the frame in nanoseconds, or -1 if unknown */
+  timestamp: number
+}
+
+/** Options for `pullSampleAsImageData` */
+export interface ImageDataOptions {
+  /** Timeout in milliseconds (default: 100ms, use 0 for non-blocking) */
+  timeoutMs?: number
+  /**
+   * Multiply the color channels by alpha, as `ImageBitmap` and WebGL with
+   * `premultipliedAlpha` expect (default: false, as `ImageData` expects)
+   */
+  premultiplied?: boolean
+}
+
+/** Options for `diffImages` */
+export interface ImageDiffOptions {
+  /**
+   * Per-channel difference from 0 to 1 above which a pixel counts as
+   * different (default: 0.1)
+   */
+  threshold?: number
+  /** Also render a PNG heatmap of the differing pixels (default: false) */
+  heatmap?: boolean
+}
+
+/** Result of `diffImages` */
+export interface ImageDiffResult {
+  /** Share of matching pixels, from 0 (all differ) to 1 (identical) */
+  similarity: number
+  /** Number of pixels whose difference exceeds the threshold */
+  differentPixels: number
+  /** Number of pixels compared */
+  totalPixels: number
+  /** Mean per-channel difference, from 0 to 1 */
+  meanDifference: number
+  /** PNG with differing pixels in red over a dimmed copy of the first image */
+  heatmap?: Buffer
+}
+
+/** Placement of a compositor input; omitted fields are left unchanged */
+export interface InputLayout {
+  /** Horizontal position of the input's left edge */
+  x?: number
+  /** Vertical position of the input's top edge */
+  y?: number
+  /** Displayed width (0 keeps the input width) */
+  width?: number
+  /** Displayed height (0 keeps the input height) */
+  height?: number
+  /** Stacking order; higher values are drawn on top */
+  zorder?: number
+  /** Opacity from 0 (invisible) to 1 (opaque) */
+  alpha?: number
+}
+
+/** Measured loudness of a mixer input, per channel */
+export interface InputLevel {
+  /** Id of the input */
+  id: string
+  /** RMS level of each channel in dB (0 is full scale) */
+  rms: Array<number>
+  /** Peak level of each channel in dB (0 is full scale) */
+  peak: Array<number>
+}
+
+/** Options for `detectInterlacing` */
+export interface InterlaceOptions {
+  /** Number of consecutive frames analysed from the start (default: 500) */
+  frames?: number
+}
+
+/** Field structure of a video */
+export interface InterlaceReport {
+  /** "progressive", "interlaced", "telecined" or "mixed" */
+  verdict: string
+  /** Number of frames analysed */
+  framesAnalyzed: number
+  /** Number of frames showing combing */
+  combedFrames: number
+  /** Share of the analysed frames showing combing, from 0 to 1 */
+  combedRatio: number
+  /**
+   * For telecined video, the position in the 5 frame cycle of the first of
+   * the two combed frames, counting from the first analysed frame
+   */
+  pulldownPhase?: number
+  /**
+   * Video filter to apply when transcoding: "ivtc" for telecined video,
+   * "deinterlace" for interlaced video
+   */
+  recommendedFilter?: string
+}
+
+/** A value of a property at a point in time */
+export interface Keyframe {
+  /** Stream time of the keyframe in milliseconds, as reported by `getPosition` */
+  timeMs: number
+  /** Value of the property, in the property's own units */
+  value: number
+}
+
+/** A KLV packet */
+export interface KlvPacket {
+  /** The 16-byte universal label */
+  key: Buffer
+  /** The value */
+  value: Buffer
+}
+
+/** A recommended encoding of the input at one resolution */
+export interface LadderRung {
+  /** Width in pixels */
+  width: number
+  /** Height in pixels */
+  height: number
+  /** Target bitrate in kbit/s */
+  bitrate: number
+  /** Constant rate factor for quality-targeted encodes (x264/x265 scale) */
+  crf: number
+}
+
+/** Options for `measureLatency` */
+export interface LatencyOptions {
+  /** How frames are recognized: "timestampOverlay" (default) or "pts" */
+  pattern?: string
+  /** Frame size as "WIDTHxHEIGHT" (default: "640x360") */
+  resolution?: string
+  /** Frames to send, at most 4096 (default: 100) */
+  frames?: number
+  /** Frames per second to send at (default: 30) */
+  framerate?: number
+}
+
+/** Latency distribution measured by `measureLatency`, in milliseconds */
+export interface LatencyReport {
+  /** Frames pushed into the pipeline */
+  sent: number
+  /** Frames recognized at the output */
+  received: number
+  /** Frames that never came out (dropped or unrecognizable) */
+  lost: number
+  /** Lowest latency */
+  min: number
+  /** Highest latency */
+  max: number
+  /** Mean latency */
+  mean: number
+  /** Median latency */
+  median: number
+  /** 95th percentile latency */
+  p95: number
+  /** 99th percentile latency */
+  p99: number
+  /** Latency of every received frame, in arrival order */
+  samples: Array<number>
+}
+
+/** A bus message of a managed pipeline */
+export interface ManagedPipelineEvent {
+  /** Id of the pipeline that posted the message */
+  pipelineId: string
+  /** The type of event ("eos", "error", "warning", "state-changed" or "element") */
+  eventType: string
+  /** Optional message associated with the event */
+  message?: string
+}
+
+/** State and timing of a managed pipeline */
+export interface ManagedPipelineStats {
+  /** Id of the pipeline */
+  id: string
+  /** Current state ("Null", "Ready", "Paused" or "Playing") */
+  state: string
+  /** Current position in nanoseconds, if known */
+  position?: number
+  /** Duration in nanoseconds, if known */
+  duration?: number
+}
+
+/**
+ * A frame laid out like an OpenCV `cv.Mat` or a numpy `uint8` array of
+ * shape `(rows, cols, 3)`
+ */
+export interface MatFrame {
+  /** Tightly packed pixels, `rows * cols * 3` bytes */
+  data: Buffer
+  /** Height in pixels */
+  rows: number
+  /** Width in pixels */
+  cols: number
+  /** Channels per pixel (3) */
+  channels: number
+  /** Array shape, `[rows, cols, channels]` */
+  shape: Array<number>
+  /** Byte strides of the shape's dimensions, `[cols * 3, 3, 1]` */
+  strides: Array<number>
+  /** Element type, "uint8" */
+  dtype: string
+  /** OpenCV matrix type, `CV_8UC3` (16), for `new cv.Mat(rows, cols, type)` */
+  matType: number
+  /** Channel order, "BGR" or "RGB" */
+  channelOrder: string
+  /** Timestamp of the frame in nanoseconds, or -1 if unknown */
+  timestamp: number
+}
+
+/** Options for `pullSampleAsMat` */
+export interface MatOptions {
+  /** Timeout in milliseconds (default: 100ms, use 0 for non-blocking) */
+  timeoutMs?: number
+  /** Return RGB instead of OpenCV's BGR channel order (default: false) */
+  rgb?: boolean
+}
+
+/** Description of a media file or stream */
+export interface MediaInfo {
+  /** URI that was probed */
+  uri: string
+  /** Human readable container name, or null for elementary streams */
+  container?: string
+  /** Duration in nanoseconds, or -1 if unknown (e.g. live streams) */
+  duration: number
+  /** Whether the media can be seeked */
+  seekable: boolean
+  /** Whether the media is a live source */
+  live: boolean
+  /** Global metadata tags */
+  tags: Array<MediaTag>
+  /** Video streams */
+  video: Array<VideoStreamInfo>
+  /** Audio streams */
+  audio: Array<AudioStreamInfo>
+  /** Subtitle streams */
+  subtitles: Array<SubtitleStreamInfo>
+}
+
+/** Options for `getMediaInfoBatch` */
+export interface MediaInfoBatchOptions {
+  /** Files probed at the same time (default: number of CPUs) */
+  concurrency?: number
+  /** Maximum time to spend probing each file (default: 10000) */
+  timeoutMs?: number
+}
+
+/** A metadata tag of a media file */
+export interface MediaTag {
+  /** Tag name, e.g. "title" or "encoder" */
+  name: string
+  /** Tag value as text */
+  value: string
+}
+
+/** An element factory named in a launch string that is not installed */
+export interface MissingElement {
+  /** Name of the element factory, e.g. "x264enc" */
+  factory: string
+  /** Name of the plugin providing it, if known */
+  plugin?: string
+  /**
+   * Plugin set the plugin belongs to ("base", "good", "bad", "ugly",
+   * "libav" or "rs"), if known
+   */
+  pluginSet?: string
+  /** Packages to install, empty if unknown */
+  packages: Array<PackageHint>
+}
+
+/** A segment of an MSE stream */
+export interface MseSegment {
+  /** "init" for the initialization segment, "media" for media segments */
+  segmentType: string
+  /**
+   * Number of the media segment, from 0; not set for the initialization
+   * segment
+   */
+  sequence?: number
+  /**
+   * Timestamp of the first frame in nanoseconds, from the first frame of
+   * the stream
+   */
+  timestamp: number
+  /** Bytes to append to the `SourceBuffer` */
+  data: Buffer
+}
+
+/** Options for `MseStreamer` */
+export interface MseStreamerOptions {
+  /**
+   * Codec of the stream: "vp8" (default), "vp9" or "av1". Pushed frames
+   * must be in this codec; raw frames from an attached sink are encoded
+   * to it.
+   */
+  codec?: string
+  /** Width of pushed frames in pixels, required by `pushFrame` */
+  width?: number
+  /** Height of pushed frames in pixels, required by `pushFrame` */
+  height?: number
+  /** Frame rate of pushed frames (default: 30) */
+  framerate?: number
+  /** Target bitrate in kbit/s when encoding raw frames */
+  videoBitrate?: number
+  /** Encoder speed preset when encoding raw frames (see `listPresets`) */
+  preset?: string
+  /**
+   * Keyframe interval when encoding raw frames, in milliseconds
+   * (default: 1000)
+   */
+  fragmentDurationMs?: number
+}
+
+/** Buffering or connection change of a watched pipeline */
+export interface NetworkEvent {
+  /** "buffering", "connection-lost", "reconnecting" or "connection-restored" */
+  eventType: string
+  /** Buffer fill level from 0 to 100, for "buffering" events */
+  percent?: number
+  /**
+   * Buffering mode for "buffering" events: "stream", "download",
+   * "timeshift" or "live"
+   */
+  mode?: string
+  /** Name of the element the event came from */
+  source?: string
+  /** Description of the error for "connection-lost" events */
+  message?: string
+  /** Retry number for "reconnecting" events, from 1 */
+  attempt?: number
+}
+
+/** Impairment of the packets reaching a sink */
+export interface NetworkImpairment {
+  /** Share of packets dropped, from 0 to 1 (default: 0) */
+  loss?: number
+  /** Delay added to every packet in milliseconds (default: 0) */
+  delayMs?: number
+  /**
+   * Random variation of the delay in milliseconds, up to `delayMs` either
+   * way (default: 0)
+   */
+  jitterMs?: number
+  /**
+   * Bandwidth cap in kbit/s; packets over it are dropped, as by a
+   * congested link (default: unlimited)
+   */
+  bandwidthKbps?: number
+  /** Share of packets sent twice, from 0 to 1 (default: 0) */
+  duplicate?: number
+  /** Whether jittered packets may overtake each other (default: false) */
+  reorder?: boolean
+}
+
+/** Options for `watchNetwork` */
+export interface NetworkWatchOptions {
+  /**
+   * Pause playback while buffering and resume when the buffer is full
+   * (default: true)
+   */
+  pauseWhileBuffering?: boolean
+  /**
+   * Times a lost connection is retried by restarting the pipeline
+   * (default: 0)
+   */
+  reconnectAttempts?: number
+  /** Delay before each retry in milliseconds (default: 1000) */
+  reconnectDelayMs?: number
+}
+
+/** Options for `overlayVideo` */
+export interface OverlayOptions {
+  /**
+   * Video filters applied to the foreground before it is composited, e.g.
+   * `["chromakey=#00FF00:0.15:0.1"]`
+   */
+  filters?: Array<string>
+  /** Horizontal position of the foreground's left edge (default: 0) */
+  x?: number
+  /** Vertical position of the foreground's top edge (default: 0) */
+  y?: number
+  /** Displayed foreground width (default: the foreground width) */
+  width?: number
+  /** Displayed foreground height (default: the foreground height) */
+  height?: number
+  /** Output frame size as "WIDTHxHEIGHT" (default: the background size) */
+  resolution?: string
+  /** Output frames per second (default: 30) */
+  fps?: number
+  /** Keep the audio of the foreground (default: true) */
+  audio?: boolean
+  /** Output container; defaults to the output file extension */
+  container?: string
+  /** Video codec, defaults to the usual codec for the container */
+  videoCodec?: string
+}
+
+/** A package providing a missing element on one system */
+export interface PackageHint {
+  /** "debian" (also Ubuntu), "fedora", "arch", "macos" (Homebrew) or "windows" */
+  system: string
+  /** Name of the package to install */
+  package: string
+}
+
+/** Options for `computePerceptualHashes` */
+export interface PerceptualHashOptions {
+  /** Hash the input resampled to this frame rate (default: every frame) */
+  fps?: number
+  /** Hash every Nth frame only (default: 1) */
+  interval?: number
+}
+
+/** Result of `diagnosePipeline` */
+export interface PipelineDiagnosis {
+  /** Whether the launch string parses into a pipeline */
+  valid: boolean
+  /** The parse error, if any */
+  error?: string
+  /** Elements that are not installed */
+  missing: Array<MissingElement>
+}
+
+/** Event types that can be emitted by the pipeline */
+export interface PipelineEvent {
+  /** The type of event */
+  eventType: string
+  /** Optional message associated with the event */
+  message?: string
+  /** Optional error code (for error events) */
+  errorCode?: number
+}
+
+/** Snapshot of pipeline statistics */
+export interface PipelineStats {
+  /** Current pipeline state */
+  state: string
+  /** Per-element statistics */
+  elements: Array<ElementStats>
+  /** Total frames rendered by all sinks */
+  renderedFrames: number
+  /** Total frames dropped by all sinks and QoS-reporting elements */
+  droppedFrames: number
+}
+
+/** Options for `setPlaylist` */
+export interface PlaylistOptions {
+  /** Video sink description (e.g. "fakesink"); defaults to an automatic sink */
+  videoSink?: string
+  /** Audio sink description (e.g. "fakesink"); defaults to an automatic sink */
+  audioSink?: string
+}
+
+/** Which of the required elements are installed */
+export interface PluginReport {
+  /** Elements found in the registry */
+  available: Array<string>
+  /** Elements no installed plugin provides */
+  missing: Array<string>
+  /** Whether every element is available */
+  ok: boolean
+}
+
+/** Options for `probeDirectory` */
+export interface ProbeDirectoryOptions {
+  /**
+   * JSON file caching the results between calls, created if missing
+   * (default: no cache)
+   */
+  cachePath?: string
+  /** Also probe the files of subdirectories (default: false) */
+  recursive?: boolean
+  /**
+   * Only probe files with these extensions, e.g. ["mp4", "mkv"]
+   * (default: every file)
+   */
+  extensions?: Array<string>
+  /** Bytes at the start of each file hashed into its cache key (default: 65536) */
+  hashBytes?: number
+  /** Maximum time to spend probing each file (default: 10000) */
+  timeoutMs?: number
+}
+
+/** Result of probing one file of a directory */
+export interface ProbedFile {
+  /** Path of the file */
+  path: string
+  /** Description of the file, or null if it could not be probed */
+  info?: MediaInfo
+  /** Why the file could not be probed */
+  error?: string
+  /** Whether the description came from the cache */
+  cached: boolean
+}
+
+/** Options for `pullFrame` */
+export interface PullFrameOptions {
+  /** Timeout in milliseconds (default: 100ms, use 0 for non-blocking) */
+  timeoutMs?: number
+  /**
+   * Remove the padding at the end of each row, so every plane's stride is
+   * its row size and `data` holds the planes back to back (default: false)
+   */
+  packed?: boolean
+  /**
+   * Only return this part of the frame, packed; the origin is moved onto
+   * the chroma grid of subsampled formats (even coordinates for 4:2:0)
+   */
+  crop?: FrameCrop
+}
+
+/** A QR code found in a video frame */
+export interface QrDetection {
+  /** Decoded text of the code */
+  payload: string
+  /** Left edge of the code's bounding box in pixels */
+  x: number
+  /** Top edge of the code's bounding box in pixels */
+  y: number
+  /** Width of the bounding box in pixels */
+  width: number
+  /** Height of the bounding box in pixels */
+  height: number
+  /** Presentation timestamp of the frame in nanoseconds, or -1 if unknown */
+  timestamp: number
+}
+
+/** Options for `recordFromPipeline` */
+export interface RecordOptions {
+  /** Output container, "ivf" or "webm"; defaults to the output file extension */
+  container?: string
+  /**
+   * Codec raw frames are encoded to: "vp8" (default), "vp9" or "av1".
+   * Encoded frames are written as they are.
+   */
+  videoCodec?: string
+  /** Target video bitrate in kbit/s */
+  videoBitrate?: number
+  /** Encoder speed preset (see `listPresets`), e.g. "ultrafast" for live input */
+  preset?: string
+  /** Encoder tuning (see `listPresets`) */
+  tune?: string
+}
+
+/** Summary of a finished recording */
+export interface RecordingStats {
+  /** Number of frames recorded */
+  frames: number
+  /** Time between the first and the last frame, in nanoseconds */
+  duration: number
+  /** Size of the output file in bytes */
+  bytes: number
+}
+
+/**
+ * A rectangle of the video frame encoded at a different quality.
+ *
+ * Regions are attached to every frame as region-of-interest metadata. They are
+ * honored by encoders that read it (the VA-API, Quick Sync and NVENC encoders)
+ * and ignored by the others.
+ */
+export interface RegionOfInterest {
+  /** Left edge in pixels */
+  x: number
+  /** Top edge in pixels */
+  y: number
+  /** Width in pixels */
+  width: number
+  /** Height in pixels */
+  height: number
+  /** Quantizer offset for the region; negative values raise the quality */
+  deltaQp: number
+}
+
+/** Options for the `ScreenRecorder` constructor */
+export interface ScreenRecorderOptions {
+  /** File path, or "rtmp://", "rtmps://" or "srt://" URL to stream to */
+  output: string
+  /** Container of a file output (default: from the file extension) */
+  container?: string
+  /** Launch description of the screen capture, replacing the platform's */
+  screen?: string
+  /** Part of the screen to record (default: the whole screen) */
+  region?: ScreenRegion
+  /** Frames per second (default: 30) */
+  fps?: number
+  /** Video bitrate in kbit/s (default: 4000) */
+  videoBitrate?: number
+  /** Video codec (default: the container's, h264 for streams) */
+  videoCodec?: string
+  /** Adds the webcam in a corner of the screen */
+  webcam?: WebcamOptions
+  /**
+   * Launch description of the microphone, or "default" for the system
+   * default; no audio is recorded without it
+   */
+  microphone?: string
+  /** Audio bitrate in kbit/s (default: the encoder's) */
+  audioBitrate?: number
+}
+
+/** Part of the screen to record, in pixels */
+export interface ScreenRegion {
+  /** Left edge */
+  x: number
+  /** Top edge */
+  y: number
+  /** Width of the region */
+  width: number
+  /** Height of the region */
+  height: number
+}
+
+/** Options for `extractSei` */
+export interface SeiExtractorOptions {
+  /** Only read messages with this UUID (default: all user data messages) */
+  uuid?: string
+}
+
+/** Options for `injectSei` */
+export interface SeiInjectorOptions {
+  /** UUID identifying the messages (default: the crate's own UUID) */
+  uuid?: string
+}
+
+/** Where and how a frame ring was created */
+export interface SharedFramesInfo {
+  /** Shared memory object name, as passed to `publishSharedFrames` */
+  name: string
+  /** Path of the backing file, for readers that memory-map it directly */
+  path: string
+  /** Total size of the mapping in bytes */
+  size: number
+  /** Number of slots in the ring */
+  slots: number
+  /** Size of each slot in bytes, including its 128 byte header */
+  slotSize: number
+  /** Size of the file header in bytes */
+  headerSize: number
+}
+
+/** Options for `publishSharedFrames` */
+export interface SharedFramesOptions {
+  /** Number of frames the ring holds (default: 4) */
+  slots?: number
+  /**
+   * Largest frame in bytes a slot can hold; bigger frames are dropped and
+   * counted (default: 8 MiB, enough for 1080p RGBA)
+   */
+  maxFrameSize?: number
+}
+
+/** Options for `renderSlate` */
+export interface SlateOptions {
+  /** Text of the slate; "\n" starts a new line */
+  text: string
+  /**
+   * Background color as "#RRGGBB" or a name ("black", "white", "gray",
+   * "red", "green", "blue"). Default: "black"
+   */
+  background?: string
+  /** Text color, in the same forms as `background` (default: "white") */
+  color?: string
+  /** Length of the clip in seconds (default: 3) */
+  durationSec?: number
+  /** Frame size as "WIDTHxHEIGHT" (default: "1280x720") */
+  resolution?: string
+  /** Frames per second (default: 30) */
+  fps?: number
+  /**
+   * Text height in pixels; long lines are shrunk to fit the frame
+   * (default: a tenth of the frame height)
+   */
+  fontSize?: number
+  /** Output container; defaults to the output file extension */
+  container?: string
+  /** Video codec, defaults to the usual codec for the container */
+  videoCodec?: string
+}
+
+/** How one stream was processed */
+export interface StreamReport {
+  /** "video" or "audio" */
+  media: string
+  /** Caps of the decoded (or, when copying, parsed) input stream */
+  inputCaps?: string
+  /** Caps of the stream handed to the muxer */
+  outputCaps?: string
+  /** Encoder element, or null when the stream was copied */
+  encoder?: string
+  /** Encoder properties that differ from their defaults */
+  settings: Array<EncoderSetting>
+  /** Filters applied before the encoder, in order */
+  filters: Array<string>
+}
+
+/** A subtitle stream of a media file */
+export interface SubtitleStreamInfo {
+  /** Human readable format name */
+  codec: string
+  /** Language code, if tagged */
+  language?: string
+}
+
+/** A codec with at least one installed backend */
+export interface SupportedCodec {
+  /** Codec name, as accepted by `TranscodeOptions` */
+  name: string
+  /** "video" or "audio" */
+  kind: string
+  /** Encoder element used for the codec */
+  encoder: string
+  /** Whether that encoder runs on dedicated hardware */
+  hardware: boolean
+  /** Every installed encoder element for the codec, in order of preference */
+  backends: Array<string>
+}
+
+/** Options for `generateTestMedia` */
+export interface TestMediaOptions {
+  /** Output format: a transcode container ("webm", "mkv", "mp4", ...) or "y4m" for raw video */
+  format: string
+  /** Picture pattern ("smpte", "gradient", "noise" or any videotestsrc pattern name). Default: "smpte" */
+  pattern?: string
+  /** Frame width in pixels (default: 320) */
+  width?: number
+  /** Frame height in pixels (default: 240) */
+  height?: number
+  /** Frames per second (default: 30) */
+  fps?: number
+  /** Clip duration in seconds (default: 1) */
+  duration?: number
+  /** Video codec, defaults to the usual codec for the container */
+  videoCodec?: string
+  /** Adds a sine tone audio track (default: false) */
+  audio?: boolean
+}
+
+/** Options for `PipelineBuilder.testSource` */
+export interface TestSourceOptions {
+  /** Test pattern, e.g. "smpte", "ball" or "black" (default: "smpte") */
+  pattern?: string
+  /** Stop after this many frames (default: unlimited) */
+  numBuffers?: number
+  /** Produce frames in real time (default: false) */
+  live?: boolean
+}
+
+/** Metadata read from a stream */
+export interface TimedMetadata {
+  /**
+   * Presentation time of the metadata (KLV) or of the frame carrying it
+   * (SEI) in nanoseconds, or -1 if unknown
+   */
+  timestamp: number
+  /** The metadata */
+  data: Buffer
+  /**
+   * UUID of the SEI message, as "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+   * not set for KLV
+   */
+  uuid?: string
+}
+
+/** Emitted when playback moves to another playlist entry */
+export interface TrackChange {
+  /** Index of the entry now playing */
+  index: number
+  /** URI of the entry now playing */
+  uri: string
+}
+
+/** Options describing the output of a transcode */
+export interface TranscodeOptions {
+  /** Output container ("webm", "mkv", "mp4", "mov", "ogg", "avi" or "ts") */
+  container: string
+  /**
+   * Video codec ("vp8", "vp9", "av1", "h264", "h265", "theora", "mjpeg", "copy" or "none").
+   * Defaults to the usual codec for the container. "copy" remuxes the input stream unchanged.
+   */
+  videoCodec?: string
+  /**
+   * Audio codec ("opus", "vorbis", "mp3", "aac", "flac", "copy" or "none").
+   * Defaults to the usual codec for the container. "copy" remuxes the input stream unchanged.
+   */
+  audioCodec?: string
+  /** Target video bitrate in kbit/s */
+  videoBitrate?: number
+  /** Target audio bitrate in kbit/s */
+  audioBitrate?: number
+  /**
+   * Constant output frame rate. By default the original per-frame timestamps
+   * are kept, so variable frame rate input stays variable; when set, frames
+   * are duplicated or dropped to conform to this rate.
+   */
+  frameRate?: number
+  /**
+   * Video encoder speed preset ("ultrafast" .. "placebo", or "0" slowest to
+   * "10" fastest). See `listPresets` for the values a codec accepts.
+   */
+  preset?: string
+  /** Video encoder tuning (e.g. "zerolatency" for h264). See `listPresets`. */
+  tune?: string
+  /** Regions of the frame to encode at a different quality */
+  regions?: Array<RegionOfInterest>
+  /**
+   * Produce byte-identical output for the same input and options: encoders
+   * run single-threaded, random identifiers (Matroska UIDs, Ogg serial
+   * numbers) come from a fixed seed and creation dates are set to the Unix
+   * epoch. Slower; concurrent transcodes may still disturb the seed.
+   */
+  deterministic?: boolean
+  /**
+   * Audio filters applied in order before the audio encoder, e.g.
+   * `["aresample=48000", "channels=stereo", "gain=-3", "fadein=1", "fadeout=9:1"]`
+   */
+  audioFilters?: Array<string>
+  /**
+   * Video filters applied in order before the video encoder, e.g.
+   * `["chromakey=#00FF00:0.1:0.05", "lut3d=grade.cube", "subtitles=movie.srt"]`
+   */
+  videoFilters?: Array<string>
+  /**
+   * Files embedded in the output, such as a cover image named "cover.jpg".
+   * Only the "mkv" container can carry attachments.
+   */
+  attachments?: Array<Attachment>
+  /** Encrypts the output frames with AES-128-CTR ("mkv" and "webm" only) */
+  encryption?: EncryptionOptions
+  /**
+   * AES-128 key, as 32 hexadecimal digits, decrypting an input written with
+   * `encryption`
+   */
+  decryptionKey?: string
+  /**
+   * Turns the video upright according to the rotation stored in the input
+   * (see `VideoStreamInfo.rotation`) before the video filters, so the
+   * output needs no rotation to display correctly
+   */
+  autorotate?: boolean
+  /**
+   * Keeps the audio in step with its timestamps, correcting the drift
+   * reported by `measureAvSync`: silence is inserted where samples are
+   * missing (and before audio starting late) and overlapping samples are
+   * dropped. Requires re-encoding the audio stream.
+   */
+  asyncCorrect?: boolean
+  /**
+   * How frames are synthesized when the frame rate is raised or time is
+   * stretched: "duplicate", "blend" (default) or "motion" (motion-compensated).
+   * When set, `frameRate` conversion uses it instead of dropping/duplicating frames.
+   */
+  interpolation?: string
+  /**
+   * Slow motion factor: 2 plays at half speed, filling the new video frames
+   * by `interpolation`. Audio is time-stretched without changing its pitch.
+   */
+  slowMotion?: number
+  /**
+   * Playback speed factor: 2 plays twice as fast, 0.5 at half speed. Video
+   * frames are dropped or duplicated (see `interpolation`) and audio is
+   * time-stretched without changing its pitch, so both streams stay in sync.
+   */
+  speed?: number
+  /**
+   * What a `TranscodeJob` does when its output file exists: "error"
+   * (default), "replace" or "rename", which writes to the first free
+   * `name (1).ext`-style path instead. Ignored by in-memory transcodes.
+   */
+  overwrite?: string
+}
+
+/** Progress of a transcode job */
+export interface TranscodeProgress {
+  /** "pending", "running", "done", "failed" or "cancelled" */
+  state: string
+  /** Input position reached, in nanoseconds */
+  position: number
+  /** Input duration in nanoseconds, or -1 if unknown */
+  duration: number
+  /** Completion from 0 to 100, or -1 if the duration is unknown */
+  percent: number
+  /** Video frames that reached the encoder */
+  framesProcessed: number
+  /**
+   * Transcoding speed as a multiple of realtime, averaged over the last
+   * two seconds
+   */
+  speed: number
+  /** Estimated seconds until the job is done, or -1 if unknown */
+  etaSeconds: number
+  /** Why the job failed */
+  error?: string
+}
+
+/** Structured description of a finished transcode */
+export interface TranscodeReport {
+  /** Output container */
+  container: string
+  /** Muxer element */
+  muxer: string
+  /** Every stream written to the output */
+  streams: Array<StreamReport>
+  /** Wall time of the transcode in milliseconds */
+  wallTimeMs: number
+  /** Video frames read from the input */
+  videoFrames: number
+  /** Video frames processed per second of wall time */
+  averageFps: number
+  /** Input size in bytes */
+  inputSize: number
+  /** Output size in bytes */
+  outputSize: number
+  /** Warnings posted while transcoding */
+  warnings: Array<string>
+}
+
+/** Output of `transcodeBufferWithReport` */
+export interface TranscodeResult {
+  /** The complete transcoded file */
+  output: Buffer
+  /** What the transcode did */
+  report: TranscodeReport
+}
+
+/** Something a `TriggerWatcher` did */
+export interface TriggerEvent {
+  /** "recording-started", "recording-stopped", "snapshot" or "error" */
+  eventType: string
+  /** Index of the rule that fired */
+  rule: number
+  /** The file created */
+  path?: string
+  /**
+   * Timestamp of the frame that fired the rule (or ended the recording),
+   * in nanoseconds
+   */
+  timestamp: number
+  /** Score of that frame */
+  score: number
+  /** Description of the error for "error" events */
+  message?: string
+}
+
+/** Options for `watchTriggers` */
+export interface TriggerOptions {
+  /** Rules evaluated on every frame, in order */
+  rules: Array<TriggerRule>
+  /**
+   * Directory receiving the recordings ("recording-0001.webm", ...) and
+   * snapshots ("snapshot-0001.jpg", ...); created if needed
+   */
+  outputDir: string
+  /** Container, codec, bitrate and preset of the recordings (default: VP8 in WebM) */
+  recording?: RecordOptions
+  /** JPEG quality of the snapshots from 1 to 100 (default: 85) */
+  snapshotQuality?: number
+}
+
+/** A condition and what to do when it holds */
+export interface TriggerRule {
+  /** What is measured: "motion" or "sceneChange" */
+  when: string
+  /** "record" or "snapshot" */
+  action: string
+  /**
+   * Score above which the rule fires (default: 0.02 for motion, 0.4 for
+   * scene changes)
+   */
+  threshold?: number
+  /**
+   * For "record", seconds without the rule firing after which the
+   * recording stops (default: 10)
+   */
+  stopAfterIdle?: number
+  /**
+   * Seconds after firing during which the rule does not fire again
+   * (default: 0)
+   */
+  cooldown?: number
+}
+
+/** A video stream of a media file */
+export interface VideoStreamInfo {
+  /** Human readable codec name, e.g. "H.264 (High Profile)" */
+  codec: string
+  /** Caps of the stream */
+  caps: string
+  /** Width in pixels */
+  width: number
+  /** Height in pixels */
+  height: number
+  /** Frames per second, or 0 if variable or unknown */
+  frameRate: number
+  /** Pixel aspect ratio as "N:D" */
+  pixelAspectRatio: string
+  /** Bitrate in bit/s, or 0 if unknown */
+  bitrate: number
+  /** Whether the stream is interlaced */
+  interlaced: boolean
+  /** Whether the stream is a still image */
+  isImage: boolean
+  /**
+   * Clockwise rotation in degrees (0, 90, 180 or 270) the frames must be
+   * displayed with, from the MP4 matrix or Matroska projection
+   */
+  rotation: number
+}
+
+/** Options for `publishVirtualCamera` */
+export interface VirtualCameraOptions {
+  /**
+   * Raw video format written to the device (default: "YUY2", which every
+   * webcam consumer reads)
+   */
+  format?: string
+  /**
+   * Sink description replacing `v4l2sink`, for other virtual camera
+   * drivers; the device argument is then only used as the camera's name
+   */
+  sink?: string
+}
+
+/** Options for `renderWaveformVideo` */
+export interface WaveformVideoOptions {
+  /**
+   * Visualization: "wave" (waveform, default), "dots" (waveform as dots),
+   * "spectrum" (frequency bars), "stereo" (left against right channel),
+   * "synaesthesia" or "goom"
+   */
+  style?: string
+  /** Frame size as "WIDTHxHEIGHT" (default: "1280x720") */
+  resolution?: string
+  /** Frames per second (default: 30) */
+  fps?: number
+  /** Keep the audio as the soundtrack of the video (default: true) */
+  audio?: boolean
+  /** Output container; defaults to the output file extension */
+  container?: string
+  /** Video codec, defaults to the usual codec for the container */
+  videoCodec?: string
+}
+
+/** Webcam picture-in-picture of a `ScreenRecorder` */
+export interface WebcamOptions {
+  /** Launch description of the camera (default: "autovideosrc") */
+  source?: string
+  /** Width of the picture in pixels (default: 320) */
+  width?: number
+  /**
+   * Height of the picture in pixels (default: three quarters of the width);
+   * the camera image is letterboxed to keep its aspect ratio
+   */
+  height?: number
+  /**
+   * Corner of the picture: "top-left", "top-right", "bottom-left" or
+   * "bottom-right" (default)
+   */
+  corner?: string
+  /** Distance from the edges of the screen in pixels (default: 16) */
+  margin?: number
+}
+
+/** A byte range of a WebM file, for an HTTP `Range` request */
+export interface WebmByteRange {
+  /** First byte of the cluster holding the keyframe at or before the time */
+  start: number
+  /**
+   * Last byte before the next cued cluster, or of the last cluster
+   * (inclusive, as in a `Content-Range` header)
+   */
+  end: number
+  /** Time of the keyframe the range starts with, in milliseconds */
+  timeMs: number
+}
+
+/** A cue point of a WebM file */
+export interface WebmCue {
+  /** Time of the cue in milliseconds */
+  timeMs: number
+  /** Number of the track the cue is for */
+  track: number
+  /** File offset of the cluster holding the cued frame */
+  clusterOffset: number
+  /** Offset of the cued frame within the cluster payload, if given */
+  relativePosition?: number
+}
+
+/** The cue index of a WebM file */
+export interface WebmCues {
+  /** Duration of the file in milliseconds, if set */
+  durationMs?: number
+  /** Size of the file in bytes */
+  fileSize: number
+  /**
+   * Size of everything before the first cluster (EBML header, segment
+   * header, tracks), which a decoder needs before any cluster
+   */
+  headerSize: number
+  /** Cue points in file order */
+  cues: Array<WebmCue>
+}
+
+/** A media segment of a fragmented WebM stream */
+export interface WebmFragment {
+  /** Number of the fragment, from 0 */
+  sequence: number
+  /**
+   * Timestamp of its first frame in nanoseconds, from the first frame of
+   * the stream
+   */
+  timestamp: number
+  /** The cluster, ready to append after the initialization segment */
+  data: Buffer
+}
+
+/**
+ * Adapts the bitrate of an encoder of a kit's pipeline to the network
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `encoder_name` - The name of the encoder element
+ * * `options` - Bitrate limits, congestion thresholds and feedback sources
+ *
+ * # Returns
+ * * `Result<BitrateAdapter>` - The running adapter
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("v4l2src ! videoconvert ! x264enc name=enc tune=zerolatency ! mpegtsmux ! queue name=out ! srtsink uri=srt://example.com:9000");
+ * kit.play();
+ * const adapter = adaptBitrate(kit, "enc", { minBitrate: 500_000, maxBitrate: 4_000_000, queue: "out" });
+ * adapter.onChange((change) => console.log(`${change.reason}: ${change.bitrate} bit/s`));
+ * ```
+ */
+export declare function adaptBitrate(kit: GstKit, encoderName: string, options?: AdaptiveBitrateOptions | undefined | null): BitrateAdapter
+
+/**
+ * Measures the spatial and temporal complexity of a video and recommends a
+ * bitrate ladder for it
+ *
+ * # Arguments
+ * * `input` - Path or URI of the video
+ * * `options` - Number of frames to sample and target codec
+ *
+ * # Returns
+ * * `Result<ComplexityReport>` - SI/TI scores and the recommended renditions
+ *
+ * # Example
+ * ```javascript
+ * const { score, ladder } = analyzeComplexity("movie.mkv", { codec: "av1" });
+ * for (const rung of ladder) {
+ *   console.log(`${rung.height}p: ${rung.bitrate} kbit/s (crf ${rung.crf})`);
+ * }
+ * ```
+ */
+export declare function analyzeComplexity(input: string, options?: ComplexityOptions | undefined | null): ComplexityReport
+
+/**
+ * Finds the bytes to serve for a seek: from the cluster holding the last
+ * keyframe at or before a time up to the next cued cluster
+ *
+ * # Arguments
+ * * `path` - Path of the WebM or Matroska file
+ * * `ms` - The time seeked to, in milliseconds
+ *
+ * # Returns
+ * * `Result<WebmByteRange>` - The byte range of the cluster
+ *
+ * # Example
+ * ```javascript
+ * const { start, end } = byteRangeForTime("recording.webm", 90_000);
+ * res.writeHead(206, { "Content-Range": `bytes ${start}-${end}/${size}` });
+ * fs.createReadStream("recording.webm", { start, end }).pipe(res);
+ * ```
+ */
+export declare function byteRangeForTime(path: string, ms: number): WebmByteRange
+
+/**
+ * Checks whether the application may capture from cameras or microphones
+ *
+ * # Arguments
+ * * `kind` - "video" for cameras or "audio" for microphones
+ *
+ * # Returns
+ * * `Result<String>` - "granted", "denied", "restricted" (by a system
+ *   policy), "not-determined" (macOS, the user has not been asked yet) or
+ *   "unknown"
+ *
+ * # Example
+ * ```javascript
+ * if (capturePermission("video") === "denied") showHelp("Allow camera access in your privacy settings");
+ * ```
+ */
+export declare function capturePermission(kind: string): string
+
+/**
+ * Renders two videos into one for visual comparison
+ *
+ * # Arguments
+ * * `input_a` - Path or URI of the first video (left, top, or left half)
+ * * `input_b` - Path or URI of the second video (right, bottom, or right half)
+ * * `output_path` - Where to write the comparison video
+ * * `options` - Layout, tile size and output format
+ *
+ * # Example
+ * ```javascript
+ * composeComparison("x264.mp4", "av1.webm", "compare.mkv", { layout: "wipe" });
+ * ```
+ */
+export declare function composeComparison(inputA: string, inputB: string, outputPath: string, options?: ComparisonOptions | undefined | null): void
+
+/**
+ * Computes the perceptual hashes (pHash and dHash) of the frames of a video
+ *
+ * # Arguments
+ * * `input` - Path or URI of the video
+ * * `options` - Frame rate and interval of the hashed frames
+ *
+ * # Returns
+ * * `Result<Vec<FrameHash>>` - The hashes of the hashed frames, in order
+ *
+ * # Example
+ * ```javascript
+ * const hashes = computePerceptualHashes("upload.mp4", { fps: 1 });
+ * const known = hashes.some(h => perceptualHashDistance(h.phash, bannedHash) <= 6);
+ * ```
+ */
+export declare function computePerceptualHashes(input: string, options?: PerceptualHashOptions | undefined | null): Array<FrameHash>
+
+/**
+ * Sets where GStreamer finds its plugins and caches its registry, then
+ * initializes it
+ *
+ * # Arguments
+ * * `config` - Plugin paths, registry path and segfault trap setting
+ *
+ * # Example
+ * ```javascript
+ * configureGStreamer({
+ *   pluginPaths: [path.join(process.resourcesPath, "gstreamer", "lib", "gstreamer-1.0")],
+ *   registryPath: path.join(app.getPath("userData"), "gst-registry.bin"),
+ * });
+ * ```
+ */
+export declare function configureGStreamer(config: GStreamerConfig): void
+
+/**
+ * Suggests a crop rectangle removing the black bars of a video
+ *
+ * # Arguments
+ * * `input` - Path or URI of the video
+ * * `options` - Number of sampled frames, black level and rounding
+ *
+ * # Returns
+ * * `Result<CropRegion>` - The rectangle of the picture, the full frame if
+ *   there are no bars
+ *
+ * # Example
+ * ```javascript
+ * const region = detectCropRegion("movie.mkv", { samples: 40 });
+ * if (region.cropped) {
+ *   new TranscodeJob("movie.mkv", "movie-cropped.mkv", { container: "mkv", videoFilters: [region.filter] }).start();
+ * }
+ * ```
+ */
+export declare function detectCropRegion(input: string, options?: CropDetectOptions | undefined | null): CropRegion
+
+/**
+ * Detects whether a video is progressive, interlaced or telecined
+ *
+ * # Arguments
+ * * `input` - Path or URI of the video
+ * * `options` - Number of frames to analyse
+ *
+ * # Returns
+ * * `Result<InterlaceReport>` - The verdict, combing statistics and the
+ *   video filter that makes the video progressive
+ *
+ * # Example
+ * ```javascript
+ * const { verdict, recommendedFilter } = detectInterlacing("dvd-rip.mkv");
+ * const videoFilters = recommendedFilter ? [recommendedFilter] : [];
+ * console.log(verdict, videoFilters);
+ * ```
+ */
+export declare function detectInterlacing(input: string, options?: InterlaceOptions | undefined | null): InterlaceReport
+
+/**
+ * Checks a launch string and reports the elements it needs that are not
+ * installed, with the packages providing them
+ *
+ * # Arguments
+ * * `pipeline_string` - A GStreamer pipeline description
+ *
+ * # Returns
+ * * `Result<PipelineDiagnosis>` - Whether it parses, and what is missing
+ *
+ * # Example
+ * ```javascript
+ * const { valid, missing } = diagnosePipeline("videotestsrc ! x264enc ! mp4mux ! filesink location=out.mp4");
+ * for (const element of missing) {
+ *   const hint = element.packages.find((p) => p.system === "debian");
+ *   console.log(`${element.factory}: apt install ${hint?.package}`);
+ * }
+ * ```
+ */
+export declare function diagnosePipeline(pipelineString: string): PipelineDiagnosis
+
+/**
+ * Compares two images pixel by pixel
+ *
+ * # Arguments
+ * * `a` - The first image, as encoded bytes or a file path
+ * * `b` - The second image, as encoded bytes or a file path
+ * * `options` - Optional difference threshold and heatmap rendering
+ *
+ * # Returns
+ * * `Result<ImageDiffResult>` - Similarity score and difference statistics
+ *
+ * # Example
+ * ```javascript
+ * const diff = diffImages("expected.png", kit.pullSample("sink"), { threshold: 0.05, heatmap: true });
+ * if (diff.similarity < 0.99) fs.writeFileSync("diff.png", diff.heatmap);
+ * ```
+ */
+export declare function diffImages(a: Buffer | string, b: Buffer | string, options?: ImageDiffOptions | undefined | null): ImageDiffResult
+
+/**
+ * Dumps the element structure of a Matroska/WebM or IVF file
+ *
+ * # Arguments
+ * * `path` - The file to dump
+ * * `options` - Maximum nesting depth to list
+ *
+ * # Returns
+ * * `Result<ContainerDump>` - Format and element tree of the file
+ *
+ * # Example
+ * ```javascript
+ * const dump = dumpContainer("broken.webm", { maxDepth: 2 });
+ * const segment = dump.elements.find(e => e.name === "Segment");
+ * console.log(segment.children.map(e => `${e.name} @${e.offset} (${e.size} bytes)`));
+ * ```
+ */
+export declare function dumpContainer(path: string, options?: DumpOptions | undefined | null): ContainerDump
+
+/**
+ * Encodes a KLV packet, with the length in BER form
+ *
+ * # Arguments
+ * * `key` - The 16-byte universal label, e.g. the UAS Datalink Local Set key of MISB ST 0601
+ * * `value` - The value
+ *
+ * # Returns
+ * * `Result<Buffer>` - The packet
+ *
+ * # Example
+ * ```javascript
+ * const key = Buffer.from("060e2b34020b01010e01030101000000", "hex");
+ * kit.pushTimedMetadata("klv", encodeKlv(key, localSet), frameTime);
+ * ```
+ */
+export declare function encodeKlv(key: Buffer, value: Buffer): Buffer
+
+/**
+ * Streams the compressed frames of every stream of a media file to an
+ * NDJSON or CSV file
+ *
+ * Frames are written as the demuxer delivers them instead of being
+ * collected, so inspecting hours of video takes constant memory. Each
+ * record has the fields of `BitstreamFrame`; in CSV the units are separated
+ * by spaces.
+ *
+ * # Arguments
+ * * `path` - The media file to inspect
+ * * `output` - The file to write
+ * * `format` - "ndjson" or "csv" (default: from the extension of `output`, NDJSON unless `.csv`)
+ *
+ * # Returns
+ * * `Result<u32>` - Number of frames written
+ *
+ * # Example
+ * ```javascript
+ * const count = exportBitstream("movie.mkv", "frames.csv");
+ * ```
+ */
+export declare function exportBitstream(path: string, output: string, format?: string | undefined | null): number
+
+/**
+ * Extracts the audio track of a media file as 16 kHz mono 16-bit PCM WAV,
+ * the input format of most speech-to-text engines
+ *
+ * # Arguments
+ * * `input` - A media file path or URI with an audio track
+ * * `output_path` - Where to write the WAV file
+ *
+ * # Example
+ * ```javascript
+ * extractAudioForASR("interview.mp4", "interview.wav");
+ * ```
+ */
+export declare function extractAudioForASR(input: string, outputPath: string): void
+
+/**
+ * Extracts the `[startMs, endMs)` range of a media file into a new file
+ *
+ * In "copy" mode the clip starts at the keyframe at or before `startMs`, so it
+ * may begin slightly earlier than requested, but no quality is lost. In
+ * "smart" mode the clip starts exactly at `startMs`: the video up to the
+ * next keyframe is re-encoded with the codec and profile of the input and
+ * the rest is copied. H.264 and H.265 clips then carry their parameter sets
+ * in-band ("avc3"/"hev1" in MP4 and Matroska).
+ *
+ * # Arguments
+ * * `input_path` - The media file to cut
+ * * `output_path` - Where to write the clip
+ * * `start_ms` - Start of the range in milliseconds
+ * * `end_ms` - End of the range in milliseconds
+ * * `options` - Optional mode and output container
+ *
+ * # Example
+ * ```javascript
+ * // Also writes highlight.en.vtt
+ * extractClip("talk.webm", "highlight.webm", 60_000, 75_000, { mode: "copy", subtitles: ["talk.en.vtt"] });
+ * ```
+ */
+export declare function extractClip(inputPath: string, outputPath: string, startMs: number, endMs: number, options?: ClipOptions | undefined | null): void
+
+/**
+ * Exports the frames of a video as JPEG or PNG images
+ *
+ * # Arguments
+ * * `input` - Path or URI of the video
+ * * `output` - Directory to write the images to (created if needed), or the
+ *   path of the zip archive when `archive` is set
+ * * `options` - Image format, frame rate, frame limit and archive settings
+ *
+ * # Returns
+ * * `Result<Vec<ExportedFrame>>` - The images written, as listed in `index.json`
+ *
+ * # Example
+ * ```javascript
+ * const frames = extractFramesToImages("talk.mp4", "talk-frames.zip", {
+ *   fps: 1,
+ *   archive: true,
+ *   password: process.env.FRAMES_PASSWORD,
+ * });
+ * console.log(`${frames.length} frames archived`);
+ * ```
+ */
+export declare function extractFramesToImages(input: string, output: string, options?: FrameExportOptions | undefined | null): Array<ExportedFrame>
+
+/**
+ * Reads the SEI user data messages of the H.264 or H.265 stream leaving an
+ * element, such as a parser after a demuxer
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `element_name` - The name of the element whose output is read
+ * * `options` - UUID of the messages to read
+ *
+ * # Returns
+ * * `Result<SeiExtractor>` - The extractor to read metadata from
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("filesrc location=flight.ts ! tsdemux ! h264parse name=parse ! avdec_h264 ! fakesink");
+ * const extractor = extractSei(kit, "parse");
+ * extractor.onMetadata((metadata) => track.push(JSON.parse(metadata.data.toString())));
+ * kit.play();
+ * ```
+ */
+export declare function extractSei(kit: GstKit, elementName: string, options?: SeiExtractorOptions | undefined | null): SeiExtractor
+
+/**
+ * Finds the segments of video present in both inputs, such as a shared
+ * intro, a reused clip or a re-upload
+ *
+ * # Arguments
+ * * `first` - Path or URI of the first video
+ * * `second` - Path or URI of the second video
+ * * `options` - Sampling rate, match threshold and minimum segment length
+ *
+ * # Returns
+ * * `Result<Vec<DuplicateSegment>>` - Non-overlapping segments, longest first
+ *
+ * # Example
+ * ```javascript
+ * for (const s of findDuplicateSegments("episode1.mkv", "episode2.mkv", { minDuration: 10 })) {
+ *   console.log(`${s.firstStart / 1e9}s in episode 1 = ${s.secondStart / 1e9}s in episode 2`);
+ * }
+ * ```
+ */
+export declare function findDuplicateSegments(first: string, second: string, options?: DuplicateSegmentOptions | undefined | null): Array<DuplicateSegment>
+
+/**
+ * Fragments the frames reaching an AppSink into live-streamable WebM
+ *
+ * Raw frames are encoded with `videoCodec`; VP8, VP9 and AV1 frames are
+ * muxed without re-encoding. The sink's callbacks are taken over until the
+ * fragmenter is stopped.
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `sink_name` - The name of the AppSink element
+ * * `options` - Codec, bitrate, preset and fragment duration
+ *
+ * # Returns
+ * * `Result<WebmFragmenter>` - The running fragmenter
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+ * const fragmenter = fragmentFromPipeline(kit, "sink", { preset: "ultrafast", fragmentDurationMs: 500 });
+ * kit.play();
+ * const fragment = fragmenter.nextFragment(2000);
+ * ws.send(fragmenter.initSegment());
+ * ws.send(fragment.data);
+ * ```
+ */
+export declare function fragmentFromPipeline(kit: GstKit, sinkName: string, options?: FragmentOptions | undefined | null): WebmFragmenter
+
+/**
+ * Writes a synthetic test clip to a file
+ *
+ * # Arguments
+ * * `output_path` - Where to write the clip
+ * * `options` - Format, pattern, size, frame rate and duration of the clip
+ *
+ * # Example
+ * ```javascript
+ * generateTestMedia("fixture.webm", { format: "webm", pattern: "gradient", width: 640, height: 360, duration: 2 });
+ * ```
+ */
+export declare function generateTestMedia(outputPath: string, options: TestMediaOptions): void
+
+/**
+ * Lists the files attached to a Matroska file
+ *
+ * # Arguments
+ * * `path` - Path of a Matroska (.mkv, .mka) file
+ *
+ * # Returns
+ * * `Result<Vec<AttachedFile>>` - The attachments, in file order
+ *
+ * # Example
+ * ```javascript
+ * const cover = getAttachments("album.mka").find(a => a.mimeType.startsWith("image/"));
+ * if (cover) fs.writeFileSync(cover.name, cover.data);
+ * ```
+ */
+export declare function getAttachments(path: string): Array<AttachedFile>
+
+/**
+ * Returns how the native module was built and the GStreamer version it runs with
+ *
+ * # Returns
+ * * `Result<BuildInfo>` - Version, features, GStreamer version, SIMD level and commit
+ *
+ * # Example
+ * ```javascript
+ * const info = getBuildInfo();
+ * console.log(`gstreamer-kit ${info.version} (${info.gitHash}) on GStreamer ${info.gstreamerVersion}`);
+ * ```
+ */
+export declare function getBuildInfo(): BuildInfo
+
+/**
+ * Describes what the installed GStreamer plugins can encode, decode, mux,
+ * demux and convert
+ *
+ * Everything is looked up at call time, so the result reflects the plugins
+ * installed on the machine rather than a fixed list.
+ *
+ * # Returns
+ * * `Result<Capabilities>` - Codec, container and pixel format capabilities
+ *
+ * # Example
+ * ```javascript
+ * const caps = getCapabilities();
+ * const h264 = caps.codecs.find((c) => c.name === "h264");
+ * const codec = h264?.encode ? "h264" : "vp8";
+ * ```
+ */
+export declare function getCapabilities(): Capabilities
+
+/**
+ * Probes many media files in parallel in a single call
+ *
+ * The files are spread over a pool of threads, each probing with its own
+ * discoverer, which avoids both the serial wait of probing one file after
+ * another and the per-call overhead of looping in JavaScript. A file that
+ * cannot be probed is reported with an error instead of failing the batch.
+ *
+ * # Arguments
+ * * `locations` - File paths or URIs, or a glob pattern such as "media/**\/*.mp4"
+ * * `options` - Optional concurrency and per-file timeout
+ *
+ * # Returns
+ * * `Result<Vec<ProbedFile>>` - One result per file, in the order given or,
+ *   for a pattern, sorted by path
+ *
+ * # Example
+ * ```javascript
+ * const results = getMediaInfoBatch("media/**\/*.mkv", { concurrency: 8 });
+ * const failed = results.filter((r) => r.error);
+ * ```
+ */
+export declare function getMediaInfoBatch(locations: string[] | string, options?: MediaInfoBatchOptions | undefined | null): Array<ProbedFile>
+
+/**
+ * Lists the codecs that can be encoded with the installed GStreamer plugins
+ *
+ * # Returns
+ * * `Result<Vec<SupportedCodec>>` - Video codecs, then audio codecs
+ *
+ * # Example
+ * ```javascript
+ * const h264 = getSupportedCodecs().find((c) => c.name === "h264");
+ * if (h264) console.log("h264 via", h264.encoder);
+ * ```
+ */
+export declare function getSupportedCodecs(): Array<SupportedCodec>
+
+/**
+ * Reads the cue points of a WebM file
+ *
+ * # Arguments
+ * * `path` - Path of the WebM or Matroska file
+ *
+ * # Returns
+ * * `Result<WebmCues>` - The cues, header size and duration
+ *
+ * # Example
+ * ```javascript
+ * const { headerSize, cues } = getWebmCues("recording.webm");
+ * res.setHeader("X-Keyframes", cues.map((cue) => cue.timeMs).join(","));
+ * ```
+ */
+export declare function getWebmCues(path: string): WebmCues
+
+/**
+ * Converts a tightly packed I420 frame to NV12
+ *
+ * # Arguments
+ * * `data` - The I420 frame: the luma, U and V planes in order
+ * * `width` - Width of the frame in pixels
+ * * `height` - Height of the frame in pixels
+ *
+ * # Returns
+ * * `Result<Buffer>` - The NV12 frame: the luma plane followed by interleaved UV samples
+ *
+ * # Example
+ * ```javascript
+ * const nv12 = i420ToNv12(i420, 1280, 720);
+ * ```
+ */
+export declare function i420ToNv12(data: Buffer, width: number, height: number): Buffer
+
+/**
+ * Inserts SEI user data messages into the H.264 or H.265 stream leaving
+ * an element, such as an encoder or a parser
+ *
+ * Decoders ignore the messages, and muxers and parsers keep them, so they
+ * reach `extractSei` on the other side in MP4, Matroska, MPEG-TS or RTP.
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `element_name` - The name of the element whose output carries the
+ *   metadata
+ * * `options` - UUID of the messages
+ *
+ * # Returns
+ * * `Result<SeiInjector>` - The injector to push metadata to
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("v4l2src ! videoconvert ! x264enc name=enc ! h264parse ! mpegtsmux ! filesink location=flight.ts");
+ * const injector = injectSei(kit, "enc");
+ * kit.play();
+ * telemetry.on("fix", (fix) => injector.push(JSON.stringify(fix)));
+ * ```
+ */
+export declare function injectSei(kit: GstKit, elementName: string, options?: SeiInjectorOptions | undefined | null): SeiInjector
+
+/**
+ * Lists the compressed frames of every stream of a media file
+ *
+ * # Arguments
+ * * `path` - The media file to inspect
+ *
+ * # Returns
+ * * `Result<Vec<BitstreamFrame>>` - Every frame in demuxing order
+ *
+ * # Example
+ * ```javascript
+ * const frames = inspectBitstream("encoded.mp4").filter(f => f.codec === "video/x-h264");
+ * const keyframes = frames.filter(f => f.keyframe).map(f => f.pts / 1e9);
+ * console.log("GOP starts at", keyframes, "first units:", frames[0].units);
+ * ```
+ */
+export declare function inspectBitstream(path: string): Array<BitstreamFrame>
+
+/**
+ * Lists the cameras and microphones currently available
+ *
+ * # Arguments
+ * * `kind` - "video" or "audio" (default: both)
+ *
+ * # Returns
+ * * `Result<Vec<DeviceInfo>>` - The devices
+ *
+ * # Example
+ * ```javascript
+ * for (const camera of listCaptureDevices("video")) console.log(camera.name, camera.path);
+ * ```
+ */
+export declare function listCaptureDevices(kind?: string | undefined | null): Array<DeviceInfo>
+
+/**
+ * Lists the `preset` and `tune` values accepted for a video codec, by the
+ * encoder backend it resolves to
+ *
+ * # Arguments
+ * * `codec` - Video codec name, as accepted by `TranscodeOptions.videoCodec`
+ *
+ * # Returns
+ * * `Result<CodecPresets>` - Valid presets and tunes for the codec (no
+ *   presets if its encoder has no speed control)
+ *
+ * # Example
+ * ```javascript
+ * const { presets, tunes } = listPresets("h264");
+ * transcodeBuffer(input, { container: "mp4", preset: presets[0], tune: tunes[0] });
+ * ```
+ */
+export declare function listPresets(codec: string): CodecPresets
+
+/**
+ * Measures the drift of the audio of a file against its video
+ *
+ * # Arguments
+ * * `input` - Path or URI of a file with audio and video
+ *
+ * # Returns
+ * * `Result<AvSyncReport>` - The start offset, the largest and final drift
+ *   and the drift at every minute
+ *
+ * # Example
+ * ```javascript
+ * const { maxDrift, offsets } = measureAvSync("recording.mkv");
+ * if (Math.abs(maxDrift) > 40_000_000) {
+ *   new TranscodeJob("recording.mkv", "fixed.mkv", { container: "mkv", asyncCorrect: true }).start();
+ * }
+ * ```
+ */
+export declare function measureAvSync(input: string): AvSyncReport
+
+/**
+ * Measures the end-to-end latency of a pipeline fragment
+ *
+ * Frames are pushed into `appsrc ! videoconvert ! <pipeline>` and read back
+ * after it from an appsink that does not sync to the clock, so the result is
+ * the time frames spend being processed. The call blocks until every frame
+ * has been sent and the pipeline reached end of stream.
+ *
+ * # Arguments
+ * * `pipeline` - Launch string fragment taking and producing raw or decodable video
+ * * `options` - Optional pattern, resolution, frame count and frame rate
+ *
+ * # Returns
+ * * `Result<LatencyReport>` - Latency distribution in milliseconds
+ *
+ * # Example
+ * ```javascript
+ * const report = measureLatency(
+ *   "x264enc tune=zerolatency ! rtph264pay ! rtph264depay ! avdec_h264",
+ *   { pattern: "timestampOverlay", frames: 300 },
+ * );
+ * console.log(`median ${report.median} ms, p99 ${report.p99} ms, lost ${report.lost}`);
+ * ```
+ */
+export declare function measureLatency(pipeline: string, options?: LatencyOptions | undefined | null): LatencyReport
+
+/**
+ * Converts a tightly packed NV12 frame to I420
+ *
+ * # Arguments
+ * * `data` - The NV12 frame: the luma plane followed by interleaved UV samples
+ * * `width` - Width of the frame in pixels
+ * * `height` - Height of the frame in pixels
+ *
+ * # Returns
+ * * `Result<Buffer>` - The I420 frame: the luma, U and V planes in order
+ *
+ * # Example
+ * ```javascript
+ * const frame = kit.pullFrame("sink", { packed: true });
+ * const i420 = nv12ToI420(frame.data, frame.width, frame.height);
+ * ```
+ */
+export declare function nv12ToI420(data: Buffer, width: number, height: number): Buffer
+
+/**
+ * Composites a foreground video over a background video or image
+ *
+ * The output lasts as long as the longer input; a still image background is
+ * shown for the whole foreground.
+ *
+ * # Arguments
+ * * `foreground` - Path or URI of the video drawn on top
+ * * `background` - Path or URI of the background video or image
+ * * `output_path` - Where to write the composited video
+ * * `options` - Foreground filters and placement, output size and format
+ *
+ * # Example
+ * ```javascript
+ * overlayVideo("presenter-green.mp4", "studio.png", "presenter.mp4", {
+ *   filters: ["chromakey=#00FF00:0.15:0.1"],
+ *   x: 640,
+ *   y: 180,
+ * });
+ * ```
+ */
+export declare function overlayVideo(foreground: string, background: string, outputPath: string, options?: OverlayOptions | undefined | null): void
+
+/**
+ * Splits data into its KLV packets
+ *
+ * # Arguments
+ * * `data` - One or more KLV packets, e.g. from `pullTimedMetadata`
+ *
+ * # Returns
+ * * `Result<Vec<KlvPacket>>` - The packets, in order
+ *
+ * # Example
+ * ```javascript
+ * const metadata = kit.pullTimedMetadata("klv");
+ * if (metadata) for (const { key, value } of parseKlv(metadata.data)) console.log(key.toString("hex"), value.length);
+ * ```
+ */
+export declare function parseKlv(data: Buffer): Array<KlvPacket>
+
+/**
+ * Hamming distance between two perceptual hashes: the number of differing
+ * bits, from 0 (identical) to 64
+ *
+ * # Arguments
+ * * `a` - A pHash or dHash as returned by `computePerceptualHashes`
+ * * `b` - A hash of the same kind
+ *
+ * # Returns
+ * * `Result<u32>` - The number of differing bits
+ *
+ * # Example
+ * ```javascript
+ * const [a, b] = [computePerceptualHashes("a.mp4")[0], computePerceptualHashes("b.mp4")[0]];
+ * console.log(perceptualHashDistance(a.phash, b.phash) <= 10 ? "same shot" : "different");
+ * ```
+ */
+export declare function perceptualHashDistance(a: string, b: string): number
+
+/**
+ * Decodes a media file and pushes its frames into an AppSrc of a pipeline
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `src_name` - The name of the AppSrc element
+ * * `input_path` - Path or URI of the media file
+ * * `options` - Pacing, stream and end of stream handling
+ *
+ * # Returns
+ * * `Result<TranscodedFeed>` - The running feed
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("appsrc name=src format=time ! videoconvert ! autovideosink");
+ * const feed = playTranscodedInto(kit, "src", "clip.mkv", { realtime: true });
+ * kit.play();
+ * ```
+ */
+export declare function playTranscodedInto(kit: GstKit, srcName: string, inputPath: string, options?: FeedOptions | undefined | null): TranscodedFeed
+
+/**
+ * Probes a media file or stream and describes it like ffprobe does
+ *
+ * The JSON matches the schema of
+ * `ffprobe -v quiet -of json -show_format -show_streams <location>`:
+ * a `streams` array with FFmpeg codec names, dimensions, frame rates and
+ * sample formats, and a `format` object with the container name, duration,
+ * size and bitrate. Numbers ffprobe prints as strings (durations, bitrates,
+ * sample rates) are strings here too.
+ *
+ * # Arguments
+ * * `location` - A file path or URI (file://, http://, rtsp://, ...)
+ * * `timeout_ms` - Maximum time to spend probing (default: 10000)
+ *
+ * # Returns
+ * * `Result<String>` - The ffprobe-compatible JSON document
+ *
+ * # Example
+ * ```javascript
+ * const probe = JSON.parse(probeAsFfprobe("movie.mkv"));
+ * const video = probe.streams.find((s) => s.codec_type === "video");
+ * console.log(probe.format.format_name, video.codec_name, video.r_frame_rate);
+ * ```
+ */
+export declare function probeAsFfprobe(location: string, timeoutMs?: number | undefined | null): string
+
+/**
+ * Probes every media file of a directory, reusing cached results
+ *
+ * Files whose fingerprint is found in the cache are not opened beyond their
+ * first bytes, so probing a library of hundreds of files again takes
+ * milliseconds. Files that cannot be probed are reported with an error
+ * rather than failing the whole call, and are not cached.
+ *
+ * # Arguments
+ * * `dir` - The directory to probe
+ * * `options` - Optional cache file, recursion, extension filter and timeout
+ *
+ * # Returns
+ * * `Result<Vec<ProbedFile>>` - One result per file, sorted by path
+ *
+ * # Example
+ * ```javascript
+ * const files = probeDirectory("/media/library", {
+ *   cachePath: "/var/cache/library-probe.json",
+ *   recursive: true,
+ *   extensions: ["mp4", "mkv", "mov"],
+ * });
+ * for (const { path, info, error } of files) {
+ *   console.log(path, info ? info.duration / 1e9 : error);
+ * }
+ * ```
+ */
+export declare function probeDirectory(dir: string, options?: ProbeDirectoryOptions | undefined | null): Array<ProbedFile>
+
+/**
+ * Probes a media file or stream with GStreamer's discoverer
+ *
+ * # Arguments
+ * * `location` - A file path or URI (file://, http://, rtsp://, ...)
+ * * `timeout_ms` - Maximum time to spend probing (default: 10000)
+ *
+ * # Returns
+ * * `Result<MediaInfo>` - Container, duration, tags and stream details
+ *
+ * # Example
+ * ```javascript
+ * const info = probeWithGStreamer("movie.mkv");
+ * const [video] = info.video;
+ * console.log(info.container, info.duration / 1e9, video?.codec, `${video?.width}x${video?.height}`);
+ * ```
+ */
+export declare function probeWithGStreamer(location: string, timeoutMs?: number | undefined | null): MediaInfo
+
+/**
+ * Trims, fades, normalizes and concatenates audio files
+ *
+ * Several inputs are joined in order before the other edits are applied, so
+ * `trim` and `fade` refer to the concatenated audio. The output keeps the
+ * sample rate and channel count of the first input.
+ *
+ * # Arguments
+ * * `input` - An input file path or URI, or a list of them to concatenate
+ * * `output_path` - Where to write the result
+ * * `options` - Trim range, fades, normalization target and output format
+ *
+ * # Returns
+ * * `Result<AudioProcessResult>` - Length, format and normalization gain of the output
+ *
+ * # Example
+ * ```javascript
+ * processAudio(["intro.wav", "episode.wav"], "episode.opus", {
+ *   trim: { startMs: 500 },
+ *   fade: { inMs: 1000, outMs: 3000 },
+ *   normalize: -1,
+ * });
+ * ```
+ */
+export declare function processAudio(input: string | Array<string>, outputPath: string, options?: AudioProcessOptions | undefined | null): AudioProcessResult
+
+/**
+ * Publishes the frames of an AppSink to a virtual camera device
+ *
+ * The sink's callbacks are taken over until the camera is stopped. Frames
+ * are converted to the device format and written as they arrive.
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `sink_name` - The name of the AppSink element
+ * * `device_path_or_name` - Device path ("/dev/video10") or card label
+ *   ("Node Cam") of a v4l2loopback device
+ * * `options` - Optional format and sink
+ *
+ * # Returns
+ * * `Result<VirtualCamera>` - The running publisher
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("v4l2src ! videoconvert ! facedetect ! videoconvert ! appsink name=sink");
+ * const camera = publishVirtualCamera(kit, "sink", "Node Cam");
+ * kit.play();
+ * ```
+ */
+export declare function publishVirtualCamera(kit: GstKit, sinkName: string, devicePathOrName: string, options?: VirtualCameraOptions | undefined | null): VirtualCamera
+
+/**
+ * Records the frames reaching an AppSink into an IVF or WebM file
+ *
+ * Raw frames are encoded with `videoCodec`; VP8, VP9 and AV1 frames are
+ * written without re-encoding. The sink's callbacks are taken over until
+ * the recording is stopped.
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `sink_name` - The name of the AppSink element
+ * * `output_path` - The file to write
+ * * `codec_options` - Container, codec, bitrate and encoder preset
+ *
+ * # Returns
+ * * `Result<PipelineRecorder>` - The running recording
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+ * const recorder = recordFromPipeline(kit, "sink", "camera.webm", { videoCodec: "vp9", preset: "ultrafast" });
+ * kit.play();
+ * setTimeout(() => console.log(recorder.stop()), 10_000);
+ * ```
+ */
+export declare function recordFromPipeline(kit: GstKit, sinkName: string, outputPath: string, codecOptions?: RecordOptions | undefined | null): PipelineRecorder
+
+/**
+ * Registers an encoder backend for a codec, ahead of the existing ones
+ *
+ * The codec may be new, in which case it becomes accepted by
+ * `TranscodeOptions` once its encoder is installed.
+ *
+ * # Arguments
+ * * `codec` - Codec name, e.g. "h264"
+ * * `backend` - Encoder element and how to configure it
+ *
+ * # Example
+ * ```javascript
+ * registerCodecBackend("h264", { kind: "video", encoder: "qsvh264enc", parser: "h264parse", bitrateProperty: "bitrate", hardware: true });
+ * ```
+ */
+export declare function registerCodecBackend(codec: string, backend: CodecBackend): void
+
+/**
+ * Fills in the `{name}` placeholders of a launch string template
+ *
+ * # Arguments
+ * * `template` - A pipeline description with `{name}` placeholders
+ * * `vars` - The value of every placeholder
+ *
+ * # Returns
+ * * `Result<String>` - The pipeline description, with the values escaped
+ *
+ * # Example
+ * ```javascript
+ * const description = renderPipelineTemplate(
+ *   "filesrc location={path} ! decodebin ! videoconvert ! appsink name=sink",
+ *   { path: "/media/My Videos/clip.mp4" },
+ * );
+ * // filesrc location="/media/My Videos/clip.mp4" ! decodebin ! ...
+ * ```
+ */
+export declare function renderPipelineTemplate(template: string, vars: Record<string, string | number | boolean>): string
+
+/**
+ * Renders a title card or slate with centered text and encodes it to a file
+ *
+ * # Arguments
+ * * `output_path` - Where to write the clip
+ * * `options` - Text, colors, duration, frame size and output format
+ *
+ * # Example
+ * ```javascript
+ * renderSlate("intro.mp4", {
+ *   text: "Episode 12\nThe Return",
+ *   background: "#1a1a2e",
+ *   durationSec: 4,
+ *   resolution: "1920x1080",
+ * });
+ * ```
+ */
+export declare function renderSlate(outputPath: string, options: SlateOptions): void
+
+/**
+ * Renders a video of the animated waveform or spectrum of an audio file
+ *
+ * # Arguments
+ * * `audio_path` - Path or URI of the audio (or of any media with an audio track)
+ * * `output_path` - Where to write the video
+ * * `options` - Visualization style, frame size and output format
+ *
+ * # Example
+ * ```javascript
+ * renderWaveformVideo("episode-clip.mp3", "clip.mp4", { style: "spectrum", resolution: "1080x1080" });
+ * ```
+ */
+export declare function renderWaveformVideo(audioPath: string, outputPath: string, options?: WaveformVideoOptions | undefined | null): void
+
+/**
+ * Multiplies every timestamp of a SubRip or WebVTT file by a factor
+ *
+ * # Arguments
+ * * `path` - The subtitle file
+ * * `factor` - Timeline scale; subtitles timed for 23.976 fps video that was
+ *   sped up to 25 fps need `23.976 / 25`
+ * * `output_path` - Where to write the result, which may be `path` itself;
+ *   nothing is written without it
+ *
+ * # Returns
+ * * `Result<String>` - The re-timed subtitles
+ *
+ * # Example
+ * ```javascript
+ * rescaleSubtitles("film.srt", 24000 / 1001 / 25, "film-pal.srt");
+ * ```
+ */
+export declare function rescaleSubtitles(path: string, factor: number, outputPath?: string | undefined | null): string
+
+/**
+ * Measures encode, decode and filter throughput on synthesized test frames
+ *
+ * Runs that fail (for example because an encoder plugin is not installed) are
+ * reported with `fps: 0` and an `error` instead of failing the whole benchmark.
+ *
+ * # Arguments
+ * * `options` - Optional resolution, duration, codecs and thread counts
+ *
+ * # Returns
+ * * `Result<Vec<BenchmarkResult>>` - One result per codec/operation/thread count
+ *
+ * # Example
+ * ```javascript
+ * const results = runBenchmark({ resolution: "1920x1080", seconds: 2 });
+ * const best = results.filter((r) => r.operation === "encode").sort((a, b) => b.fps - a.fps)[0];
+ * ```
+ */
+export declare function runBenchmark(options?: BenchmarkOptions | undefined | null): Array<BenchmarkResult>
+
+/**
+ * Shifts every cue of a SubRip or WebVTT file by an offset
+ *
+ * Cues moved entirely before zero are removed, and cues moved partly
+ * before zero start at zero.
+ *
+ * # Arguments
+ * * `path` - The subtitle file
+ * * `offset_ms` - Milliseconds added to every timestamp, negative to show
+ *   the subtitles earlier
+ * * `output_path` - Where to write the result, which may be `path` itself;
+ *   nothing is written without it
+ *
+ * # Returns
+ * * `Result<String>` - The re-timed subtitles
+ *
+ * # Example
+ * ```javascript
+ * // The subtitles show 1.5 s too late
+ * shiftSubtitles("movie.en.srt", -1500, "movie.en.srt");
+ * ```
+ */
+export declare function shiftSubtitles(path: string, offsetMs: number, outputPath?: string | undefined | null): string
+
+/**
+ * Transcodes an in-memory media file without touching the filesystem
+ *
+ * The call blocks until the whole file is transcoded, for at most
+ * `timeoutMs`.
+ *
+ * # Arguments
+ * * `input` - The complete input file
+ * * `options` - Output container and codec settings
+ * * `timeout_ms` - How long to wait for the transcode in milliseconds
+ *   (default: 10 minutes)
+ *
+ * # Returns
+ * * `Result<Buffer>` - The complete transcoded file
+ *
+ * # Example
+ * ```javascript
+ * const webm = transcodeBuffer(fs.readFileSync("sticker.gif"), { container: "webm" });
+ * ```
+ */
+export declare function transcodeBuffer(input: Buffer, options: TranscodeOptions, timeoutMs?: number | undefined | null): Buffer
+
+/**
+ * Transcodes an in-memory media file and reports what was done
+ *
+ * The report lists, per stream, the input and output caps, the filters and
+ * encoder used and the encoder settings that differ from their defaults,
+ * along with wall time, average fps, sizes and any warnings.
+ *
+ * # Arguments
+ * * `input` - The complete input file
+ * * `options` - Output container and codec settings
+ * * `report_path` - Optional file the report is also written to, as JSON
+ * * `timeout_ms` - How long to wait for the transcode in milliseconds
+ *   (default: 10 minutes)
+ *
+ * # Returns
+ * * `Result<TranscodeResult>` - The transcoded file and its report
+ *
+ * # Example
+ * ```javascript
+ * const { output, report } = transcodeBufferWithReport(input, { container: "mp4" }, "job.json");
+ * console.log(report.averageFps, report.streams.map((s) => s.encoder));
+ * ```
+ */
+export declare function transcodeBufferWithReport(input: Buffer, options: TranscodeOptions, reportPath?: string | undefined | null, timeoutMs?: number | undefined | null): TranscodeResult
+
+/**
+ * Reports which of the given elements are installed
+ *
+ * # Arguments
+ * * `elements` - Element factory names, e.g. "x264enc" or "webmmux"
+ *
+ * # Returns
+ * * `Result<PluginReport>` - The available and missing elements
+ *
+ * # Example
+ * ```javascript
+ * const { ok, missing } = verifyPlugins(["x264enc", "mp4mux", "pulsesrc"]);
+ * if (!ok) dialog.showErrorBox("Missing GStreamer plugins", missing.join(", "));
+ * ```
+ */
+export declare function verifyPlugins(elements: Array<string>): PluginReport
+
+/**
+ * Watches cameras and microphones being plugged in or out, and the
+ * permission to capture from them changing
+ *
+ * # Arguments
+ * * `options` - Kind of devices and permission check interval
+ *
+ * # Returns
+ * * `Result<DeviceWatcher>` - The running watcher
+ *
+ * # Example
+ * ```javascript
+ * const watcher = watchDevices({ kind: "video" });
+ * watcher.onEvent((event) => console.log(event.eventType, event.device?.name ?? event.permission));
+ * ```
+ */
+export declare function watchDevices(options?: DeviceWatchOptions | undefined | null): DeviceWatcher
+
+/**
+ * Watches the pipeline of a kit for buffering and connection events
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline; its current pipeline is watched
+ * * `options` - Buffering and reconnection behavior
+ *
+ * # Returns
+ * * `Result<NetworkWatcher>` - The running watcher
+ *
+ * # Example
+ * ```javascript
+ * kit.setPipeline("uridecodebin uri=https://example.com/live.m3u8 ! videoconvert ! autovideosink");
+ * const watcher = watchNetwork(kit, { reconnectAttempts: 5 });
+ * watcher.onEvent((event) => console.log(event.eventType, event.percent ?? ""));
+ * kit.play();
+ * ```
+ */
+export declare function watchNetwork(kit: GstKit, options?: NetworkWatchOptions | undefined | null): NetworkWatcher
+
+/**
+ * Runs motion and scene change rules on the frames of an AppSink
+ *
+ * The sink is set to receive I420 frames, and its callbacks are taken over
+ * until the watcher is stopped.
+ *
+ * # Arguments
+ * * `kit` - The kit running the pipeline
+ * * `sink_name` - The name of the AppSink element
+ * * `options` - Rules, output directory and recording settings
+ *
+ * # Returns
+ * * `Result<TriggerWatcher>` - The running watcher
+ *
+ * # Example
+ * ```javascript
+ * const watcher = watchTriggers(kit, "sink", {
+ *   outputDir: "captures",
+ *   rules: [
+ *     { when: "motion", action: "record", threshold: 0.02, stopAfterIdle: 10 },
+ *     { when: "sceneChange", action: "snapshot" },
+ *   ],
+ * });
+ * watcher.onEvent((event) => console.log(event.eventType, event.path));
+ * kit.play();
+ * ```
+ */
+export declare function watchTriggers(kit: GstKit, sinkName: string, options: TriggerOptions): TriggerWatcher
@@ -576,4 +576,83 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.AudioMixer = nativeBinding.AudioMixer
+module.exports.BitrateAdapter = nativeBinding.BitrateAdapter
+module.exports.CaptureSession = nativeBinding.CaptureSession
+module.exports.Compositor = nativeBinding.Compositor
+module.exports.DeviceWatcher = nativeBinding.DeviceWatcher
+module.exports.FrameClock = nativeBinding.FrameClock
+module.exports.FrameStream = nativeBinding.FrameStream
 module.exports.GstKit = nativeBinding.GstKit
+module.exports.MseStreamer = nativeBinding.MseStreamer
+module.exports.NetworkWatcher = nativeBinding.NetworkWatcher
+module.exports.PipelineBuilder = nativeBinding.PipelineBuilder
+module.exports.PipelineManager = nativeBinding.PipelineManager
+module.exports.PipelineRecorder = nativeBinding.PipelineRecorder
+module.exports.ScreenRecorder = nativeBinding.ScreenRecorder
+module.exports.SeiExtractor = nativeBinding.SeiExtractor
+module.exports.SeiInjector = nativeBinding.SeiInjector
+module.exports.TranscodeJob = nativeBinding.TranscodeJob
+module.exports.TranscodeStream = nativeBinding.TranscodeStream
+module.exports.TranscodedFeed = nativeBinding.TranscodedFeed
+module.exports.TriggerWatcher = nativeBinding.TriggerWatcher
+module.exports.VirtualCamera = nativeBinding.VirtualCamera
+module.exports.WebmFragmenter = nativeBinding.WebmFragmenter
+module.exports.adaptBitrate = nativeBinding.adaptBitrate
+module.exports.analyzeComplexity = nativeBinding.analyzeComplexity
+module.exports.byteRangeForTime = nativeBinding.byteRangeForTime
+module.exports.capturePermission = nativeBinding.capturePermission
+module.exports.composeComparison = nativeBinding.composeComparison
+module.exports.computePerceptualHashes = nativeBinding.computePerceptualHashes
+module.exports.configureGStreamer = nativeBinding.configureGStreamer
+module.exports.detectCropRegion = nativeBinding.detectCropRegion
+module.exports.detectInterlacing = nativeBinding.detectInterlacing
+module.exports.diagnosePipeline = nativeBinding.diagnosePipeline
+module.exports.diffImages = nativeBinding.diffImages
+module.exports.dumpContainer = nativeBinding.dumpContainer
+module.exports.encodeKlv = nativeBinding.encodeKlv
+module.exports.exportBitstream = nativeBinding.exportBitstream
+module.exports.extractAudioForASR = nativeBinding.extractAudioForASR
+module.exports.extractClip = nativeBinding.extractClip
+module.exports.extractFramesToImages = nativeBinding.extractFramesToImages
+module.exports.extractSei = nativeBinding.extractSei
+module.exports.findDuplicateSegments = nativeBinding.findDuplicateSegments
+module.exports.fragmentFromPipeline = nativeBinding.fragmentFromPipeline
+module.exports.generateTestMedia = nativeBinding.generateTestMedia
+module.exports.getAttachments = nativeBinding.getAttachments
+module.exports.getBuildInfo = nativeBinding.getBuildInfo
+module.exports.getCapabilities = nativeBinding.getCapabilities
+module.exports.getMediaInfoBatch = nativeBinding.getMediaInfoBatch
+module.exports.getSupportedCodecs = nativeBinding.getSupportedCodecs
+module.exports.getWebmCues = nativeBinding.getWebmCues
+module.exports.i420ToNv12 = nativeBinding.i420ToNv12
+module.exports.injectSei = nativeBinding.injectSei
+module.exports.inspectBitstream = nativeBinding.inspectBitstream
+module.exports.listCaptureDevices = nativeBinding.listCaptureDevices
+module.exports.listPresets = nativeBinding.listPresets
+module.exports.measureAvSync = nativeBinding.measureAvSync
+module.exports.measureLatency = nativeBinding.measureLatency
+module.exports.nv12ToI420 = nativeBinding.nv12ToI420
+module.exports.overlayVideo = nativeBinding.overlayVideo
+module.exports.parseKlv = nativeBinding.parseKlv
+module.exports.perceptualHashDistance = nativeBinding.perceptualHashDistance
+module.exports.playTranscodedInto = nativeBinding.playTranscodedInto
+module.exports.probeAsFfprobe = nativeBinding.probeAsFfprobe
+module.exports.probeDirectory = nativeBinding.probeDirectory
+module.exports.probeWithGStreamer = nativeBinding.probeWithGStreamer
+module.exports.processAudio = nativeBinding.processAudio
+module.exports.publishVirtualCamera = nativeBinding.publishVirtualCamera
+module.exports.recordFromPipeline = nativeBinding.recordFromPipeline
+module.exports.registerCodecBackend = nativeBinding.registerCodecBackend
+module.exports.renderPipelineTemplate = nativeBinding.renderPipelineTemplate
+module.exports.renderSlate = nativeBinding.renderSlate
+module.exports.renderWaveformVideo = nativeBinding.renderWaveformVideo
+module.exports.rescaleSubtitles = nativeBinding.rescaleSubtitles
+module.exports.runBenchmark = nativeBinding.runBenchmark
+module.exports.shiftSubtitles = nativeBinding.shiftSubtitles
+module.exports.transcodeBuffer = nativeBinding.transcodeBuffer
+module.exports.transcodeBufferWithReport = nativeBinding.transcodeBufferWithReport
+module.exports.verifyPlugins = nativeBinding.verifyPlugins
+module.exports.watchDevices = nativeBinding.watchDevices
+module.exports.watchNetwork = nativeBinding.watchNetwork
+module.exports.watchTriggers = nativeBinding.watchTriggers
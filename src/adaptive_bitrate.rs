@@ -0,0 +1,400 @@
+//! # Adaptive Bitrate
+//!
+//! Bitrate control of running encoders for live streams. `setTargetBitrate`
+//! changes the bitrate of an encoder while it plays, in bit/s whatever the
+//! unit of the encoder's own property. An adapter started by
+//! `adaptBitrate` (or `ScreenRecorder.adaptBitrate`) adjusts it on its own
+//! from congestion feedback of the output: the packet loss, round-trip time
+//! and bandwidth estimate of an `srtsink`, and the fill level of a queue in
+//! front of the network sink. The bitrate is cut quickly while the network
+//! is congested and raised slowly once it has recovered, between set limits.
+
+use crate::codecs::encoder_spec;
+use crate::kit::GstKit;
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Factor applied to the bitrate on congestion
+const DECREASE: f64 = 0.75;
+/// Factor applied to the bitrate after a clear interval
+const INCREASE: f64 = 1.05;
+/// Share of the estimated bandwidth the bitrate is kept under
+const BANDWIDTH_SHARE: f64 = 0.9;
+
+/// Options for `adaptBitrate`
+#[napi(object)]
+pub struct AdaptiveBitrateOptions {
+  /// Lowest bitrate in bit/s (default: 300000)
+  pub min_bitrate: Option<f64>,
+  /// Highest bitrate in bit/s (default: the bitrate when adaptation starts)
+  pub max_bitrate: Option<f64>,
+  /// Time between adjustments in milliseconds (default: 1000)
+  pub interval_ms: Option<u32>,
+  /// Share of lost packets above which the network counts as congested
+  /// (default: 0.02)
+  pub max_loss_rate: Option<f64>,
+  /// Queued data in milliseconds above which the network counts as
+  /// congested (default: 500)
+  pub max_queue_ms: Option<u32>,
+  /// Name of the SRT sink to read statistics from (default: the first
+  /// `srtsink` of the pipeline)
+  pub sink: Option<String>,
+  /// Name of the queue in front of the network sink whose fill level is
+  /// watched (default: none)
+  pub queue: Option<String>,
+}
+
+/// A bitrate change made by an adapter
+#[napi(object)]
+pub struct BitrateChange {
+  /// The new bitrate in bit/s
+  pub bitrate: f64,
+  /// The bitrate before the change in bit/s
+  pub previous: f64,
+  /// "congestion", "bandwidth" (above the estimated bandwidth) or "recovery"
+  pub reason: String,
+  /// Share of the packets lost over the last interval, from the SRT sink
+  pub loss_rate: Option<f64>,
+  /// Round-trip time in milliseconds, from the SRT sink
+  pub rtt_ms: Option<f64>,
+  /// Estimated bandwidth in bit/s, from the SRT sink
+  pub bandwidth: Option<f64>,
+  /// Data waiting in the watched queue in milliseconds
+  pub queue_ms: Option<f64>,
+}
+
+/// Callback receiving bitrate changes
+type BitrateCallback = ThreadsafeFunction<BitrateChange, (), BitrateChange, Status, false, true>;
+
+/// Bitrate property of an encoder and the factor converting kbit/s into its
+/// unit
+fn bitrate_property(encoder: &gst::Element) -> Result<(String, u32)> {
+  let factory = encoder
+    .factory()
+    .map(|factory| factory.name().to_string())
+    .unwrap_or_default();
+  if let Some(spec) = encoder_spec(&factory) {
+    if let Some(property) = spec.bitrate_property {
+      return Ok((property, spec.bitrate_scale));
+    }
+  }
+  // Unregistered encoders commonly take bit/s in "target-bitrate" and
+  // kbit/s in "bitrate"
+  for (property, scale) in [("target-bitrate", 1000), ("bitrate", 1)] {
+    if encoder.find_property(property).is_some() {
+      return Ok((property.to_string(), scale));
+    }
+  }
+  Err(Error::new(
+    Status::InvalidArg,
+    format!("{} has no bitrate property", encoder.name()),
+  ))
+}
+
+/// Reads the bitrate of an encoder in bit/s
+pub(crate) fn encoder_bitrate(encoder: &gst::Element) -> Result<f64> {
+  let (property, scale) = bitrate_property(encoder)?;
+  let value = encoder.property_value(&property);
+  let value = value
+    .get::<u32>()
+    .map(f64::from)
+    .or_else(|_| value.get::<i32>().map(f64::from))
+    .or_else(|_| value.get::<u64>().map(|v| v as f64))
+    .or_else(|_| value.get::<i64>().map(|v| v as f64))
+    .unwrap_or(0.0);
+  Ok(value * 1000.0 / scale as f64)
+}
+
+/// Sets the bitrate of a running encoder, clamped to the range of its
+/// property
+///
+/// # Returns
+/// * `Result<f64>` - The bitrate applied, in bit/s
+pub(crate) fn set_encoder_bitrate(encoder: &gst::Element, bps: f64) -> Result<f64> {
+  if !bps.is_finite() || bps <= 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid bitrate: {}", bps),
+    ));
+  }
+  let (property, scale) = bitrate_property(encoder)?;
+  let pspec = encoder.find_property(&property).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} has no {} property", encoder.name(), property),
+    )
+  })?;
+  let value = (bps / 1000.0 * scale as f64).round();
+  let applied = if let Some(range) = pspec.downcast_ref::<glib::ParamSpecUInt>() {
+    let value = value.clamp(range.minimum() as f64, range.maximum() as f64) as u32;
+    encoder.set_property(&property, value);
+    value as f64
+  } else if let Some(range) = pspec.downcast_ref::<glib::ParamSpecInt>() {
+    let value = value.clamp(range.minimum() as f64, range.maximum() as f64) as i32;
+    encoder.set_property(&property, value);
+    value as f64
+  } else if let Some(range) = pspec.downcast_ref::<glib::ParamSpecUInt64>() {
+    let value = value.clamp(range.minimum() as f64, range.maximum() as f64) as u64;
+    encoder.set_property(&property, value);
+    value as f64
+  } else if let Some(range) = pspec.downcast_ref::<glib::ParamSpecInt64>() {
+    let value = value.clamp(range.minimum() as f64, range.maximum() as f64) as i64;
+    encoder.set_property(&property, value);
+    value as f64
+  } else {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{}.{} is not numeric", encoder.name(), property),
+    ));
+  };
+  Ok(applied * 1000.0 / scale as f64)
+}
+
+/// Counters and estimates of an SRT connection
+struct SrtStats {
+  sent: f64,
+  lost: f64,
+  rtt_ms: Option<f64>,
+  bandwidth: Option<f64>,
+}
+
+/// A numeric field of a statistics structure, of any integer or float type
+fn number(s: &gst::StructureRef, field: &str) -> Option<f64> {
+  let value = s.value(field).ok()?;
+  value
+    .get::<f64>()
+    .ok()
+    .or_else(|| value.get::<i64>().ok().map(|v| v as f64))
+    .or_else(|| value.get::<u64>().ok().map(|v| v as f64))
+    .or_else(|| value.get::<i32>().ok().map(f64::from))
+    .or_else(|| value.get::<u32>().ok().map(f64::from))
+}
+
+/// Reads the statistics of an SRT sink; a listening sink reports those of
+/// its first caller
+fn srt_stats(sink: &gst::Element) -> Option<SrtStats> {
+  let stats = sink.property::<Option<gst::Structure>>("stats")?;
+  let caller = stats
+    .get::<glib::ValueArray>("callers")
+    .ok()
+    .and_then(|callers| callers.iter().next()?.get::<gst::Structure>().ok());
+  let s = caller.as_deref().unwrap_or(&stats);
+  Some(SrtStats {
+    sent: number(s, "packets-sent")?,
+    lost: number(s, "packets-sent-lost").unwrap_or(0.0)
+      + number(s, "packets-retransmitted").unwrap_or(0.0),
+    rtt_ms: number(s, "rtt-ms"),
+    bandwidth: number(s, "bandwidth-mbps")
+      .filter(|&mbps| mbps > 0.0)
+      .map(|mbps| mbps * 1_000_000.0),
+  })
+}
+
+/// Finds the first SRT sink of a bin, in any nested bin
+fn find_srt_sink(bin: &gst::Bin) -> Option<gst::Element> {
+  bin.iterate_recurse().into_iter().flatten().find(|element| {
+    element
+      .factory()
+      .is_some_and(|factory| matches!(factory.name().as_str(), "srtsink" | "srtclientsink"))
+  })
+}
+
+/// State of a running adapter
+struct Adaptation {
+  callback: Option<BitrateCallback>,
+  bitrate: f64,
+}
+
+/// Automatic bitrate adaptation of an encoder, created by `adaptBitrate`
+#[napi]
+pub struct BitrateAdapter {
+  state: Arc<Mutex<Adaptation>>,
+  running: Arc<AtomicBool>,
+}
+
+#[napi]
+impl BitrateAdapter {
+  /// Sets the callback announcing each bitrate change
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every change
+  ///
+  /// # Example
+  /// ```javascript
+  /// adapter.onChange((change) => console.log(change.reason, change.bitrate));
+  /// ```
+  #[napi]
+  pub fn on_change(&self, callback: BitrateCallback) {
+    self.state.lock().unwrap().callback = Some(callback);
+  }
+
+  /// The current bitrate of the encoder in bit/s
+  #[napi(getter)]
+  pub fn bitrate(&self) -> f64 {
+    self.state.lock().unwrap().bitrate
+  }
+
+  /// Stops adapting; the encoder keeps its current bitrate
+  #[napi]
+  pub fn stop(&self) {
+    self.running.store(false, Ordering::SeqCst);
+  }
+}
+
+impl Drop for BitrateAdapter {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Starts adapting the bitrate of `encoder` from the feedback of the output
+/// of `pipeline`
+pub(crate) fn start_adapter(
+  pipeline: &gst::Pipeline,
+  encoder: gst::Element,
+  options: Option<AdaptiveBitrateOptions>,
+) -> Result<BitrateAdapter> {
+  let options = options.unwrap_or(AdaptiveBitrateOptions {
+    min_bitrate: None,
+    max_bitrate: None,
+    interval_ms: None,
+    max_loss_rate: None,
+    max_queue_ms: None,
+    sink: None,
+    queue: None,
+  });
+  let bitrate = encoder_bitrate(&encoder)?;
+  let min = options.min_bitrate.unwrap_or(300_000.0);
+  let max = options.max_bitrate.unwrap_or(bitrate).max(min);
+  let interval = Duration::from_millis(options.interval_ms.unwrap_or(1000).max(1) as u64);
+  let max_loss_rate = options.max_loss_rate.unwrap_or(0.02);
+  let max_queue_ms = options.max_queue_ms.unwrap_or(500) as f64;
+  let find = |name: &str| {
+    pipeline
+      .by_name(name)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Element not found: {}", name)))
+  };
+  let sink = match &options.sink {
+    Some(name) => Some(find(name)?),
+    None => find_srt_sink(pipeline.upcast_ref()),
+  };
+  let queue = options.queue.as_deref().map(find).transpose()?;
+
+  let state = Arc::new(Mutex::new(Adaptation {
+    callback: None,
+    bitrate,
+  }));
+  let running = Arc::new(AtomicBool::new(true));
+  let (adapted, active) = (state.clone(), running.clone());
+  let (encoder, sink, queue) = (
+    encoder.downgrade(),
+    sink.map(|sink| sink.downgrade()),
+    queue.map(|queue| queue.downgrade()),
+  );
+  std::thread::spawn(move || {
+    let mut counters: Option<(f64, f64)> = None;
+    while active.load(Ordering::SeqCst) {
+      std::thread::sleep(interval);
+      let Some(encoder) = encoder.upgrade() else {
+        break;
+      };
+      let stats = sink
+        .as_ref()
+        .and_then(|sink| sink.upgrade())
+        .and_then(|sink| srt_stats(&sink));
+      let loss_rate = stats.as_ref().and_then(|stats| {
+        let previous = counters.replace((stats.sent, stats.lost));
+        let (sent, lost) = previous?;
+        let sent = stats.sent - sent;
+        (sent > 0.0).then(|| ((stats.lost - lost) / sent).clamp(0.0, 1.0))
+      });
+      let queue_ms = queue
+        .as_ref()
+        .and_then(|queue| queue.upgrade())
+        .map(|queue| queue.property::<u64>("current-level-time") as f64 / 1_000_000.0);
+      let bandwidth = stats.as_ref().and_then(|stats| stats.bandwidth);
+
+      let mut state = adapted.lock().unwrap();
+      let previous = state.bitrate;
+      let congested = loss_rate.is_some_and(|loss| loss > max_loss_rate)
+        || queue_ms.is_some_and(|queued| queued > max_queue_ms);
+      let clear = !loss_rate.is_some_and(|loss| loss > 0.0)
+        && !queue_ms.is_some_and(|queued| queued > max_queue_ms / 4.0);
+      let (target, reason) = if congested {
+        (previous * DECREASE, "congestion")
+      } else if let Some(bandwidth) = bandwidth.filter(|&bw| previous > bw * BANDWIDTH_SHARE) {
+        (bandwidth * BANDWIDTH_SHARE, "bandwidth")
+      } else if clear && (stats.is_some() || queue_ms.is_some()) {
+        let ceiling = bandwidth.map_or(max, |bw| max.min(bw * BANDWIDTH_SHARE));
+        ((previous * INCREASE).min(ceiling), "recovery")
+      } else {
+        continue;
+      };
+      let target = target.clamp(min, max);
+      // Changes under one percent are not worth an encoder reconfiguration
+      if (target - previous).abs() < previous * 0.01 {
+        continue;
+      }
+      let Ok(applied) = set_encoder_bitrate(&encoder, target) else {
+        break;
+      };
+      state.bitrate = applied;
+      if let Some(callback) = &state.callback {
+        callback.call(
+          BitrateChange {
+            bitrate: applied,
+            previous,
+            reason: reason.to_string(),
+            loss_rate,
+            rtt_ms: stats.as_ref().and_then(|stats| stats.rtt_ms),
+            bandwidth,
+            queue_ms,
+          },
+          ThreadsafeFunctionCallMode::NonBlocking,
+        );
+      }
+    }
+  });
+
+  Ok(BitrateAdapter { state, running })
+}
+
+/// Adapts the bitrate of an encoder of a kit's pipeline to the network
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `encoder_name` - The name of the encoder element
+/// * `options` - Bitrate limits, congestion thresholds and feedback sources
+///
+/// # Returns
+/// * `Result<BitrateAdapter>` - The running adapter
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("v4l2src ! videoconvert ! x264enc name=enc tune=zerolatency ! mpegtsmux ! queue name=out ! srtsink uri=srt://example.com:9000");
+/// kit.play();
+/// const adapter = adaptBitrate(kit, "enc", { minBitrate: 500_000, maxBitrate: 4_000_000, queue: "out" });
+/// adapter.onChange((change) => console.log(`${change.reason}: ${change.bitrate} bit/s`));
+/// ```
+#[napi]
+pub fn adapt_bitrate(
+  kit: &GstKit,
+  encoder_name: String,
+  options: Option<AdaptiveBitrateOptions>,
+) -> Result<BitrateAdapter> {
+  let pipeline = kit.current_pipeline()?;
+  let encoder = pipeline.by_name(&encoder_name).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Element not found: {}", encoder_name),
+    )
+  })?;
+  start_adapter(&pipeline, encoder, options)
+}
@@ -0,0 +1,130 @@
+//! # Property Animation
+//!
+//! Animates numeric properties of elements and pads through keyframes, with
+//! GStreamer controller bindings: an interpolation control source holds the
+//! keyframes and a direct control binding applies its values to the
+//! property. The values are computed for the timestamp of each buffer as it
+//! is processed, so an animation follows the pipeline clock through pauses
+//! and seeks, unlike one driven by JavaScript timers.
+//!
+//! Values are only applied by elements that sync their
+//! controlled properties, which includes every filter derived from
+//! `GstBaseTransform` (such as `volume` and `videobalance`) and the pads of
+//! aggregators (such as `compositor` and `audiomixer`).
+
+use gst::glib;
+use gst_controller::prelude::*;
+use gst_controller::{DirectControlBinding, InterpolationControlSource, InterpolationMode};
+use gstreamer as gst;
+use gstreamer_controller as gst_controller;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// A value of a property at a point in time
+#[napi(object)]
+pub struct Keyframe {
+  /// Stream time of the keyframe in milliseconds, as reported by `getPosition`
+  pub time_ms: f64,
+  /// Value of the property, in the property's own units
+  pub value: f64,
+}
+
+/// Options for `animateProperty`
+#[napi(object)]
+pub struct AnimationOptions {
+  /// How values between keyframes are computed: "linear", "cubic",
+  /// "cubic-monotonic" (cubic without overshoot) or "step" (default:
+  /// "linear")
+  pub interpolation: Option<String>,
+}
+
+/// Animates `property` of `object` through `keyframes`, replacing any
+/// animation it had
+pub(crate) fn animate(
+  object: &gst::Object,
+  property: &str,
+  keyframes: &[Keyframe],
+  options: Option<AnimationOptions>,
+) -> Result<()> {
+  let pspec = object.find_property(property).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} has no property {}", object.name(), property),
+    )
+  })?;
+  let numeric = [
+    glib::Type::I32,
+    glib::Type::U32,
+    glib::Type::I_LONG,
+    glib::Type::U_LONG,
+    glib::Type::I64,
+    glib::Type::U64,
+    glib::Type::F32,
+    glib::Type::F64,
+  ]
+  .contains(&pspec.value_type());
+  if !numeric || !pspec.flags().contains(gst::PARAM_FLAG_CONTROLLABLE) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Property {} of {} cannot be animated",
+        property,
+        object.name()
+      ),
+    ));
+  }
+  if keyframes.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "At least one keyframe is required".to_string(),
+    ));
+  }
+  if let Some(keyframe) = keyframes.iter().find(|keyframe| {
+    !keyframe.time_ms.is_finite() || keyframe.time_ms < 0.0 || !keyframe.value.is_finite()
+  }) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Invalid keyframe: {} at {}ms",
+        keyframe.value, keyframe.time_ms
+      ),
+    ));
+  }
+  let mode = match options.and_then(|options| options.interpolation).as_deref() {
+    None | Some("linear") => InterpolationMode::Linear,
+    Some("cubic") => InterpolationMode::Cubic,
+    Some("cubic-monotonic") => InterpolationMode::CubicMonotonic,
+    Some("step") => InterpolationMode::None,
+    Some(other) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unknown interpolation: {}", other),
+      ))
+    }
+  };
+
+  let source = InterpolationControlSource::new();
+  source.set_mode(mode);
+  for keyframe in keyframes {
+    let time = gst::ClockTime::from_nseconds((keyframe.time_ms * 1_000_000.0).round() as u64);
+    source.set(time, keyframe.value);
+  }
+  let binding = DirectControlBinding::new_absolute(object, property, &source);
+
+  if let Some(existing) = object.control_binding(property) {
+    object.remove_control_binding(&existing);
+  }
+  object.add_control_binding(&binding).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to animate {}: {}", property, e),
+    )
+  })
+}
+
+/// Stops animating `property` of `object`; returns whether it was animated
+pub(crate) fn clear(object: &gst::Object, property: &str) -> bool {
+  object
+    .control_binding(property)
+    .is_some_and(|binding| object.remove_control_binding(&binding))
+}
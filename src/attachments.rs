@@ -0,0 +1,238 @@
+//! # Attachments
+//!
+//! Files embedded in Matroska output next to its streams: cover art, fonts
+//! used by subtitles, artwork or notes. They are written into the Matroska
+//! Attachments element by `matroskamux` and read back through the tags
+//! `matroskademux` posts for them. By convention players show an image named
+//! "cover.jpg" or "cover.png" as the cover of the file.
+
+use crate::transcode::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+
+/// A file to embed in the output
+#[napi(object)]
+#[derive(Clone)]
+pub struct Attachment {
+  /// Path of the file to embed
+  pub path: String,
+  /// File name stored in the container (default: the name of `path`)
+  pub name: Option<String>,
+  /// MIME type (default: guessed from the file extension)
+  pub mime_type: Option<String>,
+  /// Free-form description
+  pub description: Option<String>,
+}
+
+/// A file embedded in a media file
+#[napi(object)]
+pub struct AttachedFile {
+  /// Stored file name
+  pub name: String,
+  /// MIME type
+  pub mime_type: String,
+  /// Description, if one was stored
+  pub description: Option<String>,
+  /// Contents of the file
+  pub data: Buffer,
+}
+
+/// MIME type of a file, from its extension
+fn guess_mime_type(name: &str) -> &'static str {
+  let extension = Path::new(name)
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(|ext| ext.to_lowercase());
+  match extension.as_deref() {
+    Some("jpg" | "jpeg") => "image/jpeg",
+    Some("png") => "image/png",
+    Some("webp") => "image/webp",
+    Some("gif") => "image/gif",
+    Some("ttf") => "application/x-truetype-font",
+    Some("otf") => "application/vnd.ms-opentype",
+    Some("txt") => "text/plain",
+    Some("srt") => "application/x-subrip",
+    Some("pdf") => "application/pdf",
+    _ => "application/octet-stream",
+  }
+}
+
+/// Reads `attachments` and adds them to the tags of `muxer`, which must be
+/// `matroskamux`; call before the pipeline starts
+pub(crate) fn attach_files(muxer: &gst::Element, attachments: &[Attachment]) -> Result<()> {
+  let setter = muxer
+    .dynamic_cast_ref::<gst::TagSetter>()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Muxer does not accept tags"))?;
+  for attachment in attachments {
+    let data = std::fs::read(&attachment.path).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to read attachment {}: {}", attachment.path, e),
+      )
+    })?;
+    let name = attachment.name.clone().unwrap_or_else(|| {
+      Path::new(&attachment.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| attachment.path.clone())
+    });
+    let mime_type = attachment
+      .mime_type
+      .clone()
+      .unwrap_or_else(|| guess_mime_type(&name).to_string());
+    let mut info = gst::Structure::builder("GstTagAttachment")
+      .field("filename", &name)
+      .field("mimetype", &mime_type);
+    if let Some(description) = &attachment.description {
+      info = info.field("description", description);
+    }
+    let sample = gst::Sample::builder()
+      .buffer(&gst::Buffer::from_mut_slice(data))
+      .caps(&gst::Caps::new_empty_simple(mime_type.as_str()))
+      .info(info.build())
+      .build();
+    setter.add_tag::<gst::tags::Attachment>(&sample, gst::TagMergeMode::Append);
+  }
+  Ok(())
+}
+
+/// Converts an attachment (or cover image) tag to an `AttachedFile`
+fn attached_file(sample: &gst::Sample, index: usize) -> Option<AttachedFile> {
+  let buffer = sample.buffer()?;
+  let data = buffer.map_readable().ok()?;
+  let info = sample.info();
+  let field = |name: &str| info.and_then(|info| info.get::<String>(name).ok());
+  let mime_type = field("mimetype")
+    .or_else(|| {
+      sample
+        .caps()
+        .and_then(|caps| caps.structure(0))
+        .map(|s| s.name().to_string())
+    })
+    .unwrap_or_else(|| "application/octet-stream".to_string());
+  Some(AttachedFile {
+    name: field("filename").unwrap_or_else(|| format!("attachment-{}", index + 1)),
+    mime_type,
+    description: field("description"),
+    data: data.as_slice().to_vec().into(),
+  })
+}
+
+/// Lists the files attached to a Matroska file
+///
+/// # Arguments
+/// * `path` - Path of a Matroska (.mkv, .mka) file
+///
+/// # Returns
+/// * `Result<Vec<AttachedFile>>` - The attachments, in file order
+///
+/// # Example
+/// ```javascript
+/// const cover = getAttachments("album.mka").find(a => a.mimeType.startsWith("image/"));
+/// if (cover) fs.writeFileSync(cover.name, cover.data);
+/// ```
+#[napi]
+pub fn get_attachments(path: String) -> Result<Vec<AttachedFile>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let pipeline = gst::Pipeline::new();
+  let source = gst::ElementFactory::make("filesrc")
+    .property("location", &path)
+    .build()
+    .map_err(|_| Error::new(Status::GenericFailure, "Element filesrc is not available"))?;
+  let demuxer = make_element("matroskademux")?;
+  pipeline.add_many([&source, &demuxer]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build pipeline: {}", e),
+    )
+  })?;
+  source.link(&demuxer).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link source: {}", e),
+    )
+  })?;
+  // Every stream needs a sink for the pipeline to preroll
+  let pipeline_weak = pipeline.downgrade();
+  demuxer.connect_pad_added(move |_, pad| {
+    let (Some(pipeline), Ok(sink)) = (
+      pipeline_weak.upgrade(),
+      gst::ElementFactory::make("fakesink")
+        .property("sync", false)
+        .build(),
+    ) else {
+      return;
+    };
+    if pipeline.add(&sink).is_ok() {
+      if let Some(sink_pad) = sink.static_pad("sink") {
+        let _ = pad.link(&sink_pad);
+      }
+      let _ = sink.sync_state_with_parent();
+    }
+  });
+
+  let bus = pipeline
+    .bus()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+  let started = pipeline.set_state(gst::State::Paused);
+  let mut files: Vec<AttachedFile> = Vec::new();
+  let mut result = started.map(|_| ()).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to open {}: {}", path, e),
+    )
+  });
+  while result.is_ok() {
+    let Some(msg) = bus.timed_pop_filtered(
+      gst::ClockTime::from_seconds(10),
+      &[
+        gst::MessageType::Tag,
+        gst::MessageType::AsyncDone,
+        gst::MessageType::Error,
+        gst::MessageType::Eos,
+      ],
+    ) else {
+      break;
+    };
+    match msg.view() {
+      gst::MessageView::Tag(tag) => {
+        let tags = tag.tags();
+        let samples = tags
+          .iter_tag::<gst::tags::Attachment>()
+          .chain(tags.iter_tag::<gst::tags::Image>())
+          .map(|value| value.get());
+        for sample in samples {
+          let Some(file) = attached_file(&sample, files.len()) else {
+            continue;
+          };
+          // Every stream carries the global tags; keep one copy of each file
+          if !files
+            .iter()
+            .any(|f| f.name == file.name && f.data.len() == file.data.len())
+          {
+            files.push(file);
+          }
+        }
+      }
+      gst::MessageView::Error(err) => {
+        result = Err(Error::new(
+          Status::GenericFailure,
+          format!("Failed to read {}: {}", path, err.error()),
+        ));
+      }
+      _ => break,
+    }
+  }
+  let _ = pipeline.set_state(gst::State::Null);
+  result.map(|_| files)
+}
@@ -0,0 +1,210 @@
+//! # Audio Filters
+//!
+//! A small filter chain for decoded audio, shared by the transcode audio branch
+//! and the `AudioMixer` inputs. Filters are given as `name=value` strings and
+//! applied in order:
+//!
+//! - `aresample=48000` - resample to a sample rate in Hz
+//! - `channels=stereo` - down- or upmix to "mono", "stereo" or a channel count
+//! - `gain=-6` - amplify or attenuate by a number of dB (at most +20)
+//! - `fadein=2` - fade in over the first seconds of the stream
+//! - `fadeout=8:2` - fade out over 2 seconds, starting 8 seconds into the stream
+//!
+//! Speed changes are not a filter string but a transcode option; their
+//! pitch-preserving time stretch is built by `make_tempo`.
+
+use crate::transcode::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+
+/// Highest gain accepted by the `volume` element, in dB (a factor of 10)
+const MAX_GAIN_DB: f64 = 20.0;
+
+/// A parsed audio filter
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AudioFilter {
+  Resample(i32),
+  Channels(i32),
+  Gain(f64),
+  FadeIn(gst::ClockTime),
+  FadeOut {
+    start: gst::ClockTime,
+    duration: gst::ClockTime,
+  },
+}
+
+fn invalid(filter: &str) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!("Invalid audio filter: {}", filter),
+  )
+}
+
+pub(crate) fn parse_seconds(value: &str) -> Option<gst::ClockTime> {
+  value
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .filter(|s| s.is_finite() && *s >= 0.0)
+    .map(|s| gst::ClockTime::from_nseconds((s * 1e9) as u64))
+}
+
+/// Parses a list of `name=value` filter strings
+pub(crate) fn parse_audio_filters(filters: &[String]) -> Result<Vec<AudioFilter>> {
+  filters
+    .iter()
+    .map(|filter| {
+      let (name, value) = filter.split_once('=').ok_or_else(|| invalid(filter))?;
+      let value = value.trim();
+      let parsed = match name.trim() {
+        "aresample" => value
+          .parse()
+          .ok()
+          .filter(|rate| *rate > 0)
+          .map(AudioFilter::Resample),
+        "channels" => match value {
+          "mono" => Some(1),
+          "stereo" => Some(2),
+          count => count.parse().ok().filter(|n| *n > 0),
+        }
+        .map(AudioFilter::Channels),
+        "gain" => value
+          .trim_end_matches("dB")
+          .trim()
+          .parse::<f64>()
+          .ok()
+          .filter(|db| db.is_finite() && *db <= MAX_GAIN_DB)
+          .map(AudioFilter::Gain),
+        "fadein" => parse_seconds(value)
+          .filter(|d| !d.is_zero())
+          .map(AudioFilter::FadeIn),
+        "fadeout" => value.split_once(':').and_then(|(start, duration)| {
+          Some(AudioFilter::FadeOut {
+            start: parse_seconds(start)?,
+            duration: parse_seconds(duration).filter(|d| !d.is_zero())?,
+          })
+        }),
+        _ => None,
+      };
+      parsed.ok_or_else(|| invalid(filter))
+    })
+    .collect()
+}
+
+fn capsfilter(caps: gst::Caps) -> Result<gst::Element> {
+  let element = make_element("capsfilter")?;
+  element.set_property("caps", caps);
+  Ok(element)
+}
+
+/// Linear gain of a fade at stream time `position`
+fn fade_gain(filter: &AudioFilter, position: gst::ClockTime) -> f64 {
+  let ratio = |offset: gst::ClockTime, duration: gst::ClockTime| {
+    (offset.nseconds() as f64 / duration.nseconds() as f64).clamp(0.0, 1.0)
+  };
+  match *filter {
+    AudioFilter::FadeIn(duration) => ratio(position, duration),
+    AudioFilter::FadeOut { start, duration } if position >= start => {
+      1.0 - ratio(position - start, duration)
+    }
+    _ => 1.0,
+  }
+}
+
+/// Creates a `volume` element whose gain follows a fade, updated per buffer
+fn fade(filter: AudioFilter) -> Result<gst::Element> {
+  let volume = make_element("volume")?;
+  let pad = volume
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Volume has no sink pad"))?;
+  let element = volume.downgrade();
+  pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+    let (Some(element), Some(pts)) = (
+      element.upgrade(),
+      info.buffer().and_then(|buffer| buffer.pts()),
+    ) else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let position = pad
+      .sticky_event::<gst::event::Segment>(0)
+      .and_then(|event| {
+        event
+          .segment()
+          .downcast_ref::<gst::ClockTime>()
+          .and_then(|segment| segment.to_stream_time(pts))
+      })
+      .unwrap_or(pts);
+    element.set_property("volume", fade_gain(&filter, position));
+    gst::PadProbeReturn::Ok
+  });
+  Ok(volume)
+}
+
+/// Creates the elements of a filter chain, to be linked in order after an
+/// `audioconvert ! audioresample` pair
+pub(crate) fn make_audio_filters(filters: &[AudioFilter]) -> Result<Vec<gst::Element>> {
+  let mut elements = Vec::new();
+  for filter in filters {
+    match *filter {
+      AudioFilter::Resample(rate) => {
+        elements.push(make_element("audioresample")?);
+        elements.push(capsfilter(
+          gst::Caps::builder("audio/x-raw")
+            .field("rate", rate)
+            .build(),
+        )?);
+      }
+      AudioFilter::Channels(channels) => {
+        elements.push(make_element("audioconvert")?);
+        elements.push(capsfilter(
+          gst::Caps::builder("audio/x-raw")
+            .field("channels", channels)
+            .build(),
+        )?);
+      }
+      AudioFilter::Gain(db) => {
+        let volume = make_element("volume")?;
+        volume.set_property("volume", 10f64.powf(db / 20.0));
+        elements.push(volume);
+      }
+      AudioFilter::FadeIn(_) | AudioFilter::FadeOut { .. } => {
+        elements.push(fade(filter.clone())?);
+      }
+    }
+  }
+  if !elements.is_empty() {
+    elements.push(make_element("audioconvert")?);
+  }
+  Ok(elements)
+}
+
+/// Creates a pitch-preserving time stretch playing `speed` times as fast.
+///
+/// `scaletempo` stretches by the rate of the incoming segment, so the segment
+/// rate is rewritten on its sink pad; its output is a normal-rate stream with
+/// rescaled timestamps.
+pub(crate) fn make_tempo(speed: f64) -> Result<Vec<gst::Element>> {
+  let scaletempo = make_element("scaletempo")?;
+  let pad = scaletempo
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Scaletempo has no sink pad"))?;
+  pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+    let Some(gst::PadProbeData::Event(event)) = &mut info.data else {
+      return gst::PadProbeReturn::Ok;
+    };
+    if let gst::EventView::Segment(segment) = event.view() {
+      if let Some(segment) = segment.segment().downcast_ref::<gst::ClockTime>() {
+        let mut segment = segment.clone();
+        segment.set_rate(segment.rate() * speed);
+        *event = gst::event::Segment::new(&segment);
+      }
+    }
+    gst::PadProbeReturn::Ok
+  });
+  Ok(vec![
+    make_element("audioconvert")?,
+    scaletempo,
+    make_element("audioconvert")?,
+  ])
+}
@@ -0,0 +1,366 @@
+//! # Audio Mixer
+//!
+//! Mixes several audio inputs into one output, with a volume and mute switch
+//! per input. The loudness of every input is measured before mixing, for
+//! level meters in production and monitoring tools.
+
+use crate::audio_filters::{make_audio_filters, parse_audio_filters};
+use crate::compositor::{
+  attach_input, detach_input, input_bin, push_to_input, set_pipeline_state, MixerInput,
+};
+use crate::transcode::launch;
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Prefix of input bin names, followed by the input id
+const INPUT_PREFIX: &str = "input_";
+
+/// Options for the `AudioMixer` constructor
+#[napi(object)]
+pub struct AudioMixerOptions {
+  /// Output sample rate in Hz (default: 48000)
+  pub rate: Option<u32>,
+  /// Output channel count (default: 2)
+  pub channels: Option<u32>,
+  /// Sink description receiving the mixed audio (default: "autoaudiosink")
+  pub output: Option<String>,
+}
+
+/// Volume, mute state and filters of a mixer input
+#[napi(object)]
+pub struct AudioInputOptions {
+  /// Linear gain, where 1 keeps the input level (default: 1)
+  pub volume: Option<f64>,
+  /// Whether the input is silenced (default: false)
+  pub mute: Option<bool>,
+  /// Audio filters applied to the input before it is measured and mixed,
+  /// e.g. `["gain=-6", "fadein=2"]`
+  pub filters: Option<Vec<String>>,
+}
+
+/// Measured loudness of a mixer input, per channel
+#[napi(object)]
+#[derive(Clone)]
+pub struct InputLevel {
+  /// Id of the input
+  pub id: String,
+  /// RMS level of each channel in dB (0 is full scale)
+  pub rms: Vec<f64>,
+  /// Peak level of each channel in dB (0 is full scale)
+  pub peak: Vec<f64>,
+}
+
+/// Links filter elements between the resampler and the level meter of an input bin
+fn insert_filters(bin: &gst::Bin, filters: Vec<gst::Element>) -> Result<()> {
+  let (Some(resample), Some(level)) = (bin.by_name("resample"), bin.by_name("level")) else {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Input bin has no filter insertion point",
+    ));
+  };
+  resample.unlink(&level);
+  let mut chain = vec![resample];
+  chain.extend(filters);
+  chain.push(level);
+  bin.add_many(&chain[1..chain.len() - 1]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add audio filters: {}", e),
+    )
+  })?;
+  gst::Element::link_many(&chain).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link audio filters: {}", e),
+    )
+  })
+}
+
+/// Reads a list of per-channel dB values from a `level` message field
+fn level_values(s: &gst::StructureRef, field: &str) -> Vec<f64> {
+  s.get::<glib::ValueArray>(field)
+    .map(|values| values.iter().filter_map(|v| v.get::<f64>().ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Mixer of several audio inputs with per-input volume, mute and levels
+///
+/// # Example
+/// ```javascript
+/// const mixer = new AudioMixer();
+/// mixer.addInput("music", "music.mp3", { volume: 0.3 });
+/// mixer.addInput("mic", "pulsesrc");
+/// mixer.play();
+/// setInterval(() => console.log(mixer.getLevels()), 100);
+/// ```
+#[napi]
+pub struct AudioMixer {
+  pipeline: gst::Pipeline,
+  mixer: gst::Element,
+  inputs: Mutex<HashMap<String, MixerInput>>,
+  levels: Arc<Mutex<HashMap<String, InputLevel>>>,
+}
+
+impl Drop for AudioMixer {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
+
+impl AudioMixer {
+  /// Runs `f` on the mixer pad of the input registered under `id`
+  fn with_pad<T>(&self, id: &str, f: impl FnOnce(&gst::Pad) -> T) -> Result<T> {
+    let inputs = self.inputs.lock().unwrap();
+    let input = inputs
+      .get(id)
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Input not found: {}", id)))?;
+    Ok(f(&input.pad))
+  }
+}
+
+#[napi]
+impl AudioMixer {
+  /// Creates a mixer with no inputs
+  ///
+  /// # Arguments
+  /// * `options` - Optional output format and sink
+  ///
+  /// # Example
+  /// ```javascript
+  /// const mixer = new AudioMixer({ rate: 44100, output: "filesink location=mix.wav" });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: Option<AudioMixerOptions>) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let options = options.unwrap_or(AudioMixerOptions {
+      rate: None,
+      channels: None,
+      output: None,
+    });
+    let pipeline = launch(&format!(
+      "audiomixer name=mix ! audioconvert ! audioresample ! audio/x-raw,rate={},channels={} ! {}",
+      options.rate.unwrap_or(48000),
+      options.channels.unwrap_or(2),
+      options.output.as_deref().unwrap_or("autoaudiosink"),
+    ))?;
+    let mixer = pipeline
+      .by_name("mix")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Element mix not found"))?;
+
+    let levels: Arc<Mutex<HashMap<String, InputLevel>>> = Arc::new(Mutex::new(HashMap::new()));
+    let bus = pipeline
+      .bus()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+    let levels_clone = levels.clone();
+    bus.set_sync_handler(move |_, msg| {
+      let gst::MessageView::Element(element) = msg.view() else {
+        return gst::BusSyncReply::Pass;
+      };
+      let Some(s) = element.structure().filter(|s| s.name() == "level") else {
+        return gst::BusSyncReply::Pass;
+      };
+      let id = msg
+        .src()
+        .and_then(|src| src.parent())
+        .and_then(|bin| bin.name().strip_prefix(INPUT_PREFIX).map(str::to_string));
+      if let Some(id) = id {
+        let level = InputLevel {
+          id: id.clone(),
+          rms: level_values(s, "rms"),
+          peak: level_values(s, "peak"),
+        };
+        levels_clone.lock().unwrap().insert(id, level);
+      }
+      gst::BusSyncReply::Drop
+    });
+
+    Ok(AudioMixer {
+      pipeline,
+      mixer,
+      inputs: Mutex::new(HashMap::new()),
+      levels,
+    })
+  }
+
+  /// Adds an audio input
+  ///
+  /// # Arguments
+  /// * `id` - Unique name of the input
+  /// * `source` - A file path, a URI, or a launch description producing audio
+  ///   (e.g. "pulsesrc" or "appsrc caps=audio/x-raw,... format=time")
+  /// * `options` - Optional initial volume, mute state and audio filters
+  ///
+  /// # Example
+  /// ```javascript
+  /// mixer.addInput("tone", "audiotestsrc freq=440", { volume: 0.5 });
+  /// ```
+  #[napi]
+  pub fn add_input(
+    &self,
+    id: String,
+    source: String,
+    options: Option<AudioInputOptions>,
+  ) -> Result<()> {
+    let mut inputs = self.inputs.lock().unwrap();
+    if inputs.contains_key(&id) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Input already exists: {}", id),
+      ));
+    }
+
+    let filters = parse_audio_filters(
+      options
+        .as_ref()
+        .and_then(|options| options.filters.as_deref())
+        .unwrap_or_default(),
+    )?;
+    let bin = input_bin(
+      &source,
+      "audio/x-raw",
+      "audioconvert ! audioresample name=resample ! level name=level interval=100000000 post-messages=true ! queue",
+    )?;
+    if !filters.is_empty() {
+      insert_filters(&bin, make_audio_filters(&filters)?)?;
+    }
+    bin.set_property("name", format!("{}{}", INPUT_PREFIX, id));
+    let input = attach_input(&self.pipeline, &self.mixer, bin)?;
+    if let Some(options) = options {
+      if let Some(volume) = options.volume {
+        input.pad.set_property("volume", volume.clamp(0.0, 10.0));
+      }
+      if let Some(mute) = options.mute {
+        input.pad.set_property("mute", mute);
+      }
+    }
+    inputs.insert(id, input);
+    Ok(())
+  }
+
+  /// Removes an input
+  ///
+  /// # Returns
+  /// * `bool` - Whether an input with this id existed
+  #[napi]
+  pub fn remove_input(&self, id: String) -> bool {
+    self.levels.lock().unwrap().remove(&id);
+    match self.inputs.lock().unwrap().remove(&id) {
+      Some(input) => {
+        detach_input(&self.pipeline, &self.mixer, input);
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the ids of all inputs
+  #[napi]
+  pub fn list_inputs(&self) -> Vec<String> {
+    let mut ids: Vec<String> = self.inputs.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+  }
+
+  /// Sets the linear gain of an input, from 0 to 10
+  ///
+  /// # Example
+  /// ```javascript
+  /// mixer.setVolume("music", 0.2);
+  /// ```
+  #[napi]
+  pub fn set_volume(&self, id: String, volume: f64) -> Result<()> {
+    self.with_pad(&id, |pad| {
+      pad.set_property("volume", volume.clamp(0.0, 10.0))
+    })
+  }
+
+  /// Returns the linear gain of an input
+  #[napi]
+  pub fn get_volume(&self, id: String) -> Result<f64> {
+    self.with_pad(&id, |pad| pad.property::<f64>("volume"))
+  }
+
+  /// Silences or restores an input
+  #[napi]
+  pub fn set_mute(&self, id: String, mute: bool) -> Result<()> {
+    self.with_pad(&id, |pad| pad.set_property("mute", mute))
+  }
+
+  /// Returns whether an input is muted
+  #[napi]
+  pub fn is_muted(&self, id: String) -> Result<bool> {
+    self.with_pad(&id, |pad| pad.property::<bool>("mute"))
+  }
+
+  /// Returns the latest measured levels of every input, sorted by id
+  ///
+  /// Levels are measured every 100 ms before volume and mute are applied.
+  /// Inputs that have not produced audio yet are omitted.
+  ///
+  /// # Example
+  /// ```javascript
+  /// for (const { id, peak } of mixer.getLevels()) {
+  ///   console.log(id, Math.max(...peak).toFixed(1), "dB");
+  /// }
+  /// ```
+  #[napi]
+  pub fn get_levels(&self) -> Vec<InputLevel> {
+    let mut levels: Vec<InputLevel> = self.levels.lock().unwrap().values().cloned().collect();
+    levels.sort_by(|a, b| a.id.cmp(&b.id));
+    levels
+  }
+
+  /// Pushes raw samples to an input whose source is an AppSrc
+  ///
+  /// # Arguments
+  /// * `id` - The input id
+  /// * `data` - Samples matching the caps of the input's AppSrc
+  #[napi]
+  pub fn push_samples(&self, id: String, data: Buffer) -> Result<()> {
+    let inputs = self.inputs.lock().unwrap();
+    let input = inputs
+      .get(&id)
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Input not found: {}", id)))?;
+    push_to_input(&input.bin, &id, data)
+  }
+
+  /// Returns the current state of the pipeline
+  ///
+  /// # Returns
+  /// * `String` - The current state ("Null", "Ready", "Paused" or "Playing")
+  #[napi]
+  pub fn get_state(&self) -> String {
+    let (_, state, _) = self.pipeline.state(gst::ClockTime::ZERO);
+    format!("{:?}", state)
+  }
+
+  /// Starts mixing
+  #[napi]
+  pub fn play(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Playing)
+  }
+
+  /// Pauses mixing
+  #[napi]
+  pub fn pause(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Paused)
+  }
+
+  /// Stops mixing and resets the pipeline to the Null state
+  #[napi]
+  pub fn stop(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Null)
+  }
+}
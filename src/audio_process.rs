@@ -0,0 +1,449 @@
+//! # Audio Processing
+//!
+//! Common podcast-style edits on audio files: concatenating several inputs,
+//! trimming to a time range, fading in and out and normalizing the peak level,
+//! written to WAV, FLAC, Opus, Vorbis, MP3 or AAC. The options mirror the
+//! transcode options, with `format` playing the role of the container.
+//!
+//! `extractAudioForASR` covers the step before speech recognition: the audio
+//! track of any input becomes 16 kHz mono 16-bit PCM WAV.
+
+use crate::audio_filters::{make_audio_filters, AudioFilter};
+use crate::codecs::resolve;
+use crate::compositor::{attach_input, input_bin};
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{make_element, wait_for_eos};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::bindgen_prelude::Either;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Bytes per sample of the F32LE working format
+const SAMPLE_BYTES: usize = 4;
+
+/// Highest gain applied when normalizing, in dB
+const MAX_NORMALIZE_GAIN_DB: f64 = 20.0;
+
+/// Output format of `extractAudioForASR`, expected by most speech-to-text engines
+const ASR_CAPS: &str = "audio/x-raw,format=S16LE,layout=interleaved,rate=16000,channels=1";
+
+/// Time range of the (concatenated) input to keep
+#[napi(object)]
+pub struct AudioTrim {
+  /// Start of the range in milliseconds (default: 0)
+  pub start_ms: Option<f64>,
+  /// End of the range in milliseconds (default: end of the input)
+  pub end_ms: Option<f64>,
+}
+
+/// Fade in and out lengths
+#[napi(object)]
+pub struct AudioFade {
+  /// Fade-in length in milliseconds from the start of the output
+  pub in_ms: Option<f64>,
+  /// Fade-out length in milliseconds before the end of the output
+  pub out_ms: Option<f64>,
+}
+
+/// Options for `processAudio`
+#[napi(object)]
+pub struct AudioProcessOptions {
+  /// Time range to keep
+  pub trim: Option<AudioTrim>,
+  /// Fade in and out
+  pub fade: Option<AudioFade>,
+  /// Peak level to normalize to, in dBFS (e.g. -1). The input is scanned once
+  /// to measure its peak before it is written.
+  pub normalize: Option<f64>,
+  /// Output format ("wav", "flac", "opus", "ogg", "mp3" or "m4a"); defaults to
+  /// the output file extension
+  pub format: Option<String>,
+  /// Target bitrate in kbit/s, for lossy formats
+  pub bitrate: Option<u32>,
+}
+
+/// Summary of a `processAudio` run
+#[napi(object)]
+pub struct AudioProcessResult {
+  /// Length of the written audio in milliseconds
+  pub duration_ms: f64,
+  /// Sample rate of the written audio in Hz
+  pub sample_rate: u32,
+  /// Channel count of the written audio
+  pub channels: u32,
+  /// Gain applied by normalization in dB (0 without normalization)
+  pub gain_db: f64,
+}
+
+/// Codec and muxer of an output format
+struct AudioFormat {
+  /// Audio codec name, or `None` for uncompressed PCM
+  codec: Option<&'static str>,
+  muxer: Option<&'static str>,
+}
+
+fn audio_format(format: &str) -> Result<AudioFormat> {
+  let (codec, muxer) = match format {
+    "wav" => (None, Some("wavenc")),
+    "flac" => (Some("flac"), None),
+    "opus" => (Some("opus"), Some("oggmux")),
+    "ogg" => (Some("vorbis"), Some("oggmux")),
+    "mp3" => (Some("mp3"), None),
+    "m4a" => (Some("aac"), Some("mp4mux")),
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported audio format: {}", format),
+      ))
+    }
+  };
+  Ok(AudioFormat { codec, muxer })
+}
+
+/// Sample range kept by the trim stage, with the peak of the kept samples
+#[derive(Default)]
+struct TrimState {
+  /// Frames seen so far
+  offset: u64,
+  /// Frames passed downstream so far
+  kept: u64,
+  /// Highest absolute sample value passed downstream
+  peak: f32,
+  ended: bool,
+}
+
+/// Creates an `identity` element that keeps frames `start..end` of its F32LE
+/// input, restamps them from zero and sends EOS after the range
+fn trim_stage(
+  rate: u32,
+  channels: u32,
+  start: u64,
+  end: Option<u64>,
+  state: Arc<Mutex<TrimState>>,
+) -> Result<gst::Element> {
+  let identity = make_element("identity")?;
+  let pad = identity
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Identity has no sink pad"))?;
+  let frame_bytes = channels as usize * SAMPLE_BYTES;
+  let src = identity.static_pad("src");
+  pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+    let Some(buffer) = info.buffer() else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let mut state = state.lock().unwrap();
+    let first = state.offset;
+    state.offset += (buffer.size() / frame_bytes) as u64;
+    let keep_start = first.max(start);
+    let keep_end = end.map_or(state.offset, |end| state.offset.min(end));
+    if keep_start >= keep_end {
+      if end.is_some_and(|end| first >= end) && !state.ended {
+        state.ended = true;
+        if let Some(src) = &src {
+          src.push_event(gst::event::Eos::new());
+        }
+      }
+      return gst::PadProbeReturn::Drop;
+    }
+
+    let Ok(map) = buffer.map_readable() else {
+      return gst::PadProbeReturn::Drop;
+    };
+    let bytes =
+      &map[(keep_start - first) as usize * frame_bytes..(keep_end - first) as usize * frame_bytes];
+    for sample in bytes.chunks_exact(SAMPLE_BYTES) {
+      let value = f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]).abs();
+      state.peak = state.peak.max(value);
+    }
+    let mut kept = gst::Buffer::from_slice(bytes.to_vec());
+    {
+      let kept = kept.get_mut().unwrap();
+      let to_time = |frames: u64| {
+        gst::ClockTime::SECOND
+          .mul_div_floor(frames, rate as u64)
+          .unwrap_or_default()
+      };
+      kept.set_pts(to_time(state.kept));
+      kept.set_duration(to_time(state.kept + keep_end - keep_start) - to_time(state.kept));
+    }
+    state.kept += keep_end - keep_start;
+    drop(map);
+    info.data = Some(gst::PadProbeData::Buffer(kept));
+    gst::PadProbeReturn::Ok
+  });
+  Ok(identity)
+}
+
+/// Builds `inputs -> concat -> trim stage`, ready for the output elements to be
+/// linked after the trim stage
+fn decode_inputs(
+  inputs: &[String],
+  rate: u32,
+  channels: u32,
+  trim_stage: gst::Element,
+) -> Result<gst::Pipeline> {
+  let pipeline = gst::Pipeline::new();
+  let concat = make_element("concat")?;
+  pipeline.add_many([&concat, &trim_stage]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build audio pipeline: {}", e),
+    )
+  })?;
+  let tail = format!(
+    "audioconvert ! audioresample ! audio/x-raw,format=F32LE,layout=interleaved,rate={},channels={}",
+    rate, channels
+  );
+  for input in inputs {
+    attach_input(&pipeline, &concat, input_bin(input, "audio/x-raw", &tail)?)?;
+  }
+  concat.link(&trim_stage).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link audio pipeline: {}", e),
+    )
+  })?;
+  Ok(pipeline)
+}
+
+fn run(pipeline: &gst::Pipeline) -> Result<()> {
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
+
+/// Trims, fades, normalizes and concatenates audio files
+///
+/// Several inputs are joined in order before the other edits are applied, so
+/// `trim` and `fade` refer to the concatenated audio. The output keeps the
+/// sample rate and channel count of the first input.
+///
+/// # Arguments
+/// * `input` - An input file path or URI, or a list of them to concatenate
+/// * `output_path` - Where to write the result
+/// * `options` - Trim range, fades, normalization target and output format
+///
+/// # Returns
+/// * `Result<AudioProcessResult>` - Length, format and normalization gain of the output
+///
+/// # Example
+/// ```javascript
+/// processAudio(["intro.wav", "episode.wav"], "episode.opus", {
+///   trim: { startMs: 500 },
+///   fade: { inMs: 1000, outMs: 3000 },
+///   normalize: -1,
+/// });
+/// ```
+#[napi]
+pub fn process_audio(
+  input: Either<String, Vec<String>>,
+  output_path: String,
+  options: Option<AudioProcessOptions>,
+) -> Result<AudioProcessResult> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let inputs = match input {
+    Either::A(input) => vec![input],
+    Either::B(inputs) => inputs,
+  };
+  let options = options.unwrap_or(AudioProcessOptions {
+    trim: None,
+    fade: None,
+    normalize: None,
+    format: None,
+    bitrate: None,
+  });
+  let format = options
+    .format
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer an audio format for {}", output_path),
+      )
+    })?;
+  let format = audio_format(&format)?;
+
+  let mut total = gst::ClockTime::ZERO;
+  let mut stream = None;
+  for input in &inputs {
+    let info = probe_with_gstreamer(input.clone(), None)?;
+    let audio = info
+      .audio
+      .first()
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("{} has no audio stream", input)))?;
+    stream.get_or_insert((audio.sample_rate, audio.channels));
+    total += gst::ClockTime::from_nseconds(info.duration.max(0) as u64);
+  }
+  let (rate, channels) =
+    stream.ok_or_else(|| Error::new(Status::InvalidArg, "No input given".to_string()))?;
+
+  let ms = |ms: f64| gst::ClockTime::from_nseconds((ms.max(0.0) * 1_000_000.0) as u64);
+  let (start, end) = match &options.trim {
+    Some(trim) => (ms(trim.start_ms.unwrap_or(0.0)), trim.end_ms.map(ms)),
+    None => (gst::ClockTime::ZERO, None),
+  };
+  if end.is_some_and(|end| end <= start) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Trim range must satisfy startMs < endMs".to_string(),
+    ));
+  }
+  let to_frames = |time: gst::ClockTime| {
+    time
+      .nseconds()
+      .mul_div_floor(rate as u64, 1_000_000_000)
+      .unwrap_or(0)
+  };
+  let (start_frame, end_frame) = (to_frames(start), end.map(to_frames));
+
+  let mut filters = Vec::new();
+  let mut gain_db = 0.0;
+  if let Some(target) = options.normalize {
+    let state = Arc::new(Mutex::new(TrimState::default()));
+    let trim = trim_stage(rate, channels, start_frame, end_frame, state.clone())?;
+    let pipeline = decode_inputs(&inputs, rate, channels, trim.clone())?;
+    let sink = make_element("fakesink")?;
+    pipeline
+      .add(&sink)
+      .and_then(|_| trim.link(&sink))
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to build level scan: {}", e),
+        )
+      })?;
+    run(&pipeline)?;
+    let peak = state.lock().unwrap().peak;
+    if peak > 0.0 {
+      gain_db = (target - 20.0 * (peak as f64).log10()).min(MAX_NORMALIZE_GAIN_DB);
+      filters.push(AudioFilter::Gain(gain_db));
+    }
+  }
+
+  let duration = end.unwrap_or(total).min(total).saturating_sub(start);
+  if let Some(fade) = &options.fade {
+    if let Some(fade_in) = fade.in_ms.map(ms).filter(|d| !d.is_zero()) {
+      filters.push(AudioFilter::FadeIn(fade_in));
+    }
+    if let Some(fade_out) = fade.out_ms.map(ms).filter(|d| !d.is_zero()) {
+      filters.push(AudioFilter::FadeOut {
+        start: duration.saturating_sub(fade_out),
+        duration: fade_out,
+      });
+    }
+  }
+
+  let state = Arc::new(Mutex::new(TrimState::default()));
+  let trim = trim_stage(rate, channels, start_frame, end_frame, state.clone())?;
+  let pipeline = decode_inputs(&inputs, rate, channels, trim.clone())?;
+  let mut elements = vec![trim];
+  elements.extend(make_audio_filters(&filters)?);
+  elements.push(make_element("audioconvert")?);
+  elements.push(make_element("audioresample")?);
+  if let Some(codec) = format.codec {
+    let spec = resolve("audio", codec)?;
+    let encoder = make_element(&spec.encoder)?;
+    if let (Some(property), Some(kbps)) = (&spec.bitrate_property, options.bitrate) {
+      encoder.set_property_from_str(property, &(kbps * spec.bitrate_scale).to_string());
+    }
+    elements.push(encoder);
+    if let Some(parser) = &spec.parser {
+      elements.push(make_element(parser)?);
+    }
+  }
+  if let Some(muxer) = format.muxer {
+    elements.push(make_element(muxer)?);
+  }
+  let sink = make_element("filesink")?;
+  sink.set_property("location", &output_path);
+  elements.push(sink);
+
+  pipeline.add_many(&elements[1..]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build audio pipeline: {}", e),
+    )
+  })?;
+  gst::Element::link_many(&elements).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link audio output: {}", e),
+    )
+  })?;
+  run(&pipeline)?;
+
+  let kept = state.lock().unwrap().kept;
+  Ok(AudioProcessResult {
+    duration_ms: kept as f64 * 1000.0 / rate as f64,
+    sample_rate: rate,
+    channels,
+    gain_db,
+  })
+}
+
+/// Extracts the audio track of a media file as 16 kHz mono 16-bit PCM WAV,
+/// the input format of most speech-to-text engines
+///
+/// # Arguments
+/// * `input` - A media file path or URI with an audio track
+/// * `output_path` - Where to write the WAV file
+///
+/// # Example
+/// ```javascript
+/// extractAudioForASR("interview.mp4", "interview.wav");
+/// ```
+#[napi(js_name = "extractAudioForASR")]
+pub fn extract_audio_for_asr(input: String, output_path: String) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  if probe_with_gstreamer(input.clone(), None)?.audio.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{} has no audio stream", input),
+    ));
+  }
+  let bin = input_bin(
+    &input,
+    "audio/x-raw",
+    &format!(
+      "audioconvert ! audioresample ! {} ! wavenc ! filesink name=sink",
+      ASR_CAPS
+    ),
+  )?;
+  if let Some(sink) = bin.by_name("sink") {
+    sink.set_property("location", &output_path);
+  }
+  let pipeline = gst::Pipeline::new();
+  pipeline.add(&bin).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build audio pipeline: {}", e),
+    )
+  })?;
+  run(&pipeline)
+}
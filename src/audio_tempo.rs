@@ -0,0 +1,211 @@
+//! # Audio Tempo and Pitch
+//!
+//! Playback speed and pitch controls that keep audio natural. Changing the
+//! playback rate alone resamples the audio, raising its pitch as it speeds
+//! up; a `scaletempo` element in front of the audio sink stretches it back
+//! to its original pitch. A `pitch` element (from the SoundTouch plugin)
+//! shifts the pitch without changing the speed. Both are inserted while the
+//! pipeline runs, the first time they are needed, wrapped in converters so
+//! they fit whatever format the sink negotiated.
+
+use crate::missing_plugins::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Name of the bin stretching the audio to keep its pitch
+const TEMPO_BIN: &str = "gstkit-scaletempo";
+/// Name of the bin shifting the pitch of the audio
+const PITCH_BIN: &str = "gstkit-pitch";
+/// How long to wait for the audio to pause between buffers to relink it
+const RELINK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Finds the audio sink of a pipeline, once its input is negotiated
+fn audio_sink(pipeline: &gst::Pipeline) -> Result<gst::Element> {
+  pipeline
+    .iterate_recurse()
+    .into_iter()
+    .flatten()
+    .find(|element| {
+      element.element_flags().contains(gst::ElementFlags::SINK)
+        && !element.is::<gst::Bin>()
+        && element.sink_pads().iter().any(|pad| {
+          pad
+            .current_caps()
+            .and_then(|caps| {
+              caps
+                .structure(0)
+                .map(|s| s.name().starts_with("audio/x-raw"))
+            })
+            .unwrap_or(false)
+        })
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "No playing audio sink found; start the pipeline first".to_string(),
+      )
+    })
+}
+
+/// Returns the `factory` element of the bin called `bin_name` in front of
+/// the audio sink, inserting `audioconvert ! factory ! audioconvert` the
+/// first time
+fn audio_filter(pipeline: &gst::Pipeline, bin_name: &str, factory: &str) -> Result<gst::Element> {
+  if let Some(filter) = pipeline
+    .by_name(bin_name)
+    .and_then(|bin| bin.downcast::<gst::Bin>().ok())
+    .and_then(|bin| bin.by_name(factory))
+  {
+    return Ok(filter);
+  }
+
+  let sink = audio_sink(pipeline)?;
+  let filter = make_element(factory)?;
+  filter.set_property("name", factory);
+  let (convert_in, convert_out) = (make_element("audioconvert")?, make_element("audioconvert")?);
+  let bin = gst::Bin::with_name(bin_name);
+  let link_failed = |e: gst::glib::BoolError| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build {}: {}", bin_name, e),
+    )
+  };
+  bin
+    .add_many([&convert_in, &filter, &convert_out])
+    .map_err(link_failed)?;
+  gst::Element::link_many([&convert_in, &filter, &convert_out]).map_err(link_failed)?;
+  for (name, inner) in [("sink", &convert_in), ("src", &convert_out)] {
+    let ghost =
+      gst::GhostPad::with_target(&inner.static_pad(name).unwrap()).map_err(link_failed)?;
+    bin.add_pad(&ghost).map_err(link_failed)?;
+  }
+
+  let sink_pad = sink.static_pad("sink").ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!("{} has no sink pad", sink.name()),
+    )
+  })?;
+  let upstream = sink_pad.peer().ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!("{} is not linked", sink.name()),
+    )
+  })?;
+  let parent = sink
+    .parent()
+    .and_then(|parent| parent.downcast::<gst::Bin>().ok())
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("{} is not in a bin", sink.name()),
+      )
+    })?;
+  parent.add(&bin).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add {}: {}", bin_name, e),
+    )
+  })?;
+  let _ = bin.sync_state_with_parent();
+
+  // Relink once no data is flowing; this runs right away when the pad is
+  // already idle
+  let inserted = bin.clone();
+  let (relinked, result) = mpsc::channel();
+  upstream.add_probe(gst::PadProbeType::IDLE, move |upstream, _| {
+    let mut linked = false;
+    if let (Some(bin_sink), Some(bin_src)) =
+      (inserted.static_pad("sink"), inserted.static_pad("src"))
+    {
+      if upstream.unlink(&sink_pad).is_ok() {
+        linked = upstream.link(&bin_sink).is_ok() && bin_src.link(&sink_pad).is_ok();
+        if !linked {
+          // Put the sink back as it was
+          let _ = upstream.unlink(&bin_sink);
+          let _ = bin_src.unlink(&sink_pad);
+          let _ = upstream.link(&sink_pad);
+        }
+      }
+    }
+    let _ = relinked.send(linked);
+    gst::PadProbeReturn::Remove
+  });
+  if let Ok(false) = result.recv_timeout(RELINK_TIMEOUT) {
+    let _ = bin.set_state(gst::State::Null);
+    let _ = parent.remove(&bin);
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("Failed to link {} in front of {}", bin_name, sink.name()),
+    ));
+  }
+  Ok(filter)
+}
+
+/// Plays `pipeline` at `factor` times normal speed, keeping the pitch of
+/// its audio, within `region` if a region is looped
+pub(crate) fn set_tempo(
+  pipeline: &gst::Pipeline,
+  factor: f64,
+  region: Option<(gst::ClockTime, gst::ClockTime)>,
+) -> Result<()> {
+  if !(factor.is_finite() && factor > 0.0) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Tempo must be positive, got {}", factor),
+    ));
+  }
+  audio_filter(pipeline, TEMPO_BIN, "scaletempo")?;
+  let position = pipeline.query_position::<gst::ClockTime>().ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      "Failed to query position".to_string(),
+    )
+  })?;
+  let (flags, stop) = match region {
+    Some((_, end)) => (
+      gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE | gst::SeekFlags::SEGMENT,
+      Some(end),
+    ),
+    None => (gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, None),
+  };
+  pipeline
+    .seek(
+      factor,
+      flags,
+      gst::SeekType::Set,
+      position,
+      if stop.is_some() {
+        gst::SeekType::Set
+      } else {
+        gst::SeekType::None
+      },
+      stop,
+    )
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to change the tempo: {}", e),
+      )
+    })
+}
+
+/// Shifts the pitch of the audio of `pipeline` by `semitones`, without
+/// changing its speed
+pub(crate) fn set_pitch(pipeline: &gst::Pipeline, semitones: f64) -> Result<()> {
+  if !(-24.0..=24.0).contains(&semitones) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Pitch must be within two octaves, got {} semitones",
+        semitones
+      ),
+    ));
+  }
+  let pitch = audio_filter(pipeline, PITCH_BIN, "pitch")?;
+  pitch.set_property("pitch", 2f64.powf(semitones / 12.0) as f32);
+  Ok(())
+}
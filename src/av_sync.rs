@@ -0,0 +1,223 @@
+//! # Audio/Video Sync
+//!
+//! Measures how far the audio of a file drifts from its video. Players output
+//! decoded audio samples back to back, so the audio heard at any moment is
+//! given by the number of samples played, not by the timestamps of the audio
+//! buffers. When the muxer (or a capture device with its own clock) wrote
+//! timestamps that disagree with the sample count, the audio slides against
+//! the video over the course of the file, even though every timestamp is
+//! right. The drift at a point of the file is the audio timestamp minus the
+//! time the samples before it last, counted from the first audio buffer.
+//!
+//! The `asyncCorrect` transcode option removes the drift while remuxing by
+//! inserting silence where samples are missing and dropping the ones that
+//! overlap, so the output audio follows its timestamps exactly.
+
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{launch, make_element, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+
+/// Nanoseconds in a minute
+const MINUTE: i64 = 60_000_000_000;
+
+/// Audio/video offset at one minute of a file
+#[napi(object)]
+pub struct AvSyncOffset {
+  /// Minute of the audio, from 0
+  pub minute: u32,
+  /// Drift in nanoseconds at the start of the minute, positive when the
+  /// audio is heard ahead of the video
+  pub offset: i64,
+}
+
+/// Audio/video synchronization of a file
+#[napi(object)]
+pub struct AvSyncReport {
+  /// First audio timestamp minus first video timestamp, in nanoseconds
+  pub start_offset: i64,
+  /// Drift of largest magnitude found, in nanoseconds, positive when the
+  /// audio is heard ahead of the video
+  pub max_drift: i64,
+  /// Drift at the end of the audio, in nanoseconds
+  pub final_drift: i64,
+  /// Drift at the start of every minute of the audio
+  pub offsets: Vec<AvSyncOffset>,
+  /// Number of audio buffers whose timestamp did not follow on from the
+  /// previous one
+  pub discontinuities: u32,
+}
+
+#[derive(Default)]
+struct Tracker {
+  /// First audio timestamp and the samples decoded since
+  audio_start: Option<i64>,
+  samples: u64,
+  /// End of the previous buffer according to its timestamp
+  previous_end: Option<i64>,
+  video_start: Option<i64>,
+  max_drift: i64,
+  drift: i64,
+  offsets: Vec<AvSyncOffset>,
+  discontinuities: u32,
+}
+
+impl Tracker {
+  fn audio(&mut self, pts: i64, frames: u64, rate: u32) {
+    let start = *self.audio_start.get_or_insert(pts);
+    let drift = pts - start - (self.samples as i128 * 1_000_000_000 / rate as i128) as i64;
+    let duration = (frames as i128 * 1_000_000_000 / rate as i128) as i64;
+    // Timestamps are rounded, so a gap below a millisecond is not a break
+    if self
+      .previous_end
+      .is_some_and(|end| (pts - end).abs() > 1_000_000)
+    {
+      self.discontinuities += 1;
+    }
+    self.previous_end = Some(pts + duration);
+    self.samples += frames;
+    self.drift = drift;
+    if drift.abs() > self.max_drift.abs() {
+      self.max_drift = drift;
+    }
+    let minute = ((pts - start) / MINUTE) as u32;
+    if self.offsets.last().is_none_or(|last| last.minute < minute) {
+      self.offsets.push(AvSyncOffset {
+        minute,
+        offset: drift,
+      });
+    }
+  }
+}
+
+/// Measures the drift of the audio of a file against its video
+///
+/// # Arguments
+/// * `input` - Path or URI of a file with audio and video
+///
+/// # Returns
+/// * `Result<AvSyncReport>` - The start offset, the largest and final drift
+///   and the drift at every minute
+///
+/// # Example
+/// ```javascript
+/// const { maxDrift, offsets } = measureAvSync("recording.mkv");
+/// if (Math.abs(maxDrift) > 40_000_000) {
+///   new TranscodeJob("recording.mkv", "fixed.mkv", { container: "mkv", asyncCorrect: true }).start();
+/// }
+/// ```
+#[napi]
+pub fn measure_av_sync(input: String) -> Result<AvSyncReport> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  // Both branches must be linked for the pipeline to reach end of stream
+  let info = probe_with_gstreamer(input.clone(), None)?;
+  if info.audio.is_empty() || info.video.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{} needs both an audio and a video stream", input),
+    ));
+  }
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" name=dec \
+     dec. ! queue ! audioconvert ! audio/x-raw,format=S16LE,layout=interleaved ! appsink name=audio sync=false \
+     dec. ! queue ! videoconvert ! video/x-raw ! appsink name=video sync=false",
+    to_uri(&input)?
+  ))?;
+  let sink = |name: &str| {
+    pipeline
+      .by_name(name)
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Analysis sink not found"))
+  };
+  let (audio_sink, video_sink) = (sink("audio")?, sink("video")?);
+
+  let tracker = Arc::new(Mutex::new(Tracker::default()));
+  let audio_tracker = tracker.clone();
+  audio_sink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let structure = sample
+          .caps()
+          .and_then(|caps| caps.structure(0))
+          .ok_or(gst::FlowError::Error)?;
+        let rate = structure.get::<i32>("rate").unwrap_or(0).max(0) as u32;
+        let channels = structure.get::<i32>("channels").unwrap_or(1).max(1) as u64;
+        let Some(pts) = buffer.pts().filter(|_| rate > 0) else {
+          return Ok(gst::FlowSuccess::Ok);
+        };
+        // Samples are 16 bit
+        let frames = buffer.size() as u64 / (2 * channels);
+        audio_tracker
+          .lock()
+          .unwrap()
+          .audio(pts.nseconds() as i64, frames, rate);
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+  let video_tracker = tracker.clone();
+  video_sink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        if let Some(pts) = sample.buffer().and_then(|buffer| buffer.pts()) {
+          let mut tracker = video_tracker.lock().unwrap();
+          let start = tracker.video_start.get_or_insert(pts.nseconds() as i64);
+          *start = (*start).min(pts.nseconds() as i64);
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let mut tracker = tracker.lock().unwrap();
+  let (Some(audio_start), Some(video_start)) = (tracker.audio_start, tracker.video_start) else {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!(
+        "No timestamped audio and video could be decoded from {}",
+        input
+      ),
+    ));
+  };
+  Ok(AvSyncReport {
+    start_offset: audio_start - video_start,
+    max_drift: tracker.max_drift,
+    final_drift: tracker.drift,
+    offsets: std::mem::take(&mut tracker.offsets),
+    discontinuities: tracker.discontinuities,
+  })
+}
+
+/// Creates the elements conforming decoded audio to its timestamps: silence
+/// fills gaps, and the start of the segment when the audio begins late, and
+/// overlapping samples are dropped, once the drift exceeds 40 ms
+pub(crate) fn make_async_correct() -> Result<Vec<gst::Element>> {
+  let rate = make_element("audiorate")?;
+  rate.set_property("skip-to-first", false);
+  Ok(vec![rate])
+}
@@ -0,0 +1,261 @@
+//! # Benchmark
+//!
+//! Capability self-test that measures how fast the host can encode, decode and
+//! filter synthesized video, so deployments can pick codecs and presets that
+//! suit the machine they run on.
+
+use crate::codecs::codec_names;
+use crate::transcode::{launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Filters exercised by the benchmark, as launch string fragments
+const FILTERS: &[(&str, &str)] = &[
+  ("videoconvert", "videoconvert ! video/x-raw,format=RGBA"),
+  (
+    "videoscale",
+    "videoscale ! video/x-raw,width=640,height=360",
+  ),
+  ("videoflip", "videoflip method=clockwise"),
+];
+
+/// Options for `runBenchmark`
+#[napi(object)]
+pub struct BenchmarkOptions {
+  /// Frame size as "WIDTHxHEIGHT" (default: "1280x720")
+  pub resolution: Option<String>,
+  /// Seconds of 30 fps test content to process per run (default: 2)
+  pub seconds: Option<f64>,
+  /// Video codecs to benchmark (default: every supported codec)
+  pub codecs: Option<Vec<String>>,
+  /// Encoder thread counts to try (default: 1 and the number of CPUs)
+  pub thread_counts: Option<Vec<u32>>,
+}
+
+/// A single benchmark measurement
+#[napi(object)]
+pub struct BenchmarkResult {
+  /// Codec or filter name
+  pub name: String,
+  /// The measured operation ("encode", "decode" or "filter")
+  pub operation: String,
+  /// Encoder thread count, if the operation was run with a fixed count
+  pub threads: Option<u32>,
+  /// Frames processed
+  pub frames: u32,
+  /// Achieved frames per second, or 0 if the run failed
+  pub fps: f64,
+  /// Why the run failed (e.g. the encoder is not installed)
+  pub error: Option<String>,
+}
+
+pub(crate) fn parse_resolution(resolution: &str) -> Result<(u32, u32)> {
+  resolution
+    .split_once('x')
+    .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+    .filter(|&(w, h)| w > 0 && h > 0)
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid resolution: {}", resolution),
+      )
+    })
+}
+
+/// Runs a pipeline to end of stream and returns the elapsed wall time in seconds
+fn time_pipeline(
+  pipeline: &gst::Pipeline,
+  before_wait: impl FnOnce() -> Result<()>,
+) -> Result<f64> {
+  let start = Instant::now();
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = before_wait().and_then(|_| wait_for_eos(pipeline));
+  let elapsed = start.elapsed().as_secs_f64();
+  let _ = pipeline.set_state(gst::State::Null);
+  result.map(|_| elapsed)
+}
+
+fn test_source(width: u32, height: u32, frames: u32) -> String {
+  format!(
+    "videotestsrc pattern=smpte num-buffers={} ! video/x-raw,width={},height={},framerate=30/1",
+    frames, width, height
+  )
+}
+
+/// Encodes test frames into Matroska, returning the elapsed time and the encoded bytes
+fn encode(source: &str, codec: &str, threads: u32) -> Result<(f64, Vec<u8>)> {
+  let spec = video_codec_spec(codec)?;
+  let parser = spec.parser.map(|p| format!(" ! {}", p)).unwrap_or_default();
+  let pipeline = launch(&format!(
+    "{} ! videoconvert ! {} name=enc{} ! matroskamux ! appsink name=sink sync=false",
+    source, spec.encoder, parser
+  ))?;
+
+  if let Some(encoder) = pipeline.by_name("enc") {
+    if encoder.find_property("threads").is_some() {
+      encoder.set_property_from_str("threads", &threads.to_string());
+    }
+  }
+
+  let output = Arc::new(Mutex::new(Vec::new()));
+  let output_clone = output.clone();
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Benchmark sink not found"))?;
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        output_clone
+          .lock()
+          .unwrap()
+          .extend_from_slice(map.as_slice());
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  let elapsed = time_pipeline(&pipeline, || Ok(()))?;
+  let data = std::mem::take(&mut *output.lock().unwrap());
+  Ok((elapsed, data))
+}
+
+/// Decodes an encoded Matroska file, returning the elapsed time
+fn decode(data: Vec<u8>) -> Result<f64> {
+  let pipeline = launch("appsrc name=src ! decodebin ! fakesink sync=false")?;
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Benchmark source not found"))?;
+
+  time_pipeline(&pipeline, || {
+    appsrc
+      .push_buffer(gst::Buffer::from_mut_slice(data))
+      .and_then(|_| appsrc.end_of_stream())
+      .map(|_| ())
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to push buffer: {}", e),
+        )
+      })
+  })
+}
+
+fn measurement(
+  name: &str,
+  operation: &str,
+  threads: Option<u32>,
+  frames: u32,
+  elapsed: Result<f64>,
+) -> BenchmarkResult {
+  let (fps, error) = match elapsed {
+    Ok(seconds) if seconds > 0.0 => (frames as f64 / seconds, None),
+    Ok(_) => (0.0, None),
+    Err(e) => (0.0, Some(e.reason)),
+  };
+  BenchmarkResult {
+    name: name.to_string(),
+    operation: operation.to_string(),
+    threads,
+    frames,
+    fps,
+    error,
+  }
+}
+
+/// Measures encode, decode and filter throughput on synthesized test frames
+///
+/// Runs that fail (for example because an encoder plugin is not installed) are
+/// reported with `fps: 0` and an `error` instead of failing the whole benchmark.
+///
+/// # Arguments
+/// * `options` - Optional resolution, duration, codecs and thread counts
+///
+/// # Returns
+/// * `Result<Vec<BenchmarkResult>>` - One result per codec/operation/thread count
+///
+/// # Example
+/// ```javascript
+/// const results = runBenchmark({ resolution: "1920x1080", seconds: 2 });
+/// const best = results.filter((r) => r.operation === "encode").sort((a, b) => b.fps - a.fps)[0];
+/// ```
+#[napi]
+pub fn run_benchmark(options: Option<BenchmarkOptions>) -> Result<Vec<BenchmarkResult>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(BenchmarkOptions {
+    resolution: None,
+    seconds: None,
+    codecs: None,
+    thread_counts: None,
+  });
+  let (width, height) = parse_resolution(options.resolution.as_deref().unwrap_or("1280x720"))?;
+  let frames = ((options.seconds.unwrap_or(2.0) * 30.0).round() as u32).max(1);
+  let codecs = options.codecs.unwrap_or_else(|| codec_names("video"));
+  let thread_counts = options.thread_counts.unwrap_or_else(|| {
+    let cpus = std::thread::available_parallelism()
+      .map(|n| n.get() as u32)
+      .unwrap_or(1);
+    if cpus > 1 {
+      vec![1, cpus]
+    } else {
+      vec![1]
+    }
+  });
+
+  let source = test_source(width, height, frames);
+  let mut results = Vec::new();
+
+  for codec in &codecs {
+    let mut encoded = None;
+    for &threads in &thread_counts {
+      let run = encode(&source, codec, threads);
+      let elapsed = run.map(|(elapsed, data)| {
+        encoded.get_or_insert(data);
+        elapsed
+      });
+      results.push(measurement(codec, "encode", Some(threads), frames, elapsed));
+    }
+    match encoded {
+      Some(data) => results.push(measurement(codec, "decode", None, frames, decode(data))),
+      None => results.push(measurement(
+        codec,
+        "decode",
+        None,
+        frames,
+        Err(Error::new(
+          Status::GenericFailure,
+          format!("No {} stream could be encoded to decode", codec),
+        )),
+      )),
+    }
+  }
+
+  for (name, filter) in FILTERS {
+    let elapsed = launch(&format!("{} ! {} ! fakesink sync=false", source, filter))
+      .and_then(|pipeline| time_pipeline(&pipeline, || Ok(())));
+    results.push(measurement(name, "filter", None, frames, elapsed));
+  }
+
+  Ok(results)
+}
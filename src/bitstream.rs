@@ -0,0 +1,391 @@
+//! # Bitstream Inspection
+//!
+//! Lists every compressed frame of a media file as the demuxer and parser
+//! deliver it: size, timestamps, keyframe flag and, for H.264, H.265 and AV1,
+//! the NAL unit or OBU types it carries. Useful to check keyframe cadence,
+//! B-frame reordering or where an encoder puts its parameter sets. Long
+//! files can be streamed to an NDJSON or CSV file instead of being returned.
+
+use crate::export::ResultWriter;
+use crate::transcode::{make_element, wait_for_eos};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// Columns of exported bitstream frames, in CSV order
+const COLUMNS: &[&str] = &[
+  "stream", "codec", "pts", "dts", "duration", "size", "keyframe", "units",
+];
+
+/// A compressed frame of a media file
+#[napi(object)]
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitstreamFrame {
+  /// Index of the stream, in the order the demuxer exposed it
+  pub stream: u32,
+  /// Media type of the stream, e.g. "video/x-h264" or "audio/x-opus"
+  pub codec: String,
+  /// Presentation timestamp in nanoseconds, or -1 if unknown
+  pub pts: i64,
+  /// Decoding timestamp in nanoseconds, or -1 if unknown
+  pub dts: i64,
+  /// Duration in nanoseconds, or -1 if unknown
+  pub duration: i64,
+  /// Size in bytes
+  pub size: u32,
+  /// Whether the frame can be decoded on its own
+  pub keyframe: bool,
+  /// NAL unit types (H.264, H.265) or OBU types (AV1) in the frame, in order
+  pub units: Vec<String>,
+}
+
+/// How the units of a stream are delimited
+#[derive(Clone, Copy)]
+pub(crate) enum Framing {
+  /// Annex B start codes
+  ByteStream,
+  /// Big-endian length prefixes of the given size
+  Length(usize),
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Syntax {
+  H264(Framing),
+  H265(Framing),
+  Av1,
+  Other,
+}
+
+pub(crate) fn syntax(caps: &gst::CapsRef) -> Syntax {
+  let Some(s) = caps.structure(0) else {
+    return Syntax::Other;
+  };
+  // Length size is in the low two bits of byte 4 (avcC) or 21 (hvcC), plus one
+  let framing = |length_byte: usize| match s.get::<&str>("stream-format") {
+    Ok("byte-stream") => Framing::ByteStream,
+    _ => Framing::Length(
+      s.get::<gst::Buffer>("codec_data")
+        .ok()
+        .and_then(|data| {
+          let map = data.map_readable().ok()?;
+          map.get(length_byte).map(|b| (b & 3) as usize + 1)
+        })
+        .unwrap_or(4),
+    ),
+  };
+  match s.name().as_str() {
+    "video/x-h264" => Syntax::H264(framing(4)),
+    "video/x-h265" => Syntax::H265(framing(21)),
+    "video/x-av1" => Syntax::Av1,
+    _ => Syntax::Other,
+  }
+}
+
+/// Splits a frame into its NAL units
+pub(crate) fn nal_units(data: &[u8], framing: Framing) -> Vec<&[u8]> {
+  let mut units = Vec::new();
+  match framing {
+    Framing::Length(size) => {
+      let mut rest = data;
+      while rest.len() > size {
+        let len = rest[..size]
+          .iter()
+          .fold(0usize, |len, &b| (len << 8) | b as usize);
+        let end = (size + len).min(rest.len());
+        units.push(&rest[size..end]);
+        rest = &rest[end..];
+      }
+    }
+    Framing::ByteStream => {
+      let starts: Vec<usize> = data
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w == &[0, 0, 1])
+        .map(|(i, _)| i + 3)
+        .collect();
+      for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(data.len(), |next| next - 3);
+        units.push(&data[start..end.max(start)]);
+      }
+    }
+  }
+  units
+}
+
+fn h264_unit(nal: &[u8]) -> String {
+  let kind = nal.first().map_or(0, |b| b & 0x1f);
+  match kind {
+    1 => "SLICE",
+    2..=4 => "SLICE_DP",
+    5 => "IDR",
+    6 => "SEI",
+    7 => "SPS",
+    8 => "PPS",
+    9 => "AUD",
+    10 => "END_SEQ",
+    11 => "END_STREAM",
+    12 => "FILLER",
+    _ => return format!("NAL_{}", kind),
+  }
+  .to_string()
+}
+
+fn h265_unit(nal: &[u8]) -> String {
+  let kind = nal.first().map_or(0, |b| (b >> 1) & 0x3f);
+  match kind {
+    0..=9 => "SLICE",
+    16..=18 => "BLA",
+    19 | 20 => "IDR",
+    21 => "CRA",
+    32 => "VPS",
+    33 => "SPS",
+    34 => "PPS",
+    35 => "AUD",
+    39 | 40 => "SEI",
+    _ => return format!("NAL_{}", kind),
+  }
+  .to_string()
+}
+
+/// Lists the OBU types of a low-overhead AV1 bitstream frame
+fn av1_units(data: &[u8]) -> Vec<String> {
+  let mut units = Vec::new();
+  let mut pos = 0;
+  while pos < data.len() {
+    let header = data[pos];
+    let kind = (header >> 3) & 0x0f;
+    let has_extension = header & 0x04 != 0;
+    let has_size = header & 0x02 != 0;
+    pos += 1 + has_extension as usize;
+    units.push(
+      match kind {
+        1 => "SEQUENCE_HEADER",
+        2 => "TEMPORAL_DELIMITER",
+        3 => "FRAME_HEADER",
+        4 => "TILE_GROUP",
+        5 => "METADATA",
+        6 => "FRAME",
+        7 => "REDUNDANT_FRAME_HEADER",
+        8 => "TILE_LIST",
+        15 => "PADDING",
+        _ => "RESERVED",
+      }
+      .to_string(),
+    );
+    if !has_size {
+      break;
+    }
+    // leb128 payload size
+    let mut size = 0usize;
+    for i in 0..8 {
+      let Some(&byte) = data.get(pos) else {
+        return units;
+      };
+      pos += 1;
+      size |= ((byte & 0x7f) as usize) << (7 * i);
+      if byte & 0x80 == 0 {
+        break;
+      }
+    }
+    pos += size;
+  }
+  units
+}
+
+fn units(data: &[u8], syntax: Syntax) -> Vec<String> {
+  match syntax {
+    Syntax::H264(framing) => nal_units(data, framing)
+      .into_iter()
+      .map(h264_unit)
+      .collect(),
+    Syntax::H265(framing) => nal_units(data, framing)
+      .into_iter()
+      .map(h265_unit)
+      .collect(),
+    Syntax::Av1 => av1_units(data),
+    Syntax::Other => Vec::new(),
+  }
+}
+
+fn nanoseconds(time: Option<gst::ClockTime>) -> i64 {
+  time.map(|t| t.nseconds() as i64).unwrap_or(-1)
+}
+
+/// Lists the compressed frames of every stream of a media file
+///
+/// # Arguments
+/// * `path` - The media file to inspect
+///
+/// # Returns
+/// * `Result<Vec<BitstreamFrame>>` - Every frame in demuxing order
+///
+/// # Example
+/// ```javascript
+/// const frames = inspectBitstream("encoded.mp4").filter(f => f.codec === "video/x-h264");
+/// const keyframes = frames.filter(f => f.keyframe).map(f => f.pts / 1e9);
+/// console.log("GOP starts at", keyframes, "first units:", frames[0].units);
+/// ```
+#[napi]
+pub fn inspect_bitstream(path: String) -> Result<Vec<BitstreamFrame>> {
+  let frames = Arc::new(Mutex::new(Vec::new()));
+  let frames_clone = frames.clone();
+  walk_bitstream(&path, move |frame| frames_clone.lock().unwrap().push(frame))?;
+  let frames = std::mem::take(&mut *frames.lock().unwrap());
+  Ok(frames)
+}
+
+/// Calls `on_frame` for every compressed frame of a media file, from the
+/// streaming threads
+fn walk_bitstream(
+  path: &str,
+  on_frame: impl Fn(BitstreamFrame) + Send + Sync + 'static,
+) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  if !std::path::Path::new(&path).is_file() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("File not found: {}", path),
+    ));
+  }
+  let pipeline = gst::Pipeline::new();
+  let source = make_element("filesrc")?;
+  source.set_property("location", path);
+  let parsebin = make_element("parsebin")?;
+  pipeline.add_many([&source, &parsebin]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build inspection pipeline: {}", e),
+    )
+  })?;
+  source.link(&parsebin).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link source: {}", e),
+    )
+  })?;
+
+  let on_frame = Arc::new(on_frame);
+  let streams = Arc::new(Mutex::new(0u32));
+  let pipeline_weak = pipeline.downgrade();
+  parsebin.connect_pad_added(move |_, pad| {
+    let Some(pipeline) = pipeline_weak.upgrade() else {
+      return;
+    };
+    let Ok(fakesink) = gst::ElementFactory::make("fakesink")
+      .property("sync", false)
+      .build()
+    else {
+      return;
+    };
+    if pipeline.add(&fakesink).is_err() {
+      return;
+    }
+    let _ = fakesink.sync_state_with_parent();
+    if let Some(sink_pad) = fakesink.static_pad("sink") {
+      let _ = pad.link(&sink_pad);
+    }
+
+    let stream = {
+      let mut streams = streams.lock().unwrap();
+      *streams += 1;
+      *streams - 1
+    };
+    let on_frame = on_frame.clone();
+    pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+      let Some(gst::PadProbeData::Buffer(ref buffer)) = info.data else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let caps = pad.current_caps();
+      let (codec, syntax) = match &caps {
+        Some(caps) => (
+          caps
+            .structure(0)
+            .map(|s| s.name().to_string())
+            .unwrap_or_default(),
+          syntax(caps),
+        ),
+        None => (String::new(), Syntax::Other),
+      };
+      let units = buffer
+        .map_readable()
+        .map(|map| units(&map, syntax))
+        .unwrap_or_default();
+      on_frame(BitstreamFrame {
+        stream,
+        codec,
+        pts: nanoseconds(buffer.pts()),
+        dts: nanoseconds(buffer.dts()),
+        duration: nanoseconds(buffer.duration()),
+        size: buffer.size() as u32,
+        keyframe: !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT),
+        units,
+      });
+      gst::PadProbeReturn::Ok
+    });
+  });
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
+
+/// Streams the compressed frames of every stream of a media file to an
+/// NDJSON or CSV file
+///
+/// Frames are written as the demuxer delivers them instead of being
+/// collected, so inspecting hours of video takes constant memory. Each
+/// record has the fields of `BitstreamFrame`; in CSV the units are separated
+/// by spaces.
+///
+/// # Arguments
+/// * `path` - The media file to inspect
+/// * `output` - The file to write
+/// * `format` - "ndjson" or "csv" (default: from the extension of `output`, NDJSON unless `.csv`)
+///
+/// # Returns
+/// * `Result<u32>` - Number of frames written
+///
+/// # Example
+/// ```javascript
+/// const count = exportBitstream("movie.mkv", "frames.csv");
+/// ```
+#[napi]
+pub fn export_bitstream(path: String, output: String, format: Option<String>) -> Result<u32> {
+  let writer = Arc::new(Mutex::new(ResultWriter::create(
+    &output,
+    format.as_deref(),
+    COLUMNS,
+  )?));
+  // Only the first write error is kept; later frames are skipped
+  let failure = Arc::new(Mutex::new(None::<String>));
+  let (writer_clone, failure_clone) = (writer.clone(), failure.clone());
+  walk_bitstream(&path, move |frame| {
+    let mut failure = failure_clone.lock().unwrap();
+    if failure.is_none() {
+      if let Err(e) = writer_clone.lock().unwrap().write(&frame) {
+        *failure = Some(e.reason.clone());
+      }
+    }
+  })?;
+  if let Some(reason) = failure.lock().unwrap().take() {
+    return Err(Error::new(Status::GenericFailure, reason));
+  }
+  let count = writer.lock().unwrap().finish();
+  count
+}
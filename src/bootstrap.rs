@@ -0,0 +1,139 @@
+//! # GStreamer Bootstrap
+//!
+//! Points GStreamer at its plugins on machines where it is not installed on
+//! the default paths, such as an Electron app shipping its own GStreamer
+//! build, and checks that the elements an app needs are there.
+//!
+//! GStreamer reads its plugin path and registry location once, when it is
+//! initialized by the first call into the native module, so `configureGStreamer`
+//! must run first. Plugin paths given later are still scanned into the
+//! running registry.
+
+use gst::glib;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+/// Options for `configureGStreamer`
+#[napi(object)]
+pub struct GStreamerConfig {
+  /// Directories searched for plugins before the ones in `GST_PLUGIN_PATH`
+  pub plugin_paths: Option<Vec<String>>,
+  /// File the plugin registry is cached in, e.g. in the app's data directory
+  pub registry_path: Option<String>,
+  /// Let crashes in plugins reach the app's own crash handler instead of
+  /// GStreamer's segfault trap (default: false)
+  pub disable_segtrap: Option<bool>,
+}
+
+/// Which of the required elements are installed
+#[napi(object)]
+pub struct PluginReport {
+  /// Elements found in the registry
+  pub available: Vec<String>,
+  /// Elements no installed plugin provides
+  pub missing: Vec<String>,
+  /// Whether every element is available
+  pub ok: bool,
+}
+
+/// Sets where GStreamer finds its plugins and caches its registry, then
+/// initializes it
+///
+/// # Arguments
+/// * `config` - Plugin paths, registry path and segfault trap setting
+///
+/// # Example
+/// ```javascript
+/// configureGStreamer({
+///   pluginPaths: [path.join(process.resourcesPath, "gstreamer", "lib", "gstreamer-1.0")],
+///   registryPath: path.join(app.getPath("userData"), "gst-registry.bin"),
+/// });
+/// ```
+#[napi(js_name = "configureGStreamer")]
+pub fn configure_gstreamer(config: GStreamerConfig) -> Result<()> {
+  let plugin_paths: Vec<PathBuf> = config
+    .plugin_paths
+    .unwrap_or_default()
+    .into_iter()
+    .map(PathBuf::from)
+    .collect();
+
+  if gst::INITIALIZED.load(Ordering::SeqCst) {
+    if config.registry_path.is_some() || config.disable_segtrap.is_some() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "registryPath and disableSegtrap must be set before GStreamer is initialized".to_string(),
+      ));
+    }
+    let registry = gst::Registry::get();
+    for path in &plugin_paths {
+      registry.scan_path(path);
+    }
+    return Ok(());
+  }
+
+  if !plugin_paths.is_empty() {
+    let existing = std::env::var_os("GST_PLUGIN_PATH").unwrap_or_default();
+    let paths = plugin_paths
+      .iter()
+      .cloned()
+      .chain(std::env::split_paths(&existing));
+    let joined: OsString = std::env::join_paths(paths)
+      .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid plugin path: {}", e)))?;
+    std::env::set_var("GST_PLUGIN_PATH", joined);
+  }
+  if let Some(registry_path) = &config.registry_path {
+    std::env::set_var("GST_REGISTRY", registry_path);
+  }
+  if let Some(disable) = config.disable_segtrap {
+    // Only read by gst_init
+    let enabled = if disable {
+      glib::ffi::GFALSE
+    } else {
+      glib::ffi::GTRUE
+    };
+    unsafe { gst::ffi::gst_segtrap_set_enabled(enabled) };
+  }
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })
+}
+
+/// Reports which of the given elements are installed
+///
+/// # Arguments
+/// * `elements` - Element factory names, e.g. "x264enc" or "webmmux"
+///
+/// # Returns
+/// * `Result<PluginReport>` - The available and missing elements
+///
+/// # Example
+/// ```javascript
+/// const { ok, missing } = verifyPlugins(["x264enc", "mp4mux", "pulsesrc"]);
+/// if (!ok) dialog.showErrorBox("Missing GStreamer plugins", missing.join(", "));
+/// ```
+#[napi]
+pub fn verify_plugins(elements: Vec<String>) -> Result<PluginReport> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let (available, missing): (Vec<String>, Vec<String>) = elements
+    .into_iter()
+    .partition(|element| gst::ElementFactory::find(element).is_some());
+  Ok(PluginReport {
+    ok: missing.is_empty(),
+    available,
+    missing,
+  })
+}
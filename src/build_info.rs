@@ -0,0 +1,90 @@
+//! # Build Information
+//!
+//! Reports how the native module was built and what it runs on, for bug
+//! reports and runtime feature checks.
+
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// How the native module was built
+#[napi(object)]
+pub struct BuildInfo {
+  /// Version of the native crate
+  pub version: String,
+  /// Cargo features enabled at build time
+  pub features: Vec<String>,
+  /// Version of the GStreamer library loaded at runtime, e.g. "1.24.2"
+  pub gstreamer_version: String,
+  /// Best SIMD instruction set of the CPU ("avx512", "avx2", "sse4.2", "sse2", "neon" or "none")
+  pub simd: String,
+  /// Short git commit hash of the build, or null if unknown
+  pub git_hash: Option<String>,
+  /// Rust target triple of the build
+  pub target: String,
+  /// Whether this is a debug build
+  pub debug: bool,
+}
+
+/// Detects the best SIMD instruction set supported by the CPU
+fn simd_level() -> &'static str {
+  #[cfg(target_arch = "x86_64")]
+  {
+    if std::arch::is_x86_feature_detected!("avx512f") {
+      return "avx512";
+    }
+    if std::arch::is_x86_feature_detected!("avx2") {
+      return "avx2";
+    }
+    if std::arch::is_x86_feature_detected!("sse4.2") {
+      return "sse4.2";
+    }
+    "sse2"
+  }
+  #[cfg(target_arch = "aarch64")]
+  {
+    "neon"
+  }
+  #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+  {
+    "none"
+  }
+}
+
+/// Returns how the native module was built and the GStreamer version it runs with
+///
+/// # Returns
+/// * `Result<BuildInfo>` - Version, features, GStreamer version, SIMD level and commit
+///
+/// # Example
+/// ```javascript
+/// const info = getBuildInfo();
+/// console.log(`gstreamer-kit ${info.version} (${info.gitHash}) on GStreamer ${info.gstreamerVersion}`);
+/// ```
+#[napi]
+pub fn get_build_info() -> Result<BuildInfo> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  Ok(BuildInfo {
+    version: env!("CARGO_PKG_VERSION").to_string(),
+    features: env!("GSTKIT_FEATURES")
+      .split(',')
+      .filter(|feature| !feature.is_empty())
+      .map(str::to_string)
+      .collect(),
+    gstreamer_version: gst::version_string()
+      .trim_start_matches("GStreamer ")
+      .to_string(),
+    simd: simd_level().to_string(),
+    git_hash: Some(env!("GSTKIT_GIT_HASH"))
+      .filter(|hash| !hash.is_empty())
+      .map(str::to_string),
+    target: env!("GSTKIT_TARGET").to_string(),
+    debug: cfg!(debug_assertions),
+  })
+}
@@ -0,0 +1,397 @@
+//! # Capture Sessions
+//!
+//! Runs several capture sources, such as the cameras of a multi-angle rig, as
+//! one session. Every source has its own pipeline, so a failing device does
+//! not stop the others, but all of them follow the same clock and share one
+//! base time. Sources are started together, and a source added while the
+//! session runs joins its timeline. The running time of a frame is therefore
+//! a session timestamp: frames captured at the same moment by different
+//! sources carry the same one, whatever their devices' own timestamps.
+
+use crate::compositor::input_bin;
+use crate::kit::{frame_data, FrameData};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Callback receiving the frames of every source
+type FrameCallback = ThreadsafeFunction<CaptureFrame, (), CaptureFrame, Status, false, true>;
+
+/// Options for the `CaptureSession` constructor
+#[napi(object)]
+pub struct CaptureSessionOptions {
+  /// Raw video format of the emitted frames (default: "RGBA")
+  pub format: Option<String>,
+  /// Delay between `start` and the session time 0, in milliseconds, leaving
+  /// the sources time to start (default: 200)
+  pub start_delay_ms: Option<u32>,
+}
+
+/// A frame captured by a source of a session
+#[napi(object)]
+pub struct CaptureFrame {
+  /// Id of the source that captured the frame
+  pub source_id: String,
+  /// Time of the capture since the session started, in nanoseconds, or -1
+  /// if unknown; the same for all sources
+  pub session_timestamp: i64,
+  /// Number of the frame in its source, from 0
+  pub sequence: u32,
+  /// The frame; its `timestamp` is the source's own
+  pub frame: FrameData,
+}
+
+/// Frame count and timing of a session source
+#[napi(object)]
+pub struct CaptureSourceStats {
+  /// Id of the source
+  pub id: String,
+  /// Current state ("Null", "Ready", "Paused" or "Playing")
+  pub state: String,
+  /// Frames captured since the session started
+  pub frames: u32,
+  /// Session timestamp of the last frame in nanoseconds, or -1
+  pub last_timestamp: i64,
+}
+
+#[derive(Default)]
+struct SourceCounters {
+  frames: AtomicU32,
+  last_timestamp: AtomicI64,
+}
+
+struct CaptureSource {
+  pipeline: gst::Pipeline,
+  counters: Arc<SourceCounters>,
+}
+
+impl Drop for CaptureSource {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
+
+/// Synchronized capture from several sources
+///
+/// # Example
+/// ```javascript
+/// const session = new CaptureSession({ format: "I420" });
+/// session.addSource("left", "v4l2src device=/dev/video0");
+/// session.addSource("right", "v4l2src device=/dev/video1");
+/// session.onFrame(({ sourceId, sessionTimestamp, frame }) => {
+///   writer.write(sourceId, sessionTimestamp, frame.data);
+/// });
+/// session.start();
+/// ```
+#[napi]
+pub struct CaptureSession {
+  sources: Mutex<HashMap<String, CaptureSource>>,
+  callback: Arc<Mutex<Option<FrameCallback>>>,
+  clock: gst::Clock,
+  format: String,
+  start_delay: gst::ClockTime,
+  /// Base time of the running session
+  base_time: Mutex<Option<gst::ClockTime>>,
+}
+
+impl CaptureSession {
+  /// Puts a source on the session clock and timeline and starts it
+  fn start_source(
+    &self,
+    id: &str,
+    source: &CaptureSource,
+    base_time: gst::ClockTime,
+  ) -> Result<()> {
+    let pipeline = &source.pipeline;
+    pipeline.use_clock(Some(&self.clock));
+    pipeline.set_start_time(gst::ClockTime::NONE);
+    pipeline.set_base_time(base_time);
+    pipeline
+      .set_state(gst::State::Playing)
+      .map(|_| ())
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to start source {}: {}", id, e),
+        )
+      })
+  }
+}
+
+#[napi]
+impl CaptureSession {
+  /// Creates an empty session
+  ///
+  /// # Arguments
+  /// * `options` - Optional frame format and start delay
+  ///
+  /// # Example
+  /// ```javascript
+  /// const session = new CaptureSession();
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: Option<CaptureSessionOptions>) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let options = options.unwrap_or(CaptureSessionOptions {
+      format: None,
+      start_delay_ms: None,
+    });
+    let format = options.format.unwrap_or_else(|| "RGBA".to_string());
+    if gst_video::VideoFormat::from_string(&format) == gst_video::VideoFormat::Unknown {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unknown video format: {}", format),
+      ));
+    }
+    Ok(CaptureSession {
+      sources: Mutex::new(HashMap::new()),
+      callback: Arc::new(Mutex::new(None)),
+      clock: gst::SystemClock::obtain(),
+      format,
+      start_delay: gst::ClockTime::from_mseconds(options.start_delay_ms.unwrap_or(200) as u64),
+      base_time: Mutex::new(None),
+    })
+  }
+
+  /// Sets the callback receiving the frames of every source
+  ///
+  /// # Arguments
+  /// * `callback` - Called with each frame, tagged with its source id and
+  ///   session timestamp
+  ///
+  /// # Example
+  /// ```javascript
+  /// session.onFrame((capture) => console.log(capture.sourceId, capture.sessionTimestamp));
+  /// ```
+  #[napi]
+  pub fn on_frame(
+    &self,
+    callback: ThreadsafeFunction<CaptureFrame, (), CaptureFrame, Status, false, true>,
+  ) {
+    *self.callback.lock().unwrap() = Some(callback);
+  }
+
+  /// Adds a source under the given id; a source added while the session
+  /// runs starts right away, on the session timeline
+  ///
+  /// # Arguments
+  /// * `id` - Unique name of the source
+  /// * `source` - A file path, a URI, or a launch description producing video
+  ///   (e.g. "v4l2src device=/dev/video0")
+  ///
+  /// # Example
+  /// ```javascript
+  /// session.addSource("top", "v4l2src device=/dev/video2 ! video/x-raw,width=1280,height=720");
+  /// ```
+  #[napi]
+  pub fn add_source(&self, id: String, source: String) -> Result<()> {
+    let mut sources = self.sources.lock().unwrap();
+    if sources.contains_key(&id) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Source already exists: {}", id),
+      ));
+    }
+
+    // Syncing on the shared clock also keeps non-live sources in step
+    let bin = input_bin(
+      &source,
+      "video/x-raw",
+      &format!(
+        "videoconvert ! video/x-raw,format={} ! appsink name=sink sync=true",
+        self.format
+      ),
+    )?;
+    let pipeline = gst::Pipeline::new();
+    pipeline.add(&bin).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to add source: {}", e),
+      )
+    })?;
+    let appsink = bin
+      .by_name("sink")
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Source sink not found"))?;
+
+    let counters = Arc::new(SourceCounters::default());
+    counters.last_timestamp.store(-1, Ordering::SeqCst);
+    let frames = counters.clone();
+    let callback = self.callback.clone();
+    let source_id = id.clone();
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          // The running time is the same on every pipeline of the session
+          let session_timestamp = sample
+            .buffer()
+            .and_then(|buffer| buffer.pts())
+            .and_then(|pts| {
+              sample
+                .segment()
+                .and_then(|segment| segment.downcast_ref::<gst::ClockTime>())
+                .and_then(|segment| segment.to_running_time(pts))
+            })
+            .map_or(-1, |time| time.nseconds() as i64);
+          let sequence = frames.frames.fetch_add(1, Ordering::SeqCst);
+          frames
+            .last_timestamp
+            .store(session_timestamp, Ordering::SeqCst);
+          if let Some(callback) = callback.lock().unwrap().as_ref() {
+            let frame =
+              frame_data(&sample, &source_id, false, None).map_err(|_| gst::FlowError::Error)?;
+            callback.call(
+              CaptureFrame {
+                source_id: source_id.clone(),
+                session_timestamp,
+                sequence,
+                frame,
+              },
+              ThreadsafeFunctionCallMode::NonBlocking,
+            );
+          }
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+
+    let source = CaptureSource { pipeline, counters };
+    if let Some(base_time) = *self.base_time.lock().unwrap() {
+      self.start_source(&id, &source, base_time)?;
+    }
+    sources.insert(id, source);
+    Ok(())
+  }
+
+  /// Stops and removes a source
+  ///
+  /// # Returns
+  /// * `bool` - Whether a source with this id existed
+  #[napi]
+  pub fn remove_source(&self, id: String) -> bool {
+    self.sources.lock().unwrap().remove(&id).is_some()
+  }
+
+  /// Returns the ids of all sources
+  #[napi]
+  pub fn list_sources(&self) -> Vec<String> {
+    let mut ids: Vec<String> = self.sources.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+  }
+
+  /// Starts every source on a shared clock and base time
+  ///
+  /// The session time 0 is `startDelayMs` after the call; frames captured
+  /// before it are timestamped 0.
+  ///
+  /// # Returns
+  /// * `Result<i64>` - The base time of the session on the system clock, in
+  ///   nanoseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// session.start();
+  /// setTimeout(() => session.stop(), 60_000);
+  /// ```
+  #[napi]
+  pub fn start(&self) -> Result<i64> {
+    let sources = self.sources.lock().unwrap();
+    if sources.is_empty() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Capture session has no sources".to_string(),
+      ));
+    }
+    let mut base_time = self.base_time.lock().unwrap();
+    if base_time.is_some() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Capture session already started".to_string(),
+      ));
+    }
+
+    let start = self.clock.time().unwrap_or(gst::ClockTime::ZERO) + self.start_delay;
+    for source in sources.values() {
+      source.counters.frames.store(0, Ordering::SeqCst);
+      source.counters.last_timestamp.store(-1, Ordering::SeqCst);
+    }
+    let failed: Vec<&str> = sources
+      .iter()
+      .filter(|(id, source)| self.start_source(id, source, start).is_err())
+      .map(|(id, _)| id.as_str())
+      .collect();
+    if !failed.is_empty() {
+      for source in sources.values() {
+        let _ = source.pipeline.set_state(gst::State::Null);
+      }
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Failed to start sources: {}", failed.join(", ")),
+      ));
+    }
+    *base_time = Some(start);
+    Ok(start.nseconds() as i64)
+  }
+
+  /// Stops every source
+  #[napi]
+  pub fn stop(&self) {
+    let sources = self.sources.lock().unwrap();
+    for source in sources.values() {
+      let _ = source.pipeline.set_state(gst::State::Null);
+    }
+    *self.base_time.lock().unwrap() = None;
+  }
+
+  /// Returns the current session time in nanoseconds, negative before the
+  /// session time 0 is reached, or null when the session is stopped
+  #[napi]
+  pub fn get_session_time(&self) -> Option<i64> {
+    let base_time = (*self.base_time.lock().unwrap())?;
+    let now = self.clock.time()?;
+    Some(now.nseconds() as i64 - base_time.nseconds() as i64)
+  }
+
+  /// Returns the state and frame count of every source, sorted by id
+  ///
+  /// # Example
+  /// ```javascript
+  /// for (const stats of session.stats()) {
+  ///   console.log(stats.id, stats.frames, stats.lastTimestamp);
+  /// }
+  /// ```
+  #[napi]
+  pub fn stats(&self) -> Vec<CaptureSourceStats> {
+    let sources = self.sources.lock().unwrap();
+    let mut stats: Vec<CaptureSourceStats> = sources
+      .iter()
+      .map(|(id, source)| {
+        let (_, state, _) = source.pipeline.state(gst::ClockTime::ZERO);
+        CaptureSourceStats {
+          id: id.clone(),
+          state: format!("{:?}", state),
+          frames: source.counters.frames.load(Ordering::SeqCst),
+          last_timestamp: source.counters.last_timestamp.load(Ordering::SeqCst),
+        }
+      })
+      .collect();
+    stats.sort_by(|a, b| a.id.cmp(&b.id));
+    stats
+  }
+}
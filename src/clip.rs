@@ -0,0 +1,894 @@
+//! # Clip Extraction
+//!
+//! Cuts a time range out of a media file. In "copy" mode the compressed streams
+//! are remuxed untouched, starting at the keyframe at or before the requested
+//! start; in "reencode" mode the range is decoded and re-encoded frame-accurately;
+//! in "smart" mode only the video from the start to the next keyframe is
+//! re-encoded, with the codec and profile of the input, and the rest is copied.
+//! Each mode seeks to the keyframe before the start rather than reading the
+//! input from its beginning.
+//! Sidecar subtitles are trimmed to the range of the clip written, so they
+//! stay in step with it.
+
+use crate::subtitle_timing::{clip_subtitle_path, trim_subtitles};
+use crate::transcode::{
+  build_transcode_pipeline, container_spec, make_decoder, make_element, video_codec_spec,
+  StreamHook, TranscodeOptions,
+};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+/// Application message posted when a stream passes the end of the clip
+const CLIP_END: &str = "clip-end";
+
+/// Options for `extractClip`
+#[napi(object)]
+pub struct ClipOptions {
+  /// "copy" (default) remuxes without re-encoding, cutting on keyframes;
+  /// "reencode" decodes and re-encodes the range frame-accurately; "smart"
+  /// re-encodes only the video before the first keyframe of the range
+  pub mode: Option<String>,
+  /// Output container; defaults to the output file extension
+  pub container: Option<String>,
+  /// SubRip or WebVTT files of the input, written next to the clip trimmed
+  /// to its range: "movie.en.srt" becomes "<clip name>.en.srt"
+  pub subtitles: Option<Vec<String>>,
+}
+
+fn post_clip_end(pad: &gst::Pad) {
+  if let Some(element) = pad.parent_element() {
+    let _ = element.post_message(gst::message::Application::new(gst::Structure::new_empty(
+      CLIP_END,
+    )));
+  }
+}
+
+fn file_element(factory: &str, path: &str) -> Result<gst::Element> {
+  gst::ElementFactory::make(factory)
+    .property("location", path)
+    .build()
+    .map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} is not available", factory),
+      )
+    })
+}
+
+/// Runs the pipeline until end of stream. Once every stream announced
+/// `CLIP_END` (`streams` of them), EOS is sent so the muxer can finalize
+/// without reading the rest of the input.
+fn run_until_clip_end(pipeline: &gst::Pipeline, streams: &AtomicUsize) -> Result<()> {
+  let bus = pipeline
+    .bus()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+
+  let mut ended = 0;
+  let result = loop {
+    let Some(msg) = bus.timed_pop_filtered(
+      gst::ClockTime::NONE,
+      &[
+        gst::MessageType::Eos,
+        gst::MessageType::Error,
+        gst::MessageType::Application,
+      ],
+    ) else {
+      break Err(Error::new(Status::GenericFailure, "Pipeline bus closed"));
+    };
+    match msg.view() {
+      gst::MessageView::Eos(_) => break Ok(()),
+      gst::MessageView::Error(err) => {
+        break Err(Error::new(
+          Status::GenericFailure,
+          format!("Clip extraction failed: {}", err.error()),
+        ))
+      }
+      gst::MessageView::Application(app)
+        if app.structure().map(|s| s.name() == CLIP_END) == Some(true) =>
+      {
+        ended += 1;
+        if ended == streams.load(Ordering::SeqCst) {
+          pipeline.send_event(gst::event::Eos::new());
+        }
+      }
+      _ => {}
+    }
+  };
+
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
+
+/// Seek landing on the keyframe at or before the position asked for
+const KEYFRAME_SEEK: gst::SeekFlags = gst::SeekFlags::FLUSH
+  .union(gst::SeekFlags::KEY_UNIT)
+  .union(gst::SeekFlags::SNAP_BEFORE);
+/// How long to wait for a demuxer to expose its streams before seeking it
+const SEEK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Copied video buffered while the start of a smart clip is re-encoded
+const SPLICE_BUFFER: gst::ClockTime = gst::ClockTime::from_seconds(10);
+
+/// Pauses `pipeline` and, once each of `demuxers` has exposed its streams,
+/// seeks it to the keyframe at or before `position`, so the input is not
+/// read from its beginning. Clears `seeking` if a seek could not be made,
+/// letting the streams through from where they are.
+fn seek_demuxers(
+  pipeline: &gst::Pipeline,
+  demuxers: &[gst::Element],
+  position: gst::ClockTime,
+  seeking: &AtomicBool,
+) -> Result<()> {
+  let (exposed, streams_exposed) = mpsc::channel();
+  let handlers: Vec<_> = demuxers
+    .iter()
+    .map(|demuxer| {
+      let exposed = exposed.clone();
+      demuxer.connect_no_more_pads(move |demuxer| {
+        let _ = exposed.send(demuxer.clone());
+      })
+    })
+    .collect();
+  let paused = pipeline.set_state(gst::State::Paused);
+  let mut seeked = paused.is_ok();
+  for _ in demuxers {
+    if !seeked {
+      break;
+    }
+    // A seek sent upstream from any of its pads reaches the demuxer
+    seeked = streams_exposed
+      .recv_timeout(SEEK_TIMEOUT)
+      .ok()
+      .and_then(|demuxer| demuxer.src_pads().into_iter().next())
+      .is_some_and(|pad| {
+        pad.send_event(gst::event::Seek::new(
+          1.0,
+          KEYFRAME_SEEK,
+          gst::SeekType::Set,
+          position,
+          gst::SeekType::None,
+          gst::ClockTime::NONE,
+        ))
+      });
+  }
+  for (demuxer, handler) in demuxers.iter().zip(handlers) {
+    demuxer.disconnect(handler);
+  }
+  if !seeked {
+    seeking.store(false, Ordering::SeqCst);
+  }
+  paused.map(|_| ()).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Paused: {}", e),
+    )
+  })
+}
+
+/// How `cut_stream` trims a stream
+#[derive(Clone, Copy)]
+struct Cut {
+  /// Timestamp of the stream played at the start of the clip
+  origin: gst::ClockTime,
+  /// Buffers presented before this are dropped
+  from: gst::ClockTime,
+  /// Buffers decoded from this on are dropped
+  to: gst::ClockTime,
+  /// Ends the stream at `to` instead of announcing `CLIP_END`
+  eos: bool,
+}
+
+/// Trims the stream of `pad` to `cut`, dropping everything it carries
+/// before its first flush while `seeking` is set. Its segments are replaced
+/// so running time starts at the origin of the cut, whatever segment the
+/// demuxer produced for the seek.
+fn cut_stream(pad: &gst::Pad, cut: Cut, seeking: Arc<AtomicBool>) {
+  let flushed = AtomicBool::new(false);
+  let done = AtomicBool::new(false);
+  pad.add_probe(
+    gst::PadProbeType::BUFFER
+      | gst::PadProbeType::EVENT_DOWNSTREAM
+      | gst::PadProbeType::EVENT_FLUSH,
+    move |pad, info| {
+      match &mut info.data {
+        Some(gst::PadProbeData::Event(event)) => match event.view() {
+          gst::EventView::FlushStop(_) => flushed.store(true, Ordering::SeqCst),
+          gst::EventView::Segment(segment) if segment.segment().format() == gst::Format::Time => {
+            let mut segment = gst::FormattedSegment::<gst::ClockTime>::new();
+            segment.set_start(cut.origin);
+            segment.set_time(cut.origin);
+            segment.set_position(cut.origin);
+            *event = gst::event::Segment::builder(&segment)
+              .seqnum(event.seqnum())
+              .build();
+          }
+          _ => {}
+        },
+        Some(gst::PadProbeData::Buffer(buffer)) => {
+          if seeking.load(Ordering::SeqCst) && !flushed.load(Ordering::SeqCst) {
+            return gst::PadProbeReturn::Drop;
+          }
+          if buffer.dts_or_pts().is_some_and(|ts| ts >= cut.to) {
+            if cut.eos {
+              info.flow_res = Err(gst::FlowError::Eos);
+            } else if !done.swap(true, Ordering::SeqCst) {
+              post_clip_end(pad);
+            }
+            return gst::PadProbeReturn::Drop;
+          }
+          if buffer
+            .pts()
+            .or(buffer.dts())
+            .is_some_and(|ts| ts < cut.from)
+          {
+            return gst::PadProbeReturn::Drop;
+          }
+        }
+        _ => {}
+      }
+      gst::PadProbeReturn::Ok
+    },
+  );
+}
+
+/// Keyframes around the start of a clip, found by parsing the input
+#[derive(Default)]
+struct KeyframeScan {
+  /// Decode timestamp of the last video keyframe at or before the start
+  before: Option<gst::ClockTime>,
+  /// Presentation timestamp of the first video keyframe after the start,
+  /// if there is one before the end
+  after: Option<gst::ClockTime>,
+  /// Caps of the video stream
+  caps: Option<gst::Caps>,
+}
+
+/// Finds the video keyframes around `start` by parsing (not decoding) the
+/// input from the keyframe before it, up to `end` at most
+fn scan_keyframes(
+  input_path: &str,
+  start: gst::ClockTime,
+  end: gst::ClockTime,
+) -> Result<KeyframeScan> {
+  let pipeline = gst::Pipeline::new();
+  let source = file_element("filesrc", input_path)?;
+  let parsebin = gst::ElementFactory::make("parsebin")
+    .build()
+    .map_err(|_| Error::new(Status::GenericFailure, "Element parsebin is not available"))?;
+  pipeline.add_many([&source, &parsebin]).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build keyframe scan: {}", e),
+    )
+  })?;
+  source.link(&parsebin).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link source: {}", e),
+    )
+  })?;
+
+  let scan = Arc::new(Mutex::new(KeyframeScan::default()));
+  let streams = Arc::new(AtomicUsize::new(0));
+  let seeking = Arc::new(AtomicBool::new(start > gst::ClockTime::ZERO));
+  let scan_clone = scan.clone();
+  let streams_clone = streams.clone();
+  let seeking_clone = seeking.clone();
+  let pipeline_weak = pipeline.downgrade();
+  parsebin.connect_pad_added(move |_, pad| {
+    let Some(pipeline) = pipeline_weak.upgrade() else {
+      return;
+    };
+    let Ok(fakesink) = gst::ElementFactory::make("fakesink")
+      .property("sync", false)
+      .build()
+    else {
+      return;
+    };
+    if pipeline.add(&fakesink).is_err() {
+      return;
+    }
+    let _ = fakesink.sync_state_with_parent();
+    if let Some(sink_pad) = fakesink.static_pad("sink") {
+      let _ = pad.link(&sink_pad);
+    }
+
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    let is_video = caps
+      .structure(0)
+      .map(|s| s.name().starts_with("video/"))
+      .unwrap_or(false);
+    if is_video {
+      scan_clone.lock().unwrap().caps.get_or_insert(caps);
+    }
+
+    streams_clone.fetch_add(1, Ordering::SeqCst);
+    let scan = scan_clone.clone();
+    let seeking = seeking_clone.clone();
+    let flushed = AtomicBool::new(false);
+    let done = AtomicBool::new(false);
+    pad.add_probe(
+      gst::PadProbeType::BUFFER | gst::PadProbeType::EVENT_FLUSH,
+      move |pad, info| {
+        let buffer = match &info.data {
+          Some(gst::PadProbeData::Event(event)) => {
+            if event.type_() == gst::EventType::FlushStop {
+              flushed.store(true, Ordering::SeqCst);
+            }
+            return gst::PadProbeReturn::Ok;
+          }
+          Some(gst::PadProbeData::Buffer(buffer)) => buffer,
+          _ => return gst::PadProbeReturn::Ok,
+        };
+        if done.load(Ordering::SeqCst)
+          || (seeking.load(Ordering::SeqCst) && !flushed.load(Ordering::SeqCst))
+        {
+          return gst::PadProbeReturn::Drop;
+        }
+        // Other streams only need to be read up to the start
+        let mut finished = !is_video;
+        if let (Some(ts), true) = (buffer.dts_or_pts(), is_video) {
+          let keyframe = !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT);
+          let pts = buffer.pts().unwrap_or(ts);
+          let mut scan = scan.lock().unwrap();
+          if ts >= end {
+            finished = true;
+          } else if keyframe && ts <= start {
+            scan.before = Some(ts);
+          } else if keyframe && pts > start {
+            scan.after = Some(pts);
+            finished = true;
+          }
+        }
+        if finished {
+          done.store(true, Ordering::SeqCst);
+          post_clip_end(pad);
+          return gst::PadProbeReturn::Drop;
+        }
+        gst::PadProbeReturn::Ok
+      },
+    );
+  });
+
+  if start > gst::ClockTime::ZERO {
+    seek_demuxers(&pipeline, &[parsebin], start, &seeking)?;
+  }
+  run_until_clip_end(&pipeline, &streams)?;
+  let scan = std::mem::take(&mut *scan.lock().unwrap());
+  Ok(scan)
+}
+
+/// Codec of `TranscodeOptions` producing video with `caps`
+fn video_codec(caps: &gst::Caps) -> Option<&'static str> {
+  match caps.structure(0)?.name().as_str() {
+    "video/x-h264" => Some("h264"),
+    "video/x-h265" => Some("h265"),
+    "video/x-vp8" => Some("vp8"),
+    "video/x-vp9" => Some("vp9"),
+    "video/x-av1" => Some("av1"),
+    "video/x-theora" => Some("theora"),
+    "image/jpeg" => Some("mjpeg"),
+    _ => None,
+  }
+}
+
+/// Output caps of the spliced video: H.264 and H.265 carry their parameter
+/// sets in-band, so the switch from the re-encoded frames to the copied
+/// ones changes them legally
+fn splice_caps(caps: &gst::Caps, muxer: &gst::Element) -> Option<(&'static str, gst::Caps)> {
+  let (parser, name, in_band) = match caps.structure(0)?.name().as_str() {
+    "video/x-h264" => ("h264parse", "video/x-h264", "avc3"),
+    "video/x-h265" => ("h265parse", "video/x-h265", "hev1"),
+    _ => return None,
+  };
+  let in_band = gst::Caps::builder(name)
+    .field("stream-format", in_band)
+    .field("alignment", "au")
+    .build();
+  let accepted = muxer.pad_template_list().iter().any(|template| {
+    template.direction() == gst::PadDirection::Sink && template.caps().can_intersect(&in_band)
+  });
+  let caps = if accepted {
+    in_band
+  } else {
+    gst::Caps::builder(name)
+      .field("stream-format", "byte-stream")
+      .field("alignment", "au")
+      .build()
+  };
+  Some((parser, caps))
+}
+
+fn make_named(factory: &str, properties: &[(&str, &str)]) -> Result<gst::Element> {
+  let element = make_element(factory)?;
+  for (property, value) in properties {
+    element.set_property_from_str(property, value);
+  }
+  Ok(element)
+}
+
+fn add_and_link(pipeline: &gst::Pipeline, elements: &[gst::Element]) -> Result<()> {
+  pipeline.add_many(elements).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to build smart clip: {}", e),
+    )
+  })?;
+  gst::Element::link_many(elements).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link smart clip: {}", e),
+    )
+  })?;
+  for element in elements {
+    let _ = element.sync_state_with_parent();
+  }
+  Ok(())
+}
+
+fn link_pad(pad: &gst::Pad, element: &gst::Element) -> Result<()> {
+  let sink_pad = element
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Element has no sink pad"))?;
+  pad.link(&sink_pad).map(|_| ()).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link {}: {:?}", pad.name(), e),
+    )
+  })
+}
+
+/// A smart clip: the video from `start` to the keyframe `keyframe` is
+/// decoded from one reading of the input and re-encoded with the input
+/// codec and profile, then the copied video from `keyframe` on follows it
+/// through `concat`; the audio is copied from `start`
+struct Splice {
+  start: gst::ClockTime,
+  keyframe: gst::ClockTime,
+  end: gst::ClockTime,
+  caps: gst::Caps,
+}
+
+impl Splice {
+  /// Builds the pipeline, returning it with its two demuxers
+  fn build(
+    &self,
+    input_path: &str,
+    sink: &gst::Element,
+    container: &str,
+    streams: Arc<AtomicUsize>,
+    seeking: Arc<AtomicBool>,
+  ) -> Result<(gst::Pipeline, [gst::Element; 2])> {
+    let codec = video_codec(&self.caps).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Smart clips cannot re-encode {}", self.caps),
+      )
+    })?;
+    let spec = video_codec_spec(codec)?;
+    let muxer = make_element(container_spec(container)?.muxer)?;
+
+    let pipeline = gst::Pipeline::new();
+    let (head_source, tail_source) = (
+      file_element("filesrc", input_path)?,
+      file_element("filesrc", input_path)?,
+    );
+    let (head, tail) = (make_element("parsebin")?, make_element("parsebin")?);
+    let concat = make_named("concat", &[("adjust-base", "false")])?;
+    pipeline
+      .add_many([
+        &head_source,
+        &tail_source,
+        &head,
+        &tail,
+        &concat,
+        &muxer,
+        sink,
+      ])
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to build smart clip: {}", e),
+        )
+      })?;
+    let link_failed = |e: gst::glib::BoolError| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to link smart clip: {}", e),
+      )
+    };
+    head_source.link(&head).map_err(link_failed)?;
+    tail_source.link(&tail).map_err(link_failed)?;
+    muxer.link(sink).map_err(link_failed)?;
+
+    // The re-encoded frames play first, the copied ones once they are done
+    let (Some(head_pad), Some(tail_pad)) = (
+      concat.request_pad_simple("sink_%u"),
+      concat.request_pad_simple("sink_%u"),
+    ) else {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Failed to request concat pads".to_string(),
+      ));
+    };
+    let mut output = vec![concat.clone()];
+    let mut output_caps = self.caps.clone();
+    if let Some((parser, caps)) = splice_caps(&self.caps, &muxer) {
+      output.push(make_named(parser, &[("config-interval", "-1")])?);
+      output.push(
+        gst::ElementFactory::make("capsfilter")
+          .property("caps", &caps)
+          .build()
+          .map_err(|_| {
+            Error::new(
+              Status::GenericFailure,
+              "Element capsfilter is not available",
+            )
+          })?,
+      );
+      output_caps = caps;
+    }
+    add_and_link(&pipeline, &output[1..])?;
+    gst::Element::link_many(&output[..output.len().min(2)]).map_err(link_failed)?;
+    let last = &output[output.len() - 1];
+    let video_pad = last
+      .static_pad("src")
+      .and_then(|pad| muxer.compatible_pad(&pad, Some(&output_caps)))
+      .ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          format!("{} cannot carry {}", container, codec),
+        )
+      })?;
+    last
+      .static_pad("src")
+      .map(|pad| pad.link(&video_pad))
+      .transpose()
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to link {} to the muxer: {:?}", codec, e),
+        )
+      })?;
+
+    // Re-encoded start, read from its own parsebin; its other streams are
+    // ended right away
+    let head_cut = Cut {
+      origin: self.start,
+      from: self.start,
+      to: self.keyframe,
+      eos: true,
+    };
+    let profile = self
+      .caps
+      .structure(0)
+      .and_then(|s| s.get::<String>("profile").ok());
+    let encoded_caps = self.caps.structure(0).map(|s| {
+      let mut caps = gst::Caps::builder(s.name().as_str());
+      if let Some(profile) = &profile {
+        caps = caps.field("profile", profile);
+      }
+      caps.build()
+    });
+    let pipeline_weak = pipeline.downgrade();
+    let (seeking_clone, encoder, parser) = (seeking.clone(), spec.encoder, spec.parser);
+    let head_linked = AtomicBool::new(false);
+    head.connect_pad_added(move |head, pad| {
+      let Some(pipeline) = pipeline_weak.upgrade() else {
+        return;
+      };
+      let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+      let is_video = caps
+        .structure(0)
+        .is_some_and(|s| s.name().starts_with("video/"));
+      if !is_video || head_linked.swap(true, Ordering::SeqCst) {
+        pad.add_probe(gst::PadProbeType::BUFFER, |_, info| {
+          info.flow_res = Err(gst::FlowError::Eos);
+          gst::PadProbeReturn::Drop
+        });
+        return;
+      }
+      let linked = (|| -> Result<()> {
+        let decoder = make_decoder(&caps)?;
+        let encoder = make_element(&encoder)?;
+        // Without reordering, the first re-encoded frame is decoded first
+        for property in ["bframes", "b-frames"] {
+          if encoder.find_property(property).is_some() {
+            encoder.set_property_from_str(property, "0");
+          }
+        }
+        let mut elements = vec![
+          make_element("queue")?,
+          decoder.clone(),
+          make_element("videoconvert")?,
+          encoder,
+        ];
+        if let Some(caps) = &encoded_caps {
+          elements.push(make_named("capsfilter", &[])?);
+          elements[elements.len() - 1].set_property("caps", caps);
+        }
+        if let Some(parser) = &parser {
+          elements.push(make_element(parser)?);
+        }
+        add_and_link(&pipeline, &elements)?;
+        if let Some(decoded) = decoder.static_pad("src") {
+          cut_stream(&decoded, head_cut, seeking_clone.clone());
+        }
+        let encoded = elements[elements.len() - 1]
+          .static_pad("src")
+          .ok_or_else(|| Error::new(Status::GenericFailure, "Encoder has no src pad"))?;
+        encoded.link(&head_pad).map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to link the re-encoded video: {:?}", e),
+          )
+        })?;
+        link_pad(pad, &elements[0])
+      })();
+      if let Err(e) = linked {
+        gst::element_error!(head, gst::CoreError::Negotiation, ["{}", e.reason]);
+      }
+    });
+
+    // Copied rest, read from the second parsebin along with the audio
+    let pipeline_weak = pipeline.downgrade();
+    let muxer_weak = muxer.downgrade();
+    let (start, keyframe, end) = (self.start, self.keyframe, self.end);
+    let tail_linked = AtomicBool::new(false);
+    tail.connect_pad_added(move |tail, pad| {
+      let (Some(pipeline), Some(muxer)) = (pipeline_weak.upgrade(), muxer_weak.upgrade()) else {
+        return;
+      };
+      let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+      let Some(name) = caps.structure(0).map(|s| s.name().to_string()) else {
+        return;
+      };
+      let linked = (|| -> Result<()> {
+        if name.starts_with("video/") && !tail_linked.swap(true, Ordering::SeqCst) {
+          let time = SPLICE_BUFFER.nseconds().to_string();
+          let queue = make_named(
+            "queue",
+            &[
+              ("max-size-time", time.as_str()),
+              ("max-size-buffers", "0"),
+              ("max-size-bytes", "0"),
+            ],
+          )?;
+          add_and_link(&pipeline, std::slice::from_ref(&queue))?;
+          queue
+            .static_pad("src")
+            .map(|src| src.link(&tail_pad))
+            .transpose()
+            .map_err(|e| {
+              Error::new(
+                Status::GenericFailure,
+                format!("Failed to link the copied video: {:?}", e),
+              )
+            })?;
+          streams.fetch_add(1, Ordering::SeqCst);
+          let cut = Cut {
+            origin: start,
+            from: keyframe,
+            to: end,
+            eos: false,
+          };
+          cut_stream(pad, cut, seeking.clone());
+          link_pad(pad, &queue)
+        } else if name.starts_with("audio/") {
+          let queue = make_named(
+            "queue",
+            &[
+              ("max-size-time", "0"),
+              ("max-size-buffers", "0"),
+              ("max-size-bytes", "0"),
+            ],
+          )?;
+          add_and_link(&pipeline, std::slice::from_ref(&queue))?;
+          let src = queue
+            .static_pad("src")
+            .ok_or_else(|| Error::new(Status::GenericFailure, "Queue has no src pad"))?;
+          let audio_pad = muxer.compatible_pad(&src, Some(&caps)).ok_or_else(|| {
+            Error::new(
+              Status::GenericFailure,
+              format!("Muxer does not accept {}", name),
+            )
+          })?;
+          src.link(&audio_pad).map_err(|e| {
+            Error::new(
+              Status::GenericFailure,
+              format!("Muxer does not accept {}: {:?}", name, e),
+            )
+          })?;
+          streams.fetch_add(1, Ordering::SeqCst);
+          let cut = Cut {
+            origin: start,
+            from: start,
+            to: end,
+            eos: false,
+          };
+          cut_stream(pad, cut, seeking.clone());
+          link_pad(pad, &queue)
+        } else {
+          Ok(())
+        }
+      })();
+      if let Err(e) = linked {
+        gst::element_error!(tail, gst::CoreError::Negotiation, ["{}", e.reason]);
+      }
+    });
+
+    Ok((pipeline, [head, tail]))
+  }
+}
+
+/// Extracts the `[startMs, endMs)` range of a media file into a new file
+///
+/// In "copy" mode the clip starts at the keyframe at or before `startMs`, so it
+/// may begin slightly earlier than requested, but no quality is lost. In
+/// "smart" mode the clip starts exactly at `startMs`: the video up to the
+/// next keyframe is re-encoded with the codec and profile of the input and
+/// the rest is copied. H.264 and H.265 clips then carry their parameter sets
+/// in-band ("avc3"/"hev1" in MP4 and Matroska).
+///
+/// # Arguments
+/// * `input_path` - The media file to cut
+/// * `output_path` - Where to write the clip
+/// * `start_ms` - Start of the range in milliseconds
+/// * `end_ms` - End of the range in milliseconds
+/// * `options` - Optional mode and output container
+///
+/// # Example
+/// ```javascript
+/// // Also writes highlight.en.vtt
+/// extractClip("talk.webm", "highlight.webm", 60_000, 75_000, { mode: "copy", subtitles: ["talk.en.vtt"] });
+/// ```
+#[napi]
+pub fn extract_clip(
+  input_path: String,
+  output_path: String,
+  start_ms: f64,
+  end_ms: f64,
+  options: Option<ClipOptions>,
+) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  if start_ms < 0.0 || end_ms <= start_ms {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Clip range must satisfy 0 <= startMs < endMs".to_string(),
+    ));
+  }
+  let start = gst::ClockTime::from_nseconds((start_ms * 1_000_000.0) as u64);
+  let end = gst::ClockTime::from_nseconds((end_ms * 1_000_000.0) as u64);
+
+  let (mode, container, subtitles) = match options {
+    Some(options) => (options.mode, options.container, options.subtitles),
+    None => (None, None, None),
+  };
+  let container = container
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer a container for {}", output_path),
+      )
+    })?;
+
+  let copy = Some("copy".to_string());
+  // (video codec, audio codec, start of the clip), or the splice of a smart clip
+  let plan = match mode.as_deref().unwrap_or("copy") {
+    "copy" => Ok((
+      copy.clone(),
+      copy,
+      scan_keyframes(&input_path, start, end)?
+        .before
+        .unwrap_or(start),
+    )),
+    "reencode" => Ok((None, None, start)),
+    "smart" => {
+      let scan = scan_keyframes(&input_path, start, end)?;
+      match (scan.caps, scan.after) {
+        // Cut on a keyframe, or no video: copying is exact
+        (None, _) => Ok((copy.clone(), copy, start)),
+        (Some(_), _) if scan.before == Some(start) => Ok((copy.clone(), copy, start)),
+        (Some(caps), Some(keyframe)) => Err(Splice {
+          start,
+          keyframe,
+          end,
+          caps,
+        }),
+        // No keyframe before the end: the whole clip is re-encoded
+        (Some(caps), None) => Ok((video_codec(&caps).map(str::to_string), copy, start)),
+      }
+    }
+    mode => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported clip mode: {}", mode),
+      ))
+    }
+  };
+
+  let sink = file_element("filesink", &output_path)?;
+  let streams = Arc::new(AtomicUsize::new(0));
+  let seeking = Arc::new(AtomicBool::new(start > gst::ClockTime::ZERO));
+  let (pipeline, demuxers, clip_start) = match plan {
+    Ok((video_codec, audio_codec, clip_start)) => {
+      let source = file_element("filesrc", &input_path)?;
+      let options = TranscodeOptions {
+        container,
+        video_codec,
+        audio_codec,
+        ..Default::default()
+      };
+      let cut = Cut {
+        origin: clip_start,
+        from: clip_start,
+        to: end,
+        eos: false,
+      };
+      let hook = range_hook(cut, streams.clone(), seeking.clone());
+      let pipeline = build_transcode_pipeline(&source, &sink, &options, false, Some(hook))?;
+      let demuxer = pipeline
+        .by_name("demux")
+        .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no demuxer"))?;
+      (pipeline, vec![demuxer], clip_start)
+    }
+    Err(splice) => {
+      let (pipeline, demuxers) = splice.build(
+        &input_path,
+        &sink,
+        &container,
+        streams.clone(),
+        seeking.clone(),
+      )?;
+      (pipeline, demuxers.to_vec(), start)
+    }
+  };
+
+  if seeking.load(Ordering::SeqCst) {
+    if let Err(e) = seek_demuxers(&pipeline, &demuxers, start, &seeking) {
+      let _ = pipeline.set_state(gst::State::Null);
+      return Err(e);
+    }
+  }
+  run_until_clip_end(&pipeline, &streams)?;
+  for subtitle in subtitles.unwrap_or_default() {
+    trim_subtitles(
+      &subtitle,
+      &clip_subtitle_path(&output_path, &subtitle),
+      clip_start.mseconds() as i64,
+      end.mseconds() as i64,
+    )?;
+  }
+  Ok(())
+}
+
+/// Builds the hook cutting every stream of a transcode to `cut`
+fn range_hook(cut: Cut, streams: Arc<AtomicUsize>, seeking: Arc<AtomicBool>) -> StreamHook {
+  Arc::new(move |pad: &gst::Pad| {
+    streams.fetch_add(1, Ordering::SeqCst);
+    cut_stream(pad, cut, seeking.clone());
+  })
+}
@@ -0,0 +1,629 @@
+//! # Codec Registry
+//!
+//! Maps codec names to the GStreamer encoder elements able to produce them.
+//! A codec can have several backends (e.g. `x264enc`, `openh264enc` and the
+//! VA-API or NVENC hardware encoders for h264); the first one whose plugin is
+//! installed is used. Backends are GStreamer plugins, so they are discovered
+//! at runtime rather than compiled in, and more can be registered from
+//! JavaScript with `registerCodecBackend`.
+
+use crate::transcode::{container_spec, CONTAINERS};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{LazyLock, Mutex};
+
+/// Encoder element of a codec and how its bitrate is configured
+#[derive(Clone)]
+pub(crate) struct CodecSpec {
+  pub(crate) encoder: String,
+  /// Optional parser placed after the encoder
+  pub(crate) parser: Option<String>,
+  /// Name of the bitrate property, if the encoder has one
+  pub(crate) bitrate_property: Option<String>,
+  /// Factor converting kbit/s into the unit of `bitrate_property`
+  pub(crate) bitrate_scale: u32,
+  /// Whether the encoder runs on dedicated hardware
+  pub(crate) hardware: bool,
+}
+
+/// A backend registered for a codec
+struct Registration {
+  codec: String,
+  /// "video" or "audio"
+  kind: &'static str,
+  spec: CodecSpec,
+}
+
+/// Built-in backends as (kind, codec, encoder, parser, bitrate property,
+/// bitrate scale, hardware), in order of preference
+#[allow(clippy::type_complexity)]
+const BUILTIN_BACKENDS: &[(&str, &str, &str, Option<&str>, Option<&str>, u32, bool)] = &[
+  (
+    "video",
+    "vp8",
+    "vp8enc",
+    None,
+    Some("target-bitrate"),
+    1000,
+    false,
+  ),
+  ("video", "vp8", "vavp8enc", None, Some("bitrate"), 1, true),
+  (
+    "video",
+    "vp9",
+    "vp9enc",
+    None,
+    Some("target-bitrate"),
+    1000,
+    false,
+  ),
+  ("video", "vp9", "vavp9enc", None, Some("bitrate"), 1, true),
+  (
+    "video",
+    "av1",
+    "av1enc",
+    None,
+    Some("target-bitrate"),
+    1,
+    false,
+  ),
+  (
+    "video",
+    "av1",
+    "rav1enc",
+    None,
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "video",
+    "av1",
+    "svtav1enc",
+    None,
+    Some("target-bitrate"),
+    1,
+    false,
+  ),
+  (
+    "video",
+    "av1",
+    "vaav1enc",
+    Some("av1parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "h264",
+    "x264enc",
+    Some("h264parse"),
+    Some("bitrate"),
+    1,
+    false,
+  ),
+  (
+    "video",
+    "h264",
+    "openh264enc",
+    Some("h264parse"),
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "video",
+    "h264",
+    "vah264enc",
+    Some("h264parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "h264",
+    "nvh264enc",
+    Some("h264parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "h264",
+    "vtenc_h264",
+    Some("h264parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "h265",
+    "x265enc",
+    Some("h265parse"),
+    Some("bitrate"),
+    1,
+    false,
+  ),
+  (
+    "video",
+    "h265",
+    "vah265enc",
+    Some("h265parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "h265",
+    "nvh265enc",
+    Some("h265parse"),
+    Some("bitrate"),
+    1,
+    true,
+  ),
+  (
+    "video",
+    "theora",
+    "theoraenc",
+    None,
+    Some("bitrate"),
+    1,
+    false,
+  ),
+  ("video", "mjpeg", "jpegenc", None, None, 1, false),
+  (
+    "audio",
+    "opus",
+    "opusenc",
+    None,
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "audio",
+    "vorbis",
+    "vorbisenc",
+    None,
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "audio",
+    "mp3",
+    "lamemp3enc",
+    None,
+    Some("bitrate"),
+    1,
+    false,
+  ),
+  (
+    "audio",
+    "aac",
+    "avenc_aac",
+    Some("aacparse"),
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "audio",
+    "aac",
+    "fdkaacenc",
+    Some("aacparse"),
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  (
+    "audio",
+    "aac",
+    "voaacenc",
+    Some("aacparse"),
+    Some("bitrate"),
+    1000,
+    false,
+  ),
+  ("audio", "flac", "flacenc", None, None, 1, false),
+];
+
+static REGISTRY: LazyLock<Mutex<Vec<Registration>>> = LazyLock::new(|| {
+  Mutex::new(
+    BUILTIN_BACKENDS
+      .iter()
+      .map(
+        |&(kind, codec, encoder, parser, bitrate_property, bitrate_scale, hardware)| Registration {
+          codec: codec.to_string(),
+          kind,
+          spec: CodecSpec {
+            encoder: encoder.to_string(),
+            parser: parser.map(str::to_string),
+            bitrate_property: bitrate_property.map(str::to_string),
+            bitrate_scale,
+            hardware,
+          },
+        },
+      )
+      .collect(),
+  )
+});
+
+/// Returns whether the plugin providing an element is installed
+pub(crate) fn is_installed(factory: &str) -> bool {
+  gst::init().is_ok() && gst::ElementFactory::find(factory).is_some()
+}
+
+/// Returns the names of the registered codecs of a kind, in registration order
+pub(crate) fn codec_names(kind: &str) -> Vec<String> {
+  let mut names: Vec<String> = Vec::new();
+  for registration in REGISTRY.lock().unwrap().iter() {
+    if registration.kind == kind && !names.contains(&registration.codec) {
+      names.push(registration.codec.clone());
+    }
+  }
+  names
+}
+
+/// Returns every backend registered for a codec, in order of preference
+pub(crate) fn backends(kind: &str, codec: &str) -> Vec<CodecSpec> {
+  REGISTRY
+    .lock()
+    .unwrap()
+    .iter()
+    .filter(|r| r.kind == kind && r.codec == codec)
+    .map(|r| r.spec.clone())
+    .collect()
+}
+
+/// Returns the registration of an encoder element, of any codec
+pub(crate) fn encoder_spec(encoder: &str) -> Option<CodecSpec> {
+  REGISTRY
+    .lock()
+    .unwrap()
+    .iter()
+    .find(|r| r.spec.encoder == encoder)
+    .map(|r| r.spec.clone())
+}
+
+/// Resolves a codec to its preferred installed backend
+///
+/// When no backend is installed the first one is returned, so the error names
+/// the element that is missing once the pipeline is built.
+pub(crate) fn resolve(kind: &str, codec: &str) -> Result<CodecSpec> {
+  let backends = backends(kind, codec);
+  backends
+    .iter()
+    .find(|spec| is_installed(&spec.encoder))
+    .or(backends.first())
+    .cloned()
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Unsupported {} codec: {}", kind, codec),
+      )
+    })
+}
+
+/// Encoder backend registered with `registerCodecBackend`
+#[napi(object)]
+pub struct CodecBackend {
+  /// "video" or "audio"
+  pub kind: String,
+  /// Encoder element name, e.g. "qsvh264enc"
+  pub encoder: String,
+  /// Parser placed after the encoder, e.g. "h264parse"
+  pub parser: Option<String>,
+  /// Name of the encoder's bitrate property
+  pub bitrate_property: Option<String>,
+  /// Factor converting kbit/s into the unit of the bitrate property (default: 1)
+  pub bitrate_scale: Option<u32>,
+  /// Whether the encoder runs on dedicated hardware (default: false)
+  pub hardware: Option<bool>,
+}
+
+/// A codec with at least one installed backend
+#[napi(object)]
+pub struct SupportedCodec {
+  /// Codec name, as accepted by `TranscodeOptions`
+  pub name: String,
+  /// "video" or "audio"
+  pub kind: String,
+  /// Encoder element used for the codec
+  pub encoder: String,
+  /// Whether that encoder runs on dedicated hardware
+  pub hardware: bool,
+  /// Every installed encoder element for the codec, in order of preference
+  pub backends: Vec<String>,
+}
+
+/// Registers an encoder backend for a codec, ahead of the existing ones
+///
+/// The codec may be new, in which case it becomes accepted by
+/// `TranscodeOptions` once its encoder is installed.
+///
+/// # Arguments
+/// * `codec` - Codec name, e.g. "h264"
+/// * `backend` - Encoder element and how to configure it
+///
+/// # Example
+/// ```javascript
+/// registerCodecBackend("h264", { kind: "video", encoder: "qsvh264enc", parser: "h264parse", bitrateProperty: "bitrate", hardware: true });
+/// ```
+#[napi]
+pub fn register_codec_backend(codec: String, backend: CodecBackend) -> Result<()> {
+  let kind = match backend.kind.as_str() {
+    "video" => "video",
+    "audio" => "audio",
+    other => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid codec kind: {}", other),
+      ))
+    }
+  };
+  let spec = CodecSpec {
+    encoder: backend.encoder,
+    parser: backend.parser,
+    bitrate_property: backend.bitrate_property,
+    bitrate_scale: backend.bitrate_scale.unwrap_or(1),
+    hardware: backend.hardware.unwrap_or(false),
+  };
+  let mut registry = REGISTRY.lock().unwrap();
+  registry.retain(|r| !(r.kind == kind && r.codec == codec && r.spec.encoder == spec.encoder));
+  registry.insert(0, Registration { codec, kind, spec });
+  Ok(())
+}
+
+/// Lists the codecs that can be encoded with the installed GStreamer plugins
+///
+/// # Returns
+/// * `Result<Vec<SupportedCodec>>` - Video codecs, then audio codecs
+///
+/// # Example
+/// ```javascript
+/// const h264 = getSupportedCodecs().find((c) => c.name === "h264");
+/// if (h264) console.log("h264 via", h264.encoder);
+/// ```
+#[napi]
+pub fn get_supported_codecs() -> Result<Vec<SupportedCodec>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let mut supported = Vec::new();
+  for kind in ["video", "audio"] {
+    for name in codec_names(kind) {
+      let installed: Vec<CodecSpec> = backends(kind, &name)
+        .into_iter()
+        .filter(|spec| is_installed(&spec.encoder))
+        .collect();
+      if let Some(preferred) = installed.first() {
+        supported.push(SupportedCodec {
+          name,
+          kind: kind.to_string(),
+          encoder: preferred.encoder.clone(),
+          hardware: preferred.hardware,
+          backends: installed.iter().map(|spec| spec.encoder.clone()).collect(),
+        });
+      }
+    }
+  }
+  Ok(supported)
+}
+
+/// Caps of the compressed stream of the built-in codecs
+fn builtin_caps(codec: &str) -> Option<&'static str> {
+  Some(match codec {
+    "vp8" => "video/x-vp8",
+    "vp9" => "video/x-vp9",
+    "av1" => "video/x-av1",
+    "h264" => "video/x-h264",
+    "h265" => "video/x-h265",
+    "theora" => "video/x-theora",
+    "mjpeg" => "image/jpeg",
+    "opus" => "audio/x-opus",
+    "vorbis" => "audio/x-vorbis",
+    "mp3" => "audio/mpeg,mpegversion=(int)1,layer=(int)3",
+    "aac" => "audio/mpeg,mpegversion=(int)4",
+    "flac" => "audio/x-flac",
+    _ => return None,
+  })
+}
+
+/// Caps of the stream of a container
+fn container_caps(container: &str) -> &'static str {
+  match container {
+    "webm" => "video/webm",
+    "mp4" | "mov" => "video/quicktime",
+    "ogg" => "application/ogg",
+    "avi" => "video/x-msvideo",
+    "ts" => "video/mpegts",
+    _ => "video/x-matroska",
+  }
+}
+
+/// Returns the caps of a codec's compressed stream, from the built-in table
+/// or the source pad template of an installed encoder
+fn codec_caps(codec: &str, encoders: &[CodecSpec]) -> Option<gst::Caps> {
+  if let Some(caps) = builtin_caps(codec) {
+    return caps.parse().ok();
+  }
+  encoders.iter().find_map(|spec| {
+    gst::ElementFactory::find(&spec.encoder)?
+      .static_pad_templates()
+      .iter()
+      .find(|template| template.direction() == gst::PadDirection::Src)
+      .map(|template| template.caps())
+  })
+}
+
+/// Names of the installed element factories of a type accepting `caps`, best ranked first
+fn factories_for(kind: gst::ElementFactoryType, caps: &gst::Caps) -> Vec<String> {
+  let mut factories: Vec<gst::ElementFactory> =
+    gst::ElementFactory::factories_with_type(kind, gst::Rank::NONE)
+      .into_iter()
+      .filter(|factory| factory.can_sink_any_caps(caps))
+      .collect();
+  factories.sort_by_key(|factory| std::cmp::Reverse(factory.rank()));
+  factories.iter().map(|f| f.name().to_string()).collect()
+}
+
+/// Whether an element factory drives dedicated hardware
+fn is_hardware(factory: &str) -> bool {
+  gst::ElementFactory::find(factory)
+    .and_then(|f| f.metadata(gst::ELEMENT_METADATA_KLASS).map(str::to_string))
+    .is_some_and(|klass| klass.contains("Hardware"))
+}
+
+/// What the installed plugins can do with a codec
+#[napi(object)]
+pub struct CodecCapability {
+  /// Codec name, as accepted by `TranscodeOptions`
+  pub name: String,
+  /// "video" or "audio"
+  pub kind: String,
+  /// Whether the codec can be encoded
+  pub encode: bool,
+  /// Whether the codec can be decoded
+  pub decode: bool,
+  /// Installed encoder elements, in order of preference
+  pub encoders: Vec<String>,
+  /// Installed decoder elements, best ranked first
+  pub decoders: Vec<String>,
+  /// Whether a hardware encoder or decoder is installed
+  pub hardware: bool,
+}
+
+/// What the installed plugins can do with a container
+#[napi(object)]
+pub struct ContainerCapability {
+  /// Container name, as accepted by `TranscodeOptions`
+  pub name: String,
+  /// Whether the container can be written
+  pub mux: bool,
+  /// Whether the container can be read
+  pub demux: bool,
+  /// Muxer element
+  pub muxer: String,
+  /// Installed demuxer elements, best ranked first
+  pub demuxers: Vec<String>,
+}
+
+/// Capabilities of the GStreamer installation this module runs on
+#[napi(object)]
+pub struct Capabilities {
+  /// Runtime GStreamer version, e.g. "1.24.2"
+  pub gstreamer_version: String,
+  /// Registered codecs, whether or not they are usable
+  pub codecs: Vec<CodecCapability>,
+  /// Supported containers
+  pub containers: Vec<ContainerCapability>,
+  /// Raw video formats `videoconvert` can convert between
+  pub pixel_formats: Vec<String>,
+}
+
+/// Describes what the installed GStreamer plugins can encode, decode, mux,
+/// demux and convert
+///
+/// Everything is looked up at call time, so the result reflects the plugins
+/// installed on the machine rather than a fixed list.
+///
+/// # Returns
+/// * `Result<Capabilities>` - Codec, container and pixel format capabilities
+///
+/// # Example
+/// ```javascript
+/// const caps = getCapabilities();
+/// const h264 = caps.codecs.find((c) => c.name === "h264");
+/// const codec = h264?.encode ? "h264" : "vp8";
+/// ```
+#[napi]
+pub fn get_capabilities() -> Result<Capabilities> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let mut codecs = Vec::new();
+  for kind in ["video", "audio"] {
+    for name in codec_names(kind) {
+      let registered = backends(kind, &name);
+      let encoders: Vec<String> = registered
+        .iter()
+        .filter(|spec| is_installed(&spec.encoder))
+        .map(|spec| spec.encoder.clone())
+        .collect();
+      let decoders = codec_caps(&name, &registered)
+        .map(|caps| factories_for(gst::ElementFactoryType::DECODER, &caps))
+        .unwrap_or_default();
+      codecs.push(CodecCapability {
+        kind: kind.to_string(),
+        encode: !encoders.is_empty(),
+        decode: !decoders.is_empty(),
+        hardware: encoders.iter().chain(&decoders).any(|f| is_hardware(f)),
+        name,
+        encoders,
+        decoders,
+      });
+    }
+  }
+
+  let containers = CONTAINERS
+    .iter()
+    .filter_map(|&name| {
+      let muxer = container_spec(name).ok()?.muxer;
+      let demuxers = container_caps(name)
+        .parse::<gst::Caps>()
+        .map(|caps| factories_for(gst::ElementFactoryType::DEMUXER, &caps))
+        .unwrap_or_default();
+      Some(ContainerCapability {
+        name: name.to_string(),
+        mux: is_installed(muxer),
+        demux: !demuxers.is_empty(),
+        muxer: muxer.to_string(),
+        demuxers,
+      })
+    })
+    .collect();
+
+  let pixel_formats = gst::ElementFactory::find("videoconvert")
+    .and_then(|factory| {
+      factory
+        .static_pad_templates()
+        .iter()
+        .find(|template| template.direction() == gst::PadDirection::Sink)
+        .map(|template| template.caps())
+    })
+    .and_then(|caps| {
+      let formats = caps.structure(0)?.get::<gst::List>("format").ok()?;
+      Some(
+        formats
+          .iter()
+          .filter_map(|format| format.get::<String>().ok())
+          .collect(),
+      )
+    })
+    .unwrap_or_default();
+
+  Ok(Capabilities {
+    gstreamer_version: gst::version_string()
+      .trim_start_matches("GStreamer ")
+      .to_string(),
+    codecs,
+    containers,
+    pixel_formats,
+  })
+}
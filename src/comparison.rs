@@ -0,0 +1,198 @@
+//! # Comparison Videos
+//!
+//! Renders two videos into one, side by side, stacked or split down the
+//! middle, to show the difference between two encodes or filter settings.
+//! Frames are paired by timestamp, so inputs with different frame rates stay
+//! in step.
+
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{container_spec, launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+
+/// Options for `composeComparison`
+#[napi(object)]
+pub struct ComparisonOptions {
+  /// "hstack" (side by side, default), "vstack" (A above B) or "wipe" (left
+  /// half of A next to the right half of B)
+  pub layout: Option<String>,
+  /// Width each input is scaled to (default: width of the first input)
+  pub width: Option<u32>,
+  /// Height each input is scaled to (default: height of the first input)
+  pub height: Option<u32>,
+  /// Output container; defaults to the output file extension
+  pub container: Option<String>,
+  /// Video codec, defaults to the usual codec for the container
+  pub video_codec: Option<String>,
+}
+
+/// Pipeline branch decoding `location` scaled to `width`x`height`, ending at
+/// compositor pad `pad`
+fn input_branch(location: &str, width: u32, height: u32, crop: &str, pad: &str) -> Result<String> {
+  Ok(format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw ! videoconvert ! videoscale ! \
+     video/x-raw,width={},height={},pixel-aspect-ratio=1/1{} ! queue ! mix.{}",
+    to_uri(location)?,
+    width,
+    height,
+    crop,
+    pad
+  ))
+}
+
+/// Renders two videos into one for visual comparison
+///
+/// # Arguments
+/// * `input_a` - Path or URI of the first video (left, top, or left half)
+/// * `input_b` - Path or URI of the second video (right, bottom, or right half)
+/// * `output_path` - Where to write the comparison video
+/// * `options` - Layout, tile size and output format
+///
+/// # Example
+/// ```javascript
+/// composeComparison("x264.mp4", "av1.webm", "compare.mkv", { layout: "wipe" });
+/// ```
+#[napi]
+pub fn compose_comparison(
+  input_a: String,
+  input_b: String,
+  output_path: String,
+  options: Option<ComparisonOptions>,
+) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(ComparisonOptions {
+    layout: None,
+    width: None,
+    height: None,
+    container: None,
+    video_codec: None,
+  });
+  let container = options
+    .container
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer a container for {}", output_path),
+      )
+    })?;
+  let container = container_spec(&container)?;
+  let codec = video_codec_spec(
+    options
+      .video_codec
+      .as_deref()
+      .unwrap_or(container.video_codec),
+  )?;
+
+  let mut size = (options.width, options.height);
+  for input in [&input_a, &input_b] {
+    let info = probe_with_gstreamer(input.clone(), None)?;
+    let video = info
+      .video
+      .first()
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("{} has no video stream", input)))?;
+    size.0.get_or_insert(video.width);
+    size.1.get_or_insert(video.height);
+  }
+  let (width, height) = (size.0.unwrap_or(0), size.1.unwrap_or(0));
+  if width < 2 || height == 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Width and height must be positive".to_string(),
+    ));
+  }
+
+  let (positions, branches) = match options.layout.as_deref().unwrap_or("hstack") {
+    "hstack" => (
+      [(0, 0), (width, 0)],
+      [
+        input_branch(&input_a, width, height, "", "sink_0")?,
+        input_branch(&input_b, width, height, "", "sink_1")?,
+      ],
+    ),
+    "vstack" => (
+      [(0, 0), (0, height)],
+      [
+        input_branch(&input_a, width, height, "", "sink_0")?,
+        input_branch(&input_b, width, height, "", "sink_1")?,
+      ],
+    ),
+    "wipe" => {
+      let half = width / 2;
+      (
+        [(0, 0), (half, 0)],
+        [
+          input_branch(
+            &input_a,
+            width,
+            height,
+            &format!(" ! videocrop right={}", width - half),
+            "sink_0",
+          )?,
+          input_branch(
+            &input_b,
+            width,
+            height,
+            &format!(" ! videocrop left={}", half),
+            "sink_1",
+          )?,
+        ],
+      )
+    }
+    layout => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported comparison layout: {}", layout),
+      ))
+    }
+  };
+
+  let parser = codec
+    .parser
+    .map(|p| format!(" ! {}", p))
+    .unwrap_or_default();
+  let description = format!(
+    "compositor name=mix background=black \
+     sink_0::xpos={} sink_0::ypos={} sink_1::xpos={} sink_1::ypos={} ! \
+     videoconvert ! {}{} ! queue ! {} ! filesink name=sink {} {}",
+    positions[0].0,
+    positions[0].1,
+    positions[1].0,
+    positions[1].1,
+    codec.encoder,
+    parser,
+    container.muxer,
+    branches[0],
+    branches[1]
+  );
+  let pipeline = launch(&description)?;
+  let sink = pipeline
+    .by_name("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+  sink.set_property("location", &output_path);
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
@@ -0,0 +1,371 @@
+//! # Content Complexity
+//!
+//! Per-title encoding analysis. Frames of the input are sampled and measured
+//! for spatial information (SI, the spread of Sobel edge strength) and
+//! temporal information (TI, the spread of the difference to the previous
+//! frame) as defined by ITU-T P.910. The scores drive a recommended bitrate
+//! ladder: detailed, high-motion content gets more bits per rung than a
+//! static slide deck. The scores of each measured frame can be streamed to
+//! an NDJSON or CSV file as they are computed.
+
+use crate::export::ResultWriter;
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// Frames are measured at this width at most, which keeps the analysis fast
+/// on 4K input without changing the scores much
+const ANALYSIS_WIDTH: u32 = 640;
+
+/// Ladder rung heights with their bitrate in kbit/s for H.264 content of
+/// average complexity
+const RUNGS: &[(u32, u32)] = &[
+  (2160, 14000),
+  (1440, 8000),
+  (1080, 5000),
+  (720, 3000),
+  (480, 1500),
+  (360, 800),
+  (240, 400),
+];
+
+/// Options for `analyzeComplexity`
+#[napi(object)]
+pub struct ComplexityOptions {
+  /// Number of frames to measure, spread over the whole input (default: 60)
+  pub samples: Option<u32>,
+  /// Codec the ladder is recommended for (default: "h264")
+  pub codec: Option<String>,
+  /// File the scores of each measured frame are written to while the
+  /// analysis runs
+  pub export_path: Option<String>,
+  /// "ndjson" or "csv" (default: from the extension of `exportPath`, NDJSON unless `.csv`)
+  pub export_format: Option<String>,
+}
+
+/// Columns of exported frame scores, in CSV order
+const COLUMNS: &[&str] = &["frame", "pts", "spatialInformation", "temporalInformation"];
+
+/// Scores of one measured frame, as exported
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameComplexity {
+  frame: u64,
+  pts: i64,
+  spatial_information: f64,
+  temporal_information: Option<f64>,
+}
+
+/// A recommended encoding of the input at one resolution
+#[napi(object)]
+pub struct LadderRung {
+  /// Width in pixels
+  pub width: u32,
+  /// Height in pixels
+  pub height: u32,
+  /// Target bitrate in kbit/s
+  pub bitrate: u32,
+  /// Constant rate factor for quality-targeted encodes (x264/x265 scale)
+  pub crf: u32,
+}
+
+/// Measured complexity of a video and the ladder recommended for it
+#[napi(object)]
+pub struct ComplexityReport {
+  /// Source width in pixels
+  pub width: u32,
+  /// Source height in pixels
+  pub height: u32,
+  /// Number of frames measured
+  pub frames_analyzed: u32,
+  /// Spatial information: the maximum over the measured frames
+  pub spatial_information: f64,
+  /// Temporal information: the maximum over the measured frames
+  pub temporal_information: f64,
+  /// Mean spatial information of the measured frames
+  pub mean_spatial_information: f64,
+  /// Mean temporal information of the measured frames
+  pub mean_temporal_information: f64,
+  /// Overall complexity from 0 (static, flat) to 1 (busy, high motion)
+  pub score: f64,
+  /// Recommended renditions, highest resolution first
+  pub ladder: Vec<LadderRung>,
+}
+
+/// Running SI/TI measurement
+#[derive(Default)]
+struct Measurement {
+  frame: u64,
+  previous: Option<Vec<u8>>,
+  si: Vec<f64>,
+  ti: Vec<f64>,
+  export: Option<ResultWriter>,
+  /// First export error; the analysis fails with it once done
+  export_error: Option<String>,
+}
+
+fn std_dev(values: impl Iterator<Item = f64>) -> f64 {
+  let (mut n, mut sum, mut sum_sq) = (0.0, 0.0, 0.0);
+  for v in values {
+    n += 1.0;
+    sum += v;
+    sum_sq += v * v;
+  }
+  if n == 0.0 {
+    return 0.0;
+  }
+  let mean = sum / n;
+  (sum_sq / n - mean * mean).max(0.0).sqrt()
+}
+
+/// Standard deviation of the Sobel gradient magnitude of a luma plane
+fn spatial_information(luma: &[u8], width: usize, height: usize, stride: usize) -> f64 {
+  let at = |x: usize, y: usize| luma[y * stride + x] as f64;
+  std_dev((1..height.saturating_sub(1)).flat_map(|y| {
+    (1..width.saturating_sub(1)).map(move |x| {
+      let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+        - at(x - 1, y - 1)
+        - 2.0 * at(x - 1, y)
+        - at(x - 1, y + 1);
+      let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+        - at(x - 1, y - 1)
+        - 2.0 * at(x, y - 1)
+        - at(x + 1, y - 1);
+      (gx * gx + gy * gy).sqrt()
+    })
+  }))
+}
+
+/// Standard deviation of the pixel difference between two luma planes
+fn temporal_information(
+  luma: &[u8],
+  previous: &[u8],
+  width: usize,
+  height: usize,
+  stride: usize,
+) -> f64 {
+  std_dev((0..height).flat_map(|y| {
+    (0..width).map(move |x| luma[y * stride + x] as f64 - previous[y * stride + x] as f64)
+  }))
+}
+
+/// Relative bitrate a codec needs for the quality of H.264
+fn codec_efficiency(codec: &str) -> f64 {
+  match codec {
+    "h265" | "vp9" => 0.7,
+    "av1" => 0.6,
+    "vp8" | "theora" => 1.1,
+    "mjpeg" => 4.0,
+    _ => 1.0,
+  }
+}
+
+/// Measures the spatial and temporal complexity of a video and recommends a
+/// bitrate ladder for it
+///
+/// # Arguments
+/// * `input` - Path or URI of the video
+/// * `options` - Number of frames to sample and target codec
+///
+/// # Returns
+/// * `Result<ComplexityReport>` - SI/TI scores and the recommended renditions
+///
+/// # Example
+/// ```javascript
+/// const { score, ladder } = analyzeComplexity("movie.mkv", { codec: "av1" });
+/// for (const rung of ladder) {
+///   console.log(`${rung.height}p: ${rung.bitrate} kbit/s (crf ${rung.crf})`);
+/// }
+/// ```
+#[napi]
+pub fn analyze_complexity(
+  input: String,
+  options: Option<ComplexityOptions>,
+) -> Result<ComplexityReport> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(ComplexityOptions {
+    samples: None,
+    codec: None,
+    export_path: None,
+    export_format: None,
+  });
+  let codec = options.codec.unwrap_or_else(|| "h264".to_string());
+  video_codec_spec(&codec)?;
+  let samples = options.samples.unwrap_or(60).max(1) as u64;
+
+  let info = probe_with_gstreamer(input.clone(), None)?;
+  let video = info
+    .video
+    .first()
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("{} has no video stream", input)))?;
+  let (width, height) = (video.width, video.height);
+  if width == 0 || height == 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("Cannot determine the frame size of {}", input),
+    ));
+  }
+  // Measure every `step`-th frame so the samples cover the whole input
+  let frames = (info.duration.max(0) as f64 / 1e9 * video.frame_rate).round() as u64;
+  let step = (frames / samples).max(1);
+
+  let analysis_width = (width.min(ANALYSIS_WIDTH) & !1).max(2);
+  let analysis_height = ((height as u64 * analysis_width as u64 / width as u64) as u32).max(2) & !1;
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw ! videoconvert ! videoscale ! \
+     video/x-raw,format=GRAY8,width={},height={} ! appsink name=sink sync=false",
+    to_uri(&input)?,
+    analysis_width,
+    analysis_height
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Analysis sink not found"))?;
+
+  let export = options
+    .export_path
+    .as_deref()
+    .map(|path| ResultWriter::create(path, options.export_format.as_deref(), COLUMNS))
+    .transpose()?;
+  let measurement = Arc::new(Mutex::new(Measurement {
+    export,
+    ..Default::default()
+  }));
+  let measurement_clone = measurement.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+        let video = gst_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        let (w, h, stride) = (
+          video.width() as usize,
+          video.height() as usize,
+          video.stride()[0] as usize,
+        );
+
+        let mut guard = measurement_clone.lock().unwrap();
+        let m = &mut *guard;
+        let frame = m.frame;
+        m.frame += 1;
+        if m.si.len() as u64 >= samples {
+          return Ok(gst::FlowSuccess::Ok);
+        }
+        if frame.is_multiple_of(step) {
+          let si = spatial_information(&map, w, h, stride);
+          m.si.push(si);
+          let ti = m
+            .previous
+            .as_ref()
+            .map(|previous| temporal_information(&map, previous, w, h, stride));
+          m.ti.extend(ti);
+          if let (Some(export), None) = (&mut m.export, &m.export_error) {
+            let record = FrameComplexity {
+              frame,
+              pts: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+              spatial_information: si,
+              temporal_information: ti,
+            };
+            if let Err(e) = export.write(&record) {
+              m.export_error = Some(e.reason.clone());
+            }
+          }
+        }
+        // Only the frame before a measured one is needed for TI
+        m.previous = (frame + 1).is_multiple_of(step).then(|| map.to_vec());
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let mut m = measurement.lock().unwrap();
+  if let Some(reason) = m.export_error.take() {
+    return Err(Error::new(Status::GenericFailure, reason));
+  }
+  if let Some(export) = &mut m.export {
+    export.finish()?;
+  }
+  if m.si.is_empty() {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("No frames could be decoded from {}", input),
+    ));
+  }
+  let max = |values: &[f64]| values.iter().copied().fold(0.0, f64::max);
+  let mean = |values: &[f64]| {
+    if values.is_empty() {
+      0.0
+    } else {
+      values.iter().sum::<f64>() / values.len() as f64
+    }
+  };
+  let (si, ti) = (max(&m.si), max(&m.ti));
+
+  // Typical P.910 ranges: SI up to ~120 for dense detail, TI up to ~60 for
+  // fast motion. Motion costs more bits than detail, so it weighs more.
+  let score = 0.4 * (si / 120.0).min(1.0) + 0.6 * (ti / 60.0).min(1.0);
+  let factor = (0.6 + 1.2 * score) * codec_efficiency(&codec);
+  let crf = (26.0 - 6.0 * score).round() as u32;
+  let ladder = RUNGS
+    .iter()
+    .filter(|(rung_height, _)| *rung_height <= height)
+    .map(|&(rung_height, bitrate)| LadderRung {
+      width: ((width as u64 * rung_height as u64 / height as u64) as u32 + 1) & !1,
+      height: rung_height,
+      bitrate: (bitrate as f64 * factor).round() as u32,
+      crf,
+    })
+    .collect::<Vec<_>>();
+  // Sources below the lowest rung get a single rendition at their own size
+  let ladder = if ladder.is_empty() {
+    let bitrate = RUNGS[RUNGS.len() - 1].1 as f64 * (width * height) as f64 / (426.0 * 240.0);
+    vec![LadderRung {
+      width,
+      height,
+      bitrate: (bitrate * factor).round().max(1.0) as u32,
+      crf,
+    }]
+  } else {
+    ladder
+  };
+
+  Ok(ComplexityReport {
+    width,
+    height,
+    frames_analyzed: m.si.len() as u32,
+    spatial_information: si,
+    temporal_information: ti,
+    mean_spatial_information: mean(&m.si),
+    mean_temporal_information: mean(&m.ti),
+    score,
+    ladder,
+  })
+}
@@ -0,0 +1,420 @@
+//! # Compositor
+//!
+//! Mixes several video inputs into one output picture. Each input is placed
+//! with its own position, size, stacking order and opacity, all of which can
+//! be changed while the compositor is playing.
+
+use crate::kit::to_uri;
+use crate::transcode::launch;
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Options for the `Compositor` constructor
+#[napi(object)]
+pub struct CompositorOptions {
+  /// Output width in pixels (default: 1280)
+  pub width: Option<u32>,
+  /// Output height in pixels (default: 720)
+  pub height: Option<u32>,
+  /// Output frame rate (default: 30)
+  pub framerate: Option<u32>,
+  /// Background behind the inputs ("checker", "black", "white" or "transparent", default: "black")
+  pub background: Option<String>,
+  /// Sink description receiving the mixed video (default: "autovideosink")
+  pub output: Option<String>,
+}
+
+/// Placement of a compositor input; omitted fields are left unchanged
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct InputLayout {
+  /// Horizontal position of the input's left edge
+  pub x: Option<i32>,
+  /// Vertical position of the input's top edge
+  pub y: Option<i32>,
+  /// Displayed width (0 keeps the input width)
+  pub width: Option<i32>,
+  /// Displayed height (0 keeps the input height)
+  pub height: Option<i32>,
+  /// Stacking order; higher values are drawn on top
+  pub zorder: Option<u32>,
+  /// Opacity from 0 (invisible) to 1 (opaque)
+  pub alpha: Option<f64>,
+}
+
+/// An input bin and the mixer pad it feeds
+pub(crate) struct MixerInput {
+  pub(crate) bin: gst::Bin,
+  pub(crate) pad: gst::Pad,
+}
+
+/// Builds an input bin from a file path, URI or launch description
+///
+/// Paths and URIs are decoded with `uridecodebin` restricted to `raw_caps`;
+/// anything else is parsed as a launch description. `tail` is appended to
+/// convert the input for the mixer.
+pub(crate) fn input_bin(source: &str, raw_caps: &str, tail: &str) -> Result<gst::Bin> {
+  let head = if source.contains("://") || std::path::Path::new(source).exists() {
+    format!("uridecodebin uri=\"{}\" caps={}", to_uri(source)?, raw_caps)
+  } else {
+    source.to_string()
+  };
+  gst::parse::bin_from_description(&format!("{} ! {}", head, tail), true).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to parse input: {}", e),
+    )
+  })
+}
+
+/// Links an input bin to a new request pad of a mixer element
+pub(crate) fn attach_input(
+  pipeline: &gst::Pipeline,
+  mixer: &gst::Element,
+  bin: gst::Bin,
+) -> Result<MixerInput> {
+  pipeline.add(&bin).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add input: {}", e),
+    )
+  })?;
+  let pad = mixer
+    .request_pad_simple("sink_%u")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Failed to request mixer pad"))?;
+  let linked = bin
+    .static_pad("src")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Input has no output"))
+    .and_then(|src| {
+      src.link(&pad).map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to link input: {:?}", e),
+        )
+      })
+    });
+  if let Err(e) = linked {
+    mixer.release_request_pad(&pad);
+    let _ = pipeline.remove(&bin);
+    return Err(e);
+  }
+  let _ = bin.sync_state_with_parent();
+  Ok(MixerInput { bin, pad })
+}
+
+/// Unlinks an input bin, releases its mixer pad and removes it from the pipeline
+pub(crate) fn detach_input(pipeline: &gst::Pipeline, mixer: &gst::Element, input: MixerInput) {
+  let _ = input.bin.set_state(gst::State::Null);
+  if let Some(src) = input.bin.static_pad("src") {
+    let _ = src.unlink(&input.pad);
+  }
+  mixer.release_request_pad(&input.pad);
+  let _ = pipeline.remove(&input.bin);
+}
+
+/// Pushes a buffer to the first AppSrc of an input bin
+pub(crate) fn push_to_input(bin: &gst::Bin, id: &str, data: Buffer) -> Result<()> {
+  let appsrc = bin
+    .iterate_recurse()
+    .into_iter()
+    .flatten()
+    .find_map(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Input {} has no AppSrc", id),
+      )
+    })?;
+  appsrc
+    .push_buffer(gst::Buffer::from_mut_slice(data.to_vec()))
+    .map(|_| ())
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })
+}
+
+/// Sets the state of a whole pipeline
+pub(crate) fn set_pipeline_state(pipeline: &gst::Pipeline, state: gst::State) -> Result<()> {
+  pipeline.set_state(state).map(|_| ()).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to {:?}: {}", state, e),
+    )
+  })
+}
+
+fn apply_layout(pad: &gst::Pad, layout: &InputLayout) {
+  if let Some(x) = layout.x {
+    pad.set_property("xpos", x);
+  }
+  if let Some(y) = layout.y {
+    pad.set_property("ypos", y);
+  }
+  if let Some(width) = layout.width {
+    pad.set_property("width", width);
+  }
+  if let Some(height) = layout.height {
+    pad.set_property("height", height);
+  }
+  if let Some(zorder) = layout.zorder {
+    pad.set_property("zorder", zorder);
+  }
+  if let Some(alpha) = layout.alpha {
+    pad.set_property("alpha", alpha.clamp(0.0, 1.0));
+  }
+}
+
+/// Mixer of several video inputs into one picture, for picture-in-picture,
+/// side-by-side views and overlays
+///
+/// # Example
+/// ```javascript
+/// const comp = new Compositor({ width: 1280, height: 720 });
+/// comp.addInput("main", "movie.mp4");
+/// comp.addInput("cam", "v4l2src", { x: 960, y: 540, width: 320, height: 180 });
+/// comp.play();
+/// comp.setLayout("cam", { alpha: 0.5 });
+/// ```
+#[napi]
+pub struct Compositor {
+  pipeline: gst::Pipeline,
+  mixer: gst::Element,
+  inputs: Mutex<HashMap<String, MixerInput>>,
+}
+
+impl Drop for Compositor {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
+
+impl Compositor {
+  /// Runs `f` on the mixer pad of the input registered under `id`
+  fn with_pad<T>(&self, id: &str, f: impl FnOnce(&gst::Pad) -> T) -> Result<T> {
+    let inputs = self.inputs.lock().unwrap();
+    let input = inputs
+      .get(id)
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Input not found: {}", id)))?;
+    Ok(f(&input.pad))
+  }
+}
+
+#[napi]
+impl Compositor {
+  /// Creates a compositor with no inputs
+  ///
+  /// # Arguments
+  /// * `options` - Optional output size, frame rate, background and sink
+  ///
+  /// # Example
+  /// ```javascript
+  /// const comp = new Compositor({ width: 640, height: 360, output: "appsink name=out" });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: Option<CompositorOptions>) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let options = options.unwrap_or(CompositorOptions {
+      width: None,
+      height: None,
+      framerate: None,
+      background: None,
+      output: None,
+    });
+    let pipeline = launch(&format!(
+      "compositor name=mix background={} ! videoconvert ! video/x-raw,width={},height={},framerate={}/1 ! {}",
+      options.background.as_deref().unwrap_or("black"),
+      options.width.unwrap_or(1280),
+      options.height.unwrap_or(720),
+      options.framerate.unwrap_or(30),
+      options.output.as_deref().unwrap_or("autovideosink"),
+    ))?;
+    let mixer = pipeline
+      .by_name("mix")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Element mix not found"))?;
+
+    Ok(Compositor {
+      pipeline,
+      mixer,
+      inputs: Mutex::new(HashMap::new()),
+    })
+  }
+
+  /// Adds a video input
+  ///
+  /// # Arguments
+  /// * `id` - Unique name of the input
+  /// * `source` - A file path, a URI, or a launch description producing video
+  ///   (e.g. "v4l2src" or "appsrc caps=video/x-raw,... format=time")
+  /// * `layout` - Optional initial placement; new inputs are stacked on top
+  ///
+  /// # Example
+  /// ```javascript
+  /// comp.addInput("logo", "logo.png", { x: 16, y: 16, width: 128, height: 128, alpha: 0.8 });
+  /// ```
+  #[napi]
+  pub fn add_input(&self, id: String, source: String, layout: Option<InputLayout>) -> Result<()> {
+    let mut inputs = self.inputs.lock().unwrap();
+    if inputs.contains_key(&id) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Input already exists: {}", id),
+      ));
+    }
+
+    let bin = input_bin(&source, "video/x-raw", "videoconvert ! videoscale ! queue")?;
+    let input = attach_input(&self.pipeline, &self.mixer, bin)?;
+    let layout = layout.unwrap_or_default();
+    apply_layout(
+      &input.pad,
+      &InputLayout {
+        zorder: layout.zorder.or(Some(inputs.len() as u32)),
+        ..layout
+      },
+    );
+    inputs.insert(id, input);
+    Ok(())
+  }
+
+  /// Removes an input
+  ///
+  /// # Returns
+  /// * `bool` - Whether an input with this id existed
+  #[napi]
+  pub fn remove_input(&self, id: String) -> bool {
+    match self.inputs.lock().unwrap().remove(&id) {
+      Some(input) => {
+        detach_input(&self.pipeline, &self.mixer, input);
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the ids of all inputs
+  #[napi]
+  pub fn list_inputs(&self) -> Vec<String> {
+    let mut ids: Vec<String> = self.inputs.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+  }
+
+  /// Changes the placement of an input; omitted fields are left unchanged
+  ///
+  /// # Example
+  /// ```javascript
+  /// comp.setLayout("cam", { x: 0, y: 0, zorder: 10 });
+  /// ```
+  #[napi]
+  pub fn set_layout(&self, id: String, layout: InputLayout) -> Result<()> {
+    self.with_pad(&id, |pad| apply_layout(pad, &layout))
+  }
+
+  /// Returns the current placement of an input
+  #[napi]
+  pub fn get_layout(&self, id: String) -> Result<InputLayout> {
+    self.with_pad(&id, |pad| InputLayout {
+      x: Some(pad.property("xpos")),
+      y: Some(pad.property("ypos")),
+      width: Some(pad.property("width")),
+      height: Some(pad.property("height")),
+      zorder: Some(pad.property("zorder")),
+      alpha: Some(pad.property("alpha")),
+    })
+  }
+
+  /// Pushes a raw frame to an input whose source is an AppSrc
+  ///
+  /// # Arguments
+  /// * `id` - The input id
+  /// * `data` - Frame data matching the caps of the input's AppSrc
+  #[napi]
+  pub fn push_frame(&self, id: String, data: Buffer) -> Result<()> {
+    let inputs = self.inputs.lock().unwrap();
+    let input = inputs
+      .get(&id)
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("Input not found: {}", id)))?;
+    push_to_input(&input.bin, &id, data)
+  }
+
+  /// Pulls a mixed frame when the output is an AppSink
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Maximum time to wait for a frame (default: 1000)
+  ///
+  /// # Returns
+  /// * `Result<Option<Buffer>>` - The frame data, or null on timeout
+  ///
+  /// # Example
+  /// ```javascript
+  /// const comp = new Compositor({ output: "video/x-raw,format=RGBA ! appsink name=out" });
+  /// const frame = comp.pullFrame();
+  /// ```
+  #[napi]
+  pub fn pull_frame(&self, timeout_ms: Option<u32>) -> Result<Option<Buffer>> {
+    let appsink = self
+      .pipeline
+      .iterate_sinks()
+      .into_iter()
+      .flatten()
+      .find_map(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Output is not an AppSink"))?;
+    let timeout = gst::ClockTime::from_mseconds(timeout_ms.unwrap_or(1000) as u64);
+    let Some(sample) = appsink.try_pull_sample(timeout) else {
+      return Ok(None);
+    };
+    let buffer = sample
+      .buffer()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Frame has no data"))?;
+    let map = buffer.map_readable().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to map frame: {}", e),
+      )
+    })?;
+    Ok(Some(Buffer::from(map.as_slice().to_vec())))
+  }
+
+  /// Returns the current state of the pipeline
+  ///
+  /// # Returns
+  /// * `String` - The current state ("Null", "Ready", "Paused" or "Playing")
+  #[napi]
+  pub fn get_state(&self) -> String {
+    let (_, state, _) = self.pipeline.state(gst::ClockTime::ZERO);
+    format!("{:?}", state)
+  }
+
+  /// Starts mixing
+  #[napi]
+  pub fn play(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Playing)
+  }
+
+  /// Pauses mixing
+  #[napi]
+  pub fn pause(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Paused)
+  }
+
+  /// Stops mixing and resets the pipeline to the Null state
+  #[napi]
+  pub fn stop(&self) -> Result<()> {
+    set_pipeline_state(&self.pipeline, gst::State::Null)
+  }
+}
@@ -0,0 +1,730 @@
+//! # Container Dump
+//!
+//! A structural dump of container files for debugging muxing issues, in the
+//! spirit of `mkvinfo`: the EBML element tree of Matroska/WebM files, or the
+//! header and frame table of IVF files, with the offset and size of every
+//! element and the decoded value of the ones that hold numbers or text.
+//!
+//! The file is parsed directly rather than through GStreamer, so broken files
+//! that no demuxer accepts can still be inspected.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+/// Longest payload read to show a string or binary value
+const MAX_VALUE_BYTES: u64 = 256;
+
+/// Options for `dumpContainer`
+#[napi(object)]
+pub struct DumpOptions {
+  /// Deepest nesting level of Matroska elements listed, top-level elements
+  /// being level 1 (default: unlimited)
+  pub max_depth: Option<u32>,
+}
+
+/// An element of a container file
+#[napi(object)]
+pub struct ContainerElement {
+  /// Element ID in hex (e.g. "0x1A45DFA3"); empty for IVF records
+  pub id: String,
+  /// Element name, or "Unknown"
+  pub name: String,
+  /// Offset of the element header in the file
+  pub offset: i64,
+  /// Size of the header (ID and size fields)
+  pub header_size: u32,
+  /// Size of the payload, or -1 if unknown (live-streamed Matroska)
+  pub size: i64,
+  /// Decoded value of number, text and block elements
+  pub value: Option<String>,
+  /// Child elements of master elements
+  pub children: Vec<ContainerElement>,
+}
+
+/// Structure of a container file
+#[napi(object)]
+pub struct ContainerDump {
+  /// "matroska", "webm" or "ivf"
+  pub format: String,
+  /// File size in bytes
+  pub size: i64,
+  /// Top-level elements
+  pub elements: Vec<ContainerElement>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+  Master,
+  Uint,
+  Int,
+  Float,
+  Text,
+  Date,
+  Binary,
+  Block,
+}
+
+/// Known Matroska element IDs, with their name and payload type
+const ELEMENTS: &[(u32, &str, Kind)] = &[
+  (0x1A45DFA3, "EBML", Kind::Master),
+  (0x4286, "EBMLVersion", Kind::Uint),
+  (0x42F7, "EBMLReadVersion", Kind::Uint),
+  (0x42F2, "EBMLMaxIDLength", Kind::Uint),
+  (0x42F3, "EBMLMaxSizeLength", Kind::Uint),
+  (0x4282, "DocType", Kind::Text),
+  (0x4287, "DocTypeVersion", Kind::Uint),
+  (0x4285, "DocTypeReadVersion", Kind::Uint),
+  (0xEC, "Void", Kind::Binary),
+  (0xBF, "CRC-32", Kind::Binary),
+  (0x18538067, "Segment", Kind::Master),
+  (0x114D9B74, "SeekHead", Kind::Master),
+  (0x4DBB, "Seek", Kind::Master),
+  (0x53AB, "SeekID", Kind::Binary),
+  (0x53AC, "SeekPosition", Kind::Uint),
+  (0x1549A966, "Info", Kind::Master),
+  (0x73A4, "SegmentUUID", Kind::Binary),
+  (0x2AD7B1, "TimestampScale", Kind::Uint),
+  (0x4489, "Duration", Kind::Float),
+  (0x4461, "DateUTC", Kind::Date),
+  (0x7BA9, "Title", Kind::Text),
+  (0x4D80, "MuxingApp", Kind::Text),
+  (0x5741, "WritingApp", Kind::Text),
+  (0x1F43B675, "Cluster", Kind::Master),
+  (0xE7, "Timestamp", Kind::Uint),
+  (0xA7, "Position", Kind::Uint),
+  (0xAB, "PrevSize", Kind::Uint),
+  (0xA3, "SimpleBlock", Kind::Block),
+  (0xA0, "BlockGroup", Kind::Master),
+  (0xA1, "Block", Kind::Block),
+  (0x9B, "BlockDuration", Kind::Uint),
+  (0xFB, "ReferenceBlock", Kind::Int),
+  (0x75A2, "DiscardPadding", Kind::Int),
+  (0x1654AE6B, "Tracks", Kind::Master),
+  (0xAE, "TrackEntry", Kind::Master),
+  (0xD7, "TrackNumber", Kind::Uint),
+  (0x73C5, "TrackUID", Kind::Uint),
+  (0x83, "TrackType", Kind::Uint),
+  (0xB9, "FlagEnabled", Kind::Uint),
+  (0x88, "FlagDefault", Kind::Uint),
+  (0x55AA, "FlagForced", Kind::Uint),
+  (0x9C, "FlagLacing", Kind::Uint),
+  (0x23E383, "DefaultDuration", Kind::Uint),
+  (0x536E, "Name", Kind::Text),
+  (0x22B59C, "Language", Kind::Text),
+  (0x86, "CodecID", Kind::Text),
+  (0x63A2, "CodecPrivate", Kind::Binary),
+  (0x258688, "CodecName", Kind::Text),
+  (0x56AA, "CodecDelay", Kind::Uint),
+  (0x56BB, "SeekPreRoll", Kind::Uint),
+  (0xE0, "Video", Kind::Master),
+  (0x9A, "FlagInterlaced", Kind::Uint),
+  (0xB0, "PixelWidth", Kind::Uint),
+  (0xBA, "PixelHeight", Kind::Uint),
+  (0x54B0, "DisplayWidth", Kind::Uint),
+  (0x54BA, "DisplayHeight", Kind::Uint),
+  (0x54B2, "DisplayUnit", Kind::Uint),
+  (0x55B0, "Colour", Kind::Master),
+  (0x7670, "Projection", Kind::Master),
+  (0x7671, "ProjectionType", Kind::Uint),
+  (0x7672, "ProjectionPrivate", Kind::Binary),
+  (0x7673, "ProjectionPoseYaw", Kind::Float),
+  (0x7674, "ProjectionPosePitch", Kind::Float),
+  (0x7675, "ProjectionPoseRoll", Kind::Float),
+  (0xE1, "Audio", Kind::Master),
+  (0xB5, "SamplingFrequency", Kind::Float),
+  (0x9F, "Channels", Kind::Uint),
+  (0x6264, "BitDepth", Kind::Uint),
+  (0x6D80, "ContentEncodings", Kind::Master),
+  (0x6240, "ContentEncoding", Kind::Master),
+  (0x1C53BB6B, "Cues", Kind::Master),
+  (0xBB, "CuePoint", Kind::Master),
+  (0xB3, "CueTime", Kind::Uint),
+  (0xB7, "CueTrackPositions", Kind::Master),
+  (0xF7, "CueTrack", Kind::Uint),
+  (0xF1, "CueClusterPosition", Kind::Uint),
+  (0xF0, "CueRelativePosition", Kind::Uint),
+  (0xB2, "CueDuration", Kind::Uint),
+  (0x5378, "CueBlockNumber", Kind::Uint),
+  (0x1043A770, "Chapters", Kind::Master),
+  (0x45B9, "EditionEntry", Kind::Master),
+  (0xB6, "ChapterAtom", Kind::Master),
+  (0x73C4, "ChapterUID", Kind::Uint),
+  (0x91, "ChapterTimeStart", Kind::Uint),
+  (0x92, "ChapterTimeEnd", Kind::Uint),
+  (0x80, "ChapterDisplay", Kind::Master),
+  (0x85, "ChapString", Kind::Text),
+  (0x437C, "ChapLanguage", Kind::Text),
+  (0x1254C367, "Tags", Kind::Master),
+  (0x7373, "Tag", Kind::Master),
+  (0x63C0, "Targets", Kind::Master),
+  (0x68CA, "TargetTypeValue", Kind::Uint),
+  (0x63C5, "TagTrackUID", Kind::Uint),
+  (0x67C8, "SimpleTag", Kind::Master),
+  (0x45A3, "TagName", Kind::Text),
+  (0x4487, "TagString", Kind::Text),
+  (0x447A, "TagLanguage", Kind::Text),
+  (0x4485, "TagBinary", Kind::Binary),
+  (0x1941A469, "Attachments", Kind::Master),
+  (0x61A7, "AttachedFile", Kind::Master),
+  (0x466E, "FileName", Kind::Text),
+  (0x4660, "FileMimeType", Kind::Text),
+  (0x465C, "FileData", Kind::Binary),
+  (0x46AE, "FileUID", Kind::Uint),
+];
+
+/// Children of the Segment; an unknown-size element ends where one of them starts
+const SEGMENT_CHILDREN: &[u32] = &[
+  0x114D9B74, 0x1549A966, 0x1F43B675, 0x1654AE6B, 0x1C53BB6B, 0x1043A770, 0x1254C367, 0x1941A469,
+];
+
+fn element_info(id: u32) -> (&'static str, Kind) {
+  ELEMENTS
+    .iter()
+    .find(|(known, _, _)| *known == id)
+    .map(|(_, name, kind)| (*name, *kind))
+    .unwrap_or(("Unknown", Kind::Binary))
+}
+
+fn read_error(path: &str, e: std::io::Error) -> Error {
+  Error::new(
+    Status::GenericFailure,
+    format!("Failed to read {}: {}", path, e),
+  )
+}
+
+struct Reader<R> {
+  file: R,
+  len: u64,
+}
+
+impl<R: Read + Seek> Reader<R> {
+  fn read_bytes(&mut self, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    self.file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![0; len.min(self.len.saturating_sub(offset)) as usize];
+    self.file.read_exact(&mut data)?;
+    Ok(data)
+  }
+
+  /// Reads an EBML variable-length integer, returning its value (with the
+  /// length marker kept for IDs), its length and whether all value bits are set
+  fn read_vint(&mut self, offset: u64, keep_marker: bool) -> Option<(u64, u32, bool)> {
+    let first = *self.read_bytes(offset, 1).ok()?.first()?;
+    let len = first.leading_zeros() + 1;
+    if len > 8 {
+      return None;
+    }
+    let bytes = self.read_bytes(offset, len as u64).ok()?;
+    if bytes.len() < len as usize {
+      return None;
+    }
+    let mask = if keep_marker {
+      u64::MAX
+    } else {
+      (1u64 << (7 * len)) - 1
+    };
+    let raw = bytes.iter().fold(0u64, |v, &b| (v << 8) | b as u64);
+    let value = raw & mask;
+    Some((value, len, value == (1u64 << (7 * len)) - 1))
+  }
+}
+
+fn decode_value(
+  reader: &mut Reader<impl Read + Seek>,
+  kind: Kind,
+  offset: u64,
+  size: u64,
+) -> Option<String> {
+  let read = |reader: &mut Reader<_>, len: u64| reader.read_bytes(offset, len).ok();
+  let number = |bytes: &[u8]| bytes.iter().fold(0u64, |v, &b| (v << 8) | b as u64);
+  match kind {
+    Kind::Master => None,
+    Kind::Uint if size <= 8 => Some(number(&read(reader, size)?).to_string()),
+    Kind::Int | Kind::Date if size <= 8 => {
+      let bytes = read(reader, size)?;
+      let shift = 64 - 8 * bytes.len() as u32;
+      let value = if bytes.is_empty() {
+        0
+      } else {
+        ((number(&bytes) << shift) as i64) >> shift
+      };
+      Some(if kind == Kind::Date {
+        // Nanoseconds since 2001-01-01T00:00:00 UTC
+        format!("{} ns after 2001-01-01", value)
+      } else {
+        value.to_string()
+      })
+    }
+    Kind::Float => {
+      let bytes = read(reader, size)?;
+      match bytes.len() {
+        4 => Some(f32::from_be_bytes(bytes.try_into().ok()?).to_string()),
+        8 => Some(f64::from_be_bytes(bytes.try_into().ok()?).to_string()),
+        _ => None,
+      }
+    }
+    Kind::Text => {
+      let bytes = read(reader, size.min(MAX_VALUE_BYTES))?;
+      Some(
+        String::from_utf8_lossy(&bytes)
+          .trim_end_matches('\0')
+          .to_string(),
+      )
+    }
+    Kind::Binary if size <= 16 => Some(
+      read(reader, size)?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect(),
+    ),
+    Kind::Block => {
+      let (track, len, _) = reader.read_vint(offset, false)?;
+      let header = reader.read_bytes(offset + len as u64, 3).ok()?;
+      if header.len() < 3 {
+        return None;
+      }
+      let timecode = i16::from_be_bytes([header[0], header[1]]);
+      let keyframe = if header[2] & 0x80 != 0 {
+        ", keyframe"
+      } else {
+        ""
+      };
+      Some(format!(
+        "track {}, timecode {}{}",
+        track, timecode, keyframe
+      ))
+    }
+    _ => None,
+  }
+}
+
+/// Parses the EBML elements in `start..end`
+fn parse_ebml(
+  reader: &mut Reader<impl Read + Seek>,
+  start: u64,
+  end: u64,
+  depth: u32,
+  max_depth: u32,
+  stop_at_segment_child: bool,
+) -> (Vec<ContainerElement>, u64) {
+  let mut elements = Vec::new();
+  let mut pos = start;
+  while pos < end {
+    let Some((id, id_len, _)) = reader.read_vint(pos, true) else {
+      break;
+    };
+    if stop_at_segment_child && SEGMENT_CHILDREN.contains(&(id as u32)) {
+      break;
+    }
+    let Some((size, size_len, unknown)) = reader.read_vint(pos + id_len as u64, false) else {
+      break;
+    };
+    let (name, kind) = element_info(id as u32);
+    let header_size = id_len + size_len;
+    let data = pos + header_size as u64;
+    let unknown = unknown && kind == Kind::Master;
+
+    let (children, data_end) = if kind == Kind::Master && (unknown || depth < max_depth) {
+      let child_end = if unknown { end } else { (data + size).min(end) };
+      // Only Segment and Cluster are written with unknown sizes in practice;
+      // an unknown-size Cluster ends where the next top-level element starts
+      let (children, parsed_end) = parse_ebml(
+        reader,
+        data,
+        child_end,
+        depth + 1,
+        max_depth,
+        unknown && id as u32 != 0x18538067,
+      );
+      (children, if unknown { parsed_end } else { data + size })
+    } else {
+      (Vec::new(), data + size)
+    };
+
+    if depth <= max_depth {
+      elements.push(ContainerElement {
+        id: format!("0x{:X}", id),
+        name: name.to_string(),
+        offset: pos as i64,
+        header_size,
+        size: if unknown { -1 } else { size as i64 },
+        value: decode_value(reader, kind, data, size),
+        children: if depth < max_depth {
+          children
+        } else {
+          Vec::new()
+        },
+      });
+    }
+    if data_end <= pos {
+      break;
+    }
+    pos = data_end;
+  }
+  (elements, pos)
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> u16 {
+  u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> u32 {
+  u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Lists the IVF file header and frame table
+fn parse_ivf(reader: &mut Reader<impl Read + Seek>, path: &str) -> Result<Vec<ContainerElement>> {
+  let header = reader.read_bytes(0, 32).map_err(|e| read_error(path, e))?;
+  if header.len() < 32 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Truncated IVF header in {}", path),
+    ));
+  }
+  let header_len = le_u16(&header, 6) as u64;
+  let mut elements = vec![ContainerElement {
+    id: String::new(),
+    name: "IVFHeader".to_string(),
+    offset: 0,
+    header_size: 0,
+    size: header_len as i64,
+    value: Some(format!(
+      "version {}, codec {}, {}x{}, time base {}/{}, {} frames",
+      le_u16(&header, 4),
+      String::from_utf8_lossy(&header[8..12]),
+      le_u16(&header, 12),
+      le_u16(&header, 14),
+      le_u32(&header, 20),
+      le_u32(&header, 16),
+      le_u32(&header, 24),
+    )),
+    children: Vec::new(),
+  }];
+
+  let mut pos = header_len;
+  while pos + 12 <= reader.len {
+    let frame = reader
+      .read_bytes(pos, 12)
+      .map_err(|e| read_error(path, e))?;
+    let size = le_u32(&frame, 0) as u64;
+    let pts = u64::from_le_bytes(frame[4..12].try_into().unwrap_or_default());
+    elements.push(ContainerElement {
+      id: String::new(),
+      name: "Frame".to_string(),
+      offset: pos as i64,
+      header_size: 12,
+      size: size as i64,
+      value: Some(format!("pts {}", pts)),
+      children: Vec::new(),
+    });
+    pos += 12 + size;
+  }
+  Ok(elements)
+}
+
+/// Dumps the element structure of a Matroska/WebM or IVF file
+///
+/// # Arguments
+/// * `path` - The file to dump
+/// * `options` - Maximum nesting depth to list
+///
+/// # Returns
+/// * `Result<ContainerDump>` - Format and element tree of the file
+///
+/// # Example
+/// ```javascript
+/// const dump = dumpContainer("broken.webm", { maxDepth: 2 });
+/// const segment = dump.elements.find(e => e.name === "Segment");
+/// console.log(segment.children.map(e => `${e.name} @${e.offset} (${e.size} bytes)`));
+/// ```
+#[napi]
+pub fn dump_container(path: String, options: Option<DumpOptions>) -> Result<ContainerDump> {
+  let max_depth = options
+    .and_then(|options| options.max_depth)
+    .unwrap_or(u32::MAX);
+  let file = File::open(&path).map_err(|e| read_error(&path, e))?;
+  let len = file.metadata().map_err(|e| read_error(&path, e))?.len();
+  let mut reader = Reader {
+    file: BufReader::new(file),
+    len,
+  };
+
+  let magic = reader.read_bytes(0, 4).map_err(|e| read_error(&path, e))?;
+  let (format, elements) = match magic.as_slice() {
+    [0x1A, 0x45, 0xDF, 0xA3] => {
+      // The EBML header names the document type, whatever the depth listed
+      let header_end = reader
+        .read_vint(4, false)
+        .map_or(len, |(size, size_len, _)| 4 + size_len as u64 + size);
+      let (header, _) = parse_ebml(&mut reader, 0, header_end, 1, 2, false);
+      let doc_type = header
+        .first()
+        .and_then(|header| header.children.iter().find(|e| e.name == "DocType"))
+        .and_then(|doc_type| doc_type.value.clone())
+        .unwrap_or_else(|| "matroska".to_string());
+      let (elements, _) = parse_ebml(&mut reader, 0, len, 1, max_depth, false);
+      (doc_type, elements)
+    }
+    b"DKIF" => ("ivf".to_string(), parse_ivf(&mut reader, &path)?),
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("{} is not a Matroska, WebM or IVF file", path),
+      ))
+    }
+  };
+
+  Ok(ContainerDump {
+    format,
+    size: len as i64,
+    elements,
+  })
+}
+
+/// Children of the master element whose payload spans `start..end`, as
+/// (ID, payload offset, payload size); `None` if an element runs past the
+/// data read so far
+fn children(
+  reader: &mut Reader<impl Read + Seek>,
+  start: u64,
+  end: u64,
+) -> Option<Vec<(u32, u64, u64)>> {
+  let mut elements = Vec::new();
+  let mut pos = start;
+  while pos < end {
+    let (id, id_len, _) = reader.read_vint(pos, true)?;
+    let (size, size_len, _) = reader.read_vint(pos + id_len as u64, false)?;
+    let data = pos + (id_len + size_len) as u64;
+    if data + size > reader.len {
+      return None;
+    }
+    elements.push((id as u32, data, size));
+    pos = data + size;
+  }
+  Some(elements)
+}
+
+/// `ProjectionPoseRoll` of every video track of a Matroska/WebM file, in
+/// track order: the counter-clockwise rotation in degrees the frames are
+/// shown with, 0 when not set. `None` if `data` is not Matroska or ends
+/// before the Tracks element does.
+pub(crate) fn matroska_video_rolls(data: impl Read + Seek, len: u64) -> Option<Vec<f64>> {
+  let mut reader = Reader { file: data, len };
+  if reader.read_bytes(0, 4).ok()? != [0x1A, 0x45, 0xDF, 0xA3] {
+    return None;
+  }
+  let (header_size, size_len, _) = reader.read_vint(4, false)?;
+  let segment = 4 + size_len as u64 + header_size;
+  let (id, id_len, _) = reader.read_vint(segment, true)?;
+  let (_, size_len, _) = reader.read_vint(segment + id_len as u64, false)?;
+  if id != 0x18538067 {
+    return None;
+  }
+
+  // Tracks come before the first Cluster; Segment sizes are often unknown,
+  // so its children are walked one at a time
+  let mut pos = segment + (id_len + size_len) as u64;
+  let (tracks, tracks_size) = loop {
+    let (id, id_len, _) = reader.read_vint(pos, true)?;
+    let (size, size_len, _) = reader.read_vint(pos + id_len as u64, false)?;
+    let data = pos + (id_len + size_len) as u64;
+    match id as u32 {
+      0x1654AE6B => break (data, size),
+      0x1F43B675 => return Some(Vec::new()),
+      _ => pos = data + size,
+    }
+  };
+
+  let float = |reader: &mut Reader<_>, offset: u64, size: u64| {
+    let bytes = reader.read_bytes(offset, size).ok()?;
+    match bytes.len() {
+      4 => Some(f32::from_be_bytes(bytes.try_into().ok()?) as f64),
+      8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+      _ => None,
+    }
+  };
+  let mut rolls = Vec::new();
+  for (id, data, size) in children(&mut reader, tracks, tracks + tracks_size)? {
+    if id != 0xAE {
+      continue;
+    }
+    let entry = children(&mut reader, data, data + size)?;
+    let video = entry.iter().find(|(id, _, _)| *id == 0xE0);
+    let is_video = entry.iter().any(|&(id, data, size)| {
+      id == 0x83 && size == 1 && reader.read_bytes(data, 1).ok() == Some(vec![1])
+    });
+    let (Some(&(_, data, size)), true) = (video, is_video) else {
+      continue;
+    };
+    let mut roll = 0.0;
+    for (id, data, size) in children(&mut reader, data, data + size)? {
+      if id == 0x7670 {
+        for (id, data, size) in children(&mut reader, data, data + size)? {
+          if id == 0x7675 {
+            roll = float(&mut reader, data, size).unwrap_or(0.0);
+          }
+        }
+      }
+    }
+    rolls.push(roll);
+  }
+  Some(rolls)
+}
+
+/// Index of a Matroska/WebM file, read from its Info and Cues elements
+pub(crate) struct MatroskaCues {
+  /// Nanoseconds per timestamp unit
+  pub timecode_scale: u64,
+  /// Duration in timestamp units, if set
+  pub duration: Option<f64>,
+  /// File offset of the Segment payload, which cluster positions are
+  /// relative to
+  pub segment_data: u64,
+  /// File offset of the first Cluster, if any
+  pub first_cluster: Option<u64>,
+  /// File offset of the Cues element, if any
+  pub cues_offset: Option<u64>,
+  /// Cue points as (time, track, cluster position, relative position)
+  pub points: Vec<(u64, u64, u64, Option<u64>)>,
+}
+
+/// Reads the cue points of a Matroska/WebM file; `None` if `data` is not
+/// Matroska. The Cues element is found through the SeekHead, or by walking
+/// the top-level elements when the clusters have known sizes.
+pub(crate) fn matroska_cues(data: impl Read + Seek, len: u64) -> Option<MatroskaCues> {
+  let mut reader = Reader { file: data, len };
+  if reader.read_bytes(0, 4).ok()? != [0x1A, 0x45, 0xDF, 0xA3] {
+    return None;
+  }
+  let (header_size, size_len, _) = reader.read_vint(4, false)?;
+  let segment = 4 + size_len as u64 + header_size;
+  let (id, id_len, _) = reader.read_vint(segment, true)?;
+  let (segment_size, size_len, unknown) = reader.read_vint(segment + id_len as u64, false)?;
+  if id != 0x18538067 {
+    return None;
+  }
+  let segment_data = segment + (id_len + size_len) as u64;
+  let segment_end = if unknown {
+    len
+  } else {
+    (segment_data + segment_size).min(len)
+  };
+
+  let uint = |reader: &mut Reader<_>, offset: u64, size: u64| {
+    let bytes = reader.read_bytes(offset, size.min(8)).ok()?;
+    Some(bytes.iter().fold(0u64, |v, &b| (v << 8) | b as u64))
+  };
+  let mut cues = MatroskaCues {
+    timecode_scale: 1_000_000,
+    duration: None,
+    segment_data,
+    first_cluster: None,
+    cues_offset: None,
+    points: Vec::new(),
+  };
+
+  // Walk the top-level elements up to the Cues, or up to the first Cluster
+  // if its size is unknown and the SeekHead has to point the way
+  let mut pos = segment_data;
+  let mut seek_cues = None;
+  while pos < segment_end {
+    let (id, id_len, _) = reader.read_vint(pos, true)?;
+    let (size, size_len, unknown) = reader.read_vint(pos + id_len as u64, false)?;
+    let data = pos + (id_len + size_len) as u64;
+    match id as u32 {
+      0x114D9B74 => {
+        for (id, data, size) in children(&mut reader, data, data + size)? {
+          if id != 0x4DBB {
+            continue;
+          }
+          let entry = children(&mut reader, data, data + size)?;
+          let target = entry
+            .iter()
+            .find(|(id, _, _)| *id == 0x53AB)
+            .and_then(|&(_, data, size)| reader.read_bytes(data, size).ok());
+          let position = entry
+            .iter()
+            .find(|(id, _, _)| *id == 0x53AC)
+            .and_then(|&(_, data, size)| uint(&mut reader, data, size));
+          if target.as_deref() == Some(&[0x1C, 0x53, 0xBB, 0x6B]) {
+            seek_cues = position.map(|position| segment_data + position);
+          }
+        }
+      }
+      0x1549A966 => {
+        for (id, data, size) in children(&mut reader, data, data + size)? {
+          match id {
+            0x2AD7B1 => cues.timecode_scale = uint(&mut reader, data, size)?,
+            0x4489 => {
+              let bytes = reader.read_bytes(data, size).ok()?;
+              cues.duration = match bytes.len() {
+                4 => Some(f32::from_be_bytes(bytes.try_into().ok()?) as f64),
+                8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+                _ => None,
+              };
+            }
+            _ => {}
+          }
+        }
+      }
+      0x1F43B675 => {
+        cues.first_cluster.get_or_insert(pos);
+        if unknown || seek_cues.is_some() {
+          break;
+        }
+      }
+      0x1C53BB6B => {
+        seek_cues = Some(pos);
+        break;
+      }
+      _ => {}
+    }
+    if unknown {
+      break;
+    }
+    pos = data + size;
+  }
+
+  let Some(offset) = seek_cues else {
+    return Some(cues);
+  };
+  let (id, id_len, _) = reader.read_vint(offset, true)?;
+  let (size, size_len, _) = reader.read_vint(offset + id_len as u64, false)?;
+  if id != 0x1C53BB6B {
+    return Some(cues);
+  }
+  cues.cues_offset = Some(offset);
+  let data = offset + (id_len + size_len) as u64;
+  for (id, data, size) in children(&mut reader, data, data + size)? {
+    if id != 0xBB {
+      continue;
+    }
+    let point = children(&mut reader, data, data + size)?;
+    let Some(time) = point
+      .iter()
+      .find(|(id, _, _)| *id == 0xB3)
+      .and_then(|&(_, data, size)| uint(&mut reader, data, size))
+    else {
+      continue;
+    };
+    for &(id, data, size) in &point {
+      if id != 0xB7 {
+        continue;
+      }
+      let (mut track, mut cluster, mut relative) = (None, None, None);
+      for (id, data, size) in children(&mut reader, data, data + size)? {
+        match id {
+          0xF7 => track = uint(&mut reader, data, size),
+          0xF1 => cluster = uint(&mut reader, data, size),
+          0xF0 => relative = uint(&mut reader, data, size),
+          _ => {}
+        }
+      }
+      if let (Some(track), Some(cluster)) = (track, cluster) {
+        cues.points.push((time, track, cluster, relative));
+      }
+    }
+  }
+  Some(cues)
+}
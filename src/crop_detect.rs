@@ -0,0 +1,214 @@
+//! # Crop Detection
+//!
+//! Finds black bars (letterboxing, pillarboxing or both) burned into a
+//! video. Frames sampled over the whole input are scanned from each edge for
+//! rows and columns whose mean luma stays at black level; the picture is the
+//! union of what remains in every sampled frame, so a dark scene or a fade to
+//! black does not cut into the picture of the others. The suggested
+//! rectangle can be passed to the `crop` video filter as is.
+
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+
+/// Options for `detectCropRegion`
+#[napi(object)]
+pub struct CropDetectOptions {
+  /// Number of frames to scan, spread over the whole input (default: 20)
+  pub samples: Option<u32>,
+  /// Highest mean luma, from 0 to 255, of a row or column that is part of a
+  /// bar (default: 24, black being 16 in limited range video)
+  pub threshold: Option<u32>,
+  /// Round the size of the rectangle down to a multiple of this (default: 2)
+  pub round: Option<u32>,
+}
+
+/// Suggested crop of a video
+#[napi(object)]
+pub struct CropRegion {
+  /// Left edge of the picture
+  pub x: u32,
+  /// Top edge of the picture
+  pub y: u32,
+  /// Width of the picture
+  pub width: u32,
+  /// Height of the picture
+  pub height: u32,
+  /// Width of the frames
+  pub source_width: u32,
+  /// Height of the frames
+  pub source_height: u32,
+  /// Whether there are bars to remove
+  pub cropped: bool,
+  /// The rectangle as a `crop` video filter, e.g. "crop=0,140,1920,800"
+  pub filter: String,
+}
+
+/// Bounds `[left, top, right, bottom)` of the picture in a luma plane, or
+/// `None` if the whole frame is black
+fn picture_bounds(
+  luma: &[u8],
+  width: usize,
+  height: usize,
+  stride: usize,
+  threshold: u32,
+) -> Option<[usize; 4]> {
+  let row_bright = |y: usize| {
+    let sum: u32 = luma[y * stride..y * stride + width]
+      .iter()
+      .map(|&v| v as u32)
+      .sum();
+    sum > threshold * width as u32
+  };
+  let top = (0..height).find(|&y| row_bright(y))?;
+  let bottom = (0..height).rfind(|&y| row_bright(y))? + 1;
+  let column_bright = |x: usize| {
+    let sum: u32 = (top..bottom).map(|y| luma[y * stride + x] as u32).sum();
+    sum > threshold * (bottom - top) as u32
+  };
+  let left = (0..width).find(|&x| column_bright(x))?;
+  let right = (0..width).rfind(|&x| column_bright(x))? + 1;
+  Some([left, top, right, bottom])
+}
+
+/// Fits `[start, end)` of a `size` long axis to a length that is a multiple
+/// of `round` and an even start, staying centered on the original range
+fn fit_axis(start: usize, end: usize, size: usize, round: usize) -> (usize, usize) {
+  let length = ((end - start) / round * round).max(round.min(size));
+  let center = (start + end) / 2;
+  let start = center.saturating_sub(length / 2).min(size - length) & !1;
+  (start, length)
+}
+
+/// Suggests a crop rectangle removing the black bars of a video
+///
+/// # Arguments
+/// * `input` - Path or URI of the video
+/// * `options` - Number of sampled frames, black level and rounding
+///
+/// # Returns
+/// * `Result<CropRegion>` - The rectangle of the picture, the full frame if
+///   there are no bars
+///
+/// # Example
+/// ```javascript
+/// const region = detectCropRegion("movie.mkv", { samples: 40 });
+/// if (region.cropped) {
+///   new TranscodeJob("movie.mkv", "movie-cropped.mkv", { container: "mkv", videoFilters: [region.filter] }).start();
+/// }
+/// ```
+#[napi]
+pub fn detect_crop_region(input: String, options: Option<CropDetectOptions>) -> Result<CropRegion> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(CropDetectOptions {
+    samples: None,
+    threshold: None,
+    round: None,
+  });
+  let samples = options.samples.unwrap_or(20).max(1) as u64;
+  let threshold = options.threshold.unwrap_or(24).min(255);
+  let round = options.round.unwrap_or(2).max(1) as usize;
+
+  let info = probe_with_gstreamer(input.clone(), None)?;
+  let video = info
+    .video
+    .first()
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("{} has no video stream", input)))?;
+  // Scan every `step`-th frame so the samples cover the whole input
+  let frames = (info.duration.max(0) as f64 / 1e9 * video.frame_rate).round() as u64;
+  let step = (frames / samples).max(1);
+
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! videoconvert ! \
+     video/x-raw,format=GRAY8 ! appsink name=sink sync=false",
+    to_uri(&input)?
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Analysis sink not found"))?;
+
+  // Frames decoded, frames scanned, frame size and union of the pictures
+  type Scan = (u64, u64, Option<(usize, usize)>, Option<[usize; 4]>);
+  let scan: Arc<Mutex<Scan>> = Arc::new(Mutex::new((0, 0, None, None)));
+  let scan_clone = scan.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let mut guard = scan_clone.lock().unwrap();
+        let (frame, scanned, size, union) = &mut *guard;
+        let current = *frame;
+        *frame += 1;
+        if *scanned >= samples || !current.is_multiple_of(step) {
+          return Ok(gst::FlowSuccess::Ok);
+        }
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+        let video = gst_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        let (w, h) = (video.width() as usize, video.height() as usize);
+        *scanned += 1;
+        *size = Some((w, h));
+        if let Some(bounds) = picture_bounds(&map, w, h, video.stride()[0] as usize, threshold) {
+          *union = Some(match *union {
+            Some([l, t, r, b]) => [
+              l.min(bounds[0]),
+              t.min(bounds[1]),
+              r.max(bounds[2]),
+              b.max(bounds[3]),
+            ],
+            None => bounds,
+          });
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let (_, _, size, union) = *scan.lock().unwrap();
+  let (width, height) = size.ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!("No frames could be decoded from {}", input),
+    )
+  })?;
+  // An input that is black throughout has nothing to crop to
+  let [left, top, right, bottom] = union.unwrap_or([0, 0, width, height]);
+  let (x, crop_width) = fit_axis(left, right, width, round);
+  let (y, crop_height) = fit_axis(top, bottom, height, round);
+  Ok(CropRegion {
+    x: x as u32,
+    y: y as u32,
+    width: crop_width as u32,
+    height: crop_height as u32,
+    source_width: width as u32,
+    source_height: height as u32,
+    cropped: crop_width < width || crop_height < height,
+    filter: format!("crop={},{},{},{}", x, y, crop_width, crop_height),
+  })
+}
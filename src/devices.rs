@@ -0,0 +1,539 @@
+//! # Capture Devices
+//!
+//! Cameras and microphones as GStreamer's device monitor sees them, with
+//! plug and unplug events, and the operating system's permission to capture
+//! from them. Without the permission capture sources fail with errors that
+//! rarely say why, so failures to start a pipeline with a capture source
+//! name a denied permission, and apps can check it first and tell the user
+//! where to grant it.
+//!
+//! Permissions are read from AVFoundation on macOS and from the privacy
+//! consent store of the registry on Windows. On other systems, where access
+//! is governed by device file permissions, it is reported from whether the
+//! device nodes can be opened.
+
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Device properties holding the system path or identifier of a device
+const PATH_PROPERTIES: &[&str] = &[
+  "device.path",
+  "api.v4l2.path",
+  "device.strid",
+  "device.unique-id",
+];
+
+/// Capture sources that need a camera or microphone permission, by kind
+const CAPTURE_SOURCES: &[(&str, &str)] = &[
+  ("v4l2src", "video"),
+  ("avfvideosrc", "video"),
+  ("mfvideosrc", "video"),
+  ("ksvideosrc", "video"),
+  ("autovideosrc", "video"),
+  ("pulsesrc", "audio"),
+  ("pipewiresrc", "audio"),
+  ("alsasrc", "audio"),
+  ("osxaudiosrc", "audio"),
+  ("wasapisrc", "audio"),
+  ("wasapi2src", "audio"),
+  ("autoaudiosrc", "audio"),
+];
+
+/// A camera or microphone
+#[napi(object)]
+#[derive(Clone)]
+pub struct DeviceInfo {
+  /// Name of the device for display
+  pub name: String,
+  /// GStreamer device class, e.g. "Video/Source" or "Audio/Source"
+  pub device_class: String,
+  /// "video" or "audio"
+  pub kind: String,
+  /// System API providing the device, e.g. "v4l2", "avf", "wasapi2"
+  pub api: Option<String>,
+  /// Device path or identifier, e.g. "/dev/video0"
+  pub path: Option<String>,
+  /// Caps the device can produce
+  pub caps: Option<String>,
+}
+
+/// A device or permission change reported by a `DeviceWatcher`
+#[napi(object)]
+#[derive(Clone)]
+pub struct DeviceEvent {
+  /// "added", "removed" or "permission"
+  pub event_type: String,
+  /// The device, for device events
+  pub device: Option<DeviceInfo>,
+  /// "video" or "audio"
+  pub kind: String,
+  /// The new permission, for "permission" events (see `capturePermission`)
+  pub permission: Option<String>,
+}
+
+/// Options for `watchDevices`
+#[napi(object)]
+pub struct DeviceWatchOptions {
+  /// "video", "audio" or both if not set
+  pub kind: Option<String>,
+  /// Interval at which permissions are checked for changes in milliseconds
+  /// (default: 2000, 0 to not check)
+  pub permission_poll_ms: Option<u32>,
+}
+
+/// Callback receiving device events
+type DeviceCallback = ThreadsafeFunction<DeviceEvent, (), DeviceEvent, Status, false, true>;
+
+/// Validates a device kind; `None` stands for both
+fn kinds(kind: Option<&str>) -> Result<&'static [&'static str]> {
+  match kind {
+    None => Ok(&["video", "audio"]),
+    Some("video") => Ok(&["video"]),
+    Some("audio") => Ok(&["audio"]),
+    Some(kind) => Err(Error::new(
+      Status::InvalidArg,
+      format!("Unknown device kind: {} (use \"video\" or \"audio\")", kind),
+    )),
+  }
+}
+
+fn device_info(device: &gst::Device) -> DeviceInfo {
+  let device_class = device.device_class().to_string();
+  let properties = device.properties();
+  let property = |name: &str| properties.as_ref().and_then(|s| s.get::<String>(name).ok());
+  DeviceInfo {
+    name: device.display_name().to_string(),
+    kind: if device_class.contains("Video") {
+      "video"
+    } else {
+      "audio"
+    }
+    .to_string(),
+    device_class,
+    api: property("device.api"),
+    path: PATH_PROPERTIES.iter().find_map(|name| property(name)),
+    caps: device.caps().map(|caps| caps.to_string()),
+  }
+}
+
+/// A device monitor for capture devices of `kinds`
+fn monitor(kinds: &[&str]) -> Result<gst::DeviceMonitor> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+  let monitor = gst::DeviceMonitor::new();
+  for kind in kinds {
+    let class = if *kind == "video" {
+      "Video/Source"
+    } else {
+      "Audio/Source"
+    };
+    monitor.add_filter(Some(class), None);
+  }
+  Ok(monitor)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+  use std::ffi::{c_char, c_void};
+  use std::sync::OnceLock;
+
+  type GetClassFn = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+  type SelectorFn = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+  type StatusFn = unsafe extern "C" fn(*mut c_void, *mut c_void, *mut c_void) -> isize;
+
+  /// The Objective-C runtime and AVFoundation, loaded once
+  fn libraries() -> Option<&'static (libloading::Library, libloading::Library)> {
+    static LIBRARIES: OnceLock<Option<(libloading::Library, libloading::Library)>> =
+      OnceLock::new();
+    LIBRARIES
+      .get_or_init(|| unsafe {
+        Some((
+          libloading::Library::new("/usr/lib/libobjc.A.dylib").ok()?,
+          libloading::Library::new(
+            "/System/Library/Frameworks/AVFoundation.framework/AVFoundation",
+          )
+          .ok()?,
+        ))
+      })
+      .as_ref()
+  }
+
+  /// `[AVCaptureDevice authorizationStatusForMediaType:]`
+  pub(super) fn permission(kind: &str) -> &'static str {
+    let status = libraries().and_then(|(objc, avfoundation)| unsafe {
+      let get_class = objc.get::<GetClassFn>(b"objc_getClass\0").ok()?;
+      let selector = objc.get::<SelectorFn>(b"sel_registerName\0").ok()?;
+      let send = objc.get::<StatusFn>(b"objc_msgSend\0").ok()?;
+      let media_type = avfoundation
+        .get::<*mut *mut c_void>(if kind == "video" {
+          b"AVMediaTypeVideo\0"
+        } else {
+          b"AVMediaTypeAudio\0"
+        })
+        .ok()?;
+      let class = get_class(c"AVCaptureDevice".as_ptr());
+      if class.is_null() {
+        return None;
+      }
+      Some(send(
+        class,
+        selector(c"authorizationStatusForMediaType:".as_ptr()),
+        **media_type,
+      ))
+    });
+    match status {
+      Some(0) => "not-determined",
+      Some(1) => "restricted",
+      Some(2) => "denied",
+      Some(3) => "granted",
+      _ => "unknown",
+    }
+  }
+
+  pub(super) const SETTINGS: &str = "System Settings > Privacy & Security";
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+  use std::ffi::c_void;
+  use std::sync::OnceLock;
+
+  type RegGetValueFn = unsafe extern "system" fn(
+    isize,
+    *const u16,
+    *const u16,
+    u32,
+    *mut u32,
+    *mut c_void,
+    *mut u32,
+  ) -> i32;
+
+  const HKEY_CURRENT_USER: isize = 0x8000_0001u32 as i32 as isize;
+  const HKEY_LOCAL_MACHINE: isize = 0x8000_0002u32 as i32 as isize;
+  const RRF_RT_REG_SZ: u32 = 0x2;
+
+  fn advapi32() -> Option<&'static libloading::Library> {
+    static ADVAPI32: OnceLock<Option<libloading::Library>> = OnceLock::new();
+    ADVAPI32
+      .get_or_init(|| unsafe { libloading::Library::new("advapi32.dll").ok() })
+      .as_ref()
+  }
+
+  fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+  }
+
+  /// Reads the "Value" string of a consent store key
+  fn consent(root: isize, key: &str) -> Option<String> {
+    let get = unsafe { advapi32()?.get::<RegGetValueFn>(b"RegGetValueW\0").ok()? };
+    let (key, value) = (wide(key), wide("Value"));
+    let mut data = [0u16; 32];
+    let mut size = std::mem::size_of_val(&data) as u32;
+    let status = unsafe {
+      get(
+        root,
+        key.as_ptr(),
+        value.as_ptr(),
+        RRF_RT_REG_SZ,
+        std::ptr::null_mut(),
+        data.as_mut_ptr() as *mut c_void,
+        &mut size,
+      )
+    };
+    if status != 0 {
+      return None;
+    }
+    let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+    Some(String::from_utf16_lossy(&data[..len]))
+  }
+
+  /// The camera or microphone setting of the privacy consent store; desktop
+  /// apps are governed by the "NonPackaged" subkey on top of the global one
+  pub(super) fn permission(kind: &str) -> &'static str {
+    let key = format!(
+      "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\{}",
+      if kind == "video" {
+        "webcam"
+      } else {
+        "microphone"
+      }
+    );
+    if consent(HKEY_LOCAL_MACHINE, &key).as_deref() == Some("Deny") {
+      return "restricted";
+    }
+    let user = consent(HKEY_CURRENT_USER, &key);
+    let desktop = consent(HKEY_CURRENT_USER, &format!("{}\\NonPackaged", key));
+    match (user.as_deref(), desktop.as_deref()) {
+      (Some("Deny"), _) | (_, Some("Deny")) => "denied",
+      (Some("Allow"), _) => "granted",
+      _ => "unknown",
+    }
+  }
+
+  pub(super) const SETTINGS: &str = "Settings > Privacy & security";
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+  use std::io::ErrorKind;
+
+  /// Whether the device nodes of the kind can be opened
+  pub(super) fn permission(kind: &str) -> &'static str {
+    let (dir, prefix) = if kind == "video" {
+      ("/dev", "video")
+    } else {
+      ("/dev/snd", "pcmC")
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+      return "unknown";
+    };
+    let mut denied = false;
+    for entry in entries.flatten() {
+      if !entry.file_name().to_string_lossy().starts_with(prefix) {
+        continue;
+      }
+      match std::fs::File::open(entry.path()) {
+        Ok(_) => return "granted",
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => denied = true,
+        Err(_) => {}
+      }
+    }
+    if denied {
+      "denied"
+    } else {
+      "unknown"
+    }
+  }
+
+  pub(super) const SETTINGS: &str =
+    "the permissions of the device files (e.g. the video and audio groups)";
+}
+
+/// Explains a capture failure of `pipeline` when it has a capture source
+/// whose permission is denied, as "; camera access is denied ..."
+pub(crate) fn permission_hint(pipeline: &gst::Pipeline) -> String {
+  let mut kinds: Vec<&str> = pipeline
+    .iterate_recurse()
+    .into_iter()
+    .flatten()
+    .filter_map(|element| {
+      let factory = element.factory()?;
+      CAPTURE_SOURCES
+        .iter()
+        .find(|(name, _)| *name == factory.name())
+        .map(|&(_, kind)| kind)
+    })
+    .collect();
+  kinds.sort_unstable();
+  kinds.dedup();
+  kinds
+    .into_iter()
+    .filter(|kind| matches!(platform::permission(kind), "denied" | "restricted"))
+    .map(|kind| {
+      format!(
+        "; {} access is denied, grant it in {}",
+        if kind == "video" {
+          "camera"
+        } else {
+          "microphone"
+        },
+        platform::SETTINGS
+      )
+    })
+    .collect()
+}
+
+/// Lists the cameras and microphones currently available
+///
+/// # Arguments
+/// * `kind` - "video" or "audio" (default: both)
+///
+/// # Returns
+/// * `Result<Vec<DeviceInfo>>` - The devices
+///
+/// # Example
+/// ```javascript
+/// for (const camera of listCaptureDevices("video")) console.log(camera.name, camera.path);
+/// ```
+#[napi]
+pub fn list_capture_devices(kind: Option<String>) -> Result<Vec<DeviceInfo>> {
+  let monitor = monitor(kinds(kind.as_deref())?)?;
+  monitor.start().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to start the device monitor: {}", e),
+    )
+  })?;
+  let devices = monitor.devices().iter().map(device_info).collect();
+  monitor.stop();
+  Ok(devices)
+}
+
+/// Checks whether the application may capture from cameras or microphones
+///
+/// # Arguments
+/// * `kind` - "video" for cameras or "audio" for microphones
+///
+/// # Returns
+/// * `Result<String>` - "granted", "denied", "restricted" (by a system
+///   policy), "not-determined" (macOS, the user has not been asked yet) or
+///   "unknown"
+///
+/// # Example
+/// ```javascript
+/// if (capturePermission("video") === "denied") showHelp("Allow camera access in your privacy settings");
+/// ```
+#[napi]
+pub fn capture_permission(kind: String) -> Result<String> {
+  kinds(Some(&kind))?;
+  Ok(platform::permission(&kind).to_string())
+}
+
+/// Device plug and permission events, created by `watchDevices`
+#[napi]
+pub struct DeviceWatcher {
+  monitor: gst::DeviceMonitor,
+  callback: Arc<Mutex<Option<DeviceCallback>>>,
+  running: Arc<AtomicBool>,
+}
+
+#[napi]
+impl DeviceWatcher {
+  /// Sets the callback receiving device and permission events
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every event
+  ///
+  /// # Example
+  /// ```javascript
+  /// watcher.onEvent((event) => {
+  ///   if (event.eventType === "removed") console.log(`${event.device.name} was unplugged`);
+  /// });
+  /// ```
+  #[napi]
+  pub fn on_event(&self, callback: DeviceCallback) {
+    *self.callback.lock().unwrap() = Some(callback);
+  }
+
+  /// The devices currently available
+  #[napi]
+  pub fn devices(&self) -> Vec<DeviceInfo> {
+    self.monitor.devices().iter().map(device_info).collect()
+  }
+
+  /// Stops watching
+  #[napi]
+  pub fn stop(&self) {
+    if self.running.swap(false, Ordering::SeqCst) {
+      self.monitor.stop();
+      self.monitor.bus().unset_sync_handler();
+    }
+  }
+}
+
+impl Drop for DeviceWatcher {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Watches cameras and microphones being plugged in or out, and the
+/// permission to capture from them changing
+///
+/// # Arguments
+/// * `options` - Kind of devices and permission check interval
+///
+/// # Returns
+/// * `Result<DeviceWatcher>` - The running watcher
+///
+/// # Example
+/// ```javascript
+/// const watcher = watchDevices({ kind: "video" });
+/// watcher.onEvent((event) => console.log(event.eventType, event.device?.name ?? event.permission));
+/// ```
+#[napi]
+pub fn watch_devices(options: Option<DeviceWatchOptions>) -> Result<DeviceWatcher> {
+  let options = options.unwrap_or(DeviceWatchOptions {
+    kind: None,
+    permission_poll_ms: None,
+  });
+  let kinds = kinds(options.kind.as_deref())?;
+  let monitor = monitor(kinds)?;
+  let callback: Arc<Mutex<Option<DeviceCallback>>> = Arc::new(Mutex::new(None));
+
+  let handler_callback = callback.clone();
+  monitor.bus().set_sync_handler(move |_, msg| {
+    let (event_type, device) = match msg.view() {
+      gst::MessageView::DeviceAdded(added) => ("added", added.device()),
+      gst::MessageView::DeviceRemoved(removed) => ("removed", removed.device()),
+      _ => return gst::BusSyncReply::Drop,
+    };
+    if let Some(callback) = &*handler_callback.lock().unwrap() {
+      let device = device_info(&device);
+      callback.call(
+        DeviceEvent {
+          event_type: event_type.to_string(),
+          kind: device.kind.clone(),
+          device: Some(device),
+          permission: None,
+        },
+        ThreadsafeFunctionCallMode::NonBlocking,
+      );
+    }
+    gst::BusSyncReply::Drop
+  });
+  monitor.start().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to start the device monitor: {}", e),
+    )
+  })?;
+
+  let running = Arc::new(AtomicBool::new(true));
+  let poll = options.permission_poll_ms.unwrap_or(2000);
+  if poll > 0 {
+    let (active, poll_callback) = (running.clone(), callback.clone());
+    std::thread::spawn(move || {
+      let mut permissions: Vec<&str> = kinds
+        .iter()
+        .map(|kind| platform::permission(kind))
+        .collect();
+      while active.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(poll as u64));
+        for (kind, previous) in kinds.iter().zip(permissions.iter_mut()) {
+          let permission = platform::permission(kind);
+          if std::mem::replace(previous, permission) == permission {
+            continue;
+          }
+          if let Some(callback) = &*poll_callback.lock().unwrap() {
+            callback.call(
+              DeviceEvent {
+                event_type: "permission".to_string(),
+                device: None,
+                kind: kind.to_string(),
+                permission: Some(permission.to_string()),
+              },
+              ThreadsafeFunctionCallMode::NonBlocking,
+            );
+          }
+        }
+      }
+    });
+  }
+
+  Ok(DeviceWatcher {
+    monitor,
+    callback,
+    running,
+  })
+}
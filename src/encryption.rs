@@ -0,0 +1,153 @@
+//! # Encryption
+//!
+//! AES-128-CTR encryption of the frames written to Matroska/WebM output and
+//! decryption of such files when they are read back, for intermediates that
+//! must not be playable without the key. Frames use the block layout of the
+//! WebM encryption specification (the "cenc" scheme without subsamples): a
+//! signal byte, then for encrypted frames the 8 byte IV and the encrypted
+//! payload, with the counter block made of the IV and a 64 bit block counter.
+//! Every frame gets its own IV, counting up from the initial one.
+//!
+//! Container and codec headers stay in the clear and `matroskamux` writes no
+//! ContentEncryption element, so other players see the tracks but cannot
+//! decode them; read the files back with a `decryptionKey`.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+type Aes128Ctr = ctr::Ctr64BE<aes::Aes128>;
+
+/// Signal byte of an encrypted frame
+const ENCRYPTED: u8 = 0x01;
+
+/// Encryption of the output frames
+#[napi(object)]
+#[derive(Clone)]
+pub struct EncryptionOptions {
+  /// AES-128 key as 32 hexadecimal digits
+  pub key: String,
+  /// IV of the first frame as 16 hexadecimal digits (default: random)
+  pub iv: Option<String>,
+}
+
+/// Parses `N` bytes written as hexadecimal digits
+fn parse_hex<const N: usize>(value: &str, what: &str) -> Result<[u8; N]> {
+  let invalid = || {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid {}: expected {} hexadecimal digits", what, N * 2),
+    )
+  };
+  let value = value.trim();
+  if value.len() != N * 2 || !value.is_ascii() {
+    return Err(invalid());
+  }
+  let mut bytes = [0u8; N];
+  for (i, byte) in bytes.iter_mut().enumerate() {
+    *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+  }
+  Ok(bytes)
+}
+
+/// Parses an AES-128 key
+pub(crate) fn parse_key(key: &str) -> Result<[u8; 16]> {
+  parse_hex(key, "encryption key")
+}
+
+/// Encrypts every frame entering a sink pad of `muxer`; call before the
+/// branches are linked
+pub(crate) fn encrypt_muxer_input(muxer: &gst::Element, options: &EncryptionOptions) -> Result<()> {
+  let key = parse_key(&options.key)?;
+  let first_iv = match &options.iv {
+    Some(iv) => u64::from_be_bytes(parse_hex(iv, "IV")?),
+    // Not from the GLib generator, which deterministic transcodes seed
+    // with a constant
+    None => {
+      let mut iv = [0u8; 8];
+      getrandom::fill(&mut iv).map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to generate an IV: {}", e),
+        )
+      })?;
+      u64::from_be_bytes(iv)
+    }
+  };
+  // One counter for all tracks, so no two frames share an IV
+  let next_iv = Arc::new(AtomicU64::new(first_iv));
+  muxer.connect_pad_added(move |_, pad| {
+    if pad.direction() != gst::PadDirection::Sink {
+      return;
+    }
+    let next_iv = next_iv.clone();
+    pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+      let Some(buffer) = info.buffer_mut() else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let iv = next_iv.fetch_add(1, Ordering::Relaxed).to_be_bytes();
+      let Ok(map) = buffer.map_readable() else {
+        return gst::PadProbeReturn::Drop;
+      };
+      let mut frame = Vec::with_capacity(map.len() + 9);
+      frame.push(ENCRYPTED);
+      frame.extend_from_slice(&iv);
+      frame.extend_from_slice(&map);
+      drop(map);
+      let mut nonce = [0u8; 16];
+      nonce[..8].copy_from_slice(&iv);
+      Aes128Ctr::new(&key.into(), &nonce.into()).apply_keystream(&mut frame[9..]);
+      buffer
+        .make_mut()
+        .replace_all_memory(gst::Memory::from_mut_slice(frame));
+      gst::PadProbeReturn::Ok
+    });
+  });
+  Ok(())
+}
+
+/// Decrypts the frames leaving every Matroska demuxer created inside the
+/// `demuxer` bin (a `decodebin` or `parsebin`), before they are parsed
+pub(crate) fn decrypt_demuxer_output(demuxer: &gst::Element, key: &str) -> Result<()> {
+  let key = parse_key(key)?;
+  let bin = demuxer
+    .dynamic_cast_ref::<gst::Bin>()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Demuxer is not a bin"))?;
+  bin.connect_deep_element_added(move |_, _, element| {
+    if element.factory().map(|f| f.name()).as_deref() != Some("matroskademux") {
+      return;
+    }
+    element.connect_pad_added(move |_, pad| {
+      pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+        let Some(buffer) = info.buffer_mut() else {
+          return gst::PadProbeReturn::Ok;
+        };
+        let Ok(map) = buffer.map_readable() else {
+          return gst::PadProbeReturn::Drop;
+        };
+        let frame = match map.first() {
+          Some(&signal) if signal & ENCRYPTED != 0 && map.len() >= 9 => {
+            let mut nonce = [0u8; 16];
+            nonce[..8].copy_from_slice(&map[1..9]);
+            let mut frame = map[9..].to_vec();
+            Aes128Ctr::new(&key.into(), &nonce.into()).apply_keystream(&mut frame);
+            frame
+          }
+          Some(&signal) if signal & ENCRYPTED == 0 => map[1..].to_vec(),
+          // Not a frame of an encrypted file
+          _ => return gst::PadProbeReturn::Drop,
+        };
+        drop(map);
+        buffer
+          .make_mut()
+          .replace_all_memory(gst::Memory::from_mut_slice(frame));
+        gst::PadProbeReturn::Ok
+      });
+    });
+  });
+  Ok(())
+}
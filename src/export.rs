@@ -0,0 +1,137 @@
+//! # Result Export
+//!
+//! Streams per-frame analysis results to a file while the analysis runs, so
+//! analysing a long input does not need all of its results in memory. Two
+//! formats are written: NDJSON, one JSON object per line with the same keys
+//! as the JavaScript objects, and CSV with a header row, where lists are
+//! joined by spaces.
+
+use napi::{Error, Result, Status};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Clone, Copy, PartialEq)]
+enum ExportFormat {
+  Ndjson,
+  Csv,
+}
+
+/// Writes records to an NDJSON or CSV file as they are produced
+pub(crate) struct ResultWriter {
+  out: BufWriter<File>,
+  path: String,
+  format: ExportFormat,
+  columns: &'static [&'static str],
+  records: u32,
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &Value) -> String {
+  let text = match value {
+    Value::Null => String::new(),
+    Value::String(text) => text.clone(),
+    Value::Array(items) => items
+      .iter()
+      .map(|item| match item {
+        Value::String(text) => text.clone(),
+        item => item.to_string(),
+      })
+      .collect::<Vec<_>>()
+      .join(" "),
+    value => value.to_string(),
+  };
+  if text.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", text.replace('"', "\"\""))
+  } else {
+    text
+  }
+}
+
+impl ResultWriter {
+  /// Creates `path` for records with the given (camelCase) `columns`. The
+  /// format is "ndjson" or "csv"; by default it follows the file extension,
+  /// with NDJSON for anything but `.csv`.
+  pub(crate) fn create(
+    path: &str,
+    format: Option<&str>,
+    columns: &'static [&'static str],
+  ) -> Result<Self> {
+    let format = match format {
+      Some("ndjson") => ExportFormat::Ndjson,
+      Some("csv") => ExportFormat::Csv,
+      Some(format) => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "Unsupported export format: {} (use \"ndjson\" or \"csv\")",
+            format
+          ),
+        ))
+      }
+      None if path.to_lowercase().ends_with(".csv") => ExportFormat::Csv,
+      None => ExportFormat::Ndjson,
+    };
+    let file = File::create(path).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to create {}: {}", path, e),
+      )
+    })?;
+    let mut writer = ResultWriter {
+      out: BufWriter::new(file),
+      path: path.to_string(),
+      format,
+      columns,
+      records: 0,
+    };
+    if format == ExportFormat::Csv {
+      let header = columns.join(",");
+      writer.write_line(&header)?;
+    }
+    Ok(writer)
+  }
+
+  fn write_line(&mut self, line: &str) -> Result<()> {
+    writeln!(self.out, "{}", line).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write {}: {}", self.path, e),
+      )
+    })
+  }
+
+  /// Appends one record
+  pub(crate) fn write(&mut self, record: &impl Serialize) -> Result<()> {
+    let value = serde_json::to_value(record).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to serialize record: {}", e),
+      )
+    })?;
+    let line = match self.format {
+      ExportFormat::Ndjson => value.to_string(),
+      ExportFormat::Csv => self
+        .columns
+        .iter()
+        .map(|column| csv_field(value.get(column).unwrap_or(&Value::Null)))
+        .collect::<Vec<_>>()
+        .join(","),
+    };
+    self.write_line(&line)?;
+    self.records += 1;
+    Ok(())
+  }
+
+  /// Flushes the file and returns the number of records written
+  pub(crate) fn finish(&mut self) -> Result<u32> {
+    self.out.flush().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write {}: {}", self.path, e),
+      )
+    })?;
+    Ok(self.records)
+  }
+}
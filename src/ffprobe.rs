@@ -0,0 +1,396 @@
+//! # ffprobe-Compatible Output
+//!
+//! Describes media in the JSON schema printed by
+//! `ffprobe -of json -show_format -show_streams`, so tooling written against
+//! ffprobe can use GStreamer's discoverer without changes. GStreamer caps are
+//! translated to FFmpeg's codec, pixel format and container names; fields
+//! GStreamer cannot tell are left out, as ffprobe does for unknown values.
+
+use crate::kit::to_uri;
+use crate::probe::{discover_info, discoverer, tag_list};
+use gst::prelude::*;
+use gst_pbutils::prelude::*;
+use gstreamer as gst;
+use gstreamer_pbutils as gst_pbutils;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde_json::{json, Map, Value};
+
+/// FFmpeg's name for the codec described by `caps`
+fn codec_name(caps: &gst::StructureRef) -> Option<String> {
+  let int = |field: &str| caps.get::<i32>(field).ok();
+  Some(
+    match caps.name().as_str() {
+      "video/x-h264" => "h264",
+      "video/x-h265" => "hevc",
+      "video/x-vp8" => "vp8",
+      "video/x-vp9" => "vp9",
+      "video/x-av1" => "av1",
+      "video/x-theora" => "theora",
+      "video/x-prores" => "prores",
+      "video/x-ffv" => "ffv1",
+      "video/x-raw" => "rawvideo",
+      "image/jpeg" => "mjpeg",
+      "image/png" => "png",
+      "video/mpeg" => match int("mpegversion") {
+        Some(1) => "mpeg1video",
+        Some(2) => "mpeg2video",
+        Some(4) => "mpeg4",
+        _ => return None,
+      },
+      "audio/mpeg" => match (int("mpegversion"), int("layer")) {
+        (Some(1), Some(3)) => "mp3",
+        (Some(1), Some(2)) => "mp2",
+        (Some(2) | Some(4), _) => "aac",
+        _ => return None,
+      },
+      "audio/x-opus" => "opus",
+      "audio/x-vorbis" => "vorbis",
+      "audio/x-flac" => "flac",
+      "audio/x-ac3" => "ac3",
+      "audio/x-eac3" => "eac3",
+      "audio/x-alaw" => "pcm_alaw",
+      "audio/x-mulaw" => "pcm_mulaw",
+      "audio/x-raw" => {
+        return caps
+          .get::<String>("format")
+          .ok()
+          .map(|format| format!("pcm_{}", format.to_lowercase()))
+      }
+      "text/x-raw" => "subrip",
+      "application/x-ssa" => "ssa",
+      "application/x-ass" => "ass",
+      "text/vtt" | "application/x-subtitle-vtt" => "webvtt",
+      "subpicture/x-dvd" => "dvd_subtitle",
+      "subpicture/x-pgs" => "hdmv_pgs_subtitle",
+      _ => return None,
+    }
+    .to_string(),
+  )
+}
+
+/// FFmpeg's pixel format for raw caps, or for the chroma format and bit depth
+/// parsers put in compressed caps
+fn pix_fmt(caps: &gst::StructureRef) -> Option<String> {
+  if let Ok(format) = caps.get::<String>("format") {
+    return Some(
+      match format.as_str() {
+        "I420" => "yuv420p",
+        "YV12" => "yuv420p",
+        "NV12" => "nv12",
+        "NV21" => "nv21",
+        "Y42B" => "yuv422p",
+        "Y444" => "yuv444p",
+        "YUY2" => "yuyv422",
+        "UYVY" => "uyvy422",
+        "I420_10LE" => "yuv420p10le",
+        "P010_10LE" => "p010le",
+        "GRAY8" => "gray",
+        "RGB" => "rgb24",
+        "BGR" => "bgr24",
+        "RGBA" => "rgba",
+        "BGRA" => "bgra",
+        "ARGB" => "argb",
+        "ABGR" => "abgr",
+        "RGBx" => "rgb0",
+        "BGRx" => "bgr0",
+        _ => return None,
+      }
+      .to_string(),
+    );
+  }
+  let chroma = match caps.get::<String>("chroma-format").ok()?.as_str() {
+    "4:0:0" => return Some("gray".to_string()),
+    "4:2:0" => "420",
+    "4:2:2" => "422",
+    "4:4:4" => "444",
+    _ => return None,
+  };
+  Some(match caps.get::<u32>("bit-depth-luma").unwrap_or(8) {
+    8 => format!("yuv{}p", chroma),
+    depth => format!("yuv{}p{}le", chroma, depth),
+  })
+}
+
+/// FFmpeg's sample format for raw audio caps
+fn sample_fmt(caps: &gst::StructureRef) -> Option<&'static str> {
+  let planar = caps
+    .get::<String>("layout")
+    .is_ok_and(|l| l == "non-interleaved");
+  let format = caps.get::<String>("format").ok()?;
+  let packed = match format.as_str() {
+    "U8" => "u8",
+    "S16LE" | "S16BE" => "s16",
+    "S32LE" | "S32BE" => "s32",
+    "F32LE" | "F32BE" => "flt",
+    "F64LE" | "F64BE" => "dbl",
+    _ => return None,
+  };
+  Some(match (packed, planar) {
+    ("u8", true) => "u8p",
+    ("s16", true) => "s16p",
+    ("s32", true) => "s32p",
+    ("flt", true) => "fltp",
+    ("dbl", true) => "dblp",
+    (packed, _) => packed,
+  })
+}
+
+/// ffprobe's container names, which list every format sharing a demuxer
+fn format_name(caps: &gst::StructureRef) -> Option<&'static str> {
+  Some(match caps.name().as_str() {
+    "video/quicktime" | "audio/x-m4a" | "application/x-3gp" => "mov,mp4,m4a,3gp,3g2,mj2",
+    "video/x-matroska" | "video/webm" | "audio/webm" => "matroska,webm",
+    "video/x-msvideo" => "avi",
+    "video/mpegts" => "mpegts",
+    "video/mpeg" => "mpeg",
+    "application/ogg" | "audio/ogg" | "video/ogg" => "ogg",
+    "video/x-flv" => "flv",
+    "audio/x-wav" => "wav",
+    "audio/mpeg" => "mp3",
+    "audio/x-flac" => "flac",
+    _ => return None,
+  })
+}
+
+/// Tags renamed to the keys FFmpeg's demuxers use
+fn ffprobe_tags(tags: Option<gst::TagList>) -> Map<String, Value> {
+  tag_list(tags)
+    .into_iter()
+    .map(|tag| {
+      let name = match tag.name.as_str() {
+        "language-code" => "language".to_string(),
+        "datetime" => "creation_time".to_string(),
+        name => name.replace('-', "_"),
+      };
+      (name, Value::String(tag.value))
+    })
+    .collect()
+}
+
+fn seconds(nanoseconds: u64) -> String {
+  format!("{:.6}", nanoseconds as f64 / 1e9)
+}
+
+fn fraction(fraction: gst::Fraction) -> String {
+  format!("{}/{}", fraction.numer(), fraction.denom())
+}
+
+/// Title-cases a GStreamer profile name, e.g. "constrained-baseline"
+fn profile_name(profile: &str) -> String {
+  profile
+    .split('-')
+    .map(|word| {
+      let mut chars = word.chars();
+      chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+        .unwrap_or_default()
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+fn stream_json(index: usize, stream: &gst_pbutils::DiscovererStreamInfo) -> Value {
+  let mut entry = Map::new();
+  entry.insert("index".into(), json!(index));
+  let caps = stream.caps();
+  let structure = caps.as_ref().and_then(|caps| caps.structure(0));
+  if let Some(caps) = &caps {
+    if let Some(name) = structure.and_then(codec_name) {
+      entry.insert("codec_name".into(), json!(name));
+    }
+    entry.insert(
+      "codec_long_name".into(),
+      json!(gst_pbutils::pb_utils_get_codec_description(caps).to_string()),
+    );
+  }
+  if let Some(profile) = structure.and_then(|s| s.get::<String>("profile").ok()) {
+    entry.insert("profile".into(), json!(profile_name(&profile)));
+  }
+
+  if let Some(video) = stream.downcast_ref::<gst_pbutils::DiscovererVideoInfo>() {
+    let (width, height) = (video.width(), video.height());
+    let par = video.par();
+    entry.insert("codec_type".into(), json!("video"));
+    entry.insert("width".into(), json!(width));
+    entry.insert("height".into(), json!(height));
+    entry.insert("coded_width".into(), json!(width));
+    entry.insert("coded_height".into(), json!(height));
+    if let Some(format) = structure.and_then(pix_fmt) {
+      entry.insert("pix_fmt".into(), json!(format));
+    }
+    if par.numer() > 0 && par.denom() > 0 {
+      entry.insert(
+        "sample_aspect_ratio".into(),
+        json!(format!("{}:{}", par.numer(), par.denom())),
+      );
+      let (w, h) = (
+        width as u64 * par.numer() as u64,
+        height as u64 * par.denom() as u64,
+      );
+      let divisor = gcd(w, h).max(1);
+      entry.insert(
+        "display_aspect_ratio".into(),
+        json!(format!("{}:{}", w / divisor, h / divisor)),
+      );
+    }
+    entry.insert(
+      "field_order".into(),
+      json!(if video.is_interlaced() {
+        "tt"
+      } else {
+        "progressive"
+      }),
+    );
+    let rate = video.framerate();
+    let rate = if rate.denom() > 0 {
+      fraction(rate)
+    } else {
+      "0/0".to_string()
+    };
+    entry.insert("r_frame_rate".into(), json!(rate));
+    entry.insert("avg_frame_rate".into(), json!(rate));
+    if video.bitrate() > 0 {
+      entry.insert("bit_rate".into(), json!(video.bitrate().to_string()));
+    }
+    entry.insert(
+      "bits_per_raw_sample".into(),
+      json!(video.depth().to_string()),
+    );
+  } else if let Some(audio) = stream.downcast_ref::<gst_pbutils::DiscovererAudioInfo>() {
+    entry.insert("codec_type".into(), json!("audio"));
+    if let Some(format) = structure.and_then(sample_fmt) {
+      entry.insert("sample_fmt".into(), json!(format));
+    }
+    entry.insert("sample_rate".into(), json!(audio.sample_rate().to_string()));
+    entry.insert("channels".into(), json!(audio.channels()));
+    let layout = match audio.channels() {
+      1 => Some("mono"),
+      2 => Some("stereo"),
+      6 => Some("5.1"),
+      8 => Some("7.1"),
+      _ => None,
+    };
+    if let Some(layout) = layout {
+      entry.insert("channel_layout".into(), json!(layout));
+    }
+    entry.insert("bits_per_sample".into(), json!(audio.depth()));
+    if audio.bitrate() > 0 {
+      entry.insert("bit_rate".into(), json!(audio.bitrate().to_string()));
+    }
+  } else if stream.is::<gst_pbutils::DiscovererSubtitleInfo>() {
+    entry.insert("codec_type".into(), json!("subtitle"));
+  } else {
+    entry.insert("codec_type".into(), json!("data"));
+  }
+
+  entry.insert("time_base".into(), json!("1/1000000000"));
+  let tags = ffprobe_tags(stream.tags());
+  if !tags.is_empty() {
+    entry.insert("tags".into(), Value::Object(tags));
+  }
+  Value::Object(entry)
+}
+
+/// Probes a media file or stream and describes it like ffprobe does
+///
+/// The JSON matches the schema of
+/// `ffprobe -v quiet -of json -show_format -show_streams <location>`:
+/// a `streams` array with FFmpeg codec names, dimensions, frame rates and
+/// sample formats, and a `format` object with the container name, duration,
+/// size and bitrate. Numbers ffprobe prints as strings (durations, bitrates,
+/// sample rates) are strings here too.
+///
+/// # Arguments
+/// * `location` - A file path or URI (file://, http://, rtsp://, ...)
+/// * `timeout_ms` - Maximum time to spend probing (default: 10000)
+///
+/// # Returns
+/// * `Result<String>` - The ffprobe-compatible JSON document
+///
+/// # Example
+/// ```javascript
+/// const probe = JSON.parse(probeAsFfprobe("movie.mkv"));
+/// const video = probe.streams.find((s) => s.codec_type === "video");
+/// console.log(probe.format.format_name, video.codec_name, video.r_frame_rate);
+/// ```
+#[napi]
+pub fn probe_as_ffprobe(location: String, timeout_ms: Option<u32>) -> Result<String> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let uri = to_uri(&location)?;
+  let info = discover_info(&discoverer(timeout_ms)?, &location, &uri)?;
+
+  let streams: Vec<Value> = info
+    .stream_list()
+    .iter()
+    .enumerate()
+    .map(|(index, stream)| stream_json(index, stream))
+    .collect();
+
+  let mut format = Map::new();
+  format.insert("filename".into(), json!(location));
+  format.insert("nb_streams".into(), json!(streams.len()));
+  format.insert("nb_programs".into(), json!(0));
+  let container = info
+    .stream_info()
+    .filter(|stream| stream.is::<gst_pbutils::DiscovererContainerInfo>())
+    .and_then(|stream| stream.caps());
+  if let Some(caps) = &container {
+    if let Some(name) = caps.structure(0).and_then(format_name) {
+      format.insert("format_name".into(), json!(name));
+    }
+    format.insert(
+      "format_long_name".into(),
+      json!(gst_pbutils::pb_utils_get_codec_description(caps).to_string()),
+    );
+  }
+  format.insert("start_time".into(), json!(seconds(0)));
+  let duration = info.duration().map(|d| d.nseconds());
+  if let Some(duration) = duration {
+    format.insert("duration".into(), json!(seconds(duration)));
+  }
+  let size = gst::glib::filename_from_uri(&uri)
+    .ok()
+    .and_then(|(path, _)| std::fs::metadata(path).ok())
+    .map(|metadata| metadata.len());
+  if let Some(size) = size {
+    format.insert("size".into(), json!(size.to_string()));
+    if let Some(duration) = duration.filter(|&d| d > 0) {
+      let bit_rate = (size as u128 * 8 * 1_000_000_000 / duration as u128) as u64;
+      format.insert("bit_rate".into(), json!(bit_rate.to_string()));
+    }
+  }
+  format.insert("probe_score".into(), json!(100));
+  let tags = ffprobe_tags(info.tags());
+  if !tags.is_empty() {
+    format.insert("tags".into(), Value::Object(tags));
+  }
+
+  // ffprobe indents its JSON by four spaces
+  let document = json!({ "streams": streams, "format": format });
+  let mut out = Vec::new();
+  let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+  let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+  serde::Serialize::serialize(&document, &mut serializer).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to serialize probe result: {}", e),
+    )
+  })?;
+  String::from_utf8(out).map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+}
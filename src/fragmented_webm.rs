@@ -0,0 +1,527 @@
+//! # Fragmented WebM
+//!
+//! Live-streamable WebM: frames are encoded and muxed with `webmmux` in
+//! streamable mode, and its output is split into an initialization segment
+//! (EBML header, segment header and tracks) and fragments of one cluster
+//! each. Raw frames are encoded with a keyframe forced at the start of
+//! every fragment, so each cluster starts with a keyframe and decodes on
+//! its own; VP8, VP9 and AV1 frames are muxed as they are and fragmented at
+//! their own keyframes. The segments can be appended to a Media Source
+//! Extensions `SourceBuffer` as they come: the initialization segment once,
+//! then the fragments in order, starting with any of them.
+//!
+//! `fragmentFromPipeline` fragments the frames reaching an AppSink, to be
+//! pulled with `initSegment` and `nextFragment`.
+
+use crate::kit::GstKit;
+use crate::presets::preset_properties;
+use crate::transcode::{launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Element ID starting every cluster
+const CLUSTER_ID: [u8; 4] = [0x1F, 0x43, 0xB6, 0x75];
+
+/// Caps names of the codecs muxed without re-encoding
+const ENCODED: &[&str] = &["video/x-vp8", "video/x-vp9", "video/x-av1"];
+
+/// Options for `fragmentFromPipeline`
+#[napi(object)]
+#[derive(Clone)]
+pub struct FragmentOptions {
+  /// Codec raw frames are encoded to: "vp8" (default), "vp9" or "av1".
+  /// Encoded frames are muxed as they are.
+  pub video_codec: Option<String>,
+  /// Target video bitrate in kbit/s
+  pub video_bitrate: Option<u32>,
+  /// Encoder speed preset (see `listPresets`), e.g. "ultrafast" for live input
+  pub preset: Option<String>,
+  /// Encoder tuning (see `listPresets`)
+  pub tune: Option<String>,
+  /// Duration of each fragment of raw frames in milliseconds, i.e. the
+  /// keyframe interval (default: 1000)
+  pub fragment_duration_ms: Option<u32>,
+  /// Fragments kept for `nextFragment` before the oldest are dropped
+  /// (default: 64)
+  pub max_queued: Option<u32>,
+}
+
+/// A media segment of a fragmented WebM stream
+#[napi(object)]
+pub struct WebmFragment {
+  /// Number of the fragment, from 0
+  pub sequence: u32,
+  /// Timestamp of its first frame in nanoseconds, from the first frame of
+  /// the stream
+  pub timestamp: i64,
+  /// The cluster, ready to append after the initialization segment
+  pub data: Buffer,
+}
+
+/// A cluster cut from the muxer output
+pub(crate) struct FragmentData {
+  pub sequence: u32,
+  pub timestamp: u64,
+  pub data: Vec<u8>,
+}
+
+/// Output of a `WebmFragmentWriter`
+pub(crate) enum FragmentOutput {
+  /// The initialization segment, emitted once before the first fragment
+  Init(Vec<u8>),
+  Fragment(FragmentData),
+}
+
+/// Receives the output of a `WebmFragmentWriter`, on a streaming thread
+pub(crate) type FragmentSink = Arc<dyn Fn(FragmentOutput) + Send + Sync>;
+
+/// Cuts the muxer output at cluster starts
+struct Splitter {
+  header: Vec<u8>,
+  header_done: bool,
+  current: Option<FragmentData>,
+  sequence: u32,
+}
+
+impl Splitter {
+  fn push(&mut self, data: &[u8], timestamp: u64, output: &FragmentSink) {
+    if data.starts_with(&CLUSTER_ID) {
+      if !std::mem::replace(&mut self.header_done, true) {
+        output(FragmentOutput::Init(std::mem::take(&mut self.header)));
+      }
+      self.flush(output);
+      self.current = Some(FragmentData {
+        sequence: self.sequence,
+        timestamp,
+        data: data.to_vec(),
+      });
+      self.sequence += 1;
+    } else if !self.header_done {
+      self.header.extend_from_slice(data);
+    } else if let Some(current) = &mut self.current {
+      current.data.extend_from_slice(data);
+    }
+  }
+
+  fn flush(&mut self, output: &FragmentSink) {
+    if let Some(fragment) = self.current.take() {
+      output(FragmentOutput::Fragment(fragment));
+    }
+  }
+}
+
+/// The pipeline encoding and muxing the frames
+struct Muxing {
+  pipeline: gst::Pipeline,
+  appsrc: AppSrc,
+}
+
+/// Encodes and muxes frames into fragmented WebM, started by its first frame
+pub(crate) struct WebmFragmentWriter {
+  /// Encoder and its properties, for raw frames
+  encoder: (String, Option<String>, Vec<(String, String)>),
+  interval: gst::ClockTime,
+  output: FragmentSink,
+  muxing: Option<Muxing>,
+  first_pts: Option<gst::ClockTime>,
+  next_keyframe: Option<gst::ClockTime>,
+  pub(crate) frames: u32,
+  pub(crate) error: Option<String>,
+  stopped: bool,
+}
+
+impl WebmFragmentWriter {
+  /// Validates the options of a fragmented stream writing to `output`
+  pub(crate) fn create(options: &FragmentOptions, output: FragmentSink) -> Result<Self> {
+    let codec = options
+      .video_codec
+      .clone()
+      .unwrap_or_else(|| "vp8".to_string());
+    if !matches!(codec.as_str(), "vp8" | "vp9" | "av1") {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Unsupported fragment codec: {} (use \"vp8\", \"vp9\" or \"av1\")",
+          codec
+        ),
+      ));
+    }
+    let spec = video_codec_spec(&codec)?;
+    let mut properties = match (spec.bitrate_property, options.video_bitrate) {
+      (Some(property), Some(kbps)) => vec![(property, (kbps * spec.bitrate_scale).to_string())],
+      _ => Vec::new(),
+    };
+    properties.extend(
+      preset_properties(
+        &spec.encoder,
+        options.preset.as_deref(),
+        options.tune.as_deref(),
+      )?
+      .into_iter()
+      .map(|(property, value)| (property.to_string(), value)),
+    );
+    let interval = options.fragment_duration_ms.unwrap_or(1000);
+    if interval == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "fragmentDurationMs must be positive".to_string(),
+      ));
+    }
+    Ok(WebmFragmentWriter {
+      encoder: (spec.encoder, spec.parser, properties),
+      interval: gst::ClockTime::from_mseconds(interval as u64),
+      output,
+      muxing: None,
+      first_pts: None,
+      next_keyframe: None,
+      frames: 0,
+      error: None,
+      stopped: false,
+    })
+  }
+
+  /// Builds the muxing pipeline for frames with `caps`
+  fn start(&self, caps: &gst::CapsRef) -> std::result::Result<Muxing, String> {
+    let name = caps
+      .structure(0)
+      .map(|s| s.name().to_string())
+      .unwrap_or_default();
+    let encode = if name == "video/x-raw" {
+      let (encoder, parser, _) = &self.encoder;
+      let parser = parser
+        .as_ref()
+        .map(|parser| format!(" ! {}", parser))
+        .unwrap_or_default();
+      format!("videoconvert ! {} name=enc{} ! ", encoder, parser)
+    } else if ENCODED.contains(&name.as_str()) {
+      String::new()
+    } else {
+      return Err(format!(
+        "Cannot fragment {}: expected raw video or VP8, VP9 or AV1",
+        name
+      ));
+    };
+    let pipeline = launch(&format!(
+      "appsrc name=src format=time ! queue ! {}webmmux name=mux streamable=true ! appsink name=out sync=false",
+      encode
+    ))
+    .map_err(|e| e.reason)?;
+    if let Some(encoder) = pipeline.by_name("enc") {
+      for (property, value) in &self.encoder.2 {
+        if encoder.find_property(property).is_some() {
+          encoder.set_property_from_str(property, value);
+        }
+      }
+    }
+    if let Some(mux) = pipeline.by_name("mux") {
+      // Start a cluster at every keyframe
+      if mux.find_property("min-cluster-duration").is_some() {
+        mux.set_property("min-cluster-duration", 0i64);
+      }
+    }
+    let appsrc = pipeline
+      .by_name("src")
+      .and_then(|el| el.downcast::<AppSrc>().ok())
+      .ok_or("Fragment source not found")?;
+    appsrc.set_caps(Some(&caps.to_owned()));
+    let appsink = pipeline
+      .by_name("out")
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or("Fragment sink not found")?;
+
+    let splitter = Arc::new(Mutex::new(Splitter {
+      header: Vec::new(),
+      header_done: false,
+      current: None,
+      sequence: 0,
+    }));
+    let (samples, eos) = (splitter.clone(), splitter);
+    let (sample_output, eos_output) = (self.output.clone(), self.output.clone());
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+          let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+          let timestamp = buffer.pts().map(|pts| pts.nseconds()).unwrap_or(0);
+          samples
+            .lock()
+            .unwrap()
+            .push(&map, timestamp, &sample_output);
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .eos(move |_| eos.lock().unwrap().flush(&eos_output))
+        .build(),
+    );
+    pipeline
+      .set_state(gst::State::Playing)
+      .map_err(|e| format!("Failed to start fragmenting: {}", e))?;
+    Ok(Muxing { pipeline, appsrc })
+  }
+
+  /// Hands a frame to the muxing pipeline, starting it on the first one
+  pub(crate) fn push(&mut self, sample: &gst::Sample) -> std::result::Result<(), String> {
+    let buffer = sample.buffer().ok_or("Sample has no buffer")?;
+    let caps = sample.caps().ok_or("Sample has no caps")?;
+    if self.muxing.is_none() {
+      self.muxing = Some(self.start(caps)?);
+    }
+    let Some(muxing) = &self.muxing else {
+      return Ok(());
+    };
+    if muxing.appsrc.caps().as_deref() != Some(caps) {
+      muxing.appsrc.set_caps(Some(&caps.to_owned()));
+    }
+
+    let mut buffer = buffer.copy();
+    if let Some(pts) = buffer.pts() {
+      let first = *self.first_pts.get_or_insert(pts);
+      let dts = buffer.dts();
+      let pts = pts.saturating_sub(first);
+      {
+        let buffer = buffer.make_mut();
+        buffer.set_pts(pts);
+        buffer.set_dts(dts.map(|dts| dts.saturating_sub(first)));
+      }
+      if self.next_keyframe.is_none_or(|next| pts >= next) {
+        self.next_keyframe = Some(pts + self.interval);
+        let event = gst_video::DownstreamForceKeyUnitEvent::builder()
+          .timestamp(pts)
+          .stream_time(pts)
+          .running_time(pts)
+          .all_headers(true)
+          .build();
+        muxing.appsrc.send_event(event);
+      }
+    }
+    muxing
+      .appsrc
+      .push_buffer(buffer)
+      .map_err(|e| format!("Fragmenting stopped: {:?}", e))?;
+    self.frames += 1;
+    Ok(())
+  }
+
+  /// Signals that no more frames follow, without waiting for the muxer
+  pub(crate) fn end_of_stream(&self) {
+    if let Some(muxing) = &self.muxing {
+      let _ = muxing.appsrc.end_of_stream();
+    }
+  }
+
+  /// Ends the stream, emitting the last fragment
+  pub(crate) fn finish(&mut self) -> Result<()> {
+    if std::mem::replace(&mut self.stopped, true) {
+      return Ok(());
+    }
+    let Some(muxing) = self.muxing.take() else {
+      return Ok(());
+    };
+    let _ = muxing.appsrc.end_of_stream();
+    let result = wait_for_eos(&muxing.pipeline);
+    let _ = muxing.pipeline.set_state(gst::State::Null);
+    if let Some(error) = self.error.take() {
+      return Err(Error::new(Status::GenericFailure, error));
+    }
+    result
+  }
+}
+
+/// Hands the samples reaching `appsink` to `writer`, ending its stream at
+/// the sink's end of stream
+pub(crate) fn feed_from_sink(appsink: &AppSink, writer: Arc<Mutex<WebmFragmentWriter>>) {
+  let eos = writer.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let mut writer = writer.lock().unwrap();
+        // A failed fragmenter must not stop the pipeline it reads from
+        if writer.error.is_none() {
+          if let Err(reason) = writer.push(&sample) {
+            writer.error = Some(reason);
+          }
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .eos(move |_| eos.lock().unwrap().end_of_stream())
+      .build(),
+  );
+}
+
+/// Segments waiting to be pulled
+#[derive(Default)]
+struct Queue {
+  init: Option<Vec<u8>>,
+  fragments: VecDeque<FragmentData>,
+  dropped: u32,
+  ended: bool,
+}
+
+/// Fragmented WebM of the frames reaching an AppSink, created by
+/// `fragmentFromPipeline`
+#[napi]
+pub struct WebmFragmenter {
+  appsink: AppSink,
+  writer: Arc<Mutex<WebmFragmentWriter>>,
+  queue: Arc<(Mutex<Queue>, Condvar)>,
+}
+
+#[napi]
+impl WebmFragmenter {
+  /// Returns the initialization segment, or null until the first fragment
+  /// is complete
+  ///
+  /// # Example
+  /// ```javascript
+  /// sourceBuffer.appendBuffer(fragmenter.initSegment());
+  /// ```
+  #[napi]
+  pub fn init_segment(&self) -> Option<Buffer> {
+    let queue = self.queue.0.lock().unwrap();
+    queue.init.clone().map(Buffer::from)
+  }
+
+  /// Takes the oldest complete fragment
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Time to wait for a fragment (default: 0, don't wait)
+  ///
+  /// # Returns
+  /// * `Option<WebmFragment>` - The fragment, or null if none is ready
+  ///
+  /// # Example
+  /// ```javascript
+  /// let fragment;
+  /// while ((fragment = fragmenter.nextFragment(2000))) socket.send(fragment.data);
+  /// ```
+  #[napi]
+  pub fn next_fragment(&self, timeout_ms: Option<u32>) -> Option<WebmFragment> {
+    let (queue, ready) = &*self.queue;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(0) as u64);
+    let (mut queue, _) = ready
+      .wait_timeout_while(queue.lock().unwrap(), timeout, |queue| {
+        queue.fragments.is_empty() && !queue.ended
+      })
+      .unwrap();
+    queue.fragments.pop_front().map(|fragment| WebmFragment {
+      sequence: fragment.sequence,
+      timestamp: fragment.timestamp as i64,
+      data: fragment.data.into(),
+    })
+  }
+
+  /// Number of fragments dropped because they were not pulled in time
+  #[napi(getter)]
+  pub fn dropped(&self) -> u32 {
+    self.queue.0.lock().unwrap().dropped
+  }
+
+  /// Number of frames fragmented so far
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.writer.lock().unwrap().frames
+  }
+
+  /// Detaches from the sink and ends the stream; the last fragment can
+  /// still be pulled
+  ///
+  /// # Example
+  /// ```javascript
+  /// fragmenter.stop();
+  /// const last = fragmenter.nextFragment();
+  /// ```
+  #[napi]
+  pub fn stop(&self) -> Result<()> {
+    self
+      .appsink
+      .set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    let result = self.writer.lock().unwrap().finish();
+    let (queue, ready) = &*self.queue;
+    queue.lock().unwrap().ended = true;
+    ready.notify_all();
+    result
+  }
+}
+
+impl Drop for WebmFragmenter {
+  fn drop(&mut self) {
+    let _ = self.stop();
+  }
+}
+
+/// Fragments the frames reaching an AppSink into live-streamable WebM
+///
+/// Raw frames are encoded with `videoCodec`; VP8, VP9 and AV1 frames are
+/// muxed without re-encoding. The sink's callbacks are taken over until the
+/// fragmenter is stopped.
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `sink_name` - The name of the AppSink element
+/// * `options` - Codec, bitrate, preset and fragment duration
+///
+/// # Returns
+/// * `Result<WebmFragmenter>` - The running fragmenter
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+/// const fragmenter = fragmentFromPipeline(kit, "sink", { preset: "ultrafast", fragmentDurationMs: 500 });
+/// kit.play();
+/// const fragment = fragmenter.nextFragment(2000);
+/// ws.send(fragmenter.initSegment());
+/// ws.send(fragment.data);
+/// ```
+#[napi]
+pub fn fragment_from_pipeline(
+  kit: &GstKit,
+  sink_name: String,
+  options: Option<FragmentOptions>,
+) -> Result<WebmFragmenter> {
+  let options = options.unwrap_or(FragmentOptions {
+    video_codec: None,
+    video_bitrate: None,
+    preset: None,
+    tune: None,
+    fragment_duration_ms: None,
+    max_queued: None,
+  });
+  let max_queued = options.max_queued.unwrap_or(64).max(1) as usize;
+  let appsink = kit.app_sink(&sink_name)?;
+
+  let queue = Arc::new((Mutex::new(Queue::default()), Condvar::new()));
+  let output_queue = queue.clone();
+  let output: FragmentSink = Arc::new(move |output| {
+    let (queue, ready) = &*output_queue;
+    let mut queue = queue.lock().unwrap();
+    match output {
+      FragmentOutput::Init(init) => queue.init = Some(init),
+      FragmentOutput::Fragment(fragment) => {
+        if queue.fragments.len() == max_queued {
+          queue.fragments.pop_front();
+          queue.dropped += 1;
+        }
+        queue.fragments.push_back(fragment);
+        ready.notify_all();
+      }
+    }
+  });
+  let writer = Arc::new(Mutex::new(WebmFragmentWriter::create(&options, output)?));
+
+  feed_from_sink(&appsink, writer.clone());
+  Ok(WebmFragmenter {
+    appsink,
+    writer,
+    queue,
+  })
+}
@@ -0,0 +1,337 @@
+//! # Frame Clock
+//!
+//! A timer ticking at the frame boundaries of a frame rate, for producers of
+//! synthetic content (generated graphics, game frames, canvas renders) pushed
+//! into an `appsrc`. Every deadline is computed from the start of the clock
+//! and the exact rational frame rate, so rounding never accumulates into
+//! drift, and the timer sleeps until just before a deadline and then spins,
+//! which lands within microseconds of it rather than within the scheduler's
+//! millisecond. Ticks that could not be delivered on time are skipped by
+//! default, keeping the producer on the wall clock; with `catchUp` they are
+//! delivered late, back to back, keeping every frame.
+//!
+//! The lateness of each tick (its jitter) is recorded, and the frames a
+//! producer makes for a tick are given its timestamp by `pushFrame`.
+
+use crate::kit::GstKit;
+use gstreamer as gst;
+use napi::bindgen_prelude::Buffer;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Default time before a deadline at which sleeping gives way to spinning
+const DEFAULT_SPIN_US: u32 = 1000;
+
+/// Longest single sleep, so `stop` is noticed at low frame rates
+const MAX_SLEEP: Duration = Duration::from_millis(50);
+
+/// Options for the `FrameClock` constructor
+#[napi(object)]
+pub struct FrameClockOptions {
+  /// Frames per second; 29.97 and 59.94 are taken as 30000/1001 and 60000/1001
+  pub fps: f64,
+  /// Deliver ticks that were missed late instead of skipping them (default:
+  /// false)
+  pub catch_up: Option<bool>,
+  /// Microseconds before a deadline at which the clock stops sleeping and
+  /// spins (default: 1000); 0 saves CPU at the cost of precision
+  pub spin_us: Option<u32>,
+}
+
+/// A frame boundary
+#[napi(object)]
+#[derive(Clone)]
+pub struct FrameTick {
+  /// Number of the frame since the clock started, from 0
+  pub index: u32,
+  /// Timestamp of the frame in nanoseconds since the clock started
+  pub timestamp: i64,
+  /// Duration of the frame in nanoseconds
+  pub duration: i64,
+  /// How late the tick fired after its deadline, in nanoseconds
+  pub lateness: i64,
+}
+
+/// Timing statistics of a `FrameClock`
+#[napi(object)]
+pub struct FrameClockStats {
+  /// Ticks delivered
+  pub ticks: u32,
+  /// Ticks skipped because the clock fell more than a frame behind
+  pub skipped: u32,
+  /// Average lateness of the ticks, in nanoseconds
+  pub mean_jitter: f64,
+  /// Standard deviation of the lateness, in nanoseconds
+  pub jitter_std_dev: f64,
+  /// Largest lateness, in nanoseconds
+  pub max_jitter: i64,
+}
+
+#[derive(Default)]
+struct Jitter {
+  ticks: u32,
+  skipped: u32,
+  sum: f64,
+  sum_squares: f64,
+  max: i64,
+}
+
+impl Jitter {
+  fn record(&mut self, lateness: i64) {
+    self.ticks += 1;
+    self.sum += lateness as f64;
+    self.sum_squares += (lateness as f64).powi(2);
+    self.max = self.max.max(lateness);
+  }
+
+  fn stats(&self) -> FrameClockStats {
+    let count = self.ticks.max(1) as f64;
+    let mean = self.sum / count;
+    FrameClockStats {
+      ticks: self.ticks,
+      skipped: self.skipped,
+      mean_jitter: mean,
+      jitter_std_dev: (self.sum_squares / count - mean * mean).max(0.0).sqrt(),
+      max_jitter: self.max,
+    }
+  }
+}
+
+/// Timestamp of frame `index` at `num/den` frames per second, in nanoseconds
+fn frame_time(index: u64, num: u64, den: u64) -> u64 {
+  (index as u128 * 1_000_000_000 * den as u128 / num as u128) as u64
+}
+
+/// Frame rate as a fraction, taking NTSC rates such as 29.97 as x000/1001
+pub(crate) fn rational_rate(fps: f64) -> Option<(u64, u64)> {
+  if !fps.is_finite() || fps <= 0.0 {
+    return None;
+  }
+  let ntsc = (fps * 1.001).round();
+  if fps.fract() != 0.0 && (fps * 1.001 - ntsc).abs() < 0.005 {
+    return Some((ntsc as u64 * 1000, 1001));
+  }
+  let rate = gst::Fraction::approximate_f64(fps)?;
+  (rate.numer() > 0 && rate.denom() > 0).then(|| (rate.numer() as u64, rate.denom() as u64))
+}
+
+/// Waits for `deadline`, sleeping until `spin` before it and spinning after;
+/// returns false if the clock was stopped meanwhile
+fn wait_until(deadline: Instant, spin: Duration, running: &AtomicBool) -> bool {
+  loop {
+    if !running.load(Ordering::SeqCst) {
+      return false;
+    }
+    let now = Instant::now();
+    if now >= deadline {
+      return true;
+    }
+    let remaining = deadline - now;
+    if remaining > spin {
+      std::thread::sleep((remaining - spin).min(MAX_SLEEP));
+    } else {
+      std::hint::spin_loop();
+    }
+  }
+}
+
+/// Ticks at the frame boundaries of a frame rate
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("appsrc name=src is-live=true format=time caps=video/x-raw,format=RGBA,width=640,height=360,framerate=60/1 ! videoconvert ! autovideosink");
+/// kit.play();
+/// const clock = new FrameClock({ fps: 60 });
+/// clock.start((tick) => clock.pushFrame(kit, "src", tick.index, render(tick.timestamp)));
+/// ```
+#[napi]
+pub struct FrameClock {
+  num: u64,
+  den: u64,
+  catch_up: bool,
+  spin: Duration,
+  running: Arc<AtomicBool>,
+  jitter: Arc<Mutex<Jitter>>,
+  thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FrameClock {
+  /// Starts ticking, calling `producer` on the clock thread at every frame
+  /// boundary
+  pub(crate) fn start_producer(
+    &self,
+    mut producer: impl FnMut(FrameTick) + Send + 'static,
+  ) -> Result<()> {
+    let mut thread = self.thread.lock().unwrap();
+    if thread.is_some() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Frame clock already started".to_string(),
+      ));
+    }
+    *self.jitter.lock().unwrap() = Jitter::default();
+    self.running.store(true, Ordering::SeqCst);
+
+    let (num, den, catch_up, spin) = (self.num, self.den, self.catch_up, self.spin);
+    let running = self.running.clone();
+    let jitter = self.jitter.clone();
+    *thread = Some(std::thread::spawn(move || {
+      let origin = Instant::now();
+      let mut index = 0u64;
+      loop {
+        let deadline = origin + Duration::from_nanos(frame_time(index, num, den));
+        if !wait_until(deadline, spin, &running) {
+          return;
+        }
+        let elapsed = origin.elapsed().as_nanos() as u64;
+        if !catch_up && elapsed >= frame_time(index + 1, num, den) {
+          // Jump to the frame being shown now
+          let current = (elapsed as u128 * num as u128 / (1_000_000_000 * den as u128)) as u64;
+          jitter.lock().unwrap().skipped += (current - index) as u32;
+          index = current;
+        }
+        let timestamp = frame_time(index, num, den);
+        let lateness = elapsed.saturating_sub(timestamp) as i64;
+        jitter.lock().unwrap().record(lateness);
+        producer(FrameTick {
+          index: index as u32,
+          timestamp: timestamp as i64,
+          duration: (frame_time(index + 1, num, den) - timestamp) as i64,
+          lateness,
+        });
+        index += 1;
+      }
+    }));
+    Ok(())
+  }
+}
+
+impl Drop for FrameClock {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+#[napi]
+impl FrameClock {
+  /// Creates a stopped clock
+  ///
+  /// # Arguments
+  /// * `options` - Frame rate and catch-up and spin settings
+  ///
+  /// # Example
+  /// ```javascript
+  /// const clock = new FrameClock({ fps: 29.97 });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: FrameClockOptions) -> Result<Self> {
+    let (num, den) = rational_rate(options.fps)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid fps: {}", options.fps)))?;
+    Ok(FrameClock {
+      num,
+      den,
+      catch_up: options.catch_up.unwrap_or(false),
+      spin: Duration::from_micros(options.spin_us.unwrap_or(DEFAULT_SPIN_US) as u64),
+      running: Arc::new(AtomicBool::new(false)),
+      jitter: Arc::new(Mutex::new(Jitter::default())),
+      thread: Mutex::new(None),
+    })
+  }
+
+  /// Starts ticking; the statistics are reset
+  ///
+  /// The tick is taken on a dedicated thread and queued to JavaScript, so a
+  /// busy event loop delays the callback but not the clock.
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every tick
+  ///
+  /// # Example
+  /// ```javascript
+  /// clock.start((tick) => draw(tick.index));
+  /// ```
+  #[napi]
+  pub fn start(
+    &self,
+    callback: ThreadsafeFunction<FrameTick, (), FrameTick, Status, false, true>,
+  ) -> Result<()> {
+    self.start_producer(move |tick| {
+      callback.call(tick, ThreadsafeFunctionCallMode::NonBlocking);
+    })
+  }
+
+  /// Stops ticking; the clock can be started again
+  #[napi]
+  pub fn stop(&self) {
+    self.running.store(false, Ordering::SeqCst);
+    if let Some(thread) = self.thread.lock().unwrap().take() {
+      let _ = thread.join();
+    }
+  }
+
+  /// Whether the clock is ticking
+  #[napi(getter)]
+  pub fn running(&self) -> bool {
+    self.running.load(Ordering::SeqCst)
+  }
+
+  /// Frame rate of the clock as a fraction, e.g. "30000/1001"
+  #[napi(getter)]
+  pub fn frame_rate(&self) -> String {
+    format!("{}/{}", self.num, self.den)
+  }
+
+  /// Returns the tick count and jitter since the clock started
+  ///
+  /// # Example
+  /// ```javascript
+  /// const { maxJitter, skipped } = clock.stats();
+  /// ```
+  #[napi]
+  pub fn stats(&self) -> FrameClockStats {
+    self.jitter.lock().unwrap().stats()
+  }
+
+  /// Pushes the data of a frame into an AppSrc, timestamped for its tick
+  ///
+  /// # Arguments
+  /// * `kit` - The kit running the pipeline
+  /// * `src_name` - The name of the AppSrc element
+  /// * `index` - Index of the tick the frame was made for
+  /// * `data` - The frame data, in the format of the AppSrc caps
+  ///
+  /// # Example
+  /// ```javascript
+  /// clock.start((tick) => clock.pushFrame(kit, "src", tick.index, canvas.toBuffer("raw")));
+  /// ```
+  #[napi]
+  pub fn push_frame(&self, kit: &GstKit, src_name: String, index: u32, data: Buffer) -> Result<()> {
+    let appsrc = kit.app_src(&src_name)?;
+    let timestamp = frame_time(index as u64, self.num, self.den);
+    let duration = frame_time(index as u64 + 1, self.num, self.den) - timestamp;
+    let mut buffer = gst::Buffer::from_mut_slice(data.to_vec());
+    {
+      let buffer = buffer.get_mut().ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          "Failed to timestamp buffer".to_string(),
+        )
+      })?;
+      buffer.set_pts(gst::ClockTime::from_nseconds(timestamp));
+      buffer.set_duration(gst::ClockTime::from_nseconds(duration));
+      buffer.set_offset(index as u64);
+    }
+    appsrc.push_buffer(buffer).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })?;
+    Ok(())
+  }
+}
@@ -0,0 +1,307 @@
+//! # Frame Export
+//!
+//! Decodes a video and writes its frames as JPEG or PNG images, either as
+//! files in a directory or streamed into a single zip archive, which is much
+//! easier to move around than thousands of loose files. Archives can be
+//! encrypted with a password (AES-256, as read by 7-Zip, WinZip and most
+//! unzip tools other than the classic Info-ZIP `unzip`). Both forms contain an
+//! `index.json` listing every image with its frame number and timestamp.
+
+use crate::kit::to_uri;
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+/// Options for `extractFramesToImages`
+#[napi(object)]
+pub struct FrameExportOptions {
+  /// Image format: "jpeg" (default) or "png"
+  pub format: Option<String>,
+  /// Frames per second to export; by default every frame is exported
+  pub fps: Option<f64>,
+  /// Stop after this many frames
+  pub max_frames: Option<u32>,
+  /// JPEG quality from 1 to 100 (default: 85)
+  pub quality: Option<u32>,
+  /// Write a zip archive at the output path instead of files in a directory
+  pub archive: Option<bool>,
+  /// Encrypt the archive entries with AES-256 using this password
+  pub password: Option<String>,
+}
+
+/// An image written by `extractFramesToImages`
+#[napi(object)]
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedFrame {
+  /// File name of the image, in the directory or archive
+  pub name: String,
+  /// Number of the frame among the exported ones, from 0
+  pub frame: u32,
+  /// Presentation timestamp in nanoseconds, or -1 if unknown
+  pub pts: i64,
+  /// Size of the image in bytes
+  pub size: u32,
+}
+
+/// Contents of `index.json`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameIndex<'a> {
+  source: &'a str,
+  format: &'a str,
+  frames: &'a [ExportedFrame],
+}
+
+/// Where the images go
+enum FrameWriter {
+  Directory(PathBuf),
+  Archive {
+    zip: Box<ZipWriter<File>>,
+    password: Option<String>,
+  },
+}
+
+impl FrameWriter {
+  fn add(&mut self, name: &str, data: &[u8]) -> std::result::Result<(), String> {
+    match self {
+      FrameWriter::Directory(dir) => fs::write(dir.join(name), data).map_err(|e| e.to_string()),
+      FrameWriter::Archive { zip, password } => {
+        // Images are already compressed
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let started = match password {
+          Some(password) => {
+            zip.start_file(name, options.with_aes_encryption(AesMode::Aes256, password))
+          }
+          None => zip.start_file(name, options),
+        };
+        started
+          .map_err(|e| e.to_string())
+          .and_then(|_| zip.write_all(data).map_err(|e| e.to_string()))
+      }
+    }
+  }
+
+  fn finish(self) -> std::result::Result<(), String> {
+    match self {
+      FrameWriter::Directory(_) => Ok(()),
+      FrameWriter::Archive { zip, .. } => zip.finish().map(|_| ()).map_err(|e| e.to_string()),
+    }
+  }
+}
+
+#[derive(Default)]
+struct Export {
+  writer: Option<FrameWriter>,
+  frames: Vec<ExportedFrame>,
+  error: Option<String>,
+}
+
+/// `videorate` and caps converting to `fps` frames per second, ready to be
+/// placed in a launch line before the next element, or nothing without `fps`
+pub(crate) fn rate_filter(fps: Option<f64>) -> Result<String> {
+  let Some(fps) = fps else {
+    return Ok(String::new());
+  };
+  let fraction = gst::Fraction::approximate_f64(fps)
+    .filter(|_| fps > 0.0)
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid frame rate: {}", fps)))?;
+  Ok(format!(
+    "videorate ! video/x-raw,framerate={}/{} ! ",
+    fraction.numer(),
+    fraction.denom()
+  ))
+}
+
+/// Exports the frames of a video as JPEG or PNG images
+///
+/// # Arguments
+/// * `input` - Path or URI of the video
+/// * `output` - Directory to write the images to (created if needed), or the
+///   path of the zip archive when `archive` is set
+/// * `options` - Image format, frame rate, frame limit and archive settings
+///
+/// # Returns
+/// * `Result<Vec<ExportedFrame>>` - The images written, as listed in `index.json`
+///
+/// # Example
+/// ```javascript
+/// const frames = extractFramesToImages("talk.mp4", "talk-frames.zip", {
+///   fps: 1,
+///   archive: true,
+///   password: process.env.FRAMES_PASSWORD,
+/// });
+/// console.log(`${frames.length} frames archived`);
+/// ```
+#[napi]
+pub fn extract_frames_to_images(
+  input: String,
+  output: String,
+  options: Option<FrameExportOptions>,
+) -> Result<Vec<ExportedFrame>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(FrameExportOptions {
+    format: None,
+    fps: None,
+    max_frames: None,
+    quality: None,
+    archive: None,
+    password: None,
+  });
+  let format = options.format.unwrap_or_else(|| "jpeg".to_string());
+  let (encoder, extension) = match format.as_str() {
+    "jpeg" | "jpg" => (
+      format!(
+        "jpegenc quality={}",
+        options.quality.unwrap_or(85).clamp(1, 100)
+      ),
+      "jpg",
+    ),
+    "png" => ("pngenc".to_string(), "png"),
+    format => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Unsupported image format: {} (use \"jpeg\" or \"png\")",
+          format
+        ),
+      ))
+    }
+  };
+  let rate = rate_filter(options.fps)?;
+  let archive = options.archive.unwrap_or(false);
+  if options.password.is_some() && !archive {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "password requires archive".to_string(),
+    ));
+  }
+
+  let writer = if archive {
+    let file = File::create(&output).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to create {}: {}", output, e),
+      )
+    })?;
+    FrameWriter::Archive {
+      zip: Box::new(ZipWriter::new(file)),
+      password: options.password,
+    }
+  } else {
+    fs::create_dir_all(&output).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to create {}: {}", output, e),
+      )
+    })?;
+    FrameWriter::Directory(PathBuf::from(&output))
+  };
+
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! videoconvert ! {}{} ! \
+     appsink name=sink sync=false",
+    to_uri(&input)?,
+    rate,
+    encoder
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Export sink not found"))?;
+
+  let max_frames = options.max_frames.unwrap_or(u32::MAX) as usize;
+  let export = Arc::new(Mutex::new(Export {
+    writer: Some(writer),
+    ..Default::default()
+  }));
+  let export_clone = export.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+
+        let mut guard = export_clone.lock().unwrap();
+        let e = &mut *guard;
+        if e.frames.len() >= max_frames || e.error.is_some() {
+          return Err(gst::FlowError::Eos);
+        }
+        let frame = e.frames.len() as u32;
+        let name = format!("frame-{:06}.{}", frame + 1, extension);
+        if let Some(writer) = &mut e.writer {
+          if let Err(reason) = writer.add(&name, &map) {
+            e.error = Some(format!("Failed to write {}: {}", name, reason));
+            return Err(gst::FlowError::Eos);
+          }
+        }
+        e.frames.push(ExportedFrame {
+          name,
+          frame,
+          pts: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+          size: map.len() as u32,
+        });
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+
+  let mut guard = export.lock().unwrap();
+  let e = &mut *guard;
+  if let Some(reason) = e.error.take() {
+    return Err(Error::new(Status::GenericFailure, reason));
+  }
+  result?;
+  let index = serde_json::to_vec_pretty(&FrameIndex {
+    source: &input,
+    format: extension,
+    frames: &e.frames,
+  })
+  .map_err(|err| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to serialize the frame index: {}", err),
+    )
+  })?;
+  let mut writer = e
+    .writer
+    .take()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Export already finished"))?;
+  writer
+    .add("index.json", &index)
+    .and_then(|_| writer.finish())
+    .map_err(|reason| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write {}: {}", output, reason),
+      )
+    })?;
+  Ok(std::mem::take(&mut e.frames))
+}
@@ -0,0 +1,139 @@
+//! # Frame Metadata
+//!
+//! Application data carried by individual frames through a pipeline, e.g.
+//! a sequence number or the JSON of a detection result, so a frame read
+//! from an AppSink can be matched with the one pushed into an AppSrc. The
+//! data travels in a buffer meta registered by this crate. It has no tags,
+//! so elements that keep the metas of their input frames carry it along,
+//! which includes converters, scalers and the base classes of most encoders
+//! and decoders; elements that build new frames from several inputs, such
+//! as mixers and muxers, drop it.
+
+use gst::glib;
+use gst::glib::translate::{from_glib, IntoGlib};
+use gstreamer as gst;
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::{Error, Result, Status};
+use std::ptr::{self, NonNull};
+use std::sync::LazyLock;
+
+/// Largest metadata attached to a frame, in bytes
+const MAX_METADATA_SIZE: usize = 64 * 1024;
+
+/// The meta as laid out in a buffer
+#[repr(C)]
+pub(crate) struct FrameMetadataMeta {
+  parent: gst::ffi::GstMeta,
+  data: Vec<u8>,
+}
+
+/// Application data attached to a buffer
+#[repr(transparent)]
+pub(crate) struct FrameMetadata(FrameMetadataMeta);
+
+unsafe impl Send for FrameMetadata {}
+unsafe impl Sync for FrameMetadata {}
+
+unsafe impl gst::MetaAPI for FrameMetadata {
+  type GstType = FrameMetadataMeta;
+
+  fn meta_api() -> glib::Type {
+    static API: LazyLock<glib::Type> = LazyLock::new(|| unsafe {
+      let mut tags: [*const std::ffi::c_char; 1] = [ptr::null()];
+      from_glib(gst::ffi::gst_meta_api_type_register(
+        c"GstKitFrameMetadataAPI".as_ptr(),
+        tags.as_mut_ptr(),
+      ))
+    });
+    *API
+  }
+}
+
+unsafe extern "C" fn init(
+  meta: *mut gst::ffi::GstMeta,
+  _params: glib::ffi::gpointer,
+  _buffer: *mut gst::ffi::GstBuffer,
+) -> glib::ffi::gboolean {
+  ptr::write(&mut (*(meta as *mut FrameMetadataMeta)).data, Vec::new());
+  true.into_glib()
+}
+
+unsafe extern "C" fn free(meta: *mut gst::ffi::GstMeta, _buffer: *mut gst::ffi::GstBuffer) {
+  ptr::drop_in_place(&mut (*(meta as *mut FrameMetadataMeta)).data);
+}
+
+/// Copies the data to buffers derived from the one carrying it
+unsafe extern "C" fn transform(
+  dest: *mut gst::ffi::GstBuffer,
+  meta: *mut gst::ffi::GstMeta,
+  _buffer: *mut gst::ffi::GstBuffer,
+  _type: glib::ffi::GQuark,
+  _data: glib::ffi::gpointer,
+) -> glib::ffi::gboolean {
+  let data = (*(meta as *const FrameMetadataMeta)).data.clone();
+  attach_raw(gst::BufferRef::from_mut_ptr(dest), data);
+  true.into_glib()
+}
+
+/// Registered implementation of the meta
+struct MetaInfo(NonNull<gst::ffi::GstMetaInfo>);
+
+unsafe impl Send for MetaInfo {}
+unsafe impl Sync for MetaInfo {}
+
+fn meta_info() -> *const gst::ffi::GstMetaInfo {
+  static INFO: LazyLock<MetaInfo> = LazyLock::new(|| unsafe {
+    MetaInfo(
+      NonNull::new(gst::ffi::gst_meta_register(
+        <FrameMetadata as gst::MetaAPI>::meta_api().into_glib(),
+        c"GstKitFrameMetadata".as_ptr(),
+        std::mem::size_of::<FrameMetadataMeta>(),
+        Some(init),
+        Some(free),
+        Some(transform),
+      ) as *mut _)
+      .expect("Failed to register the frame metadata meta"),
+    )
+  });
+  INFO.0.as_ptr()
+}
+
+/// Adds the meta to `buffer`, replacing the data of any it has
+fn attach_raw(buffer: &mut gst::BufferRef, data: Vec<u8>) {
+  if let Some(mut existing) = buffer.meta_mut::<FrameMetadata>() {
+    existing.0.data = data;
+    return;
+  }
+  unsafe {
+    let meta = gst::ffi::gst_buffer_add_meta(buffer.as_mut_ptr(), meta_info(), ptr::null_mut())
+      as *mut FrameMetadataMeta;
+    (*meta).data = data;
+  }
+}
+
+/// Attaches metadata given as bytes or a string (stored as UTF-8) to a frame
+pub(crate) fn attach(buffer: &mut gst::BufferRef, metadata: Either<Buffer, String>) -> Result<()> {
+  let data = match metadata {
+    Either::A(bytes) => bytes.to_vec(),
+    Either::B(text) => text.into_bytes(),
+  };
+  if data.len() > MAX_METADATA_SIZE {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Frame metadata is {} bytes, more than the {} allowed",
+        data.len(),
+        MAX_METADATA_SIZE
+      ),
+    ));
+  }
+  attach_raw(buffer, data);
+  Ok(())
+}
+
+/// Reads the metadata attached to a frame, if any
+pub(crate) fn read(buffer: &gst::BufferRef) -> Option<Buffer> {
+  buffer
+    .meta::<FrameMetadata>()
+    .map(|meta| Buffer::from(meta.0.data.clone()))
+}
@@ -0,0 +1,299 @@
+//! # Frame Streams
+//!
+//! Delivers the frames of an AppSink as a JavaScript async iterable:
+//!
+//! ```javascript
+//! for await (const frame of kit.frames("sink")) { ... }
+//! ```
+//!
+//! Frames are queued between the streaming thread and JavaScript in a
+//! bounded queue. What happens when the consumer falls behind is set by a
+//! backpressure policy:
+//!
+//! - "dropOldest" (default) drops the oldest queued frame, so a slow loop
+//!   always sees recent frames and never stalls the pipeline
+//! - "dropNewest" drops the incoming frame instead, keeping the frames
+//!   already queued
+//! - "block" holds the streaming thread until there is room, slowing the
+//!   pipeline down to the pace of the consumer
+//! - "sampleEveryN" only queues every `sampleEvery`-th frame, dropping the
+//!   oldest one if the queue is still full
+
+use crate::kit::{frame_data, FrameData};
+use crate::pixel_layout::FrameCrop;
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::bindgen_prelude::{AsyncGenerator, Undefined};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+/// Options for `startFrameEmission` and `frames`
+#[napi(object)]
+pub struct FrameEmissionOptions {
+  /// What to do when the consumer falls behind: "dropOldest" (default),
+  /// "dropNewest", "block" or "sampleEveryN"
+  pub policy: Option<String>,
+  /// Frames held while the consumer is busy (default: 4)
+  pub max_queue_length: Option<u32>,
+  /// Keep one frame out of this many with "sampleEveryN" (default: 2)
+  pub sample_every: Option<u32>,
+  /// Only deliver this part of each frame, packed; the origin is moved onto
+  /// the chroma grid of subsampled formats (even coordinates for 4:2:0)
+  pub crop: Option<FrameCrop>,
+}
+
+/// What a full frame queue does with the next frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BackpressurePolicy {
+  DropOldest,
+  DropNewest,
+  Block,
+  SampleEvery(u32),
+}
+
+/// Parsed `FrameEmissionOptions`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EmissionConfig {
+  pub(crate) policy: BackpressurePolicy,
+  pub(crate) max_queue_length: usize,
+  pub(crate) crop: Option<FrameCrop>,
+}
+
+impl Default for EmissionConfig {
+  fn default() -> Self {
+    EmissionConfig {
+      policy: BackpressurePolicy::DropOldest,
+      max_queue_length: 4,
+      crop: None,
+    }
+  }
+}
+
+impl EmissionConfig {
+  pub(crate) fn parse(options: FrameEmissionOptions) -> Result<Self> {
+    let policy = match options.policy.as_deref().unwrap_or("dropOldest") {
+      "dropOldest" => BackpressurePolicy::DropOldest,
+      "dropNewest" => BackpressurePolicy::DropNewest,
+      "block" => BackpressurePolicy::Block,
+      "sampleEveryN" => match options.sample_every.unwrap_or(2) {
+        0 => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "sampleEvery must be at least 1".to_string(),
+          ))
+        }
+        n => BackpressurePolicy::SampleEvery(n),
+      },
+      policy => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Unsupported backpressure policy: {}", policy),
+        ))
+      }
+    };
+    if options.sample_every.is_some() && !matches!(policy, BackpressurePolicy::SampleEvery(_)) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "sampleEvery requires the sampleEveryN policy".to_string(),
+      ));
+    }
+    Ok(EmissionConfig {
+      policy,
+      max_queue_length: options.max_queue_length.unwrap_or(4).max(1) as usize,
+      crop: options.crop,
+    })
+  }
+}
+
+#[derive(Default)]
+struct QueueState {
+  frames: VecDeque<FrameData>,
+  /// Task waiting in `next()` for a frame
+  waker: Option<Waker>,
+  closed: bool,
+  dropped: u32,
+  /// Frames offered so far, for sampling
+  offered: u64,
+}
+
+/// Bounded frame queue shared by the appsink callbacks and the iterator
+struct FrameQueue {
+  state: Mutex<QueueState>,
+  /// Signalled when a frame is taken, for the "block" policy
+  space: Condvar,
+  config: EmissionConfig,
+}
+
+impl FrameQueue {
+  /// Queues a frame following the backpressure policy. `flushing` tells a
+  /// blocked push to give up because the sink is shutting down.
+  fn push(&self, frame: FrameData, flushing: impl Fn() -> bool) {
+    let mut state = self.state.lock().unwrap();
+    state.offered += 1;
+    if let BackpressurePolicy::SampleEvery(n) = self.config.policy {
+      if !(state.offered - 1).is_multiple_of(n as u64) {
+        return;
+      }
+    }
+    while !state.closed && state.frames.len() >= self.config.max_queue_length {
+      match self.config.policy {
+        BackpressurePolicy::DropNewest => {
+          state.dropped += 1;
+          return;
+        }
+        BackpressurePolicy::Block => {
+          if flushing() {
+            return;
+          }
+          state = self
+            .space
+            .wait_timeout(state, Duration::from_millis(50))
+            .unwrap()
+            .0;
+        }
+        _ => {
+          state.frames.pop_front();
+          state.dropped += 1;
+        }
+      }
+    }
+    if state.closed {
+      return;
+    }
+    state.frames.push_back(frame);
+    if let Some(waker) = state.waker.take() {
+      waker.wake();
+    }
+  }
+
+  fn close(&self) {
+    let mut state = self.state.lock().unwrap();
+    state.closed = true;
+    if let Some(waker) = state.waker.take() {
+      waker.wake();
+    }
+    self.space.notify_all();
+  }
+}
+
+/// Async iterable over the frames of an AppSink, created by `GstKit.frames`
+///
+/// Iteration ends at end of stream, or when the loop is left with `break`,
+/// which also detaches the stream from the sink.
+#[napi(async_iterator)]
+pub struct FrameStream {
+  queue: Arc<FrameQueue>,
+  appsink: AppSink,
+}
+
+impl FrameStream {
+  /// Takes over the callbacks of `appsink` to feed a new stream
+  pub(crate) fn attach(appsink: AppSink, config: EmissionConfig) -> Self {
+    let queue = Arc::new(FrameQueue {
+      state: Mutex::new(QueueState::default()),
+      space: Condvar::new(),
+      config,
+    });
+
+    let sink_name = appsink.name().to_string();
+    let samples = queue.clone();
+    let eos = queue.clone();
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          let frame = frame_data(&sample, &sink_name, false, config.crop.as_ref())
+            .map_err(|_| gst::FlowError::Error)?;
+          let pad = appsink.static_pad("sink");
+          samples.push(frame, || match &pad {
+            Some(pad) => pad.pad_flags().contains(gst::PadFlags::FLUSHING),
+            None => true,
+          });
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .eos(move |_| eos.close())
+        .build(),
+    );
+    FrameStream { queue, appsink }
+  }
+
+  fn detach(&self) {
+    self.queue.close();
+    self.queue.state.lock().unwrap().frames.clear();
+    self
+      .appsink
+      .set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+  }
+}
+
+#[napi]
+impl FrameStream {
+  /// Number of frames dropped so far because the consumer fell behind (frames
+  /// skipped by "sampleEveryN" are not counted)
+  #[napi(getter)]
+  pub fn dropped(&self) -> u32 {
+    self.queue.state.lock().unwrap().dropped
+  }
+
+  /// Ends the stream and detaches it from the sink
+  ///
+  /// # Example
+  /// ```javascript
+  /// const frames = kit.frames("sink");
+  /// setTimeout(() => frames.close(), 5000);
+  /// for await (const frame of frames) { ... }
+  /// ```
+  #[napi]
+  pub fn close(&self) {
+    self.detach();
+  }
+}
+
+#[napi]
+impl AsyncGenerator for FrameStream {
+  type Yield = FrameData;
+  type Next = Undefined;
+  type Return = Undefined;
+
+  fn next(
+    &mut self,
+    _value: Option<Self::Next>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    let queue = self.queue.clone();
+    std::future::poll_fn(move |cx| {
+      let mut state = queue.state.lock().unwrap();
+      match state.frames.pop_front() {
+        Some(frame) => {
+          queue.space.notify_one();
+          Poll::Ready(Ok(Some(frame)))
+        }
+        None if state.closed => Poll::Ready(Ok(None)),
+        None => {
+          state.waker = Some(cx.waker().clone());
+          Poll::Pending
+        }
+      }
+    })
+  }
+
+  fn complete(
+    &mut self,
+    _value: Option<Self::Return>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    self.detach();
+    std::future::ready(Ok(None))
+  }
+}
+
+impl Drop for FrameStream {
+  fn drop(&mut self) {
+    self.detach();
+  }
+}
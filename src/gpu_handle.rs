@@ -0,0 +1,274 @@
+//! # GPU Frame Handles
+//!
+//! Hands out the GPU-side handle of a frame instead of downloading it to
+//! system memory, for renderers that import the frame themselves (e.g. with
+//! `EGL_EXT_image_dma_buf_import` or a WebGPU external texture).
+//!
+//! The accessors live in the `gstreamer-allocators`, `gstreamer-gl` and
+//! `gstreamer-d3d11` libraries and in the CoreVideo framework, which elements
+//! producing such memory already load. They are looked up at runtime, so the
+//! module does not link against any of them and simply reports the memory as
+//! unsupported where they are missing.
+//!
+//! DMABuf memory (Linux), GL memory (Linux and macOS), the IOSurfaces behind
+//! the CoreVideo buffers of `applemedia` elements (macOS) and shared D3D11
+//! textures (Windows) are exported.
+
+use gst::glib;
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// GPU handle of a video frame
+#[napi(object)]
+pub struct GpuFrameHandle {
+  /// Kind of handle: "dmabuf" (file descriptors), "gl" (texture names),
+  /// "iosurface" (IOSurface IDs) or "d3d11" (shared NT handles)
+  pub kind: String,
+  /// One handle per memory of the buffer, or a single IOSurface ID for the
+  /// whole frame. DMABuf file descriptors are duplicated and owned by the
+  /// caller, who must close them; GL texture names belong to the pipeline's
+  /// GL context and are only valid while it renders the frame; IOSurface IDs
+  /// are resolved with `IOSurfaceLookup`; D3D11 handles belong to the
+  /// pipeline and are opened with `ID3D11Device1::OpenSharedResource1`
+  pub handles: Vec<i64>,
+  /// Raw video format, or the DRM fourcc and modifier (e.g. "NV12:0x0100000000000002")
+  /// for DMA_DRM caps
+  pub format: String,
+  /// Width of the frame in pixels
+  pub width: u32,
+  /// Height of the frame in pixels
+  pub height: u32,
+  /// Byte offset of each plane within its memory
+  pub offsets: Vec<u32>,
+  /// Stride of each plane in bytes
+  pub strides: Vec<u32>,
+  /// Timestamp of the frame in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+  /// The negotiated caps, with their memory feature
+  pub caps: String,
+}
+
+#[cfg(unix)]
+fn lookup(symbol: &std::ffi::CStr) -> Option<*mut std::ffi::c_void> {
+  // SAFETY: dlsym with RTLD_DEFAULT only searches already loaded objects
+  let address = unsafe { libc::dlsym(libc::RTLD_DEFAULT, symbol.as_ptr()) };
+  (!address.is_null()).then_some(address)
+}
+
+/// Resolves `check` and `get` and applies them to every memory of `buffer`
+#[cfg(unix)]
+fn memory_handles(
+  buffer: &gst::BufferRef,
+  check: &std::ffi::CStr,
+  get: &std::ffi::CStr,
+) -> Option<Vec<i64>> {
+  type Check = unsafe extern "C" fn(*mut gst::ffi::GstMemory) -> glib::ffi::gboolean;
+  type Get = unsafe extern "C" fn(*mut gst::ffi::GstMemory) -> std::ffi::c_int;
+  // SAFETY: both symbols have the signatures above in every GStreamer 1.x
+  let (check, get): (Check, Get) = unsafe {
+    (
+      std::mem::transmute::<*mut std::ffi::c_void, Check>(lookup(check)?),
+      std::mem::transmute::<*mut std::ffi::c_void, Get>(lookup(get)?),
+    )
+  };
+  buffer
+    .iter_memories()
+    .map(|memory| {
+      let memory = memory.as_ptr() as *mut gst::ffi::GstMemory;
+      // SAFETY: the memory stays alive with the sample for the duration of the call
+      unsafe { (check(memory) != glib::ffi::GFALSE).then(|| get(memory) as i64) }
+    })
+    .collect()
+}
+
+#[cfg(not(unix))]
+fn memory_handles(
+  _buffer: &gst::BufferRef,
+  _check: &std::ffi::CStr,
+  _get: &std::ffi::CStr,
+) -> Option<Vec<i64>> {
+  None
+}
+
+/// Layout of the meta `applemedia` elements attach to their CoreVideo buffers
+#[cfg(target_os = "macos")]
+#[repr(C)]
+struct CoreVideoMeta {
+  meta: gst::ffi::GstMeta,
+  cvbuf: *mut std::ffi::c_void,
+  pixbuf: *mut std::ffi::c_void,
+}
+
+/// Returns the ID of the IOSurface backing the CoreVideo pixel buffer of
+/// `buffer`, if it has one
+#[cfg(target_os = "macos")]
+fn iosurface_handles(buffer: &gst::BufferRef) -> Option<Vec<i64>> {
+  use glib::translate::IntoGlib;
+  type GetSurface = unsafe extern "C" fn(*mut std::ffi::c_void) -> *mut std::ffi::c_void;
+  type GetId = unsafe extern "C" fn(*mut std::ffi::c_void) -> u32;
+  let api = glib::Type::from_name("GstCoreVideoMetaAPI")?;
+  // SAFETY: both functions have the signatures above in every macOS release
+  let (get_surface, get_id): (GetSurface, GetId) = unsafe {
+    (
+      std::mem::transmute::<*mut std::ffi::c_void, GetSurface>(lookup(
+        c"CVPixelBufferGetIOSurface",
+      )?),
+      std::mem::transmute::<*mut std::ffi::c_void, GetId>(lookup(c"IOSurfaceGetID")?),
+    )
+  };
+  // SAFETY: metas of this API are `GstCoreVideoMeta`s, alive with the buffer
+  unsafe {
+    let meta = gst::ffi::gst_buffer_get_meta(buffer.as_mut_ptr(), api.into_glib());
+    let meta = (meta as *const CoreVideoMeta).as_ref()?;
+    if meta.pixbuf.is_null() {
+      return None;
+    }
+    let surface = get_surface(meta.pixbuf);
+    (!surface.is_null()).then(|| vec![get_id(surface) as i64])
+  }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn iosurface_handles(_buffer: &gst::BufferRef) -> Option<Vec<i64>> {
+  None
+}
+
+/// Returns the shared NT handle of every D3D11 memory of `buffer`
+#[cfg(windows)]
+fn d3d11_handles(buffer: &gst::BufferRef) -> Option<Vec<i64>> {
+  use std::sync::OnceLock;
+  type Check = unsafe extern "C" fn(*mut gst::ffi::GstMemory) -> glib::ffi::gboolean;
+  type Get = unsafe extern "C" fn(
+    *mut gst::ffi::GstMemory,
+    *mut *mut std::ffi::c_void,
+  ) -> glib::ffi::gboolean;
+  static D3D11: OnceLock<Option<libloading::Library>> = OnceLock::new();
+  let library = D3D11
+    .get_or_init(|| unsafe { libloading::Library::new("gstd3d11-1.0-0.dll").ok() })
+    .as_ref()?;
+  // SAFETY: both functions have the signatures above since GStreamer 1.22
+  let (check, get) = unsafe {
+    (
+      *library.get::<Check>(b"gst_is_d3d11_memory\0").ok()?,
+      *library
+        .get::<Get>(b"gst_d3d11_memory_get_nt_handle\0")
+        .ok()?,
+    )
+  };
+  buffer
+    .iter_memories()
+    .map(|memory| {
+      let memory = memory.as_ptr() as *mut gst::ffi::GstMemory;
+      let mut handle = std::ptr::null_mut();
+      // SAFETY: the memory stays alive with the sample for the duration of the call
+      unsafe {
+        (check(memory) != glib::ffi::GFALSE && get(memory, &mut handle) != glib::ffi::GFALSE)
+          .then_some(handle as i64)
+      }
+    })
+    .collect()
+}
+
+#[cfg(not(windows))]
+fn d3d11_handles(_buffer: &gst::BufferRef) -> Option<Vec<i64>> {
+  None
+}
+
+/// Extracts the GPU handles of a sample whose caps carry `memory:DMABuf`,
+/// `memory:GLMemory` or `memory:D3D11Memory`, or whose buffer is a CoreVideo
+/// buffer backed by an IOSurface
+pub(crate) fn gpu_frame_handle(sample: &gst::Sample) -> Result<GpuFrameHandle> {
+  let caps = sample
+    .caps()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no caps"))?;
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+  let (Some(structure), Some(features)) = (caps.structure(0), caps.features(0)) else {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("Sample has empty caps: {}", caps),
+    ));
+  };
+
+  let iosurfaces = iosurface_handles(buffer);
+  let kind = if features.contains("memory:DMABuf") {
+    "dmabuf"
+  } else if features.contains("memory:GLMemory") {
+    "gl"
+  } else if features.contains("memory:D3D11Memory") {
+    "d3d11"
+  } else if iosurfaces.is_some() {
+    "iosurface"
+  } else {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Sample is not in DMABuf, GL, D3D11 or IOSurface memory (caps: {}); negotiate e.g. \
+         video/x-raw(memory:DMABuf) or use glupload before the sink",
+        caps
+      ),
+    ));
+  };
+
+  let handles = match kind {
+    "dmabuf" => memory_handles(buffer, c"gst_is_dmabuf_memory", c"gst_dmabuf_memory_get_fd"),
+    "gl" => memory_handles(buffer, c"gst_is_gl_memory", c"gst_gl_memory_get_texture_id"),
+    "d3d11" => d3d11_handles(buffer),
+    _ => iosurfaces,
+  };
+  #[cfg_attr(not(unix), allow(unused_mut))]
+  let mut handles = handles
+    .filter(|h| !h.is_empty() && h.iter().all(|&h| h >= 0))
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Cannot export {} handles on this system", kind),
+      )
+    })?;
+  // The buffer's fds close with it, so the caller gets its own copies
+  #[cfg(unix)]
+  if kind == "dmabuf" {
+    for i in 0..handles.len() {
+      // SAFETY: the descriptor is valid and owned by the buffer
+      let fd = unsafe { libc::dup(handles[i] as i32) };
+      if fd < 0 {
+        let error = std::io::Error::last_os_error();
+        for &copy in &handles[..i] {
+          // SAFETY: the copies made so far are owned here
+          unsafe { libc::close(copy as i32) };
+        }
+        return Err(Error::new(
+          Status::GenericFailure,
+          format!("Failed to duplicate a DMABuf descriptor: {}", error),
+        ));
+      }
+      handles[i] = fd as i64;
+    }
+  }
+
+  let format = structure
+    .get::<String>("drm-format")
+    .or_else(|_| structure.get::<String>("format"))
+    .unwrap_or_default();
+  let (offsets, strides) = match buffer.meta::<gst_video::VideoMeta>() {
+    Some(meta) => (
+      meta.offset().iter().map(|&o| o as u32).collect(),
+      meta.stride().iter().map(|s| s.unsigned_abs()).collect(),
+    ),
+    None => (Vec::new(), Vec::new()),
+  };
+  Ok(GpuFrameHandle {
+    kind: kind.to_string(),
+    handles,
+    format,
+    width: structure.get::<i32>("width").unwrap_or(0).max(0) as u32,
+    height: structure.get::<i32>("height").unwrap_or(0).max(0) as u32,
+    offsets,
+    strides,
+    timestamp: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+    caps: caps.to_string(),
+  })
+}
@@ -0,0 +1,285 @@
+//! # Image Diff
+//!
+//! Pixel comparison of two images, for visual-regression tests of filters and
+//! pipelines. Images are decoded with GStreamer, so any format it can decode
+//! (PNG, JPEG, BMP, ...) can be compared.
+
+use crate::transcode::launch;
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Options for `diffImages`
+#[napi(object)]
+pub struct ImageDiffOptions {
+  /// Per-channel difference from 0 to 1 above which a pixel counts as
+  /// different (default: 0.1)
+  pub threshold: Option<f64>,
+  /// Also render a PNG heatmap of the differing pixels (default: false)
+  pub heatmap: Option<bool>,
+}
+
+/// Result of `diffImages`
+#[napi(object)]
+pub struct ImageDiffResult {
+  /// Share of matching pixels, from 0 (all differ) to 1 (identical)
+  pub similarity: f64,
+  /// Number of pixels whose difference exceeds the threshold
+  pub different_pixels: u32,
+  /// Number of pixels compared
+  pub total_pixels: u32,
+  /// Mean per-channel difference, from 0 to 1
+  pub mean_difference: f64,
+  /// PNG with differing pixels in red over a dimmed copy of the first image
+  pub heatmap: Option<Buffer>,
+}
+
+/// Decoded image as tightly packed RGBA
+struct RgbaImage {
+  width: u32,
+  height: u32,
+  pixels: Vec<u8>,
+}
+
+fn pull_sample(pipeline: &gst::Pipeline, what: &str) -> Result<gst::Sample> {
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Image sink not found"))?;
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+
+  let sample = appsink.try_pull_sample(gst::ClockTime::from_seconds(10));
+  let error = pipeline
+    .bus()
+    .and_then(|bus| bus.pop_filtered(&[gst::MessageType::Error]))
+    .and_then(|msg| match msg.view() {
+      gst::MessageView::Error(err) => Some(err.error().to_string()),
+      _ => None,
+    });
+  let _ = pipeline.set_state(gst::State::Null);
+
+  sample.ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!(
+        "Failed to {}: {}",
+        what,
+        error.unwrap_or_else(|| "no output".to_string())
+      ),
+    )
+  })
+}
+
+fn load_image(image: Either<Buffer, String>) -> Result<RgbaImage> {
+  let tail = "decodebin ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink sync=false";
+  let pipeline = match image {
+    Either::A(data) => {
+      let pipeline = launch(&format!("appsrc name=src ! {}", tail))?;
+      let appsrc = pipeline
+        .by_name("src")
+        .and_then(|el| el.downcast::<AppSrc>().ok())
+        .ok_or_else(|| Error::new(Status::GenericFailure, "Image source not found"))?;
+      appsrc
+        .push_buffer(gst::Buffer::from_mut_slice(data.to_vec()))
+        .and_then(|_| appsrc.end_of_stream())
+        .map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to push buffer: {}", e),
+          )
+        })?;
+      pipeline
+    }
+    Either::B(path) => {
+      let pipeline = launch(&format!("filesrc name=src ! {}", tail))?;
+      if let Some(src) = pipeline.by_name("src") {
+        src.set_property("location", &path);
+      }
+      pipeline
+    }
+  };
+
+  let sample = pull_sample(&pipeline, "decode image")?;
+  let caps = sample
+    .caps()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Decoded image has no caps"))?;
+  let info = gst_video::VideoInfo::from_caps(caps)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Invalid image caps: {}", e)))?;
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Decoded image has no data"))?;
+  let map = buffer.map_readable().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to map image: {}", e),
+    )
+  })?;
+
+  let row = info.width() as usize * 4;
+  let stride = info.stride()[0] as usize;
+  let offset = info.offset()[0];
+  let pixels = (0..info.height() as usize)
+    .flat_map(|y| &map.as_slice()[offset + y * stride..offset + y * stride + row])
+    .copied()
+    .collect();
+  Ok(RgbaImage {
+    width: info.width(),
+    height: info.height(),
+    pixels,
+  })
+}
+
+fn encode_png(width: u32, height: u32, pixels: Vec<u8>) -> Result<Buffer> {
+  let pipeline =
+    launch("appsrc name=src format=time ! videoconvert ! pngenc snapshot=true ! appsink name=sink sync=false")?;
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Heatmap source not found"))?;
+  let info = gst_video::VideoInfo::builder(gst_video::VideoFormat::Rgba, width, height)
+    .fps(gst::Fraction::new(0, 1))
+    .build()
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Invalid heatmap size: {}", e),
+      )
+    })?;
+  let caps = info.to_caps().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Invalid heatmap caps: {}", e),
+    )
+  })?;
+  appsrc.set_caps(Some(&caps));
+
+  let mut buffer = gst::Buffer::from_mut_slice(pixels);
+  if let Some(buffer) = buffer.get_mut() {
+    buffer.set_pts(gst::ClockTime::ZERO);
+  }
+  appsrc
+    .push_buffer(buffer)
+    .and_then(|_| appsrc.end_of_stream())
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })?;
+
+  let sample = pull_sample(&pipeline, "encode heatmap")?;
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Heatmap has no data"))?;
+  let map = buffer.map_readable().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to map heatmap: {}", e),
+    )
+  })?;
+  Ok(Buffer::from(map.as_slice().to_vec()))
+}
+
+/// Compares two images pixel by pixel
+///
+/// # Arguments
+/// * `a` - The first image, as encoded bytes or a file path
+/// * `b` - The second image, as encoded bytes or a file path
+/// * `options` - Optional difference threshold and heatmap rendering
+///
+/// # Returns
+/// * `Result<ImageDiffResult>` - Similarity score and difference statistics
+///
+/// # Example
+/// ```javascript
+/// const diff = diffImages("expected.png", kit.pullSample("sink"), { threshold: 0.05, heatmap: true });
+/// if (diff.similarity < 0.99) fs.writeFileSync("diff.png", diff.heatmap);
+/// ```
+#[napi]
+pub fn diff_images(
+  #[napi(ts_arg_type = "Buffer | string")] a: Either<Buffer, String>,
+  #[napi(ts_arg_type = "Buffer | string")] b: Either<Buffer, String>,
+  options: Option<ImageDiffOptions>,
+) -> Result<ImageDiffResult> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let (threshold, heatmap) = match options {
+    Some(options) => (options.threshold, options.heatmap),
+    None => (None, None),
+  };
+  let threshold = (threshold.unwrap_or(0.1).clamp(0.0, 1.0) * 255.0) as u8;
+
+  let a = load_image(a)?;
+  let b = load_image(b)?;
+  if (a.width, a.height) != (b.width, b.height) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Image sizes differ: {}x{} and {}x{}",
+        a.width, a.height, b.width, b.height
+      ),
+    ));
+  }
+
+  let total_pixels = a.width * a.height;
+  let mut different_pixels = 0;
+  let mut difference_sum = 0u64;
+  let mut map = heatmap
+    .unwrap_or(false)
+    .then(|| Vec::with_capacity(a.pixels.len()));
+
+  for (pa, pb) in a.pixels.chunks_exact(4).zip(b.pixels.chunks_exact(4)) {
+    let diffs = [0, 1, 2, 3].map(|c| pa[c].abs_diff(pb[c]));
+    difference_sum += diffs.iter().map(|&d| d as u64).sum::<u64>();
+    let different = diffs.iter().any(|&d| d > threshold);
+    if different {
+      different_pixels += 1;
+    }
+    if let Some(map) = map.as_mut() {
+      if different {
+        map.extend_from_slice(&[255, 0, 0, 255]);
+      } else {
+        let gray = ((pa[0] as u32 + pa[1] as u32 + pa[2] as u32) / 9) as u8;
+        map.extend_from_slice(&[gray, gray, gray, 255]);
+      }
+    }
+  }
+
+  let heatmap = match map {
+    Some(map) => Some(encode_png(a.width, a.height, map)?),
+    None => None,
+  };
+  let similarity = if total_pixels > 0 {
+    1.0 - different_pixels as f64 / total_pixels as f64
+  } else {
+    1.0
+  };
+  let mean_difference = if total_pixels > 0 {
+    difference_sum as f64 / (total_pixels as f64 * 4.0 * 255.0)
+  } else {
+    0.0
+  };
+
+  Ok(ImageDiffResult {
+    similarity,
+    different_pixels,
+    total_pixels,
+    mean_difference,
+    heatmap,
+  })
+}
@@ -0,0 +1,206 @@
+//! # Interlace Detection
+//!
+//! Tells progressive, interlaced and telecined video apart by looking for
+//! combing: rows that differ from both of their neighbours in the same
+//! direction, as happens where the two fields of a frame were captured at
+//! different times and something moved in between. Interlaced video combs in
+//! every frame with motion. Telecined film (3:2 pulldown) combs in two out of
+//! every five frames, always at the same positions of the cycle, and is
+//! restored by the `ivtc` video filter rather than deinterlaced.
+//!
+//! Combing only shows where there is motion, so a static video is reported
+//! as progressive whatever its field structure.
+
+use crate::kit::to_uri;
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+
+/// Smallest difference, in luma levels, between a row and each of its
+/// neighbours for a pixel to count as combed
+const COMB_THRESHOLD: i32 = 12;
+
+/// Share of combed pixels above which a frame counts as combed
+const COMBED_FRAME: f64 = 0.002;
+
+/// Options for `detectInterlacing`
+#[napi(object)]
+pub struct InterlaceOptions {
+  /// Number of consecutive frames analysed from the start (default: 500)
+  pub frames: Option<u32>,
+}
+
+/// Field structure of a video
+#[napi(object)]
+pub struct InterlaceReport {
+  /// "progressive", "interlaced", "telecined" or "mixed"
+  pub verdict: String,
+  /// Number of frames analysed
+  pub frames_analyzed: u32,
+  /// Number of frames showing combing
+  pub combed_frames: u32,
+  /// Share of the analysed frames showing combing, from 0 to 1
+  pub combed_ratio: f64,
+  /// For telecined video, the position in the 5 frame cycle of the first of
+  /// the two combed frames, counting from the first analysed frame
+  pub pulldown_phase: Option<u32>,
+  /// Video filter to apply when transcoding: "ivtc" for telecined video,
+  /// "deinterlace" for interlaced video
+  pub recommended_filter: Option<String>,
+}
+
+/// Share of the pixels of a luma plane that are combed
+fn comb_score(luma: &[u8], width: usize, height: usize, stride: usize) -> f64 {
+  if height < 3 || width == 0 {
+    return 0.0;
+  }
+  let mut combed = 0usize;
+  for y in 1..height - 1 {
+    let (above, row, below) = (
+      &luma[(y - 1) * stride..],
+      &luma[y * stride..],
+      &luma[(y + 1) * stride..],
+    );
+    for x in 0..width {
+      let (a, b, c) = (above[x] as i32, row[x] as i32, below[x] as i32);
+      if (a - b) * (c - b) > COMB_THRESHOLD * COMB_THRESHOLD && (a - c).abs() < (a - b).abs() {
+        combed += 1;
+      }
+    }
+  }
+  combed as f64 / (width * (height - 2)) as f64
+}
+
+/// Classifies a sequence of combed flags, returning the verdict and, for
+/// telecined video, the pulldown phase
+fn classify(combed: &[bool]) -> (&'static str, Option<u32>) {
+  let total = combed.iter().filter(|c| **c).count();
+  let ratio = total as f64 / combed.len().max(1) as f64;
+  if ratio < 0.1 {
+    return ("progressive", None);
+  }
+  // 3:2 pulldown combs two adjacent positions of every five frame cycle
+  let mut phases = [0usize; 5];
+  for (index, _) in combed.iter().enumerate().filter(|(_, c)| **c) {
+    phases[index % 5] += 1;
+  }
+  let (phase, pair) = (0..5)
+    .map(|p| (p, phases[p] + phases[(p + 1) % 5]))
+    .max_by_key(|(_, count)| *count)
+    .unwrap_or((0, 0));
+  if (0.2..=0.6).contains(&ratio) && pair as f64 >= 0.8 * total as f64 {
+    ("telecined", Some(phase as u32))
+  } else if ratio >= 0.5 {
+    ("interlaced", None)
+  } else {
+    ("mixed", None)
+  }
+}
+
+/// Detects whether a video is progressive, interlaced or telecined
+///
+/// # Arguments
+/// * `input` - Path or URI of the video
+/// * `options` - Number of frames to analyse
+///
+/// # Returns
+/// * `Result<InterlaceReport>` - The verdict, combing statistics and the
+///   video filter that makes the video progressive
+///
+/// # Example
+/// ```javascript
+/// const { verdict, recommendedFilter } = detectInterlacing("dvd-rip.mkv");
+/// const videoFilters = recommendedFilter ? [recommendedFilter] : [];
+/// console.log(verdict, videoFilters);
+/// ```
+#[napi]
+pub fn detect_interlacing(
+  input: String,
+  options: Option<InterlaceOptions>,
+) -> Result<InterlaceReport> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let max_frames = options
+    .and_then(|options| options.frames)
+    .unwrap_or(500)
+    .max(1) as usize;
+  // Frames are analysed at full size: scaling would blend the fields
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! videoconvert ! \
+     video/x-raw,format=GRAY8 ! appsink name=sink sync=false",
+    to_uri(&input)?
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Analysis sink not found"))?;
+
+  let combed = Arc::new(Mutex::new(Vec::new()));
+  let combed_clone = combed.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+        let video = gst_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        let score = comb_score(
+          &map,
+          video.width() as usize,
+          video.height() as usize,
+          video.stride()[0] as usize,
+        );
+        let mut combed = combed_clone.lock().unwrap();
+        combed.push(score > COMBED_FRAME);
+        if combed.len() >= max_frames {
+          return Err(gst::FlowError::Eos);
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let combed = combed.lock().unwrap();
+  if combed.is_empty() {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("No frames could be decoded from {}", input),
+    ));
+  }
+  let (verdict, pulldown_phase) = classify(&combed);
+  let combed_frames = combed.iter().filter(|c| **c).count() as u32;
+  Ok(InterlaceReport {
+    verdict: verdict.to_string(),
+    frames_analyzed: combed.len() as u32,
+    combed_frames,
+    combed_ratio: combed_frames as f64 / combed.len() as f64,
+    pulldown_phase,
+    recommended_filter: match verdict {
+      "telecined" => Some("ivtc".to_string()),
+      "interlaced" | "mixed" => Some("deinterlace".to_string()),
+      _ => None,
+    },
+  })
+}
@@ -0,0 +1,291 @@
+//! # Frame Interpolation
+//!
+//! Synthesizes intermediate video frames, to raise the frame rate (30 to 60
+//! fps) or to stretch time for slow motion. Three quality modes are offered:
+//!
+//! - "duplicate" repeats the nearest source frame (fastest, what `videorate` does)
+//! - "blend" cross-fades the two neighbouring frames
+//! - "motion" estimates a motion vector per 16x16 block and blends the two
+//!   frames along it, which keeps moving edges sharp (slowest)
+//!
+//! The interpolator is an `identity` element whose pad probe consumes the
+//! decoded RGBA frames and pushes the frames of the output time grid itself.
+
+use crate::transcode::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use std::sync::{Arc, Mutex};
+
+/// Block size of the motion search, in pixels
+const BLOCK: usize = 16;
+/// Largest motion searched between two frames, in pixels
+const SEARCH_RADIUS: i32 = 8;
+
+/// How intermediate frames are produced
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum InterpolationMode {
+  Duplicate,
+  Blend,
+  Motion,
+}
+
+/// Parses an interpolation mode name
+pub(crate) fn interpolation_mode(mode: &str) -> Result<InterpolationMode> {
+  match mode {
+    "duplicate" => Ok(InterpolationMode::Duplicate),
+    "blend" => Ok(InterpolationMode::Blend),
+    "motion" => Ok(InterpolationMode::Motion),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!("Unsupported interpolation mode: {}", mode),
+    )),
+  }
+}
+
+/// Frame rate conversion and time stretch performed by the interpolator
+#[derive(Clone, Debug)]
+pub(crate) struct Interpolation {
+  pub(crate) mode: InterpolationMode,
+  /// Output frame rate; the input frame rate when `None`
+  pub(crate) frame_rate: Option<gst::Fraction>,
+  /// Factor applied to every timestamp (2 plays at half speed)
+  pub(crate) stretch: f64,
+}
+
+/// A decoded RGBA frame
+struct Frame {
+  data: Vec<u8>,
+  /// Output timestamp, after stretching
+  time: gst::ClockTime,
+  duration: Option<gst::ClockTime>,
+}
+
+#[derive(Default)]
+struct State {
+  info: Option<gst_video::VideoInfo>,
+  /// Output frame duration, once known
+  period: Option<gst::ClockTime>,
+  previous: Option<Frame>,
+  /// Time of the next frame on the output grid
+  next: Option<gst::ClockTime>,
+}
+
+/// Creates the interpolator elements, to be linked in order after a
+/// `videoconvert`; the last one converts back for the encoder
+pub(crate) fn make_interpolator(interpolation: &Interpolation) -> Result<Vec<gst::Element>> {
+  let rgba = make_element("capsfilter")?;
+  rgba.set_property(
+    "caps",
+    gst::Caps::builder("video/x-raw")
+      .field("format", "RGBA")
+      .build(),
+  );
+  let identity = make_element("identity")?;
+  let sink = identity
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Identity has no sink pad"))?;
+  let src = identity
+    .static_pad("src")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Identity has no src pad"))?;
+
+  let interpolation = interpolation.clone();
+  let state = Arc::new(Mutex::new(State::default()));
+  sink.add_probe(
+    gst::PadProbeType::BUFFER | gst::PadProbeType::EVENT_DOWNSTREAM,
+    move |_, info| {
+      let mut state = state.lock().unwrap();
+      match &mut info.data {
+        Some(gst::PadProbeData::Event(event)) => match event.view() {
+          gst::EventView::Caps(caps) => {
+            let Ok(video) = gst_video::VideoInfo::from_caps(caps.caps()) else {
+              return gst::PadProbeReturn::Ok;
+            };
+            let rate = interpolation
+              .frame_rate
+              .or_else(|| Some(video.fps()).filter(|fps| fps.numer() > 0));
+            state.period = rate.and_then(|rate| {
+              gst::ClockTime::SECOND.mul_div_floor(rate.denom() as u64, rate.numer() as u64)
+            });
+            let mut caps = caps.caps_owned();
+            if let Some(rate) = rate {
+              caps.make_mut().set("framerate", rate);
+            }
+            state.info = Some(video);
+            *event = gst::event::Caps::new(&caps);
+            gst::PadProbeReturn::Ok
+          }
+          gst::EventView::Eos(_) => {
+            flush_last(&mut state, &src);
+            gst::PadProbeReturn::Ok
+          }
+          gst::EventView::FlushStop(_) => {
+            state.previous = None;
+            state.next = None;
+            gst::PadProbeReturn::Ok
+          }
+          _ => gst::PadProbeReturn::Ok,
+        },
+        Some(gst::PadProbeData::Buffer(buffer)) => {
+          let Some(pts) = buffer.pts() else {
+            return gst::PadProbeReturn::Drop;
+          };
+          let Ok(map) = buffer.map_readable() else {
+            return gst::PadProbeReturn::Drop;
+          };
+          let stretch = |time: gst::ClockTime| {
+            gst::ClockTime::from_nseconds((time.nseconds() as f64 * interpolation.stretch) as u64)
+          };
+          let frame = Frame {
+            data: map.to_vec(),
+            time: stretch(pts),
+            duration: buffer.duration().map(stretch),
+          };
+          drop(map);
+          interpolate_until(&mut state, &src, interpolation.mode, frame);
+          gst::PadProbeReturn::Drop
+        }
+        _ => gst::PadProbeReturn::Ok,
+      }
+    },
+  );
+
+  Ok(vec![rgba, identity, make_element("videoconvert")?])
+}
+
+fn push(src: &gst::Pad, data: Vec<u8>, time: gst::ClockTime, duration: Option<gst::ClockTime>) {
+  let mut buffer = gst::Buffer::from_mut_slice(data);
+  {
+    let buffer = buffer.get_mut().unwrap();
+    buffer.set_pts(time);
+    buffer.set_duration(duration);
+  }
+  let _ = src.push(buffer);
+}
+
+/// Emits the output frames between the previous frame and `frame`, then keeps
+/// `frame` as the previous one
+fn interpolate_until(state: &mut State, src: &gst::Pad, mode: InterpolationMode, frame: Frame) {
+  let Some(previous) = state.previous.take() else {
+    state.next = Some(frame.time);
+    state.previous = Some(frame);
+    return;
+  };
+  let Some(period) = state.period else {
+    push(src, previous.data, previous.time, previous.duration);
+    state.previous = Some(frame);
+    return;
+  };
+  let span = frame.time.saturating_sub(previous.time).nseconds() as f64;
+  let mut next = state.next.unwrap_or(previous.time);
+  while next < frame.time {
+    let alpha = if span > 0.0 {
+      (next.saturating_sub(previous.time).nseconds() as f64 / span) as f32
+    } else {
+      0.0
+    };
+    let data = match (mode, &state.info) {
+      (InterpolationMode::Motion, Some(info)) if alpha > 0.0 => {
+        motion_blend(&previous.data, &frame.data, info, alpha)
+      }
+      (InterpolationMode::Blend, _) if alpha > 0.0 => blend(&previous.data, &frame.data, alpha),
+      _ if alpha < 0.5 => previous.data.clone(),
+      _ => frame.data.clone(),
+    };
+    push(src, data, next, Some(period));
+    next += period;
+  }
+  state.next = Some(next);
+  state.previous = Some(frame);
+}
+
+/// Emits the last frame for as long as it was displayed
+fn flush_last(state: &mut State, src: &gst::Pad) {
+  let Some(last) = state.previous.take() else {
+    return;
+  };
+  let Some(period) = state.period else {
+    push(src, last.data, last.time, last.duration);
+    return;
+  };
+  let end = last.time + last.duration.unwrap_or(period);
+  let mut next = state.next.unwrap_or(last.time);
+  while next < end {
+    push(src, last.data.clone(), next, Some(period));
+    next += period;
+  }
+}
+
+fn blend(a: &[u8], b: &[u8], alpha: f32) -> Vec<u8> {
+  a.iter()
+    .zip(b)
+    .map(|(&a, &b)| (a as f32 + (b as f32 - a as f32) * alpha + 0.5) as u8)
+    .collect()
+}
+
+/// Blends `a` and `b` along per-block motion vectors, for the instant `alpha`
+/// between them.
+///
+/// For every block of the intermediate frame, the vector `v` minimizing the
+/// difference between `a` at `p - alpha * v` and `b` at `p + (1 - alpha) * v`
+/// is searched, so every output pixel has a source in both frames.
+fn motion_blend(a: &[u8], b: &[u8], info: &gst_video::VideoInfo, alpha: f32) -> Vec<u8> {
+  let (width, height) = (info.width() as i32, info.height() as i32);
+  let stride = info.stride()[0] as usize;
+  // Byte offset of the pixel at (x, y), clamped to the frame
+  let at =
+    |x: i32, y: i32| y.clamp(0, height - 1) as usize * stride + x.clamp(0, width - 1) as usize * 4;
+  let offsets = |dx: i32, dy: i32| {
+    let back = (
+      (-alpha * dx as f32).round() as i32,
+      (-alpha * dy as f32).round() as i32,
+    );
+    (back, (back.0 + dx, back.1 + dy))
+  };
+
+  let mut out = a.to_vec();
+  for by in (0..height).step_by(BLOCK) {
+    for bx in (0..width).step_by(BLOCK) {
+      let (bw, bh) = (
+        (width - bx).min(BLOCK as i32),
+        (height - by).min(BLOCK as i32),
+      );
+      let mut best = (u32::MAX, 0, 0);
+      for dy in -SEARCH_RADIUS..=SEARCH_RADIUS {
+        for dx in -SEARCH_RADIUS..=SEARCH_RADIUS {
+          let ((ax, ay), (cx, cy)) = offsets(dx, dy);
+          // Bias towards small vectors so flat areas stay still
+          let mut cost = (dx.unsigned_abs() + dy.unsigned_abs()) * 4;
+          for y in (0..bh).step_by(2) {
+            for x in (0..bw).step_by(2) {
+              let (px, py) = (bx + x, by + y);
+              let (pa, pb) = (at(px + ax, py + ay), at(px + cx, py + cy));
+              cost += a[pa + 1].abs_diff(b[pb + 1]) as u32;
+            }
+            if cost >= best.0 {
+              break;
+            }
+          }
+          if cost < best.0 {
+            best = (cost, dx, dy);
+          }
+        }
+      }
+
+      let ((ax, ay), (cx, cy)) = offsets(best.1, best.2);
+      for y in 0..bh {
+        for x in 0..bw {
+          let (px, py) = (bx + x, by + y);
+          let (pa, pb) = (at(px + ax, py + ay), at(px + cx, py + cy));
+          let offset = py as usize * stride + px as usize * 4;
+          for channel in 0..4 {
+            let (ca, cb) = (a[pa + channel] as f32, b[pb + channel] as f32);
+            out[offset + channel] = (ca + (cb - ca) * alpha + 0.5) as u8;
+          }
+        }
+      }
+    }
+  }
+  out
+}
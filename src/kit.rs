@@ -4,13 +4,38 @@
 //! This module provides the `GstKit` struct which allows creating, controlling,
 //! and interacting with GStreamer pipelines from JavaScript/TypeScript.
 
+use crate::adaptive_bitrate::set_encoder_bitrate;
+use crate::animation::{animate, clear, AnimationOptions, Keyframe};
+use crate::audio_tempo;
+use crate::devices::permission_hint;
+use crate::frame_metadata;
+use crate::frame_stream::{BackpressurePolicy, EmissionConfig, FrameEmissionOptions, FrameStream};
+use crate::gpu_handle::{gpu_frame_handle, GpuFrameHandle};
+use crate::manager::PipelineManager;
+use crate::missing_plugins::parse_launch;
+use crate::network_impairment::{self, NetworkImpairment};
+use crate::pipeline_template::render;
+use crate::pixel_layout::{
+  crop_window, image_data, mat_frame, pack_rows, FrameCrop, ImageDataFrame, ImageDataOptions,
+  MatFrame, MatOptions,
+};
+use crate::playback_loop::{self, LoopRegion};
+use crate::shared_frames::{FrameRing, SharedFramesInfo, SharedFramesOptions};
+use crate::state_wait::{parse_state, StateWait};
+use crate::timed_metadata::{self, TimedMetadata};
 use gst::prelude::*;
 use gst_app::{AppSink, AppSrc};
 use gstreamer as gst;
 use gstreamer_app as gst_app;
-use napi::{Env, Error, Result, Status};
+use gstreamer_net as gst_net;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::{AsyncTask, Buffer, Either, Either3, Float32Array};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, Result, Status, Task};
 use napi_derive::napi;
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 /// Event types that can be emitted by the pipeline
 #[napi(object)]
@@ -30,8 +55,670 @@ pub struct FrameData {
   pub data: napi::bindgen_prelude::Buffer,
   /// The name of the sink element
   pub sink_name: String,
-  /// Timestamp of the frame in nanoseconds
+  /// Timestamp of the frame in nanoseconds, or -1 if unknown
   pub timestamp: i64,
+  /// Raw video format of the frame, e.g. "RGBA", "I420" or "NV12"
+  pub format: String,
+  /// Width of the frame in pixels
+  pub width: u32,
+  /// Height of the frame in pixels
+  pub height: u32,
+  /// The planes of the frame, each with its own stride; a single plane for
+  /// packed formats such as RGBA
+  pub planes: Vec<FramePlane>,
+  /// Metadata attached to the frame with `pushSample`, if it carries any
+  pub metadata: Option<napi::bindgen_prelude::Buffer>,
+}
+
+/// A plane of a raw video frame
+#[napi(object)]
+pub struct FramePlane {
+  /// The plane's rows, including any padding at the end of each row
+  pub data: napi::bindgen_prelude::Buffer,
+  /// Bytes from the start of one row to the start of the next
+  pub stride: u32,
+  /// Width of the plane in samples (half the frame width for the chroma of I420)
+  pub width: u32,
+  /// Number of rows of the plane
+  pub height: u32,
+  /// Byte offset of the plane in `FrameData.data`
+  pub offset: u32,
+}
+
+/// Options for `pullFrame`
+#[napi(object)]
+pub struct PullFrameOptions {
+  /// Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  pub timeout_ms: Option<u32>,
+  /// Remove the padding at the end of each row, so every plane's stride is
+  /// its row size and `data` holds the planes back to back (default: false)
+  pub packed: Option<bool>,
+  /// Only return this part of the frame, packed; the origin is moved onto
+  /// the chroma grid of subsampled formats (even coordinates for 4:2:0)
+  pub crop: Option<FrameCrop>,
+}
+
+/// Statistics of a single pipeline element
+#[napi(object)]
+pub struct ElementStats {
+  /// The name of the element
+  pub name: String,
+  /// The factory the element was created from (e.g. "queue", "x264enc")
+  pub factory: Option<String>,
+  /// Negotiated caps of each linked source pad
+  pub caps: Vec<String>,
+  /// Buffers currently held, for queue elements
+  pub queue_buffers: Option<i64>,
+  /// Bytes currently held, for queue elements
+  pub queue_bytes: Option<i64>,
+  /// Nanoseconds of data currently held, for queue elements
+  pub queue_time: Option<i64>,
+  /// Configured bitrate, for encoders (in the encoder's own unit)
+  pub bitrate: Option<i64>,
+  /// Frames or buffers rendered, for sinks and elements reporting QoS
+  pub rendered: Option<i64>,
+  /// Frames or buffers dropped, for sinks and elements reporting QoS
+  pub dropped: Option<i64>,
+}
+
+/// Snapshot of pipeline statistics
+#[napi(object)]
+pub struct PipelineStats {
+  /// Current pipeline state
+  pub state: String,
+  /// Per-element statistics
+  pub elements: Vec<ElementStats>,
+  /// Total frames rendered by all sinks
+  pub rendered_frames: i64,
+  /// Total frames dropped by all sinks and QoS-reporting elements
+  pub dropped_frames: i64,
+}
+
+/// Clock used to drive a pipeline
+#[napi(object)]
+pub struct ClockOptions {
+  /// Clock type: "system", "ntp", "ptp" or "net" (a remote `publishClock` server)
+  pub kind: String,
+  /// Server address, for "ntp" and "net" clocks
+  pub address: Option<String>,
+  /// Server port, for "ntp" (default: 123) and "net" clocks
+  pub port: Option<u32>,
+  /// PTP domain, for "ptp" clocks (default: 0)
+  pub domain: Option<u32>,
+  /// How long to wait for a network clock to synchronize (default: 5000)
+  pub sync_timeout_ms: Option<u32>,
+}
+
+/// Waits for a clock chosen with `useClock` to synchronize, then makes the
+/// pipeline use it
+pub struct ClockSync {
+  pipeline: gst::Pipeline,
+  clock: gst::Clock,
+  kind: String,
+  /// How long to wait for synchronization, or `None` for a local clock
+  timeout: Option<gst::ClockTime>,
+}
+
+impl Task for ClockSync {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<()> {
+    if let Some(timeout) = self.timeout {
+      self.clock.wait_for_sync(timeout).map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Clock {} did not synchronize", self.kind),
+        )
+      })?;
+    }
+    self.pipeline.use_clock(Some(&self.clock));
+    Ok(())
+  }
+
+  fn resolve(&mut self, _env: Env, _output: ()) -> Result<()> {
+    Ok(())
+  }
+}
+
+/// Audio sample pulled from an AppSink, with its parsed caps
+#[napi(object)]
+pub struct AudioSample {
+  /// The raw sample data as a buffer
+  pub data: Buffer,
+  /// Sample format (e.g. "S16LE", "F32LE")
+  pub format: String,
+  /// Sample rate in Hz
+  pub rate: u32,
+  /// Number of channels
+  pub channels: u32,
+  /// Channel layout ("interleaved" or "non-interleaved")
+  pub layout: String,
+  /// Timestamp of the sample in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+  /// One array of samples in [-1, 1] per channel, if requested
+  pub channel_data: Option<Vec<Float32Array>>,
+}
+
+/// Options for `pullAudioSample`
+#[napi(object)]
+pub struct AudioPullOptions {
+  /// Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  pub timeout_ms: Option<u32>,
+  /// Also return the samples as one Float32Array per channel (default: false)
+  pub deinterleave: Option<bool>,
+}
+
+/// Converts raw audio samples to floats in [-1, 1]
+fn samples_to_f32(format: &str, data: &[u8]) -> Option<Vec<f32>> {
+  let little = !format.ends_with("BE");
+  let convert = |bytes: &[u8], size: usize, f: fn(u64) -> f32| -> Vec<f32> {
+    bytes
+      .chunks_exact(size)
+      .map(|chunk| {
+        let value = chunk.iter().enumerate().fold(0u64, |acc, (i, &byte)| {
+          let shift = (if little { i } else { size - 1 - i }) * 8;
+          acc | (byte as u64) << shift
+        });
+        f(value)
+      })
+      .collect()
+  };
+  let samples = match format {
+    "U8" => convert(data, 1, |v| (v as f32 - 128.0) / 128.0),
+    "S8" => convert(data, 1, |v| v as u8 as i8 as f32 / 128.0),
+    "S16LE" | "S16BE" => convert(data, 2, |v| v as u16 as i16 as f32 / 32768.0),
+    "S32LE" | "S32BE" => convert(data, 4, |v| v as u32 as i32 as f32 / 2_147_483_648.0),
+    "F32LE" | "F32BE" => convert(data, 4, |v| f32::from_bits(v as u32)),
+    "F64LE" | "F64BE" => convert(data, 8, |v| f64::from_bits(v) as f32),
+    _ => return None,
+  };
+  Some(samples)
+}
+
+/// Options for `setPlaylist`
+#[napi(object)]
+pub struct PlaylistOptions {
+  /// Video sink description (e.g. "fakesink"); defaults to an automatic sink
+  pub video_sink: Option<String>,
+  /// Audio sink description (e.g. "fakesink"); defaults to an automatic sink
+  pub audio_sink: Option<String>,
+}
+
+/// Emitted when playback moves to another playlist entry
+#[napi(object)]
+#[derive(Clone)]
+pub struct TrackChange {
+  /// Index of the entry now playing
+  pub index: u32,
+  /// URI of the entry now playing
+  pub uri: String,
+}
+
+/// Callback receiving playlist track changes
+type TrackCallback = ThreadsafeFunction<TrackChange, (), TrackChange, Status, false, true>;
+
+/// Playlist entries and position of a `setPlaylist` pipeline
+#[derive(Default)]
+struct Playlist {
+  uris: Vec<String>,
+  current: usize,
+  /// Entry queued for a gapless transition, not yet started
+  queued: Option<usize>,
+}
+
+/// Turns file paths into URIs, leaving URIs untouched
+pub(crate) fn to_uri(location: &str) -> Result<String> {
+  if location.contains("://") {
+    return Ok(location.to_string());
+  }
+  std::path::absolute(location)
+    .map_err(|e| e.to_string())
+    .and_then(|path| {
+      gst::glib::filename_to_uri(path, None)
+        .map(|uri| uri.to_string())
+        .map_err(|e| e.to_string())
+    })
+    .map_err(|e| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid media location {}: {}", location, e),
+      )
+    })
+}
+
+/// Latest (processed, dropped) counters from QoS messages, by element name
+type QosCounters = Arc<Mutex<HashMap<String, (i64, i64)>>>;
+
+/// Collects the QoS messages of a pipeline as they are posted, leaving the
+/// bus untouched; stops when dropped
+struct QosWatch {
+  bus: gst::Bus,
+  handler: Option<gst::glib::SignalHandlerId>,
+}
+
+impl QosWatch {
+  fn start(pipeline: &gst::Pipeline, counters: &QosCounters) -> Option<Self> {
+    let bus = pipeline.bus()?;
+    let counters = Arc::downgrade(counters);
+    bus.enable_sync_message_emission();
+    let handler = bus.connect_sync_message(Some("qos"), move |_, msg| {
+      let (Some(counters), gst::MessageView::Qos(qos), Some(src)) =
+        (counters.upgrade(), msg.view(), msg.src())
+      else {
+        return;
+      };
+      let (processed, dropped) = qos.stats();
+      counters
+        .lock()
+        .unwrap()
+        .insert(src.name().to_string(), (processed.value(), dropped.value()));
+    });
+    Some(QosWatch {
+      bus,
+      handler: Some(handler),
+    })
+  }
+}
+
+impl Drop for QosWatch {
+  fn drop(&mut self) {
+    if let Some(handler) = self.handler.take() {
+      self.bus.disconnect(handler);
+      self.bus.disable_sync_message_emission();
+    }
+  }
+}
+
+/// Elements spliced after a tapped element by `addFrameTap` or `addQrDetector`
+#[derive(Clone)]
+struct FrameTap {
+  src_pad: gst::Pad,
+  peer: Option<gst::Pad>,
+  tee: gst::Element,
+  /// Chain fed by the tee, from its leaky queue to its appsink
+  branch: Vec<gst::Element>,
+}
+
+/// A QR code found in a video frame
+#[napi(object)]
+pub struct QrDetection {
+  /// Decoded text of the code
+  pub payload: String,
+  /// Left edge of the code's bounding box in pixels
+  pub x: i32,
+  /// Top edge of the code's bounding box in pixels
+  pub y: i32,
+  /// Width of the bounding box in pixels
+  pub width: i32,
+  /// Height of the bounding box in pixels
+  pub height: i32,
+  /// Presentation timestamp of the frame in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+}
+
+/// How long to wait for a pad to go idle to relink it
+const RELINK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Makes an element for a frame tap branch
+fn make_tap_element(factory: &str) -> Result<gst::Element> {
+  gst::ElementFactory::make(factory).build().map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Element {} is not available", factory),
+    )
+  })
+}
+
+/// Splices `tee ! queue ! ... ! appsink` after the source pad of an element
+///
+/// The tee is inserted once no data flows through the pad, so this also
+/// works while the pipeline is running. `branch` lists the elements fed by
+/// the tee after its leaky queue, ending with the appsink.
+fn splice_tap(
+  pipeline: &gst::Pipeline,
+  element_name: &str,
+  branch: Vec<gst::Element>,
+) -> Result<FrameTap> {
+  let element = pipeline.by_name(element_name).ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Element {} not found", element_name),
+    )
+  })?;
+  let src_pad = element.src_pads().into_iter().next().ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Element {} has no source pad", element_name),
+    )
+  })?;
+
+  let tee = make_tap_element("tee")?;
+  tee.set_property("allow-not-linked", true);
+  let queue = make_tap_element("queue")?;
+  queue.set_property_from_str("leaky", "downstream");
+  queue.set_property("max-size-buffers", 5u32);
+  let branch: Vec<gst::Element> = std::iter::once(queue).chain(branch).collect();
+
+  pipeline
+    .add(&tee)
+    .and_then(|_| pipeline.add_many(&branch))
+    .and_then(|_| gst::Element::link_many(&branch))
+    .and_then(|_| tee.link(&branch[0]))
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to build frame tap: {}", e),
+      )
+    })?;
+
+  let peer = src_pad.peer();
+  let tap = FrameTap {
+    src_pad: src_pad.clone(),
+    peer: peer.clone(),
+    tee: tee.clone(),
+    branch: branch.clone(),
+  };
+
+  // Splice the tee in once no data is flowing through the pad
+  let (spliced, result) = mpsc::channel();
+  src_pad.add_probe(gst::PadProbeType::IDLE, move |pad, _| {
+    let mut linked = false;
+    if let Some(tee_sink) = tee.static_pad("sink") {
+      match &peer {
+        Some(peer) => {
+          if let Some(tee_src) = tee.request_pad_simple("src_%u") {
+            if pad.unlink(peer).is_ok() {
+              linked = tee_src.link(peer).is_ok() && pad.link(&tee_sink).is_ok();
+              if !linked {
+                // Put the peer back as it was
+                let _ = tee_src.unlink(peer);
+                let _ = pad.unlink(&tee_sink);
+                let _ = pad.link(peer);
+              }
+            }
+            if !linked {
+              tee.release_request_pad(&tee_src);
+            }
+          }
+        }
+        None => linked = pad.link(&tee_sink).is_ok(),
+      }
+    }
+    if linked {
+      for element in branch.iter().rev().chain([&tee]) {
+        let _ = element.sync_state_with_parent();
+      }
+    }
+    let _ = spliced.send(linked);
+    gst::PadProbeReturn::Remove
+  });
+  if let Ok(false) = result.recv_timeout(RELINK_TIMEOUT) {
+    for element in std::iter::once(&tap.tee).chain(&tap.branch) {
+      let _ = element.set_state(gst::State::Null);
+      let _ = pipeline.remove(element);
+    }
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("Failed to splice a frame tap after {}", element_name),
+    ));
+  }
+
+  Ok(tap)
+}
+
+/// Splits a raw video sample into its planes, removing row padding if `packed`
+/// and keeping only the `crop` rectangle if given
+pub(crate) fn frame_data(
+  sample: &gst::Sample,
+  sink_name: &str,
+  packed: bool,
+  crop: Option<&FrameCrop>,
+) -> Result<FrameData> {
+  let caps = sample
+    .caps()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no caps"))?;
+  let info = gst_video::VideoInfo::from_caps(caps).map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Sample is not raw video: {}", caps),
+    )
+  })?;
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+  let map = buffer
+    .map_readable()
+    .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+  let data = map.as_slice();
+
+  // Buffers may carry their own plane layout, e.g. from hardware decoders
+  let meta = buffer.meta::<gst_video::VideoMeta>();
+  let (offsets, strides): (Vec<usize>, Vec<i32>) = match &meta {
+    Some(meta) => (meta.offset().to_vec(), meta.stride().to_vec()),
+    None => (info.offset().to_vec(), info.stride().to_vec()),
+  };
+  let format_info = info.format_info();
+  // Cropped frames are always packed, as only part of each row is copied
+  let window = crop.map(|crop| crop_window(&info, crop)).transpose()?;
+  let packed = packed || window.is_some();
+  let (x, y, frame_width, frame_height) = window.unwrap_or((0, 0, info.width(), info.height()));
+  let mut packed_data = Vec::new();
+  let planes = (0..info.n_planes() as usize)
+    .map(|plane| {
+      // The first component stored in the plane gives its subsampling
+      let component = format_info
+        .plane()
+        .iter()
+        .position(|&p| p as usize == plane)
+        .unwrap_or(0);
+      let width = format_info.scale_width(component as u8, frame_width);
+      let height = format_info.scale_height(component as u8, frame_height);
+      let stride = strides[plane].unsigned_abs();
+      let pixel_stride = format_info.pixel_stride()[component] as u32;
+      // Formats packing several pixels into one unit (e.g. v210) report no
+      // pixel stride; they keep their padding and cannot be cropped
+      if pixel_stride == 0 && window.is_some() {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Cannot crop {} frames", info.format().to_str()),
+        ));
+      }
+      let row_bytes = match pixel_stride {
+        0 => stride,
+        pixel_stride => pixel_stride * width,
+      };
+      // Position of the window in this plane, in subsampled rows and bytes
+      let skip = (y >> format_info.h_sub()[component]) as usize * stride as usize
+        + (x >> format_info.w_sub()[component]) as usize * pixel_stride as usize;
+      let start = (offsets[plane] + skip).min(data.len());
+      let end = (start + stride as usize * height as usize).min(data.len());
+      if !packed {
+        return Ok(FramePlane {
+          data: Buffer::from(data[start..end].to_vec()),
+          stride,
+          width,
+          height,
+          offset: start as u32,
+        });
+      }
+      let rows = pack_rows(
+        &data[start..end],
+        stride as usize,
+        row_bytes as usize,
+        height as usize,
+      );
+      let offset = packed_data.len() as u32;
+      packed_data.extend_from_slice(&rows);
+      Ok(FramePlane {
+        data: Buffer::from(rows),
+        stride: row_bytes.min(stride),
+        width,
+        height,
+        offset,
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  Ok(FrameData {
+    data: Buffer::from(if packed { packed_data } else { data.to_vec() }),
+    sink_name: sink_name.to_string(),
+    timestamp: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+    format: info.format().to_str().to_string(),
+    width: frame_width,
+    height: frame_height,
+    planes,
+    metadata: frame_metadata::read(buffer),
+  })
+}
+
+/// Finds and decodes the QR codes of a GRAY8 video sample
+fn detect_qr_codes(sample: &gst::Sample) -> Vec<QrDetection> {
+  let (Some(buffer), Some(info)) = (
+    sample.buffer(),
+    sample
+      .caps()
+      .and_then(|caps| gst_video::VideoInfo::from_caps(caps).ok()),
+  ) else {
+    return Vec::new();
+  };
+  let Ok(map) = buffer.map_readable() else {
+    return Vec::new();
+  };
+  let data = map.as_slice();
+  let stride = info.stride()[0] as usize;
+  let offset = info.offset()[0];
+  let timestamp = buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1);
+
+  let mut image = rqrr::PreparedImage::prepare_from_greyscale(
+    info.width() as usize,
+    info.height() as usize,
+    |x, y| data[offset + y * stride + x],
+  );
+  image
+    .detect_grids()
+    .into_iter()
+    .filter_map(|grid| {
+      let (_, payload) = grid.decode().ok()?;
+      let xs = grid.bounds.map(|p| p.x);
+      let ys = grid.bounds.map(|p| p.y);
+      let (x, y) = (*xs.iter().min()?, *ys.iter().min()?);
+      Some(QrDetection {
+        payload,
+        x,
+        y,
+        width: xs.iter().max()? - x,
+        height: ys.iter().max()? - y,
+        timestamp,
+      })
+    })
+    .collect()
+}
+
+/// Makes an AppSink always receive raw video in `format`, inserting a
+/// `videoconvert` in front of it if it has none
+fn force_output_format(pipeline: &gst::Pipeline, sink_name: &str, format: &str) -> Result<()> {
+  let appsink = pipeline
+    .by_name(sink_name)
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("AppSink {} not found", sink_name),
+      )
+    })?;
+  let caps = gst::Caps::builder("video/x-raw")
+    .field("format", format)
+    .build();
+
+  let sink_pad = appsink
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "AppSink has no sink pad"))?;
+  let peer = sink_pad.peer();
+  let has_converter = peer
+    .as_ref()
+    .and_then(|peer| peer.parent_element())
+    .and_then(|el| el.factory())
+    .map(|f| f.name() == "videoconvert")
+    .unwrap_or(false);
+  let Some(peer) = peer.filter(|_| !has_converter) else {
+    appsink.set_caps(Some(&caps));
+    return Ok(());
+  };
+
+  let convert = gst::ElementFactory::make("videoconvert")
+    .name(format!("{}_convert", sink_name))
+    .build()
+    .map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        "Element videoconvert is not available".to_string(),
+      )
+    })?;
+  pipeline.add(&convert).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add converter: {}", e),
+    )
+  })?;
+
+  // Splice the converter in once no data is flowing into the sink
+  let (spliced, result) = mpsc::channel();
+  let inserted = convert.clone();
+  peer.add_probe(gst::PadProbeType::IDLE, move |peer, _| {
+    let mut linked = false;
+    if let (Some(convert_sink), Some(convert_src)) =
+      (inserted.static_pad("sink"), inserted.static_pad("src"))
+    {
+      if peer.unlink(&sink_pad).is_ok() {
+        linked = peer.link(&convert_sink).is_ok() && convert_src.link(&sink_pad).is_ok();
+        if !linked {
+          // Put the sink back as it was
+          let _ = peer.unlink(&convert_sink);
+          let _ = convert_src.unlink(&sink_pad);
+          let _ = peer.link(&sink_pad);
+        }
+      }
+    }
+    if linked {
+      appsink.set_caps(Some(&caps));
+      let _ = inserted.sync_state_with_parent();
+    }
+    let _ = spliced.send(linked);
+    gst::PadProbeReturn::Remove
+  });
+  if let Ok(false) = result.recv_timeout(RELINK_TIMEOUT) {
+    let _ = convert.set_state(gst::State::Null);
+    let _ = pipeline.remove(&convert);
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("Failed to link a converter in front of {}", sink_name),
+    ));
+  }
+  Ok(())
+}
+
+/// Reads a numeric property as i64, if the element has it
+fn property_i64(element: &gst::Element, name: &str) -> Option<i64> {
+  element.find_property(name)?;
+  element
+    .property_value(name)
+    .transform::<i64>()
+    .ok()?
+    .get::<i64>()
+    .ok()
+}
+
+/// Reads the rendered/dropped counters of a sink's "stats" property
+fn sink_stats(element: &gst::Element) -> Option<(i64, i64)> {
+  let pspec = element.find_property("stats")?;
+  if pspec.value_type() != gst::Structure::static_type() {
+    return None;
+  }
+  let stats = element.property::<gst::Structure>("stats");
+  let rendered = stats.get::<u64>("rendered").ok()?;
+  let dropped = stats.get::<u64>("dropped").ok()?;
+  Some((rendered as i64, dropped as i64))
 }
 
 /// Main GStreamer wrapper class for Node.js
@@ -45,6 +732,22 @@ pub struct GstKit {
   pipeline: Mutex<Option<gst::Pipeline>>,
   /// Flag to control frame emission
   emit_frames: Arc<Mutex<bool>>,
+  /// Latest (processed, dropped) counters from QoS messages, by element name
+  qos: QosCounters,
+  /// Collector filling `qos` from the pipeline bus
+  qos_watch: Mutex<Option<QosWatch>>,
+  /// Network time provider publishing the pipeline clock
+  time_provider: Mutex<Option<gst_net::NetTimeProvider>>,
+  /// Active frame taps, by tapped element name
+  frame_taps: Mutex<HashMap<String, FrameTap>>,
+  /// Backpressure settings from `startFrameEmission`, by sink name
+  emission_configs: Mutex<HashMap<String, EmissionConfig>>,
+  /// Playlist of a `setPlaylist` pipeline
+  playlist: Arc<Mutex<Playlist>>,
+  /// Callback receiving playlist track changes
+  track_callback: Arc<Mutex<Option<TrackCallback>>>,
+  /// Region looped by `setLoopRegion`
+  loop_region: Mutex<Option<LoopRegion>>,
 }
 
 /// Drop implementation to ensure proper cleanup of GStreamer resources
@@ -62,6 +765,156 @@ impl Drop for GstKit {
   }
 }
 
+impl GstKit {
+  /// Finds the AppSink named `name` in the current pipeline
+  pub(crate) fn app_sink(&self, name: &str) -> Result<AppSink> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    pipeline
+      .by_name(name)
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("AppSink {} not found", name),
+        )
+      })
+  }
+
+  /// Makes the AppSink named `name` receive raw video in `format`
+  pub(crate) fn force_sink_format(&self, name: &str, format: &str) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    force_output_format(pipeline, name, format)
+  }
+
+  /// Finds the AppSrc named `name` in the current pipeline
+  pub(crate) fn app_src(&self, name: &str) -> Result<AppSrc> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    pipeline
+      .by_name(name)
+      .and_then(|el| el.downcast::<AppSrc>().ok())
+      .ok_or_else(|| Error::new(Status::GenericFailure, format!("AppSrc {} not found", name)))
+  }
+
+  /// Creates a kit managing a pipeline built elsewhere
+  ///
+  /// # Example
+  /// ```rust,ignore
+  /// let pipeline = gst::Pipeline::new();
+  /// pipeline.add_many([&src, &sink])?;
+  /// let kit = GstKit::from_pipeline(pipeline)?;
+  /// ```
+  pub fn from_pipeline(pipeline: gst::Pipeline) -> Result<Self> {
+    let kit = GstKit::new()?;
+    kit.adopt_pipeline(pipeline);
+    Ok(kit)
+  }
+
+  /// Makes `pipeline` the pipeline of the kit, in whatever state it is;
+  /// the previous pipeline is stopped
+  pub fn adopt_pipeline(&self, pipeline: gst::Pipeline) {
+    *self.qos_watch.lock().unwrap() = QosWatch::start(&pipeline, &self.qos);
+    let previous = self.pipeline.lock().unwrap().replace(pipeline);
+    if let Some(previous) = previous {
+      let _ = previous.set_state(gst::State::Null);
+    }
+    self.qos.lock().unwrap().clear();
+    self.frame_taps.lock().unwrap().clear();
+    self.emission_configs.lock().unwrap().clear();
+    *self.loop_region.lock().unwrap() = None;
+    *self.time_provider.lock().unwrap() = None;
+    *self.playlist.lock().unwrap() = Playlist::default();
+  }
+
+  /// Makes `element` the pipeline of the kit, wrapping it in a new pipeline
+  /// unless it is one; `setPipeline` never wraps
+  pub fn adopt_element(&self, element: gst::Element) -> Result<()> {
+    let pipeline = match element.downcast::<gst::Pipeline>() {
+      Ok(pipeline) => pipeline,
+      Err(element) => {
+        if element.parent().is_some() {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Element {} already belongs to a bin", element.name()),
+          ));
+        }
+        let pipeline = gst::Pipeline::new();
+        pipeline.add(&element).map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to wrap {}: {}", element.name(), e),
+          )
+        })?;
+        pipeline
+      }
+    };
+    self.adopt_pipeline(pipeline);
+    Ok(())
+  }
+
+  /// Returns the current pipeline
+  pub(crate) fn current_pipeline(&self) -> Result<gst::Pipeline> {
+    self.pipeline.lock().unwrap().clone().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })
+  }
+
+  /// Finds the element named `target` in the current pipeline, or the pad
+  /// of an element for a target such as "mix.sink_1"
+  fn pipeline_object(&self, target: &str) -> Result<gst::Object> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    let (element_name, pad_name) = match target.split_once('.') {
+      Some((element_name, pad_name)) => (element_name, Some(pad_name)),
+      None => (target, None),
+    };
+    let element = pipeline.by_name(element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+    match pad_name {
+      Some(pad_name) => element
+        .static_pad(pad_name)
+        .or_else(|| {
+          element
+            .pads()
+            .into_iter()
+            .find(|pad| pad.name() == pad_name)
+        })
+        .map(|pad| pad.upcast())
+        .ok_or_else(|| Error::new(Status::GenericFailure, format!("Pad {} not found", target))),
+      None => Ok(element.upcast()),
+    }
+  }
+}
+
 #[napi]
 impl GstKit {
   /// Creates a new `GstKit` instance and initializes GStreamer
@@ -84,6 +937,14 @@ impl GstKit {
     Ok(GstKit {
       pipeline: Mutex::new(None),
       emit_frames: Arc::new(Mutex::new(false)),
+      qos: Arc::new(Mutex::new(HashMap::new())),
+      qos_watch: Mutex::new(None),
+      time_provider: Mutex::new(None),
+      frame_taps: Mutex::new(HashMap::new()),
+      emission_configs: Mutex::new(HashMap::new()),
+      playlist: Arc::new(Mutex::new(Playlist::default())),
+      track_callback: Arc::new(Mutex::new(None)),
+      loop_region: Mutex::new(None),
     })
   }
 
@@ -98,22 +959,61 @@ impl GstKit {
   /// ```
   #[napi]
   pub fn set_pipeline(&self, pipeline_string: String) -> Result<()> {
-    let element = gst::parse::launch(&pipeline_string).map_err(|e| {
-      Error::new(
-        Status::GenericFailure,
-        format!("Failed to parse pipeline: {}", e),
-      )
-    })?;
+    let pipeline = parse_launch(&pipeline_string)?
+      .downcast::<gst::Pipeline>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          "Provided string is not a valid pipeline".to_string(),
+        )
+      })?;
+    self.adopt_pipeline(pipeline);
+    Ok(())
+  }
 
-    let pipeline_cast = element.downcast::<gst::Pipeline>().map_err(|_| {
-      Error::new(
-        Status::GenericFailure,
-        "Provided string is not a valid pipeline".to_string(),
-      )
-    })?;
+  /// Sets the pipeline from a launch string template with `{name}` placeholders
+  ///
+  /// Every placeholder must have a value. Values are escaped so each stays
+  /// one token of the launch string; `{{` and `}}` stand for literal braces.
+  ///
+  /// # Arguments
+  /// * `template` - A pipeline description with `{name}` placeholders
+  /// * `vars` - The value of every placeholder
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipelineTemplate(
+  ///   "v4l2src device={device} ! video/x-raw,width={width},height={height} ! videoconvert ! appsink name=sink",
+  ///   { device: "/dev/video0", width: 1280, height: 720 },
+  /// );
+  /// ```
+  #[napi]
+  pub fn set_pipeline_template(
+    &self,
+    template: String,
+    vars: HashMap<String, Either3<String, f64, bool>>,
+  ) -> Result<()> {
+    self.set_pipeline(render(&template, &vars)?)
+  }
 
-    let mut pipeline = self.pipeline.lock().unwrap();
-    *pipeline = Some(pipeline_cast);
+  /// Takes over a pipeline of a `PipelineManager`, which stops managing it
+  ///
+  /// The pipeline keeps its state, so a playing pipeline keeps playing and
+  /// can be controlled and tapped through the kit from then on.
+  ///
+  /// # Arguments
+  /// * `manager` - The manager owning the pipeline
+  /// * `id` - The id the pipeline was added under
+  ///
+  /// # Example
+  /// ```javascript
+  /// manager.addPipeline("cam1", "v4l2src ! videoconvert ! appsink name=sink");
+  /// kit.adoptPipeline(manager, "cam1");
+  /// const frame = kit.pullFrame("sink");
+  /// ```
+  #[napi(js_name = "adoptPipeline")]
+  pub fn adopt_pipeline_from(&self, manager: &PipelineManager, id: String) -> Result<()> {
+    self.adopt_pipeline(manager.take_pipeline(&id)?);
     Ok(())
   }
 
@@ -157,17 +1057,31 @@ impl GstKit {
   ///
   /// # Arguments
   /// * `sink_names` - Optional list of sink names to emit frames from. If empty, emits from all AppSinks.
+  /// * `preferred_output_format` - Optional raw video format (e.g. "RGBA") the sinks
+  ///   are forced to receive; a `videoconvert` is inserted in front of them if needed.
+  /// * `options` - Optional backpressure policy and queue length, used by the
+  ///   `frames()` streams of the sinks. For `pullSample` callers the sinks
+  ///   hold at most `maxQueueLength` frames: "block" stalls the pipeline when
+  ///   they are full, the other policies drop the oldest frame.
   ///
   /// # Example
   /// ```javascript
   /// // Emit frames from all sinks
   /// kit.startFrameEmission();
   ///
-  /// // Emit frames from specific sink
-  /// kit.startFrameEmission(["mysink"]);
+  /// // Emit RGBA frames from specific sink
+  /// kit.startFrameEmission(["mysink"], "RGBA");
+  ///
+  /// // Hand every third frame to a slow consumer, never stalling the pipeline
+  /// kit.startFrameEmission(["mysink"], null, { policy: "sampleEveryN", sampleEvery: 3 });
   /// ```
   #[napi]
-  pub fn start_frame_emission(&self, sink_names: Option<Vec<String>>) -> Result<()> {
+  pub fn start_frame_emission(
+    &self,
+    sink_names: Option<Vec<String>>,
+    preferred_output_format: Option<String>,
+    options: Option<FrameEmissionOptions>,
+  ) -> Result<()> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -196,6 +1110,37 @@ impl GstKit {
       ));
     }
 
+    if let Some(format) = &preferred_output_format {
+      if gst_video::VideoFormat::from_string(format) == gst_video::VideoFormat::Unknown {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Unknown video format: {}", format),
+        ));
+      }
+      for sink in &sinks {
+        force_output_format(pipeline, sink, format)?;
+      }
+    }
+
+    if let Some(options) = options {
+      let config = EmissionConfig::parse(options)?;
+      let mut configs = self.emission_configs.lock().unwrap();
+      for sink in &sinks {
+        let appsink = pipeline
+          .by_name(sink)
+          .and_then(|el| el.downcast::<AppSink>().ok())
+          .ok_or_else(|| {
+            Error::new(
+              Status::GenericFailure,
+              format!("AppSink {} not found", sink),
+            )
+          })?;
+        appsink.set_max_buffers(config.max_queue_length as u32);
+        appsink.set_drop(config.policy != BackpressurePolicy::Block);
+        configs.insert(sink.clone(), config);
+      }
+    }
+
     // Start emitting frames
     {
       let mut emit = self.emit_frames.lock().unwrap();
@@ -220,21 +1165,121 @@ impl GstKit {
     Ok(())
   }
 
-  /// Starts monitoring the pipeline bus for events
+  /// Publishes the frames of an AppSink into a shared-memory ring buffer
   ///
-  /// This will call the event callback for various pipeline events:
-  /// - "eos": End of stream
-  /// - "error": Pipeline error
-  /// - "warning": Pipeline warning
-  /// - "state-changed": Pipeline state changed
-  /// - "element": Element message
+  /// Every frame reaching the sink is copied into the next slot of the ring
+  /// named `name`, where other processes read it without crossing into
+  /// JavaScript; see the `shared_frames` module for the layout and the read
+  /// protocol. The sink's callbacks are taken over, so `pullSample` no longer
+  /// returns its frames. Use `startFrameEmission` first to pick the format.
+  ///
+  /// # Arguments
+  /// * `sink_name` - The name of the AppSink element
+  /// * `name` - Name of the shared memory object
+  /// * `options` - Optional slot count and maximum frame size
+  ///
+  /// # Returns
+  /// * `Result<SharedFramesInfo>` - Path and geometry of the ring
   ///
   /// # Example
   /// ```javascript
-  /// kit.startBusMonitoring();
+  /// kit.startFrameEmission(["sink"], "RGB");
+  /// const ring = kit.publishSharedFrames("sink", "camera0", { slots: 8 });
+  /// spawn("python3", ["infer.py", ring.name]);
   /// ```
   #[napi]
-  pub fn start_bus_monitoring(&self) -> Result<()> {
+  pub fn publish_shared_frames(
+    &self,
+    sink_name: String,
+    name: String,
+    options: Option<SharedFramesOptions>,
+  ) -> Result<SharedFramesInfo> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    let appsink = pipeline
+      .by_name(&sink_name)
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("AppSink {} not found", sink_name),
+        )
+      })?;
+
+    let ring = FrameRing::create(&name, options)?;
+    let info = ring.info(&name);
+    let ring = Mutex::new(ring);
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          ring
+            .lock()
+            .unwrap()
+            .publish(&sample)
+            .map_err(|_| gst::FlowError::Error)?;
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+    Ok(info)
+  }
+
+  /// Stops publishing an AppSink's frames to shared memory and removes the ring
+  ///
+  /// Readers that still have the ring mapped keep their mapping, but no new
+  /// frames arrive. The sink's frames can be pulled again afterwards.
+  ///
+  /// # Arguments
+  /// * `sink_name` - The name of the AppSink element
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.stopSharedFrames("sink");
+  /// ```
+  #[napi]
+  pub fn stop_shared_frames(&self, sink_name: String) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+    let appsink = pipeline
+      .by_name(&sink_name)
+      .and_then(|el| el.downcast::<AppSink>().ok())
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("AppSink {} not found", sink_name),
+        )
+      })?;
+    // Replacing the callbacks drops the ring, which unlinks it
+    appsink.set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    Ok(())
+  }
+
+  /// Starts monitoring the pipeline bus for events
+  ///
+  /// This will call the event callback for various pipeline events:
+  /// - "eos": End of stream
+  /// - "error": Pipeline error
+  /// - "warning": Pipeline warning
+  /// - "state-changed": Pipeline state changed
+  /// - "element": Element message
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.startBusMonitoring();
+  /// ```
+  #[napi]
+  pub fn start_bus_monitoring(&self) -> Result<()> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let _pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -263,7 +1308,11 @@ impl GstKit {
       res.map_err(|e| {
         Error::new(
           Status::GenericFailure,
-          format!("Failed to set state to Playing: {}", e),
+          format!(
+            "Failed to set state to Playing: {}{}",
+            e,
+            permission_hint(pipeline)
+          ),
         )
       })?;
       Ok(())
@@ -302,6 +1351,78 @@ impl GstKit {
     }
   }
 
+  /// Starts playback and resolves once the pipeline is playing
+  ///
+  /// Unlike `play`, which returns as soon as the change is started, this
+  /// rejects with the error an element posted if it fails to start, e.g.
+  /// because a device is busy or caps cannot be negotiated.
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Reject if the pipeline is not playing by then
+  ///   (default: wait indefinitely)
+  ///
+  /// # Example
+  /// ```javascript
+  /// await kit.playAsync(5000);
+  /// ```
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn play_async(&self, timeout_ms: Option<u32>) -> Result<AsyncTask<StateWait>> {
+    let pipeline = self.current_pipeline()?;
+    Ok(AsyncTask::new(StateWait::change(
+      pipeline,
+      gst::State::Playing,
+      timeout_ms,
+    )))
+  }
+
+  /// Pauses the pipeline and resolves once it is paused, which for
+  /// non-live pipelines means the first frame has reached every sink
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Reject if the pipeline is not paused by then
+  ///   (default: wait indefinitely)
+  ///
+  /// # Example
+  /// ```javascript
+  /// await kit.pauseAsync();
+  /// const duration = kit.getDuration();
+  /// ```
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn pause_async(&self, timeout_ms: Option<u32>) -> Result<AsyncTask<StateWait>> {
+    let pipeline = self.current_pipeline()?;
+    Ok(AsyncTask::new(StateWait::change(
+      pipeline,
+      gst::State::Paused,
+      timeout_ms,
+    )))
+  }
+
+  /// Resolves once the pipeline reaches a state, without changing it
+  ///
+  /// # Arguments
+  /// * `state` - "Null", "Ready", "Paused" or "Playing", as returned by
+  ///   `getState`
+  /// * `timeout_ms` - Reject if the state is not reached by then (default:
+  ///   wait indefinitely)
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.play();
+  /// await kit.waitForState("Playing", 2000);
+  /// ```
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn wait_for_state(
+    &self,
+    state: String,
+    timeout_ms: Option<u32>,
+  ) -> Result<AsyncTask<StateWait>> {
+    let target = parse_state(&state)?;
+    let pipeline = self.current_pipeline()?;
+    Ok(AsyncTask::new(StateWait::wait(
+      pipeline, target, timeout_ms,
+    )))
+  }
+
   /// Stops the pipeline and sets it to NULL state
   ///
   /// # Example
@@ -394,22 +1515,37 @@ impl GstKit {
     }
   }
 
-  /// Pushes a buffer to a named AppSrc element
+  /// Pulls a raw video frame from a named AppSink element, split into its planes
+  ///
+  /// Unlike `pullSample`, the frame keeps its negotiated format and comes with
+  /// the stride and offset of every plane, so planar YUV (e.g. I420 or NV12)
+  /// can be consumed without converting to RGBA first. Use
+  /// `startFrameEmission` to force a format on the sink, and the `packed`
+  /// option to drop the row padding sinks often negotiate.
   ///
   /// # Arguments
-  /// * `element_name` - The name of the AppSrc element
-  /// * `data` - The data to push as a Buffer
+  /// * `element_name` - The name of the AppSink element
+  /// * `options` - Optional timeout and row packing
+  ///
+  /// # Returns
+  /// * `Result<Option<FrameData>>` - The frame, or null if no sample is available
   ///
   /// # Example
   /// ```javascript
-  /// kit.pushSample("mysrc", Buffer.from([0, 1, 2, 3]));
+  /// kit.setPipeline("videotestsrc ! video/x-raw,format=I420 ! appsink name=sink");
+  /// kit.play();
+  /// const frame = kit.pullFrame("sink");
+  /// if (frame) {
+  ///   const [y, u, v] = frame.planes;
+  ///   console.log(frame.format, y.stride, u.width, v.height);
+  /// }
   /// ```
   #[napi]
-  pub fn push_sample(
+  pub fn pull_frame(
     &self,
     element_name: String,
-    data: napi::bindgen_prelude::Buffer,
-  ) -> Result<()> {
+    options: Option<PullFrameOptions>,
+  ) -> Result<Option<FrameData>> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -418,70 +1554,65 @@ impl GstKit {
       )
     })?;
 
-    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
-      Error::new(
-        Status::GenericFailure,
-        format!("Element {} not found", element_name),
-      )
-    })?;
-
-    let appsrc = element.downcast::<AppSrc>().map_err(|_| {
-      Error::new(
-        Status::GenericFailure,
-        format!("Element {} is not an AppSrc", element_name),
-      )
-    })?;
-
-    let buffer = gst::Buffer::from_mut_slice(data.to_vec());
-    appsrc.push_buffer(buffer).map_err(|e| {
-      Error::new(
-        Status::GenericFailure,
-        format!("Failed to push buffer: {}", e),
-      )
-    })?;
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
 
-    Ok(())
+    let options = options.unwrap_or(PullFrameOptions {
+      timeout_ms: None,
+      packed: None,
+      crop: None,
+    });
+    let timeout = options.timeout_ms.unwrap_or(100);
+    let packed = options.packed.unwrap_or(false);
+    match appsink.try_pull_sample(gst::ClockTime::from_mseconds(timeout as u64)) {
+      Some(sample) => frame_data(&sample, &element_name, packed, options.crop.as_ref()).map(Some),
+      None => Ok(None),
+    }
   }
 
-  /// Returns the current state of the pipeline
+  /// Returns the frames of a named AppSink element as an async iterable
   ///
-  /// # Returns
-  /// * `Result<String>` - The current state as a string ("Playing", "Paused", "Null", "Ready")
+  /// Frames arrive as `FrameData`, like with `pullFrame`. They are queued
+  /// until the loop asks for them; when it falls behind, the backpressure
+  /// policy decides which frames are lost (by default the oldest queued
+  /// one). The stream takes over the sink's callbacks, so use one stream per
+  /// sink and no `pullSample` calls alongside it. Leaving the loop or
+  /// calling `close()` on the stream hands the sink back.
   ///
-  /// # Example
-  /// ```javascript
-  /// const state = kit.getState();
-  /// console.log("Current state:", state);
-  /// ```
-  #[napi]
-  pub fn get_state(&self) -> Result<String> {
-    let pipeline_guard = self.pipeline.lock().unwrap();
-    if let Some(pipeline) = &*pipeline_guard {
-      let (success, state, _pending): (
-        std::result::Result<gst::StateChangeSuccess, gst::StateChangeError>,
-        gst::State,
-        gst::State,
-      ) = gst::prelude::ElementExt::state(pipeline, gst::ClockTime::NONE);
-
-      if success.is_ok() {
-        return Ok(format!("{:?}", state));
-      }
-    }
-    Ok("Null".to_string())
-  }
-
-  /// Returns the current position of the pipeline in nanoseconds
+  /// # Arguments
+  /// * `element_name` - The name of the AppSink element
+  /// * `options` - Optional backpressure policy and queue length; defaults to
+  ///   the settings given to `startFrameEmission` for the sink
   ///
   /// # Returns
-  /// * `Result<i64>` - Current position in nanoseconds
+  /// * `Result<FrameStream>` - An async iterable ending at end of stream
   ///
   /// # Example
   /// ```javascript
-  /// const position = kit.getPosition();
-  /// console.log("Position (ns):", position);
+  /// kit.play();
+  /// for await (const frame of kit.frames("sink", { maxQueueLength: 2 })) {
+  ///   await detector.process(frame.data, frame.width, frame.height);
+  /// }
   /// ```
   #[napi]
-  pub fn get_position(&self) -> Result<i64> {
+  pub fn frames(
+    &self,
+    element_name: String,
+    options: Option<FrameEmissionOptions>,
+  ) -> Result<FrameStream> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -490,28 +1621,61 @@ impl GstKit {
       )
     })?;
 
-    let position = pipeline.query_position::<gst::ClockTime>().ok_or_else(|| {
-      Error::new(
-        Status::GenericFailure,
-        "Failed to query position".to_string(),
-      )
-    })?;
-
-    Ok(position.nseconds() as i64)
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
+    let config = match options {
+      Some(options) => EmissionConfig::parse(options)?,
+      None => self
+        .emission_configs
+        .lock()
+        .unwrap()
+        .get(&element_name)
+        .copied()
+        .unwrap_or_default(),
+    };
+    Ok(FrameStream::attach(appsink, config))
   }
 
-  /// Returns the duration of the pipeline in nanoseconds
+  /// Pulls a frame from a named AppSink element laid out like `ImageData`
+  ///
+  /// The pixels come as a tightly packed RGBA `Uint8ClampedArray`, whatever
+  /// the row padding and channel order of the sink (RGBA, BGRA, ARGB, RGBx,
+  /// RGB, GRAY8, ...), ready for `new ImageData(data, width, height)` or
+  /// `createImageBitmap` in Electron and other HTML canvas hosts.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the AppSink element
+  /// * `options` - Optional timeout and alpha premultiplication
   ///
   /// # Returns
-  /// * `Result<i64>` - Duration in nanoseconds, or -1 if unknown
+  /// * `Result<Option<ImageDataFrame>>` - The frame, or null if no sample is available
   ///
   /// # Example
   /// ```javascript
-  /// const duration = kit.getDuration();
-  /// console.log("Duration (ns):", duration);
+  /// const frame = kit.pullSampleAsImageData("sink");
+  /// if (frame) {
+  ///   ctx.putImageData(new ImageData(frame.data, frame.width, frame.height), 0, 0);
+  /// }
   /// ```
   #[napi]
-  pub fn get_duration(&self) -> Result<i64> {
+  pub fn pull_sample_as_image_data(
+    &self,
+    element_name: String,
+    options: Option<ImageDataOptions>,
+  ) -> Result<Option<ImageDataFrame>> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -520,28 +1684,61 @@ impl GstKit {
       )
     })?;
 
-    let duration = pipeline.query_duration::<gst::ClockTime>().ok_or_else(|| {
-      Error::new(
-        Status::GenericFailure,
-        "Failed to query duration".to_string(),
-      )
-    })?;
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
 
-    Ok(duration.nseconds() as i64)
+    let options = options.unwrap_or(ImageDataOptions {
+      timeout_ms: None,
+      premultiplied: None,
+    });
+    let timeout = options.timeout_ms.unwrap_or(100);
+    match appsink.try_pull_sample(gst::ClockTime::from_mseconds(timeout as u64)) {
+      Some(sample) => image_data(&sample, options.premultiplied.unwrap_or(false)).map(Some),
+      None => Ok(None),
+    }
   }
 
-  /// Seeks to a specific position in the pipeline
+  /// Pulls a frame from a named AppSink element laid out for OpenCV and numpy
+  ///
+  /// The pixels come tightly packed in BGR order, whatever the row padding and
+  /// channel order of the sink, with the shape, strides and matrix type that
+  /// `cv.Mat` and `ndarray` consumers expect, so no channel shuffle is needed
+  /// in JavaScript.
   ///
   /// # Arguments
-  /// * `position_ns` - Position to seek to in nanoseconds
+  /// * `element_name` - The name of the AppSink element
+  /// * `options` - Optional timeout and RGB channel order
+  ///
+  /// # Returns
+  /// * `Result<Option<MatFrame>>` - The frame, or null if no sample is available
   ///
   /// # Example
   /// ```javascript
-  /// // Seek to 5 seconds (5 * 1_000_000_000 nanoseconds)
-  /// kit.seek(5_000_000_000);
+  /// const frame = kit.pullSampleAsMat("sink");
+  /// if (frame) {
+  ///   const mat = new cv.Mat(frame.rows, frame.cols, frame.matType);
+  ///   mat.data.set(frame.data);
+  /// }
   /// ```
   #[napi]
-  pub fn seek(&self, position_ns: i64) -> Result<()> {
+  pub fn pull_sample_as_mat(
+    &self,
+    element_name: String,
+    options: Option<MatOptions>,
+  ) -> Result<Option<MatFrame>> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -550,32 +1747,66 @@ impl GstKit {
       )
     })?;
 
-    let seek_pos = gst::ClockTime::from_nseconds(position_ns as u64);
-    let res = pipeline.seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, seek_pos);
-
-    res.map_err(|e| Error::new(Status::GenericFailure, format!("Failed to seek: {}", e)))?;
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
 
-    Ok(())
+    let options = options.unwrap_or(MatOptions {
+      timeout_ms: None,
+      rgb: None,
+    });
+    let timeout = options.timeout_ms.unwrap_or(100);
+    match appsink.try_pull_sample(gst::ClockTime::from_mseconds(timeout as u64)) {
+      Some(sample) => mat_frame(&sample, options.rgb.unwrap_or(false)).map(Some),
+      None => Ok(None),
+    }
   }
 
-  /// Sets a property on a named element in the pipeline
+  /// Pulls a frame from a named AppSink element as a GPU handle, without
+  /// downloading it to system memory
+  ///
+  /// The sink must negotiate DMABuf, GL or D3D11 memory, e.g. with
+  /// `appsink caps="video/x-raw(memory:DMABuf)"` after a hardware decoder or
+  /// `glupload ! appsink caps="video/x-raw(memory:GLMemory)"`, or receive the
+  /// IOSurface-backed buffers of an `applemedia` decoder such as `vtdec`.
+  /// D3D11 textures are only exported when allocated as shared resources.
+  /// Other memory types are rejected.
   ///
   /// # Arguments
-  /// * `element_name` - The name of the element
-  /// * `property_name` - The name of the property
-  /// * `value` - The value to set (as a string)
+  /// * `element_name` - The name of the AppSink element
+  /// * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  ///
+  /// # Returns
+  /// * `Result<Option<GpuFrameHandle>>` - The handles and layout, or null if no sample is available
   ///
   /// # Example
   /// ```javascript
-  /// kit.setProperty("mysrc", "is-live", "true");
+  /// kit.setPipeline("filesrc location=in.mp4 ! parsebin ! vah264dec ! appsink name=sink caps=video/x-raw(memory:DMABuf)");
+  /// kit.play();
+  /// const frame = kit.pullSampleAsGpuHandle("sink", 1000);
+  /// if (frame) {
+  ///   renderer.importDmaBuf(frame.handles, frame.format, frame.strides, frame.offsets);
+  ///   frame.handles.forEach((fd) => fs.closeSync(fd));
+  /// }
   /// ```
   #[napi]
-  pub fn set_property(
+  pub fn pull_sample_as_gpu_handle(
     &self,
     element_name: String,
-    property_name: String,
-    value: String,
-  ) -> Result<()> {
+    #[napi(ts_arg_type = "number | undefined")] timeout_ms: Option<u32>,
+  ) -> Result<Option<GpuFrameHandle>> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -584,34 +1815,1126 @@ impl GstKit {
       )
     })?;
 
-    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
-      Error::new(
-        Status::GenericFailure,
-        format!("Element {} not found", element_name),
-      )
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
+
+    let timeout = timeout_ms.unwrap_or(100);
+    match appsink.try_pull_sample(gst::ClockTime::from_mseconds(timeout as u64)) {
+      Some(sample) => gpu_frame_handle(&sample).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  /// Pulls an audio sample from a named AppSink element, with its format info
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the AppSink element
+  /// * `options` - Optional timeout and deinterleaving
+  ///
+  /// # Returns
+  /// * `Result<Option<AudioSample>>` - The sample and its parsed caps, or null if no sample is available
+  ///
+  /// # Example
+  /// ```javascript
+  /// const sample = kit.pullAudioSample("audiosink", { deinterleave: true });
+  /// if (sample) {
+  ///   const left = sample.channelData[0];
+  ///   console.log(sample.rate, sample.channels, left.length);
+  /// }
+  /// ```
+  #[napi]
+  pub fn pull_audio_sample(
+    &self,
+    element_name: String,
+    options: Option<AudioPullOptions>,
+  ) -> Result<Option<AudioSample>> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let appsink = pipeline
+      .by_name(&element_name)
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} not found", element_name),
+        )
+      })?
+      .downcast::<AppSink>()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} is not an AppSink", element_name),
+        )
+      })?;
+
+    let (timeout_ms, deinterleave) = match options {
+      Some(options) => (options.timeout_ms, options.deinterleave),
+      None => (None, None),
+    };
+    let timeout = gst::ClockTime::from_mseconds(timeout_ms.unwrap_or(100) as u64);
+    let Some(sample) = appsink.try_pull_sample(timeout) else {
+      return Ok(None);
+    };
+
+    let structure = sample
+      .caps()
+      .and_then(|caps| caps.structure(0))
+      .filter(|s| s.name() == "audio/x-raw")
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Element {} does not receive raw audio", element_name),
+        )
+      })?;
+    let format = structure.get::<String>("format").unwrap_or_default();
+    let rate = structure.get::<i32>("rate").unwrap_or(0) as u32;
+    let channels = structure.get::<i32>("channels").unwrap_or(1).max(1) as u32;
+    let layout = structure
+      .get::<String>("layout")
+      .unwrap_or_else(|_| "interleaved".to_string());
+
+    let buffer = sample
+      .buffer()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+    let map = buffer
+      .map_readable()
+      .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+
+    let channel_data = if deinterleave.unwrap_or(false) {
+      let samples = samples_to_f32(&format, map.as_slice()).ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Cannot convert {} samples to floats", format),
+        )
+      })?;
+      let channels = channels as usize;
+      let frames = samples.len() / channels;
+      let channel = |c: usize| -> Vec<f32> {
+        if layout == "interleaved" {
+          samples.iter().skip(c).step_by(channels).copied().collect()
+        } else {
+          samples[c * frames..(c + 1) * frames].to_vec()
+        }
+      };
+      Some(
+        (0..channels)
+          .map(|c| Float32Array::new(channel(c)))
+          .collect(),
+      )
+    } else {
+      None
+    };
+
+    Ok(Some(AudioSample {
+      data: Buffer::from(map.as_slice().to_vec()),
+      format,
+      rate,
+      channels,
+      layout,
+      timestamp: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+      channel_data,
+    }))
+  }
+
+  /// Pushes a buffer to a named AppSrc element
+  ///
+  /// Metadata attached to the buffer travels with the frame and is returned
+  /// as `metadata` by `pullFrame`, `frames` and planar frame taps.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the AppSrc element
+  /// * `data` - The data to push as a Buffer
+  /// * `metadata` - Optional bytes or string (up to 64 KiB) to attach to the frame
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.pushSample("mysrc", Buffer.from([0, 1, 2, 3]));
+  /// kit.pushSample("mysrc", frame, JSON.stringify({ sequence: 42 }));
+  /// ```
+  #[napi]
+  pub fn push_sample(
+    &self,
+    element_name: String,
+    data: napi::bindgen_prelude::Buffer,
+    metadata: Option<Either<Buffer, String>>,
+  ) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+
+    let appsrc = element.downcast::<AppSrc>().map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} is not an AppSrc", element_name),
+      )
+    })?;
+
+    let mut buffer = gst::Buffer::from_mut_slice(data.to_vec());
+    if let Some(metadata) = metadata {
+      frame_metadata::attach(buffer.get_mut().unwrap(), metadata)?;
+    }
+    appsrc.push_buffer(buffer).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })?;
+
+    Ok(())
+  }
+
+  /// Pushes timed metadata, such as a KLV packet, to a named AppSrc element
+  ///
+  /// Linked to `mpegtsmux`, an AppSrc with `meta/x-klv,parsed=true` caps and
+  /// `format=time` becomes a KLV stream of the transport stream, each packet
+  /// presented at its timestamp.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the AppSrc element
+  /// * `data` - Bytes or a string (stored as UTF-8), up to 64 KiB
+  /// * `timestamp_ns` - Presentation time in nanoseconds, in the running time of the pipeline
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline(
+  ///   "v4l2src ! videoconvert ! x264enc tune=zerolatency ! h264parse ! mux. " +
+  ///   "appsrc name=klv format=time is-live=true caps=meta/x-klv,parsed=true ! mux. " +
+  ///   "mpegtsmux name=mux ! filesink location=flight.ts"
+  /// );
+  /// kit.play();
+  /// kit.pushTimedMetadata("klv", encodeKlv(key, localSet), kit.getPosition());
+  /// ```
+  #[napi]
+  pub fn push_timed_metadata(
+    &self,
+    element_name: String,
+    data: Either<Buffer, String>,
+    timestamp_ns: i64,
+  ) -> Result<()> {
+    let appsrc = self.app_src(&element_name)?;
+    timed_metadata::push(&appsrc, data, timestamp_ns)
+  }
+
+  /// Pulls timed metadata, such as KLV packets, from a named AppSink element
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the AppSink element, e.g. one linked to the `meta/x-klv` pad of `tsdemux`
+  /// * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  ///
+  /// # Returns
+  /// * `Result<Option<TimedMetadata>>` - The metadata with its presentation time, or null if none is available
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("filesrc location=flight.ts ! tsdemux name=demux demux. ! meta/x-klv ! appsink name=klv sync=false");
+  /// kit.play();
+  /// const metadata = kit.pullTimedMetadata("klv", 1000);
+  /// if (metadata) console.log(metadata.timestamp, parseKlv(metadata.data));
+  /// ```
+  #[napi]
+  pub fn pull_timed_metadata(
+    &self,
+    element_name: String,
+    #[napi(ts_arg_type = "number | undefined")] timeout_ms: Option<u32>,
+  ) -> Result<Option<TimedMetadata>> {
+    let appsink = self.app_sink(&element_name)?;
+    timed_metadata::pull(&appsink, timeout_ms.unwrap_or(100))
+  }
+
+  /// Returns the current state of the pipeline
+  ///
+  /// # Returns
+  /// * `Result<String>` - The current state as a string ("Playing", "Paused", "Null", "Ready")
+  ///
+  /// # Example
+  /// ```javascript
+  /// const state = kit.getState();
+  /// console.log("Current state:", state);
+  /// ```
+  #[napi]
+  pub fn get_state(&self) -> Result<String> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    if let Some(pipeline) = &*pipeline_guard {
+      let (success, state, _pending): (
+        std::result::Result<gst::StateChangeSuccess, gst::StateChangeError>,
+        gst::State,
+        gst::State,
+      ) = gst::prelude::ElementExt::state(pipeline, gst::ClockTime::NONE);
+
+      if success.is_ok() {
+        return Ok(format!("{:?}", state));
+      }
+    }
+    Ok("Null".to_string())
+  }
+
+  /// Returns the current position of the pipeline in nanoseconds
+  ///
+  /// # Returns
+  /// * `Result<i64>` - Current position in nanoseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// const position = kit.getPosition();
+  /// console.log("Position (ns):", position);
+  /// ```
+  #[napi]
+  pub fn get_position(&self) -> Result<i64> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let position = pipeline.query_position::<gst::ClockTime>().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Failed to query position".to_string(),
+      )
+    })?;
+
+    Ok(position.nseconds() as i64)
+  }
+
+  /// Returns the duration of the pipeline in nanoseconds
+  ///
+  /// # Returns
+  /// * `Result<i64>` - Duration in nanoseconds, or -1 if unknown
+  ///
+  /// # Example
+  /// ```javascript
+  /// const duration = kit.getDuration();
+  /// console.log("Duration (ns):", duration);
+  /// ```
+  #[napi]
+  pub fn get_duration(&self) -> Result<i64> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let duration = pipeline.query_duration::<gst::ClockTime>().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Failed to query duration".to_string(),
+      )
+    })?;
+
+    Ok(duration.nseconds() as i64)
+  }
+
+  /// Seeks to a specific position in the pipeline
+  ///
+  /// # Arguments
+  /// * `position_ns` - Position to seek to in nanoseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// // Seek to 5 seconds (5 * 1_000_000_000 nanoseconds)
+  /// kit.seek(5_000_000_000);
+  /// ```
+  #[napi]
+  pub fn seek(&self, position_ns: i64) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    *self.loop_region.lock().unwrap() = None;
+    let seek_pos = gst::ClockTime::from_nseconds(position_ns as u64);
+    let res = pipeline.seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, seek_pos);
+
+    res.map_err(|e| Error::new(Status::GenericFailure, format!("Failed to seek: {}", e)))?;
+
+    Ok(())
+  }
+
+  /// Loops a region of the media, e.g. for A/B repeat in a review tool
+  ///
+  /// Playback jumps to the start of the region unless it is already inside
+  /// it, and returns to the start at the end of every pass without a gap.
+  /// The start and end are frame-accurate. Setting another region replaces
+  /// the current one; seeking with `seek` leaves the loop.
+  ///
+  /// # Arguments
+  /// * `start_ns` - Start of the region in nanoseconds
+  /// * `end_ns` - End of the region in nanoseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("playbin uri=file:///clip.mp4");
+  /// await kit.playAsync();
+  /// kit.setLoopRegion(2_000_000_000, 4_500_000_000);
+  /// ```
+  #[napi]
+  pub fn set_loop_region(&self, start_ns: i64, end_ns: i64) -> Result<()> {
+    if start_ns < 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The loop must not start before 0".to_string(),
+      ));
+    }
+    let pipeline = self.current_pipeline()?;
+    let mut region = self.loop_region.lock().unwrap();
+    *region = None;
+    *region = Some(LoopRegion::start(
+      &pipeline,
+      gst::ClockTime::from_nseconds(start_ns as u64),
+      gst::ClockTime::from_nseconds(end_ns.max(0) as u64),
+    )?);
+    Ok(())
+  }
+
+  /// Stops looping; playback continues from the current position
+  ///
+  /// # Returns
+  /// * `Result<bool>` - Whether a region was looped
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.clearLoop();
+  /// ```
+  #[napi]
+  pub fn clear_loop(&self) -> Result<bool> {
+    let Some(region) = self.loop_region.lock().unwrap().take() else {
+      return Ok(false);
+    };
+    playback_loop::release(&self.current_pipeline()?, region)?;
+    Ok(true)
+  }
+
+  /// Changes the playback speed, keeping the pitch of the audio
+  ///
+  /// A `scaletempo` element is inserted in front of the audio sink on first
+  /// use, so call it once the pipeline has prerolled. A looped region keeps
+  /// looping at the new speed.
+  ///
+  /// # Arguments
+  /// * `factor` - Speed relative to normal playback, e.g. 0.5 for half speed
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("playbin uri=file:///lecture.mp4");
+  /// await kit.playAsync();
+  /// kit.setTempo(1.5);
+  /// ```
+  #[napi]
+  pub fn set_tempo(&self, factor: f64) -> Result<()> {
+    let pipeline = self.current_pipeline()?;
+    let region = self
+      .loop_region
+      .lock()
+      .unwrap()
+      .as_ref()
+      .map(LoopRegion::bounds);
+    audio_tempo::set_tempo(&pipeline, factor, region)
+  }
+
+  /// Shifts the pitch of the audio without changing the playback speed
+  ///
+  /// A `pitch` element (SoundTouch plugin) is inserted in front of the audio
+  /// sink on first use, so call it once the pipeline has prerolled.
+  ///
+  /// # Arguments
+  /// * `semitones` - Shift in semitones, from -24 to 24; 0 restores the original pitch
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPitch(-2);
+  /// ```
+  #[napi]
+  pub fn set_pitch(&self, semitones: f64) -> Result<()> {
+    audio_tempo::set_pitch(&self.current_pipeline()?, semitones)
+  }
+
+  /// Sets a property on a named element in the pipeline
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the element
+  /// * `property_name` - The name of the property
+  /// * `value` - The value to set (as a string)
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setProperty("mysrc", "is-live", "true");
+  /// ```
+  #[napi]
+  pub fn set_property(
+    &self,
+    element_name: String,
+    property_name: String,
+    value: String,
+  ) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+
+    element.set_property_from_str(&property_name, &value);
+
+    Ok(())
+  }
+
+  /// Changes the bitrate of a running encoder
+  ///
+  /// The value is converted to the unit of the encoder's bitrate property
+  /// and clamped to its range.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the encoder element
+  /// * `bps` - The target bitrate in bit/s
+  ///
+  /// # Returns
+  /// * `Result<f64>` - The bitrate applied, in bit/s
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setTargetBitrate("enc", 1_500_000);
+  /// ```
+  #[napi]
+  pub fn set_target_bitrate(&self, element_name: String, bps: f64) -> Result<f64> {
+    let pipeline = self.current_pipeline()?;
+    let encoder = pipeline.by_name(&element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+    set_encoder_bitrate(&encoder, bps)
+  }
+
+  /// Animates a numeric property of an element or pad through keyframes
+  ///
+  /// Values are computed for the timestamp of each buffer on the pipeline
+  /// clock, so the animation follows pauses and seeks. Any earlier animation
+  /// of the property is replaced.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the element, or "element.pad" for a pad
+  /// * `property_name` - The name of a controllable numeric property
+  /// * `keyframes` - Values of the property at stream times
+  /// * `options` - Interpolation between keyframes
+  ///
+  /// # Example
+  /// ```javascript
+  /// // Fade in over two seconds, then slide the second input to the right
+  /// kit.animateProperty("vol", "volume", [{ timeMs: 0, value: 0 }, { timeMs: 2000, value: 1 }]);
+  /// kit.animateProperty("mix.sink_1", "xpos", [{ timeMs: 2000, value: 0 }, { timeMs: 4000, value: 640 }], {
+  ///   interpolation: "cubic-monotonic",
+  /// });
+  /// ```
+  #[napi]
+  pub fn animate_property(
+    &self,
+    element_name: String,
+    property_name: String,
+    keyframes: Vec<Keyframe>,
+    options: Option<AnimationOptions>,
+  ) -> Result<()> {
+    let object = self.pipeline_object(&element_name)?;
+    animate(&object, &property_name, &keyframes, options)
+  }
+
+  /// Stops animating a property, leaving it at its current value
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the element, or "element.pad" for a pad
+  /// * `property_name` - The name of the animated property
+  ///
+  /// # Returns
+  /// * `Result<bool>` - Whether the property was animated
+  #[napi]
+  pub fn clear_animation(&self, element_name: String, property_name: String) -> Result<bool> {
+    let object = self.pipeline_object(&element_name)?;
+    Ok(clear(&object, &property_name))
+  }
+
+  /// Simulates a bad network in front of a sink, for testing
+  ///
+  /// A `netsim` element is inserted before the sink on first use, also
+  /// while the pipeline plays; later calls replace its impairment. Naming a
+  /// `netsim` element of the pipeline configures it instead.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the sink, e.g. an `udpsink` or `srtsink`
+  /// * `impairment` - Loss, delay, jitter, bandwidth cap and duplication
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("videotestsrc is-live=true ! x264enc tune=zerolatency ! rtph264pay ! udpsink name=out port=5000");
+  /// kit.play();
+  /// kit.impairNetwork("out", { loss: 0.05, delayMs: 80, jitterMs: 30, bandwidthKbps: 2000 });
+  /// ```
+  #[napi]
+  pub fn impair_network(&self, element_name: String, impairment: NetworkImpairment) -> Result<()> {
+    let pipeline = self.current_pipeline()?;
+    network_impairment::impair(&pipeline, &element_name, &impairment)
+  }
+
+  /// Lets packets reach a sink unimpaired again
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the impaired sink
+  ///
+  /// # Returns
+  /// * `Result<bool>` - Whether the sink was impaired
+  #[napi]
+  pub fn clear_network_impairment(&self, element_name: String) -> Result<bool> {
+    let pipeline = self.current_pipeline()?;
+    network_impairment::clear(&pipeline, &element_name)
+  }
+
+  /// Gets a property value from a named element in the pipeline
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the element
+  /// * `property_name` - The name of the property
+  ///
+  /// # Returns
+  /// * `Result<String>` - The property value as a string
+  ///
+  /// # Example
+  /// ```javascript
+  /// const value = kit.getProperty("mysrc", "is-live");
+  /// console.log("Property value:", value);
+  /// ```
+  #[napi]
+  pub fn get_property(&self, element_name: String, property_name: String) -> Result<String> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+
+    let value: gst::glib::Value = element.property(&property_name);
+
+    Ok(format!("{:?}", value))
+  }
+
+  /// Returns a list of all element names in the pipeline
+  ///
+  /// # Returns
+  /// * `Result<Vec<String>>` - Array of element names
+  ///
+  /// # Example
+  /// ```javascript
+  /// const elements = kit.getElements();
+  /// console.log("Elements:", elements);
+  /// ```
+  #[napi]
+  pub fn get_elements(&self) -> Result<Vec<String>> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let mut elements = Vec::new();
+    for element in pipeline.iterate_elements() {
+      match element {
+        Ok(el) => {
+          let name = el.name();
+          elements.push(name.to_string());
+        }
+        Err(_) => continue,
+      }
+    }
+
+    Ok(elements)
+  }
+
+  /// Returns a snapshot of pipeline statistics
+  ///
+  /// Reports queue fill levels, encoder bitrates, negotiated caps and the
+  /// rendered/dropped frame counts of sinks and of elements posting QoS
+  /// messages. QoS messages are collected as they are posted and stay on
+  /// the pipeline bus.
+  ///
+  /// # Returns
+  /// * `Result<PipelineStats>` - Per-element statistics and frame totals
+  ///
+  /// # Example
+  /// ```javascript
+  /// const stats = kit.getStats();
+  /// console.log("Dropped frames:", stats.droppedFrames);
+  /// for (const el of stats.elements.filter((e) => e.queueBuffers !== undefined)) {
+  ///   console.log(el.name, el.queueBuffers);
+  /// }
+  /// ```
+  #[napi]
+  pub fn get_stats(&self) -> Result<PipelineStats> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let qos = self.qos.lock().unwrap();
+
+    let (_, state, _) = pipeline.state(gst::ClockTime::ZERO);
+    let mut rendered_frames = 0;
+    let mut dropped_frames = 0;
+    let mut elements = Vec::new();
+    for element in pipeline.iterate_recurse().into_iter().flatten() {
+      if element.is::<gst::Bin>() {
+        continue;
+      }
+      let name = element.name().to_string();
+      let factory = element.factory();
+      let is_encoder = factory
+        .as_ref()
+        .map(|f| f.has_type(gst::ElementFactoryType::ENCODER))
+        .unwrap_or(false);
+      let is_queue = element.find_property("current-level-buffers").is_some();
+
+      let mut counters = sink_stats(&element);
+      if let Some((rendered, dropped)) = counters {
+        rendered_frames += rendered;
+        dropped_frames += dropped;
+      }
+      if let Some(&(processed, dropped)) = qos.get(&name) {
+        if counters.is_none() {
+          dropped_frames += dropped;
+          counters = Some((processed, dropped));
+        }
+      }
+
+      elements.push(ElementStats {
+        caps: element
+          .src_pads()
+          .iter()
+          .filter_map(|pad| pad.current_caps())
+          .map(|caps| caps.to_string())
+          .collect(),
+        factory: factory.map(|f| f.name().to_string()),
+        queue_buffers: is_queue
+          .then(|| property_i64(&element, "current-level-buffers"))
+          .flatten(),
+        queue_bytes: is_queue
+          .then(|| property_i64(&element, "current-level-bytes"))
+          .flatten(),
+        queue_time: is_queue
+          .then(|| property_i64(&element, "current-level-time"))
+          .flatten(),
+        bitrate: is_encoder
+          .then(|| {
+            property_i64(&element, "bitrate").or_else(|| property_i64(&element, "target-bitrate"))
+          })
+          .flatten(),
+        rendered: counters.map(|(rendered, _)| rendered),
+        dropped: counters.map(|(_, dropped)| dropped),
+        name,
+      });
+    }
+
+    Ok(PipelineStats {
+      state: format!("{:?}", state),
+      elements,
+      rendered_frames,
+      dropped_frames,
+    })
+  }
+
+  /// Returns the pipeline latency in milliseconds
+  ///
+  /// This is the latency set with `setLatency`, or otherwise the minimum
+  /// latency reported by the pipeline's live elements.
+  ///
+  /// # Returns
+  /// * `Result<f64>` - Latency in milliseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// console.log("Latency (ms):", kit.getLatency());
+  /// ```
+  #[napi]
+  pub fn get_latency(&self) -> Result<f64> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let latency = match pipeline.latency() {
+      Some(latency) => latency,
+      None => {
+        let mut query = gst::query::Latency::new();
+        if pipeline.query(&mut query) {
+          query.result().1
+        } else {
+          gst::ClockTime::ZERO
+        }
+      }
+    };
+    Ok(latency.nseconds() as f64 / 1_000_000.0)
+  }
+
+  /// Sets a fixed pipeline latency, overriding the latency computed from the
+  /// live elements
+  ///
+  /// # Arguments
+  /// * `latency_ms` - Latency in milliseconds
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setLatency(200);
+  /// ```
+  #[napi]
+  pub fn set_latency(&self, latency_ms: f64) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    if latency_ms < 0.0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Latency must not be negative".to_string(),
+      ));
+    }
+    pipeline.set_latency(gst::ClockTime::from_nseconds(
+      (latency_ms * 1_000_000.0) as u64,
+    ));
+    Ok(())
+  }
+
+  /// Selects the clock driving the pipeline, resolving once it is in use
+  ///
+  /// Network clocks ("ntp", "ptp" and "net") are waited on until they are
+  /// synchronized, so pipelines on several machines can share one timeline.
+  /// The wait happens off the JavaScript thread.
+  ///
+  /// # Arguments
+  /// * `options` - Clock type and, for network clocks, the server to follow
+  ///
+  /// # Example
+  /// ```javascript
+  /// await kit.useClock({ kind: "net", address: "192.168.1.10", port: 8554 });
+  /// kit.setBaseTime(sharedBaseTime);
+  /// kit.play();
+  /// ```
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn use_clock(&self, options: ClockOptions) -> Result<AsyncTask<ClockSync>> {
+    let pipeline = self.current_pipeline()?;
+
+    let address = || {
+      options.address.as_deref().ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          format!("Clock {} requires an address", options.kind),
+        )
+      })
+    };
+    let clock: gst::Clock = match options.kind.as_str() {
+      "system" => gst::SystemClock::obtain(),
+      "ntp" => {
+        gst_net::NtpClock::new(None, address()?, options.port.unwrap_or(123) as i32, None).upcast()
+      }
+      "net" => {
+        let port = options
+          .port
+          .ok_or_else(|| Error::new(Status::InvalidArg, "Clock net requires a port".to_string()))?;
+        gst_net::NetClientClock::new(None, address()?, port as i32, None).upcast()
+      }
+      "ptp" => {
+        gst_net::PtpClock::init(None, &[]).map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to initialize PTP: {}", e),
+          )
+        })?;
+        gst_net::PtpClock::new(None, options.domain.unwrap_or(0))
+          .map_err(|e| {
+            Error::new(
+              Status::GenericFailure,
+              format!("Failed to create PTP clock: {}", e),
+            )
+          })?
+          .upcast()
+      }
+      kind => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Unsupported clock: {}", kind),
+        ))
+      }
+    };
+
+    let timeout = (options.kind != "system")
+      .then(|| gst::ClockTime::from_mseconds(options.sync_timeout_ms.unwrap_or(5000) as u64));
+    Ok(AsyncTask::new(ClockSync {
+      pipeline,
+      clock,
+      kind: options.kind,
+      timeout,
+    }))
+  }
+
+  /// Publishes the pipeline clock on the network, so other machines can
+  /// follow it with `useClock({ kind: "net" })`
+  ///
+  /// # Arguments
+  /// * `port` - UDP port to serve the clock on (0 picks a free port)
+  ///
+  /// # Returns
+  /// * `Result<u32>` - The port the clock is served on
+  ///
+  /// # Example
+  /// ```javascript
+  /// const port = kit.publishClock(8554);
+  /// ```
+  #[napi]
+  pub fn publish_clock(&self, port: u32) -> Result<u32> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
     })?;
 
-    element.set_property_from_str(&property_name, &value);
+    let clock = pipeline.pipeline_clock();
+    pipeline.use_clock(Some(&clock));
+    let provider = gst_net::NetTimeProvider::new(&clock, None, port as i32).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to publish clock: {}", e),
+      )
+    })?;
+    let port = provider.property::<i32>("port") as u32;
+    *self.time_provider.lock().unwrap() = Some(provider);
+    Ok(port)
+  }
+
+  /// Returns the current time of the pipeline clock in nanoseconds
+  ///
+  /// # Returns
+  /// * `Result<i64>` - Clock time in nanoseconds
+  #[napi]
+  pub fn get_clock_time(&self) -> Result<i64> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let clock = pipeline
+      .clock()
+      .unwrap_or_else(|| pipeline.pipeline_clock());
+    Ok(clock.time().map(|t| t.nseconds() as i64).unwrap_or(0))
+  }
+
+  /// Returns the base time of the pipeline in nanoseconds
+  ///
+  /// # Returns
+  /// * `Result<i64>` - Base time in nanoseconds, or -1 if not set
+  #[napi]
+  pub fn get_base_time(&self) -> Result<i64> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    Ok(
+      pipeline
+        .base_time()
+        .map(|t| t.nseconds() as i64)
+        .unwrap_or(-1),
+    )
+  }
+
+  /// Sets the base time of the pipeline in nanoseconds
+  ///
+  /// Automatic base time selection is disabled, so pipelines sharing a clock
+  /// and a base time render the same running time at the same moment.
+  ///
+  /// # Arguments
+  /// * `base_time_ns` - Base time in nanoseconds of the pipeline clock
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setBaseTime(kit.getClockTime() + 500_000_000);
+  /// ```
+  #[napi]
+  pub fn set_base_time(&self, base_time_ns: i64) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
 
+    if base_time_ns < 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Base time must not be negative".to_string(),
+      ));
+    }
+    pipeline.set_start_time(gst::ClockTime::NONE);
+    pipeline.set_base_time(gst::ClockTime::from_nseconds(base_time_ns as u64));
     Ok(())
   }
 
-  /// Gets a property value from a named element in the pipeline
+  /// Delivers the buffers leaving an element to a callback, without changing
+  /// the launch string
+  ///
+  /// A `tee ! queue ! appsink` branch is spliced after the element's source
+  /// pad, also while the pipeline is running. The tap queue is leaky, so a
+  /// slow callback drops tapped buffers instead of stalling the pipeline.
   ///
   /// # Arguments
-  /// * `element_name` - The name of the element
-  /// * `property_name` - The name of the property
+  /// * `element_name` - The name of the element to tap
+  /// * `callback` - Called with the data of each buffer
   ///
-  /// # Returns
-  /// * `Result<String>` - The property value as a string
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("videotestsrc ! videoconvert name=conv ! autovideosink");
+  /// kit.addFrameTap("conv", (frame) => console.log("Frame of size:", frame.length));
+  /// kit.play();
+  /// ```
+  #[napi]
+  pub fn add_frame_tap(
+    &self,
+    element_name: String,
+    callback: ThreadsafeFunction<Buffer, (), Buffer, Status, false>,
+  ) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let mut taps = self.frame_taps.lock().unwrap();
+    if taps.contains_key(&element_name) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Element {} is already tapped", element_name),
+      ));
+    }
+
+    let appsink = AppSink::builder().sync(false).async_(false).build();
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+          let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+          callback.call(
+            Buffer::from(map.as_slice().to_vec()),
+            ThreadsafeFunctionCallMode::NonBlocking,
+          );
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+
+    let tap = splice_tap(pipeline, &element_name, vec![appsink.upcast()])?;
+    taps.insert(element_name, tap);
+    Ok(())
+  }
+
+  /// Taps the raw video frames leaving an element, split into their planes
+  ///
+  /// Works like `addFrameTap`, but delivers `FrameData` with the format,
+  /// size and per-plane stride of each frame. When `format` is given the
+  /// tapped frames are converted to it (e.g. "I420" or "NV12" for models
+  /// fed with YUV); otherwise they keep the format they have in the pipeline
+  /// and no conversion happens. Remove the tap with `removeFrameTap`.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of an element producing raw video
+  /// * `format` - Optional raw video format the tapped frames are converted to
+  /// * `callback` - Called with each frame
   ///
   /// # Example
   /// ```javascript
-  /// const value = kit.getProperty("mysrc", "is-live");
-  /// console.log("Property value:", value);
+  /// kit.setPipeline("videotestsrc ! videoconvert name=conv ! autovideosink");
+  /// kit.addPlanarFrameTap("conv", "I420", (frame) => {
+  ///   const luma = frame.planes[0];
+  ///   model.feed(luma.data, luma.stride, frame.width, frame.height);
+  /// });
+  /// kit.play();
   /// ```
   #[napi]
-  pub fn get_property(&self, element_name: String, property_name: String) -> Result<String> {
+  pub fn add_planar_frame_tap(
+    &self,
+    element_name: String,
+    format: Option<String>,
+    callback: ThreadsafeFunction<FrameData, (), FrameData, Status, false>,
+  ) -> Result<()> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -620,30 +2943,130 @@ impl GstKit {
       )
     })?;
 
-    let element = gst::prelude::GstBinExt::by_name(pipeline, &element_name).ok_or_else(|| {
+    let mut taps = self.frame_taps.lock().unwrap();
+    if taps.contains_key(&element_name) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Element {} is already tapped", element_name),
+      ));
+    }
+
+    let mut branch = Vec::new();
+    let mut builder = AppSink::builder().sync(false).async_(false);
+    if let Some(format) = &format {
+      if gst_video::VideoFormat::from_string(format) == gst_video::VideoFormat::Unknown {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Unknown video format: {}", format),
+        ));
+      }
+      branch.push(make_tap_element("videoconvert")?);
+      builder = builder.caps(
+        &gst::Caps::builder("video/x-raw")
+          .field("format", format)
+          .build(),
+      );
+    }
+    let appsink = builder.build();
+    let sink_name = element_name.clone();
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          let frame =
+            frame_data(&sample, &sink_name, false, None).map_err(|_| gst::FlowError::Error)?;
+          callback.call(frame, ThreadsafeFunctionCallMode::NonBlocking);
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+    branch.push(appsink.upcast());
+
+    let tap = splice_tap(pipeline, &element_name, branch)?;
+    taps.insert(element_name, tap);
+    Ok(())
+  }
+
+  /// Scans the frames leaving an element for QR codes
+  ///
+  /// Frames are converted to grayscale in a tapped branch, as with
+  /// `addFrameTap`, so the pipeline itself is not slowed down; frames are
+  /// skipped while the previous one is still being scanned. Remove the
+  /// detector with `removeFrameTap`.
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of an element producing raw video
+  /// * `callback` - Called with each code found, once per frame it appears in
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("v4l2src ! videoconvert name=conv ! autovideosink");
+  /// kit.addQrDetector("conv", ({ payload, x, y }) => console.log(payload, "at", x, y));
+  /// kit.play();
+  /// ```
+  #[napi]
+  pub fn add_qr_detector(
+    &self,
+    element_name: String,
+    callback: ThreadsafeFunction<QrDetection, (), QrDetection, Status, false>,
+  ) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
         Status::GenericFailure,
-        format!("Element {} not found", element_name),
+        "Pipeline not initialized".to_string(),
       )
     })?;
 
-    let value: gst::glib::Value = element.property(&property_name);
+    let mut taps = self.frame_taps.lock().unwrap();
+    if taps.contains_key(&element_name) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Element {} is already tapped", element_name),
+      ));
+    }
 
-    Ok(format!("{:?}", value))
+    let convert = make_tap_element("videoconvert")?;
+    let appsink = AppSink::builder()
+      .caps(
+        &gst::Caps::builder("video/x-raw")
+          .field("format", "GRAY8")
+          .build(),
+      )
+      .sync(false)
+      .async_(false)
+      .build();
+    appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          for detection in detect_qr_codes(&sample) {
+            callback.call(detection, ThreadsafeFunctionCallMode::NonBlocking);
+          }
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+
+    let tap = splice_tap(pipeline, &element_name, vec![convert, appsink.upcast()])?;
+    taps.insert(element_name, tap);
+    Ok(())
   }
 
-  /// Returns a list of all element names in the pipeline
+  /// Removes a frame tap added with `addFrameTap` and restores the original link
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the tapped element
   ///
   /// # Returns
-  /// * `Result<Vec<String>>` - Array of element names
+  /// * `bool` - Whether the element was tapped
   ///
   /// # Example
   /// ```javascript
-  /// const elements = kit.getElements();
-  /// console.log("Elements:", elements);
+  /// kit.removeFrameTap("conv");
   /// ```
   #[napi]
-  pub fn get_elements(&self) -> Result<Vec<String>> {
+  pub fn remove_frame_tap(&self, element_name: String) -> Result<bool> {
     let pipeline_guard = self.pipeline.lock().unwrap();
     let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
       Error::new(
@@ -652,18 +3075,298 @@ impl GstKit {
       )
     })?;
 
-    let mut elements = Vec::new();
-    for element in pipeline.iterate_elements() {
-      match element {
-        Ok(el) => {
-          let name = el.name();
-          elements.push(name.to_string());
+    let Some(tap) = self.frame_taps.lock().unwrap().remove(&element_name) else {
+      return Ok(false);
+    };
+
+    let (unspliced, result) = mpsc::channel();
+    let pipeline = pipeline.clone();
+    let removed = tap.clone();
+    tap
+      .src_pad
+      .add_probe(gst::PadProbeType::IDLE, move |pad, _| {
+        let tee_sink = removed.tee.static_pad("sink");
+        let tee_src = removed.peer.as_ref().and_then(|peer| peer.peer());
+        let mut unlinked = tee_sink
+          .as_ref()
+          .is_none_or(|tee_sink| pad.unlink(tee_sink).is_ok());
+        if let (true, Some(peer)) = (unlinked, &removed.peer) {
+          unlinked = tee_src
+            .as_ref()
+            .is_none_or(|tee_src| tee_src.unlink(peer).is_ok())
+            && pad.link(peer).is_ok();
+          if !unlinked {
+            // Keep the tap in place
+            let _ = pad.unlink(peer);
+            if let Some(tee_src) = &tee_src {
+              let _ = tee_src.link(peer);
+            }
+            if let Some(tee_sink) = &tee_sink {
+              let _ = pad.link(tee_sink);
+            }
+          }
         }
-        Err(_) => continue,
+        if unlinked {
+          if let Some(tee_src) = &tee_src {
+            removed.tee.release_request_pad(tee_src);
+          }
+          for element in std::iter::once(&removed.tee).chain(&removed.branch) {
+            let _ = element.set_state(gst::State::Null);
+            let _ = pipeline.remove(element);
+          }
+        }
+        let _ = unspliced.send(unlinked);
+        gst::PadProbeReturn::Remove
+      });
+    if let Ok(false) = result.recv_timeout(RELINK_TIMEOUT) {
+      self
+        .frame_taps
+        .lock()
+        .unwrap()
+        .insert(element_name.clone(), tap);
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Failed to restore the link after {}", element_name),
+      ));
+    }
+    Ok(true)
+  }
+
+  /// Sets the caps of a capsfilter (or any element with a "caps" property),
+  /// renegotiating the running pipeline
+  ///
+  /// # Arguments
+  /// * `element_name` - The name of the capsfilter element
+  /// * `caps` - A caps string, e.g. "video/x-raw,width=640,height=360"
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("videotestsrc ! videoscale ! capsfilter name=size ! appsink name=sink");
+  /// kit.setCapsFilter("size", "video/x-raw,width=640,height=360");
+  /// ```
+  #[napi]
+  pub fn set_caps_filter(&self, element_name: String, caps: String) -> Result<()> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let element = pipeline.by_name(&element_name).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} not found", element_name),
+      )
+    })?;
+    if element.find_property("caps").is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Element {} has no caps property", element_name),
+      ));
+    }
+    let caps = caps
+      .parse::<gst::Caps>()
+      .map_err(|_| Error::new(Status::InvalidArg, format!("Invalid caps: {}", caps)))?;
+    element.set_property("caps", &caps);
+    Ok(())
+  }
+
+  /// Replaces the pipeline with a `playbin` playing a list of media gaplessly
+  ///
+  /// The next entry is queued when the current one is about to finish, so
+  /// there is no gap between tracks. Use `onTrackChange` to follow playback.
+  ///
+  /// # Arguments
+  /// * `uris` - Media URIs or file paths, in playback order
+  /// * `options` - Optional video and audio sinks
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPlaylist(["intro.ogg", "song.ogg", "https://example.com/outro.ogg"]);
+  /// kit.onTrackChange(({ index, uri }) => console.log("Now playing", index, uri));
+  /// kit.play();
+  /// ```
+  #[napi]
+  pub fn set_playlist(&self, uris: Vec<String>, options: Option<PlaylistOptions>) -> Result<()> {
+    if uris.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Playlist must not be empty".to_string(),
+      ));
+    }
+    let uris = uris
+      .iter()
+      .map(|uri| to_uri(uri))
+      .collect::<Result<Vec<_>>>()?;
+
+    let playbin = gst::ElementFactory::make("playbin")
+      .property("uri", &uris[0])
+      .build()
+      .map_err(|_| {
+        Error::new(
+          Status::GenericFailure,
+          "Element playbin is not available".to_string(),
+        )
+      })?;
+    let (video_sink, audio_sink) = match options {
+      Some(options) => (options.video_sink, options.audio_sink),
+      None => (None, None),
+    };
+    for (property, description) in [("video-sink", video_sink), ("audio-sink", audio_sink)] {
+      if let Some(description) = description {
+        let sink = gst::parse::bin_from_description(&description, true).map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to parse {}: {}", property, e),
+          )
+        })?;
+        playbin.set_property(property, &sink);
       }
     }
 
-    Ok(elements)
+    let playlist = self.playlist.clone();
+    playbin.connect("about-to-finish", false, move |args| {
+      let playbin = args[0].get::<gst::Element>().ok()?;
+      let mut playlist = playlist.lock().unwrap();
+      let next = playlist.queued.unwrap_or(playlist.current) + 1;
+      if next < playlist.uris.len() {
+        playbin.set_property("uri", &playlist.uris[next]);
+        playlist.queued = Some(next);
+      }
+      None
+    });
+
+    let bus = playbin
+      .downcast_ref::<gst::Pipeline>()
+      .and_then(|pipeline| pipeline.bus())
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+    let playlist = self.playlist.clone();
+    let callback = self.track_callback.clone();
+    let playbin_weak = playbin.downgrade();
+    bus.set_sync_handler(move |_, msg| {
+      let from_playbin = playbin_weak
+        .upgrade()
+        .map(|playbin| msg.src() == Some(playbin.upcast_ref::<gst::Object>()))
+        .unwrap_or(false);
+      if let (gst::MessageView::StreamStart(_), true) = (msg.view(), from_playbin) {
+        let mut playlist = playlist.lock().unwrap();
+        if let Some(queued) = playlist.queued.take() {
+          playlist.current = queued;
+        }
+        let change = TrackChange {
+          index: playlist.current as u32,
+          uri: playlist.uris[playlist.current].clone(),
+        };
+        if let Some(callback) = callback.lock().unwrap().as_ref() {
+          callback.call(change, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+      }
+      gst::BusSyncReply::Pass
+    });
+
+    let pipeline = playbin.downcast::<gst::Pipeline>().map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        "Provided string is not a valid pipeline".to_string(),
+      )
+    })?;
+    // Adopting resets the playlist, so it is filled in afterwards
+    self.adopt_pipeline(pipeline);
+    *self.playlist.lock().unwrap() = Playlist {
+      uris,
+      current: 0,
+      queued: None,
+    };
+    Ok(())
+  }
+
+  /// Sets the callback called when playback moves to another playlist entry
+  ///
+  /// # Arguments
+  /// * `callback` - Called with the index and URI of the entry now playing
+  #[napi]
+  pub fn on_track_change(
+    &self,
+    callback: ThreadsafeFunction<TrackChange, (), TrackChange, Status, false, true>,
+  ) {
+    *self.track_callback.lock().unwrap() = Some(callback);
+  }
+
+  /// Jumps to a playlist entry, keeping the current playback state
+  fn jump_to(&self, index: usize) -> Result<u32> {
+    let pipeline_guard = self.pipeline.lock().unwrap();
+    let pipeline = pipeline_guard.as_ref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Pipeline not initialized".to_string(),
+      )
+    })?;
+
+    let uri = {
+      let mut playlist = self.playlist.lock().unwrap();
+      let uri = playlist.uris.get(index).cloned().ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          "No playlist entry to move to".to_string(),
+        )
+      })?;
+      playlist.current = index;
+      playlist.queued = None;
+      uri
+    };
+
+    let state = pipeline.current_state().max(pipeline.pending_state());
+    let state_error = |e: gst::StateChangeError| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to change track: {}", e),
+      )
+    };
+    pipeline.set_state(gst::State::Ready).map_err(state_error)?;
+    pipeline.set_property("uri", &uri);
+    if state > gst::State::Ready {
+      pipeline.set_state(state).map_err(state_error)?;
+    }
+    Ok(index as u32)
+  }
+
+  /// Moves to the next playlist entry
+  ///
+  /// # Returns
+  /// * `Result<u32>` - Index of the new entry
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.next();
+  /// ```
+  #[napi]
+  pub fn next(&self) -> Result<u32> {
+    let current = self.playlist.lock().unwrap().current;
+    self.jump_to(current + 1)
+  }
+
+  /// Moves to the previous playlist entry
+  ///
+  /// # Returns
+  /// * `Result<u32>` - Index of the new entry
+  #[napi]
+  pub fn previous(&self) -> Result<u32> {
+    let current = self.playlist.lock().unwrap().current;
+    let index = current.checked_sub(1).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "No playlist entry to move to".to_string(),
+      )
+    })?;
+    self.jump_to(index)
+  }
+
+  /// Returns the index of the playlist entry currently playing
+  #[napi]
+  pub fn get_playlist_index(&self) -> u32 {
+    self.playlist.lock().unwrap().current as u32
   }
 
   /// Checks if the pipeline has been initialized
@@ -704,6 +3407,12 @@ impl GstKit {
       })?;
     }
     *pipeline = None;
+    *self.time_provider.lock().unwrap() = None;
+    *self.qos_watch.lock().unwrap() = None;
+    self.qos.lock().unwrap().clear();
+    self.frame_taps.lock().unwrap().clear();
+    self.emission_configs.lock().unwrap().clear();
+    *self.loop_region.lock().unwrap() = None;
     Ok(())
   }
 }
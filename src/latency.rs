@@ -0,0 +1,264 @@
+//! # Latency Measurement
+//!
+//! End-to-end latency harness for user-supplied pipelines. Numbered frames
+//! are pushed into the pipeline at a steady rate, recognized again when they
+//! come out, and the time each one spent inside is collected.
+//!
+//! With the "timestampOverlay" pattern the frame number is painted into the
+//! picture as a grid of black and white cells, so it survives encoders,
+//! network hops and scaling. The "pts" pattern relies on buffer timestamps
+//! instead, for pipelines that keep them intact.
+
+use crate::benchmark::parse_resolution;
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cells per side of the frame number grid
+const GRID: usize = 4;
+/// Bits of the frame number; the remaining cells hold a checksum
+const NUMBER_BITS: usize = 12;
+
+/// Options for `measureLatency`
+#[napi(object)]
+pub struct LatencyOptions {
+  /// How frames are recognized: "timestampOverlay" (default) or "pts"
+  pub pattern: Option<String>,
+  /// Frame size as "WIDTHxHEIGHT" (default: "640x360")
+  pub resolution: Option<String>,
+  /// Frames to send, at most 4096 (default: 100)
+  pub frames: Option<u32>,
+  /// Frames per second to send at (default: 30)
+  pub framerate: Option<u32>,
+}
+
+/// Latency distribution measured by `measureLatency`, in milliseconds
+#[napi(object)]
+pub struct LatencyReport {
+  /// Frames pushed into the pipeline
+  pub sent: u32,
+  /// Frames recognized at the output
+  pub received: u32,
+  /// Frames that never came out (dropped or unrecognizable)
+  pub lost: u32,
+  /// Lowest latency
+  pub min: f64,
+  /// Highest latency
+  pub max: f64,
+  /// Mean latency
+  pub mean: f64,
+  /// Median latency
+  pub median: f64,
+  /// 95th percentile latency
+  pub p95: f64,
+  /// 99th percentile latency
+  pub p99: f64,
+  /// Latency of every received frame, in arrival order
+  pub samples: Vec<f64>,
+}
+
+/// Checksum stored next to a frame number, to reject misread frames
+fn checksum(number: u32) -> u32 {
+  (number ^ (number >> 4) ^ (number >> 8)) & 0xf
+}
+
+/// Paints a frame number as a grid of black (1) and white (0) GRAY8 cells
+fn render_frame(number: u32, width: usize, height: usize) -> Vec<u8> {
+  let bits = (number << 4) | checksum(number);
+  let mut frame = vec![0u8; width * height];
+  for (y, row) in frame.chunks_exact_mut(width).enumerate() {
+    let cell_y = y * GRID / height;
+    for (x, pixel) in row.iter_mut().enumerate() {
+      let bit = cell_y * GRID + x * GRID / width;
+      *pixel = if (bits >> (GRID * GRID - 1 - bit)) & 1 == 1 {
+        0
+      } else {
+        255
+      };
+    }
+  }
+  frame
+}
+
+/// Reads back a frame number painted by `render_frame` from a GRAY8 sample
+fn read_frame(sample: &gst::Sample) -> Option<u32> {
+  let buffer = sample.buffer()?;
+  let info = gst_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+  let map = buffer.map_readable().ok()?;
+  let (width, height) = (info.width() as usize, info.height() as usize);
+  let stride = info.stride()[0] as usize;
+  let offset = info.offset()[0];
+
+  let bits = (0..GRID * GRID).fold(0u32, |bits, bit| {
+    let x = (bit % GRID * 2 + 1) * width / (GRID * 2);
+    let y = (bit / GRID * 2 + 1) * height / (GRID * 2);
+    let dark = map.as_slice()[offset + y * stride + x] < 128;
+    (bits << 1) | dark as u32
+  });
+  let number = bits >> (GRID * GRID - NUMBER_BITS);
+  (checksum(number) == bits & 0xf).then_some(number)
+}
+
+/// Returns the value at a percentile of sorted samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+  if sorted.is_empty() {
+    return 0.0;
+  }
+  let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+  sorted[index]
+}
+
+/// Measures the end-to-end latency of a pipeline fragment
+///
+/// Frames are pushed into `appsrc ! videoconvert ! <pipeline>` and read back
+/// after it from an appsink that does not sync to the clock, so the result is
+/// the time frames spend being processed. The call blocks until every frame
+/// has been sent and the pipeline reached end of stream.
+///
+/// # Arguments
+/// * `pipeline` - Launch string fragment taking and producing raw or decodable video
+/// * `options` - Optional pattern, resolution, frame count and frame rate
+///
+/// # Returns
+/// * `Result<LatencyReport>` - Latency distribution in milliseconds
+///
+/// # Example
+/// ```javascript
+/// const report = measureLatency(
+///   "x264enc tune=zerolatency ! rtph264pay ! rtph264depay ! avdec_h264",
+///   { pattern: "timestampOverlay", frames: 300 },
+/// );
+/// console.log(`median ${report.median} ms, p99 ${report.p99} ms, lost ${report.lost}`);
+/// ```
+#[napi]
+pub fn measure_latency(pipeline: String, options: Option<LatencyOptions>) -> Result<LatencyReport> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(LatencyOptions {
+    pattern: None,
+    resolution: None,
+    frames: None,
+    framerate: None,
+  });
+  let by_overlay = match options.pattern.as_deref().unwrap_or("timestampOverlay") {
+    "timestampOverlay" => true,
+    "pts" => false,
+    other => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported pattern: {}", other),
+      ))
+    }
+  };
+  let (width, height) = parse_resolution(options.resolution.as_deref().unwrap_or("640x360"))?;
+  let frames = options.frames.unwrap_or(100).clamp(1, 1 << NUMBER_BITS);
+  let framerate = options.framerate.unwrap_or(30).max(1);
+  let frame_duration = gst::ClockTime::SECOND / framerate as u64;
+
+  let pipeline = launch(&format!(
+    "appsrc name=src format=time is-live=true caps=video/x-raw,format=GRAY8,width={},height={},framerate={}/1 \
+     ! videoconvert ! {} ! videoconvert ! video/x-raw,format=GRAY8 ! appsink name=sink sync=false",
+    width, height, framerate, pipeline
+  ))?;
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Latency source not found"))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Latency sink not found"))?;
+
+  let sent_at: Arc<Mutex<HashMap<u32, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+  let samples: Arc<Mutex<Vec<f64>>> = Arc::new(Mutex::new(Vec::new()));
+  let sent_clone = sent_at.clone();
+  let samples_clone = samples.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let number = if by_overlay {
+          read_frame(&sample)
+        } else {
+          sample
+            .buffer()
+            .and_then(|buffer| buffer.pts())
+            .map(|pts| (pts.nseconds() / frame_duration.nseconds()) as u32)
+        };
+        if let Some(sent) = number.and_then(|n| sent_clone.lock().unwrap().remove(&n)) {
+          let latency = sent.elapsed().as_secs_f64() * 1000.0;
+          samples_clone.lock().unwrap().push(latency);
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+
+  let start = Instant::now();
+  let interval = Duration::from_nanos(frame_duration.nseconds());
+  let pushed = (0..frames).try_for_each(|number| {
+    if let Some(wait) = (interval * number).checked_sub(start.elapsed()) {
+      std::thread::sleep(wait);
+    }
+    let mut buffer =
+      gst::Buffer::from_mut_slice(render_frame(number, width as usize, height as usize));
+    if let Some(buffer) = buffer.get_mut() {
+      buffer.set_pts(frame_duration * number as u64);
+      buffer.set_duration(frame_duration);
+    }
+    sent_at.lock().unwrap().insert(number, Instant::now());
+    appsrc.push_buffer(buffer).map(|_| ())
+  });
+  let result = pushed
+    .and_then(|_| appsrc.end_of_stream().map(|_| ()))
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })
+    .and_then(|_| wait_for_eos(&pipeline));
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let samples = std::mem::take(&mut *samples.lock().unwrap());
+  let mut sorted = samples.clone();
+  sorted.sort_by(f64::total_cmp);
+  let received = samples.len() as u32;
+  Ok(LatencyReport {
+    sent: frames,
+    received,
+    lost: frames - received,
+    min: sorted.first().copied().unwrap_or(0.0),
+    max: sorted.last().copied().unwrap_or(0.0),
+    mean: if sorted.is_empty() {
+      0.0
+    } else {
+      sorted.iter().sum::<f64>() / sorted.len() as f64
+    },
+    median: percentile(&sorted, 0.5),
+    p95: percentile(&sorted, 0.95),
+    p99: percentile(&sorted, 0.99),
+    samples,
+  })
+}
@@ -7,12 +7,82 @@
 //! ## Features
 //!
 //! - Pipeline creation from launch strings
-//! - Playback control (play, pause, stop)
-//! - Data extraction from AppSink elements
+//! - Launch string templates with escaped variable substitution
+//! - Step-by-step pipeline builder for users unfamiliar with launch syntax
+//! - Playback control (play, pause, stop), with promises settling when a state is reached
+//! - Data extraction from AppSink elements, as RGBA or raw planar YUV with strides
+//! - Frame export to JPEG/PNG files or password-protected zip archives
+//! - Async iteration over live frames with configurable backpressure policies
+//! - Row de-padding, NV12/I420 conversion, ImageData-ready RGBA and OpenCV-ready BGR frames
+//! - Shared-memory frame rings for readers in other processes
+//! - Virtual camera output to v4l2loopback devices
+//! - Camera and microphone listing, plug events and capture permission checks
+//! - DMABuf and GL texture handles of frames kept in GPU memory
 //! - Data injection via AppSrc elements
+//! - Per-frame application metadata carried from AppSrc to AppSink
+//! - KLV and SEI user data timed metadata injected into and extracted from encoded streams
+//! - Frame-accurate tick clock with jitter statistics for synthetic AppSrc content
+//! - Timed playback of decoded media files into AppSrc elements
 //! - Seeking and position/duration queries
+//! - Gapless, frame-accurate A/B loops of a region of the media
+//! - Playback speed changes that keep the audio pitch, and pitch shifting
+//! - Buffering and connection events of network playback, with pause-while-buffering and reconnects
+//! - Simulated packet loss, jitter and bandwidth caps in front of sinks for robustness tests
+//! - Clock selection, latency and base-time control
 //! - Property manipulation on pipeline elements
+//! - Keyframe animation of element and pad properties on the pipeline clock
 //! - Pipeline inspection and state management
+//! - Media probing of any format GStreamer can read, with cached directory probes and parallel batches
+//! - ffprobe-compatible JSON output for existing probe tooling
+//! - Per-frame bitstream inspection with NAL unit and OBU types
+//! - Matroska/WebM and IVF structure dumps
+//! - WebM cue lookups for byte-range seeking over HTTP
+//! - Build and runtime version information
+//! - Plugin path and registry bootstrap for bundled GStreamer builds, with plugin checks
+//! - Missing element reports with the packages providing them
+//! - Streaming transcoding of JavaScript-supplied media chunks
+//! - Continuous recording of pipeline output to IVF or WebM files
+//! - Fragmented WebM output split into init and media segments for live streaming
+//! - Media Source Extensions segment streaming of live encoded frames
+//! - Motion and scene change triggers that start recordings and save snapshots
+//! - Screen recording with webcam picture-in-picture and microphone to files or live streams
+//! - Live encoder bitrate changes, adapted automatically to SRT statistics and queue levels
+//! - Structured reports of what each transcode did
+//! - Frame interpolation for frame rate conversion and slow motion
+//! - Background file-to-file transcode jobs with progress and cancellation
+//! - Encode/decode/filter throughput benchmarking
+//! - Content complexity (SI/TI) analysis with bitrate ladder recommendations
+//! - Streaming NDJSON/CSV export of per-frame analysis results
+//! - Synthetic test media generation
+//! - Title cards and slates rendered with a bundled font
+//! - Waveform and spectrum videos of audio files
+//! - Cover art and file attachments in Matroska output
+//! - AES-CTR encryption and decryption of Matroska/WebM frames
+//! - Clip extraction with or without re-encoding
+//! - Encoder speed/quality presets
+//! - Codec backend registry and runtime capability reporting
+//! - Image comparison for visual-regression testing
+//! - Perceptual frame hashes (pHash, dHash) and duplicate segment detection
+//! - Black bar (letterbox) detection with a suggested crop rectangle
+//! - Interlace and 3:2 pulldown detection, with inverse telecine and deinterlace filters
+//! - Rotation metadata of MP4 and Matroska video, applied on request when transcoding
+//! - Managing several named pipelines from one object
+//! - Adopting pipelines built in Rust code or handed over by a pipeline manager
+//! - Synchronized multi-camera capture sessions with shared session timestamps
+//! - Picture-in-picture composition of several video inputs
+//! - Side-by-side, stacked and split-screen comparison videos
+//! - Chroma-key (green screen) filtering and overlays over a video or image background
+//! - Color grading with 3D LUTs in the .cube format
+//! - Blur and pixelation of the whole frame or of moving regions, for redaction
+//! - Burned-in SubRip subtitles with wrapping, outlines and position tags
+//! - Shifting, rescaling and clip trimming of SubRip and WebVTT subtitle files
+//! - Audio mixing with per-input volume, mute and levels
+//! - Audio resampling, channel mixing, gain and fade filters
+//! - Audio trimming, fading, normalization and concatenation
+//! - Speech-recognition ready audio extraction
+//! - QR code detection on video frames
+//! - End-to-end latency measurement of pipelines
+//! - Audio/video sync drift measurement, with drift correction when transcoding
 //!
 //! ## Example
 //!
@@ -33,7 +103,79 @@
 
 #![deny(clippy::all)]
 
+pub mod adaptive_bitrate;
+pub mod animation;
+pub mod attachments;
+pub mod audio_filters;
+pub mod audio_mixer;
+pub mod audio_process;
+pub mod audio_tempo;
+pub mod av_sync;
+pub mod benchmark;
+pub mod bitstream;
+pub mod bootstrap;
+pub mod build_info;
+pub mod capture_session;
+pub mod clip;
+pub mod codecs;
+pub mod comparison;
+pub mod complexity;
+pub mod compositor;
+pub mod container_dump;
+pub mod crop_detect;
+pub mod devices;
+pub mod encryption;
+pub mod export;
+pub mod ffprobe;
+pub mod fragmented_webm;
+pub mod frame_clock;
+pub mod frame_export;
+pub mod frame_metadata;
+pub mod frame_stream;
+pub mod gpu_handle;
+pub mod image_diff;
+pub mod interlace;
+pub mod interpolate;
 pub mod kit;
+pub mod latency;
+pub mod manager;
+pub mod missing_plugins;
+pub mod mse_streamer;
+pub mod network_impairment;
+pub mod network_watch;
+pub mod overlay;
+pub mod perceptual_hash;
+pub mod pipeline_builder;
+pub mod pipeline_template;
+pub mod pixel_layout;
+pub mod playback_feed;
+pub mod playback_loop;
+pub mod presets;
+pub mod probe;
+pub mod probe_cache;
+pub mod recorder;
+pub mod report;
+pub mod rotation;
+pub mod screen_recorder;
+pub mod shared_frames;
+pub mod slate;
+pub mod state_wait;
+pub mod subtitle_timing;
+pub mod subtitles;
+pub mod test_media;
+pub mod timed_metadata;
+pub mod transcode;
+pub mod transcode_job;
+pub mod triggers;
+pub mod video_filters;
+pub mod virtual_camera;
+pub mod waveform;
+pub mod webm_cues;
 
 // Re-export the main struct for convenience
+pub use audio_mixer::AudioMixer;
+pub use compositor::Compositor;
 pub use kit::GstKit;
+pub use manager::PipelineManager;
+pub use transcode::TranscodeStream;
+pub use transcode_job::TranscodeJob;
@@ -0,0 +1,363 @@
+//! # Pipeline Manager
+//!
+//! Owns several named pipelines at once. All pipeline buses are watched from a
+//! single GLib main loop thread, and their messages are delivered to one
+//! JavaScript callback tagged with the id of the pipeline that posted them.
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Callback receiving the bus messages of every managed pipeline
+type EventCallback =
+  ThreadsafeFunction<ManagedPipelineEvent, (), ManagedPipelineEvent, Status, false, true>;
+
+/// A bus message of a managed pipeline
+#[napi(object)]
+#[derive(Clone)]
+pub struct ManagedPipelineEvent {
+  /// Id of the pipeline that posted the message
+  pub pipeline_id: String,
+  /// The type of event ("eos", "error", "warning", "state-changed" or "element")
+  pub event_type: String,
+  /// Optional message associated with the event
+  pub message: Option<String>,
+}
+
+/// State and timing of a managed pipeline
+#[napi(object)]
+pub struct ManagedPipelineStats {
+  /// Id of the pipeline
+  pub id: String,
+  /// Current state ("Null", "Ready", "Paused" or "Playing")
+  pub state: String,
+  /// Current position in nanoseconds, if known
+  pub position: Option<i64>,
+  /// Duration in nanoseconds, if known
+  pub duration: Option<i64>,
+}
+
+/// A pipeline and the bus watch attached to the shared main loop
+struct ManagedPipeline {
+  pipeline: gst::Pipeline,
+  watch: glib::Source,
+  /// Whether the pipeline was handed over and must keep running
+  detached: bool,
+}
+
+impl Drop for ManagedPipeline {
+  fn drop(&mut self) {
+    self.watch.destroy();
+    if !self.detached {
+      let _ = self.pipeline.set_state(gst::State::Null);
+    }
+  }
+}
+
+/// Converts a bus message into an event, for the message types that are reported
+fn message_event(
+  pipeline_id: &str,
+  pipeline: &gst::Pipeline,
+  msg: &gst::Message,
+) -> Option<ManagedPipelineEvent> {
+  let (event_type, message) = match msg.view() {
+    gst::MessageView::Eos(_) => ("eos", None),
+    gst::MessageView::Error(err) => ("error", Some(err.error().to_string())),
+    gst::MessageView::Warning(warning) => ("warning", Some(warning.error().to_string())),
+    gst::MessageView::StateChanged(state)
+      if msg.src() == Some(pipeline.upcast_ref::<gst::Object>()) =>
+    {
+      (
+        "state-changed",
+        Some(format!("{:?} -> {:?}", state.old(), state.current())),
+      )
+    }
+    gst::MessageView::Element(element) => ("element", element.structure().map(|s| s.to_string())),
+    _ => return None,
+  };
+  Some(ManagedPipelineEvent {
+    pipeline_id: pipeline_id.to_string(),
+    event_type: event_type.to_string(),
+    message,
+  })
+}
+
+/// Manager for multiple named GStreamer pipelines
+///
+/// # Example
+/// ```javascript
+/// const manager = new PipelineManager();
+/// manager.onEvent((event) => console.log(event.pipelineId, event.eventType));
+/// manager.addPipeline("cam1", "v4l2src device=/dev/video0 ! autovideosink");
+/// manager.addPipeline("cam2", "v4l2src device=/dev/video1 ! autovideosink");
+/// manager.playAll();
+/// ```
+#[napi]
+pub struct PipelineManager {
+  pipelines: Mutex<HashMap<String, ManagedPipeline>>,
+  callback: Arc<Mutex<Option<EventCallback>>>,
+  context: glib::MainContext,
+  main_loop: glib::MainLoop,
+  loop_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for PipelineManager {
+  fn drop(&mut self) {
+    self.pipelines.lock().unwrap().clear();
+    self.main_loop.quit();
+    if let Some(thread) = self.loop_thread.lock().unwrap().take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl PipelineManager {
+  /// Runs `f` on the pipeline registered under `id`
+  fn with_pipeline<T>(&self, id: &str, f: impl FnOnce(&gst::Pipeline) -> Result<T>) -> Result<T> {
+    let pipelines = self.pipelines.lock().unwrap();
+    let managed = pipelines.get(id).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Pipeline not found: {}", id),
+      )
+    })?;
+    f(&managed.pipeline)
+  }
+
+  /// Removes the pipeline registered under `id` without stopping it
+  pub(crate) fn take_pipeline(&self, id: &str) -> Result<gst::Pipeline> {
+    let mut managed = self.pipelines.lock().unwrap().remove(id).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Pipeline not found: {}", id),
+      )
+    })?;
+    managed.detached = true;
+    Ok(managed.pipeline.clone())
+  }
+
+  fn set_state(&self, id: &str, state: gst::State) -> Result<()> {
+    self.with_pipeline(id, |pipeline| {
+      pipeline.set_state(state).map(|_| ()).map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to set state to {:?}: {}", state, e),
+        )
+      })
+    })
+  }
+
+  fn set_state_all(&self, state: gst::State) -> Result<()> {
+    let pipelines = self.pipelines.lock().unwrap();
+    let failed: Vec<&str> = pipelines
+      .iter()
+      .filter(|(_, managed)| managed.pipeline.set_state(state).is_err())
+      .map(|(id, _)| id.as_str())
+      .collect();
+    if failed.is_empty() {
+      Ok(())
+    } else {
+      Err(Error::new(
+        Status::GenericFailure,
+        format!("Failed to set state to {:?}: {}", state, failed.join(", ")),
+      ))
+    }
+  }
+}
+
+#[napi]
+impl PipelineManager {
+  /// Creates an empty manager and starts its main loop thread
+  ///
+  /// # Example
+  /// ```javascript
+  /// const manager = new PipelineManager();
+  /// ```
+  #[napi(constructor)]
+  pub fn new() -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let context = glib::MainContext::new();
+    let main_loop = glib::MainLoop::new(Some(&context), false);
+    let loop_context = context.clone();
+    let loop_clone = main_loop.clone();
+    let loop_thread = std::thread::spawn(move || {
+      let _ = loop_context.with_thread_default(|| loop_clone.run());
+    });
+
+    Ok(PipelineManager {
+      pipelines: Mutex::new(HashMap::new()),
+      callback: Arc::new(Mutex::new(None)),
+      context,
+      main_loop,
+      loop_thread: Mutex::new(Some(loop_thread)),
+    })
+  }
+
+  /// Sets the callback receiving the bus messages of every pipeline
+  ///
+  /// # Arguments
+  /// * `callback` - Called with each event, tagged with its pipeline id
+  ///
+  /// # Example
+  /// ```javascript
+  /// manager.onEvent((event) => {
+  ///   if (event.eventType === "error") console.error(event.pipelineId, event.message);
+  /// });
+  /// ```
+  #[napi]
+  pub fn on_event(
+    &self,
+    callback: ThreadsafeFunction<
+      ManagedPipelineEvent,
+      (),
+      ManagedPipelineEvent,
+      Status,
+      false,
+      true,
+    >,
+  ) {
+    *self.callback.lock().unwrap() = Some(callback);
+  }
+
+  /// Adds a pipeline from a launch string under the given id
+  ///
+  /// # Arguments
+  /// * `id` - Unique name of the pipeline
+  /// * `pipeline_string` - A valid GStreamer pipeline description
+  ///
+  /// # Example
+  /// ```javascript
+  /// manager.addPipeline("preview", "videotestsrc ! autovideosink");
+  /// ```
+  #[napi]
+  pub fn add_pipeline(&self, id: String, pipeline_string: String) -> Result<()> {
+    let mut pipelines = self.pipelines.lock().unwrap();
+    if pipelines.contains_key(&id) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Pipeline already exists: {}", id),
+      ));
+    }
+
+    let pipeline = crate::transcode::launch(&pipeline_string)?;
+    let bus = pipeline
+      .bus()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+    let callback = self.callback.clone();
+    let pipeline_weak = pipeline.downgrade();
+    let pipeline_id = id.clone();
+    let watch = bus.create_watch(None, glib::Priority::DEFAULT, move |_, msg| {
+      let Some(pipeline) = pipeline_weak.upgrade() else {
+        return glib::ControlFlow::Break;
+      };
+      if let Some(event) = message_event(&pipeline_id, &pipeline, msg) {
+        if let Some(callback) = callback.lock().unwrap().as_ref() {
+          callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+      }
+      glib::ControlFlow::Continue
+    });
+    watch.attach(Some(&self.context));
+
+    pipelines.insert(
+      id,
+      ManagedPipeline {
+        pipeline,
+        watch,
+        detached: false,
+      },
+    );
+    Ok(())
+  }
+
+  /// Stops and removes a pipeline
+  ///
+  /// # Returns
+  /// * `bool` - Whether a pipeline with this id existed
+  #[napi]
+  pub fn remove_pipeline(&self, id: String) -> bool {
+    self.pipelines.lock().unwrap().remove(&id).is_some()
+  }
+
+  /// Returns the ids of all managed pipelines
+  #[napi]
+  pub fn list_pipelines(&self) -> Vec<String> {
+    let mut ids: Vec<String> = self.pipelines.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+  }
+
+  /// Starts playback of a pipeline
+  #[napi]
+  pub fn play(&self, id: String) -> Result<()> {
+    self.set_state(&id, gst::State::Playing)
+  }
+
+  /// Pauses a pipeline
+  #[napi]
+  pub fn pause(&self, id: String) -> Result<()> {
+    self.set_state(&id, gst::State::Paused)
+  }
+
+  /// Stops a pipeline and resets it to the Null state
+  #[napi]
+  pub fn stop(&self, id: String) -> Result<()> {
+    self.set_state(&id, gst::State::Null)
+  }
+
+  /// Starts playback of every pipeline
+  #[napi]
+  pub fn play_all(&self) -> Result<()> {
+    self.set_state_all(gst::State::Playing)
+  }
+
+  /// Stops every pipeline
+  #[napi]
+  pub fn stop_all(&self) -> Result<()> {
+    self.set_state_all(gst::State::Null)
+  }
+
+  /// Returns the state and timing of every pipeline, sorted by id
+  ///
+  /// # Example
+  /// ```javascript
+  /// for (const stats of manager.statsAll()) {
+  ///   console.log(stats.id, stats.state, stats.position);
+  /// }
+  /// ```
+  #[napi]
+  pub fn stats_all(&self) -> Vec<ManagedPipelineStats> {
+    let pipelines = self.pipelines.lock().unwrap();
+    let mut stats: Vec<ManagedPipelineStats> = pipelines
+      .iter()
+      .map(|(id, managed)| {
+        let pipeline = &managed.pipeline;
+        let (_, state, _) = pipeline.state(gst::ClockTime::ZERO);
+        ManagedPipelineStats {
+          id: id.clone(),
+          state: format!("{:?}", state),
+          position: pipeline
+            .query_position::<gst::ClockTime>()
+            .map(|t| t.nseconds() as i64),
+          duration: pipeline
+            .query_duration::<gst::ClockTime>()
+            .map(|t| t.nseconds() as i64),
+        }
+      })
+      .collect();
+    stats.sort_by(|a, b| a.id.cmp(&b.id));
+    stats
+  }
+}
@@ -0,0 +1,366 @@
+//! # Missing Plugins
+//!
+//! Turns "no element" parse failures into actionable reports. When a launch
+//! string names element factories that no installed plugin provides, the
+//! error lists them together with the plugin and plugin set (base, good,
+//! bad, ugly, libav or gst-plugins-rs) they come from and the package to
+//! install on common systems. `diagnosePipeline` returns the same report as
+//! an object.
+//!
+//! The element table covers the elements used by this module's own pipelines
+//! and the most common gst-launch ones; other elements are reported without
+//! a package hint, except the FFmpeg based `av*` elements of gst-libav.
+
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Element factories and the plugin and plugin set providing them
+const ELEMENTS: &[(&str, &str, &str)] = &[
+  ("appsink", "app", "base"),
+  ("appsrc", "app", "base"),
+  ("audioconvert", "audioconvert", "base"),
+  ("audiorate", "audiorate", "base"),
+  ("audioresample", "audioresample", "base"),
+  ("audiotestsrc", "audiotestsrc", "base"),
+  ("compositor", "compositor", "base"),
+  ("decodebin", "playback", "base"),
+  ("encodebin", "encoding", "base"),
+  ("oggdemux", "ogg", "base"),
+  ("oggmux", "ogg", "base"),
+  ("opusdec", "opus", "base"),
+  ("opusenc", "opus", "base"),
+  ("playbin", "playback", "base"),
+  ("textoverlay", "pango", "base"),
+  ("theoradec", "theora", "base"),
+  ("theoraenc", "theora", "base"),
+  ("uridecodebin", "playback", "base"),
+  ("videoconvert", "videoconvertscale", "base"),
+  ("videorate", "videorate", "base"),
+  ("videoscale", "videoconvertscale", "base"),
+  ("videotestsrc", "videotestsrc", "base"),
+  ("volume", "volume", "base"),
+  ("vorbisdec", "vorbis", "base"),
+  ("vorbisenc", "vorbis", "base"),
+  ("alpha", "alpha", "good"),
+  ("autoaudiosink", "autodetect", "good"),
+  ("autoaudiosrc", "autodetect", "good"),
+  ("autovideosink", "autodetect", "good"),
+  ("autovideosrc", "autodetect", "good"),
+  ("avidemux", "avi", "good"),
+  ("avimux", "avi", "good"),
+  ("deinterlace", "deinterlace", "good"),
+  ("flacenc", "flac", "good"),
+  ("flvdemux", "flv", "good"),
+  ("flvmux", "flv", "good"),
+  ("imagefreeze", "imagefreeze", "good"),
+  ("jpegdec", "jpeg", "good"),
+  ("jpegenc", "jpeg", "good"),
+  ("lamemp3enc", "lame", "good"),
+  ("level", "level", "good"),
+  ("matroskademux", "matroska", "good"),
+  ("matroskamux", "matroska", "good"),
+  ("mp4mux", "isomp4", "good"),
+  ("mpg123audiodec", "mpg123", "good"),
+  ("multifilesink", "multifile", "good"),
+  ("pngdec", "png", "good"),
+  ("pngenc", "png", "good"),
+  ("pulsesink", "pulseaudio", "good"),
+  ("pulsesrc", "pulseaudio", "good"),
+  ("qtdemux", "isomp4", "good"),
+  ("qtmux", "isomp4", "good"),
+  ("rtph264depay", "rtp", "good"),
+  ("rtph264pay", "rtp", "good"),
+  ("rtspsrc", "rtsp", "good"),
+  ("scaletempo", "scaletempo", "good"),
+  ("souphttpsrc", "soup", "good"),
+  ("spectrum", "spectrum", "good"),
+  ("splitmuxsink", "multifile", "good"),
+  ("udpsink", "udp", "good"),
+  ("udpsrc", "udp", "good"),
+  ("v4l2sink", "video4linux2", "good"),
+  ("v4l2src", "video4linux2", "good"),
+  ("videobox", "videobox", "good"),
+  ("videocrop", "videocrop", "good"),
+  ("vp8dec", "vpx", "good"),
+  ("vp8enc", "vpx", "good"),
+  ("vp9dec", "vpx", "good"),
+  ("vp9enc", "vpx", "good"),
+  ("wavenc", "wavenc", "good"),
+  ("wavparse", "wavparse", "good"),
+  ("webmmux", "matroska", "good"),
+  ("ximagesrc", "ximagesrc", "good"),
+  ("av1enc", "aom", "bad"),
+  ("av1parse", "videoparsersbad", "bad"),
+  ("avfvideosrc", "applemedia", "bad"),
+  ("d3d11screencapturesrc", "d3d11", "bad"),
+  ("fdkaacenc", "fdkaac", "bad"),
+  ("h264parse", "videoparsersbad", "bad"),
+  ("h265parse", "videoparsersbad", "bad"),
+  ("ivfparse", "ivfparse", "bad"),
+  ("ivtc", "ivtc", "bad"),
+  ("mfvideosrc", "mediafoundation", "bad"),
+  ("mpegtsmux", "mpegtsmux", "bad"),
+  ("netsim", "netsim", "bad"),
+  ("nvh264enc", "nvcodec", "bad"),
+  ("openh264enc", "openh264", "bad"),
+  ("pitch", "soundtouch", "bad"),
+  ("rtmp2sink", "rtmp2", "bad"),
+  ("rtmp2src", "rtmp2", "bad"),
+  ("srtsink", "srt", "bad"),
+  ("srtsrc", "srt", "bad"),
+  ("svtav1enc", "svtav1", "bad"),
+  ("tsdemux", "mpegtsdemux", "bad"),
+  ("vah264enc", "va", "bad"),
+  ("voaacenc", "voaacenc", "bad"),
+  ("vp9parse", "videoparsersbad", "bad"),
+  ("webrtcbin", "webrtc", "bad"),
+  ("x265enc", "x265", "bad"),
+  ("mpeg2dec", "mpeg2dec", "ugly"),
+  ("x264enc", "x264", "ugly"),
+  ("dav1ddec", "dav1d", "rs"),
+  ("fmp4mux", "fmp4", "rs"),
+  ("rav1enc", "rav1e", "rs"),
+  ("webrtcsink", "webrtc", "rs"),
+];
+
+/// Packages of each plugin set on Debian/Ubuntu, Fedora, Arch, macOS
+/// (Homebrew) and Windows
+const PACKAGES: &[(&str, [&str; 5])] = &[
+  (
+    "base",
+    [
+      "gstreamer1.0-plugins-base",
+      "gstreamer1-plugins-base",
+      "gst-plugins-base",
+      "gstreamer",
+      "GStreamer runtime installer",
+    ],
+  ),
+  (
+    "good",
+    [
+      "gstreamer1.0-plugins-good",
+      "gstreamer1-plugins-good",
+      "gst-plugins-good",
+      "gstreamer",
+      "GStreamer runtime installer",
+    ],
+  ),
+  (
+    "bad",
+    [
+      "gstreamer1.0-plugins-bad",
+      "gstreamer1-plugins-bad-free",
+      "gst-plugins-bad",
+      "gstreamer",
+      "GStreamer runtime installer (Complete)",
+    ],
+  ),
+  (
+    "ugly",
+    [
+      "gstreamer1.0-plugins-ugly",
+      "gstreamer1-plugins-ugly",
+      "gst-plugins-ugly",
+      "gstreamer",
+      "GStreamer runtime installer (Complete)",
+    ],
+  ),
+  (
+    "libav",
+    [
+      "gstreamer1.0-libav",
+      "gstreamer1-plugin-libav",
+      "gst-libav",
+      "gstreamer",
+      "GStreamer runtime installer (Complete)",
+    ],
+  ),
+  (
+    "rs",
+    [
+      "gstreamer1.0-plugins-rs",
+      "gstreamer1-plugins-rs",
+      "gst-plugins-rs",
+      "gstreamer",
+      "GStreamer runtime installer (Complete)",
+    ],
+  ),
+];
+
+/// Systems of the `PACKAGES` columns
+const SYSTEMS: [&str; 5] = ["debian", "fedora", "arch", "macos", "windows"];
+
+/// A package providing a missing element on one system
+#[napi(object)]
+pub struct PackageHint {
+  /// "debian" (also Ubuntu), "fedora", "arch", "macos" (Homebrew) or "windows"
+  pub system: String,
+  /// Name of the package to install
+  pub package: String,
+}
+
+/// An element factory named in a launch string that is not installed
+#[napi(object)]
+pub struct MissingElement {
+  /// Name of the element factory, e.g. "x264enc"
+  pub factory: String,
+  /// Name of the plugin providing it, if known
+  pub plugin: Option<String>,
+  /// Plugin set the plugin belongs to ("base", "good", "bad", "ugly",
+  /// "libav" or "rs"), if known
+  pub plugin_set: Option<String>,
+  /// Packages to install, empty if unknown
+  pub packages: Vec<PackageHint>,
+}
+
+/// Result of `diagnosePipeline`
+#[napi(object)]
+pub struct PipelineDiagnosis {
+  /// Whether the launch string parses into a pipeline
+  pub valid: bool,
+  /// The parse error, if any
+  pub error: Option<String>,
+  /// Elements that are not installed
+  pub missing: Vec<MissingElement>,
+}
+
+/// Looks up where an element factory comes from
+fn missing_element(factory: &str) -> MissingElement {
+  let found = ELEMENTS
+    .iter()
+    .find(|(name, _, _)| *name == factory)
+    .map(|&(_, plugin, set)| (plugin, set))
+    .or_else(|| {
+      ["avdec_", "avenc_", "avmux_", "avdemux_"]
+        .iter()
+        .any(|prefix| factory.starts_with(prefix))
+        .then_some(("libav", "libav"))
+    });
+  let packages = found
+    .and_then(|(_, set)| PACKAGES.iter().find(|(name, _)| *name == set))
+    .map(|(_, packages)| {
+      SYSTEMS
+        .iter()
+        .zip(packages)
+        .map(|(system, package)| PackageHint {
+          system: system.to_string(),
+          package: package.to_string(),
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  MissingElement {
+    factory: factory.to_string(),
+    plugin: found.map(|(plugin, _)| plugin.to_string()),
+    plugin_set: found.map(|(_, set)| set.to_string()),
+    packages,
+  }
+}
+
+/// Describes a missing element on one line, e.g. "x264enc (x264 plugin of
+/// gst-plugins-ugly; debian: gstreamer1.0-plugins-ugly, ...)"
+fn describe(element: &MissingElement) -> String {
+  match (&element.plugin, &element.plugin_set) {
+    (Some(plugin), Some(set)) => format!(
+      "{} ({} plugin of {}; {})",
+      element.factory,
+      plugin,
+      match set.as_str() {
+        "libav" => "gst-libav".to_string(),
+        set => format!("gst-plugins-{}", set),
+      },
+      element
+        .packages
+        .iter()
+        .map(|hint| format!("{}: {}", hint.system, hint.package))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    _ => format!("{} (unknown plugin)", element.factory),
+  }
+}
+
+/// Parses a launch string, returning the parse error and the missing elements
+fn parse(description: &str) -> std::result::Result<gst::Element, (String, Vec<MissingElement>)> {
+  let mut context = gst::ParseContext::new();
+  gst::parse::launch_full(description, Some(&mut context), gst::ParseFlags::empty()).map_err(|e| {
+    let missing = context
+      .missing_elements()
+      .iter()
+      .map(|factory| missing_element(factory))
+      .collect();
+    (e.to_string(), missing)
+  })
+}
+
+/// Parses a launch string; when elements are missing, the error lists them
+/// with the packages providing them
+pub(crate) fn parse_launch(description: &str) -> Result<gst::Element> {
+  parse(description).map_err(|(error, missing)| {
+    let mut message = format!("Failed to parse pipeline: {}", error);
+    if !missing.is_empty() {
+      message.push_str(&format!(
+        "\nMissing elements: {}",
+        missing.iter().map(describe).collect::<Vec<_>>().join("; ")
+      ));
+    }
+    Error::new(Status::GenericFailure, message)
+  })
+}
+
+/// Creates an element; when it is not installed, the error names the
+/// package providing it
+pub(crate) fn make_element(factory: &str) -> Result<gst::Element> {
+  gst::ElementFactory::make(factory).build().map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      format!(
+        "Element {} is not available: {}",
+        factory,
+        describe(&missing_element(factory))
+      ),
+    )
+  })
+}
+
+/// Checks a launch string and reports the elements it needs that are not
+/// installed, with the packages providing them
+///
+/// # Arguments
+/// * `pipeline_string` - A GStreamer pipeline description
+///
+/// # Returns
+/// * `Result<PipelineDiagnosis>` - Whether it parses, and what is missing
+///
+/// # Example
+/// ```javascript
+/// const { valid, missing } = diagnosePipeline("videotestsrc ! x264enc ! mp4mux ! filesink location=out.mp4");
+/// for (const element of missing) {
+///   const hint = element.packages.find((p) => p.system === "debian");
+///   console.log(`${element.factory}: apt install ${hint?.package}`);
+/// }
+/// ```
+#[napi]
+pub fn diagnose_pipeline(pipeline_string: String) -> Result<PipelineDiagnosis> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  Ok(match parse(&pipeline_string) {
+    Ok(_) => PipelineDiagnosis {
+      valid: true,
+      error: None,
+      missing: Vec::new(),
+    },
+    Err((error, missing)) => PipelineDiagnosis {
+      valid: false,
+      error: Some(error),
+      missing,
+    },
+  })
+}
@@ -0,0 +1,355 @@
+//! # MSE Streamer
+//!
+//! Live video for a browser `<video>` element through Media Source
+//! Extensions. Encoded VP8, VP9 or AV1 frames pushed from JavaScript, or the
+//! frames reaching an AppSink of a kit, are muxed into fragmented WebM (see
+//! `fragmentFromPipeline`) and delivered as numbered segments: the
+//! initialization segment first, then media segments of one cluster each,
+//! every one starting with a keyframe. A WebSocket server sends the
+//! initialization segment to each client as it connects, followed by the
+//! media segments from the next one on.
+
+use crate::fragmented_webm::{
+  feed_from_sink, FragmentOptions, FragmentOutput, FragmentSink, WebmFragmentWriter,
+};
+use crate::kit::GstKit;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::bindgen_prelude::Buffer;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+
+/// Options for `MseStreamer`
+#[napi(object)]
+pub struct MseStreamerOptions {
+  /// Codec of the stream: "vp8" (default), "vp9" or "av1". Pushed frames
+  /// must be in this codec; raw frames from an attached sink are encoded
+  /// to it.
+  pub codec: Option<String>,
+  /// Width of pushed frames in pixels, required by `pushFrame`
+  pub width: Option<u32>,
+  /// Height of pushed frames in pixels, required by `pushFrame`
+  pub height: Option<u32>,
+  /// Frame rate of pushed frames (default: 30)
+  pub framerate: Option<u32>,
+  /// Target bitrate in kbit/s when encoding raw frames
+  pub video_bitrate: Option<u32>,
+  /// Encoder speed preset when encoding raw frames (see `listPresets`)
+  pub preset: Option<String>,
+  /// Keyframe interval when encoding raw frames, in milliseconds
+  /// (default: 1000)
+  pub fragment_duration_ms: Option<u32>,
+}
+
+/// A segment of an MSE stream
+#[napi(object)]
+pub struct MseSegment {
+  /// "init" for the initialization segment, "media" for media segments
+  pub segment_type: String,
+  /// Number of the media segment, from 0; not set for the initialization
+  /// segment
+  pub sequence: Option<u32>,
+  /// Timestamp of the first frame in nanoseconds, from the first frame of
+  /// the stream
+  pub timestamp: i64,
+  /// Bytes to append to the `SourceBuffer`
+  pub data: Buffer,
+}
+
+/// Callback receiving segments
+type SegmentCallback = ThreadsafeFunction<MseSegment, (), MseSegment, Status, false, true>;
+
+/// State shared with the fragment writer
+#[derive(Default)]
+struct Output {
+  callback: Option<SegmentCallback>,
+  init: Option<Vec<u8>>,
+  segments: u32,
+}
+
+/// Codecs parameter of the MIME type of a stream
+fn mime_codec(codec: &str) -> &'static str {
+  match codec {
+    "vp9" => "vp9",
+    "av1" => "av01.0.08M.08",
+    _ => "vp8",
+  }
+}
+
+/// Muxes live encoded frames into Media Source Extensions segments
+#[napi]
+pub struct MseStreamer {
+  codec: String,
+  caps: Option<gst::Caps>,
+  frame_duration: gst::ClockTime,
+  writer: Arc<Mutex<WebmFragmentWriter>>,
+  output: Arc<Mutex<Output>>,
+  /// Whether a keyframe has been pushed, before which frames are dropped
+  started: bool,
+  appsink: Option<AppSink>,
+}
+
+#[napi]
+impl MseStreamer {
+  /// Creates a streamer; nothing is muxed until the first frame
+  ///
+  /// # Arguments
+  /// * `options` - Codec, frame size and encoder settings
+  ///
+  /// # Example
+  /// ```javascript
+  /// const streamer = new MseStreamer({ codec: "vp8", width: 1280, height: 720, framerate: 30 });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: Option<MseStreamerOptions>) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+    let options = options.unwrap_or(MseStreamerOptions {
+      codec: None,
+      width: None,
+      height: None,
+      framerate: None,
+      video_bitrate: None,
+      preset: None,
+      fragment_duration_ms: None,
+    });
+    let codec = options.codec.unwrap_or_else(|| "vp8".to_string());
+    let framerate = options.framerate.unwrap_or(30);
+    if framerate == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "framerate must be positive".to_string(),
+      ));
+    }
+
+    let output = Arc::new(Mutex::new(Output::default()));
+    let writer_output = output.clone();
+    let sink: FragmentSink = Arc::new(move |fragment| {
+      let mut output = writer_output.lock().unwrap();
+      let segment = match fragment {
+        FragmentOutput::Init(init) => {
+          output.init = Some(init.clone());
+          MseSegment {
+            segment_type: "init".to_string(),
+            sequence: None,
+            timestamp: 0,
+            data: init.into(),
+          }
+        }
+        FragmentOutput::Fragment(fragment) => {
+          output.segments += 1;
+          MseSegment {
+            segment_type: "media".to_string(),
+            sequence: Some(fragment.sequence),
+            timestamp: fragment.timestamp as i64,
+            data: fragment.data.into(),
+          }
+        }
+      };
+      if let Some(callback) = &output.callback {
+        callback.call(segment, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    });
+    let writer = WebmFragmentWriter::create(
+      &FragmentOptions {
+        video_codec: Some(codec.clone()),
+        video_bitrate: options.video_bitrate,
+        preset: options.preset,
+        tune: None,
+        fragment_duration_ms: options.fragment_duration_ms,
+        max_queued: None,
+      },
+      sink,
+    )?;
+
+    let caps = match (options.width, options.height) {
+      (Some(width), Some(height)) => Some(
+        gst::Caps::builder(format!("video/x-{}", codec))
+          .field("width", width as i32)
+          .field("height", height as i32)
+          .field("framerate", gst::Fraction::new(framerate as i32, 1))
+          .build(),
+      ),
+      _ => None,
+    };
+    Ok(MseStreamer {
+      codec,
+      caps,
+      frame_duration: gst::ClockTime::SECOND / framerate as u64,
+      writer: Arc::new(Mutex::new(writer)),
+      output,
+      started: false,
+      appsink: None,
+    })
+  }
+
+  /// MIME type to pass to `MediaSource.addSourceBuffer`
+  #[napi(getter)]
+  pub fn mime_type(&self) -> String {
+    format!("video/webm; codecs=\"{}\"", mime_codec(&self.codec))
+  }
+
+  /// Sets the callback receiving the initialization and media segments
+  ///
+  /// Segments produced before a callback is set are not delivered, apart
+  /// from the initialization segment, which `initSegment` keeps.
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every segment, in order
+  ///
+  /// # Example
+  /// ```javascript
+  /// streamer.onSegment((segment) => {
+  ///   for (const client of wss.clients) client.send(segment.data);
+  /// });
+  /// ```
+  #[napi]
+  pub fn on_segment(&self, callback: SegmentCallback) {
+    self.output.lock().unwrap().callback = Some(callback);
+  }
+
+  /// Returns the initialization segment, or null until the first media
+  /// segment has started; send it to each client before any media segment
+  ///
+  /// # Example
+  /// ```javascript
+  /// wss.on("connection", (ws) => ws.send(streamer.initSegment()));
+  /// ```
+  #[napi]
+  pub fn init_segment(&self) -> Option<Buffer> {
+    self.output.lock().unwrap().init.clone().map(Buffer::from)
+  }
+
+  /// Number of media segments produced so far
+  #[napi(getter)]
+  pub fn segments(&self) -> u32 {
+    self.output.lock().unwrap().segments
+  }
+
+  /// Pushes an encoded frame
+  ///
+  /// Frames before the first keyframe are dropped, since a decoder cannot
+  /// start with them.
+  ///
+  /// # Arguments
+  /// * `data` - The frame in the streamer's codec
+  /// * `timestamp_ns` - Presentation time of the frame in nanoseconds
+  ///   (default: one frame after the previous one)
+  /// * `keyframe` - Whether the frame is a keyframe (default: false)
+  ///
+  /// # Returns
+  /// * `Result<bool>` - Whether the frame was muxed
+  ///
+  /// # Example
+  /// ```javascript
+  /// encoder.on("frame", (frame) => streamer.pushFrame(frame.data, frame.pts, frame.keyframe));
+  /// ```
+  #[napi]
+  pub fn push_frame(
+    &mut self,
+    data: Buffer,
+    timestamp_ns: Option<i64>,
+    keyframe: Option<bool>,
+  ) -> Result<bool> {
+    let caps = self.caps.clone().ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        "pushFrame needs the width and height options".to_string(),
+      )
+    })?;
+    if self.appsink.is_some() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Cannot push frames while attached to a sink".to_string(),
+      ));
+    }
+    let keyframe = keyframe.unwrap_or(false);
+    if !self.started && !keyframe {
+      return Ok(false);
+    }
+    self.started = true;
+
+    let mut writer = self.writer.lock().unwrap();
+    let mut buffer = gst::Buffer::from_slice(data.to_vec());
+    {
+      let buffer = buffer.get_mut().unwrap();
+      let pts = match timestamp_ns {
+        Some(ns) => gst::ClockTime::from_nseconds(ns.max(0) as u64),
+        None => self.frame_duration * writer.frames as u64,
+      };
+      buffer.set_pts(pts);
+      buffer.set_duration(self.frame_duration);
+      if !keyframe {
+        buffer.set_flags(gst::BufferFlags::DELTA_UNIT);
+      }
+    }
+    let sample = gst::Sample::builder().buffer(&buffer).caps(&caps).build();
+    writer
+      .push(&sample)
+      .map_err(|reason| Error::new(Status::GenericFailure, reason))?;
+    Ok(true)
+  }
+
+  /// Streams the frames reaching an AppSink instead of pushed frames
+  ///
+  /// Raw frames are encoded to the streamer's codec; frames already in it
+  /// are muxed as they are. The sink's callbacks are taken over until the
+  /// streamer ends.
+  ///
+  /// # Arguments
+  /// * `kit` - The kit running the pipeline
+  /// * `sink_name` - The name of the AppSink element
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+  /// streamer.attach(kit, "sink");
+  /// kit.play();
+  /// ```
+  #[napi]
+  pub fn attach(&mut self, kit: &GstKit, sink_name: String) -> Result<()> {
+    if self.appsink.is_some() || self.started {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "The streamer already has a frame source".to_string(),
+      ));
+    }
+    let appsink = kit.app_sink(&sink_name)?;
+    feed_from_sink(&appsink, self.writer.clone());
+    self.appsink = Some(appsink);
+    Ok(())
+  }
+
+  /// Ends the stream, delivering the last media segment
+  ///
+  /// # Example
+  /// ```javascript
+  /// streamer.end();
+  /// ```
+  #[napi]
+  pub fn end(&mut self) -> Result<()> {
+    if let Some(appsink) = self.appsink.take() {
+      appsink.set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    }
+    let mut writer = self.writer.lock().unwrap();
+    let result = writer.finish();
+    // A failed attached sink is only reported here
+    match writer.error.take() {
+      Some(error) => Err(Error::new(Status::GenericFailure, error)),
+      None => result,
+    }
+  }
+}
+
+impl Drop for MseStreamer {
+  fn drop(&mut self) {
+    let _ = self.end();
+  }
+}
@@ -0,0 +1,168 @@
+//! # Network Impairment
+//!
+//! Simulated bad networks for testing streaming code. A `netsim` element is
+//! placed in front of a sink, usually the `udpsink` of an RTP stream or the
+//! `srtsink` of an SRT stream, and drops, delays, duplicates or reorders the
+//! packets reaching it, or caps their bandwidth. The element is inserted
+//! while the pipeline runs, and the impairment can be changed or cleared at
+//! any time, e.g. to check that a receiver recovers once the loss stops.
+
+use crate::missing_plugins::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Impairment of the packets reaching a sink
+#[napi(object)]
+#[derive(Clone)]
+pub struct NetworkImpairment {
+  /// Share of packets dropped, from 0 to 1 (default: 0)
+  pub loss: Option<f64>,
+  /// Delay added to every packet in milliseconds (default: 0)
+  pub delay_ms: Option<u32>,
+  /// Random variation of the delay in milliseconds, up to `delayMs` either
+  /// way (default: 0)
+  pub jitter_ms: Option<u32>,
+  /// Bandwidth cap in kbit/s; packets over it are dropped, as by a
+  /// congested link (default: unlimited)
+  pub bandwidth_kbps: Option<u32>,
+  /// Share of packets sent twice, from 0 to 1 (default: 0)
+  pub duplicate: Option<f64>,
+  /// Whether jittered packets may overtake each other (default: false)
+  pub reorder: Option<bool>,
+}
+
+/// Name of the element impairing the input of `target`
+fn netsim_name(target: &str) -> String {
+  format!("{}-netsim", target)
+}
+
+/// Applies an impairment to a `netsim` element
+fn configure(netsim: &gst::Element, impairment: &NetworkImpairment) -> Result<()> {
+  for (name, value) in [
+    ("loss", impairment.loss),
+    ("duplicate", impairment.duplicate),
+  ] {
+    if value.is_some_and(|value| !(0.0..=1.0).contains(&value)) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("{} must be between 0 and 1", name),
+      ));
+    }
+  }
+  let delay = impairment.delay_ms.unwrap_or(0);
+  let jitter = impairment.jitter_ms.unwrap_or(0).min(delay);
+  netsim.set_property("drop-probability", impairment.loss.unwrap_or(0.0) as f32);
+  netsim.set_property(
+    "duplicate-probability",
+    impairment.duplicate.unwrap_or(0.0) as f32,
+  );
+  netsim.set_property_from_str("delay-distribution", "uniform");
+  // Keep the minimum under the maximum at every step, since packets may
+  // be flowing
+  netsim.set_property("min-delay", 0i32);
+  netsim.set_property("max-delay", (delay + jitter) as i32);
+  netsim.set_property("min-delay", (delay - jitter) as i32);
+  netsim.set_property(
+    "max-kbps",
+    impairment
+      .bandwidth_kbps
+      .map_or(-1, |kbps| kbps.min(i32::MAX as u32) as i32),
+  );
+  netsim.set_property("allow-reordering", impairment.reorder.unwrap_or(false));
+  Ok(())
+}
+
+/// Impairs the packets reaching `target`, inserting a `netsim` element in
+/// front of it on first use
+pub(crate) fn impair(
+  pipeline: &gst::Pipeline,
+  target: &str,
+  impairment: &NetworkImpairment,
+) -> Result<()> {
+  let not_found = |name: &str| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Element {} not found", name),
+    )
+  };
+  let element = pipeline.by_name(target).ok_or_else(|| not_found(target))?;
+  let is_netsim = element
+    .factory()
+    .is_some_and(|factory| factory.name() == "netsim");
+  if is_netsim {
+    return configure(&element, impairment);
+  }
+  if let Some(netsim) = pipeline.by_name(&netsim_name(target)) {
+    return configure(&netsim, impairment);
+  }
+
+  let sink_pad = element.sink_pads().into_iter().next().ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} has no input to impair", target),
+    )
+  })?;
+  let upstream = sink_pad
+    .peer()
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("{} is not linked", target)))?;
+  let bin = element
+    .parent()
+    .and_then(|parent| parent.downcast::<gst::Bin>().ok())
+    .ok_or_else(|| not_found(target))?;
+  let netsim = make_element("netsim")?;
+  netsim.set_property("name", netsim_name(target));
+  configure(&netsim, impairment)?;
+  bin.add(&netsim).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add netsim: {}", e),
+    )
+  })?;
+  let _ = netsim.sync_state_with_parent();
+
+  // Relink once no data is flowing; this runs right away when the pad is
+  // already idle
+  let inserted = netsim.clone();
+  upstream.add_probe(gst::PadProbeType::IDLE, move |upstream, _| {
+    if let (Some(netsim_sink), Some(netsim_src)) =
+      (inserted.static_pad("sink"), inserted.static_pad("src"))
+    {
+      let _ = upstream.unlink(&sink_pad);
+      let _ = upstream.link(&netsim_sink);
+      let _ = netsim_src.link(&sink_pad);
+    }
+    gst::PadProbeReturn::Remove
+  });
+  Ok(())
+}
+
+/// Lets packets reach `target` unimpaired again
+///
+/// # Returns
+/// * `bool` - Whether `target` was impaired
+pub(crate) fn clear(pipeline: &gst::Pipeline, target: &str) -> Result<bool> {
+  let netsim = pipeline.by_name(&netsim_name(target)).or_else(|| {
+    pipeline
+      .by_name(target)
+      .filter(|element| element.factory().is_some_and(|f| f.name() == "netsim"))
+  });
+  match netsim {
+    Some(netsim) => {
+      configure(
+        &netsim,
+        &NetworkImpairment {
+          loss: None,
+          delay_ms: None,
+          jitter_ms: None,
+          bandwidth_kbps: None,
+          duplicate: None,
+          reorder: None,
+        },
+      )?;
+      Ok(true)
+    }
+    None => Ok(false),
+  }
+}
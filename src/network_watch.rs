@@ -0,0 +1,299 @@
+//! # Network Watch
+//!
+//! Buffering and connection events of pipelines reading from the network,
+//! such as `uridecodebin`, `playbin` (`setPlaylist`) or `souphttpsrc`
+//! pipelines. Buffering messages are reported with their fill level, and by
+//! default playback pauses while the buffer refills and resumes once it is
+//! full, as players are expected to do; live streams are never paused.
+//! Read errors of network sources are reported as a lost connection, which
+//! can be retried by restarting the pipeline, and the first data after that
+//! as a restored one.
+//!
+//! Messages are observed as they are posted, through sync-message signals,
+//! so the bus stays free for `setPlaylist` and for pulling errors.
+
+use crate::kit::GstKit;
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Options for `watchNetwork`
+#[napi(object)]
+pub struct NetworkWatchOptions {
+  /// Pause playback while buffering and resume when the buffer is full
+  /// (default: true)
+  pub pause_while_buffering: Option<bool>,
+  /// Times a lost connection is retried by restarting the pipeline
+  /// (default: 0)
+  pub reconnect_attempts: Option<u32>,
+  /// Delay before each retry in milliseconds (default: 1000)
+  pub reconnect_delay_ms: Option<u32>,
+}
+
+/// Buffering or connection change of a watched pipeline
+#[napi(object)]
+#[derive(Clone)]
+pub struct NetworkEvent {
+  /// "buffering", "connection-lost", "reconnecting" or "connection-restored"
+  pub event_type: String,
+  /// Buffer fill level from 0 to 100, for "buffering" events
+  pub percent: Option<u32>,
+  /// Buffering mode for "buffering" events: "stream", "download",
+  /// "timeshift" or "live"
+  pub mode: Option<String>,
+  /// Name of the element the event came from
+  pub source: Option<String>,
+  /// Description of the error for "connection-lost" events
+  pub message: Option<String>,
+  /// Retry number for "reconnecting" events, from 1
+  pub attempt: Option<u32>,
+}
+
+/// Callback receiving network events
+type NetworkCallback = ThreadsafeFunction<NetworkEvent, (), NetworkEvent, Status, false, true>;
+
+/// State shared with the sync-message handler
+#[derive(Default)]
+struct Watch {
+  callback: Option<NetworkCallback>,
+  percent: u32,
+  /// Whether the watcher paused playback to buffer
+  paused: bool,
+  connected: bool,
+  attempts: u32,
+}
+
+impl Watch {
+  fn emit(&self, event: NetworkEvent) {
+    if let Some(callback) = &self.callback {
+      callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+}
+
+fn event(event_type: &str) -> NetworkEvent {
+  NetworkEvent {
+    event_type: event_type.to_string(),
+    percent: None,
+    mode: None,
+    source: None,
+    message: None,
+    attempt: None,
+  }
+}
+
+/// Whether `object` is a source reading from something other than a file
+fn is_network_source(object: &gst::Object) -> bool {
+  object
+    .dynamic_cast_ref::<gst::URIHandler>()
+    .is_some_and(|handler| {
+      handler.uri_type() == gst::URIType::Src
+        && !handler
+          .protocols()
+          .iter()
+          .any(|protocol| protocol == "file")
+    })
+}
+
+/// Pipeline buffering and connection events, created by `watchNetwork`
+#[napi]
+pub struct NetworkWatcher {
+  bus: gst::Bus,
+  handler: Mutex<Option<glib::SignalHandlerId>>,
+  watch: Arc<Mutex<Watch>>,
+}
+
+#[napi]
+impl NetworkWatcher {
+  /// Sets the callback receiving buffering and connection events
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every event
+  ///
+  /// # Example
+  /// ```javascript
+  /// watcher.onEvent((event) => {
+  ///   if (event.eventType === "buffering") spinner.show(event.percent < 100);
+  /// });
+  /// ```
+  #[napi]
+  pub fn on_event(&self, callback: NetworkCallback) {
+    self.watch.lock().unwrap().callback = Some(callback);
+  }
+
+  /// Fill level of the buffer from 0 to 100 at the last buffering message
+  #[napi(getter)]
+  pub fn buffering(&self) -> u32 {
+    self.watch.lock().unwrap().percent
+  }
+
+  /// Whether the network sources are connected, as far as known
+  #[napi(getter)]
+  pub fn connected(&self) -> bool {
+    self.watch.lock().unwrap().connected
+  }
+
+  /// Stops watching; playback paused for buffering is not resumed
+  #[napi]
+  pub fn stop(&self) {
+    if let Some(handler) = self.handler.lock().unwrap().take() {
+      self.bus.disconnect(handler);
+      self.bus.disable_sync_message_emission();
+    }
+  }
+}
+
+impl Drop for NetworkWatcher {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Restarts `pipeline` after `delay`, back to the state it was going to
+fn schedule_reconnect(pipeline: glib::WeakRef<gst::Pipeline>, delay: Duration) {
+  std::thread::spawn(move || {
+    std::thread::sleep(delay);
+    if let Some(pipeline) = pipeline.upgrade() {
+      let target = pipeline.current_state().max(pipeline.pending_state());
+      let _ = pipeline.set_state(gst::State::Ready);
+      let _ = pipeline.set_state(target.max(gst::State::Paused));
+    }
+  });
+}
+
+/// Watches the pipeline of a kit for buffering and connection events
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline; its current pipeline is watched
+/// * `options` - Buffering and reconnection behavior
+///
+/// # Returns
+/// * `Result<NetworkWatcher>` - The running watcher
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("uridecodebin uri=https://example.com/live.m3u8 ! videoconvert ! autovideosink");
+/// const watcher = watchNetwork(kit, { reconnectAttempts: 5 });
+/// watcher.onEvent((event) => console.log(event.eventType, event.percent ?? ""));
+/// kit.play();
+/// ```
+#[napi]
+pub fn watch_network(kit: &GstKit, options: Option<NetworkWatchOptions>) -> Result<NetworkWatcher> {
+  let options = options.unwrap_or(NetworkWatchOptions {
+    pause_while_buffering: None,
+    reconnect_attempts: None,
+    reconnect_delay_ms: None,
+  });
+  let pause = options.pause_while_buffering.unwrap_or(true);
+  let max_attempts = options.reconnect_attempts.unwrap_or(0);
+  let delay = Duration::from_millis(options.reconnect_delay_ms.unwrap_or(1000) as u64);
+
+  let pipeline = kit.current_pipeline()?;
+  let bus = pipeline
+    .bus()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus".to_string()))?;
+  let watch = Arc::new(Mutex::new(Watch {
+    percent: 100,
+    connected: true,
+    ..Watch::default()
+  }));
+
+  let handler_watch = watch.clone();
+  let pipeline_weak = pipeline.downgrade();
+  bus.enable_sync_message_emission();
+  let handler = bus.connect_sync_message(None, move |_, msg| {
+    let Some(pipeline) = pipeline_weak.upgrade() else {
+      return;
+    };
+    let source = msg.src().map(|src| src.name().to_string());
+    let mut watch = handler_watch.lock().unwrap();
+    match msg.view() {
+      gst::MessageView::Buffering(buffering) => {
+        let percent = buffering.percent().clamp(0, 100) as u32;
+        let (mode, ..) = buffering.buffering_stats();
+        watch.percent = percent;
+        if !watch.connected {
+          watch.connected = true;
+          watch.attempts = 0;
+          watch.emit(NetworkEvent {
+            source: source.clone(),
+            ..event("connection-restored")
+          });
+        }
+        watch.emit(NetworkEvent {
+          percent: Some(percent),
+          mode: Some(
+            match mode {
+              gst::BufferingMode::Download => "download",
+              gst::BufferingMode::Timeshift => "timeshift",
+              gst::BufferingMode::Live => "live",
+              _ => "stream",
+            }
+            .to_string(),
+          ),
+          source,
+          ..event("buffering")
+        });
+
+        if !pause || mode == gst::BufferingMode::Live {
+          return;
+        }
+        let playing = pipeline.current_state().max(pipeline.pending_state()) == gst::State::Playing;
+        // State changes must not be made from a streaming thread
+        if percent < 100 && playing && !watch.paused {
+          watch.paused = true;
+          pipeline.call_async(|pipeline| {
+            let _ = pipeline.set_state(gst::State::Paused);
+          });
+        } else if percent == 100 && watch.paused {
+          watch.paused = false;
+          pipeline.call_async(|pipeline| {
+            let _ = pipeline.set_state(gst::State::Playing);
+          });
+        }
+      }
+      gst::MessageView::Error(err)
+        if err.error().kind::<gst::ResourceError>().is_some()
+          && msg.src().is_some_and(is_network_source) =>
+      {
+        if watch.connected {
+          watch.connected = false;
+          watch.emit(NetworkEvent {
+            source,
+            message: Some(err.error().to_string()),
+            ..event("connection-lost")
+          });
+        }
+        if watch.attempts < max_attempts {
+          watch.attempts += 1;
+          watch.emit(NetworkEvent {
+            attempt: Some(watch.attempts),
+            ..event("reconnecting")
+          });
+          schedule_reconnect(pipeline.downgrade(), delay);
+        }
+      }
+      gst::MessageView::StateChanged(change)
+        if !watch.connected
+          && change.current() == gst::State::Playing
+          && msg.src() == Some(pipeline.upcast_ref::<gst::Object>()) =>
+      {
+        watch.connected = true;
+        watch.attempts = 0;
+        watch.emit(event("connection-restored"));
+      }
+      _ => {}
+    }
+  });
+
+  Ok(NetworkWatcher {
+    bus,
+    handler: Mutex::new(Some(handler)),
+    watch,
+  })
+}
@@ -0,0 +1,271 @@
+//! # Overlays
+//!
+//! Composites a foreground video over a background video or still image and
+//! encodes the result: the green screen workflow, where video filters such as
+//! a chroma key make the foreground partly transparent before it is placed.
+
+use crate::benchmark::parse_resolution;
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{audio_codec_spec, container_spec, launch, video_codec_spec, wait_for_eos};
+use crate::video_filters::{make_video_filters, parse_video_filters};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+
+/// Options for `overlayVideo`
+#[napi(object)]
+pub struct OverlayOptions {
+  /// Video filters applied to the foreground before it is composited, e.g.
+  /// `["chromakey=#00FF00:0.15:0.1"]`
+  pub filters: Option<Vec<String>>,
+  /// Horizontal position of the foreground's left edge (default: 0)
+  pub x: Option<i32>,
+  /// Vertical position of the foreground's top edge (default: 0)
+  pub y: Option<i32>,
+  /// Displayed foreground width (default: the foreground width)
+  pub width: Option<i32>,
+  /// Displayed foreground height (default: the foreground height)
+  pub height: Option<i32>,
+  /// Output frame size as "WIDTHxHEIGHT" (default: the background size)
+  pub resolution: Option<String>,
+  /// Output frames per second (default: 30)
+  pub fps: Option<u32>,
+  /// Keep the audio of the foreground (default: true)
+  pub audio: Option<bool>,
+  /// Output container; defaults to the output file extension
+  pub container: Option<String>,
+  /// Video codec, defaults to the usual codec for the container
+  pub video_codec: Option<String>,
+}
+
+/// Composites a foreground video over a background video or image
+///
+/// The output lasts as long as the longer input; a still image background is
+/// shown for the whole foreground.
+///
+/// # Arguments
+/// * `foreground` - Path or URI of the video drawn on top
+/// * `background` - Path or URI of the background video or image
+/// * `output_path` - Where to write the composited video
+/// * `options` - Foreground filters and placement, output size and format
+///
+/// # Example
+/// ```javascript
+/// overlayVideo("presenter-green.mp4", "studio.png", "presenter.mp4", {
+///   filters: ["chromakey=#00FF00:0.15:0.1"],
+///   x: 640,
+///   y: 180,
+/// });
+/// ```
+#[napi]
+pub fn overlay_video(
+  foreground: String,
+  background: String,
+  output_path: String,
+  options: Option<OverlayOptions>,
+) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(OverlayOptions {
+    filters: None,
+    x: None,
+    y: None,
+    width: None,
+    height: None,
+    resolution: None,
+    fps: None,
+    audio: None,
+    container: None,
+    video_codec: None,
+  });
+  let filters = parse_video_filters(options.filters.as_deref().unwrap_or_default())?;
+  let fps = options.fps.unwrap_or(30).max(1);
+  let container = options
+    .container
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer a container for {}", output_path),
+      )
+    })?;
+  let container = container_spec(&container)?;
+  let codec = video_codec_spec(
+    options
+      .video_codec
+      .as_deref()
+      .unwrap_or(container.video_codec),
+  )?;
+
+  let foreground_info = probe_with_gstreamer(foreground.clone(), None)?;
+  if foreground_info.video.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{} has no video stream", foreground),
+    ));
+  }
+  let background_info = probe_with_gstreamer(background.clone(), None)?;
+  let background_video = background_info.video.first().ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} has no video stream or image", background),
+    )
+  })?;
+  let (width, height) = match options.resolution.as_deref() {
+    Some(resolution) => parse_resolution(resolution)?,
+    None => (background_video.width, background_video.height),
+  };
+  // A still image is turned upright and repeated for as long as the
+  // foreground plays; without a known duration it is ended with the foreground
+  let end_with_foreground = background_video.is_image && foreground_info.duration < 0;
+  let (orient, freeze) = if !background_video.is_image {
+    ("", String::new())
+  } else if end_with_foreground {
+    (
+      "videoflip video-direction=auto ! ",
+      "imagefreeze ! ".to_string(),
+    )
+  } else {
+    let frames = (foreground_info.duration as f64 * fps as f64 / 1e9).ceil() as i64;
+    (
+      "videoflip video-direction=auto ! ",
+      format!("imagefreeze num-buffers={} ! ", frames.max(1)),
+    )
+  };
+  let mut layout = format!(
+    "sink_1::xpos={} sink_1::ypos={}",
+    options.x.unwrap_or(0),
+    options.y.unwrap_or(0)
+  );
+  if let Some(w) = options.width {
+    layout.push_str(&format!(" sink_1::width={}", w));
+  }
+  if let Some(h) = options.height {
+    layout.push_str(&format!(" sink_1::height={}", h));
+  }
+
+  let parser = codec
+    .parser
+    .map(|p| format!(" ! {}", p))
+    .unwrap_or_default();
+  let mut description = format!(
+    "compositor name=mix background=black sink_0::zorder=0 sink_1::zorder=1 {} ! \
+     video/x-raw,width={},height={},framerate={}/1 ! videoconvert ! {}{} ! queue ! \
+     {} name=mux ! filesink name=sink \
+     uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! {}videoconvert ! {}\
+     videorate ! videoscale ! video/x-raw,width={},height={},framerate={}/1 ! queue ! mix.sink_0 \
+     uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! videoconvert name=fgin \
+     videoconvert name=fgout ! videorate ! video/x-raw,format=AYUV,framerate={}/1 ! queue ! mix.sink_1",
+    layout,
+    width,
+    height,
+    fps,
+    codec.encoder,
+    parser,
+    container.muxer,
+    to_uri(&background)?,
+    orient,
+    freeze,
+    width,
+    height,
+    fps,
+    to_uri(&foreground)?,
+    fps
+  );
+  if options.audio.unwrap_or(true) && !foreground_info.audio.is_empty() {
+    let audio = audio_codec_spec(container.audio_codec)?;
+    let parser = audio
+      .parser
+      .map(|p| format!(" ! {}", p))
+      .unwrap_or_default();
+    description.push_str(&format!(
+      " uridecodebin uri=\"{}\" caps=audio/x-raw expose-all-streams=false ! \
+       audioconvert ! audioresample ! {}{} ! queue ! mux.",
+      to_uri(&foreground)?,
+      audio.encoder,
+      parser
+    ));
+  }
+  let pipeline = launch(&description)?;
+  let sink = pipeline
+    .by_name("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+  sink.set_property("location", &output_path);
+
+  // Foreground filters go between the two named converters
+  let (Some(fg_in), Some(fg_out)) = (pipeline.by_name("fgin"), pipeline.by_name("fgout")) else {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Foreground converters not found",
+    ));
+  };
+  let filters = make_video_filters(&filters)?;
+  pipeline.add_many(&filters).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add video filters: {}", e),
+    )
+  })?;
+  let mut chain = vec![fg_in];
+  chain.extend(filters);
+  chain.push(fg_out);
+  gst::Element::link_many(&chain).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link video filters: {}", e),
+    )
+  })?;
+
+  if end_with_foreground {
+    end_background_with_foreground(&pipeline)?;
+  }
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
+
+/// Ends the background of the compositor when the foreground ends, for a
+/// still image repeated without a known duration
+fn end_background_with_foreground(pipeline: &gst::Pipeline) -> Result<()> {
+  let mixer = pipeline
+    .by_name("mix")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Compositor not found"))?;
+  let (Some(background), Some(foreground)) =
+    (mixer.static_pad("sink_0"), mixer.static_pad("sink_1"))
+  else {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Compositor pads not found",
+    ));
+  };
+  foreground.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+    if let Some(gst::PadProbeData::Event(event)) = &info.data {
+      if event.type_() == gst::EventType::Eos {
+        background.send_event(gst::event::Eos::new());
+        return gst::PadProbeReturn::Remove;
+      }
+    }
+    gst::PadProbeReturn::Ok
+  });
+  Ok(())
+}
@@ -0,0 +1,410 @@
+//! # Perceptual Hashing
+//!
+//! 64 bit fingerprints of video frames that stay (nearly) the same when a
+//! frame is re-encoded, scaled or slightly color shifted, for content
+//! matching and deduplication. Two hashes are computed per frame from its
+//! luma scaled to 32x32:
+//!
+//! - pHash: the signs of the 8x8 lowest frequencies of the DCT relative to
+//!   their median, robust to compression and gamma changes
+//! - dHash: whether brightness increases between horizontal neighbours on a
+//!   9x8 grid, cheap and sensitive to the structure of the frame
+//!
+//! Hashes are compared by Hamming distance, the number of differing bits:
+//! re-encodes of a frame are usually within 6 bits, unrelated frames around
+//! 32. Duplicate segments between two files are runs of consecutive frames
+//! whose pHashes match, with both files sampled at the same frame rate.
+
+use crate::frame_export::rate_filter;
+use crate::kit::to_uri;
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+
+/// Side of the luma image hashes are computed from
+const HASH_SIZE: usize = 32;
+
+/// Options for `computePerceptualHashes`
+#[napi(object)]
+pub struct PerceptualHashOptions {
+  /// Hash the input resampled to this frame rate (default: every frame)
+  pub fps: Option<f64>,
+  /// Hash every Nth frame only (default: 1)
+  pub interval: Option<u32>,
+}
+
+/// Perceptual hashes of one frame
+#[napi(object)]
+#[derive(Clone)]
+pub struct FrameHash {
+  /// Number of the frame, counting all frames after `fps` resampling
+  pub frame: u32,
+  /// Presentation timestamp in nanoseconds, or -1 if unknown
+  pub pts: i64,
+  /// DCT hash as 16 hexadecimal digits
+  pub phash: String,
+  /// Difference hash as 16 hexadecimal digits
+  pub dhash: String,
+}
+
+/// Options for `findDuplicateSegments`
+#[napi(object)]
+pub struct DuplicateSegmentOptions {
+  /// Frame rate both inputs are sampled at for matching (default: 5)
+  pub fps: Option<f64>,
+  /// Maximum pHash distance of matching frames, 0 to 64 (default: 10)
+  pub threshold: Option<u32>,
+  /// Minimum length of a segment in seconds (default: 2)
+  pub min_duration: Option<f64>,
+}
+
+/// Content found in both inputs of `findDuplicateSegments`
+#[napi(object)]
+pub struct DuplicateSegment {
+  /// Start in the first input in nanoseconds
+  pub first_start: i64,
+  /// End in the first input in nanoseconds
+  pub first_end: i64,
+  /// Start in the second input in nanoseconds
+  pub second_start: i64,
+  /// End in the second input in nanoseconds
+  pub second_end: i64,
+  /// Number of matching sampled frames
+  pub frames: u32,
+  /// Mean pHash distance of the matching frames
+  pub distance: f64,
+}
+
+/// A hashed frame, before conversion for JavaScript
+struct Hashed {
+  frame: u32,
+  pts: i64,
+  phash: u64,
+  dhash: u64,
+}
+
+/// DCT hash of a `HASH_SIZE` square luma image
+fn phash(luma: &[f64]) -> u64 {
+  // Only the 8 lowest frequencies are needed in each direction
+  let basis: Vec<f64> = (0..8)
+    .flat_map(|u| {
+      (0..HASH_SIZE)
+        .map(move |x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * HASH_SIZE) as f64).cos())
+    })
+    .collect();
+  let mut rows = [[0.0; 8]; HASH_SIZE];
+  for (y, row) in rows.iter_mut().enumerate() {
+    for (u, value) in row.iter_mut().enumerate() {
+      *value = (0..HASH_SIZE)
+        .map(|x| luma[y * HASH_SIZE + x] * basis[u * HASH_SIZE + x])
+        .sum();
+    }
+  }
+  let mut coefficients = [0.0; 64];
+  for v in 0..8 {
+    for u in 0..8 {
+      coefficients[v * 8 + u] = (0..HASH_SIZE)
+        .map(|y| rows[y][u] * basis[v * HASH_SIZE + y])
+        .sum();
+    }
+  }
+  let mut sorted = coefficients;
+  sorted.sort_by(f64::total_cmp);
+  let median = (sorted[31] + sorted[32]) / 2.0;
+  coefficients
+    .iter()
+    .enumerate()
+    .fold(0, |hash, (i, &c)| hash | ((c > median) as u64) << (63 - i))
+}
+
+/// Difference hash of a `HASH_SIZE` square luma image
+fn dhash(luma: &[f64]) -> u64 {
+  // Mean of the pixels of each cell of a 9x8 grid
+  let cell = |column: usize, row: usize| {
+    let (x0, x1) = (column * HASH_SIZE / 9, (column + 1) * HASH_SIZE / 9);
+    let (y0, y1) = (row * HASH_SIZE / 8, (row + 1) * HASH_SIZE / 8);
+    let sum: f64 = (y0..y1)
+      .flat_map(|y| (x0..x1).map(move |x| luma[y * HASH_SIZE + x]))
+      .sum();
+    sum / ((x1 - x0) * (y1 - y0)) as f64
+  };
+  let mut hash = 0;
+  for row in 0..8 {
+    let cells: Vec<f64> = (0..9).map(|column| cell(column, row)).collect();
+    for column in 0..8 {
+      hash = hash << 1 | (cells[column + 1] > cells[column]) as u64;
+    }
+  }
+  hash
+}
+
+/// Decodes `input` and hashes every `interval`th frame after resampling to `fps`
+fn hash_frames(input: &str, fps: Option<f64>, interval: u32) -> Result<Vec<Hashed>> {
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps=video/x-raw expose-all-streams=false ! videoconvert ! {}\
+     videoscale add-borders=false ! video/x-raw,format=GRAY8,width={},height={} ! \
+     appsink name=sink sync=false",
+    to_uri(input)?,
+    rate_filter(fps)?,
+    HASH_SIZE,
+    HASH_SIZE
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Hash sink not found"))?;
+
+  let interval = interval.max(1);
+  let frames = Arc::new(Mutex::new((0u32, Vec::new())));
+  let frames_clone = frames.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+        let video = gst_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+
+        let mut guard = frames_clone.lock().unwrap();
+        let (count, hashes) = &mut *guard;
+        let frame = *count;
+        *count += 1;
+        if frame.is_multiple_of(interval) {
+          let stride = video.stride()[0] as usize;
+          let luma: Vec<f64> = (0..HASH_SIZE)
+            .flat_map(|y| (0..HASH_SIZE).map(move |x| y * stride + x))
+            .map(|at| map[at] as f64)
+            .collect();
+          hashes.push(Hashed {
+            frame,
+            pts: buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1),
+            phash: phash(&luma),
+            dhash: dhash(&luma),
+          });
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let hashes = std::mem::take(&mut frames.lock().unwrap().1);
+  if hashes.is_empty() {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("No frames could be decoded from {}", input),
+    ));
+  }
+  Ok(hashes)
+}
+
+/// Computes the perceptual hashes (pHash and dHash) of the frames of a video
+///
+/// # Arguments
+/// * `input` - Path or URI of the video
+/// * `options` - Frame rate and interval of the hashed frames
+///
+/// # Returns
+/// * `Result<Vec<FrameHash>>` - The hashes of the hashed frames, in order
+///
+/// # Example
+/// ```javascript
+/// const hashes = computePerceptualHashes("upload.mp4", { fps: 1 });
+/// const known = hashes.some(h => perceptualHashDistance(h.phash, bannedHash) <= 6);
+/// ```
+#[napi]
+pub fn compute_perceptual_hashes(
+  input: String,
+  options: Option<PerceptualHashOptions>,
+) -> Result<Vec<FrameHash>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(PerceptualHashOptions {
+    fps: None,
+    interval: None,
+  });
+  let hashes = hash_frames(&input, options.fps, options.interval.unwrap_or(1))?;
+  Ok(
+    hashes
+      .into_iter()
+      .map(|h| FrameHash {
+        frame: h.frame,
+        pts: h.pts,
+        phash: format!("{:016x}", h.phash),
+        dhash: format!("{:016x}", h.dhash),
+      })
+      .collect(),
+  )
+}
+
+/// Parses a hash written as 16 hexadecimal digits
+fn parse_hash(hash: &str) -> Result<u64> {
+  let hash = hash.trim();
+  u64::from_str_radix(hash, 16)
+    .ok()
+    .filter(|_| hash.len() == 16)
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!(
+          "Invalid perceptual hash: {} (expected 16 hexadecimal digits)",
+          hash
+        ),
+      )
+    })
+}
+
+/// Hamming distance between two perceptual hashes: the number of differing
+/// bits, from 0 (identical) to 64
+///
+/// # Arguments
+/// * `a` - A pHash or dHash as returned by `computePerceptualHashes`
+/// * `b` - A hash of the same kind
+///
+/// # Returns
+/// * `Result<u32>` - The number of differing bits
+///
+/// # Example
+/// ```javascript
+/// const [a, b] = [computePerceptualHashes("a.mp4")[0], computePerceptualHashes("b.mp4")[0]];
+/// console.log(perceptualHashDistance(a.phash, b.phash) <= 10 ? "same shot" : "different");
+/// ```
+#[napi]
+pub fn perceptual_hash_distance(a: String, b: String) -> Result<u32> {
+  Ok((parse_hash(&a)? ^ parse_hash(&b)?).count_ones())
+}
+
+/// A run of matching frames, by index in the hashes of each input
+struct Run {
+  first: usize,
+  second: usize,
+  length: usize,
+  distance: u32,
+}
+
+/// Finds the segments of video present in both inputs, such as a shared
+/// intro, a reused clip or a re-upload
+///
+/// # Arguments
+/// * `first` - Path or URI of the first video
+/// * `second` - Path or URI of the second video
+/// * `options` - Sampling rate, match threshold and minimum segment length
+///
+/// # Returns
+/// * `Result<Vec<DuplicateSegment>>` - Non-overlapping segments, longest first
+///
+/// # Example
+/// ```javascript
+/// for (const s of findDuplicateSegments("episode1.mkv", "episode2.mkv", { minDuration: 10 })) {
+///   console.log(`${s.firstStart / 1e9}s in episode 1 = ${s.secondStart / 1e9}s in episode 2`);
+/// }
+/// ```
+#[napi]
+pub fn find_duplicate_segments(
+  first: String,
+  second: String,
+  options: Option<DuplicateSegmentOptions>,
+) -> Result<Vec<DuplicateSegment>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(DuplicateSegmentOptions {
+    fps: None,
+    threshold: None,
+    min_duration: None,
+  });
+  let fps = options.fps.unwrap_or(5.0);
+  let threshold = options.threshold.unwrap_or(10).min(64);
+  let min_frames = ((options.min_duration.unwrap_or(2.0).max(0.0) * fps).ceil() as usize).max(1);
+  let a = hash_frames(&first, Some(fps), 1)?;
+  let b = hash_frames(&second, Some(fps), 1)?;
+
+  // Runs of matching frames along the diagonals of the distance matrix,
+  // computed a row at a time as (length, summed distance) of the run ending
+  // at each cell; a run is complete at the first cell that does not match
+  let mut runs = Vec::new();
+  let mut previous = vec![(0usize, 0u32); b.len() + 1];
+  for i in 0..=a.len() {
+    let mut current = vec![(0usize, 0u32); b.len() + 1];
+    for j in 0..=b.len() {
+      let (length, sum) = if i > 0 && j > 0 {
+        previous[j - 1]
+      } else {
+        (0, 0)
+      };
+      let distance = (i < a.len() && j < b.len())
+        .then(|| (a[i].phash ^ b[j].phash).count_ones())
+        .filter(|&d| d <= threshold);
+      match distance {
+        Some(d) => current[j] = (length + 1, sum + d),
+        None if length >= min_frames => runs.push(Run {
+          first: i - length,
+          second: j - length,
+          length,
+          distance: sum,
+        }),
+        None => {}
+      }
+    }
+    previous = current;
+  }
+
+  // Static content matches on many diagonals; keep the longest, closest runs
+  // that do not overlap in either input
+  runs.sort_by(|x, y| {
+    y.length
+      .cmp(&x.length)
+      .then((x.distance as u64 * y.length as u64).cmp(&(y.distance as u64 * x.length as u64)))
+  });
+  let mut kept: Vec<Run> = Vec::new();
+  for run in runs {
+    let overlaps = kept.iter().any(|k| {
+      (run.first < k.first + k.length && k.first < run.first + run.length)
+        || (run.second < k.second + k.length && k.second < run.second + run.length)
+    });
+    if !overlaps {
+      kept.push(run);
+    }
+  }
+
+  let frame_duration = (1e9 / fps).round() as i64;
+  Ok(
+    kept
+      .into_iter()
+      .map(|run| DuplicateSegment {
+        first_start: a[run.first].pts,
+        first_end: a[run.first + run.length - 1].pts + frame_duration,
+        second_start: b[run.second].pts,
+        second_end: b[run.second + run.length - 1].pts + frame_duration,
+        frames: run.length as u32,
+        distance: run.distance as f64 / run.length as f64,
+      })
+      .collect(),
+  )
+}
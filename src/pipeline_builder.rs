@@ -0,0 +1,451 @@
+//! # Pipeline Builder
+//!
+//! Composes linear pipelines step by step, for users who would rather not
+//! write gst-launch syntax: a source, then filters such as decoding,
+//! conversion and scaling, then a sink. Every method returns the builder, so
+//! the steps chain, and `build` returns the launch string for `setPipeline`
+//! once it has checked that the pipeline starts with a source, ends with a
+//! sink and parses with the installed plugins.
+//!
+//! Values given to the builder are escaped like template variables, and
+//! caps and formats are checked as they are added, so mistakes surface at
+//! the step that made them.
+
+use crate::frame_clock::rational_rate;
+use crate::missing_plugins::parse_launch;
+use crate::pipeline_template::escape;
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::{Either3, This};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Options for `PipelineBuilder.testSource`
+#[napi(object)]
+pub struct TestSourceOptions {
+  /// Test pattern, e.g. "smpte", "ball" or "black" (default: "smpte")
+  pub pattern: Option<String>,
+  /// Stop after this many frames (default: unlimited)
+  pub num_buffers: Option<u32>,
+  /// Produce frames in real time (default: false)
+  pub live: Option<bool>,
+}
+
+/// Options for `PipelineBuilder.appsink`
+#[napi(object)]
+pub struct BuilderSinkOptions {
+  /// Frames queued in the sink before it blocks or drops (default: unlimited)
+  pub max_buffers: Option<u32>,
+  /// Drop the oldest frame instead of blocking when the queue is full
+  /// (default: false)
+  pub drop: Option<bool>,
+  /// Deliver frames at their playback time instead of as fast as possible
+  /// (default: true)
+  pub sync: Option<bool>,
+}
+
+#[derive(PartialEq)]
+enum Step {
+  Source,
+  Filter,
+  Sink,
+}
+
+/// Composes a linear pipeline and generates its launch string
+///
+/// # Example
+/// ```javascript
+/// const description = new PipelineBuilder()
+///   .source("clip.mp4")
+///   .decode()
+///   .convert("RGBA")
+///   .scale(640, 360)
+///   .appsink("out", { maxBuffers: 2, drop: true })
+///   .build();
+/// kit.setPipeline(description);
+/// ```
+#[napi]
+#[derive(Default)]
+pub struct PipelineBuilder {
+  elements: Vec<String>,
+  has_sink: bool,
+}
+
+impl PipelineBuilder {
+  /// Appends an element description, checking it may follow the steps so far
+  fn push(&mut self, method: &str, step: Step, element: String) -> Result<()> {
+    if self.has_sink {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("{}() cannot follow the sink of the pipeline", method),
+      ));
+    }
+    if (step == Step::Source) != self.elements.is_empty() {
+      let message = if step == Step::Source {
+        format!("{}() must be the first step of the pipeline", method)
+      } else {
+        format!("{}() needs a source before it", method)
+      };
+      return Err(Error::new(Status::InvalidArg, message));
+    }
+    self.has_sink = step == Step::Sink;
+    self.elements.push(element);
+    Ok(())
+  }
+}
+
+/// Escapes a string value
+fn quote(value: &str) -> String {
+  escape(&Either3::A(value.to_string()))
+}
+
+/// Checks that `name` can be an element factory name
+fn check_factory(factory: &str) -> Result<()> {
+  let valid = !factory.is_empty()
+    && factory
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+  if valid {
+    Ok(())
+  } else {
+    Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid element name: {}", factory),
+    ))
+  }
+}
+
+/// Parses a caps string, reporting invalid caps
+fn parse_caps(caps: &str) -> Result<gst::Caps> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+  gst::Caps::from_str(caps)
+    .map_err(|_| Error::new(Status::InvalidArg, format!("Invalid caps: {}", caps)))
+}
+
+#[napi]
+impl PipelineBuilder {
+  /// Creates an empty builder
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    PipelineBuilder::default()
+  }
+
+  /// Starts the pipeline with a file or URI
+  ///
+  /// # Arguments
+  /// * `location` - A file path, or a URI such as "https://..." or "rtsp://..."
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.source("/media/clip.mp4");
+  /// ```
+  #[napi]
+  pub fn source<'env>(&mut self, this: This<'env>, location: String) -> Result<This<'env>> {
+    let element = if location.contains("://") {
+      format!("urisourcebin uri={}", quote(&location))
+    } else {
+      format!("filesrc location={}", quote(&location))
+    };
+    self.push("source", Step::Source, element)?;
+    Ok(this)
+  }
+
+  /// Starts the pipeline with a generated test pattern
+  ///
+  /// # Arguments
+  /// * `options` - Pattern, frame count and live setting
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.testSource({ pattern: "ball", numBuffers: 100 });
+  /// ```
+  #[napi]
+  pub fn test_source<'env>(
+    &mut self,
+    this: This<'env>,
+    options: Option<TestSourceOptions>,
+  ) -> Result<This<'env>> {
+    let options = options.unwrap_or(TestSourceOptions {
+      pattern: None,
+      num_buffers: None,
+      live: None,
+    });
+    let mut element = "videotestsrc".to_string();
+    if let Some(pattern) = &options.pattern {
+      element.push_str(&format!(" pattern={}", quote(pattern)));
+    }
+    if let Some(num_buffers) = options.num_buffers {
+      element.push_str(&format!(" num-buffers={}", num_buffers));
+    }
+    if let Some(live) = options.live {
+      element.push_str(&format!(" is-live={}", live));
+    }
+    self.push("testSource", Step::Source, element)?;
+    Ok(this)
+  }
+
+  /// Starts the pipeline with a camera
+  ///
+  /// # Arguments
+  /// * `device` - A V4L2 device path such as "/dev/video0"; the default
+  ///   camera of the system if omitted
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.camera("/dev/video2");
+  /// ```
+  #[napi]
+  pub fn camera<'env>(&mut self, this: This<'env>, device: Option<String>) -> Result<This<'env>> {
+    let element = match device {
+      Some(device) => format!("v4l2src device={}", quote(&device)),
+      None => "autovideosrc".to_string(),
+    };
+    self.push("camera", Step::Source, element)?;
+    Ok(this)
+  }
+
+  /// Starts the pipeline with an AppSrc fed by `pushSample`
+  ///
+  /// # Arguments
+  /// * `name` - The name of the AppSrc element
+  /// * `caps` - Caps of the pushed data, e.g. "video/x-raw,format=RGBA,width=640,height=360,framerate=30/1"
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.appsrc("src", "video/x-raw,format=RGBA,width=640,height=360,framerate=30/1");
+  /// ```
+  #[napi]
+  pub fn appsrc<'env>(
+    &mut self,
+    this: This<'env>,
+    name: String,
+    caps: Option<String>,
+  ) -> Result<This<'env>> {
+    let mut element = format!("appsrc name={} format=time", quote(&name));
+    if let Some(caps) = &caps {
+      parse_caps(caps)?;
+      element.push_str(&format!(" caps={}", quote(caps)));
+    }
+    self.push("appsrc", Step::Source, element)?;
+    Ok(this)
+  }
+
+  /// Decodes the source into raw video
+  #[napi]
+  pub fn decode<'env>(&mut self, this: This<'env>) -> Result<This<'env>> {
+    self.push("decode", Step::Filter, "decodebin".to_string())?;
+    Ok(this)
+  }
+
+  /// Converts raw video to another pixel format
+  ///
+  /// # Arguments
+  /// * `format` - A raw video format such as "RGBA", "BGR" or "NV12"; any
+  ///   format the next element accepts if omitted
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.convert("RGBA");
+  /// ```
+  #[napi]
+  pub fn convert<'env>(&mut self, this: This<'env>, format: Option<String>) -> Result<This<'env>> {
+    let element = match format {
+      Some(format) => {
+        if gst_video::VideoFormat::from_string(&format) == gst_video::VideoFormat::Unknown {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Unknown video format: {}", format),
+          ));
+        }
+        format!("videoconvert ! video/x-raw,format={}", format)
+      }
+      None => "videoconvert".to_string(),
+    };
+    self.push("convert", Step::Filter, element)?;
+    Ok(this)
+  }
+
+  /// Scales raw video to a size
+  ///
+  /// # Arguments
+  /// * `width` - Width in pixels
+  /// * `height` - Height in pixels
+  #[napi]
+  pub fn scale<'env>(&mut self, this: This<'env>, width: u32, height: u32) -> Result<This<'env>> {
+    if width == 0 || height == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid size: {}x{}", width, height),
+      ));
+    }
+    self.push(
+      "scale",
+      Step::Filter,
+      format!("videoscale ! video/x-raw,width={},height={}", width, height),
+    )?;
+    Ok(this)
+  }
+
+  /// Changes the frame rate of raw video by dropping or duplicating frames
+  ///
+  /// # Arguments
+  /// * `fps` - Frames per second; 29.97 and 59.94 are taken as 30000/1001 and 60000/1001
+  #[napi]
+  pub fn rate<'env>(&mut self, this: This<'env>, fps: f64) -> Result<This<'env>> {
+    let (num, den) = rational_rate(fps)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid fps: {}", fps)))?;
+    self.push(
+      "rate",
+      Step::Filter,
+      format!("videorate ! video/x-raw,framerate={}/{}", num, den),
+    )?;
+    Ok(this)
+  }
+
+  /// Restricts the stream to caps
+  ///
+  /// # Arguments
+  /// * `caps` - A caps string, e.g. "video/x-raw,width=1280,height=720"
+  #[napi]
+  pub fn caps<'env>(&mut self, this: This<'env>, caps: String) -> Result<This<'env>> {
+    parse_caps(&caps)?;
+    self.push(
+      "caps",
+      Step::Filter,
+      format!("capsfilter caps={}", quote(&caps)),
+    )?;
+    Ok(this)
+  }
+
+  /// Adds a queue, decoupling the steps before and after it into threads
+  ///
+  /// # Arguments
+  /// * `max_buffers` - Frames the queue holds before it blocks (default: 200)
+  #[napi]
+  pub fn queue<'env>(&mut self, this: This<'env>, max_buffers: Option<u32>) -> Result<This<'env>> {
+    let element = match max_buffers {
+      Some(max_buffers) => format!("queue max-size-buffers={}", max_buffers),
+      None => "queue".to_string(),
+    };
+    self.push("queue", Step::Filter, element)?;
+    Ok(this)
+  }
+
+  /// Adds any element, for steps the builder has no method for
+  ///
+  /// # Arguments
+  /// * `factory` - The element factory name, e.g. "videoflip"
+  /// * `properties` - Properties of the element
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.element("videoflip", { method: "horizontal-flip" });
+  /// ```
+  #[napi]
+  pub fn element<'env>(
+    &mut self,
+    this: This<'env>,
+    factory: String,
+    properties: Option<HashMap<String, Either3<String, f64, bool>>>,
+  ) -> Result<This<'env>> {
+    check_factory(&factory)?;
+    let mut properties: Vec<_> = properties.unwrap_or_default().into_iter().collect();
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut element = factory;
+    for (name, value) in &properties {
+      check_factory(name)?;
+      element.push_str(&format!(" {}={}", name, escape(value)));
+    }
+    let step = if self.elements.is_empty() {
+      Step::Source
+    } else {
+      Step::Filter
+    };
+    self.push("element", step, element)?;
+    Ok(this)
+  }
+
+  /// Ends the pipeline with an AppSink for `pullFrame` and `pullSample`
+  ///
+  /// # Arguments
+  /// * `name` - The name of the AppSink element
+  /// * `options` - Queue size, dropping and sync settings
+  ///
+  /// # Example
+  /// ```javascript
+  /// builder.appsink("out", { maxBuffers: 1, drop: true });
+  /// ```
+  #[napi]
+  pub fn appsink<'env>(
+    &mut self,
+    this: This<'env>,
+    name: String,
+    options: Option<BuilderSinkOptions>,
+  ) -> Result<This<'env>> {
+    let options = options.unwrap_or(BuilderSinkOptions {
+      max_buffers: None,
+      drop: None,
+      sync: None,
+    });
+    let mut element = format!("appsink name={}", quote(&name));
+    if let Some(max_buffers) = options.max_buffers {
+      element.push_str(&format!(" max-buffers={}", max_buffers));
+    }
+    if let Some(drop) = options.drop {
+      element.push_str(&format!(" drop={}", drop));
+    }
+    if let Some(sync) = options.sync {
+      element.push_str(&format!(" sync={}", sync));
+    }
+    self.push("appsink", Step::Sink, element)?;
+    Ok(this)
+  }
+
+  /// Ends the pipeline with a display window or another sink element
+  ///
+  /// # Arguments
+  /// * `factory` - The sink element factory name (default: "autovideosink")
+  #[napi]
+  pub fn sink<'env>(&mut self, this: This<'env>, factory: Option<String>) -> Result<This<'env>> {
+    let factory = factory.unwrap_or_else(|| "autovideosink".to_string());
+    check_factory(&factory)?;
+    self.push("sink", Step::Sink, factory)?;
+    Ok(this)
+  }
+
+  /// Checks the pipeline and returns its launch string
+  ///
+  /// # Returns
+  /// * `Result<String>` - The pipeline description, for `setPipeline`
+  ///
+  /// # Example
+  /// ```javascript
+  /// kit.setPipeline(new PipelineBuilder().testSource().convert("RGBA").appsink("out").build());
+  /// ```
+  #[napi]
+  pub fn build(&self) -> Result<String> {
+    if !self.has_sink {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The pipeline needs a source and a sink".to_string(),
+      ));
+    }
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let description = self.elements.join(" ! ");
+    parse_launch(&description)?;
+    Ok(description)
+  }
+}
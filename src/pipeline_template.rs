@@ -0,0 +1,112 @@
+//! # Pipeline Templates
+//!
+//! Launch strings with `{name}` placeholders, filled in from an object of
+//! variables. Every placeholder must have a value, and values are escaped so
+//! they stay a single token of the launch string: a value holding spaces,
+//! quotes or `!` is double-quoted, so a device name or path can never add
+//! elements or properties to the pipeline. `{{` and `}}` stand for literal
+//! braces; braces that do not enclose a name, as in the caps list
+//! `format={ RGBA, BGRA }`, are left as they are.
+
+use napi::bindgen_prelude::Either3;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+
+/// Whether `name` can name a placeholder
+fn is_placeholder(name: &str) -> bool {
+  let mut chars = name.chars();
+  matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Formats a value as one token of a launch string
+pub(crate) fn escape(value: &Either3<String, f64, bool>) -> String {
+  let text = match value {
+    Either3::A(text) => text.clone(),
+    Either3::B(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+      format!("{}", *number as i64)
+    }
+    Either3::B(number) => number.to_string(),
+    Either3::C(flag) => flag.to_string(),
+  };
+  let plain = !text.is_empty()
+    && text
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "_-+.,:/=()".contains(c));
+  if plain {
+    return text;
+  }
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Fills in the placeholders of a template
+pub(crate) fn render(
+  template: &str,
+  vars: &HashMap<String, Either3<String, f64, bool>>,
+) -> Result<String> {
+  let mut output = String::with_capacity(template.len());
+  let mut missing: Vec<&str> = Vec::new();
+  let mut rest = template;
+  while let Some(index) = rest.find(['{', '}']) {
+    output.push_str(&rest[..index]);
+    rest = &rest[index..];
+    if rest.starts_with("{{") || rest.starts_with("}}") {
+      output.push_str(&rest[..1]);
+      rest = &rest[2..];
+      continue;
+    }
+    let placeholder = rest
+      .strip_prefix('{')
+      .and_then(|after| after.find('}').map(|end| &after[..end]))
+      .filter(|name| is_placeholder(name));
+    match placeholder {
+      Some(name) => {
+        match vars.get(name) {
+          Some(value) => output.push_str(&escape(value)),
+          None if !missing.contains(&name) => missing.push(name),
+          None => {}
+        }
+        rest = &rest[name.len() + 2..];
+      }
+      None => {
+        output.push_str(&rest[..1]);
+        rest = &rest[1..];
+      }
+    }
+  }
+  output.push_str(rest);
+
+  if !missing.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Missing template variables: {}", missing.join(", ")),
+    ));
+  }
+  Ok(output)
+}
+
+/// Fills in the `{name}` placeholders of a launch string template
+///
+/// # Arguments
+/// * `template` - A pipeline description with `{name}` placeholders
+/// * `vars` - The value of every placeholder
+///
+/// # Returns
+/// * `Result<String>` - The pipeline description, with the values escaped
+///
+/// # Example
+/// ```javascript
+/// const description = renderPipelineTemplate(
+///   "filesrc location={path} ! decodebin ! videoconvert ! appsink name=sink",
+///   { path: "/media/My Videos/clip.mp4" },
+/// );
+/// // filesrc location="/media/My Videos/clip.mp4" ! decodebin ! ...
+/// ```
+#[napi]
+pub fn render_pipeline_template(
+  template: String,
+  vars: HashMap<String, Either3<String, f64, bool>>,
+) -> Result<String> {
+  render(&template, &vars)
+}
@@ -0,0 +1,345 @@
+//! # Pixel Layout
+//!
+//! Helpers for raw video memory layouts. Sinks often negotiate frames whose
+//! rows are padded to an alignment (a 1366 pixel wide NV12 frame may have a
+//! stride of 1408 bytes); consumers such as ML runtimes usually want tightly
+//! packed planes instead. This module removes row padding, converts
+//! between the two common 4:2:0 layouts, NV12 (interleaved chroma) and I420
+//! (separate U and V planes), and lays out RGB frames the way the web's
+//! `ImageData`, OpenCV's `cv.Mat` and numpy arrays expect them.
+
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::{Buffer, Uint8ClampedArray};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// A frame laid out like the web's `ImageData`
+#[napi(object)]
+pub struct ImageDataFrame {
+  /// Width in pixels
+  pub width: u32,
+  /// Height in pixels
+  pub height: u32,
+  /// Tightly packed RGBA pixels, `width * height * 4` bytes
+  pub data: Uint8ClampedArray,
+  /// Whether the color channels are multiplied by alpha
+  pub premultiplied: bool,
+  /// Timestamp of the frame in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+}
+
+/// Options for `pullSampleAsImageData`
+#[napi(object)]
+pub struct ImageDataOptions {
+  /// Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  pub timeout_ms: Option<u32>,
+  /// Multiply the color channels by alpha, as `ImageBitmap` and WebGL with
+  /// `premultipliedAlpha` expect (default: false, as `ImageData` expects)
+  pub premultiplied: Option<bool>,
+}
+
+/// OpenCV's type constant for 8-bit, 3 channel matrices
+const CV_8UC3: u32 = 16;
+
+/// A frame laid out like an OpenCV `cv.Mat` or a numpy `uint8` array of
+/// shape `(rows, cols, 3)`
+#[napi(object)]
+pub struct MatFrame {
+  /// Tightly packed pixels, `rows * cols * 3` bytes
+  pub data: Buffer,
+  /// Height in pixels
+  pub rows: u32,
+  /// Width in pixels
+  pub cols: u32,
+  /// Channels per pixel (3)
+  pub channels: u32,
+  /// Array shape, `[rows, cols, channels]`
+  pub shape: Vec<u32>,
+  /// Byte strides of the shape's dimensions, `[cols * 3, 3, 1]`
+  pub strides: Vec<u32>,
+  /// Element type, "uint8"
+  pub dtype: String,
+  /// OpenCV matrix type, `CV_8UC3` (16), for `new cv.Mat(rows, cols, type)`
+  pub mat_type: u32,
+  /// Channel order, "BGR" or "RGB"
+  pub channel_order: String,
+  /// Timestamp of the frame in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+}
+
+/// Options for `pullSampleAsMat`
+#[napi(object)]
+pub struct MatOptions {
+  /// Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  pub timeout_ms: Option<u32>,
+  /// Return RGB instead of OpenCV's BGR channel order (default: false)
+  pub rgb: Option<bool>,
+}
+
+/// A sub-rectangle of a frame, in pixels
+#[napi(object)]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameCrop {
+  /// Left edge
+  pub x: u32,
+  /// Top edge
+  pub y: u32,
+  /// Width
+  pub width: u32,
+  /// Height
+  pub height: u32,
+}
+
+/// Fits `crop` to a frame: the origin moves up and left onto the chroma grid
+/// (even coordinates for 4:2:0), so every plane is cut at the same pixels,
+/// and the size is clamped to the frame. Returns x, y, width and height.
+pub(crate) fn crop_window(
+  info: &gst_video::VideoInfo,
+  crop: &FrameCrop,
+) -> Result<(u32, u32, u32, u32)> {
+  if crop.width == 0 || crop.height == 0 || crop.x >= info.width() || crop.y >= info.height() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Crop {}x{} at {},{} is outside the {}x{} frame",
+        crop.width,
+        crop.height,
+        crop.x,
+        crop.y,
+        info.width(),
+        info.height()
+      ),
+    ));
+  }
+  let format_info = info.format_info();
+  let x_align = 1 << format_info.w_sub().iter().copied().max().unwrap_or(0);
+  let y_align = 1 << format_info.h_sub().iter().copied().max().unwrap_or(0);
+  let (x, y) = (crop.x - crop.x % x_align, crop.y - crop.y % y_align);
+  let right = crop.x.saturating_add(crop.width).min(info.width());
+  let bottom = crop.y.saturating_add(crop.height).min(info.height());
+  Ok((x, y, right - x, bottom - y))
+}
+
+/// Copies `height` rows of `row_bytes` bytes out of rows `stride` bytes apart
+pub(crate) fn pack_rows(data: &[u8], stride: usize, row_bytes: usize, height: usize) -> Vec<u8> {
+  let row_bytes = row_bytes.min(stride);
+  let mut packed = Vec::with_capacity(row_bytes * height);
+  for row in data.chunks(stride).take(height) {
+    packed.extend_from_slice(&row[..row_bytes.min(row.len())]);
+  }
+  packed
+}
+
+/// Size of the luma plane and of one chroma plane of a 4:2:0 frame
+fn plane_sizes(width: u32, height: u32) -> Result<(usize, usize)> {
+  if width == 0 || height == 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Width and height must be positive".to_string(),
+    ));
+  }
+  let (width, height) = (width as usize, height as usize);
+  Ok((width * height, width.div_ceil(2) * height.div_ceil(2)))
+}
+
+fn check_size(data: &[u8], expected: usize, format: &str, width: u32, height: u32) -> Result<()> {
+  if data.len() != expected {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "A packed {}x{} {} frame has {} bytes, got {}",
+        width,
+        height,
+        format,
+        expected,
+        data.len()
+      ),
+    ));
+  }
+  Ok(())
+}
+
+/// Converts a tightly packed NV12 frame to I420
+///
+/// # Arguments
+/// * `data` - The NV12 frame: the luma plane followed by interleaved UV samples
+/// * `width` - Width of the frame in pixels
+/// * `height` - Height of the frame in pixels
+///
+/// # Returns
+/// * `Result<Buffer>` - The I420 frame: the luma, U and V planes in order
+///
+/// # Example
+/// ```javascript
+/// const frame = kit.pullFrame("sink", { packed: true });
+/// const i420 = nv12ToI420(frame.data, frame.width, frame.height);
+/// ```
+#[napi(js_name = "nv12ToI420")]
+pub fn nv12_to_i420(data: Buffer, width: u32, height: u32) -> Result<Buffer> {
+  let (luma, chroma) = plane_sizes(width, height)?;
+  check_size(&data, luma + 2 * chroma, "NV12", width, height)?;
+
+  let mut out = vec![0u8; luma + 2 * chroma];
+  out[..luma].copy_from_slice(&data[..luma]);
+  let (u, v) = out[luma..].split_at_mut(chroma);
+  for (i, uv) in data[luma..].chunks_exact(2).enumerate() {
+    u[i] = uv[0];
+    v[i] = uv[1];
+  }
+  Ok(Buffer::from(out))
+}
+
+/// Converts a tightly packed I420 frame to NV12
+///
+/// # Arguments
+/// * `data` - The I420 frame: the luma, U and V planes in order
+/// * `width` - Width of the frame in pixels
+/// * `height` - Height of the frame in pixels
+///
+/// # Returns
+/// * `Result<Buffer>` - The NV12 frame: the luma plane followed by interleaved UV samples
+///
+/// # Example
+/// ```javascript
+/// const nv12 = i420ToNv12(i420, 1280, 720);
+/// ```
+#[napi(js_name = "i420ToNv12")]
+pub fn i420_to_nv12(data: Buffer, width: u32, height: u32) -> Result<Buffer> {
+  let (luma, chroma) = plane_sizes(width, height)?;
+  check_size(&data, luma + 2 * chroma, "I420", width, height)?;
+
+  let mut out = Vec::with_capacity(luma + 2 * chroma);
+  out.extend_from_slice(&data[..luma]);
+  let (u, v) = data[luma..].split_at(chroma);
+  for (&u, &v) in u.iter().zip(v) {
+    out.push(u);
+    out.push(v);
+  }
+  Ok(Buffer::from(out))
+}
+
+/// Byte positions of red, green, blue and alpha within a pixel of an 8-bit
+/// RGB format, and the pixel size
+fn rgba_layout(format: gst_video::VideoFormat) -> Option<([usize; 3], Option<usize>, usize)> {
+  use gst_video::VideoFormat::*;
+  Some(match format {
+    Rgba => ([0, 1, 2], Some(3), 4),
+    Bgra => ([2, 1, 0], Some(3), 4),
+    Argb => ([1, 2, 3], Some(0), 4),
+    Abgr => ([3, 2, 1], Some(0), 4),
+    Rgbx => ([0, 1, 2], None, 4),
+    Bgrx => ([2, 1, 0], None, 4),
+    Xrgb => ([1, 2, 3], None, 4),
+    Xbgr => ([3, 2, 1], None, 4),
+    Rgb => ([0, 1, 2], None, 3),
+    Bgr => ([2, 1, 0], None, 3),
+    Gray8 => ([0, 0, 0], None, 1),
+    _ => return None,
+  })
+}
+
+/// Repacks an 8-bit RGB or grayscale sample pixel by pixel: `convert` gets the
+/// red, green, blue and alpha values and appends `pixel_size` bytes. Returns
+/// the video info, the pixels and the timestamp.
+fn repack_rgb(
+  sample: &gst::Sample,
+  target: &str,
+  pixel_size: usize,
+  convert: impl Fn([u8; 4], &mut Vec<u8>),
+) -> Result<(gst_video::VideoInfo, Vec<u8>, i64)> {
+  let caps = sample
+    .caps()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no caps"))?;
+  let info = gst_video::VideoInfo::from_caps(caps).map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Sample is not raw video: {}", caps),
+    )
+  })?;
+  let (color, alpha, source_size) = rgba_layout(info.format()).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!(
+        "Cannot lay out {} frames as {}; force RGBA with startFrameEmission",
+        info.format().to_str(),
+        target
+      ),
+    )
+  })?;
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+  let map = buffer
+    .map_readable()
+    .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+  let (offset, stride) = match buffer.meta::<gst_video::VideoMeta>() {
+    Some(meta) => (meta.offset()[0], meta.stride()[0] as usize),
+    None => (info.offset()[0], info.stride()[0] as usize),
+  };
+
+  let (width, height) = (info.width() as usize, info.height() as usize);
+  let mut pixels = Vec::with_capacity(width * height * pixel_size);
+  for y in 0..height {
+    let start = offset + y * stride;
+    let row = map.get(start..start + width * source_size).ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "Buffer is smaller than its caps announce".to_string(),
+      )
+    })?;
+    for pixel in row.chunks_exact(source_size) {
+      let a = alpha.map_or(255, |a| pixel[a]);
+      convert(
+        [pixel[color[0]], pixel[color[1]], pixel[color[2]], a],
+        &mut pixels,
+      );
+    }
+  }
+  let timestamp = buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1);
+  Ok((info, pixels, timestamp))
+}
+
+/// Converts an 8-bit RGB or grayscale sample to tightly packed RGBA
+pub(crate) fn image_data(sample: &gst::Sample, premultiplied: bool) -> Result<ImageDataFrame> {
+  let (info, pixels, timestamp) = repack_rgb(sample, "ImageData", 4, |[r, g, b, a], out| {
+    for c in [r, g, b] {
+      out.push(if premultiplied {
+        ((c as u32 * a as u32 + 127) / 255) as u8
+      } else {
+        c
+      });
+    }
+    out.push(a);
+  })?;
+
+  Ok(ImageDataFrame {
+    width: info.width(),
+    height: info.height(),
+    data: Uint8ClampedArray::new(pixels),
+    premultiplied,
+    timestamp,
+  })
+}
+
+/// Converts an 8-bit RGB or grayscale sample to a tightly packed 3 channel
+/// matrix, BGR unless `rgb`
+pub(crate) fn mat_frame(sample: &gst::Sample, rgb: bool) -> Result<MatFrame> {
+  let (info, pixels, timestamp) = repack_rgb(sample, "a matrix", 3, |[r, g, b, _], out| {
+    out.extend_from_slice(&if rgb { [r, g, b] } else { [b, g, r] });
+  })?;
+
+  let (rows, cols) = (info.height(), info.width());
+  Ok(MatFrame {
+    data: Buffer::from(pixels),
+    rows,
+    cols,
+    channels: 3,
+    shape: vec![rows, cols, 3],
+    strides: vec![cols * 3, 3, 1],
+    dtype: "uint8".to_string(),
+    mat_type: CV_8UC3,
+    channel_order: if rgb { "RGB" } else { "BGR" }.to_string(),
+    timestamp,
+  })
+}
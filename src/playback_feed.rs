@@ -0,0 +1,195 @@
+//! # Playback Feeds
+//!
+//! Decodes a media file and pushes its frames, timed, into an AppSrc of a
+//! `GstKit` pipeline, so files can be played or composited by pipelines
+//! built around an `appsrc` without a decoder of their own. Frames are
+//! converted to the caps set on the AppSrc, when there are any, and
+//! otherwise pushed in their decoded format with the caps set from the
+//! first frame. In realtime mode they are pushed at the pace of their
+//! timestamps, as a live source would deliver them; otherwise as fast as the
+//! AppSrc accepts them.
+
+use crate::kit::{to_uri, GstKit};
+use crate::transcode::{launch, pop_bus_error};
+use gst::prelude::*;
+use gst_app::AppSink;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Options for `playTranscodedInto`
+#[napi(object)]
+pub struct FeedOptions {
+  /// Push frames at the pace of their timestamps (default: true). Otherwise
+  /// they are pushed as fast as the pipeline consumes them, the AppSrc
+  /// being set to block when its queue is full.
+  pub realtime: Option<bool>,
+  /// Stream of the file to push, "video" or "audio"; defaults to the media
+  /// of the AppSrc caps, or "video"
+  pub media: Option<String>,
+  /// Send end of stream to the AppSrc when the file ends (default: true)
+  pub end_of_stream: Option<bool>,
+}
+
+#[derive(Default)]
+struct FeedState {
+  frames: AtomicU32,
+  finished: AtomicBool,
+}
+
+/// A file being pushed into an AppSrc, started by `playTranscodedInto`
+#[napi]
+pub struct TranscodedFeed {
+  pipeline: gst::Pipeline,
+  state: Arc<FeedState>,
+}
+
+#[napi]
+impl TranscodedFeed {
+  /// Number of frames (or audio buffers) pushed so far
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.state.frames.load(Ordering::SeqCst)
+  }
+
+  /// Whether the whole file has been pushed
+  #[napi(getter)]
+  pub fn finished(&self) -> bool {
+    self.state.finished.load(Ordering::SeqCst)
+  }
+
+  /// Stops pushing frames. End of stream is not sent to the AppSrc.
+  ///
+  /// # Returns
+  /// * `Result<()>` - An error if decoding the file failed
+  ///
+  /// # Example
+  /// ```javascript
+  /// feed.stop();
+  /// ```
+  #[napi]
+  pub fn stop(&self) -> Result<()> {
+    let error = pop_bus_error(&self.pipeline);
+    let _ = self.pipeline.set_state(gst::State::Null);
+    match error {
+      Some(error) => Err(error),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Drop for TranscodedFeed {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
+
+/// Decodes a media file and pushes its frames into an AppSrc of a pipeline
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `src_name` - The name of the AppSrc element
+/// * `input_path` - Path or URI of the media file
+/// * `options` - Pacing, stream and end of stream handling
+///
+/// # Returns
+/// * `Result<TranscodedFeed>` - The running feed
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("appsrc name=src format=time ! videoconvert ! autovideosink");
+/// const feed = playTranscodedInto(kit, "src", "clip.mkv", { realtime: true });
+/// kit.play();
+/// ```
+#[napi]
+pub fn play_transcoded_into(
+  kit: &GstKit,
+  src_name: String,
+  input_path: String,
+  options: Option<FeedOptions>,
+) -> Result<TranscodedFeed> {
+  let options = options.unwrap_or(FeedOptions {
+    realtime: None,
+    media: None,
+    end_of_stream: None,
+  });
+  let appsrc = kit.app_src(&src_name)?;
+  let target = appsrc.caps();
+  let media = match options.media {
+    Some(media) => media,
+    None => target
+      .as_ref()
+      .and_then(|caps| caps.structure(0))
+      .filter(|s| s.name().starts_with("audio/"))
+      .map_or("video", |_| "audio")
+      .to_string(),
+  };
+  let converters = match media.as_str() {
+    "video" => "videoconvert ! videoscale ! videorate",
+    "audio" => "audioconvert ! audioresample",
+    media => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported media: {} (use \"video\" or \"audio\")", media),
+      ))
+    }
+  };
+  let realtime = options.realtime.unwrap_or(true);
+
+  let pipeline = launch(&format!(
+    "uridecodebin uri=\"{}\" caps={}/x-raw expose-all-streams=false ! {} ! appsink name=sink sync={}",
+    to_uri(&input_path)?,
+    media,
+    converters,
+    realtime
+  ))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Feed sink not found"))?;
+  // Convert to what the AppSrc announces, when it is raw media of this kind
+  if let Some(caps) = target.filter(|caps| {
+    caps
+      .structure(0)
+      .is_some_and(|s| s.name() == format!("{}/x-raw", media).as_str())
+  }) {
+    appsink.set_caps(Some(&caps));
+  }
+  appsrc.set_format(gst::Format::Time);
+  if !realtime {
+    appsrc.set_block(true);
+  }
+
+  let state = Arc::new(FeedState::default());
+  let samples = state.clone();
+  let finished = state.clone();
+  let eos_src = appsrc.clone();
+  let end_of_stream = options.end_of_stream.unwrap_or(true);
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        appsrc.push_sample(&sample)?;
+        samples.frames.fetch_add(1, Ordering::SeqCst);
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .eos(move |_| {
+        finished.finished.store(true, Ordering::SeqCst);
+        if end_of_stream {
+          let _ = eos_src.end_of_stream();
+        }
+      })
+      .build(),
+  );
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  Ok(TranscodedFeed { pipeline, state })
+}
@@ -0,0 +1,145 @@
+//! # Playback Loop
+//!
+//! A/B loops of a region of the media, for review and annotation tools. The
+//! region is played with a segment seek; when it is done, a non-flushing
+//! segment seek back to its start queues the next pass right behind the
+//! current one, so the loop has no gap and no flush. Seeks are accurate, so
+//! the loop starts and ends on the frames asked for rather than on
+//! keyframes.
+
+use gst::glib;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+
+/// Playback rate of the current segment of `pipeline`, 1 if unknown
+pub(crate) fn current_rate(pipeline: &gst::Pipeline) -> f64 {
+  let mut query = gst::query::Segment::new(gst::Format::Time);
+  if pipeline.query(&mut query) {
+    query.result().0
+  } else {
+    1.0
+  }
+}
+
+/// A region looped by a pipeline, until dropped
+pub(crate) struct LoopRegion {
+  bus: gst::Bus,
+  handler: Option<glib::SignalHandlerId>,
+  start: gst::ClockTime,
+  end: gst::ClockTime,
+}
+
+impl LoopRegion {
+  /// Starts looping `start` to `end`, from the current position if it is
+  /// inside the region and from the start of the region otherwise
+  pub(crate) fn start(
+    pipeline: &gst::Pipeline,
+    start: gst::ClockTime,
+    end: gst::ClockTime,
+  ) -> Result<Self> {
+    if start >= end {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The loop must end after it starts".to_string(),
+      ));
+    }
+    let bus = pipeline
+      .bus()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+
+    let rate = current_rate(pipeline);
+    let position = pipeline
+      .query_position::<gst::ClockTime>()
+      .filter(|position| (start..end).contains(position));
+    // Playing backwards, the region is entered from its end
+    let (from, to) = match position {
+      Some(position) if rate < 0.0 => (start, position),
+      Some(position) => (position, end),
+      None => (start, end),
+    };
+    pipeline
+      .seek(
+        rate,
+        gst::SeekFlags::FLUSH | gst::SeekFlags::SEGMENT | gst::SeekFlags::ACCURATE,
+        gst::SeekType::Set,
+        from,
+        gst::SeekType::Set,
+        to,
+      )
+      .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to seek: {}", e)))?;
+
+    let looped = pipeline.downgrade();
+    bus.enable_sync_message_emission();
+    let handler = bus.connect_sync_message(Some("segment-done"), move |_, msg| {
+      let Some(pipeline) = looped.upgrade() else {
+        return;
+      };
+      // Bins post it once all their streams are done; only the pipeline's
+      // marks the end of the region
+      if msg.src() != Some(pipeline.upcast_ref::<gst::Object>()) {
+        return;
+      }
+      // The message comes from a streaming thread, which must not seek
+      pipeline.call_async(move |pipeline| {
+        let _ = pipeline.seek(
+          current_rate(pipeline),
+          gst::SeekFlags::SEGMENT | gst::SeekFlags::ACCURATE,
+          gst::SeekType::Set,
+          start,
+          gst::SeekType::Set,
+          end,
+        );
+      });
+    });
+    Ok(LoopRegion {
+      bus,
+      handler: Some(handler),
+      start,
+      end,
+    })
+  }
+
+  /// Start and end of the region
+  pub(crate) fn bounds(&self) -> (gst::ClockTime, gst::ClockTime) {
+    (self.start, self.end)
+  }
+}
+
+impl Drop for LoopRegion {
+  fn drop(&mut self) {
+    if let Some(handler) = self.handler.take() {
+      self.bus.disconnect(handler);
+      self.bus.disable_sync_message_emission();
+    }
+  }
+}
+
+/// Stops looping, letting playback continue from the current position to
+/// the end of the media
+pub(crate) fn release(pipeline: &gst::Pipeline, region: LoopRegion) -> Result<()> {
+  drop(region);
+  let Some(position) = pipeline.query_position::<gst::ClockTime>() else {
+    return Ok(());
+  };
+  let rate = current_rate(pipeline);
+  let (from, to) = if rate < 0.0 {
+    (gst::ClockTime::ZERO, Some(position))
+  } else {
+    (position, None)
+  };
+  pipeline
+    .seek(
+      rate,
+      gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+      gst::SeekType::Set,
+      from,
+      if to.is_some() {
+        gst::SeekType::Set
+      } else {
+        gst::SeekType::None
+      },
+      to,
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to seek: {}", e)))
+}
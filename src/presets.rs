@@ -0,0 +1,195 @@
+//! # Encoder Presets
+//!
+//! Maps encoder-agnostic speed/quality presets onto the properties of each
+//! GStreamer encoder element, since the backends of a codec (e.g. `av1enc`,
+//! `rav1enc` and `svtav1enc` for AV1) each have their own speed control.
+//!
+//! A preset is either a name ("ultrafast" .. "placebo") or a speed level from
+//! "0" (slowest, best quality) to "10" (fastest). Tunes are passed through to
+//! encoders that support them.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Preset names from fastest to slowest, with their speed level
+const NAMED_PRESETS: &[(&str, u32)] = &[
+  ("ultrafast", 10),
+  ("superfast", 9),
+  ("veryfast", 8),
+  ("faster", 7),
+  ("fast", 6),
+  ("medium", 5),
+  ("slow", 4),
+  ("slower", 3),
+  ("veryslow", 2),
+  ("placebo", 0),
+];
+
+/// Valid presets and tunes of a codec
+#[napi(object)]
+pub struct CodecPresets {
+  /// Accepted `preset` values
+  pub presets: Vec<String>,
+  /// Accepted `tune` values (empty if the encoder has no tuning)
+  pub tunes: Vec<String>,
+}
+
+/// Tunes supported by an encoder element
+fn encoder_tunes(encoder: &str) -> &'static [&'static str] {
+  match encoder {
+    "x264enc" => &["stillimage", "fastdecode", "zerolatency"],
+    "x265enc" => &[
+      "psnr",
+      "ssim",
+      "grain",
+      "zerolatency",
+      "fastdecode",
+      "animation",
+    ],
+    "vp8enc" | "vp9enc" => &["psnr", "ssim"],
+    _ => &[],
+  }
+}
+
+/// Returns whether an encoder element has a speed control
+pub(crate) fn has_presets(encoder: &str) -> bool {
+  matches!(
+    encoder,
+    "x264enc"
+      | "x265enc"
+      | "openh264enc"
+      | "nvh264enc"
+      | "nvh265enc"
+      | "vp8enc"
+      | "vp9enc"
+      | "av1enc"
+      | "rav1enc"
+      | "svtav1enc"
+      | "theoraenc"
+  ) || (encoder.starts_with("va") && encoder.ends_with("enc"))
+}
+
+/// Resolves a preset name or number into a speed level from 0 to 10
+fn speed_level(preset: &str) -> Result<u32> {
+  NAMED_PRESETS
+    .iter()
+    .find(|(name, _)| *name == preset)
+    .map(|&(_, level)| level)
+    .or_else(|| preset.parse::<u32>().ok().filter(|&level| level <= 10))
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid preset: {}", preset)))
+}
+
+/// Returns the name of the preset closest to a speed level
+fn preset_name(level: u32) -> &'static str {
+  NAMED_PRESETS
+    .iter()
+    .min_by_key(|(_, named)| named.abs_diff(level))
+    .map(|&(name, _)| name)
+    .unwrap_or("medium")
+}
+
+/// Translates `preset` and `tune` into property values of a video encoder element
+pub(crate) fn preset_properties(
+  encoder: &str,
+  preset: Option<&str>,
+  tune: Option<&str>,
+) -> Result<Vec<(&'static str, String)>> {
+  let mut properties = Vec::new();
+
+  if let Some(preset) = preset {
+    if !has_presets(encoder) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Encoder {} does not support presets", encoder),
+      ));
+    }
+    let level = speed_level(preset)?;
+    match encoder {
+      "x264enc" | "x265enc" => properties.push(("speed-preset", preset_name(level).to_string())),
+      "openh264enc" => {
+        let complexity = match level {
+          7.. => "low",
+          4..=6 => "medium",
+          _ => "high",
+        };
+        properties.push(("complexity", complexity.to_string()));
+      }
+      "nvh264enc" | "nvh265enc" => {
+        let nvenc_preset = match level {
+          7.. => "hp",
+          4..=6 => "default",
+          _ => "hq",
+        };
+        properties.push(("preset", nvenc_preset.to_string()));
+      }
+      "vp8enc" | "vp9enc" => {
+        properties.push(("cpu-used", (level * 8 / 10).to_string()));
+        let deadline = match level {
+          0 => 0,
+          8.. => 1,
+          _ => 1_000_000,
+        };
+        properties.push(("deadline", deadline.to_string()));
+      }
+      "av1enc" => properties.push(("cpu-used", (level * 8 / 10).to_string())),
+      "rav1enc" => properties.push(("speed-preset", level.to_string())),
+      "svtav1enc" => properties.push(("preset", (level * 13 / 10).to_string())),
+      "theoraenc" => properties.push(("speed-level", (level * 2 / 10).to_string())),
+      // VA-API encoders go from 1 (best quality) to 7 (fastest)
+      _ => properties.push(("target-usage", (1 + level * 6 / 10).to_string())),
+    }
+  }
+
+  if let Some(tune) = tune {
+    if !encoder_tunes(encoder).contains(&tune) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid tune for {}: {}", encoder, tune),
+      ));
+    }
+    let property = if encoder.starts_with("vp") {
+      "tuning"
+    } else {
+      "tune"
+    };
+    properties.push((property, tune.to_string()));
+  }
+
+  Ok(properties)
+}
+
+/// Lists the `preset` and `tune` values accepted for a video codec, by the
+/// encoder backend it resolves to
+///
+/// # Arguments
+/// * `codec` - Video codec name, as accepted by `TranscodeOptions.videoCodec`
+///
+/// # Returns
+/// * `Result<CodecPresets>` - Valid presets and tunes for the codec (no
+///   presets if its encoder has no speed control)
+///
+/// # Example
+/// ```javascript
+/// const { presets, tunes } = listPresets("h264");
+/// transcodeBuffer(input, { container: "mp4", preset: presets[0], tune: tunes[0] });
+/// ```
+#[napi]
+pub fn list_presets(codec: String) -> Result<CodecPresets> {
+  let encoder = crate::transcode::video_codec_spec(&codec)?.encoder;
+  let presets = if has_presets(&encoder) {
+    NAMED_PRESETS
+      .iter()
+      .map(|(name, _)| name.to_string())
+      .chain((0..=10).map(|level: u32| level.to_string()))
+      .collect()
+  } else {
+    Vec::new()
+  };
+  Ok(CodecPresets {
+    presets,
+    tunes: encoder_tunes(&encoder)
+      .iter()
+      .map(|t| t.to_string())
+      .collect(),
+  })
+}
@@ -0,0 +1,416 @@
+//! # Media Probing
+//!
+//! Describes media files and streams with GStreamer's discoverer, so every
+//! format GStreamer can demux and decode is supported: container, duration,
+//! tags and the properties of each video, audio and subtitle stream. Batches
+//! of files are probed in parallel on a pool of threads.
+
+use crate::kit::to_uri;
+use crate::probe_cache::ProbedFile;
+use crate::rotation::{matroska_rotations, orientation_rotation};
+use gst::prelude::*;
+use gst_pbutils::prelude::*;
+use gstreamer as gst;
+use gstreamer_pbutils as gst_pbutils;
+use napi::bindgen_prelude::Either;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A metadata tag of a media file
+#[napi(object)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaTag {
+  /// Tag name, e.g. "title" or "encoder"
+  pub name: String,
+  /// Tag value as text
+  pub value: String,
+}
+
+/// A video stream of a media file
+#[napi(object)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoStreamInfo {
+  /// Human readable codec name, e.g. "H.264 (High Profile)"
+  pub codec: String,
+  /// Caps of the stream
+  pub caps: String,
+  /// Width in pixels
+  pub width: u32,
+  /// Height in pixels
+  pub height: u32,
+  /// Frames per second, or 0 if variable or unknown
+  pub frame_rate: f64,
+  /// Pixel aspect ratio as "N:D"
+  pub pixel_aspect_ratio: String,
+  /// Bitrate in bit/s, or 0 if unknown
+  pub bitrate: u32,
+  /// Whether the stream is interlaced
+  pub interlaced: bool,
+  /// Whether the stream is a still image
+  pub is_image: bool,
+  /// Clockwise rotation in degrees (0, 90, 180 or 270) the frames must be
+  /// displayed with, from the MP4 matrix or Matroska projection
+  pub rotation: u32,
+}
+
+/// An audio stream of a media file
+#[napi(object)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioStreamInfo {
+  /// Human readable codec name, e.g. "Opus"
+  pub codec: String,
+  /// Caps of the stream
+  pub caps: String,
+  /// Number of channels
+  pub channels: u32,
+  /// Sample rate in Hz
+  pub sample_rate: u32,
+  /// Bitrate in bit/s, or 0 if unknown
+  pub bitrate: u32,
+  /// Language code, if tagged
+  pub language: Option<String>,
+}
+
+/// A subtitle stream of a media file
+#[napi(object)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleStreamInfo {
+  /// Human readable format name
+  pub codec: String,
+  /// Language code, if tagged
+  pub language: Option<String>,
+}
+
+/// Description of a media file or stream
+#[napi(object)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaInfo {
+  /// URI that was probed
+  pub uri: String,
+  /// Human readable container name, or null for elementary streams
+  pub container: Option<String>,
+  /// Duration in nanoseconds, or -1 if unknown (e.g. live streams)
+  pub duration: i64,
+  /// Whether the media can be seeked
+  pub seekable: bool,
+  /// Whether the media is a live source
+  pub live: bool,
+  /// Global metadata tags
+  pub tags: Vec<MediaTag>,
+  /// Video streams
+  pub video: Vec<VideoStreamInfo>,
+  /// Audio streams
+  pub audio: Vec<AudioStreamInfo>,
+  /// Subtitle streams
+  pub subtitles: Vec<SubtitleStreamInfo>,
+}
+
+/// Options for `getMediaInfoBatch`
+#[napi(object)]
+pub struct MediaInfoBatchOptions {
+  /// Files probed at the same time (default: number of CPUs)
+  pub concurrency: Option<u32>,
+  /// Maximum time to spend probing each file (default: 10000)
+  pub timeout_ms: Option<u32>,
+}
+
+/// Returns the caps of a stream and their human readable description
+fn describe_caps(stream: &impl IsA<gst_pbutils::DiscovererStreamInfo>) -> (String, String) {
+  match stream.caps() {
+    Some(caps) => (
+      gst_pbutils::pb_utils_get_codec_description(&caps).to_string(),
+      caps.to_string(),
+    ),
+    None => ("unknown".to_string(), String::new()),
+  }
+}
+
+pub(crate) fn tag_list(tags: Option<gst::TagList>) -> Vec<MediaTag> {
+  let Some(tags) = tags else {
+    return Vec::new();
+  };
+  tags
+    .iter()
+    .filter_map(|(name, value)| {
+      let value = value
+        .get::<String>()
+        .ok()
+        .or_else(|| value.serialize().ok().map(|v| v.to_string()))?;
+      Some(MediaTag {
+        name: name.to_string(),
+        value,
+      })
+    })
+    .collect()
+}
+
+/// Probes a media file or stream with GStreamer's discoverer
+///
+/// # Arguments
+/// * `location` - A file path or URI (file://, http://, rtsp://, ...)
+/// * `timeout_ms` - Maximum time to spend probing (default: 10000)
+///
+/// # Returns
+/// * `Result<MediaInfo>` - Container, duration, tags and stream details
+///
+/// # Example
+/// ```javascript
+/// const info = probeWithGStreamer("movie.mkv");
+/// const [video] = info.video;
+/// console.log(info.container, info.duration / 1e9, video?.codec, `${video?.width}x${video?.height}`);
+/// ```
+#[napi(js_name = "probeWithGStreamer")]
+pub fn probe_with_gstreamer(location: String, timeout_ms: Option<u32>) -> Result<MediaInfo> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  discover(&discoverer(timeout_ms)?, &location)
+}
+
+/// Creates a discoverer giving up on each file after `timeout_ms` (default: 10000)
+pub(crate) fn discoverer(timeout_ms: Option<u32>) -> Result<gst_pbutils::Discoverer> {
+  let timeout = gst::ClockTime::from_mseconds(timeout_ms.unwrap_or(10000) as u64);
+  gst_pbutils::Discoverer::new(timeout).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to create discoverer: {}", e),
+    )
+  })
+}
+
+/// Runs the discoverer on `uri`, naming `location` in errors
+pub(crate) fn discover_info(
+  discoverer: &gst_pbutils::Discoverer,
+  location: &str,
+  uri: &str,
+) -> Result<gst_pbutils::DiscovererInfo> {
+  discoverer.discover_uri(uri).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to probe {}: {}", location, e),
+    )
+  })
+}
+
+/// Probes one file or URI with an existing discoverer
+pub(crate) fn discover(discoverer: &gst_pbutils::Discoverer, location: &str) -> Result<MediaInfo> {
+  let uri = to_uri(location)?;
+  let info = discover_info(discoverer, location, &uri)?;
+
+  let container = info
+    .stream_info()
+    .filter(|stream| stream.is::<gst_pbutils::DiscovererContainerInfo>())
+    .map(|stream| describe_caps(&stream).0);
+
+  // Matroska rotations are not tagged by the demuxer; read them from the file
+  let is_matroska = info
+    .stream_info()
+    .and_then(|stream| stream.caps())
+    .and_then(|caps| caps.structure(0).map(|s| s.name().to_string()))
+    .is_some_and(|name| matches!(name.as_str(), "video/x-matroska" | "video/webm"));
+  let matroska_rotations = is_matroska
+    .then(|| gst::glib::filename_from_uri(&uri).ok())
+    .flatten()
+    .map(|(path, _)| matroska_rotations(&path.to_string_lossy()))
+    .unwrap_or_default();
+
+  let video = info
+    .video_streams()
+    .iter()
+    .enumerate()
+    .map(|(index, stream)| {
+      let (codec, caps) = describe_caps(stream);
+      let rate = stream.framerate();
+      let par = stream.par();
+      VideoStreamInfo {
+        codec,
+        caps,
+        width: stream.width(),
+        height: stream.height(),
+        frame_rate: if rate.denom() > 0 {
+          rate.numer() as f64 / rate.denom() as f64
+        } else {
+          0.0
+        },
+        pixel_aspect_ratio: format!("{}:{}", par.numer(), par.denom()),
+        bitrate: stream.bitrate(),
+        interlaced: stream.is_interlaced(),
+        is_image: stream.is_image(),
+        rotation: stream
+          .tags()
+          .and_then(|tags| tags.get::<gst::tags::ImageOrientation>())
+          .map(|orientation| orientation_rotation(orientation.get()))
+          .or_else(|| matroska_rotations.get(index).copied())
+          .unwrap_or(0),
+      }
+    })
+    .collect();
+
+  let audio = info
+    .audio_streams()
+    .iter()
+    .map(|stream| {
+      let (codec, caps) = describe_caps(stream);
+      AudioStreamInfo {
+        codec,
+        caps,
+        channels: stream.channels(),
+        sample_rate: stream.sample_rate(),
+        bitrate: stream.bitrate(),
+        language: stream.language().map(|l| l.to_string()),
+      }
+    })
+    .collect();
+
+  let subtitles = info
+    .subtitle_streams()
+    .iter()
+    .map(|stream| SubtitleStreamInfo {
+      codec: describe_caps(stream).0,
+      language: stream.language().map(|l| l.to_string()),
+    })
+    .collect();
+
+  Ok(MediaInfo {
+    uri,
+    container,
+    duration: info.duration().map(|d| d.nseconds() as i64).unwrap_or(-1),
+    seekable: info.is_seekable(),
+    live: info.is_live(),
+    tags: tag_list(info.tags()),
+    video,
+    audio,
+    subtitles,
+  })
+}
+
+/// Probes `locations` on `concurrency` threads, each with its own discoverer.
+/// Results are in the order of `locations`.
+pub(crate) fn discover_all(
+  locations: &[String],
+  timeout_ms: Option<u32>,
+  concurrency: usize,
+) -> Vec<std::result::Result<MediaInfo, String>> {
+  let next = AtomicUsize::new(0);
+  let mut results: Vec<_> = locations
+    .iter()
+    .map(|_| Err("Probe worker failed".to_string()))
+    .collect();
+  std::thread::scope(|scope| {
+    let workers: Vec<_> = (0..concurrency.clamp(1, locations.len().max(1)))
+      .map(|_| {
+        scope.spawn(|| {
+          let discoverer = discoverer(timeout_ms).map_err(|e| e.reason.clone());
+          let mut done = Vec::new();
+          loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(location) = locations.get(index) else {
+              break;
+            };
+            let result = match &discoverer {
+              Ok(discoverer) => discover(discoverer, location).map_err(|e| e.reason.clone()),
+              Err(e) => Err(e.clone()),
+            };
+            done.push((index, result));
+          }
+          done
+        })
+      })
+      .collect();
+    for worker in workers {
+      for (index, result) in worker.join().unwrap_or_default() {
+        results[index] = result;
+      }
+    }
+  });
+  results
+}
+
+/// Probes many media files in parallel in a single call
+///
+/// The files are spread over a pool of threads, each probing with its own
+/// discoverer, which avoids both the serial wait of probing one file after
+/// another and the per-call overhead of looping in JavaScript. A file that
+/// cannot be probed is reported with an error instead of failing the batch.
+///
+/// # Arguments
+/// * `locations` - File paths or URIs, or a glob pattern such as "media/**/*.mp4"
+/// * `options` - Optional concurrency and per-file timeout
+///
+/// # Returns
+/// * `Result<Vec<ProbedFile>>` - One result per file, in the order given or,
+///   for a pattern, sorted by path
+///
+/// # Example
+/// ```javascript
+/// const results = getMediaInfoBatch("media/**/*.mkv", { concurrency: 8 });
+/// const failed = results.filter((r) => r.error);
+/// ```
+#[napi]
+pub fn get_media_info_batch(
+  #[napi(ts_arg_type = "string[] | string")] locations: Either<Vec<String>, String>,
+  options: Option<MediaInfoBatchOptions>,
+) -> Result<Vec<ProbedFile>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let locations = match locations {
+    Either::A(locations) => locations,
+    Either::B(pattern) => glob::glob(&pattern)
+      .map_err(|e| {
+        Error::new(
+          Status::InvalidArg,
+          format!("Invalid glob pattern {}: {}", pattern, e),
+        )
+      })?
+      .filter_map(|entry| entry.ok())
+      .filter(|path| path.is_file())
+      .map(|path| path.to_string_lossy().into_owned())
+      .collect(),
+  };
+  let options = options.unwrap_or(MediaInfoBatchOptions {
+    concurrency: None,
+    timeout_ms: None,
+  });
+  let concurrency = match options.concurrency {
+    Some(concurrency) => concurrency as usize,
+    None => std::thread::available_parallelism().map_or(4, |n| n.get()),
+  };
+
+  let results = discover_all(&locations, options.timeout_ms, concurrency);
+  Ok(
+    locations
+      .into_iter()
+      .zip(results)
+      .map(|(path, result)| match result {
+        Ok(info) => ProbedFile {
+          path,
+          info: Some(info),
+          error: None,
+          cached: false,
+        },
+        Err(error) => ProbedFile {
+          path,
+          info: None,
+          error: Some(error),
+          cached: false,
+        },
+      })
+      .collect(),
+  )
+}
@@ -0,0 +1,260 @@
+//! # Probe Cache
+//!
+//! Probes whole directories, remembering the results in an optional JSON
+//! cache file so that probing the same library again only re-parses files
+//! that changed. Entries are keyed by a fingerprint of the file contents
+//! (a hash of its first bytes, its size and its modification time): an
+//! edited, replaced or touched file gets a new key and is probed again, while
+//! a renamed or moved file keeps its entry.
+
+use crate::kit::to_uri;
+use crate::probe::{discover, discoverer, MediaInfo};
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Version of the cache file layout; files of other versions are ignored
+const CACHE_VERSION: u32 = 2;
+
+/// Options for `probeDirectory`
+#[napi(object)]
+pub struct ProbeDirectoryOptions {
+  /// JSON file caching the results between calls, created if missing
+  /// (default: no cache)
+  pub cache_path: Option<String>,
+  /// Also probe the files of subdirectories (default: false)
+  pub recursive: Option<bool>,
+  /// Only probe files with these extensions, e.g. ["mp4", "mkv"]
+  /// (default: every file)
+  pub extensions: Option<Vec<String>>,
+  /// Bytes at the start of each file hashed into its cache key (default: 65536)
+  pub hash_bytes: Option<u32>,
+  /// Maximum time to spend probing each file (default: 10000)
+  pub timeout_ms: Option<u32>,
+}
+
+/// Result of probing one file of a directory
+#[napi(object)]
+pub struct ProbedFile {
+  /// Path of the file
+  pub path: String,
+  /// Description of the file, or null if it could not be probed
+  pub info: Option<MediaInfo>,
+  /// Why the file could not be probed
+  pub error: Option<String>,
+  /// Whether the description came from the cache
+  pub cached: bool,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+  version: u32,
+  entries: HashMap<String, MediaInfo>,
+}
+
+/// 64-bit FNV-1a, stable across builds unlike the standard library's hasher
+fn fnv1a(data: &[u8]) -> u64 {
+  data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+    (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+  })
+}
+
+/// Cache key of a file: hash of its first `hash_bytes` bytes, size and mtime
+fn fingerprint(path: &Path, hash_bytes: usize) -> std::io::Result<String> {
+  let metadata = std::fs::metadata(path)?;
+  let mtime = metadata
+    .modified()?
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_nanos())
+    .unwrap_or(0);
+  let mut head = Vec::with_capacity(hash_bytes);
+  std::fs::File::open(path)?
+    .take(hash_bytes as u64)
+    .read_to_end(&mut head)?;
+  Ok(format!(
+    "{:016x}-{}-{}",
+    fnv1a(&head),
+    metadata.len(),
+    mtime
+  ))
+}
+
+/// Lists the files of `dir` matching `extensions`, skipping hidden entries
+fn list_files(
+  dir: &Path,
+  recursive: bool,
+  extensions: &[String],
+  files: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+  for entry in std::fs::read_dir(dir)? {
+    let entry = entry?;
+    if entry.file_name().to_string_lossy().starts_with('.') {
+      continue;
+    }
+    let path = entry.path();
+    let file_type = entry.file_type()?;
+    if file_type.is_dir() {
+      if recursive {
+        list_files(&path, recursive, extensions, files)?;
+      }
+    } else if extensions.is_empty()
+      || path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| extensions.contains(&ext))
+    {
+      files.push(path);
+    }
+  }
+  Ok(())
+}
+
+fn load_cache(path: &str) -> CacheFile {
+  std::fs::read(path)
+    .ok()
+    .and_then(|json| serde_json::from_slice::<CacheFile>(&json).ok())
+    .filter(|cache| cache.version == CACHE_VERSION)
+    .unwrap_or_default()
+}
+
+/// Writes the cache next to its final path first, so readers never see a
+/// partial file
+fn save_cache(path: &str, cache: &CacheFile) -> Result<()> {
+  let json = serde_json::to_vec(cache).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to serialize probe cache: {}", e),
+    )
+  })?;
+  let partial = format!("{}.partial", path);
+  std::fs::write(&partial, json)
+    .and_then(|_| std::fs::rename(&partial, path))
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write probe cache {}: {}", path, e),
+      )
+    })
+}
+
+/// Probes every media file of a directory, reusing cached results
+///
+/// Files whose fingerprint is found in the cache are not opened beyond their
+/// first bytes, so probing a library of hundreds of files again takes
+/// milliseconds. Files that cannot be probed are reported with an error
+/// rather than failing the whole call, and are not cached.
+///
+/// # Arguments
+/// * `dir` - The directory to probe
+/// * `options` - Optional cache file, recursion, extension filter and timeout
+///
+/// # Returns
+/// * `Result<Vec<ProbedFile>>` - One result per file, sorted by path
+///
+/// # Example
+/// ```javascript
+/// const files = probeDirectory("/media/library", {
+///   cachePath: "/var/cache/library-probe.json",
+///   recursive: true,
+///   extensions: ["mp4", "mkv", "mov"],
+/// });
+/// for (const { path, info, error } of files) {
+///   console.log(path, info ? info.duration / 1e9 : error);
+/// }
+/// ```
+#[napi]
+pub fn probe_directory(
+  dir: String,
+  options: Option<ProbeDirectoryOptions>,
+) -> Result<Vec<ProbedFile>> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(ProbeDirectoryOptions {
+    cache_path: None,
+    recursive: None,
+    extensions: None,
+    hash_bytes: None,
+    timeout_ms: None,
+  });
+  let extensions: Vec<String> = options
+    .extensions
+    .unwrap_or_default()
+    .iter()
+    .map(|ext| ext.trim_start_matches('.').to_lowercase())
+    .collect();
+  let mut files = Vec::new();
+  list_files(
+    Path::new(&dir),
+    options.recursive.unwrap_or(false),
+    &extensions,
+    &mut files,
+  )
+  .map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to list directory {}: {}", dir, e),
+    )
+  })?;
+  files.sort();
+
+  let mut cache = options
+    .cache_path
+    .as_deref()
+    .map(load_cache)
+    .unwrap_or_default();
+  cache.version = CACHE_VERSION;
+  let hash_bytes = options.hash_bytes.unwrap_or(65536) as usize;
+  let discoverer = discoverer(options.timeout_ms)?;
+  let mut changed = false;
+
+  let mut results = Vec::with_capacity(files.len());
+  for path in files {
+    let location = path.to_string_lossy().into_owned();
+    let key = fingerprint(&path, hash_bytes).ok();
+    if let Some(mut info) = key.as_ref().and_then(|key| cache.entries.get(key)).cloned() {
+      // The entry may have been made for a copy of the file elsewhere
+      info.uri = to_uri(&location)?;
+      results.push(ProbedFile {
+        path: location,
+        info: Some(info),
+        error: None,
+        cached: true,
+      });
+      continue;
+    }
+
+    let result = discover(&discoverer, &location);
+    if let (Ok(info), Some(key)) = (&result, key) {
+      cache.entries.insert(key, info.clone());
+      changed = true;
+    }
+    results.push(match result {
+      Ok(info) => ProbedFile {
+        path: location,
+        info: Some(info),
+        error: None,
+        cached: false,
+      },
+      Err(e) => ProbedFile {
+        path: location,
+        info: None,
+        error: Some(e.reason.clone()),
+        cached: false,
+      },
+    });
+  }
+
+  if let (Some(path), true) = (options.cache_path.as_deref(), changed) {
+    save_cache(path, &cache)?;
+  }
+  Ok(results)
+}
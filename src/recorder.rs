@@ -0,0 +1,496 @@
+//! # Pipeline Recording
+//!
+//! Records the frames reaching an AppSink of a `GstKit` pipeline into an IVF
+//! or WebM file while the pipeline runs. Raw video is encoded to VP8, VP9 or
+//! AV1; VP8, VP9 and AV1 streams, from an encoder placed before the sink,
+//! are written as they are. The recording runs until `PipelineRecorder.stop`
+//! is called, which finalizes the file, and stops taking frames when the
+//! pipeline reaches end of stream. Timestamps start at zero with the first
+//! recorded frame.
+//!
+//! The recorder takes over the callbacks of the sink, so `pullSample` no
+//! longer returns its frames while recording. Frames are handed to a
+//! separate pipeline that encodes and muxes them, queueing there when
+//! encoding falls behind rather than stalling the recorded pipeline.
+
+use crate::kit::GstKit;
+use crate::presets::preset_properties;
+use crate::transcode::{launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Options for `recordFromPipeline`
+#[napi(object)]
+#[derive(Clone)]
+pub struct RecordOptions {
+  /// Output container, "ivf" or "webm"; defaults to the output file extension
+  pub container: Option<String>,
+  /// Codec raw frames are encoded to: "vp8" (default), "vp9" or "av1".
+  /// Encoded frames are written as they are.
+  pub video_codec: Option<String>,
+  /// Target video bitrate in kbit/s
+  pub video_bitrate: Option<u32>,
+  /// Encoder speed preset (see `listPresets`), e.g. "ultrafast" for live input
+  pub preset: Option<String>,
+  /// Encoder tuning (see `listPresets`)
+  pub tune: Option<String>,
+}
+
+/// Summary of a finished recording
+#[napi(object)]
+pub struct RecordingStats {
+  /// Number of frames recorded
+  pub frames: u32,
+  /// Time between the first and the last frame, in nanoseconds
+  pub duration: i64,
+  /// Size of the output file in bytes
+  pub bytes: i64,
+}
+
+/// Codecs an IVF or WebM file carries: name, caps name and IVF fourcc
+const CODECS: &[(&str, &str, &[u8; 4])] = &[
+  ("vp8", "video/x-vp8", b"VP80"),
+  ("vp9", "video/x-vp9", b"VP90"),
+  ("av1", "video/x-av1", b"AV01"),
+];
+
+/// Writes frames into an IVF file: a 32 byte header followed by frames, each
+/// with a 12 byte header holding its size and timestamp
+struct IvfWriter {
+  file: File,
+  /// Time base numerator and denominator
+  time_base: Option<(u64, u64)>,
+  frames: u32,
+}
+
+impl IvfWriter {
+  fn write(&mut self, sample: &gst::Sample) -> std::io::Result<()> {
+    let invalid = |reason: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, reason);
+    let buffer = sample
+      .buffer()
+      .ok_or_else(|| invalid("sample has no buffer"))?;
+    let (num, den) = match self.time_base {
+      Some(time_base) => time_base,
+      None => {
+        let structure = sample
+          .caps()
+          .and_then(|caps| caps.structure(0))
+          .ok_or_else(|| invalid("sample has no caps"))?;
+        let fourcc = CODECS
+          .iter()
+          .find(|(_, name, _)| structure.name() == *name)
+          .map(|(_, _, fourcc)| *fourcc)
+          .ok_or_else(|| invalid("unsupported codec"))?;
+        // Frame durations make the best time base, milliseconds otherwise
+        let time_base = match structure.get::<gst::Fraction>("framerate") {
+          Ok(rate) if rate.numer() > 0 => (rate.denom() as u64, rate.numer() as u64),
+          _ => (1, 1000),
+        };
+        let mut header = Vec::with_capacity(32);
+        header.extend_from_slice(b"DKIF");
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&32u16.to_le_bytes());
+        header.extend_from_slice(fourcc);
+        for field in ["width", "height"] {
+          let value = structure.get::<i32>(field).unwrap_or(0);
+          header.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        header.extend_from_slice(&(time_base.1 as u32).to_le_bytes());
+        header.extend_from_slice(&(time_base.0 as u32).to_le_bytes());
+        header.extend_from_slice(&[0; 8]);
+        self.file.write_all(&header)?;
+        *self.time_base.insert(time_base)
+      }
+    };
+    let pts = buffer.pts().map(|pts| pts.nseconds()).unwrap_or(0);
+    let unit = num as u128 * 1_000_000_000;
+    let pts = ((pts as u128 * den as u128 + unit / 2) / unit) as u64;
+    let map = buffer
+      .map_readable()
+      .map_err(|_| invalid("unreadable buffer"))?;
+    self.file.write_all(&(map.len() as u32).to_le_bytes())?;
+    self.file.write_all(&pts.to_le_bytes())?;
+    self.file.write_all(&map)?;
+    self.frames += 1;
+    Ok(())
+  }
+
+  /// Stores the frame count in the header
+  fn finish(&mut self) -> std::io::Result<()> {
+    self.file.seek(SeekFrom::Start(24))?;
+    self.file.write_all(&self.frames.to_le_bytes())?;
+    self.file.flush()
+  }
+}
+
+/// The pipeline encoding and muxing the recorded frames
+struct Recording {
+  pipeline: gst::Pipeline,
+  appsrc: AppSrc,
+  ivf: Option<Arc<Mutex<IvfWriter>>>,
+}
+
+/// A recording to one file, started by its first frame
+pub(crate) struct RecorderState {
+  output_path: String,
+  /// Output file, kept for IVF until the first frame starts the recording
+  ivf_file: Option<File>,
+  /// Encoder and its properties, for raw frames
+  encoder: (String, Option<String>, Vec<(String, String)>),
+  recording: Option<Recording>,
+  first_pts: Option<gst::ClockTime>,
+  last_pts: Option<gst::ClockTime>,
+  frames: u32,
+  error: Option<String>,
+  stopped: bool,
+}
+
+impl RecorderState {
+  /// Validates the options and creates the output file of a recording
+  pub(crate) fn create(output_path: String, options: RecordOptions) -> Result<Self> {
+    let container = options
+      .container
+      .or_else(|| {
+        Path::new(&output_path)
+          .extension()
+          .and_then(|ext| ext.to_str())
+          .map(|ext| ext.to_lowercase())
+      })
+      .unwrap_or_default();
+    if !matches!(container.as_str(), "ivf" | "webm") {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Unsupported recording container: \"{}\" (use \"ivf\" or \"webm\")",
+          container
+        ),
+      ));
+    }
+    let codec = options.video_codec.unwrap_or_else(|| "vp8".to_string());
+    if !CODECS.iter().any(|(name, _, _)| *name == codec) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Unsupported recording codec: {} (use \"vp8\", \"vp9\" or \"av1\")",
+          codec
+        ),
+      ));
+    }
+    let spec = video_codec_spec(&codec)?;
+    let mut properties = match (spec.bitrate_property, options.video_bitrate) {
+      (Some(property), Some(kbps)) => vec![(property, (kbps * spec.bitrate_scale).to_string())],
+      _ => Vec::new(),
+    };
+    properties.extend(
+      preset_properties(
+        &spec.encoder,
+        options.preset.as_deref(),
+        options.tune.as_deref(),
+      )?
+      .into_iter()
+      .map(|(property, value)| (property.to_string(), value)),
+    );
+
+    let file = File::create(&output_path).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to create {}: {}", output_path, e),
+      )
+    })?;
+    Ok(RecorderState {
+      output_path,
+      ivf_file: (container == "ivf").then_some(file),
+      encoder: (spec.encoder, spec.parser, properties),
+      recording: None,
+      first_pts: None,
+      last_pts: None,
+      frames: 0,
+      error: None,
+      stopped: false,
+    })
+  }
+
+  /// Builds the recording pipeline for frames with `caps`
+  fn start(&mut self, caps: &gst::CapsRef) -> std::result::Result<Recording, String> {
+    let name = caps
+      .structure(0)
+      .map(|s| s.name().to_string())
+      .unwrap_or_default();
+    let encode = if name == "video/x-raw" {
+      let (encoder, parser, _) = &self.encoder;
+      let parser = parser
+        .as_ref()
+        .map(|parser| format!(" ! {}", parser))
+        .unwrap_or_default();
+      format!("videoconvert ! {} name=enc{} ! ", encoder, parser)
+    } else if CODECS.iter().any(|(_, codec, _)| *codec == name) {
+      String::new()
+    } else {
+      return Err(format!(
+        "Cannot record {}: expected raw video or VP8, VP9 or AV1",
+        name
+      ));
+    };
+    let tail = match self.ivf_file {
+      Some(_) => "appsink name=ivf sync=false".to_string(),
+      None => "webmmux ! filesink name=sink".to_string(),
+    };
+    let pipeline = launch(&format!(
+      "appsrc name=src format=time ! queue ! {}{}",
+      encode, tail
+    ))
+    .map_err(|e| e.reason)?;
+    if let Some(sink) = pipeline.by_name("sink") {
+      sink.set_property("location", &self.output_path);
+    }
+    if let Some(encoder) = pipeline.by_name("enc") {
+      for (property, value) in &self.encoder.2 {
+        if encoder.find_property(property).is_some() {
+          encoder.set_property_from_str(property, value);
+        }
+      }
+    }
+    let appsrc = pipeline
+      .by_name("src")
+      .and_then(|el| el.downcast::<AppSrc>().ok())
+      .ok_or("Recording source not found")?;
+    appsrc.set_caps(Some(&caps.to_owned()));
+
+    let ivf = match self.ivf_file.take() {
+      Some(file) => {
+        let writer = Arc::new(Mutex::new(IvfWriter {
+          file,
+          time_base: None,
+          frames: 0,
+        }));
+        let sink = pipeline
+          .by_name("ivf")
+          .and_then(|el| el.downcast::<AppSink>().ok())
+          .ok_or("Recording sink not found")?;
+        let frames = writer.clone();
+        sink.set_callbacks(
+          gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+              let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+              frames
+                .lock()
+                .unwrap()
+                .write(&sample)
+                .map_err(|_| gst::FlowError::Error)?;
+              Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+        );
+        Some(writer)
+      }
+      None => None,
+    };
+    pipeline
+      .set_state(gst::State::Playing)
+      .map_err(|e| format!("Failed to start recording: {}", e))?;
+    Ok(Recording {
+      pipeline,
+      appsrc,
+      ivf,
+    })
+  }
+
+  /// Hands a frame to the recording pipeline, starting it on the first one
+  pub(crate) fn push(&mut self, sample: &gst::Sample) -> std::result::Result<(), String> {
+    let buffer = sample.buffer().ok_or("Sample has no buffer")?;
+    let caps = sample.caps().ok_or("Sample has no caps")?;
+    if self.recording.is_none() {
+      self.recording = Some(self.start(caps)?);
+    }
+    let Some(recording) = &self.recording else {
+      return Ok(());
+    };
+    if recording.appsrc.caps().as_deref() != Some(caps) {
+      recording.appsrc.set_caps(Some(&caps.to_owned()));
+    }
+
+    let mut buffer = buffer.copy();
+    if let Some(pts) = buffer.pts() {
+      let first = *self.first_pts.get_or_insert(pts);
+      self.last_pts = Some(pts);
+      let dts = buffer.dts();
+      let buffer = buffer.make_mut();
+      buffer.set_pts(pts.saturating_sub(first));
+      buffer.set_dts(dts.map(|dts| dts.saturating_sub(first)));
+    }
+    recording
+      .appsrc
+      .push_buffer(buffer)
+      .map_err(|e| format!("Recording stopped: {:?}", e))?;
+    self.frames += 1;
+    Ok(())
+  }
+
+  /// Ends the recording and finalizes the output file; `source` names where
+  /// the frames came from
+  pub(crate) fn finish(&mut self, source: &str) -> Result<RecordingStats> {
+    if std::mem::replace(&mut self.stopped, true) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Recording already stopped".to_string(),
+      ));
+    }
+    let Some(recording) = self.recording.take() else {
+      // Nothing was written to the file created for the recording
+      self.ivf_file = None;
+      let _ = fs::remove_file(&self.output_path);
+      return Err(Error::new(
+        Status::GenericFailure,
+        self
+          .error
+          .take()
+          .unwrap_or_else(|| format!("No frames reached {}", source)),
+      ));
+    };
+    let _ = recording.appsrc.end_of_stream();
+    let result = wait_for_eos(&recording.pipeline);
+    let _ = recording.pipeline.set_state(gst::State::Null);
+    if let Some(ivf) = &recording.ivf {
+      ivf.lock().unwrap().finish().map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to write {}: {}", self.output_path, e),
+        )
+      })?;
+    }
+    if let Some(error) = self.error.take() {
+      return Err(Error::new(Status::GenericFailure, error));
+    }
+    result?;
+    Ok(RecordingStats {
+      frames: self.frames,
+      duration: match (self.first_pts, self.last_pts) {
+        (Some(first), Some(last)) => last.saturating_sub(first).nseconds() as i64,
+        _ => 0,
+      },
+      bytes: fs::metadata(&self.output_path)
+        .map(|m| m.len() as i64)
+        .unwrap_or(0),
+    })
+  }
+}
+
+/// A recording started by `recordFromPipeline`
+#[napi]
+pub struct PipelineRecorder {
+  appsink: AppSink,
+  state: Arc<Mutex<RecorderState>>,
+}
+
+impl PipelineRecorder {
+  /// Detaches from the sink and finalizes the output file
+  fn finish(&self) -> Result<RecordingStats> {
+    self
+      .appsink
+      .set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    self.state.lock().unwrap().finish(&self.appsink.name())
+  }
+}
+
+#[napi]
+impl PipelineRecorder {
+  /// Number of frames recorded so far
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.state.lock().unwrap().frames
+  }
+
+  /// Stops recording and finalizes the output file
+  ///
+  /// # Returns
+  /// * `Result<RecordingStats>` - Frames, duration and size of the recording
+  ///
+  /// # Example
+  /// ```javascript
+  /// const { frames, bytes } = recorder.stop();
+  /// ```
+  #[napi]
+  pub fn stop(&self) -> Result<RecordingStats> {
+    self.finish()
+  }
+}
+
+impl Drop for PipelineRecorder {
+  fn drop(&mut self) {
+    if !self.state.lock().unwrap().stopped {
+      let _ = self.finish();
+    }
+  }
+}
+
+/// Records the frames reaching an AppSink into an IVF or WebM file
+///
+/// Raw frames are encoded with `videoCodec`; VP8, VP9 and AV1 frames are
+/// written without re-encoding. The sink's callbacks are taken over until
+/// the recording is stopped.
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `sink_name` - The name of the AppSink element
+/// * `output_path` - The file to write
+/// * `codec_options` - Container, codec, bitrate and encoder preset
+///
+/// # Returns
+/// * `Result<PipelineRecorder>` - The running recording
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("v4l2src ! videoconvert ! appsink name=sink");
+/// const recorder = recordFromPipeline(kit, "sink", "camera.webm", { videoCodec: "vp9", preset: "ultrafast" });
+/// kit.play();
+/// setTimeout(() => console.log(recorder.stop()), 10_000);
+/// ```
+#[napi]
+pub fn record_from_pipeline(
+  kit: &GstKit,
+  sink_name: String,
+  output_path: String,
+  codec_options: Option<RecordOptions>,
+) -> Result<PipelineRecorder> {
+  let options = codec_options.unwrap_or(RecordOptions {
+    container: None,
+    video_codec: None,
+    video_bitrate: None,
+    preset: None,
+    tune: None,
+  });
+  let appsink = kit.app_sink(&sink_name)?;
+  let state = Arc::new(Mutex::new(RecorderState::create(output_path, options)?));
+
+  let samples = state.clone();
+  let eos = state.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let mut state = samples.lock().unwrap();
+        // A failed recording must not stop the recorded pipeline
+        if state.error.is_none() {
+          if let Err(reason) = state.push(&sample) {
+            state.error = Some(reason);
+          }
+        }
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .eos(move |_| {
+        if let Some(recording) = &eos.lock().unwrap().recording {
+          let _ = recording.appsrc.end_of_stream();
+        }
+      })
+      .build(),
+  );
+  Ok(PipelineRecorder { appsink, state })
+}
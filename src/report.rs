@@ -0,0 +1,168 @@
+//! # Transcode Reports
+//!
+//! Describes what a finished transcode actually did: the caps entering and
+//! leaving every stream branch, the filters and encoder it went through, the
+//! encoder settings that differ from their defaults, timing and warnings.
+//! Reports serialize to JSON for logging.
+
+use gst::prelude::*;
+use gstreamer as gst;
+use napi_derive::napi;
+use serde::Serialize;
+
+/// An encoder property whose value differs from its default
+#[napi(object)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderSetting {
+  /// Property name
+  pub name: String,
+  /// Property value, serialized as in a launch string
+  pub value: String,
+}
+
+/// How one stream was processed
+#[napi(object)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamReport {
+  /// "video" or "audio"
+  pub media: String,
+  /// Caps of the decoded (or, when copying, parsed) input stream
+  pub input_caps: Option<String>,
+  /// Caps of the stream handed to the muxer
+  pub output_caps: Option<String>,
+  /// Encoder element, or null when the stream was copied
+  pub encoder: Option<String>,
+  /// Encoder properties that differ from their defaults
+  pub settings: Vec<EncoderSetting>,
+  /// Filters applied before the encoder, in order
+  pub filters: Vec<String>,
+}
+
+/// Structured description of a finished transcode
+#[napi(object)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscodeReport {
+  /// Output container
+  pub container: String,
+  /// Muxer element
+  pub muxer: String,
+  /// Every stream written to the output
+  pub streams: Vec<StreamReport>,
+  /// Wall time of the transcode in milliseconds
+  pub wall_time_ms: f64,
+  /// Video frames read from the input
+  pub video_frames: u32,
+  /// Video frames processed per second of wall time
+  pub average_fps: f64,
+  /// Input size in bytes
+  pub input_size: i64,
+  /// Output size in bytes
+  pub output_size: i64,
+  /// Warnings posted while transcoding
+  pub warnings: Vec<String>,
+}
+
+/// Returns the encoder properties that differ from their defaults
+fn changed_settings(encoder: &gst::Element) -> Vec<EncoderSetting> {
+  encoder
+    .list_properties()
+    .iter()
+    .filter(|pspec| {
+      pspec.flags().contains(gst::glib::ParamFlags::READWRITE)
+        && !matches!(pspec.name(), "name" | "parent")
+    })
+    .filter_map(|pspec| {
+      let value = encoder.property_value(pspec.name()).serialize().ok()?;
+      let default = pspec.default_value().serialize().ok()?;
+      (value != default).then(|| EncoderSetting {
+        name: pspec.name().to_string(),
+        value: value.to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Describes the branch feeding one muxer sink pad, walking upstream until
+/// the demuxing element named "demux"
+fn describe_stream(pad: &gst::Pad) -> Option<StreamReport> {
+  let output_caps = pad.current_caps();
+  let media = output_caps
+    .as_ref()
+    .and_then(|caps| caps.structure(0))
+    .map(|s| s.name().split('/').next().unwrap_or_default().to_string())?;
+
+  let mut chain = Vec::new();
+  let mut input_caps = None;
+  let mut upstream = pad.peer();
+  while let Some(src) = upstream {
+    let element = src.parent_element()?;
+    if element.name() == "demux" {
+      input_caps = src.current_caps();
+      break;
+    }
+    upstream = element.sink_pads().first().and_then(|sink| sink.peer());
+    chain.push(element);
+  }
+  chain.reverse();
+
+  let klass = |element: &gst::Element| {
+    element
+      .factory()
+      .and_then(|f| f.metadata(gst::ELEMENT_METADATA_KLASS).map(str::to_string))
+      .unwrap_or_default()
+  };
+  let encoder = chain.iter().find(|el| klass(el).contains("Encoder"));
+  let filters = chain
+    .iter()
+    .filter_map(|el| {
+      let factory = el.factory()?.name();
+      if factory == "capsfilter" {
+        Some(format!(
+          "capsfilter caps={}",
+          el.property::<gst::Caps>("caps")
+        ))
+      } else if klass(el).starts_with("Filter") {
+        Some(factory.to_string())
+      } else {
+        None
+      }
+    })
+    .collect();
+
+  Some(StreamReport {
+    media,
+    input_caps: input_caps.map(|caps| caps.to_string()),
+    output_caps: output_caps.map(|caps| caps.to_string()),
+    encoder: encoder
+      .and_then(|el| el.factory())
+      .map(|f| f.name().to_string()),
+    settings: encoder.map(changed_settings).unwrap_or_default(),
+    filters,
+  })
+}
+
+/// Describes every stream written by `muxer`; call before the pipeline is
+/// shut down, while negotiated caps are still set
+pub(crate) fn describe_streams(muxer: &gst::Element) -> Vec<StreamReport> {
+  muxer
+    .sink_pads()
+    .iter()
+    .filter_map(describe_stream)
+    .collect()
+}
+
+/// Drains the warnings posted on a pipeline bus
+pub(crate) fn drain_warnings(pipeline: &gst::Pipeline) -> Vec<String> {
+  let Some(bus) = pipeline.bus() else {
+    return Vec::new();
+  };
+  std::iter::from_fn(|| bus.pop_filtered(&[gst::MessageType::Warning]))
+    .filter_map(|msg| match msg.view() {
+      gst::MessageView::Warning(warning) => Some(warning.error().to_string()),
+      _ => None,
+    })
+    .collect()
+}
@@ -0,0 +1,130 @@
+//! # Rotation
+//!
+//! Phones record portrait video as landscape frames plus a note telling
+//! players to rotate them. GStreamer reports that note as the
+//! `image-orientation` tag, which the MP4/MOV demuxer posts from the track
+//! matrix. Matroska stores it as the `ProjectionPoseRoll` of the track, which
+//! no demuxer turns into a tag, so it is read from the file header here.
+//!
+//! Rotations are given clockwise in degrees (0, 90, 180 or 270), the angle
+//! the frames must be turned by to display upright, as in the
+//! `image-orientation` tag. Mirrored orientations are not reported.
+
+use crate::container_dump::matroska_video_rolls;
+use crate::transcode::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::Result;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::sync::{Arc, Mutex};
+
+/// Longest Matroska prefix searched for the Tracks element while transcoding
+const MAX_SNIFF_BYTES: usize = 4 * 1024 * 1024;
+
+/// Rotation of an `image-orientation` tag value such as "rotate-90"
+pub(crate) fn orientation_rotation(orientation: &str) -> u32 {
+  match orientation {
+    "rotate-90" => 90,
+    "rotate-180" => 180,
+    "rotate-270" => 270,
+    _ => 0,
+  }
+}
+
+/// Rotation of a Matroska `ProjectionPoseRoll`, which turns the frames
+/// counter-clockwise, rounded to a quarter turn
+pub(crate) fn roll_rotation(roll: f64) -> u32 {
+  ((-roll / 90.0).round() as i64 * 90).rem_euclid(360) as u32
+}
+
+/// Rotations of the video tracks of a Matroska file, in track order; empty
+/// for files of other formats
+pub(crate) fn matroska_rotations(path: &str) -> Vec<u32> {
+  let Ok(file) = File::open(path) else {
+    return Vec::new();
+  };
+  let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+  matroska_video_rolls(BufReader::new(file), len)
+    .unwrap_or_default()
+    .into_iter()
+    .map(roll_rotation)
+    .collect()
+}
+
+/// Reads the rotation of the first video track from the Matroska header
+/// flowing out of `source`, for inputs read as a stream. The rotation is
+/// known by the time the demuxer adds its pads, and stays `None` for other
+/// formats.
+pub(crate) fn sniff_matroska_rotation(source: &gst::Element) -> Arc<Mutex<Option<u32>>> {
+  let rotation = Arc::new(Mutex::new(None));
+  let Some(pad) = source.static_pad("src") else {
+    return rotation;
+  };
+  let found = rotation.clone();
+  let header = Mutex::new(Vec::new());
+  pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+    let Some(buffer) = info.buffer() else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let mut header = header.lock().unwrap();
+    // Only the start of the file, read in order, is of use
+    if buffer.offset() != gst::format::Buffers::OFFSET_NONE
+      && buffer.offset() != header.len() as u64
+    {
+      return gst::PadProbeReturn::Ok;
+    }
+    if let Ok(map) = buffer.map_readable() {
+      header.extend_from_slice(&map);
+    }
+    if header.len() >= 4 && header[..4] != [0x1A, 0x45, 0xDF, 0xA3] {
+      return gst::PadProbeReturn::Remove;
+    }
+    let len = header.len() as u64;
+    if let Some(rolls) = matroska_video_rolls(Cursor::new(header.as_slice()), len) {
+      *found.lock().unwrap() = rolls.first().map(|roll| roll_rotation(*roll));
+      return gst::PadProbeReturn::Remove;
+    }
+    if header.len() > MAX_SNIFF_BYTES {
+      return gst::PadProbeReturn::Remove;
+    }
+    gst::PadProbeReturn::Ok
+  });
+  rotation
+}
+
+/// Creates a `videoflip` turning frames upright: by `rotation` when known
+/// from the container, otherwise by the `image-orientation` tag of the
+/// stream. The tag is reset downstream so players do not rotate again.
+pub(crate) fn make_autorotate(rotation: Option<u32>) -> Result<Vec<gst::Element>> {
+  let flip = make_element("videoflip")?;
+  let direction = match rotation {
+    Some(90) => "90r",
+    Some(180) => "180",
+    Some(270) => "90l",
+    Some(_) => "identity",
+    None => "auto",
+  };
+  flip.set_property_from_str("video-direction", direction);
+  if let Some(pad) = flip.static_pad("src") {
+    pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, |_, info| {
+      let Some(gst::PadProbeData::Event(event)) = &mut info.data else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let gst::EventView::Tag(tag) = event.view() else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let tags = tag.tag();
+      if tags.get::<gst::tags::ImageOrientation>().is_none() {
+        return gst::PadProbeReturn::Ok;
+      }
+      let mut tags = tags.to_owned();
+      tags
+        .make_mut()
+        .add::<gst::tags::ImageOrientation>(&"rotate-0", gst::TagMergeMode::Replace);
+      *event = gst::event::Tag::new(tags);
+      gst::PadProbeReturn::Ok
+    });
+  }
+  Ok(vec![flip])
+}
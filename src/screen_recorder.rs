@@ -0,0 +1,618 @@
+//! # Screen Recorder
+//!
+//! Records the screen, optionally with a webcam picture-in-picture and the
+//! microphone, into one encoded file or live stream. It is the whole of a
+//! basic screen recording app behind a handful of options: the captured
+//! region, the frame rate and the bitrate. Outputs are files in any
+//! `TranscodeOptions` container, RTMP URLs (FLV with H.264 and AAC) or SRT
+//! URLs (MPEG-TS with H.264 and AAC).
+//!
+//! The screen is captured with `ximagesrc` on Linux, `avfvideosrc` on macOS
+//! and `d3d11screencapturesrc` on Windows; the webcam and the microphone use
+//! the system defaults unless other sources are given.
+
+use crate::adaptive_bitrate::{
+  set_encoder_bitrate, start_adapter, AdaptiveBitrateOptions, BitrateAdapter,
+};
+use crate::presets::{has_presets, preset_properties};
+use crate::recorder::RecordingStats;
+use crate::transcode::{
+  audio_codec_spec, container_spec, launch, pop_bus_error, video_codec_spec, wait_for_eos,
+};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Screen capture source of the platform
+const SCREEN_SOURCE: &str = if cfg!(target_os = "macos") {
+  "avfvideosrc capture-screen=true"
+} else if cfg!(target_os = "windows") {
+  "d3d11screencapturesrc"
+} else {
+  "ximagesrc use-damage=false"
+};
+
+/// Part of the screen to record, in pixels
+#[napi(object)]
+#[derive(Clone)]
+pub struct ScreenRegion {
+  /// Left edge
+  pub x: u32,
+  /// Top edge
+  pub y: u32,
+  /// Width of the region
+  pub width: u32,
+  /// Height of the region
+  pub height: u32,
+}
+
+/// Webcam picture-in-picture of a `ScreenRecorder`
+#[napi(object)]
+pub struct WebcamOptions {
+  /// Launch description of the camera (default: "autovideosrc")
+  pub source: Option<String>,
+  /// Width of the picture in pixels (default: 320)
+  pub width: Option<u32>,
+  /// Height of the picture in pixels (default: three quarters of the width);
+  /// the camera image is letterboxed to keep its aspect ratio
+  pub height: Option<u32>,
+  /// Corner of the picture: "top-left", "top-right", "bottom-left" or
+  /// "bottom-right" (default)
+  pub corner: Option<String>,
+  /// Distance from the edges of the screen in pixels (default: 16)
+  pub margin: Option<u32>,
+}
+
+/// Options for the `ScreenRecorder` constructor
+#[napi(object)]
+pub struct ScreenRecorderOptions {
+  /// File path, or "rtmp://", "rtmps://" or "srt://" URL to stream to
+  pub output: String,
+  /// Container of a file output (default: from the file extension)
+  pub container: Option<String>,
+  /// Launch description of the screen capture, replacing the platform's
+  pub screen: Option<String>,
+  /// Part of the screen to record (default: the whole screen)
+  pub region: Option<ScreenRegion>,
+  /// Frames per second (default: 30)
+  pub fps: Option<u32>,
+  /// Video bitrate in kbit/s (default: 4000)
+  pub video_bitrate: Option<u32>,
+  /// Video codec (default: the container's, h264 for streams)
+  pub video_codec: Option<String>,
+  /// Adds the webcam in a corner of the screen
+  pub webcam: Option<WebcamOptions>,
+  /// Launch description of the microphone, or "default" for the system
+  /// default; no audio is recorded without it
+  pub microphone: Option<String>,
+  /// Audio bitrate in kbit/s (default: the encoder's)
+  pub audio_bitrate: Option<u32>,
+}
+
+/// Where the recording goes: muxer, default codecs and sink
+struct Output {
+  muxer: String,
+  video_codec: &'static str,
+  audio_codec: &'static str,
+  sink: String,
+  /// Property of the element named "sink" receiving the target, and the target
+  location: (&'static str, String),
+  /// Whether the output is a file, whose size is reported
+  file: bool,
+}
+
+fn output(options: &ScreenRecorderOptions) -> Result<Output> {
+  let target = options.output.as_str();
+  if target.starts_with("rtmp://") || target.starts_with("rtmps://") {
+    return Ok(Output {
+      muxer: "flvmux streamable=true".to_string(),
+      video_codec: "h264",
+      audio_codec: "aac",
+      sink: "queue name=netq ! rtmp2sink name=sink".to_string(),
+      location: ("location", target.to_string()),
+      file: false,
+    });
+  }
+  if target.starts_with("srt://") {
+    return Ok(Output {
+      muxer: "mpegtsmux".to_string(),
+      video_codec: "h264",
+      audio_codec: "aac",
+      sink: "queue name=netq ! srtsink name=sink".to_string(),
+      location: ("uri", target.to_string()),
+      file: false,
+    });
+  }
+  let container = options
+    .container
+    .clone()
+    .or_else(|| {
+      Path::new(target)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot tell the container of {}", target),
+      )
+    })?;
+  let spec = container_spec(&container)?;
+  Ok(Output {
+    muxer: spec.muxer.to_string(),
+    video_codec: spec.video_codec,
+    audio_codec: spec.audio_codec,
+    sink: "filesink name=sink".to_string(),
+    location: ("location", target.to_string()),
+    file: true,
+  })
+}
+
+/// Position of the webcam picture of `size` on a screen of `screen` pixels
+fn webcam_position(corner: &str, margin: u32, size: (u32, u32), screen: (i32, i32)) -> (i32, i32) {
+  let (width, height) = (size.0 as i32, size.1 as i32);
+  let margin = margin as i32;
+  let left = margin;
+  let right = screen.0 - width - margin;
+  let top = margin;
+  let bottom = screen.1 - height - margin;
+  match corner {
+    "top-left" => (left, top),
+    "top-right" => (right, top),
+    "bottom-left" => (left, bottom),
+    _ => (right, bottom),
+  }
+}
+
+/// Screen, webcam and microphone recording into a file or stream
+///
+/// # Example
+/// ```javascript
+/// const recorder = new ScreenRecorder({
+///   output: "capture.mp4",
+///   region: { x: 0, y: 0, width: 1920, height: 1080 },
+///   fps: 30,
+///   videoBitrate: 6000,
+///   webcam: { corner: "bottom-right" },
+///   microphone: "default",
+/// });
+/// recorder.start();
+/// // ...
+/// const { duration, bytes } = recorder.stop();
+/// ```
+#[napi]
+pub struct ScreenRecorder {
+  pipeline: gst::Pipeline,
+  output_path: Option<String>,
+  frames: Arc<AtomicU32>,
+  /// Timestamp of the first and the last frame encoded
+  span: Arc<Mutex<(Option<gst::ClockTime>, Option<gst::ClockTime>)>>,
+  stopped: Mutex<bool>,
+}
+
+#[napi]
+impl ScreenRecorder {
+  /// Builds the recording pipeline; nothing is captured until `start`
+  ///
+  /// # Arguments
+  /// * `options` - Output, region, frame rate, bitrate, webcam and microphone
+  ///
+  /// # Example
+  /// ```javascript
+  /// const recorder = new ScreenRecorder({ output: "rtmp://live.example.com/app/key" });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: ScreenRecorderOptions) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let output = output(&options)?;
+    let fps = options.fps.unwrap_or(30);
+    if fps == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "fps must be positive".to_string(),
+      ));
+    }
+    if let Some(region) = &options.region {
+      if region.width == 0 || region.height == 0 {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "Region width and height must be positive".to_string(),
+        ));
+      }
+    }
+    let corner = options
+      .webcam
+      .as_ref()
+      .and_then(|webcam| webcam.corner.clone())
+      .unwrap_or_else(|| "bottom-right".to_string());
+    if !matches!(
+      corner.as_str(),
+      "top-left" | "top-right" | "bottom-left" | "bottom-right"
+    ) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported webcam corner: {}", corner),
+      ));
+    }
+
+    let video_codec = options
+      .video_codec
+      .clone()
+      .unwrap_or_else(|| output.video_codec.to_string());
+    let video_spec = video_codec_spec(&video_codec)?;
+    let mut video_properties: Vec<(String, String)> = Vec::new();
+    if let Some(property) = &video_spec.bitrate_property {
+      let kbps = options.video_bitrate.unwrap_or(4000);
+      video_properties.push((
+        property.clone(),
+        (kbps * video_spec.bitrate_scale).to_string(),
+      ));
+    }
+    // Capture runs in real time, so the encoder must keep up with it
+    if has_presets(&video_spec.encoder) {
+      let tune = (!output.file && matches!(video_spec.encoder.as_str(), "x264enc" | "x265enc"))
+        .then_some("zerolatency");
+      video_properties.extend(
+        preset_properties(&video_spec.encoder, Some("veryfast"), tune)?
+          .into_iter()
+          .map(|(property, value)| (property.to_string(), value)),
+      );
+    }
+    let video_parser = video_spec
+      .parser
+      .as_ref()
+      .map(|parser| format!(" ! {}", parser))
+      .unwrap_or_default();
+
+    let mut description = format!(
+      "compositor name=mix background=black ! video/x-raw,framerate={fps}/1 ! videoconvert ! queue \
+       ! {} name=venc{} ! queue ! {} name=mux ! {} \
+       {} ! videoconvert ! videocrop name=region ! videorate ! video/x-raw,framerate={fps}/1 ! queue ! mix.sink_0",
+      video_spec.encoder,
+      video_parser,
+      output.muxer,
+      output.sink,
+      options.screen.as_deref().unwrap_or(SCREEN_SOURCE),
+    );
+    let webcam_size = options.webcam.as_ref().map(|webcam| {
+      let width = webcam.width.unwrap_or(320);
+      (width, webcam.height.unwrap_or(width * 3 / 4))
+    });
+    if let (Some(webcam), Some((width, height))) = (&options.webcam, webcam_size) {
+      description.push_str(&format!(
+        " {} ! videoconvert ! videoscale add-borders=true ! video/x-raw,width={},height={},pixel-aspect-ratio=1/1 ! queue ! mix.sink_1",
+        webcam.source.as_deref().unwrap_or("autovideosrc"),
+        width,
+        height
+      ));
+    }
+    let mut audio_properties = Vec::new();
+    if let Some(microphone) = &options.microphone {
+      let audio_spec = audio_codec_spec(output.audio_codec)?;
+      if let (Some(property), Some(kbps)) = (&audio_spec.bitrate_property, options.audio_bitrate) {
+        audio_properties.push((
+          property.clone(),
+          (kbps * audio_spec.bitrate_scale).to_string(),
+        ));
+      }
+      description.push_str(&format!(
+        " {} ! audioconvert ! audioresample ! volume name=mic ! queue ! {} name=aenc{} ! queue ! mux.",
+        match microphone.as_str() {
+          "default" => "autoaudiosrc",
+          source => source,
+        },
+        audio_spec.encoder,
+        audio_spec
+          .parser
+          .as_ref()
+          .map(|parser| format!(" ! {}", parser))
+          .unwrap_or_default()
+      ));
+    }
+
+    let pipeline = launch(&description)?;
+    let sink = pipeline
+      .by_name("sink")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+    sink.set_property(output.location.0, &output.location.1);
+    for (name, properties) in [("venc", &video_properties), ("aenc", &audio_properties)] {
+      if let Some(encoder) = pipeline.by_name(name) {
+        for (property, value) in properties {
+          if encoder.find_property(property).is_some() {
+            encoder.set_property_from_str(property, value);
+          }
+        }
+      }
+    }
+    if let Some(muxer) = pipeline.by_name("mux") {
+      if !output.file && muxer.find_property("streamable").is_some() {
+        muxer.set_property_from_str("streamable", "true");
+      }
+    }
+
+    // The crop and the webcam position depend on the size of the screen,
+    // known once the capture negotiates its caps
+    let crop = pipeline.by_name("region");
+    let mixer = pipeline
+      .by_name("mix")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Compositor not found"))?;
+    if let Some(crop) = &crop {
+      let region = options.region.clone();
+      let webcam = webcam_size.map(|size| {
+        let margin = options
+          .webcam
+          .as_ref()
+          .and_then(|webcam| webcam.margin)
+          .unwrap_or(16);
+        (size, margin, mixer.static_pad("sink_1"))
+      });
+      let pad = crop
+        .static_pad("sink")
+        .ok_or_else(|| Error::new(Status::GenericFailure, "Crop has no input"))?;
+      let crop_element = crop.clone();
+      pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+        let Some(gst::PadProbeData::Event(event)) = &info.data else {
+          return gst::PadProbeReturn::Ok;
+        };
+        let gst::EventView::Caps(caps) = event.view() else {
+          return gst::PadProbeReturn::Ok;
+        };
+        let Some(s) = caps.caps().structure(0) else {
+          return gst::PadProbeReturn::Ok;
+        };
+        let width = s.get::<i32>("width").unwrap_or(0).max(0) as u32;
+        let height = s.get::<i32>("height").unwrap_or(0).max(0) as u32;
+        let screen = match &region {
+          Some(region) => {
+            let left = region.x.min(width);
+            let top = region.y.min(height);
+            let right = width.saturating_sub(left + region.width);
+            let bottom = height.saturating_sub(top + region.height);
+            crop_element.set_property("left", left as i32);
+            crop_element.set_property("top", top as i32);
+            crop_element.set_property("right", right as i32);
+            crop_element.set_property("bottom", bottom as i32);
+            (width - left - right, height - top - bottom)
+          }
+          None => (width, height),
+        };
+        if let Some((size, margin, Some(pad))) = &webcam {
+          let (x, y) = webcam_position(&corner, *margin, *size, (screen.0 as i32, screen.1 as i32));
+          pad.set_property("xpos", x);
+          pad.set_property("ypos", y);
+          pad.set_property("zorder", 1u32);
+        }
+        gst::PadProbeReturn::Ok
+      });
+    }
+
+    let frames = Arc::new(AtomicU32::new(0));
+    let span = Arc::new(Mutex::new((None, None)));
+    if let Some(pad) = pipeline
+      .by_name("venc")
+      .and_then(|enc| enc.static_pad("sink"))
+    {
+      let counted = frames.clone();
+      let timed = span.clone();
+      pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+        if let Some(pts) = info.buffer().and_then(|buffer| buffer.pts()) {
+          let mut span = timed.lock().unwrap();
+          span.0.get_or_insert(pts);
+          span.1 = Some(pts);
+        }
+        counted.fetch_add(1, Ordering::SeqCst);
+        gst::PadProbeReturn::Ok
+      });
+    }
+
+    Ok(ScreenRecorder {
+      pipeline,
+      output_path: output.file.then(|| options.output.clone()),
+      frames,
+      span,
+      stopped: Mutex::new(false),
+    })
+  }
+
+  /// Starts capturing and encoding
+  ///
+  /// # Example
+  /// ```javascript
+  /// recorder.start();
+  /// ```
+  #[napi]
+  pub fn start(&self) -> Result<()> {
+    if *self.stopped.lock().unwrap() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Recording already stopped".to_string(),
+      ));
+    }
+    self
+      .pipeline
+      .set_state(gst::State::Playing)
+      .map(|_| ())
+      .map_err(|e| {
+        let reason = pop_bus_error(&self.pipeline)
+          .map(|error| error.reason)
+          .unwrap_or_else(|| e.to_string());
+        let _ = self.pipeline.set_state(gst::State::Null);
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to start recording: {}", reason),
+        )
+      })
+  }
+
+  /// Number of video frames encoded so far
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.frames.load(Ordering::SeqCst)
+  }
+
+  /// Shows or hides the webcam picture
+  ///
+  /// # Arguments
+  /// * `visible` - Whether the webcam is drawn over the screen
+  #[napi]
+  pub fn set_webcam_visible(&self, visible: bool) -> Result<()> {
+    let pad = self
+      .pipeline
+      .by_name("mix")
+      .and_then(|mixer| mixer.static_pad("sink_1"))
+      .ok_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          "The recording has no webcam".to_string(),
+        )
+      })?;
+    pad.set_property("alpha", if visible { 1.0f64 } else { 0.0 });
+    Ok(())
+  }
+
+  /// Mutes or unmutes the microphone
+  ///
+  /// # Arguments
+  /// * `muted` - Whether silence is recorded instead of the microphone
+  #[napi]
+  pub fn set_microphone_muted(&self, muted: bool) -> Result<()> {
+    let volume = self.pipeline.by_name("mic").ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "The recording has no microphone".to_string(),
+      )
+    })?;
+    volume.set_property("mute", muted);
+    Ok(())
+  }
+
+  /// Changes the video bitrate while recording
+  ///
+  /// # Arguments
+  /// * `bps` - The target bitrate in bit/s
+  ///
+  /// # Returns
+  /// * `Result<f64>` - The bitrate applied, in bit/s
+  ///
+  /// # Example
+  /// ```javascript
+  /// recorder.setTargetBitrate(2_500_000);
+  /// ```
+  #[napi]
+  pub fn set_target_bitrate(&self, bps: f64) -> Result<f64> {
+    set_encoder_bitrate(&self.video_encoder()?, bps)
+  }
+
+  /// Adapts the video bitrate to the network of a live stream
+  ///
+  /// SRT outputs are adapted from their packet loss and bandwidth estimate,
+  /// and RTMP and SRT outputs from the data waiting to be sent.
+  ///
+  /// # Arguments
+  /// * `options` - Bitrate limits and congestion thresholds
+  ///
+  /// # Returns
+  /// * `Result<BitrateAdapter>` - The running adapter
+  ///
+  /// # Example
+  /// ```javascript
+  /// const adapter = recorder.adaptBitrate({ minBitrate: 800_000 });
+  /// adapter.onChange((change) => console.log(change.reason, change.bitrate));
+  /// ```
+  #[napi]
+  pub fn adapt_bitrate(&self, options: Option<AdaptiveBitrateOptions>) -> Result<BitrateAdapter> {
+    if self.output_path.is_some() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Bitrate adaptation needs a live stream output".to_string(),
+      ));
+    }
+    let mut options = options.unwrap_or(AdaptiveBitrateOptions {
+      min_bitrate: None,
+      max_bitrate: None,
+      interval_ms: None,
+      max_loss_rate: None,
+      max_queue_ms: None,
+      sink: None,
+      queue: None,
+    });
+    options.queue.get_or_insert_with(|| "netq".to_string());
+    start_adapter(&self.pipeline, self.video_encoder()?, Some(options))
+  }
+
+  /// Stops recording and finalizes the file or ends the stream
+  ///
+  /// # Returns
+  /// * `Result<RecordingStats>` - Frames, duration and, for files, size of
+  ///   the recording
+  ///
+  /// # Example
+  /// ```javascript
+  /// const { frames, duration, bytes } = recorder.stop();
+  /// ```
+  #[napi]
+  pub fn stop(&self) -> Result<RecordingStats> {
+    if std::mem::replace(&mut *self.stopped.lock().unwrap(), true) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Recording already stopped".to_string(),
+      ));
+    }
+    let (_, state, _) = self.pipeline.state(gst::ClockTime::ZERO);
+    let result = if state == gst::State::Playing {
+      self.pipeline.send_event(gst::event::Eos::new());
+      wait_for_eos(&self.pipeline)
+    } else {
+      Err(
+        pop_bus_error(&self.pipeline)
+          .unwrap_or_else(|| Error::new(Status::GenericFailure, "Recording was not started")),
+      )
+    };
+    let _ = self.pipeline.set_state(gst::State::Null);
+    result?;
+
+    let (first, last) = *self.span.lock().unwrap();
+    Ok(RecordingStats {
+      frames: self.frames.load(Ordering::SeqCst),
+      duration: match (first, last) {
+        (Some(first), Some(last)) => last.saturating_sub(first).nseconds() as i64,
+        _ => 0,
+      },
+      bytes: self
+        .output_path
+        .as_ref()
+        .and_then(|path| fs::metadata(path).ok())
+        .map_or(0, |m| m.len() as i64),
+    })
+  }
+}
+
+impl ScreenRecorder {
+  fn video_encoder(&self) -> Result<gst::Element> {
+    self
+      .pipeline
+      .by_name("venc")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Video encoder not found"))
+  }
+}
+
+impl Drop for ScreenRecorder {
+  fn drop(&mut self) {
+    let stopped = *self.stopped.lock().unwrap();
+    if !stopped {
+      let _ = self.stop();
+    }
+  }
+}
@@ -0,0 +1,252 @@
+//! # Shared-Memory Frame Transport
+//!
+//! Publishes the frames of an AppSink into a ring buffer in shared memory, so
+//! other processes (a Python inference worker, say) can read decoded video
+//! without a copy through JavaScript for every frame. On Linux the ring is a
+//! POSIX shared memory object in `/dev/shm`, which Python opens with
+//! `multiprocessing.shared_memory.SharedMemory(name)`; elsewhere it is a file
+//! in the temporary directory, to be memory-mapped by the reader.
+//!
+//! ## Layout
+//!
+//! All integers are little-endian. The file starts with a 64 byte header:
+//!
+//! | Offset | Type     | Field                                         |
+//! |--------|----------|-----------------------------------------------|
+//! | 0      | [u8; 8]  | magic `GSTSHMR1`                              |
+//! | 8      | u32      | header size (64)                              |
+//! | 12     | u32      | number of slots                               |
+//! | 16     | u64      | slot size, including the slot header          |
+//! | 24     | u64      | frames published so far                       |
+//! | 32     | u64      | frames dropped because they exceed a slot     |
+//!
+//! Slot `n` starts at `64 + n * slotSize` with a 128 byte slot header:
+//!
+//! | Offset | Type     | Field                                         |
+//! |--------|----------|-----------------------------------------------|
+//! | 0      | u64      | sequence: 0 while written, else frame number  |
+//! | 8      | u64      | payload size in bytes                         |
+//! | 16     | i64      | timestamp in nanoseconds, or -1               |
+//! | 24     | u32      | width                                         |
+//! | 28     | u32      | height                                        |
+//! | 32     | [u8; 16] | video format, NUL padded (e.g. "NV12")        |
+//! | 48     | u32      | number of planes                              |
+//! | 56     | [u32; 4] | plane strides                                 |
+//! | 72     | [u32; 4] | plane offsets within the payload              |
+//!
+//! followed by the payload. Frame number `n` (from 1) goes to slot
+//! `(n - 1) % slots`.
+//!
+//! ## Reading
+//!
+//! Slots are guarded by their sequence number: the writer zeroes it before
+//! touching a slot and stores the frame number once the slot is complete.
+//! To read the latest frame, load the published count `n`, read the sequence
+//! of its slot, copy the slot, then read the sequence again. The copy is
+//! valid if both reads equal `n`; otherwise the writer lapped the reader and
+//! it should retry with the new count.
+
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use memmap2::MmapMut;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const MAGIC: &[u8; 8] = b"GSTSHMR1";
+const HEADER_SIZE: usize = 64;
+const SLOT_HEADER_SIZE: usize = 128;
+const PUBLISHED: usize = 24;
+const DROPPED: usize = 32;
+const MAX_PLANES: usize = 4;
+
+/// Options for `publishSharedFrames`
+#[napi(object)]
+pub struct SharedFramesOptions {
+  /// Number of frames the ring holds (default: 4)
+  pub slots: Option<u32>,
+  /// Largest frame in bytes a slot can hold; bigger frames are dropped and
+  /// counted (default: 8 MiB, enough for 1080p RGBA)
+  pub max_frame_size: Option<u32>,
+}
+
+/// Where and how a frame ring was created
+#[napi(object)]
+pub struct SharedFramesInfo {
+  /// Shared memory object name, as passed to `publishSharedFrames`
+  pub name: String,
+  /// Path of the backing file, for readers that memory-map it directly
+  pub path: String,
+  /// Total size of the mapping in bytes
+  pub size: u32,
+  /// Number of slots in the ring
+  pub slots: u32,
+  /// Size of each slot in bytes, including its 128 byte header
+  pub slot_size: u32,
+  /// Size of the file header in bytes
+  pub header_size: u32,
+}
+
+/// A ring of frame slots in a shared memory mapping, removed on drop
+pub(crate) struct FrameRing {
+  map: MmapMut,
+  path: PathBuf,
+  slots: usize,
+  slot_size: usize,
+}
+
+fn shared_memory_path(name: &str) -> PathBuf {
+  let shm = PathBuf::from("/dev/shm");
+  if cfg!(target_os = "linux") && shm.is_dir() {
+    shm.join(name)
+  } else {
+    std::env::temp_dir().join(name)
+  }
+}
+
+impl FrameRing {
+  /// Creates the shared memory object `name`, replacing a stale one
+  pub(crate) fn create(name: &str, options: Option<SharedFramesOptions>) -> Result<Self> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid shared memory name: {}", name),
+      ));
+    }
+    let options = options.unwrap_or(SharedFramesOptions {
+      slots: None,
+      max_frame_size: None,
+    });
+    let slots = options.slots.unwrap_or(4).max(1) as usize;
+    let slot_size = SLOT_HEADER_SIZE + options.max_frame_size.unwrap_or(8 << 20) as usize;
+    // Keep slots 8 byte aligned for their atomic sequence numbers
+    let slot_size = slot_size.next_multiple_of(8);
+    let size = HEADER_SIZE + slots * slot_size;
+    if size > u32::MAX as usize {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "A ring of {} slots of {} bytes is too large",
+          slots, slot_size
+        ),
+      ));
+    }
+
+    let path = shared_memory_path(name);
+    let file = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .create(true)
+      .truncate(true)
+      .open(&path)
+      .and_then(|file| file.set_len(size as u64).map(|_| file))
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to create shared memory {}: {}", path.display(), e),
+        )
+      })?;
+    // SAFETY: the file was just created and sized by us; other processes
+    // only read it following the sequence protocol described above
+    let mut map = unsafe { MmapMut::map_mut(&file) }.map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to map shared memory {}: {}", path.display(), e),
+      )
+    })?;
+
+    map[..8].copy_from_slice(MAGIC);
+    map[8..12].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+    map[12..16].copy_from_slice(&(slots as u32).to_le_bytes());
+    map[16..24].copy_from_slice(&(slot_size as u64).to_le_bytes());
+    Ok(FrameRing {
+      map,
+      path,
+      slots,
+      slot_size,
+    })
+  }
+
+  pub(crate) fn info(&self, name: &str) -> SharedFramesInfo {
+    SharedFramesInfo {
+      name: name.to_string(),
+      path: self.path.to_string_lossy().into_owned(),
+      size: self.map.len() as u32,
+      slots: self.slots as u32,
+      slot_size: self.slot_size as u32,
+      header_size: HEADER_SIZE as u32,
+    }
+  }
+
+  fn counter(&mut self, offset: usize) -> &AtomicU64 {
+    // SAFETY: callers pass 8 byte aligned offsets within the page aligned
+    // mapping, which lives as long as `self`
+    unsafe { AtomicU64::from_ptr(self.map.as_mut_ptr().add(offset) as *mut u64) }
+  }
+
+  /// Copies a raw video sample into the next slot
+  pub(crate) fn publish(&mut self, sample: &gst::Sample) -> Result<()> {
+    let (Some(buffer), Some(caps)) = (sample.buffer(), sample.caps()) else {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Sample has no buffer or caps",
+      ));
+    };
+    let info = gst_video::VideoInfo::from_caps(caps).map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Sample is not raw video: {}", caps),
+      )
+    })?;
+    let map = buffer
+      .map_readable()
+      .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+    if map.len() > self.slot_size - SLOT_HEADER_SIZE {
+      self.counter(DROPPED).fetch_add(1, Ordering::Relaxed);
+      return Ok(());
+    }
+    let (offsets, strides): (Vec<usize>, Vec<i32>) = match buffer.meta::<gst_video::VideoMeta>() {
+      Some(meta) => (meta.offset().to_vec(), meta.stride().to_vec()),
+      None => (info.offset().to_vec(), info.stride().to_vec()),
+    };
+
+    let frame = self.counter(PUBLISHED).load(Ordering::Relaxed) + 1;
+    let slot = HEADER_SIZE + ((frame - 1) as usize % self.slots) * self.slot_size;
+    self.counter(slot).store(0, Ordering::Release);
+    std::sync::atomic::fence(Ordering::Release);
+
+    let header = &mut self.map[slot + 8..slot + SLOT_HEADER_SIZE];
+    header.fill(0);
+    header[0..8].copy_from_slice(&(map.len() as u64).to_le_bytes());
+    let pts = buffer.pts().map(|t| t.nseconds() as i64).unwrap_or(-1);
+    header[8..16].copy_from_slice(&pts.to_le_bytes());
+    header[16..20].copy_from_slice(&info.width().to_le_bytes());
+    header[20..24].copy_from_slice(&info.height().to_le_bytes());
+    let format = info.format().to_str().as_bytes();
+    let len = format.len().min(16);
+    header[24..24 + len].copy_from_slice(&format[..len]);
+    let planes = (info.n_planes() as usize).min(MAX_PLANES);
+    header[40..44].copy_from_slice(&(planes as u32).to_le_bytes());
+    for plane in 0..planes {
+      let at = 48 + plane * 4;
+      header[at..at + 4].copy_from_slice(&strides[plane].unsigned_abs().to_le_bytes());
+      let at = 64 + plane * 4;
+      header[at..at + 4].copy_from_slice(&(offsets[plane] as u32).to_le_bytes());
+    }
+    let payload = slot + SLOT_HEADER_SIZE;
+    self.map[payload..payload + map.len()].copy_from_slice(&map);
+
+    self.counter(slot).store(frame, Ordering::Release);
+    self.counter(PUBLISHED).store(frame, Ordering::Release);
+    Ok(())
+  }
+}
+
+impl Drop for FrameRing {
+  fn drop(&mut self) {
+    // Readers keep their mapping; only the name goes away
+    let _ = std::fs::remove_file(&self.path);
+  }
+}
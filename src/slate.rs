@@ -0,0 +1,277 @@
+//! # Slates
+//!
+//! Renders title cards, intro cards and error slates: centered text on a
+//! solid background, encoded to a short clip. Text is drawn with a font
+//! bundled in the module (DejaVu Sans Bold, see `assets/fonts/LICENSE`), so
+//! slates look the same on every machine and need no system fonts or
+//! GStreamer text plugins.
+
+use crate::benchmark::parse_resolution;
+use crate::transcode::{container_spec, launch, video_codec_spec, wait_for_eos};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use gst::prelude::*;
+use gst_app::AppSrc;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+
+pub(crate) static FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Options for `renderSlate`
+#[napi(object)]
+pub struct SlateOptions {
+  /// Text of the slate; "\n" starts a new line
+  pub text: String,
+  /// Background color as "#RRGGBB" or a name ("black", "white", "gray",
+  /// "red", "green", "blue"). Default: "black"
+  pub background: Option<String>,
+  /// Text color, in the same forms as `background` (default: "white")
+  pub color: Option<String>,
+  /// Length of the clip in seconds (default: 3)
+  pub duration_sec: Option<f64>,
+  /// Frame size as "WIDTHxHEIGHT" (default: "1280x720")
+  pub resolution: Option<String>,
+  /// Frames per second (default: 30)
+  pub fps: Option<u32>,
+  /// Text height in pixels; long lines are shrunk to fit the frame
+  /// (default: a tenth of the frame height)
+  pub font_size: Option<f64>,
+  /// Output container; defaults to the output file extension
+  pub container: Option<String>,
+  /// Video codec, defaults to the usual codec for the container
+  pub video_codec: Option<String>,
+}
+
+/// Parses "#RRGGBB", "RRGGBB" or a basic color name
+pub(crate) fn parse_color(color: &str) -> Result<[u8; 3]> {
+  let rgb = match color.to_lowercase().as_str() {
+    "black" => Some([0, 0, 0]),
+    "white" => Some([255, 255, 255]),
+    "gray" | "grey" => Some([128, 128, 128]),
+    "red" => Some([255, 0, 0]),
+    "green" => Some([0, 128, 0]),
+    "blue" => Some([0, 0, 255]),
+    hex => {
+      let hex = hex.trim_start_matches('#');
+      (hex.len() == 6)
+        .then(|| u32::from_str_radix(hex, 16).ok())
+        .flatten()
+        .map(|rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+    }
+  };
+  rgb.ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid color: {}", color)))
+}
+
+/// Width in pixels of one line of text
+pub(crate) fn line_width<F: Font>(font: &impl ScaleFont<F>, line: &str) -> f32 {
+  let mut width = 0.0;
+  let mut previous = None;
+  for c in line.chars() {
+    let glyph = font.glyph_id(c);
+    if let Some(previous) = previous {
+      width += font.kern(previous, glyph);
+    }
+    width += font.h_advance(glyph);
+    previous = Some(glyph);
+  }
+  width
+}
+
+/// Draws `text` centered on a `width`x`height` RGBx frame filled with
+/// `background`; four bytes per pixel keep rows aligned at any width
+fn render_frame(
+  text: &str,
+  width: u32,
+  height: u32,
+  font_size: f32,
+  background: [u8; 3],
+  color: [u8; 3],
+) -> Result<Vec<u8>> {
+  let font = FontRef::try_from_slice(FONT).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Invalid bundled font: {}", e),
+    )
+  })?;
+  let lines: Vec<&str> = text.lines().collect();
+
+  // Shrink the text until the widest line fits in 90% of the frame width
+  let widest = lines
+    .iter()
+    .map(|line| line_width(&font.as_scaled(PxScale::from(font_size)), line))
+    .fold(0.0, f32::max);
+  let font_size = if widest > width as f32 * 0.9 {
+    font_size * width as f32 * 0.9 / widest
+  } else {
+    font_size
+  };
+  let scaled = font.as_scaled(PxScale::from(font_size));
+  let line_height = scaled.height() + scaled.line_gap();
+
+  let [r, g, b] = background;
+  let mut pixels: Vec<u8> = [r, g, b, 255]
+    .into_iter()
+    .cycle()
+    .take(width as usize * height as usize * 4)
+    .collect();
+  let top = (height as f32 - line_height * lines.len() as f32) / 2.0;
+  for (row, line) in lines.iter().enumerate() {
+    let baseline = top + row as f32 * line_height + scaled.ascent();
+    let x = (width as f32 - line_width(&scaled, line)) / 2.0;
+    draw_line(&font, font_size, line, x, baseline, |px, py, coverage| {
+      if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+        return;
+      }
+      let at = (py as usize * width as usize + px as usize) * 4;
+      for (channel, &value) in color.iter().enumerate() {
+        let under = pixels[at + channel] as f32;
+        pixels[at + channel] = (under + (value as f32 - under) * coverage).round() as u8;
+      }
+    });
+  }
+  Ok(pixels)
+}
+
+/// Rasterizes one line of text starting at `x` on `baseline`, passing the
+/// coverage (0 to 1) of every pixel it touches to `plot`
+pub(crate) fn draw_line(
+  font: &FontRef,
+  font_size: f32,
+  line: &str,
+  mut x: f32,
+  baseline: f32,
+  mut plot: impl FnMut(i32, i32, f32),
+) {
+  let scaled = font.as_scaled(PxScale::from(font_size));
+  let mut previous = None;
+  for c in line.chars() {
+    let id = scaled.glyph_id(c);
+    if let Some(previous) = previous {
+      x += scaled.kern(previous, id);
+    }
+    let glyph = id.with_scale_and_position(font_size, ab_glyph::point(x, baseline));
+    x += scaled.h_advance(id);
+    previous = Some(id);
+    let Some(outline) = font.outline_glyph(glyph) else {
+      continue;
+    };
+    let bounds = outline.px_bounds();
+    outline.draw(|gx, gy, coverage| {
+      plot(
+        bounds.min.x as i32 + gx as i32,
+        bounds.min.y as i32 + gy as i32,
+        coverage.clamp(0.0, 1.0),
+      )
+    });
+  }
+}
+
+/// Renders a title card or slate with centered text and encodes it to a file
+///
+/// # Arguments
+/// * `output_path` - Where to write the clip
+/// * `options` - Text, colors, duration, frame size and output format
+///
+/// # Example
+/// ```javascript
+/// renderSlate("intro.mp4", {
+///   text: "Episode 12\nThe Return",
+///   background: "#1a1a2e",
+///   durationSec: 4,
+///   resolution: "1920x1080",
+/// });
+/// ```
+#[napi]
+pub fn render_slate(output_path: String, options: SlateOptions) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let (width, height) = parse_resolution(options.resolution.as_deref().unwrap_or("1280x720"))?;
+  let fps = options.fps.unwrap_or(30).max(1);
+  let duration = options.duration_sec.unwrap_or(3.0);
+  if duration <= 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Duration must be positive".to_string(),
+    ));
+  }
+  let background = parse_color(options.background.as_deref().unwrap_or("black"))?;
+  let color = parse_color(options.color.as_deref().unwrap_or("white"))?;
+  let font_size = options.font_size.unwrap_or(height as f64 / 10.0).max(1.0) as f32;
+  let container = options
+    .container
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer a container for {}", output_path),
+      )
+    })?;
+  let container = container_spec(&container)?;
+  let codec = video_codec_spec(
+    options
+      .video_codec
+      .as_deref()
+      .unwrap_or(container.video_codec),
+  )?;
+
+  let frame = render_frame(&options.text, width, height, font_size, background, color)?;
+  let parser = codec
+    .parser
+    .map(|p| format!(" ! {}", p))
+    .unwrap_or_default();
+  let pipeline = launch(&format!(
+    "appsrc name=src format=time caps=video/x-raw,format=RGBx,width={},height={},framerate={}/1 ! \
+     videoconvert ! {}{} ! queue ! {} ! filesink name=sink",
+    width, height, fps, codec.encoder, parser, container.muxer
+  ))?;
+  let sink = pipeline
+    .by_name("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+  sink.set_property("location", &output_path);
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Slate source not found"))?;
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  // Every frame shares the memory of the one rendered frame
+  let frame = gst::Buffer::from_mut_slice(frame);
+  let frames = ((duration * fps as f64).round() as u64).max(1);
+  let frame_duration = gst::ClockTime::SECOND / fps as u64;
+  let pushed = (0..frames)
+    .try_for_each(|i| {
+      let mut buffer = frame.copy();
+      let buffer_mut = buffer.make_mut();
+      buffer_mut.set_pts(frame_duration * i);
+      buffer_mut.set_duration(frame_duration);
+      appsrc.push_buffer(buffer).map(|_| ())
+    })
+    .and_then(|_| appsrc.end_of_stream().map(|_| ()));
+  let result = match pushed {
+    Ok(()) => wait_for_eos(&pipeline),
+    // A flow error means the pipeline failed; the bus has the reason
+    Err(_) => wait_for_eos(&pipeline).and(Err(Error::new(
+      Status::GenericFailure,
+      "Failed to push slate frames".to_string(),
+    ))),
+  };
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
@@ -0,0 +1,188 @@
+//! # State Change Waits
+//!
+//! `setState` calls on a pipeline usually return before the change is done:
+//! elements preroll, open devices and negotiate in their own threads, and a
+//! failure found there is only posted on the bus. The tasks here wait for a
+//! state on a worker thread and settle a promise when the pipeline reaches
+//! it, or reject it with the error posted on the bus.
+
+use crate::devices::permission_hint;
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Env, Error, Result, Status, Task};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long each state query blocks before errors are checked again
+const POLL_INTERVAL_MS: u64 = 50;
+
+/// Parses a state name as returned by `getState`, in any case
+pub(crate) fn parse_state(name: &str) -> Result<gst::State> {
+  match name.to_ascii_lowercase().as_str() {
+    "null" => Ok(gst::State::Null),
+    "ready" => Ok(gst::State::Ready),
+    "paused" => Ok(gst::State::Paused),
+    "playing" => Ok(gst::State::Playing),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!("Unknown state: {}", name),
+    )),
+  }
+}
+
+/// Error posted on the bus of a pipeline while a wait runs; stops watching
+/// when dropped
+struct ErrorWatch {
+  bus: gst::Bus,
+  handler: Option<gst::glib::SignalHandlerId>,
+  error: Arc<Mutex<Option<String>>>,
+}
+
+impl ErrorWatch {
+  /// Watches for errors posted from now on, leaving every message on the
+  /// bus for other readers
+  fn start(pipeline: &gst::Pipeline) -> Option<Self> {
+    let bus = pipeline.bus()?;
+    let error = Arc::new(Mutex::new(None));
+    let posted = error.clone();
+    bus.enable_sync_message_emission();
+    let handler = bus.connect_sync_message(Some("error"), move |_, msg| {
+      if let gst::MessageView::Error(err) = msg.view() {
+        let mut posted = posted.lock().unwrap();
+        if posted.is_none() {
+          *posted = Some(format!(
+            "{} ({})",
+            err.error(),
+            err
+              .src()
+              .map(|src| src.path_string().to_string())
+              .unwrap_or_default()
+          ));
+        }
+      }
+    });
+    Some(ErrorWatch {
+      bus,
+      handler: Some(handler),
+      error,
+    })
+  }
+
+  /// The first error posted since the watch started, if any
+  fn error(&self, pipeline: &gst::Pipeline, target: gst::State) -> Option<Error> {
+    let error = self.error.lock().unwrap().clone()?;
+    Some(Error::new(
+      Status::GenericFailure,
+      format!(
+        "Failed to set state to {:?}: {}{}",
+        target,
+        error,
+        permission_hint(pipeline)
+      ),
+    ))
+  }
+}
+
+impl Drop for ErrorWatch {
+  fn drop(&mut self) {
+    if let Some(handler) = self.handler.take() {
+      self.bus.disconnect(handler);
+      self.bus.disable_sync_message_emission();
+    }
+  }
+}
+
+/// Waits on a worker thread until a pipeline reaches a state, after
+/// optionally setting it
+pub struct StateWait {
+  pipeline: gst::Pipeline,
+  target: gst::State,
+  set: bool,
+  timeout: Option<Duration>,
+}
+
+impl StateWait {
+  /// Sets `pipeline` to `target` and waits for the change to complete
+  pub(crate) fn change(
+    pipeline: gst::Pipeline,
+    target: gst::State,
+    timeout_ms: Option<u32>,
+  ) -> Self {
+    StateWait {
+      pipeline,
+      target,
+      set: true,
+      timeout: timeout_ms.map(|ms| Duration::from_millis(ms as u64)),
+    }
+  }
+
+  /// Waits for `pipeline` to reach `target` without changing its state
+  pub(crate) fn wait(pipeline: gst::Pipeline, target: gst::State, timeout_ms: Option<u32>) -> Self {
+    StateWait {
+      set: false,
+      ..StateWait::change(pipeline, target, timeout_ms)
+    }
+  }
+}
+
+impl Task for StateWait {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<()> {
+    let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+    let watch = ErrorWatch::start(&self.pipeline);
+    let bus_error = |pipeline: &gst::Pipeline, target: gst::State| {
+      watch
+        .as_ref()
+        .and_then(|watch| watch.error(pipeline, target))
+    };
+    let failed = |pipeline: &gst::Pipeline, target: gst::State| {
+      bus_error(pipeline, target).unwrap_or_else(|| {
+        Error::new(
+          Status::GenericFailure,
+          format!(
+            "Failed to set state to {:?}{}",
+            target,
+            permission_hint(pipeline)
+          ),
+        )
+      })
+    };
+
+    if self.set && self.pipeline.set_state(self.target).is_err() {
+      return Err(failed(&self.pipeline, self.target));
+    }
+    loop {
+      if let Some(error) = bus_error(&self.pipeline, self.target) {
+        return Err(error);
+      }
+      let (result, current, pending) = self
+        .pipeline
+        .state(gst::ClockTime::from_mseconds(POLL_INTERVAL_MS));
+      if result.is_err() {
+        return Err(failed(&self.pipeline, self.target));
+      }
+      if current == self.target && pending == gst::State::VoidPending {
+        return Ok(());
+      }
+      if pending == gst::State::VoidPending {
+        // Nothing in progress to block on
+        std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+      }
+      if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        return Err(Error::new(
+          Status::GenericFailure,
+          format!(
+            "Timed out waiting for state {:?} (currently {:?})",
+            self.target, current
+          ),
+        ));
+      }
+    }
+  }
+
+  fn resolve(&mut self, _env: Env, _output: ()) -> Result<()> {
+    Ok(())
+  }
+}
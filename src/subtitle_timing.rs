@@ -0,0 +1,238 @@
+//! # Subtitle Timing
+//!
+//! Re-times sidecar SubRip (`.srt`) and WebVTT (`.vtt`) files: shifting every
+//! cue by an offset, rescaling the timeline by a factor (to follow a speed
+//! change such as 23.976 to 25 fps), and trimming it to a time range, which
+//! is how `extractClip` keeps the subtitles of a clip in step with it. Only
+//! the timing lines are rewritten; text, tags, cue settings and the WebVTT
+//! header, notes and styles are kept as they are. Cues that end up entirely
+//! before zero are removed and SubRip cues are renumbered.
+
+use crate::subtitles::parse_timestamp;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Formats milliseconds as "HH:MM:SS,mmm", or "HH:MM:SS.mmm" for WebVTT
+fn format_timestamp(ms: i64, vtt: bool) -> String {
+  let ms = ms.max(0);
+  format!(
+    "{:02}:{:02}:{:02}{}{:03}",
+    ms / 3_600_000,
+    ms / 60_000 % 60,
+    ms / 1000 % 60,
+    if vtt { '.' } else { ',' },
+    ms % 1000
+  )
+}
+
+/// Rewrites the cue timings of a SubRip or WebVTT document. `map` receives
+/// the start and end of every cue in milliseconds and returns the new ones,
+/// or `None` to remove the cue.
+fn retime(path: &str, text: &str, map: impl Fn(i64, i64) -> Option<(i64, i64)>) -> Result<String> {
+  let text = text.trim_start_matches('\u{feff}');
+  let vtt = text.starts_with("WEBVTT");
+  let invalid = |line: usize, reason: &str| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid subtitles {} (line {}): {}", path, line, reason),
+    )
+  };
+
+  let mut blocks = Vec::new();
+  let mut number = 0;
+  let mut lines = text.lines().map(str::trim_end).enumerate().peekable();
+  loop {
+    while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+    let mut block = Vec::new();
+    while let Some(line) = lines.next_if(|(_, line)| !line.trim().is_empty()) {
+      block.push(line);
+    }
+    if block.is_empty() {
+      break;
+    }
+    // Headers, notes and styles have no timing line
+    let Some(timing) = block.iter().position(|(_, line)| line.contains("-->")) else {
+      blocks.push(block.iter().map(|(_, line)| line.to_string()).collect());
+      continue;
+    };
+    let (index, line) = block[timing];
+    let (start, rest) = line.split_once("-->").unwrap_or_default();
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let (start, end) = parse_timestamp(start)
+      .zip(parse_timestamp(end))
+      .ok_or_else(|| invalid(index + 1, "invalid timestamp"))?;
+    let Some((start, end)) = map(start.mseconds() as i64, end.mseconds() as i64) else {
+      continue;
+    };
+
+    let mut lines = Vec::new();
+    for (position, (_, line)) in block.iter().enumerate() {
+      if position == timing {
+        let settings = settings.trim();
+        lines.push(format!(
+          "{} --> {}{}{}",
+          format_timestamp(start, vtt),
+          format_timestamp(end, vtt),
+          if settings.is_empty() { "" } else { " " },
+          settings
+        ));
+      } else if !vtt && position + 1 == timing && line.trim().parse::<u64>().is_ok() {
+        number += 1;
+        lines.push(number.to_string());
+      } else {
+        lines.push(line.to_string());
+      }
+    }
+    blocks.push(lines);
+  }
+  let mut output = blocks
+    .iter()
+    .map(|lines| lines.join("\n"))
+    .collect::<Vec<_>>()
+    .join("\n\n");
+  output.push('\n');
+  Ok(output)
+}
+
+/// Reads a subtitle file, re-times it with `map` and writes the result to
+/// `output_path` when given
+fn retime_file(
+  path: &str,
+  output_path: Option<&Path>,
+  map: impl Fn(i64, i64) -> Option<(i64, i64)>,
+) -> Result<String> {
+  let text = fs::read_to_string(path).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read subtitles {}: {}", path, e),
+    )
+  })?;
+  let retimed = retime(path, &text, map)?;
+  if let Some(output_path) = output_path {
+    fs::write(output_path, &retimed).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write {}: {}", output_path.display(), e),
+      )
+    })?;
+  }
+  Ok(retimed)
+}
+
+/// Trims a subtitle file to `[start_ms, end_ms)`, moving the start of the
+/// range to zero, and writes it to `output_path`
+pub(crate) fn trim_subtitles(
+  path: &str,
+  output_path: &Path,
+  start_ms: i64,
+  end_ms: i64,
+) -> Result<()> {
+  retime_file(path, Some(output_path), |start, end| {
+    (end > start_ms && start < end_ms)
+      .then(|| (start.max(start_ms) - start_ms, end.min(end_ms) - start_ms))
+  })
+  .map(|_| ())
+}
+
+/// Path of the subtitles of a clip: the clip path with the extension of the
+/// subtitle file, keeping a language tag such as "en" or "pt-BR" that
+/// precedes it ("movie.en.srt" becomes "clip.en.srt")
+pub(crate) fn clip_subtitle_path(clip_path: &str, subtitle_path: &str) -> PathBuf {
+  let subtitle = Path::new(subtitle_path);
+  let extension = subtitle
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("srt");
+  let language = subtitle
+    .file_stem()
+    .map(Path::new)
+    .and_then(|stem| stem.extension())
+    .and_then(|tag| tag.to_str())
+    .filter(|tag| {
+      (2..=5).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+    });
+  let clip = Path::new(clip_path);
+  let stem = clip
+    .file_stem()
+    .map(|stem| stem.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  clip.with_file_name(match language {
+    Some(language) => format!("{}.{}.{}", stem, language, extension),
+    None => format!("{}.{}", stem, extension),
+  })
+}
+
+/// Shifts every cue of a SubRip or WebVTT file by an offset
+///
+/// Cues moved entirely before zero are removed, and cues moved partly
+/// before zero start at zero.
+///
+/// # Arguments
+/// * `path` - The subtitle file
+/// * `offset_ms` - Milliseconds added to every timestamp, negative to show
+///   the subtitles earlier
+/// * `output_path` - Where to write the result, which may be `path` itself;
+///   nothing is written without it
+///
+/// # Returns
+/// * `Result<String>` - The re-timed subtitles
+///
+/// # Example
+/// ```javascript
+/// // The subtitles show 1.5 s too late
+/// shiftSubtitles("movie.en.srt", -1500, "movie.en.srt");
+/// ```
+#[napi]
+pub fn shift_subtitles(
+  path: String,
+  offset_ms: f64,
+  output_path: Option<String>,
+) -> Result<String> {
+  if !offset_ms.is_finite() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid offset: {}", offset_ms),
+    ));
+  }
+  let offset = offset_ms.round() as i64;
+  retime_file(
+    &path,
+    output_path.as_deref().map(Path::new),
+    |start, end| (end + offset > 0).then(|| ((start + offset).max(0), end + offset)),
+  )
+}
+
+/// Multiplies every timestamp of a SubRip or WebVTT file by a factor
+///
+/// # Arguments
+/// * `path` - The subtitle file
+/// * `factor` - Timeline scale; subtitles timed for 23.976 fps video that was
+///   sped up to 25 fps need `23.976 / 25`
+/// * `output_path` - Where to write the result, which may be `path` itself;
+///   nothing is written without it
+///
+/// # Returns
+/// * `Result<String>` - The re-timed subtitles
+///
+/// # Example
+/// ```javascript
+/// rescaleSubtitles("film.srt", 24000 / 1001 / 25, "film-pal.srt");
+/// ```
+#[napi]
+pub fn rescale_subtitles(path: String, factor: f64, output_path: Option<String>) -> Result<String> {
+  if !factor.is_finite() || factor <= 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid factor: {}", factor),
+    ));
+  }
+  let scale = |ms: i64| (ms as f64 * factor).round() as i64;
+  retime_file(
+    &path,
+    output_path.as_deref().map(Path::new),
+    |start, end| Some((scale(start), scale(end))),
+  )
+}
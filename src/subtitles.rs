@@ -0,0 +1,346 @@
+//! # Subtitles
+//!
+//! Reads SubRip (`.srt`) files and draws their cues into video frames, which
+//! is how the `subtitles` video filter burns them in. Text is set in the font
+//! bundled for slates, white with a black outline, wrapped to the frame width
+//! and placed at the bottom center unless a cue starts with an `{\anN}`
+//! position tag (numeric keypad layout: 8 is top center, 7 top left...).
+//! Formatting tags such as `<i>` are ignored.
+
+use crate::slate::{draw_line, line_width, FONT};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+/// One subtitle shown from `start` until `end`
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Cue {
+  start: gst::ClockTime,
+  end: gst::ClockTime,
+  text: String,
+  /// Numeric keypad position, 1 to 9
+  alignment: u8,
+}
+
+/// Parses an SRT timestamp, "HH:MM:SS,mmm" (a "." is accepted for the comma,
+/// and the hours may be left out as in WebVTT)
+pub(crate) fn parse_timestamp(value: &str) -> Option<gst::ClockTime> {
+  let (clock, millis) = value.trim().split_once([',', '.'])?;
+  let parts: Vec<u64> = clock
+    .split(':')
+    .map(|part| part.parse().ok())
+    .collect::<Option<_>>()?;
+  let (hours, minutes, seconds) = match parts[..] {
+    [hours, minutes, seconds] => (hours, minutes, seconds),
+    [minutes, seconds] => (0, minutes, seconds),
+    _ => return None,
+  };
+  let millis: u64 = millis.parse().ok()?;
+  (minutes < 60 && seconds < 60 && millis < 1000)
+    .then(|| gst::ClockTime::from_mseconds(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis))
+}
+
+/// Removes `<...>` and `{...}` tags, returning the text and the position of
+/// the first `{\anN}` tag (bottom center by default)
+fn strip_tags(text: &str) -> (String, u8) {
+  let mut alignment = 2;
+  let mut clean = String::new();
+  let mut rest = text;
+  while let Some(open) = rest.find(['<', '{']) {
+    clean.push_str(&rest[..open]);
+    let close = if rest[open..].starts_with('<') {
+      '>'
+    } else {
+      '}'
+    };
+    let Some(length) = rest[open..].find(close) else {
+      rest = &rest[open..];
+      break;
+    };
+    let tag = &rest[open + 1..open + length];
+    if let Some(n) = tag.strip_prefix("\\an").and_then(|n| n.parse().ok()) {
+      if (1..=9).contains(&n) {
+        alignment = n;
+      }
+    }
+    rest = &rest[open + length + 1..];
+  }
+  clean.push_str(rest);
+  (clean, alignment)
+}
+
+/// Reads the cues of a SubRip file
+pub(crate) fn parse_srt(path: &str) -> Result<Vec<Cue>> {
+  let text = fs::read_to_string(path).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read subtitles {}: {}", path, e),
+    )
+  })?;
+  let invalid = |line: usize, reason: &str| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid subtitles {} (line {}): {}", path, line, reason),
+    )
+  };
+
+  let mut cues = Vec::new();
+  let mut lines = text
+    .trim_start_matches('\u{feff}')
+    .lines()
+    .map(str::trim_end)
+    .enumerate()
+    .peekable();
+  loop {
+    while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+    let Some((mut index, mut line)) = lines.next() else {
+      break;
+    };
+    // The cue number is optional in practice
+    if !line.contains("-->") {
+      (index, line) = lines
+        .next()
+        .ok_or_else(|| invalid(index + 1, "missing cue timing"))?;
+    }
+    let (start, end) = line
+      .split_once("-->")
+      .ok_or_else(|| invalid(index + 1, "expected \"start --> end\""))?;
+    // Cue settings from WebVTT-style files may follow the end time
+    let end = end.split_whitespace().next().unwrap_or_default();
+    let (start, end) = parse_timestamp(start)
+      .zip(parse_timestamp(end))
+      .ok_or_else(|| invalid(index + 1, "invalid timestamp"))?;
+    let mut text = Vec::new();
+    while let Some((_, line)) = lines.next_if(|(_, line)| !line.trim().is_empty()) {
+      text.push(line);
+    }
+    let (text, alignment) = strip_tags(&text.join("\n"));
+    if end > start && !text.trim().is_empty() {
+      cues.push(Cue {
+        start,
+        end,
+        text,
+        alignment,
+      });
+    }
+  }
+  Ok(cues)
+}
+
+/// Splits a line into lines no wider than `max_width`, breaking at spaces
+fn wrap<F: Font>(font: &impl ScaleFont<F>, line: &str, max_width: f32) -> Vec<String> {
+  let mut lines = Vec::new();
+  let mut current = String::new();
+  for word in line.split_whitespace() {
+    let candidate = if current.is_empty() {
+      word.to_string()
+    } else {
+      format!("{} {}", current, word)
+    };
+    if !current.is_empty() && line_width(font, &candidate) > max_width {
+      lines.push(std::mem::replace(&mut current, word.to_string()));
+    } else {
+      current = candidate;
+    }
+  }
+  if !current.is_empty() {
+    lines.push(current);
+  }
+  lines
+}
+
+/// The rendered text of a cue: coverage of the glyphs and of their outline
+struct Mask {
+  width: usize,
+  height: usize,
+  /// Transparent border around the text, taken by the outline
+  padding: usize,
+  text: Vec<u8>,
+  outline: Vec<u8>,
+}
+
+/// Renders a cue for a `width`x`height` frame
+fn render_mask(cue: &Cue, width: usize, height: usize) -> Option<Mask> {
+  let font = FontRef::try_from_slice(FONT).ok()?;
+  let font_size = (height as f32 / 18.0).max(10.0);
+  let scaled = font.as_scaled(PxScale::from(font_size));
+  let lines: Vec<String> = cue
+    .text
+    .lines()
+    .flat_map(|line| wrap(&scaled, line, width as f32 * 0.9))
+    .collect();
+  if lines.is_empty() {
+    return None;
+  }
+  let widths: Vec<f32> = lines.iter().map(|line| line_width(&scaled, line)).collect();
+  let text_width = widths.iter().copied().fold(0.0, f32::max);
+  let line_height = scaled.height() + scaled.line_gap();
+  let radius = (font_size / 14.0).ceil() as usize;
+  let padding = radius + 1;
+  let mask_width = text_width.ceil() as usize + padding * 2;
+  let mask_height = (line_height * lines.len() as f32).ceil() as usize + padding * 2;
+
+  let mut text = vec![0u8; mask_width * mask_height];
+  for (row, (line, line_width)) in lines.iter().zip(&widths).enumerate() {
+    let x = padding as f32
+      + match cue.alignment % 3 {
+        1 => 0.0,
+        0 => text_width - line_width,
+        _ => (text_width - line_width) / 2.0,
+      };
+    let baseline = padding as f32 + row as f32 * line_height + scaled.ascent();
+    draw_line(&font, font_size, line, x, baseline, |px, py, coverage| {
+      if px < 0 || py < 0 || px as usize >= mask_width || py as usize >= mask_height {
+        return;
+      }
+      let at = py as usize * mask_width + px as usize;
+      text[at] = text[at].max((coverage * 255.0).round() as u8);
+    });
+  }
+
+  // The outline is the text grown by `radius` pixels in every direction
+  let radius = radius as isize;
+  let mut outline = vec![0u8; text.len()];
+  for y in 0..mask_height as isize {
+    for x in 0..mask_width as isize {
+      let mut max = 0;
+      for dy in -radius..=radius {
+        for dx in -radius..=radius {
+          let (sx, sy) = (x + dx, y + dy);
+          if dx * dx + dy * dy > radius * radius
+            || sx < 0
+            || sy < 0
+            || sx >= mask_width as isize
+            || sy >= mask_height as isize
+          {
+            continue;
+          }
+          max = max.max(text[sy as usize * mask_width + sx as usize]);
+        }
+      }
+      outline[y as usize * mask_width + x as usize] = max;
+    }
+  }
+  Some(Mask {
+    width: mask_width,
+    height: mask_height,
+    padding,
+    text,
+    outline,
+  })
+}
+
+/// Draws the cues of a subtitle file into RGBA frames
+pub(crate) struct SubtitleRenderer {
+  cues: Arc<Vec<Cue>>,
+  /// Masks of the cues on screen, by cue index and frame size
+  masks: Mutex<HashMap<(usize, usize, usize), Arc<Mask>>>,
+}
+
+impl SubtitleRenderer {
+  pub(crate) fn new(cues: Arc<Vec<Cue>>) -> Self {
+    SubtitleRenderer {
+      cues,
+      masks: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Draws the cues shown at `position` into an RGBA frame
+  pub(crate) fn draw(
+    &self,
+    data: &mut [u8],
+    stride: usize,
+    width: usize,
+    height: usize,
+    position: gst::ClockTime,
+  ) {
+    let active: Vec<usize> = (0..self.cues.len())
+      .filter(|&i| self.cues[i].start <= position && position < self.cues[i].end)
+      .collect();
+    let masks: Vec<(usize, Arc<Mask>)> = {
+      let Ok(mut cache) = self.masks.lock() else {
+        return;
+      };
+      cache.retain(|(index, w, h), _| active.contains(index) && *w == width && *h == height);
+      active
+        .iter()
+        .filter_map(|&index| {
+          let mask = match cache.get(&(index, width, height)) {
+            Some(mask) => mask.clone(),
+            None => {
+              let mask = Arc::new(render_mask(&self.cues[index], width, height)?);
+              cache.insert((index, width, height), mask.clone());
+              mask
+            }
+          };
+          Some((index, mask))
+        })
+        .collect()
+    };
+
+    let (margin_x, margin_y) = (width / 20, height / 20);
+    // Space taken by earlier cues at the bottom and at the top
+    let (mut bottom, mut top) = (0, 0);
+    for (index, mask) in masks {
+      let alignment = self.cues[index].alignment;
+      let x = match alignment % 3 {
+        1 => margin_x as isize - mask.padding as isize,
+        0 => (width - margin_x + mask.padding) as isize - mask.width as isize,
+        _ => (width as isize - mask.width as isize) / 2,
+      };
+      let y = match alignment {
+        1..=3 => {
+          bottom += mask.height;
+          (height - margin_y) as isize - bottom as isize
+        }
+        7..=9 => {
+          top += mask.height;
+          (margin_y + top - mask.height) as isize
+        }
+        _ => (height as isize - mask.height as isize) / 2,
+      };
+      composite(data, stride, width, height, &mask, x, y);
+    }
+  }
+}
+
+/// Blends black through the outline and then white through the text of
+/// `mask`, placed at `x`, `y`
+fn composite(
+  data: &mut [u8],
+  stride: usize,
+  width: usize,
+  height: usize,
+  mask: &Mask,
+  x: isize,
+  y: isize,
+) {
+  for my in 0..mask.height {
+    let fy = y + my as isize;
+    if fy < 0 || fy >= height as isize {
+      continue;
+    }
+    for mx in 0..mask.width {
+      let fx = x + mx as isize;
+      if fx < 0 || fx >= width as isize {
+        continue;
+      }
+      let (outline, text) = (
+        mask.outline[my * mask.width + mx] as u32,
+        mask.text[my * mask.width + mx] as u32,
+      );
+      if outline == 0 {
+        continue;
+      }
+      let at = fy as usize * stride + fx as usize * 4;
+      for channel in &mut data[at..at + 3] {
+        let dark = *channel as u32 * (255 - outline) / 255;
+        *channel = (dark + (255 - dark) * text / 255) as u8;
+      }
+      data[at + 3] = data[at + 3].max(outline as u8);
+    }
+  }
+}
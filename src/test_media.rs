@@ -0,0 +1,137 @@
+//! # Test Media
+//!
+//! Generator for synthetic test clips, so JavaScript users can create fixtures
+//! for their own tests without shipping media files.
+
+use crate::transcode::{audio_codec_spec, container_spec, launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// Options for `generateTestMedia`
+#[napi(object)]
+pub struct TestMediaOptions {
+  /// Output format: a transcode container ("webm", "mkv", "mp4", ...) or "y4m" for raw video
+  pub format: String,
+  /// Picture pattern ("smpte", "gradient", "noise" or any videotestsrc pattern name). Default: "smpte"
+  pub pattern: Option<String>,
+  /// Frame width in pixels (default: 320)
+  pub width: Option<u32>,
+  /// Frame height in pixels (default: 240)
+  pub height: Option<u32>,
+  /// Frames per second (default: 30)
+  pub fps: Option<u32>,
+  /// Clip duration in seconds (default: 1)
+  pub duration: Option<f64>,
+  /// Video codec, defaults to the usual codec for the container
+  pub video_codec: Option<String>,
+  /// Adds a sine tone audio track (default: false)
+  pub audio: Option<bool>,
+}
+
+/// Writes a synthetic test clip to a file
+///
+/// # Arguments
+/// * `output_path` - Where to write the clip
+/// * `options` - Format, pattern, size, frame rate and duration of the clip
+///
+/// # Example
+/// ```javascript
+/// generateTestMedia("fixture.webm", { format: "webm", pattern: "gradient", width: 640, height: 360, duration: 2 });
+/// ```
+#[napi]
+pub fn generate_test_media(output_path: String, options: TestMediaOptions) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let width = options.width.unwrap_or(320);
+  let height = options.height.unwrap_or(240);
+  let fps = options.fps.unwrap_or(30).max(1);
+  let duration = options.duration.unwrap_or(1.0);
+  if width == 0 || height == 0 || duration <= 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Width, height and duration must be positive".to_string(),
+    ));
+  }
+  let frames = ((duration * fps as f64).round() as u32).max(1);
+  let pattern = match options.pattern.as_deref().unwrap_or("smpte") {
+    "noise" => "snow",
+    pattern => pattern,
+  };
+
+  let video = format!(
+    "videotestsrc name=src num-buffers={} ! video/x-raw,width={},height={},framerate={}/1 ! videoconvert",
+    frames, width, height, fps
+  );
+  let description = if options.format == "y4m" {
+    format!("{} ! y4menc ! filesink name=sink", video)
+  } else {
+    let container = container_spec(&options.format)?;
+    let codec = video_codec_spec(
+      options
+        .video_codec
+        .as_deref()
+        .unwrap_or(container.video_codec),
+    )?;
+    let parser = codec
+      .parser
+      .map(|p| format!(" ! {}", p))
+      .unwrap_or_default();
+    let mut description = format!(
+      "{} ! {}{} ! queue ! {} name=mux ! filesink name=sink",
+      video, codec.encoder, parser, container.muxer
+    );
+    if options.audio.unwrap_or(false) {
+      let audio = audio_codec_spec(container.audio_codec)?;
+      let parser = audio
+        .parser
+        .map(|p| format!(" ! {}", p))
+        .unwrap_or_default();
+      let buffers = (duration * 48000.0 / 1024.0).ceil() as u32;
+      description.push_str(&format!(
+        " audiotestsrc wave=sine samplesperbuffer=1024 num-buffers={} ! audio/x-raw,rate=48000,channels=2 ! audioconvert ! audioresample ! {}{} ! queue ! mux.",
+        buffers, audio.encoder, parser
+      ));
+    }
+    description
+  };
+
+  let pipeline = launch(&description)?;
+  let source = pipeline
+    .by_name("src")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Test source not found"))?;
+  let known = source
+    .find_property("pattern")
+    .and_then(|pspec| pspec.downcast::<gst::glib::ParamSpecEnum>().ok())
+    .is_some_and(|pspec| {
+      let patterns = pspec.enum_class();
+      patterns.value_by_nick(pattern).is_some() || patterns.value_by_name(pattern).is_some()
+    });
+  if !known {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Unknown test pattern: {}", pattern),
+    ));
+  }
+  source.set_property_from_str("pattern", pattern);
+  let sink = pipeline
+    .by_name("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+  sink.set_property("location", &output_path);
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
@@ -0,0 +1,747 @@
+//! # Timed Metadata
+//!
+//! Metadata tied to moments of a stream, such as the position and attitude
+//! of a drone camera, carried inside encoded media so that it survives
+//! muxing, transport and demuxing. Two carriers are supported:
+//!
+//! - KLV packets (SMPTE 336) in their own MPEG-TS stream, pushed into an
+//!   AppSrc linked to `mpegtsmux` and pulled from the `meta/x-klv` pad of
+//!   `tsdemux`, each with its presentation time
+//! - SEI user data (unregistered SEI messages, identified by a UUID) inside
+//!   H.264 and H.265 access units, inserted after an encoder and read back
+//!   after a parser, whatever the container
+
+use crate::bitstream::{nal_units, syntax, Framing, Syntax};
+use crate::kit::GstKit;
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Largest metadata pushed at once, in bytes
+const MAX_METADATA_SIZE: usize = 64 * 1024;
+/// Extracted messages kept for `next`; older ones are dropped
+const MAX_QUEUED: usize = 256;
+/// UUID of SEI messages when none is given: "gstkit-metadata1"
+const DEFAULT_UUID: [u8; 16] = *b"gstkit-metadata1";
+
+/// Metadata read from a stream
+#[napi(object)]
+pub struct TimedMetadata {
+  /// Presentation time of the metadata (KLV) or of the frame carrying it
+  /// (SEI) in nanoseconds, or -1 if unknown
+  pub timestamp: i64,
+  /// The metadata
+  pub data: Buffer,
+  /// UUID of the SEI message, as "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+  /// not set for KLV
+  pub uuid: Option<String>,
+}
+
+/// A KLV packet
+#[napi(object)]
+pub struct KlvPacket {
+  /// The 16-byte universal label
+  pub key: Buffer,
+  /// The value
+  pub value: Buffer,
+}
+
+/// Options for `injectSei`
+#[napi(object)]
+pub struct SeiInjectorOptions {
+  /// UUID identifying the messages (default: the crate's own UUID)
+  pub uuid: Option<String>,
+}
+
+/// Options for `extractSei`
+#[napi(object)]
+pub struct SeiExtractorOptions {
+  /// Only read messages with this UUID (default: all user data messages)
+  pub uuid: Option<String>,
+}
+
+/// Callback receiving extracted metadata
+type MetadataCallback = ThreadsafeFunction<TimedMetadata, (), TimedMetadata, Status, false, true>;
+
+/// Parses a UUID given as 32 hex digits, optionally split by dashes
+fn parse_uuid(uuid: &str) -> Result<[u8; 16]> {
+  let digits: Vec<u8> = uuid.bytes().filter(|&b| b != b'-').collect();
+  let invalid = || Error::new(Status::InvalidArg, format!("Invalid UUID: {}", uuid));
+  if digits.len() != 32 {
+    return Err(invalid());
+  }
+  let mut bytes = [0u8; 16];
+  for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+    let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+    *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+  }
+  Ok(bytes)
+}
+
+fn format_uuid(uuid: &[u8]) -> String {
+  let hex: String = uuid.iter().map(|b| format!("{:02x}", b)).collect();
+  format!(
+    "{}-{}-{}-{}-{}",
+    &hex[..8],
+    &hex[8..12],
+    &hex[12..16],
+    &hex[16..20],
+    &hex[20..]
+  )
+}
+
+fn metadata_bytes(data: Either<Buffer, String>) -> Result<Vec<u8>> {
+  let data = match data {
+    Either::A(bytes) => bytes.to_vec(),
+    Either::B(text) => text.into_bytes(),
+  };
+  if data.len() > MAX_METADATA_SIZE {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Metadata is {} bytes, more than the {} allowed",
+        data.len(),
+        MAX_METADATA_SIZE
+      ),
+    ));
+  }
+  Ok(data)
+}
+
+fn timestamp(time: Option<gst::ClockTime>) -> i64 {
+  time.map(|t| t.nseconds() as i64).unwrap_or(-1)
+}
+
+/// Encodes a KLV packet, with the length in BER form
+///
+/// # Arguments
+/// * `key` - The 16-byte universal label, e.g. the UAS Datalink Local Set key of MISB ST 0601
+/// * `value` - The value
+///
+/// # Returns
+/// * `Result<Buffer>` - The packet
+///
+/// # Example
+/// ```javascript
+/// const key = Buffer.from("060e2b34020b01010e01030101000000", "hex");
+/// kit.pushTimedMetadata("klv", encodeKlv(key, localSet), frameTime);
+/// ```
+#[napi]
+pub fn encode_klv(key: Buffer, value: Buffer) -> Result<Buffer> {
+  if key.len() != 16 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("KLV keys are 16 bytes, got {}", key.len()),
+    ));
+  }
+  let mut packet = key.to_vec();
+  let len = value.len();
+  if len < 0x80 {
+    packet.push(len as u8);
+  } else {
+    let bytes: Vec<u8> = len
+      .to_be_bytes()
+      .into_iter()
+      .skip_while(|&b| b == 0)
+      .collect();
+    packet.push(0x80 | bytes.len() as u8);
+    packet.extend(bytes);
+  }
+  packet.extend_from_slice(&value);
+  Ok(packet.into())
+}
+
+/// Splits data into its KLV packets
+///
+/// # Arguments
+/// * `data` - One or more KLV packets, e.g. from `pullTimedMetadata`
+///
+/// # Returns
+/// * `Result<Vec<KlvPacket>>` - The packets, in order
+///
+/// # Example
+/// ```javascript
+/// const metadata = kit.pullTimedMetadata("klv");
+/// if (metadata) for (const { key, value } of parseKlv(metadata.data)) console.log(key.toString("hex"), value.length);
+/// ```
+#[napi]
+pub fn parse_klv(data: Buffer) -> Result<Vec<KlvPacket>> {
+  let truncated = || Error::new(Status::InvalidArg, "Truncated KLV packet".to_string());
+  let mut packets = Vec::new();
+  let mut rest: &[u8] = &data;
+  while !rest.is_empty() {
+    if rest.len() < 17 {
+      return Err(truncated());
+    }
+    let (key, after_key) = rest.split_at(16);
+    let (len, header) = match after_key[0] {
+      short if short < 0x80 => (short as usize, 1),
+      long => {
+        let size = (long & 0x7f) as usize;
+        if size == 0 || size > std::mem::size_of::<usize>() || after_key.len() < 1 + size {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "Invalid KLV length".to_string(),
+          ));
+        }
+        let len = after_key[1..=size]
+          .iter()
+          .fold(0usize, |len, &b| (len << 8) | b as usize);
+        (len, 1 + size)
+      }
+    };
+    let value = after_key[header..].get(..len).ok_or_else(truncated)?;
+    packets.push(KlvPacket {
+      key: key.to_vec().into(),
+      value: value.to_vec().into(),
+    });
+    rest = &after_key[header + len..];
+  }
+  Ok(packets)
+}
+
+/// Pushes metadata at `timestamp_ns` into an AppSrc
+pub(crate) fn push(appsrc: &AppSrc, data: Either<Buffer, String>, timestamp_ns: i64) -> Result<()> {
+  let mut buffer = gst::Buffer::from_mut_slice(metadata_bytes(data)?);
+  buffer
+    .get_mut()
+    .unwrap()
+    .set_pts(gst::ClockTime::from_nseconds(timestamp_ns.max(0) as u64));
+  appsrc.push_buffer(buffer).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to push metadata: {}", e),
+    )
+  })?;
+  Ok(())
+}
+
+/// Pulls metadata with its presentation time from an AppSink
+pub(crate) fn pull(appsink: &AppSink, timeout_ms: u32) -> Result<Option<TimedMetadata>> {
+  let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_mseconds(timeout_ms as u64))
+  else {
+    return Ok(None);
+  };
+  let buffer = sample
+    .buffer()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+  let map = buffer
+    .map_readable()
+    .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+  Ok(Some(TimedMetadata {
+    timestamp: timestamp(buffer.pts()),
+    data: map.as_slice().to_vec().into(),
+    uuid: None,
+  }))
+}
+
+/// NAL unit type, and whether it is a slice of a picture
+fn nal_type(nal: &[u8], h265: bool) -> (u8, bool) {
+  let header = nal.first().copied().unwrap_or(0);
+  if h265 {
+    let kind = (header >> 1) & 0x3f;
+    (kind, kind < 32)
+  } else {
+    let kind = header & 0x1f;
+    (kind, (1..=5).contains(&kind))
+  }
+}
+
+/// Inserts emulation prevention bytes into an RBSP
+fn escape(rbsp: &[u8]) -> Vec<u8> {
+  let mut escaped = Vec::with_capacity(rbsp.len() + rbsp.len() / 64);
+  let mut zeros = 0;
+  for &byte in rbsp {
+    if zeros >= 2 && byte <= 3 {
+      escaped.push(3);
+      zeros = 0;
+    }
+    escaped.push(byte);
+    zeros = if byte == 0 { zeros + 1 } else { 0 };
+  }
+  escaped
+}
+
+/// Removes emulation prevention bytes from a NAL unit payload
+fn unescape(payload: &[u8]) -> Vec<u8> {
+  let mut rbsp = Vec::with_capacity(payload.len());
+  let mut zeros = 0;
+  for &byte in payload {
+    if zeros >= 2 && byte == 3 {
+      zeros = 0;
+      continue;
+    }
+    rbsp.push(byte);
+    zeros = if byte == 0 { zeros + 1 } else { 0 };
+  }
+  rbsp
+}
+
+/// Writes a payload type or size of an SEI message
+fn push_sei_value(rbsp: &mut Vec<u8>, mut value: usize) {
+  while value >= 255 {
+    rbsp.push(255);
+    value -= 255;
+  }
+  rbsp.push(value as u8);
+}
+
+/// Builds an SEI NAL unit of user data messages, without start code or
+/// length prefix
+fn sei_nal(uuid: &[u8; 16], messages: &[Vec<u8>], h265: bool) -> Vec<u8> {
+  let mut rbsp = Vec::new();
+  for message in messages {
+    push_sei_value(&mut rbsp, 5);
+    push_sei_value(&mut rbsp, 16 + message.len());
+    rbsp.extend_from_slice(uuid);
+    rbsp.extend_from_slice(message);
+  }
+  rbsp.push(0x80);
+  // Prefix SEI in H.265, with layer 0 and temporal id 0 (nuh_temporal_id_plus1 = 1)
+  let mut nal = if h265 { vec![39 << 1, 1] } else { vec![6] };
+  nal.extend(escape(&rbsp));
+  nal
+}
+
+/// Reads the user data messages of an SEI NAL unit as (UUID, data)
+fn sei_messages(nal: &[u8], h265: bool) -> Vec<([u8; 16], Vec<u8>)> {
+  let header = if h265 { 2 } else { 1 };
+  let rbsp = unescape(nal.get(header..).unwrap_or_default());
+  let mut messages = Vec::new();
+  let mut pos = 0;
+  let read_value = |pos: &mut usize| -> Option<usize> {
+    let mut value = 0;
+    loop {
+      let byte = *rbsp.get(*pos)?;
+      *pos += 1;
+      value += byte as usize;
+      if byte != 255 {
+        return Some(value);
+      }
+    }
+  };
+  // Stop at the trailing bits
+  while pos + 1 < rbsp.len() {
+    let (Some(kind), Some(size)) = (read_value(&mut pos), read_value(&mut pos)) else {
+      break;
+    };
+    let Some(payload) = rbsp.get(pos..pos + size) else {
+      break;
+    };
+    pos += size;
+    if kind == 5 && size >= 16 {
+      let mut uuid = [0u8; 16];
+      uuid.copy_from_slice(&payload[..16]);
+      messages.push((uuid, payload[16..].to_vec()));
+    }
+  }
+  messages
+}
+
+/// Start of the unit at `offset` of an access unit, including its start
+/// code or length prefix
+fn unit_start(data: &[u8], offset: usize, framing: Framing) -> usize {
+  match framing {
+    Framing::Length(size) => offset - size,
+    Framing::ByteStream if offset >= 4 && data[offset - 4] == 0 => offset - 4,
+    Framing::ByteStream => offset - 3,
+  }
+}
+
+/// Inserts an SEI unit into an access unit before its first slice
+///
+/// # Returns
+/// * `Option<Vec<u8>>` - The new access unit, or None if the unit does not
+///   fit the length prefixes of the stream
+fn insert_sei(data: &[u8], sei: &[u8], framing: Framing, h265: bool) -> Option<Vec<u8>> {
+  let at = nal_units(data, framing)
+    .into_iter()
+    .find(|nal| nal_type(nal, h265).1)
+    .map_or(data.len(), |nal| {
+      let offset = nal.as_ptr() as usize - data.as_ptr() as usize;
+      unit_start(data, offset, framing)
+    });
+  let mut out = Vec::with_capacity(data.len() + sei.len() + 4);
+  out.extend_from_slice(&data[..at]);
+  match framing {
+    Framing::ByteStream => out.extend_from_slice(&[0, 0, 0, 1]),
+    Framing::Length(size) => {
+      if size < 4 && sei.len() >> (8 * size) != 0 {
+        return None;
+      }
+      out.extend_from_slice(&(sei.len() as u32).to_be_bytes()[4 - size..]);
+    }
+  }
+  out.extend_from_slice(sei);
+  out.extend_from_slice(&data[at..]);
+  Some(out)
+}
+
+/// Codec and framing of the stream on `pad`, if it is H.264 or H.265
+fn pad_stream(pad: &gst::Pad) -> Option<(Framing, bool)> {
+  match syntax(pad.current_caps()?.as_ref()) {
+    Syntax::H264(framing) => Some((framing, false)),
+    Syntax::H265(framing) => Some((framing, true)),
+    _ => None,
+  }
+}
+
+/// Finds the source pad of the element named `element_name`
+fn source_pad(kit: &GstKit, element_name: &str) -> Result<gst::Pad> {
+  let pipeline = kit.current_pipeline()?;
+  let element = pipeline.by_name(element_name).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Element not found: {}", element_name),
+    )
+  })?;
+  element.static_pad("src").ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} has no src pad", element_name),
+    )
+  })
+}
+
+/// A pad probe removed on `stop`
+struct Probe(Mutex<Option<(gst::Pad, gst::PadProbeId)>>);
+
+impl Probe {
+  fn remove(&self) {
+    if let Some((pad, id)) = self.0.lock().unwrap().take() {
+      pad.remove_probe(id);
+    }
+  }
+}
+
+/// Messages waiting for their access unit
+#[derive(Default)]
+struct Pending {
+  messages: VecDeque<(Option<gst::ClockTime>, Vec<u8>)>,
+  injected: u32,
+}
+
+/// Inserts SEI user data into an encoded stream, created by `injectSei`
+#[napi]
+pub struct SeiInjector {
+  pending: Arc<Mutex<Pending>>,
+  probe: Probe,
+}
+
+#[napi]
+impl SeiInjector {
+  /// Queues metadata for the stream
+  ///
+  /// The metadata is carried by the first access unit presented at or
+  /// after `timestampNs`, or by the next one to pass without a timestamp.
+  ///
+  /// # Arguments
+  /// * `data` - Bytes or a string (stored as UTF-8), up to 64 KiB
+  /// * `timestamp_ns` - Presentation time in nanoseconds, in stream time
+  ///   as reported by `getPosition`
+  ///
+  /// # Example
+  /// ```javascript
+  /// injector.push(JSON.stringify({ lat: 48.85, lon: 2.35 }), kit.getPosition());
+  /// ```
+  #[napi]
+  pub fn push(&self, data: Either<Buffer, String>, timestamp_ns: Option<i64>) -> Result<()> {
+    let data = metadata_bytes(data)?;
+    let time = timestamp_ns.map(|ns| gst::ClockTime::from_nseconds(ns.max(0) as u64));
+    self
+      .pending
+      .lock()
+      .unwrap()
+      .messages
+      .push_back((time, data));
+    Ok(())
+  }
+
+  /// Number of messages waiting for their access unit
+  #[napi(getter)]
+  pub fn pending(&self) -> u32 {
+    self.pending.lock().unwrap().messages.len() as u32
+  }
+
+  /// Number of messages inserted into the stream so far
+  #[napi(getter)]
+  pub fn injected(&self) -> u32 {
+    self.pending.lock().unwrap().injected
+  }
+
+  /// Stops inserting metadata; pending messages are dropped
+  #[napi]
+  pub fn stop(&self) {
+    self.probe.remove();
+    self.pending.lock().unwrap().messages.clear();
+  }
+}
+
+impl Drop for SeiInjector {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Inserts SEI user data messages into the H.264 or H.265 stream leaving
+/// an element, such as an encoder or a parser
+///
+/// Decoders ignore the messages, and muxers and parsers keep them, so they
+/// reach `extractSei` on the other side in MP4, Matroska, MPEG-TS or RTP.
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `element_name` - The name of the element whose output carries the
+///   metadata
+/// * `options` - UUID of the messages
+///
+/// # Returns
+/// * `Result<SeiInjector>` - The injector to push metadata to
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("v4l2src ! videoconvert ! x264enc name=enc ! h264parse ! mpegtsmux ! filesink location=flight.ts");
+/// const injector = injectSei(kit, "enc");
+/// kit.play();
+/// telemetry.on("fix", (fix) => injector.push(JSON.stringify(fix)));
+/// ```
+#[napi]
+pub fn inject_sei(
+  kit: &GstKit,
+  element_name: String,
+  options: Option<SeiInjectorOptions>,
+) -> Result<SeiInjector> {
+  let options = options.unwrap_or(SeiInjectorOptions { uuid: None });
+  let uuid = match &options.uuid {
+    Some(uuid) => parse_uuid(uuid)?,
+    None => DEFAULT_UUID,
+  };
+  let pad = source_pad(kit, &element_name)?;
+  let pending = Arc::new(Mutex::new(Pending::default()));
+  let queued = pending.clone();
+  let id = pad
+    .add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+      let Some((framing, h265)) = pad_stream(pad) else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let Some(gst::PadProbeData::Buffer(buffer)) = &mut info.data else {
+        return gst::PadProbeReturn::Ok;
+      };
+      // Compare in stream time, the time `getPosition` reports
+      let time = buffer.pts().or(buffer.dts()).map(|time| {
+        pad
+          .sticky_event::<gst::event::Segment>(0)
+          .and_then(|event| {
+            event
+              .segment()
+              .downcast_ref::<gst::ClockTime>()
+              .and_then(|segment| segment.to_stream_time(time))
+          })
+          .unwrap_or(time)
+      });
+      let mut pending = queued.lock().unwrap();
+      let due = pending
+        .messages
+        .iter()
+        .take_while(|(at, _)| at.is_none_or(|at| time.is_some_and(|time| at <= time)))
+        .count();
+      if due == 0 {
+        return gst::PadProbeReturn::Ok;
+      }
+      let messages: Vec<Vec<u8>> = pending
+        .messages
+        .drain(..due)
+        .map(|(_, data)| data)
+        .collect();
+      let Ok(map) = buffer.map_readable() else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let sei = sei_nal(&uuid, &messages, h265);
+      let Some(data) = insert_sei(map.as_slice(), &sei, framing, h265) else {
+        return gst::PadProbeReturn::Ok;
+      };
+      drop(map);
+      let mut out = gst::Buffer::from_mut_slice(data);
+      let _ = buffer.copy_into(
+        out.get_mut().unwrap(),
+        gst::BufferCopyFlags::FLAGS | gst::BufferCopyFlags::TIMESTAMPS | gst::BufferCopyFlags::META,
+        ..,
+      );
+      *buffer = out;
+      pending.injected += messages.len() as u32;
+      gst::PadProbeReturn::Ok
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to watch the output of {}", element_name),
+      )
+    })?;
+  Ok(SeiInjector {
+    pending,
+    probe: Probe(Mutex::new(Some((pad, id)))),
+  })
+}
+
+/// State of an extractor
+#[derive(Default)]
+struct Extracted {
+  callback: Option<MetadataCallback>,
+  queue: VecDeque<TimedMetadata>,
+}
+
+/// Reads SEI user data from an encoded stream, created by `extractSei`
+#[napi]
+pub struct SeiExtractor {
+  extracted: Arc<(Mutex<Extracted>, Condvar)>,
+  probe: Probe,
+}
+
+#[napi]
+impl SeiExtractor {
+  /// Sets the callback receiving each message as it passes; messages
+  /// delivered to it are not returned by `next`
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every message
+  ///
+  /// # Example
+  /// ```javascript
+  /// extractor.onMetadata((metadata) => console.log(metadata.timestamp, metadata.data.toString()));
+  /// ```
+  #[napi]
+  pub fn on_metadata(&self, callback: MetadataCallback) {
+    self.extracted.0.lock().unwrap().callback = Some(callback);
+  }
+
+  /// Returns the next message, waiting for one up to `timeoutMs`
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Timeout in milliseconds (default: 100, use 0 for non-blocking)
+  ///
+  /// # Returns
+  /// * `Option<TimedMetadata>` - The message, or null if none arrived in time
+  ///
+  /// # Example
+  /// ```javascript
+  /// const metadata = extractor.next(1000);
+  /// if (metadata) console.log(JSON.parse(metadata.data.toString()));
+  /// ```
+  #[napi]
+  pub fn next(&self, timeout_ms: Option<u32>) -> Option<TimedMetadata> {
+    let (extracted, arrived) = &*self.extracted;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(100) as u64);
+    let (mut extracted, _) = arrived
+      .wait_timeout_while(extracted.lock().unwrap(), timeout, |extracted| {
+        extracted.queue.is_empty()
+      })
+      .unwrap();
+    extracted.queue.pop_front()
+  }
+
+  /// Stops reading metadata
+  #[napi]
+  pub fn stop(&self) {
+    self.probe.remove();
+  }
+}
+
+impl Drop for SeiExtractor {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Reads the SEI user data messages of the H.264 or H.265 stream leaving an
+/// element, such as a parser after a demuxer
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `element_name` - The name of the element whose output is read
+/// * `options` - UUID of the messages to read
+///
+/// # Returns
+/// * `Result<SeiExtractor>` - The extractor to read metadata from
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("filesrc location=flight.ts ! tsdemux ! h264parse name=parse ! avdec_h264 ! fakesink");
+/// const extractor = extractSei(kit, "parse");
+/// extractor.onMetadata((metadata) => track.push(JSON.parse(metadata.data.toString())));
+/// kit.play();
+/// ```
+#[napi]
+pub fn extract_sei(
+  kit: &GstKit,
+  element_name: String,
+  options: Option<SeiExtractorOptions>,
+) -> Result<SeiExtractor> {
+  let options = options.unwrap_or(SeiExtractorOptions { uuid: None });
+  let filter = options.uuid.as_deref().map(parse_uuid).transpose()?;
+  let pad = source_pad(kit, &element_name)?;
+  let extracted = Arc::new((Mutex::new(Extracted::default()), Condvar::new()));
+  let found = extracted.clone();
+  let id = pad
+    .add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+      let Some((framing, h265)) = pad_stream(pad) else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let Some(buffer) = info.buffer() else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let Ok(map) = buffer.map_readable() else {
+        return gst::PadProbeReturn::Ok;
+      };
+      let sei_type = if h265 { 39 } else { 6 };
+      let messages: Vec<([u8; 16], Vec<u8>)> = nal_units(map.as_slice(), framing)
+        .into_iter()
+        .filter(|nal| nal_type(nal, h265).0 == sei_type)
+        .flat_map(|nal| sei_messages(nal, h265))
+        .filter(|(uuid, _)| filter.is_none_or(|filter| filter == *uuid))
+        .collect();
+      if messages.is_empty() {
+        return gst::PadProbeReturn::Ok;
+      }
+      let time = timestamp(buffer.pts().or(buffer.dts()));
+      let (extracted, arrived) = &*found;
+      let mut extracted = extracted.lock().unwrap();
+      for (uuid, data) in messages {
+        let metadata = TimedMetadata {
+          timestamp: time,
+          data: data.into(),
+          uuid: Some(format_uuid(&uuid)),
+        };
+        match &extracted.callback {
+          Some(callback) => {
+            callback.call(metadata, ThreadsafeFunctionCallMode::NonBlocking);
+          }
+          None => {
+            if extracted.queue.len() == MAX_QUEUED {
+              extracted.queue.pop_front();
+            }
+            extracted.queue.push_back(metadata);
+          }
+        }
+      }
+      arrived.notify_all();
+      gst::PadProbeReturn::Ok
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to watch the output of {}", element_name),
+      )
+    })?;
+  Ok(SeiExtractor {
+    extracted,
+    probe: Probe(Mutex::new(Some((pad, id)))),
+  })
+}
@@ -0,0 +1,1224 @@
+//! # Transcoding
+//!
+//! GStreamer-backed transcoding helpers. Input media is decoded with `decodebin`,
+//! every decoded video/audio stream is re-encoded with the requested encoder and
+//! the result is muxed into the requested container.
+//!
+//! This module provides the `TranscodeStream` class, which accepts input chunks
+//! from JavaScript and hands back transcoded output chunks, so it can sit in the
+//! middle of a Node stream pipeline, and `transcodeBuffer` for small media that
+//! fits in memory.
+
+use crate::attachments::{attach_files, Attachment};
+use crate::audio_filters::{make_audio_filters, make_tempo, parse_audio_filters, AudioFilter};
+use crate::av_sync::make_async_correct;
+use crate::codecs::{resolve, CodecSpec};
+use crate::encryption::{decrypt_demuxer_output, encrypt_muxer_input, EncryptionOptions};
+use crate::interpolate::{interpolation_mode, make_interpolator, Interpolation};
+use crate::missing_plugins::parse_launch;
+use crate::presets::preset_properties;
+use crate::report::{describe_streams, drain_warnings, TranscodeReport};
+use crate::rotation::{make_autorotate, sniff_matroska_rotation};
+use crate::video_filters::{make_video_filters, parse_video_filters, VideoFilter};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::bindgen_prelude::Buffer;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Options describing the output of a transcode
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct TranscodeOptions {
+  /// Output container ("webm", "mkv", "mp4", "mov", "ogg", "avi" or "ts")
+  pub container: String,
+  /// Video codec ("vp8", "vp9", "av1", "h264", "h265", "theora", "mjpeg", "copy" or "none").
+  /// Defaults to the usual codec for the container. "copy" remuxes the input stream unchanged.
+  pub video_codec: Option<String>,
+  /// Audio codec ("opus", "vorbis", "mp3", "aac", "flac", "copy" or "none").
+  /// Defaults to the usual codec for the container. "copy" remuxes the input stream unchanged.
+  pub audio_codec: Option<String>,
+  /// Target video bitrate in kbit/s
+  pub video_bitrate: Option<u32>,
+  /// Target audio bitrate in kbit/s
+  pub audio_bitrate: Option<u32>,
+  /// Constant output frame rate. By default the original per-frame timestamps
+  /// are kept, so variable frame rate input stays variable; when set, frames
+  /// are duplicated or dropped to conform to this rate.
+  pub frame_rate: Option<f64>,
+  /// Video encoder speed preset ("ultrafast" .. "placebo", or "0" slowest to
+  /// "10" fastest). See `listPresets` for the values a codec accepts.
+  pub preset: Option<String>,
+  /// Video encoder tuning (e.g. "zerolatency" for h264). See `listPresets`.
+  pub tune: Option<String>,
+  /// Regions of the frame to encode at a different quality
+  pub regions: Option<Vec<RegionOfInterest>>,
+  /// Produce byte-identical output for the same input and options: encoders
+  /// run single-threaded, random identifiers (Matroska UIDs, Ogg serial
+  /// numbers) come from a fixed seed and creation dates are set to the Unix
+  /// epoch. Slower; concurrent transcodes may still disturb the seed.
+  pub deterministic: Option<bool>,
+  /// Audio filters applied in order before the audio encoder, e.g.
+  /// `["aresample=48000", "channels=stereo", "gain=-3", "fadein=1", "fadeout=9:1"]`
+  pub audio_filters: Option<Vec<String>>,
+  /// Video filters applied in order before the video encoder, e.g.
+  /// `["chromakey=#00FF00:0.1:0.05", "lut3d=grade.cube", "subtitles=movie.srt"]`
+  pub video_filters: Option<Vec<String>>,
+  /// Files embedded in the output, such as a cover image named "cover.jpg".
+  /// Only the "mkv" container can carry attachments.
+  pub attachments: Option<Vec<Attachment>>,
+  /// Encrypts the output frames with AES-128-CTR ("mkv" and "webm" only)
+  pub encryption: Option<EncryptionOptions>,
+  /// AES-128 key, as 32 hexadecimal digits, decrypting an input written with
+  /// `encryption`
+  pub decryption_key: Option<String>,
+  /// Turns the video upright according to the rotation stored in the input
+  /// (see `VideoStreamInfo.rotation`) before the video filters, so the
+  /// output needs no rotation to display correctly
+  pub autorotate: Option<bool>,
+  /// Keeps the audio in step with its timestamps, correcting the drift
+  /// reported by `measureAvSync`: silence is inserted where samples are
+  /// missing (and before audio starting late) and overlapping samples are
+  /// dropped. Requires re-encoding the audio stream.
+  pub async_correct: Option<bool>,
+  /// How frames are synthesized when the frame rate is raised or time is
+  /// stretched: "duplicate", "blend" (default) or "motion" (motion-compensated).
+  /// When set, `frameRate` conversion uses it instead of dropping/duplicating frames.
+  pub interpolation: Option<String>,
+  /// Slow motion factor: 2 plays at half speed, filling the new video frames
+  /// by `interpolation`. Audio is time-stretched without changing its pitch.
+  pub slow_motion: Option<f64>,
+  /// Playback speed factor: 2 plays twice as fast, 0.5 at half speed. Video
+  /// frames are dropped or duplicated (see `interpolation`) and audio is
+  /// time-stretched without changing its pitch, so both streams stay in sync.
+  pub speed: Option<f64>,
+  /// What a `TranscodeJob` does when its output file exists: "error"
+  /// (default), "replace" or "rename", which writes to the first free
+  /// `name (1).ext`-style path instead. Ignored by in-memory transcodes.
+  pub overwrite: Option<String>,
+}
+
+/// A rectangle of the video frame encoded at a different quality.
+///
+/// Regions are attached to every frame as region-of-interest metadata. They are
+/// honored by encoders that read it (the VA-API, Quick Sync and NVENC encoders)
+/// and ignored by the others.
+#[napi(object)]
+#[derive(Clone)]
+pub struct RegionOfInterest {
+  /// Left edge in pixels
+  pub x: u32,
+  /// Top edge in pixels
+  pub y: u32,
+  /// Width in pixels
+  pub width: u32,
+  /// Height in pixels
+  pub height: u32,
+  /// Quantizer offset for the region; negative values raise the quality
+  pub delta_qp: i32,
+}
+
+/// Region-of-interest parameter structures read by the encoders
+const ROI_PARAMS: &[&str] = &["roi/va", "roi/vaapi", "roi/qsv", "roi/nvenc"];
+
+/// Muxer and default codecs of an output container
+pub(crate) struct ContainerSpec {
+  pub(crate) muxer: &'static str,
+  pub(crate) video_codec: &'static str,
+  pub(crate) audio_codec: &'static str,
+}
+
+/// How long `transcodeBuffer` waits for the transcode to finish by default
+const BUFFER_TIMEOUT_MS: u32 = 600_000;
+
+/// Container names accepted by `TranscodeOptions`
+pub(crate) const CONTAINERS: &[&str] = &["webm", "mkv", "mp4", "mov", "ogg", "avi", "ts"];
+
+pub(crate) fn container_spec(container: &str) -> Result<ContainerSpec> {
+  let (muxer, video_codec, audio_codec) = match container {
+    "webm" => ("webmmux", "vp8", "opus"),
+    "mkv" | "matroska" => ("matroskamux", "vp8", "opus"),
+    "mp4" => ("mp4mux", "h264", "aac"),
+    "mov" => ("qtmux", "h264", "aac"),
+    "ogg" | "ogv" => ("oggmux", "theora", "vorbis"),
+    "avi" => ("avimux", "mjpeg", "mp3"),
+    "ts" | "mpegts" => ("mpegtsmux", "h264", "aac"),
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported container: {}", container),
+      ))
+    }
+  };
+  Ok(ContainerSpec {
+    muxer,
+    video_codec,
+    audio_codec,
+  })
+}
+
+/// Resolves a video codec to its preferred installed encoder
+pub(crate) fn video_codec_spec(codec: &str) -> Result<CodecSpec> {
+  resolve("video", codec)
+}
+
+/// Resolves an audio codec to its preferred installed encoder
+pub(crate) fn audio_codec_spec(codec: &str) -> Result<CodecSpec> {
+  resolve("audio", codec)
+}
+
+/// Encoding branch appended to a decoded stream
+#[derive(Clone)]
+struct Branch {
+  /// Converter elements placed before the encoder
+  converters: &'static [&'static str],
+  codec: String,
+  /// Encoder element, or `None` to copy the compressed stream
+  encoder: Option<String>,
+  parser: Option<String>,
+  /// Encoder properties, applied when the encoder has them
+  properties: Vec<(String, String)>,
+  /// Constant frame rate enforced with `videorate` before the encoder
+  frame_rate: Option<gst::Fraction>,
+  /// Regions of interest attached to every frame entering the encoder
+  regions: Vec<RegionOfInterest>,
+  /// Audio filters placed after the converters
+  audio_filters: Vec<AudioFilter>,
+  /// Video filters placed after the converters
+  video_filters: Vec<VideoFilter>,
+  /// Rotation read from a Matroska input, shared with the source probe
+  /// filling it in; when set, the frames are turned upright before the filters
+  autorotate: Option<Arc<Mutex<Option<u32>>>>,
+  /// Conform the audio to its timestamps before the filters
+  async_correct: bool,
+  /// Frame interpolation, replacing `videorate` when set
+  interpolation: Option<Interpolation>,
+  /// Audio playback speed, applied after the audio filters
+  tempo: Option<f64>,
+}
+
+fn make_branch(
+  converters: &'static [&'static str],
+  codec: Option<&str>,
+  default_codec: &'static str,
+  bitrate_kbps: Option<u32>,
+  spec_for: fn(&str) -> Result<CodecSpec>,
+) -> Result<Option<Branch>> {
+  let codec = codec.unwrap_or(default_codec);
+  if codec == "none" {
+    return Ok(None);
+  }
+  if codec == "copy" {
+    return Ok(Some(Branch {
+      converters: &[],
+      codec: codec.to_string(),
+      encoder: None,
+      parser: None,
+      properties: Vec::new(),
+      frame_rate: None,
+      regions: Vec::new(),
+      audio_filters: Vec::new(),
+      video_filters: Vec::new(),
+      autorotate: None,
+      async_correct: false,
+      interpolation: None,
+      tempo: None,
+    }));
+  }
+  let spec = spec_for(codec)?;
+  let properties = match (spec.bitrate_property, bitrate_kbps) {
+    (Some(property), Some(kbps)) => vec![(property, (kbps * spec.bitrate_scale).to_string())],
+    _ => Vec::new(),
+  };
+  Ok(Some(Branch {
+    converters,
+    codec: codec.to_string(),
+    encoder: Some(spec.encoder),
+    parser: spec.parser,
+    properties,
+    frame_rate: None,
+    regions: Vec::new(),
+    audio_filters: Vec::new(),
+    video_filters: Vec::new(),
+    autorotate: None,
+    async_correct: false,
+    interpolation: None,
+    tempo: None,
+  }))
+}
+
+pub(crate) fn make_element(factory: &str) -> Result<gst::Element> {
+  gst::ElementFactory::make(factory).build().map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Element {} is not available", factory),
+    )
+  })
+}
+
+/// Creates the highest ranked decoder accepting `caps`
+pub(crate) fn make_decoder(caps: &gst::Caps) -> Result<gst::Element> {
+  let mut factories: Vec<gst::ElementFactory> =
+    gst::ElementFactory::factories_with_type(gst::ElementFactoryType::DECODER, gst::Rank::MARGINAL)
+      .into_iter()
+      .filter(|factory| factory.can_sink_any_caps(caps))
+      .collect();
+  factories.sort_by_key(|factory| std::cmp::Reverse(factory.rank()));
+  let factory = factories
+    .first()
+    .ok_or_else(|| Error::new(Status::GenericFailure, format!("No decoder for {}", caps)))?;
+  factory.create().build().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to create decoder {}: {}", factory.name(), e),
+    )
+  })
+}
+
+/// Attaches region-of-interest metadata to every buffer entering `encoder`
+fn attach_regions(encoder: &gst::Element, regions: Vec<RegionOfInterest>) {
+  let Some(pad) = encoder.static_pad("sink") else {
+    return;
+  };
+  pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+    let Some(buffer) = info.buffer_mut() else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let buffer = buffer.make_mut();
+    for region in &regions {
+      let mut meta = gst_video::VideoRegionOfInterestMeta::add(
+        buffer,
+        "roi",
+        (region.x, region.y, region.width, region.height),
+      );
+      for name in ROI_PARAMS {
+        meta.add_param(
+          gst::Structure::builder(*name)
+            .field("delta-qp", region.delta_qp)
+            .build(),
+        );
+      }
+    }
+    gst::PadProbeReturn::Ok
+  });
+}
+
+/// Creates the elements of a branch and links them from `pad` to `muxer`.
+///
+/// `decode` inserts a decoder in front of the encoder, for pads carrying
+/// compressed (parsed) streams.
+fn link_branch(
+  pipeline: &gst::Pipeline,
+  pad: &gst::Pad,
+  muxer: &gst::Element,
+  branch: &Branch,
+  decode: bool,
+) -> Result<()> {
+  let mut elements = vec![make_element("queue")?];
+  if let Some(encoder) = &branch.encoder {
+    if decode {
+      let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+      elements.push(make_decoder(&caps)?);
+    }
+    for converter in branch.converters {
+      elements.push(make_element(converter)?);
+    }
+    if let Some(rotation) = &branch.autorotate {
+      elements.extend(make_autorotate(*rotation.lock().unwrap())?);
+    }
+    if branch.async_correct {
+      elements.extend(make_async_correct()?);
+    }
+    elements.extend(make_audio_filters(&branch.audio_filters)?);
+    elements.extend(make_video_filters(&branch.video_filters)?);
+    if let Some(tempo) = branch.tempo {
+      elements.extend(make_tempo(tempo)?);
+    }
+    if let Some(interpolation) = &branch.interpolation {
+      elements.extend(make_interpolator(interpolation)?);
+    } else if let Some(frame_rate) = branch.frame_rate {
+      elements.push(make_element("videorate")?);
+      let caps = gst::Caps::builder("video/x-raw")
+        .field("framerate", frame_rate)
+        .build();
+      elements.push(
+        gst::ElementFactory::make("capsfilter")
+          .property("caps", caps)
+          .build()
+          .map_err(|_| {
+            Error::new(
+              Status::GenericFailure,
+              "Element capsfilter is not available",
+            )
+          })?,
+      );
+    }
+    let encoder = make_element(encoder)?;
+    for (property, value) in &branch.properties {
+      if encoder.find_property(property).is_some() {
+        encoder.set_property_from_str(property, value);
+      }
+    }
+    if !branch.regions.is_empty() {
+      attach_regions(&encoder, branch.regions.clone());
+    }
+    elements.push(encoder);
+    if let Some(parser) = &branch.parser {
+      elements.push(make_element(parser)?);
+    }
+  }
+
+  pipeline.add_many(&elements).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to add {} branch: {}", branch.codec, e),
+    )
+  })?;
+  gst::Element::link_many(&elements).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link {} branch: {}", branch.codec, e),
+    )
+  })?;
+  elements[elements.len() - 1].link(muxer).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Muxer does not accept {}: {}", branch.codec, e),
+    )
+  })?;
+  for element in &elements {
+    let _ = element.sync_state_with_parent();
+  }
+
+  let sink_pad = elements[0]
+    .static_pad("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Queue has no sink pad"))?;
+  pad.link(&sink_pad).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link decoded stream: {:?}", e),
+    )
+  })?;
+  Ok(())
+}
+
+/// Callback invoked on every decoded (or parsed) stream pad before it is linked
+pub(crate) type StreamHook = Arc<dyn Fn(&gst::Pad) + Send + Sync>;
+
+/// Links `source ! <demuxer>` and `muxer ! sink` and calls `link_stream` with the
+/// media type ("video" or "audio") of every pad the demuxer exposes.
+fn assemble_pipeline<F>(
+  source: &gst::Element,
+  sink: &gst::Element,
+  demuxer: &str,
+  muxer: &str,
+  streaming: bool,
+  link_stream: F,
+) -> Result<gst::Pipeline>
+where
+  F: Fn(&gst::Pipeline, &gst::Pad, &gst::Element, &str) -> Result<()> + Send + Sync + 'static,
+{
+  let demuxer = gst::ElementFactory::make(demuxer)
+    .name("demux")
+    .build()
+    .map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} is not available", demuxer),
+      )
+    })?;
+  let muxer = gst::ElementFactory::make(muxer)
+    .name("mux")
+    .build()
+    .map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Element {} is not available", muxer),
+      )
+    })?;
+  if streaming {
+    if muxer.find_property("streamable").is_some() {
+      muxer.set_property_from_str("streamable", "true");
+    }
+    if muxer.find_property("fragment-duration").is_some() {
+      muxer.set_property_from_str("fragment-duration", "1000");
+    }
+  }
+
+  let pipeline = gst::Pipeline::new();
+  pipeline
+    .add_many([source, &demuxer, &muxer, sink])
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to build transcode pipeline: {}", e),
+      )
+    })?;
+  source.link(&demuxer).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link source: {}", e),
+    )
+  })?;
+  muxer.link(sink).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to link muxer: {}", e),
+    )
+  })?;
+
+  let pipeline_weak = pipeline.downgrade();
+  demuxer.connect_pad_added(move |demuxer, pad| {
+    let Some(pipeline) = pipeline_weak.upgrade() else {
+      return;
+    };
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    let Some(structure) = caps.structure(0) else {
+      return;
+    };
+    let media = if structure.name().starts_with("video/") {
+      "video"
+    } else if structure.name().starts_with("audio/") {
+      "audio"
+    } else {
+      return;
+    };
+    if let Err(e) = link_stream(&pipeline, pad, &muxer, media) {
+      gst::element_error!(demuxer, gst::CoreError::Negotiation, ["{}", e.reason]);
+    }
+  });
+
+  Ok(pipeline)
+}
+
+/// Builds a `source ! decodebin ! <encoders> ! muxer ! sink` pipeline.
+///
+/// Encoding branches are created as `decodebin` exposes its pads, so inputs
+/// without audio (or without video) only get the branches they need. When a
+/// stream is copied, `parsebin` is used instead and the re-encoded streams get
+/// their own decoder. `streaming` configures the muxer for non-seekable
+/// output. The demuxing element is named "demux" and the muxer "mux".
+pub(crate) fn build_transcode_pipeline(
+  source: &gst::Element,
+  sink: &gst::Element,
+  options: &TranscodeOptions,
+  streaming: bool,
+  hook: Option<StreamHook>,
+) -> Result<gst::Pipeline> {
+  let container = container_spec(&options.container)?;
+  let valid = |factor: f64| factor.is_finite() && factor > 0.0;
+  // Factor applied to every output timestamp
+  let stretch = match (options.speed, options.slow_motion) {
+    (Some(_), Some(_)) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "speed and slowMotion cannot be combined".to_string(),
+      ))
+    }
+    (Some(speed), None) if valid(speed) => 1.0 / speed,
+    (Some(speed), None) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid speed: {}", speed),
+      ))
+    }
+    (None, Some(factor)) if valid(factor) => factor,
+    (None, Some(factor)) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid slow motion factor: {}", factor),
+      ))
+    }
+    (None, None) => 1.0,
+  };
+  let mut video = make_branch(
+    &["videoconvert", "videoscale"],
+    options.video_codec.as_deref(),
+    container.video_codec,
+    options.video_bitrate,
+    video_codec_spec,
+  )?;
+  if let Some(branch) = video.as_mut() {
+    let copying = branch.encoder.is_none();
+    if copying
+      && (options.frame_rate.is_some()
+        || options.interpolation.is_some()
+        || stretch != 1.0
+        || options.preset.is_some()
+        || options.tune.is_some()
+        || options.regions.is_some()
+        || options.video_filters.is_some()
+        || options.autorotate == Some(true))
+    {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "frameRate, interpolation, speed, slowMotion, preset, tune, regions, videoFilters and autorotate require re-encoding the video stream".to_string(),
+      ));
+    }
+    if options.autorotate == Some(true) {
+      branch.autorotate = Some(sniff_matroska_rotation(source));
+    }
+    branch.regions = options.regions.clone().unwrap_or_default();
+    branch.video_filters =
+      parse_video_filters(options.video_filters.as_deref().unwrap_or_default())?;
+    let presets = preset_properties(
+      branch.encoder.as_deref().unwrap_or_default(),
+      options.preset.as_deref(),
+      options.tune.as_deref(),
+    )?;
+    branch.properties.extend(
+      presets
+        .into_iter()
+        .map(|(property, value)| (property.to_string(), value)),
+    );
+  }
+  if let (Some(branch), Some(fps)) = (video.as_mut(), options.frame_rate) {
+    branch.frame_rate = Some(
+      gst::Fraction::approximate_f64(fps)
+        .filter(|_| fps > 0.0)
+        .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid frame rate: {}", fps)))?,
+    );
+  }
+  if let Some(branch) = video
+    .as_mut()
+    .filter(|_| options.interpolation.is_some() || stretch != 1.0)
+  {
+    // Slow motion looks smoother blended; a plain speed change keeps whole frames
+    let default_mode = if options.slow_motion.is_some() {
+      "blend"
+    } else {
+      "duplicate"
+    };
+    branch.interpolation = Some(Interpolation {
+      mode: interpolation_mode(options.interpolation.as_deref().unwrap_or(default_mode))?,
+      frame_rate: branch.frame_rate.take(),
+      stretch,
+    });
+  }
+  let mut audio = make_branch(
+    &["audioconvert", "audioresample"],
+    options.audio_codec.as_deref(),
+    container.audio_codec,
+    options.audio_bitrate,
+    audio_codec_spec,
+  )?;
+  if let Some(branch) = audio.as_mut().filter(|_| stretch != 1.0) {
+    if branch.encoder.is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "speed and slowMotion require re-encoding the audio stream".to_string(),
+      ));
+    }
+    branch.tempo = Some(1.0 / stretch);
+  }
+  if let Some(branch) = audio
+    .as_mut()
+    .filter(|_| options.async_correct == Some(true))
+  {
+    if branch.encoder.is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "asyncCorrect requires re-encoding the audio stream".to_string(),
+      ));
+    }
+    branch.async_correct = true;
+  }
+  let audio_filters = parse_audio_filters(options.audio_filters.as_deref().unwrap_or_default())?;
+  if let Some(branch) = audio.as_mut().filter(|_| !audio_filters.is_empty()) {
+    if branch.encoder.is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "audioFilters require re-encoding the audio stream".to_string(),
+      ));
+    }
+    branch.audio_filters = audio_filters;
+  }
+
+  let deterministic = options.deterministic.unwrap_or(false);
+  if deterministic {
+    for branch in [&mut video, &mut audio].into_iter().flatten() {
+      branch
+        .properties
+        .push(("threads".to_string(), "1".to_string()));
+    }
+  }
+
+  let copying = [&video, &audio]
+    .iter()
+    .any(|branch| matches!(branch, Some(branch) if branch.encoder.is_none()));
+  let demuxer = if copying { "parsebin" } else { "decodebin" };
+
+  let pipeline = assemble_pipeline(
+    source,
+    sink,
+    demuxer,
+    container.muxer,
+    streaming,
+    move |pipeline, pad, muxer, media| {
+      let branch = if media == "video" {
+        video.as_ref()
+      } else {
+        audio.as_ref()
+      };
+      let Some(branch) = branch else {
+        return Ok(());
+      };
+      if let Some(hook) = &hook {
+        hook(pad);
+      }
+      link_branch(pipeline, pad, muxer, branch, copying)
+    },
+  )?;
+  if deterministic {
+    make_deterministic(&pipeline);
+  }
+  if let Some(attachments) = options.attachments.as_deref().filter(|a| !a.is_empty()) {
+    let muxer = pipeline
+      .by_name("mux")
+      .filter(|_| container.muxer == "matroskamux")
+      .ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          "attachments require the mkv container".to_string(),
+        )
+      })?;
+    attach_files(&muxer, attachments)?;
+  }
+  if let Some(encryption) = &options.encryption {
+    let muxer = pipeline
+      .by_name("mux")
+      .filter(|_| matches!(container.muxer, "matroskamux" | "webmmux"))
+      .ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          "encryption requires the mkv or webm container".to_string(),
+        )
+      })?;
+    encrypt_muxer_input(&muxer, encryption)?;
+  }
+  if let Some(key) = &options.decryption_key {
+    let demuxer = pipeline
+      .by_name("demux")
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Demuxer not found"))?;
+    decrypt_demuxer_output(&demuxer, key)?;
+  }
+  Ok(pipeline)
+}
+
+/// Removes the sources of nondeterminism in the muxer output
+fn make_deterministic(pipeline: &gst::Pipeline) {
+  gst::glib::random_set_seed(0);
+  let (Some(muxer), Ok(epoch)) = (
+    pipeline.by_name("mux"),
+    gst::glib::DateTime::from_unix_utc(0),
+  ) else {
+    return;
+  };
+  if muxer.find_property("creation-time").is_some() {
+    muxer.set_property("creation-time", &epoch);
+  }
+  if let Some(setter) = muxer.dynamic_cast_ref::<gst::TagSetter>() {
+    setter.add_tag::<gst::tags::DateTime>(&gst::DateTime::from(epoch), gst::TagMergeMode::Replace);
+  }
+}
+
+/// Parses a launch string into a pipeline
+pub(crate) fn launch(description: &str) -> Result<gst::Pipeline> {
+  parse_launch(description)?
+    .downcast::<gst::Pipeline>()
+    .map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        "Provided string is not a valid pipeline".to_string(),
+      )
+    })
+}
+
+/// Returns the first error message posted on the pipeline bus, if any
+pub(crate) fn pop_bus_error(pipeline: &gst::Pipeline) -> Option<Error> {
+  let bus = pipeline.bus()?;
+  let msg = bus.pop_filtered(&[gst::MessageType::Error])?;
+  match msg.view() {
+    gst::MessageView::Error(err) => Some(Error::new(
+      Status::GenericFailure,
+      format!("Transcode failed: {}", err.error()),
+    )),
+    _ => None,
+  }
+}
+
+/// Blocks until the pipeline reaches end of stream or posts an error
+pub(crate) fn wait_for_eos(pipeline: &gst::Pipeline) -> Result<()> {
+  wait_for_eos_within(pipeline, gst::ClockTime::NONE)
+}
+
+/// Like `wait_for_eos`, failing if the pipeline is not done within `timeout`
+pub(crate) fn wait_for_eos_within(
+  pipeline: &gst::Pipeline,
+  timeout: Option<gst::ClockTime>,
+) -> Result<()> {
+  let bus = pipeline
+    .bus()
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+  let msg = bus
+    .timed_pop_filtered(timeout, &[gst::MessageType::Eos, gst::MessageType::Error])
+    .ok_or_else(|| match timeout {
+      Some(timeout) => Error::new(
+        Status::GenericFailure,
+        format!("Pipeline did not finish within {} ms", timeout.mseconds()),
+      ),
+      None => Error::new(Status::GenericFailure, "Pipeline bus closed"),
+    })?;
+  match msg.view() {
+    gst::MessageView::Error(err) => Err(Error::new(
+      Status::GenericFailure,
+      format!("Pipeline error: {}", err.error()),
+    )),
+    _ => Ok(()),
+  }
+}
+
+/// Output of `transcodeBufferWithReport`
+#[napi(object)]
+pub struct TranscodeResult {
+  /// The complete transcoded file
+  pub output: Buffer,
+  /// What the transcode did
+  pub report: TranscodeReport,
+}
+
+/// Stream hook counting the video frames reaching the encoder in `frames`
+pub(crate) fn count_video_frames(frames: &Arc<AtomicU32>) -> StreamHook {
+  let frames = frames.clone();
+  Arc::new(move |pad| {
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    if caps
+      .structure(0)
+      .is_some_and(|s| s.name().starts_with("video/"))
+    {
+      let frames = frames.clone();
+      pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+        frames.fetch_add(1, Ordering::Relaxed);
+        gst::PadProbeReturn::Ok
+      });
+    }
+  })
+}
+
+/// Output file held in memory, written at the byte offsets the muxer seeks to
+#[derive(Default)]
+struct MemoryFile {
+  data: Vec<u8>,
+  position: usize,
+}
+
+impl MemoryFile {
+  fn write(&mut self, bytes: &[u8]) {
+    let end = self.position + bytes.len();
+    if self.data.len() < end {
+      self.data.resize(end, 0);
+    }
+    self.data[self.position..end].copy_from_slice(bytes);
+    self.position = end;
+  }
+}
+
+/// Lets the muxer in front of `appsink` seek back into `file`, as it would
+/// in a file, to rewrite its headers once the streams are done
+fn make_seekable(appsink: &AppSink, file: &Arc<Mutex<MemoryFile>>) {
+  let Some(pad) = appsink.static_pad("sink") else {
+    return;
+  };
+  pad.add_probe(gst::PadProbeType::QUERY_DOWNSTREAM, |_, info| {
+    if let Some(gst::PadProbeData::Query(query)) = &mut info.data {
+      if let gst::QueryViewMut::Seeking(seeking) = query.view_mut() {
+        if seeking.format() == gst::Format::Bytes {
+          seeking.set(true, gst::format::Bytes::ZERO, gst::format::Bytes::NONE);
+          return gst::PadProbeReturn::Handled;
+        }
+      }
+    }
+    gst::PadProbeReturn::Ok
+  });
+  let file = file.clone();
+  pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+    if let Some(gst::PadProbeData::Event(event)) = &info.data {
+      if let gst::EventView::Segment(segment) = event.view() {
+        if let Some(start) = segment
+          .segment()
+          .downcast_ref::<gst::format::Bytes>()
+          .and_then(|segment| segment.start())
+        {
+          file.lock().unwrap().position = *start as usize;
+        }
+      }
+    }
+    gst::PadProbeReturn::Ok
+  });
+}
+
+/// Transcodes an in-memory media file and describes what was done
+fn transcode_in_memory(
+  input: Buffer,
+  options: &TranscodeOptions,
+  timeout_ms: Option<u32>,
+) -> Result<TranscodeResult> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let appsrc = AppSrc::builder()
+    .stream_type(gst_app::AppStreamType::Stream)
+    .format(gst::Format::Bytes)
+    .build();
+  let appsink = AppSink::builder().sync(false).build();
+  let video_frames = Arc::new(AtomicU32::new(0));
+  let hook = count_video_frames(&video_frames);
+  let pipeline = build_transcode_pipeline(
+    appsrc.upcast_ref(),
+    appsink.upcast_ref(),
+    options,
+    false,
+    Some(hook),
+  )?;
+
+  let output = Arc::new(Mutex::new(MemoryFile::default()));
+  make_seekable(&appsink, &output);
+  let output_clone = output.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        output_clone.lock().unwrap().write(map.as_slice());
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+
+  let input_size = input.len() as i64;
+  let start = Instant::now();
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let pushed = appsrc
+    .push_buffer(gst::Buffer::from_mut_slice(input.to_vec()))
+    .and_then(|_| appsrc.end_of_stream());
+  let result = match pushed {
+    Ok(_) => wait_for_eos_within(
+      &pipeline,
+      Some(gst::ClockTime::from_mseconds(
+        timeout_ms.unwrap_or(BUFFER_TIMEOUT_MS) as u64,
+      )),
+    ),
+    Err(e) => Err(Error::new(
+      Status::GenericFailure,
+      format!("Failed to push buffer: {}", e),
+    )),
+  };
+  let wall_time = start.elapsed().as_secs_f64();
+  let muxer = pipeline.by_name("mux");
+  let streams = muxer.as_ref().map(describe_streams).unwrap_or_default();
+  let warnings = drain_warnings(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result?;
+
+  let data = std::mem::take(&mut output.lock().unwrap().data);
+  let video_frames = video_frames.load(Ordering::Relaxed);
+  let report = TranscodeReport {
+    container: options.container.clone(),
+    muxer: muxer
+      .and_then(|mux| mux.factory())
+      .map(|factory| factory.name().to_string())
+      .unwrap_or_default(),
+    streams,
+    wall_time_ms: wall_time * 1000.0,
+    video_frames,
+    average_fps: if wall_time > 0.0 {
+      video_frames as f64 / wall_time
+    } else {
+      0.0
+    },
+    input_size,
+    output_size: data.len() as i64,
+    warnings,
+  };
+  Ok(TranscodeResult {
+    output: Buffer::from(data),
+    report,
+  })
+}
+
+/// Transcodes an in-memory media file without touching the filesystem
+///
+/// The call blocks until the whole file is transcoded, for at most
+/// `timeoutMs`.
+///
+/// # Arguments
+/// * `input` - The complete input file
+/// * `options` - Output container and codec settings
+/// * `timeout_ms` - How long to wait for the transcode in milliseconds
+///   (default: 10 minutes)
+///
+/// # Returns
+/// * `Result<Buffer>` - The complete transcoded file
+///
+/// # Example
+/// ```javascript
+/// const webm = transcodeBuffer(fs.readFileSync("sticker.gif"), { container: "webm" });
+/// ```
+#[napi]
+pub fn transcode_buffer(
+  input: Buffer,
+  options: TranscodeOptions,
+  timeout_ms: Option<u32>,
+) -> Result<Buffer> {
+  transcode_in_memory(input, &options, timeout_ms).map(|result| result.output)
+}
+
+/// Transcodes an in-memory media file and reports what was done
+///
+/// The report lists, per stream, the input and output caps, the filters and
+/// encoder used and the encoder settings that differ from their defaults,
+/// along with wall time, average fps, sizes and any warnings.
+///
+/// # Arguments
+/// * `input` - The complete input file
+/// * `options` - Output container and codec settings
+/// * `report_path` - Optional file the report is also written to, as JSON
+/// * `timeout_ms` - How long to wait for the transcode in milliseconds
+///   (default: 10 minutes)
+///
+/// # Returns
+/// * `Result<TranscodeResult>` - The transcoded file and its report
+///
+/// # Example
+/// ```javascript
+/// const { output, report } = transcodeBufferWithReport(input, { container: "mp4" }, "job.json");
+/// console.log(report.averageFps, report.streams.map((s) => s.encoder));
+/// ```
+#[napi]
+pub fn transcode_buffer_with_report(
+  input: Buffer,
+  options: TranscodeOptions,
+  report_path: Option<String>,
+  timeout_ms: Option<u32>,
+) -> Result<TranscodeResult> {
+  let result = transcode_in_memory(input, &options, timeout_ms)?;
+  if let Some(path) = report_path {
+    let json = serde_json::to_string_pretty(&result.report).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to serialize report: {}", e),
+      )
+    })?;
+    std::fs::write(&path, json).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to write report {}: {}", path, e),
+      )
+    })?;
+  }
+  Ok(result)
+}
+
+/// Streaming transcoder fed from JavaScript
+///
+/// `TranscodeStream` accepts encoded input chunks through `write` and produces
+/// chunks of the transcoded container, which are either pulled with `read` or
+/// delivered to a callback registered with `onData`.
+#[napi]
+pub struct TranscodeStream {
+  pipeline: gst::Pipeline,
+  appsrc: AppSrc,
+  appsink: AppSink,
+}
+
+impl Drop for TranscodeStream {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
+
+#[napi]
+impl TranscodeStream {
+  /// Creates a new transcoder and starts its pipeline
+  ///
+  /// # Arguments
+  /// * `options` - Output container and codec settings
+  ///
+  /// # Example
+  /// ```javascript
+  /// const stream = new TranscodeStream({ container: "webm", videoCodec: "vp8" });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(options: TranscodeOptions) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let appsrc = AppSrc::builder()
+      .stream_type(gst_app::AppStreamType::Stream)
+      .format(gst::Format::Bytes)
+      .build();
+    let appsink = AppSink::builder().sync(false).build();
+
+    let pipeline = build_transcode_pipeline(
+      appsrc.upcast_ref(),
+      appsink.upcast_ref(),
+      &options,
+      true,
+      None,
+    )?;
+    pipeline.set_state(gst::State::Playing).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to set state to Playing: {}", e),
+      )
+    })?;
+
+    Ok(TranscodeStream {
+      pipeline,
+      appsrc,
+      appsink,
+    })
+  }
+
+  /// Writes a chunk of input media
+  ///
+  /// # Arguments
+  /// * `chunk` - The next chunk of the input file
+  ///
+  /// # Example
+  /// ```javascript
+  /// readable.on("data", (chunk) => stream.write(chunk));
+  /// ```
+  #[napi]
+  pub fn write(&self, chunk: Buffer) -> Result<()> {
+    if let Some(err) = pop_bus_error(&self.pipeline) {
+      return Err(err);
+    }
+    let buffer = gst::Buffer::from_mut_slice(chunk.to_vec());
+    self.appsrc.push_buffer(buffer).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to push buffer: {}", e),
+      )
+    })?;
+    Ok(())
+  }
+
+  /// Signals that all input has been written
+  ///
+  /// # Example
+  /// ```javascript
+  /// readable.on("end", () => stream.end());
+  /// ```
+  #[napi]
+  pub fn end(&self) -> Result<()> {
+    self.appsrc.end_of_stream().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to end stream: {}", e),
+      )
+    })?;
+    Ok(())
+  }
+
+  /// Reads the next chunk of transcoded output
+  ///
+  /// # Arguments
+  /// * `timeout_ms` - Timeout in milliseconds (default: 100ms, use 0 for non-blocking)
+  ///
+  /// # Returns
+  /// * `Result<Option<Buffer>>` - The next output chunk, or null if none is available yet
+  ///
+  /// # Example
+  /// ```javascript
+  /// while (!stream.isFinished()) {
+  ///   const chunk = stream.read(100);
+  ///   if (chunk) writable.write(chunk);
+  /// }
+  /// ```
+  #[napi]
+  pub fn read(
+    &self,
+    #[napi(ts_arg_type = "number | undefined")] timeout_ms: Option<u32>,
+  ) -> Result<Option<Buffer>> {
+    if let Some(err) = pop_bus_error(&self.pipeline) {
+      return Err(err);
+    }
+
+    let timeout = timeout_ms.unwrap_or(100);
+    match self
+      .appsink
+      .try_pull_sample(gst::ClockTime::from_mseconds(timeout as u64))
+    {
+      Some(sample) => {
+        let buffer = sample
+          .buffer()
+          .ok_or_else(|| Error::new(Status::GenericFailure, "Sample has no buffer"))?;
+        let map = buffer
+          .map_readable()
+          .map_err(|_| Error::new(Status::GenericFailure, "Failed to map buffer"))?;
+        Ok(Some(Buffer::from(map.as_slice().to_vec())))
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// Delivers every transcoded output chunk to a callback
+  ///
+  /// Once a callback is registered, chunks are no longer available through `read`.
+  ///
+  /// # Arguments
+  /// * `callback` - A JavaScript function called with each output chunk
+  ///
+  /// # Example
+  /// ```javascript
+  /// stream.onData((chunk) => writable.write(chunk));
+  /// ```
+  #[napi]
+  pub fn on_data(&self, callback: ThreadsafeFunction<Buffer, (), Buffer, Status, false>) {
+    self.appsink.set_callbacks(
+      gst_app::AppSinkCallbacks::builder()
+        .new_sample(move |appsink| {
+          let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+          let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+          let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+          callback.call(
+            Buffer::from(map.as_slice().to_vec()),
+            ThreadsafeFunctionCallMode::NonBlocking,
+          );
+          Ok(gst::FlowSuccess::Ok)
+        })
+        .build(),
+    );
+  }
+
+  /// Checks if all transcoded output has been produced
+  ///
+  /// # Returns
+  /// * `bool` - true once the output reached end of stream
+  #[napi]
+  pub fn is_finished(&self) -> bool {
+    self.appsink.is_eos()
+  }
+}
@@ -0,0 +1,396 @@
+//! # Transcode Jobs
+//!
+//! File-to-file transcoding in the background. A `TranscodeJob` reads its
+//! input with `filesrc`, runs the same decode, encode and mux pipeline as
+//! `transcodeBuffer`, and writes the result with `filesink`, so inputs of any
+//! size and any format GStreamer can demux (MP4/H.264, Matroska, ...) can be
+//! transcoded without holding them in memory. Progress is reported to a
+//! callback and the job can be cancelled at any time.
+//!
+//! The output is written next to its destination as `<output>.tmp`, synced
+//! to disk and renamed once the transcode succeeds, so the destination never
+//! holds a partial file; the temporary file is deleted if the job fails or
+//! is cancelled.
+
+use crate::transcode::{build_transcode_pipeline, count_video_frames, TranscodeOptions};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// Interval between progress updates
+const PROGRESS_INTERVAL_MS: u64 = 250;
+/// Progress updates the speed is averaged over
+const SPEED_WINDOW: usize = 8;
+
+/// Progress of a transcode job
+#[napi(object)]
+#[derive(Clone)]
+pub struct TranscodeProgress {
+  /// "pending", "running", "done", "failed" or "cancelled"
+  pub state: String,
+  /// Input position reached, in nanoseconds
+  pub position: i64,
+  /// Input duration in nanoseconds, or -1 if unknown
+  pub duration: i64,
+  /// Completion from 0 to 100, or -1 if the duration is unknown
+  pub percent: f64,
+  /// Video frames that reached the encoder
+  pub frames_processed: u32,
+  /// Transcoding speed as a multiple of realtime, averaged over the last
+  /// two seconds
+  pub speed: f64,
+  /// Estimated seconds until the job is done, or -1 if unknown
+  pub eta_seconds: f64,
+  /// Why the job failed
+  pub error: Option<String>,
+}
+
+/// Callback receiving job progress
+type ProgressCallback =
+  ThreadsafeFunction<TranscodeProgress, (), TranscodeProgress, Status, false, true>;
+
+/// Reads the position and duration of a pipeline into `progress`
+fn update_position(pipeline: &gst::Pipeline, progress: &mut TranscodeProgress) {
+  if let Some(position) = pipeline.query_position::<gst::ClockTime>() {
+    progress.position = position.nseconds() as i64;
+  }
+  if let Some(duration) = pipeline.query_duration::<gst::ClockTime>() {
+    progress.duration = duration.nseconds() as i64;
+  }
+  progress.percent = if progress.duration > 0 {
+    (progress.position as f64 * 100.0 / progress.duration as f64).min(100.0)
+  } else {
+    -1.0
+  };
+}
+
+/// Positions reached by a job over the last progress updates
+#[derive(Default)]
+struct SpeedWindow {
+  samples: VecDeque<(Instant, i64)>,
+}
+
+impl SpeedWindow {
+  /// Records the position of `progress` and updates its speed and ETA
+  fn update(&mut self, progress: &mut TranscodeProgress) {
+    let now = Instant::now();
+    self.samples.push_back((now, progress.position));
+    while self.samples.len() > SPEED_WINDOW {
+      self.samples.pop_front();
+    }
+    let (since, from) = self.samples[0];
+    let elapsed = now.duration_since(since).as_secs_f64();
+    if elapsed > 0.0 {
+      progress.speed = (progress.position - from) as f64 / 1e9 / elapsed;
+    }
+    progress.eta_seconds = if progress.duration > 0 && progress.speed > 0.0 {
+      (progress.duration - progress.position).max(0) as f64 / 1e9 / progress.speed
+    } else {
+      -1.0
+    };
+  }
+}
+
+/// Applies the `overwrite` policy to `output`, returning the path to write
+fn resolve_output(output: &str, overwrite: Option<&str>) -> Result<String> {
+  let path = Path::new(output);
+  match overwrite.unwrap_or("error") {
+    "replace" => Ok(output.to_string()),
+    "error" if path.exists() => Err(Error::new(
+      Status::InvalidArg,
+      format!("Output {} already exists", output),
+    )),
+    "error" => Ok(output.to_string()),
+    "rename" => {
+      let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+      let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()));
+      let mut candidate = path.to_path_buf();
+      let mut n = 1;
+      while candidate.exists() {
+        candidate = path.with_file_name(format!(
+          "{} ({}){}",
+          stem,
+          n,
+          extension.as_deref().unwrap_or("")
+        ));
+        n += 1;
+      }
+      Ok(candidate.to_string_lossy().into_owned())
+    }
+    policy => Err(Error::new(
+      Status::InvalidArg,
+      format!("Unsupported overwrite policy: {}", policy),
+    )),
+  }
+}
+
+/// Syncs the finished `temp` file to disk and moves it to `output`
+fn commit_output(temp: &str, output: &str) -> std::io::Result<()> {
+  std::fs::File::open(temp)?.sync_all()?;
+  std::fs::rename(temp, output)
+}
+
+/// Background file-to-file transcode with progress reporting and cancellation
+///
+/// # Example
+/// ```javascript
+/// const job = new TranscodeJob("input.mp4", "output.webm", { container: "webm" });
+/// job.onProgress(({ state, percent }) => console.log(state, percent.toFixed(1)));
+/// job.start();
+/// process.on("SIGINT", () => job.cancel());
+/// ```
+#[napi]
+pub struct TranscodeJob {
+  pipeline: gst::Pipeline,
+  output: String,
+  temp: String,
+  frames: Arc<AtomicU32>,
+  progress: Arc<Mutex<TranscodeProgress>>,
+  callback: Arc<Mutex<Option<ProgressCallback>>>,
+  cancelled: Arc<AtomicBool>,
+  watcher: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for TranscodeJob {
+  fn drop(&mut self) {
+    self.cancelled.store(true, Ordering::SeqCst);
+    if let Some(watcher) = self.watcher.lock().unwrap().take() {
+      let _ = watcher.join();
+    }
+    let _ = self.pipeline.set_state(gst::State::Null);
+    if self.progress.lock().unwrap().state == "running" {
+      let _ = std::fs::remove_file(&self.temp);
+    }
+  }
+}
+
+#[napi]
+impl TranscodeJob {
+  /// Creates a job transcoding one file into another
+  ///
+  /// # Arguments
+  /// * `input` - Path of the input file
+  /// * `output` - Path of the output file; see `overwrite` for when it exists
+  /// * `options` - Output container and codec settings
+  ///
+  /// # Example
+  /// ```javascript
+  /// const job = new TranscodeJob("camera.mov", "camera.mp4", { container: "mp4", videoCodec: "copy" });
+  /// ```
+  #[napi(constructor)]
+  pub fn new(input: String, output: String, options: TranscodeOptions) -> Result<Self> {
+    gst::init().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to initialize GStreamer: {}", e),
+      )
+    })?;
+
+    let make = |factory: &str, location: &str| {
+      gst::ElementFactory::make(factory)
+        .property("location", location)
+        .build()
+        .map_err(|_| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Element {} is not available", factory),
+          )
+        })
+    };
+    let output = resolve_output(&output, options.overwrite.as_deref())?;
+    let temp = format!("{}.tmp", output);
+    let frames = Arc::new(AtomicU32::new(0));
+    let source = make("filesrc", &input)?;
+    let sink = make("filesink", &temp)?;
+    let pipeline = build_transcode_pipeline(
+      &source,
+      &sink,
+      &options,
+      false,
+      Some(count_video_frames(&frames)),
+    )?;
+
+    Ok(TranscodeJob {
+      pipeline,
+      output,
+      temp,
+      frames,
+      progress: Arc::new(Mutex::new(TranscodeProgress {
+        state: "pending".to_string(),
+        position: 0,
+        duration: -1,
+        percent: -1.0,
+        frames_processed: 0,
+        speed: 0.0,
+        eta_seconds: -1.0,
+        error: None,
+      })),
+      callback: Arc::new(Mutex::new(None)),
+      cancelled: Arc::new(AtomicBool::new(false)),
+      watcher: Mutex::new(None),
+    })
+  }
+
+  /// Sets the callback receiving progress updates, about four times a second
+  /// while running and once when the job ends
+  ///
+  /// # Arguments
+  /// * `callback` - Called with the job progress
+  #[napi]
+  pub fn on_progress(
+    &self,
+    callback: ThreadsafeFunction<TranscodeProgress, (), TranscodeProgress, Status, false, true>,
+  ) {
+    *self.callback.lock().unwrap() = Some(callback);
+  }
+
+  /// Starts transcoding in the background
+  #[napi]
+  pub fn start(&self) -> Result<()> {
+    let mut watcher = self.watcher.lock().unwrap();
+    let mut progress = self.progress.lock().unwrap();
+    if progress.state != "pending" {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Cannot start a {} transcode job", progress.state),
+      ));
+    }
+    if let Err(e) = self.pipeline.set_state(gst::State::Playing) {
+      let _ = self.pipeline.set_state(gst::State::Null);
+      let _ = std::fs::remove_file(&self.temp);
+      progress.state = "failed".to_string();
+      progress.error = Some(e.to_string());
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!("Failed to set state to Playing: {}", e),
+      ));
+    }
+    progress.state = "running".to_string();
+    drop(progress);
+
+    let bus = self
+      .pipeline
+      .bus()
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Pipeline has no bus"))?;
+    let pipeline = self.pipeline.clone();
+    let (output, temp) = (self.output.clone(), self.temp.clone());
+    let frames = self.frames.clone();
+    let progress = self.progress.clone();
+    let callback = self.callback.clone();
+    let cancelled = self.cancelled.clone();
+    *watcher = Some(std::thread::spawn(move || {
+      let mut speed = SpeedWindow::default();
+      loop {
+        let msg = bus.timed_pop_filtered(
+          gst::ClockTime::from_mseconds(PROGRESS_INTERVAL_MS),
+          &[gst::MessageType::Eos, gst::MessageType::Error],
+        );
+        if cancelled.load(Ordering::SeqCst) {
+          let _ = pipeline.set_state(gst::State::Null);
+          return;
+        }
+        let snapshot = {
+          let mut progress = progress.lock().unwrap();
+          if progress.state != "running" {
+            return;
+          }
+          update_position(&pipeline, &mut progress);
+          progress.frames_processed = frames.load(Ordering::Relaxed);
+          speed.update(&mut progress);
+          match msg.as_ref().map(|msg| msg.view()) {
+            Some(gst::MessageView::Eos(_)) => {
+              let _ = pipeline.set_state(gst::State::Null);
+              match commit_output(&temp, &output) {
+                Ok(()) => {
+                  progress.state = "done".to_string();
+                  progress.percent = 100.0;
+                  progress.eta_seconds = 0.0;
+                }
+                Err(e) => {
+                  let _ = std::fs::remove_file(&temp);
+                  progress.state = "failed".to_string();
+                  progress.error = Some(format!("Failed to write {}: {}", output, e));
+                }
+              }
+            }
+            Some(gst::MessageView::Error(err)) => {
+              let _ = pipeline.set_state(gst::State::Null);
+              let _ = std::fs::remove_file(&temp);
+              progress.state = "failed".to_string();
+              progress.error = Some(err.error().to_string());
+            }
+            _ => {}
+          }
+          progress.clone()
+        };
+        let finished = snapshot.state != "running";
+        if let Some(callback) = callback.lock().unwrap().as_ref() {
+          callback.call(snapshot, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+        if finished {
+          return;
+        }
+      }
+    }));
+    Ok(())
+  }
+
+  /// Stops the job and deletes the partially written output
+  ///
+  /// # Returns
+  /// * `bool` - Whether the job was pending or running
+  #[napi]
+  pub fn cancel(&self) -> bool {
+    let (snapshot, started) = {
+      let mut progress = self.progress.lock().unwrap();
+      if progress.state != "running" && progress.state != "pending" {
+        return false;
+      }
+      let started = progress.state == "running";
+      progress.state = "cancelled".to_string();
+      (progress.clone(), started)
+    };
+    self.cancelled.store(true, Ordering::SeqCst);
+    let _ = self.pipeline.set_state(gst::State::Null);
+    if started {
+      let _ = std::fs::remove_file(&self.temp);
+    }
+    if let Some(callback) = self.callback.lock().unwrap().as_ref() {
+      callback.call(snapshot, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+    true
+  }
+
+  /// Path the output is written to, which differs from the one asked for
+  /// when `overwrite` is "rename"
+  #[napi(getter)]
+  pub fn output_path(&self) -> String {
+    self.output.clone()
+  }
+
+  /// Returns the current progress of the job
+  #[napi]
+  pub fn get_progress(&self) -> TranscodeProgress {
+    self.progress.lock().unwrap().clone()
+  }
+
+  /// Checks if the job has ended, successfully or not
+  #[napi]
+  pub fn is_finished(&self) -> bool {
+    let progress = self.progress.lock().unwrap();
+    progress.state != "pending" && progress.state != "running"
+  }
+}
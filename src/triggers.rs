@@ -0,0 +1,665 @@
+//! # Recording Triggers
+//!
+//! Watches the frames of an AppSink for motion and scene changes and acts on
+//! them, following rules set from JavaScript: start recording when motion
+//! rises above a threshold and stop once the picture has been still for a
+//! while, or save a snapshot when the scene changes. Every file created is
+//! announced to the `onEvent` callback.
+//!
+//! Both measures compare each frame with the previous one on a grid of at
+//! most 64 luma samples across:
+//!
+//! - motion is the share of samples whose luma changed by more than 25
+//!   levels, from 0 to 1
+//! - a scene change is the distance between the 32 bin luma histograms of
+//!   the two frames, from 0 (same distribution) to 1 (nothing in common),
+//!   which stays low for movement within a scene
+//!
+//! The sink is set to receive I420 frames. Recordings are encoded as with
+//! `recordFromPipeline` and snapshots are saved as JPEG images.
+
+use crate::kit::GstKit;
+use crate::recorder::{RecordOptions, RecorderState};
+use crate::transcode::{launch, wait_for_eos};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Callback receiving the events of a `TriggerWatcher`
+type EventCallback = ThreadsafeFunction<TriggerEvent, (), TriggerEvent, Status, false, true>;
+
+/// Largest number of luma samples across a frame
+const GRID_WIDTH: usize = 64;
+
+/// Smallest luma change, in levels, of a sample counted as moving
+const MOTION_DELTA: i32 = 25;
+
+/// A condition and what to do when it holds
+#[napi(object)]
+pub struct TriggerRule {
+  /// What is measured: "motion" or "sceneChange"
+  pub when: String,
+  /// "record" or "snapshot"
+  pub action: String,
+  /// Score above which the rule fires (default: 0.02 for motion, 0.4 for
+  /// scene changes)
+  pub threshold: Option<f64>,
+  /// For "record", seconds without the rule firing after which the
+  /// recording stops (default: 10)
+  pub stop_after_idle: Option<f64>,
+  /// Seconds after firing during which the rule does not fire again
+  /// (default: 0)
+  pub cooldown: Option<f64>,
+}
+
+/// Options for `watchTriggers`
+#[napi(object)]
+pub struct TriggerOptions {
+  /// Rules evaluated on every frame, in order
+  pub rules: Vec<TriggerRule>,
+  /// Directory receiving the recordings ("recording-0001.webm", ...) and
+  /// snapshots ("snapshot-0001.jpg", ...); created if needed
+  pub output_dir: String,
+  /// Container, codec, bitrate and preset of the recordings (default: VP8 in WebM)
+  pub recording: Option<RecordOptions>,
+  /// JPEG quality of the snapshots from 1 to 100 (default: 85)
+  pub snapshot_quality: Option<u32>,
+}
+
+/// Something a `TriggerWatcher` did
+#[napi(object)]
+#[derive(Clone)]
+pub struct TriggerEvent {
+  /// "recording-started", "recording-stopped", "snapshot" or "error"
+  pub event_type: String,
+  /// Index of the rule that fired
+  pub rule: u32,
+  /// The file created
+  pub path: Option<String>,
+  /// Timestamp of the frame that fired the rule (or ended the recording),
+  /// in nanoseconds
+  pub timestamp: i64,
+  /// Score of that frame
+  pub score: f64,
+  /// Description of the error for "error" events
+  pub message: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Measure {
+  Motion,
+  SceneChange,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+  Record { idle: gst::ClockTime },
+  Snapshot,
+}
+
+struct Rule {
+  measure: Measure,
+  action: Action,
+  threshold: f64,
+  cooldown: gst::ClockTime,
+  last_fired: Option<gst::ClockTime>,
+}
+
+/// Seconds given in an option as a duration
+fn seconds(value: f64, name: &str) -> Result<gst::ClockTime> {
+  if !value.is_finite() || value < 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid {}: {}", name, value),
+    ));
+  }
+  Ok(gst::ClockTime::from_nseconds((value * 1e9) as u64))
+}
+
+impl Rule {
+  fn parse(rule: TriggerRule) -> Result<Self> {
+    let measure = match rule.when.as_str() {
+      "motion" => Measure::Motion,
+      "sceneChange" => Measure::SceneChange,
+      when => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "Unsupported trigger: {} (use \"motion\" or \"sceneChange\")",
+            when
+          ),
+        ))
+      }
+    };
+    let action = match rule.action.as_str() {
+      "record" => Action::Record {
+        idle: seconds(rule.stop_after_idle.unwrap_or(10.0), "stopAfterIdle")?,
+      },
+      "snapshot" => Action::Snapshot,
+      action => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "Unsupported trigger action: {} (use \"record\" or \"snapshot\")",
+            action
+          ),
+        ))
+      }
+    };
+    Ok(Rule {
+      measure,
+      action,
+      threshold: rule.threshold.unwrap_or(match measure {
+        Measure::Motion => 0.02,
+        Measure::SceneChange => 0.4,
+      }),
+      cooldown: seconds(rule.cooldown.unwrap_or(0.0), "cooldown")?,
+      last_fired: None,
+    })
+  }
+}
+
+/// Luma samples of an I420 (or other planar YUV or gray) frame, on a grid of
+/// at most `GRID_WIDTH` columns
+fn luma_grid(sample: &gst::Sample) -> Option<Vec<u8>> {
+  let info = gst_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+  if !(info.is_gray() || (info.is_yuv() && info.n_planes() > 1)) {
+    return None;
+  }
+  let buffer = sample.buffer()?;
+  let map = buffer.map_readable().ok()?;
+  let (offset, stride) = match buffer.meta::<gst_video::VideoMeta>() {
+    Some(meta) => (meta.offset()[0], meta.stride()[0] as usize),
+    None => (info.offset()[0], info.stride()[0] as usize),
+  };
+  let (width, height) = (info.width() as usize, info.height() as usize);
+  let step = width.div_ceil(GRID_WIDTH).max(1);
+  let mut grid = Vec::new();
+  for y in (0..height).step_by(step) {
+    for x in (0..width).step_by(step) {
+      grid.push(*map.get(offset + y * stride + x)?);
+    }
+  }
+  Some(grid)
+}
+
+/// Share of the samples that changed by more than `MOTION_DELTA`
+fn motion_score(previous: &[u8], current: &[u8]) -> f64 {
+  let moved = previous
+    .iter()
+    .zip(current)
+    .filter(|(a, b)| (**a as i32 - **b as i32).abs() > MOTION_DELTA)
+    .count();
+  moved as f64 / current.len().max(1) as f64
+}
+
+/// Distance between the 32 bin luma histograms of two grids, from 0 to 1
+fn scene_score(previous: &[u8], current: &[u8]) -> f64 {
+  let histogram = |grid: &[u8]| {
+    let mut bins = [0usize; 32];
+    for value in grid {
+      bins[*value as usize / 8] += 1;
+    }
+    bins
+  };
+  let (a, b) = (histogram(previous), histogram(current));
+  let difference: usize = a.iter().zip(&b).map(|(a, b)| a.abs_diff(*b)).sum();
+  difference as f64 / (2 * current.len().max(1)) as f64
+}
+
+/// The recording in progress
+struct Active {
+  recorder: RecorderState,
+  rule: usize,
+  path: String,
+  /// Timestamp of the last frame that fired its rule
+  last_trigger: gst::ClockTime,
+}
+
+/// Snapshot being encoded: path, rule, timestamp and score
+type PendingSnapshot = (String, usize, i64, f64);
+
+/// JPEG encoding pipeline of the snapshots
+struct Snapshots {
+  pipeline: gst::Pipeline,
+  appsrc: AppSrc,
+  pending: Arc<Mutex<VecDeque<PendingSnapshot>>>,
+}
+
+struct Watch {
+  rules: Vec<Rule>,
+  output_dir: PathBuf,
+  recording: RecordOptions,
+  extension: String,
+  previous: Option<Vec<u8>>,
+  active: Option<Active>,
+  recordings: u32,
+  snapshot_count: u32,
+  snapshots: Option<Snapshots>,
+  callback: Arc<Mutex<Option<EventCallback>>>,
+}
+
+fn emit(callback: &Mutex<Option<EventCallback>>, event: TriggerEvent) {
+  if let Some(callback) = callback.lock().unwrap().as_ref() {
+    callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+  }
+}
+
+/// Finishes a recording, announcing the file or the failure
+fn stop_recording(
+  mut active: Active,
+  timestamp: i64,
+  score: f64,
+  callback: &Mutex<Option<EventCallback>>,
+) {
+  let result = active.recorder.finish(&active.path);
+  emit(
+    callback,
+    TriggerEvent {
+      event_type: if result.is_ok() {
+        "recording-stopped"
+      } else {
+        "error"
+      }
+      .to_string(),
+      rule: active.rule as u32,
+      path: Some(active.path),
+      timestamp,
+      score,
+      message: result.err().map(|e| e.reason),
+    },
+  );
+}
+
+impl Watch {
+  fn event(&self, event_type: &str, rule: usize, path: &str, timestamp: i64, score: f64) {
+    emit(
+      &self.callback,
+      TriggerEvent {
+        event_type: event_type.to_string(),
+        rule: rule as u32,
+        path: Some(path.to_string()),
+        timestamp,
+        score,
+        message: None,
+      },
+    );
+  }
+
+  fn error(&self, rule: usize, path: &str, timestamp: i64, message: String) {
+    emit(
+      &self.callback,
+      TriggerEvent {
+        event_type: "error".to_string(),
+        rule: rule as u32,
+        path: Some(path.to_string()),
+        timestamp,
+        score: 0.0,
+        message: Some(message),
+      },
+    );
+  }
+
+  fn fire(&mut self, index: usize, sample: &gst::Sample, pts: gst::ClockTime, score: f64) {
+    let timestamp = pts.nseconds() as i64;
+    match self.rules[index].action {
+      Action::Record { .. } => {
+        if let Some(active) = &mut self.active {
+          active.last_trigger = pts;
+          return;
+        }
+        self.recordings += 1;
+        let path = self
+          .output_dir
+          .join(format!(
+            "recording-{:04}.{}",
+            self.recordings, self.extension
+          ))
+          .to_string_lossy()
+          .into_owned();
+        match RecorderState::create(path.clone(), self.recording.clone()) {
+          Ok(recorder) => {
+            self.event("recording-started", index, &path, timestamp, score);
+            self.active = Some(Active {
+              recorder,
+              rule: index,
+              path,
+              last_trigger: pts,
+            });
+          }
+          Err(e) => self.error(index, &path, timestamp, e.reason),
+        }
+      }
+      Action::Snapshot => {
+        let Some(snapshots) = &self.snapshots else {
+          return;
+        };
+        self.snapshot_count += 1;
+        let path = self
+          .output_dir
+          .join(format!("snapshot-{:04}.jpg", self.snapshot_count))
+          .to_string_lossy()
+          .into_owned();
+        snapshots
+          .pending
+          .lock()
+          .unwrap()
+          .push_back((path.clone(), index, timestamp, score));
+        if let Err(e) = snapshots.appsrc.push_sample(sample) {
+          snapshots.pending.lock().unwrap().pop_back();
+          self.error(index, &path, timestamp, format!("Snapshot failed: {:?}", e));
+        }
+      }
+    }
+  }
+
+  fn frame(&mut self, sample: &gst::Sample) {
+    let Some(grid) = luma_grid(sample) else {
+      return;
+    };
+    let pts = sample
+      .buffer()
+      .and_then(|buffer| buffer.pts())
+      .unwrap_or(gst::ClockTime::ZERO);
+    let previous = self.previous.replace(grid);
+    let (Some(previous), Some(current)) = (previous, &self.previous) else {
+      return self.record(sample, pts, 0.0);
+    };
+    let (motion, scene) = if previous.len() == current.len() {
+      (
+        motion_score(&previous, current),
+        scene_score(&previous, current),
+      )
+    } else {
+      (1.0, 1.0)
+    };
+
+    let mut recording_score = 0.0;
+    for index in 0..self.rules.len() {
+      let rule = &self.rules[index];
+      let score = match rule.measure {
+        Measure::Motion => motion,
+        Measure::SceneChange => scene,
+      };
+      if matches!(rule.action, Action::Record { .. }) {
+        recording_score = f64::max(recording_score, score);
+      }
+      let cooling = rule
+        .last_fired
+        .is_some_and(|fired| pts < fired + rule.cooldown);
+      if score > rule.threshold && !cooling {
+        self.rules[index].last_fired = Some(pts);
+        self.fire(index, sample, pts, score);
+      }
+    }
+    self.record(sample, pts, recording_score);
+  }
+
+  /// Adds the frame to the recording in progress, stopping it once its rule
+  /// has been idle long enough
+  fn record(&mut self, sample: &gst::Sample, pts: gst::ClockTime, score: f64) {
+    let Some(active) = &mut self.active else {
+      return;
+    };
+    let Action::Record { idle } = self.rules[active.rule].action else {
+      return;
+    };
+    let pushed = active.recorder.push(sample);
+    if pushed.is_ok() && pts < active.last_trigger + idle {
+      return;
+    }
+    let Some(active) = self.active.take() else {
+      return;
+    };
+    if let Err(message) = pushed {
+      self.error(active.rule, &active.path, pts.nseconds() as i64, message);
+    }
+    // Draining the encoder takes a while; the watched pipeline goes on
+    let callback = self.callback.clone();
+    std::thread::spawn(move || stop_recording(active, pts.nseconds() as i64, score, &callback));
+  }
+}
+
+/// Encodes the frames pushed into it as JPEG files, in order
+fn snapshot_pipeline(
+  quality: u32,
+  callback: Arc<Mutex<Option<EventCallback>>>,
+) -> Result<Snapshots> {
+  let pipeline = launch(&format!(
+    "appsrc name=src format=time ! videoconvert ! jpegenc quality={} ! appsink name=sink sync=false",
+    quality.clamp(1, 100)
+  ))?;
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Snapshot source not found"))?;
+  let appsink = pipeline
+    .by_name("sink")
+    .and_then(|el| el.downcast::<AppSink>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Snapshot sink not found"))?;
+  let pending: Arc<Mutex<VecDeque<PendingSnapshot>>> = Arc::new(Mutex::new(VecDeque::new()));
+  let images = pending.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        let Some((path, rule, timestamp, score)) = images.lock().unwrap().pop_front() else {
+          return Ok(gst::FlowSuccess::Ok);
+        };
+        let written = sample
+          .buffer()
+          .ok_or_else(|| "Snapshot has no buffer".to_string())
+          .and_then(|buffer| buffer.map_readable().map_err(|e| e.to_string()))
+          .and_then(|map| fs::write(&path, &map).map_err(|e| e.to_string()));
+        emit(
+          &callback,
+          TriggerEvent {
+            event_type: if written.is_ok() { "snapshot" } else { "error" }.to_string(),
+            rule: rule as u32,
+            path: Some(path),
+            timestamp,
+            score,
+            message: written.err(),
+          },
+        );
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  Ok(Snapshots {
+    pipeline,
+    appsrc,
+    pending,
+  })
+}
+
+/// Motion and scene change rules running on an AppSink, created by
+/// `watchTriggers`
+#[napi]
+pub struct TriggerWatcher {
+  appsink: AppSink,
+  watch: Arc<Mutex<Watch>>,
+}
+
+#[napi]
+impl TriggerWatcher {
+  /// Sets the callback announcing recordings, snapshots and errors
+  ///
+  /// # Arguments
+  /// * `callback` - Called with every event
+  ///
+  /// # Example
+  /// ```javascript
+  /// watcher.onEvent((event) => {
+  ///   if (event.eventType === "recording-stopped") upload(event.path);
+  /// });
+  /// ```
+  #[napi]
+  pub fn on_event(
+    &self,
+    callback: ThreadsafeFunction<TriggerEvent, (), TriggerEvent, Status, false, true>,
+  ) {
+    let watch = self.watch.lock().unwrap();
+    *watch.callback.lock().unwrap() = Some(callback);
+  }
+
+  /// Whether a recording is in progress
+  #[napi(getter)]
+  pub fn recording(&self) -> bool {
+    self.watch.lock().unwrap().active.is_some()
+  }
+
+  /// Stops watching the sink, finishing the recording in progress and the
+  /// pending snapshots
+  ///
+  /// # Example
+  /// ```javascript
+  /// watcher.stop();
+  /// ```
+  #[napi]
+  pub fn stop(&self) {
+    self
+      .appsink
+      .set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    let mut watch = self.watch.lock().unwrap();
+    if let Some(active) = watch.active.take() {
+      let timestamp = active.last_trigger.nseconds() as i64;
+      stop_recording(active, timestamp, 0.0, &watch.callback);
+    }
+    if let Some(snapshots) = watch.snapshots.take() {
+      let _ = snapshots.appsrc.end_of_stream();
+      let _ = wait_for_eos(&snapshots.pipeline);
+      let _ = snapshots.pipeline.set_state(gst::State::Null);
+    }
+  }
+}
+
+impl Drop for TriggerWatcher {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Runs motion and scene change rules on the frames of an AppSink
+///
+/// The sink is set to receive I420 frames, and its callbacks are taken over
+/// until the watcher is stopped.
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `sink_name` - The name of the AppSink element
+/// * `options` - Rules, output directory and recording settings
+///
+/// # Returns
+/// * `Result<TriggerWatcher>` - The running watcher
+///
+/// # Example
+/// ```javascript
+/// const watcher = watchTriggers(kit, "sink", {
+///   outputDir: "captures",
+///   rules: [
+///     { when: "motion", action: "record", threshold: 0.02, stopAfterIdle: 10 },
+///     { when: "sceneChange", action: "snapshot" },
+///   ],
+/// });
+/// watcher.onEvent((event) => console.log(event.eventType, event.path));
+/// kit.play();
+/// ```
+#[napi]
+pub fn watch_triggers(
+  kit: &GstKit,
+  sink_name: String,
+  options: TriggerOptions,
+) -> Result<TriggerWatcher> {
+  let rules = options
+    .rules
+    .into_iter()
+    .map(Rule::parse)
+    .collect::<Result<Vec<_>>>()?;
+  if rules.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "At least one trigger rule is required".to_string(),
+    ));
+  }
+  let recording = options.recording.unwrap_or(RecordOptions {
+    container: None,
+    video_codec: None,
+    video_bitrate: None,
+    preset: None,
+    tune: None,
+  });
+  let extension = recording
+    .container
+    .clone()
+    .unwrap_or_else(|| "webm".to_string());
+  if !matches!(extension.as_str(), "ivf" | "webm") {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Unsupported recording container: \"{}\" (use \"ivf\" or \"webm\")",
+        extension
+      ),
+    ));
+  }
+  fs::create_dir_all(&options.output_dir).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to create {}: {}", options.output_dir, e),
+    )
+  })?;
+
+  let appsink = kit.app_sink(&sink_name)?;
+  kit.force_sink_format(&sink_name, "I420")?;
+  let callback = Arc::new(Mutex::new(None));
+  let snapshots = if rules
+    .iter()
+    .any(|rule| matches!(rule.action, Action::Snapshot))
+  {
+    Some(snapshot_pipeline(
+      options.snapshot_quality.unwrap_or(85),
+      callback.clone(),
+    )?)
+  } else {
+    None
+  };
+  let watch = Arc::new(Mutex::new(Watch {
+    rules,
+    output_dir: Path::new(&options.output_dir).to_path_buf(),
+    recording,
+    extension,
+    previous: None,
+    active: None,
+    recordings: 0,
+    snapshot_count: 0,
+    snapshots,
+    callback,
+  }));
+
+  let frames = watch.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        frames.lock().unwrap().frame(&sample);
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+  Ok(TriggerWatcher { appsink, watch })
+}
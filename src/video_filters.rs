@@ -0,0 +1,704 @@
+//! # Video Filters
+//!
+//! A small filter chain for decoded video, shared by the transcode video
+//! branch and `overlayVideo`. Filters are given as `name=value` strings and
+//! applied in order:
+//!
+//! - `chromakey=#00FF00:0.1:0.05` - make pixels close to a color transparent.
+//!   The color is "#RRGGBB", "0xRRGGBB" or a name; the optional similarity
+//!   (default 0.01) is the chroma distance, from 0 to 1, that is keyed out
+//!   completely, and the optional blend (default 0) widens that into a soft
+//!   edge where pixels are partly transparent.
+//! - `lut3d=grade.cube` - color grade with a 3D LUT in the Adobe/Resolve
+//!   `.cube` format, interpolated trilinearly between its grid points
+//! - `boxblur=8` - blur with a box of the given radius in pixels
+//! - `pixelate=16` - replace blocks of the given size by their average color
+//!
+//! - `subtitles=movie.srt` - burn in the cues of a SubRip file at their
+//!   times (see the `subtitles` module for how they are laid out)
+//! - `crop=0,140,1920,800` - keep the rectangle `x,y,width,height`, such as
+//!   the one `detectCropRegion` suggests to remove letterboxing
+//! - `ivtc` - inverse telecine: rebuild the progressive film frames of 3:2
+//!   pulldown content, turning 29.97 fps back into 23.976 fps
+//! - `deinterlace` - deinterlace every frame, for interlaced content not
+//!   flagged as such
+//!
+//! `detectInterlacing` tells which of the last two a video needs.
+//!
+//! `boxblur` and `pixelate` can be limited to regions, e.g. to redact faces or
+//! number plates, by following the size with `:` and a `;`-separated list of
+//! rectangles `x,y,width,height`:
+//!
+//! - `pixelate=16:40,40,120,80` - always pixelate one rectangle
+//! - `boxblur=12:0,0,200,100@1-3` - blur a rectangle from 1 to 3 seconds
+//! - `pixelate=16:100,80,64,64@0>180,80,64,64@2` - move a rectangle from one
+//!   position at 0 seconds to another at 2 seconds; a region with keyframes
+//!   is active from its first keyframe to its last and moves linearly
+//!   between them
+//!
+//! Keying compares chroma only, so shadows and highlights on the screen are
+//! keyed like its flat color. It runs on AYUV frames: 4:2:0 inputs have their
+//! chroma upsampled first, which gives every pixel its own alpha instead of
+//! one per 2x2 block sharing a chroma sample. Encoders without alpha support
+//! drop the transparency, so a key is mostly useful before compositing.
+
+use crate::audio_filters::parse_seconds;
+use crate::slate::parse_color;
+use crate::subtitles::{parse_srt, Cue, SubtitleRenderer};
+use crate::transcode::make_element;
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use std::fs;
+use std::sync::Arc;
+
+/// A parsed video filter
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum VideoFilter {
+  ChromaKey {
+    color: [u8; 3],
+    similarity: f64,
+    blend: f64,
+  },
+  Lut3d(Arc<Lut3d>),
+  BoxBlur {
+    radius: u32,
+    regions: Vec<MaskRegion>,
+  },
+  Pixelate {
+    size: u32,
+    regions: Vec<MaskRegion>,
+  },
+  Subtitles(Arc<Vec<Cue>>),
+  Crop {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+  },
+  InverseTelecine,
+  Deinterlace,
+}
+
+/// A rectangle `[x, y, width, height]` in pixels
+type Rect = [f64; 4];
+
+/// A rectangle a filter is limited to, possibly moving or only active for a
+/// time range
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MaskRegion {
+  /// Positions at increasing stream times, interpolated linearly
+  keyframes: Vec<(gst::ClockTime, Rect)>,
+  start: gst::ClockTime,
+  /// End of the active range; `None` for regions active until the end
+  end: Option<gst::ClockTime>,
+}
+
+impl MaskRegion {
+  /// Rectangle covered at `position`, if the region is active then
+  fn rect_at(&self, position: gst::ClockTime) -> Option<Rect> {
+    if position < self.start || self.end.is_some_and(|end| position > end) {
+      return None;
+    }
+    let next = self
+      .keyframes
+      .iter()
+      .position(|(time, _)| *time > position)
+      .unwrap_or(self.keyframes.len());
+    let (before_time, before) = self.keyframes[next.saturating_sub(1)];
+    let Some(&(after_time, after)) = self.keyframes.get(next).filter(|_| next > 0) else {
+      return Some(before);
+    };
+    let t =
+      (position - before_time).nseconds() as f64 / (after_time - before_time).nseconds() as f64;
+    Some(std::array::from_fn(|i| {
+      before[i] + (after[i] - before[i]) * t
+    }))
+  }
+}
+
+fn parse_rect(value: &str) -> Option<Rect> {
+  let values: Vec<f64> = value
+    .split(',')
+    .map(|v| v.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+    .collect::<Option<_>>()?;
+  match values[..] {
+    [x, y, width, height] if width > 0.0 && height > 0.0 => Some([x, y, width, height]),
+    _ => None,
+  }
+}
+
+/// Parses `rect`, `rect@start-end` or `rect@time>rect@time...`
+fn parse_region(value: &str) -> Option<MaskRegion> {
+  let keyframes: Vec<&str> = value.split('>').collect();
+  if let [single] = keyframes[..] {
+    return match single.split_once('@') {
+      None => Some(MaskRegion {
+        keyframes: vec![(gst::ClockTime::ZERO, parse_rect(single)?)],
+        start: gst::ClockTime::ZERO,
+        end: None,
+      }),
+      Some((rect, range)) => {
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (parse_seconds(start)?, parse_seconds(end)?);
+        (end > start).then_some(MaskRegion {
+          keyframes: vec![(start, parse_rect(rect)?)],
+          start,
+          end: Some(end),
+        })
+      }
+    };
+  }
+  let keyframes: Vec<(gst::ClockTime, Rect)> = keyframes
+    .iter()
+    .map(|keyframe| {
+      let (rect, time) = keyframe.split_once('@')?;
+      Some((parse_seconds(time)?, parse_rect(rect)?))
+    })
+    .collect::<Option<_>>()?;
+  if keyframes.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+    return None;
+  }
+  Some(MaskRegion {
+    start: keyframes[0].0,
+    end: keyframes.last().map(|(time, _)| *time),
+    keyframes,
+  })
+}
+
+/// Parses `size` or `size:region;region...` of a regional filter
+fn parse_regional(
+  value: &str,
+  sizes: std::ops::RangeInclusive<u32>,
+) -> Option<(u32, Vec<MaskRegion>)> {
+  let (size, regions) = value.split_once(':').unwrap_or((value, ""));
+  let size = size
+    .trim()
+    .parse()
+    .ok()
+    .filter(|size| sizes.contains(size))?;
+  let regions = regions
+    .split(';')
+    .filter(|region| !region.trim().is_empty())
+    .map(parse_region)
+    .collect::<Option<_>>()?;
+  Some((size, regions))
+}
+
+/// A 3D color lookup table read from a `.cube` file
+#[derive(Debug, PartialEq)]
+pub(crate) struct Lut3d {
+  /// Grid points per axis
+  size: usize,
+  /// Output colors, red varying fastest, then green, then blue
+  table: Vec<[f32; 3]>,
+  domain_min: [f32; 3],
+  domain_max: [f32; 3],
+}
+
+/// Reads a `.cube` file: keywords, `#` comments and one "R G B" line per
+/// grid point
+pub(crate) fn load_cube(path: &str) -> Result<Lut3d> {
+  let text = fs::read_to_string(path).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read LUT {}: {}", path, e),
+    )
+  })?;
+  let invalid = |line: usize, reason: &str| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid LUT {} (line {}): {}", path, line, reason),
+    )
+  };
+  let triple = |words: &[&str]| -> Option<[f32; 3]> {
+    match words {
+      [r, g, b] => Some([r.parse().ok()?, g.parse().ok()?, b.parse().ok()?]),
+      _ => None,
+    }
+  };
+
+  let mut size = None;
+  let mut domain_min = [0.0; 3];
+  let mut domain_max = [1.0; 3];
+  let mut table = Vec::new();
+  for (index, line) in text.lines().enumerate() {
+    let number = index + 1;
+    let line = line.split('#').next().unwrap_or_default().trim();
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.first().copied() {
+      None | Some("TITLE") => {}
+      Some("LUT_3D_SIZE") => {
+        size = Some(
+          words
+            .get(1)
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|n| (2..=256).contains(n))
+            .ok_or_else(|| invalid(number, "LUT_3D_SIZE must be from 2 to 256"))?,
+        );
+      }
+      Some("LUT_1D_SIZE") => return Err(invalid(number, "1D LUTs are not supported")),
+      Some("DOMAIN_MIN") => {
+        domain_min = triple(&words[1..]).ok_or_else(|| invalid(number, "expected 3 numbers"))?
+      }
+      Some("DOMAIN_MAX") => {
+        domain_max = triple(&words[1..]).ok_or_else(|| invalid(number, "expected 3 numbers"))?
+      }
+      Some(_) => table.push(triple(&words).ok_or_else(|| invalid(number, "expected \"R G B\""))?),
+    }
+  }
+  let size = size.ok_or_else(|| invalid(0, "missing LUT_3D_SIZE"))?;
+  if table.len() != size * size * size {
+    return Err(invalid(
+      0,
+      &format!(
+        "expected {} entries, found {}",
+        size * size * size,
+        table.len()
+      ),
+    ));
+  }
+  if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+    return Err(invalid(0, "DOMAIN_MAX must be above DOMAIN_MIN"));
+  }
+  Ok(Lut3d {
+    size,
+    table,
+    domain_min,
+    domain_max,
+  })
+}
+
+impl Lut3d {
+  /// Grid cell and position in it of every 8-bit value of each channel
+  fn cells(&self) -> [Vec<(usize, f32)>; 3] {
+    let last = (self.size - 1) as f32;
+    std::array::from_fn(|c| {
+      (0..=255u8)
+        .map(|value| {
+          let t =
+            (value as f32 / 255.0 - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]);
+          let t = t.clamp(0.0, 1.0) * last;
+          let cell = (t.floor() as usize).min(self.size - 2);
+          (cell, t - cell as f32)
+        })
+        .collect()
+    })
+  }
+
+  /// Trilinear interpolation between the eight grid points around a color
+  fn apply(&self, cells: &[Vec<(usize, f32)>; 3], rgb: [u8; 3]) -> [u8; 3] {
+    let (r, fr) = cells[0][rgb[0] as usize];
+    let (g, fg) = cells[1][rgb[1] as usize];
+    let (b, fb) = cells[2][rgb[2] as usize];
+    let at = |r: usize, g: usize, b: usize| self.table[(b * self.size + g) * self.size + r];
+    let lerp = |a: [f32; 3], b: [f32; 3], t: f32| std::array::from_fn(|c| a[c] + (b[c] - a[c]) * t);
+    let c00 = lerp(at(r, g, b), at(r + 1, g, b), fr);
+    let c10 = lerp(at(r, g + 1, b), at(r + 1, g + 1, b), fr);
+    let c01 = lerp(at(r, g, b + 1), at(r + 1, g, b + 1), fr);
+    let c11 = lerp(at(r, g + 1, b + 1), at(r + 1, g + 1, b + 1), fr);
+    let color: [f32; 3] = lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb);
+    color.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+  }
+}
+
+fn invalid(filter: &str) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!("Invalid video filter: {}", filter),
+  )
+}
+
+/// Parses an optional fraction from 0 to 1
+fn parse_fraction(value: Option<&str>, default: f64) -> Option<f64> {
+  match value {
+    None => Some(default),
+    Some(value) => value
+      .trim()
+      .parse::<f64>()
+      .ok()
+      .filter(|v| (0.0..=1.0).contains(v)),
+  }
+}
+
+/// Parses a list of `name=value` (or bare `name`) filter strings
+pub(crate) fn parse_video_filters(filters: &[String]) -> Result<Vec<VideoFilter>> {
+  filters
+    .iter()
+    .map(|filter| {
+      let (name, value) = filter.split_once('=').unwrap_or((filter.as_str(), ""));
+      let parsed = match name.trim() {
+        "chromakey" => {
+          let mut args = value.split(':');
+          let color = args
+            .next()
+            .map(|color| color.trim())
+            .map(|color| color.strip_prefix("0x").unwrap_or(color))
+            .and_then(|color| parse_color(color).ok());
+          let similarity = parse_fraction(args.next(), 0.01).filter(|s| *s > 0.0);
+          let blend = parse_fraction(args.next(), 0.0);
+          match (color, similarity, blend, args.next()) {
+            (Some(color), Some(similarity), Some(blend), None) => Some(VideoFilter::ChromaKey {
+              color,
+              similarity,
+              blend,
+            }),
+            _ => None,
+          }
+        }
+        "boxblur" => parse_regional(value, 1..=255)
+          .map(|(radius, regions)| VideoFilter::BoxBlur { radius, regions }),
+        "pixelate" => parse_regional(value, 2..=512)
+          .map(|(size, regions)| VideoFilter::Pixelate { size, regions }),
+        "lut3d" if !value.trim().is_empty() => {
+          return Ok(VideoFilter::Lut3d(Arc::new(load_cube(value.trim())?)))
+        }
+        "subtitles" if !value.trim().is_empty() => {
+          return Ok(VideoFilter::Subtitles(Arc::new(parse_srt(value.trim())?)))
+        }
+        "ivtc" if value.is_empty() => Some(VideoFilter::InverseTelecine),
+        "deinterlace" if value.is_empty() => Some(VideoFilter::Deinterlace),
+        "crop" => parse_rect(value)
+          .filter(|rect| rect.iter().all(|v| v.fract() == 0.0 && *v >= 0.0))
+          .map(|[x, y, width, height]| VideoFilter::Crop {
+            x: x as u32,
+            y: y as u32,
+            width: width as u32,
+            height: height as u32,
+          }),
+        _ => None,
+      };
+      parsed.ok_or_else(|| invalid(filter))
+    })
+    .collect()
+}
+
+/// Cb and Cr of an RGB color in limited range BT.601, the colorimetry the
+/// keyed frames are converted to
+fn chroma(color: [u8; 3]) -> (f64, f64) {
+  let [r, g, b] = color.map(f64::from);
+  (
+    128.0 + (-37.797 * r - 74.203 * g + 112.0 * b) / 255.0,
+    128.0 + (112.0 * r - 93.786 * g - 18.214 * b) / 255.0,
+  )
+}
+
+/// Alpha of every (Cb, Cr) pair, indexed by `cb << 8 | cr`
+fn key_table(color: [u8; 3], similarity: f64, blend: f64) -> Vec<u8> {
+  let (key_cb, key_cr) = chroma(color);
+  (0..=u16::MAX)
+    .map(|index| {
+      let (cb, cr) = ((index >> 8) as f64, (index & 0xff) as f64);
+      let distance =
+        (((cb - key_cb).powi(2) + (cr - key_cr).powi(2)) / (2.0 * 255.0 * 255.0)).sqrt();
+      let alpha = if blend > 0.0 {
+        ((distance - similarity) / blend).clamp(0.0, 1.0)
+      } else if distance > similarity {
+        1.0
+      } else {
+        0.0
+      };
+      (alpha * 255.0).round() as u8
+    })
+    .collect()
+}
+
+/// A mapped four byte per pixel frame
+struct Frame<'a> {
+  data: &'a mut [u8],
+  stride: usize,
+  width: usize,
+  height: usize,
+}
+
+impl Frame<'_> {
+  fn for_each_pixel(&mut self, edit: impl Fn(&mut [u8])) {
+    let row_bytes = self.width * 4;
+    for row in self.data.chunks_mut(self.stride).take(self.height) {
+      let row_len = row_bytes.min(row.len());
+      row[..row_len].chunks_exact_mut(4).for_each(&edit);
+    }
+  }
+}
+
+/// Creates a converter to `caps` (a four byte per pixel format) followed by a
+/// caps filter whose output frames are passed to `edit` with their stream
+/// time, to be modified in place
+fn frame_filter(
+  caps: gst::Caps,
+  edit: impl Fn(&mut Frame, gst::ClockTime) + Send + Sync + 'static,
+) -> Result<Vec<gst::Element>> {
+  let capsfilter = make_element("capsfilter")?;
+  capsfilter.set_property("caps", caps);
+  let pad = capsfilter
+    .static_pad("src")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Capsfilter has no src pad"))?;
+  pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+    let Some(video_info) = pad
+      .current_caps()
+      .and_then(|caps| gst_video::VideoInfo::from_caps(&caps).ok())
+    else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let Some(buffer) = info.buffer_mut() else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let pts = buffer.pts().unwrap_or(gst::ClockTime::ZERO);
+    let position = pad
+      .sticky_event::<gst::event::Segment>(0)
+      .and_then(|event| {
+        event
+          .segment()
+          .downcast_ref::<gst::ClockTime>()
+          .and_then(|segment| segment.to_stream_time(pts))
+      })
+      .unwrap_or(pts);
+    let Ok(mut mapped) =
+      gst_video::VideoFrameRef::from_buffer_ref_writable(buffer.make_mut(), &video_info)
+    else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let Ok(data) = mapped.plane_data_mut(0) else {
+      return gst::PadProbeReturn::Ok;
+    };
+    let mut frame = Frame {
+      data,
+      stride: video_info.stride()[0] as usize,
+      width: video_info.width() as usize,
+      height: video_info.height() as usize,
+    };
+    edit(&mut frame, position);
+    gst::PadProbeReturn::Ok
+  });
+  Ok(vec![make_element("videoconvert")?, capsfilter])
+}
+
+/// A `frame_filter` applying `edit` to every pixel
+fn pixel_filter(
+  caps: gst::Caps,
+  edit: impl Fn(&mut [u8]) + Send + Sync + 'static,
+) -> Result<Vec<gst::Element>> {
+  frame_filter(caps, move |frame, _| frame.for_each_pixel(&edit))
+}
+
+/// Keys on AYUV frames, scaling the alpha of each pixel by its chroma distance
+fn chroma_key(color: [u8; 3], similarity: f64, blend: f64) -> Result<Vec<gst::Element>> {
+  let table = key_table(color, similarity, blend);
+  pixel_filter(
+    gst::Caps::builder("video/x-raw")
+      .field("format", "AYUV")
+      .field("colorimetry", "bt601")
+      .build(),
+    move |pixel| {
+      let key = table[(pixel[2] as usize) << 8 | pixel[3] as usize];
+      pixel[0] = ((pixel[0] as u16 * key as u16 + 127) / 255) as u8;
+    },
+  )
+}
+
+/// Grades RGBA frames through a LUT, keeping their alpha
+fn lut3d(lut: Arc<Lut3d>) -> Result<Vec<gst::Element>> {
+  let cells = lut.cells();
+  pixel_filter(
+    gst::Caps::builder("video/x-raw")
+      .field("format", "RGBA")
+      .build(),
+    move |pixel| {
+      let graded = lut.apply(&cells, [pixel[0], pixel[1], pixel[2]]);
+      pixel[..3].copy_from_slice(&graded);
+    },
+  )
+}
+
+/// Pixel bounds `(x0, y0, x1, y1)` of a rectangle clipped to the frame
+fn clip(frame: &Frame, rect: Rect) -> Option<(usize, usize, usize, usize)> {
+  let [x, y, width, height] = rect;
+  let x0 = x.round().clamp(0.0, frame.width as f64) as usize;
+  let y0 = y.round().clamp(0.0, frame.height as f64) as usize;
+  let x1 = (x + width).round().clamp(0.0, frame.width as f64) as usize;
+  let y1 = (y + height).round().clamp(0.0, frame.height as f64) as usize;
+  (x1 > x0 && y1 > y0).then_some((x0, y0, x1, y1))
+}
+
+/// Rectangles to filter at `position`: the active regions, or the whole
+/// frame when there are none
+fn active_rects(frame: &Frame, regions: &[MaskRegion], position: gst::ClockTime) -> Vec<Rect> {
+  if regions.is_empty() {
+    return vec![[0.0, 0.0, frame.width as f64, frame.height as f64]];
+  }
+  regions
+    .iter()
+    .filter_map(|region| region.rect_at(position))
+    .collect()
+}
+
+/// Replaces each of `len` values, `stride` bytes apart from `start`, by the
+/// average of the values within `radius` of it
+fn blur_line(
+  data: &mut [u8],
+  start: usize,
+  stride: usize,
+  len: usize,
+  radius: usize,
+  sums: &mut Vec<u32>,
+) {
+  sums.clear();
+  sums.push(0);
+  for i in 0..len {
+    let sum = sums[i] + data[start + i * stride] as u32;
+    sums.push(sum);
+  }
+  for i in 0..len {
+    let (lo, hi) = (i.saturating_sub(radius), (i + radius + 1).min(len));
+    data[start + i * stride] = ((sums[hi] - sums[lo]) / (hi - lo) as u32) as u8;
+  }
+}
+
+/// Blurs RGBA frames inside the active regions, horizontally then vertically
+fn box_blur(radius: u32, regions: Vec<MaskRegion>) -> Result<Vec<gst::Element>> {
+  let radius = radius as usize;
+  frame_filter(
+    gst::Caps::builder("video/x-raw")
+      .field("format", "RGBA")
+      .build(),
+    move |frame, position| {
+      let mut sums = Vec::new();
+      for rect in active_rects(frame, &regions, position) {
+        let Some((x0, y0, x1, y1)) = clip(frame, rect) else {
+          continue;
+        };
+        for channel in 0..4 {
+          for y in y0..y1 {
+            let start = y * frame.stride + x0 * 4 + channel;
+            blur_line(frame.data, start, 4, x1 - x0, radius, &mut sums);
+          }
+          for x in x0..x1 {
+            let start = y0 * frame.stride + x * 4 + channel;
+            blur_line(frame.data, start, frame.stride, y1 - y0, radius, &mut sums);
+          }
+        }
+      }
+    },
+  )
+}
+
+/// Fills blocks of RGBA frames inside the active regions with their average
+/// color; blocks start at the region's top left corner
+fn pixelate(size: u32, regions: Vec<MaskRegion>) -> Result<Vec<gst::Element>> {
+  let size = size as usize;
+  frame_filter(
+    gst::Caps::builder("video/x-raw")
+      .field("format", "RGBA")
+      .build(),
+    move |frame, position| {
+      for rect in active_rects(frame, &regions, position) {
+        let Some((x0, y0, x1, y1)) = clip(frame, rect) else {
+          continue;
+        };
+        for by in (y0..y1).step_by(size) {
+          for bx in (x0..x1).step_by(size) {
+            let (bx1, by1) = ((bx + size).min(x1), (by + size).min(y1));
+            let mut sum = [0u32; 4];
+            for y in by..by1 {
+              let row = &frame.data[y * frame.stride + bx * 4..y * frame.stride + bx1 * 4];
+              for pixel in row.chunks_exact(4) {
+                for c in 0..4 {
+                  sum[c] += pixel[c] as u32;
+                }
+              }
+            }
+            let count = ((bx1 - bx) * (by1 - by)) as u32;
+            let average = sum.map(|total| (total / count) as u8);
+            for y in by..by1 {
+              let row = &mut frame.data[y * frame.stride + bx * 4..y * frame.stride + bx1 * 4];
+              for pixel in row.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&average);
+              }
+            }
+          }
+        }
+      }
+    },
+  )
+}
+
+/// Burns subtitle cues into RGBA frames
+fn subtitles(cues: Arc<Vec<Cue>>) -> Result<Vec<gst::Element>> {
+  let renderer = SubtitleRenderer::new(cues);
+  frame_filter(
+    gst::Caps::builder("video/x-raw")
+      .field("format", "RGBA")
+      .build(),
+    move |frame, position| {
+      renderer.draw(
+        frame.data,
+        frame.stride,
+        frame.width,
+        frame.height,
+        position,
+      )
+    },
+  )
+}
+
+/// Keeps a rectangle of the frames; `videocrop` works out the right and
+/// bottom margins from the input size and the caps that follow it
+fn crop(x: u32, y: u32, width: u32, height: u32) -> Result<Vec<gst::Element>> {
+  let videocrop = make_element("videocrop")?;
+  videocrop.set_property("left", x as i32);
+  videocrop.set_property("top", y as i32);
+  videocrop.set_property("right", -1i32);
+  videocrop.set_property("bottom", -1i32);
+  let capsfilter = make_element("capsfilter")?;
+  capsfilter.set_property(
+    "caps",
+    gst::Caps::builder("video/x-raw")
+      .field("width", width as i32)
+      .field("height", height as i32)
+      .build(),
+  );
+  Ok(vec![videocrop, capsfilter])
+}
+
+/// Creates the elements of a filter chain, to be linked in order after a
+/// `videoconvert`
+pub(crate) fn make_video_filters(filters: &[VideoFilter]) -> Result<Vec<gst::Element>> {
+  let mut elements = Vec::new();
+  for filter in filters {
+    match *filter {
+      VideoFilter::ChromaKey {
+        color,
+        similarity,
+        blend,
+      } => elements.extend(chroma_key(color, similarity, blend)?),
+      VideoFilter::Lut3d(ref lut) => elements.extend(lut3d(lut.clone())?),
+      VideoFilter::BoxBlur {
+        radius,
+        ref regions,
+      } => elements.extend(box_blur(radius, regions.clone())?),
+      VideoFilter::Pixelate { size, ref regions } => {
+        elements.extend(pixelate(size, regions.clone())?)
+      }
+      VideoFilter::Subtitles(ref cues) => elements.extend(subtitles(cues.clone())?),
+      VideoFilter::Crop {
+        x,
+        y,
+        width,
+        height,
+      } => elements.extend(crop(x, y, width, height)?),
+      VideoFilter::InverseTelecine => elements.push(make_element("ivtc")?),
+      VideoFilter::Deinterlace => {
+        // Decoders often flag interlaced content as progressive, which the
+        // default mode would pass through
+        let deinterlace = make_element("deinterlace")?;
+        deinterlace.set_property_from_str("mode", "interlaced");
+        elements.push(deinterlace);
+      }
+    }
+  }
+  if !elements.is_empty() {
+    elements.push(make_element("videoconvert")?);
+  }
+  Ok(elements)
+}
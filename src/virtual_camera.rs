@@ -0,0 +1,212 @@
+//! # Virtual Cameras
+//!
+//! Publishes the frames of an AppSink as a webcam that other applications
+//! (browsers, video call apps, OBS) can open. On Linux the frames are written
+//! to a v4l2loopback device, found by path or by its card label
+//! (`modprobe v4l2loopback card_label="Node Cam"`). Other virtual camera
+//! drivers, such as akvirtualcamera, are fed by passing the sink element
+//! they provide in `VirtualCameraOptions.sink`.
+
+use crate::kit::GstKit;
+use crate::transcode::{launch, pop_bus_error};
+use gst::prelude::*;
+use gst_app::{AppSink, AppSrc};
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Options for `publishVirtualCamera`
+#[napi(object)]
+pub struct VirtualCameraOptions {
+  /// Raw video format written to the device (default: "YUY2", which every
+  /// webcam consumer reads)
+  pub format: Option<String>,
+  /// Sink description replacing `v4l2sink`, for other virtual camera
+  /// drivers; the device argument is then only used as the camera's name
+  pub sink: Option<String>,
+}
+
+/// Finds the video device with a path or card label
+fn resolve_device(device: &str) -> Result<String> {
+  if device.starts_with("/dev/") || Path::new(device).exists() {
+    return Ok(device.to_string());
+  }
+  if !cfg!(target_os = "linux") {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Virtual camera devices are only found by name on Linux; pass the sink of your virtual camera driver for {}",
+        device
+      ),
+    ));
+  }
+  let entries = std::fs::read_dir("/sys/class/video4linux").map_err(|_| {
+    Error::new(
+      Status::GenericFailure,
+      "No video devices found (is v4l2loopback loaded?)".to_string(),
+    )
+  })?;
+  let mut nodes: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+  nodes.sort();
+  nodes
+    .iter()
+    .find(|node| {
+      std::fs::read_to_string(node.join("name"))
+        .is_ok_and(|name| name.trim().eq_ignore_ascii_case(device.trim()))
+    })
+    .and_then(|node| node.file_name())
+    .map(|name| format!("/dev/{}", name.to_string_lossy()))
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Video device not found: {}", device),
+      )
+    })
+}
+
+/// Frames of an AppSink published as a webcam, created by
+/// `publishVirtualCamera`
+#[napi]
+pub struct VirtualCamera {
+  appsink: AppSink,
+  pipeline: gst::Pipeline,
+  device: String,
+  frames: Arc<AtomicU32>,
+}
+
+#[napi]
+impl VirtualCamera {
+  /// Path of the device written to, or the name given with a custom sink
+  #[napi(getter)]
+  pub fn device(&self) -> String {
+    self.device.clone()
+  }
+
+  /// Number of frames published so far
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.frames.load(Ordering::SeqCst)
+  }
+
+  /// Stops publishing; the sink's frames can be pulled again afterwards
+  ///
+  /// # Returns
+  /// * `Result<()>` - An error if writing to the device failed
+  ///
+  /// # Example
+  /// ```javascript
+  /// camera.stop();
+  /// ```
+  #[napi]
+  pub fn stop(&self) -> Result<()> {
+    self
+      .appsink
+      .set_callbacks(gst_app::AppSinkCallbacks::builder().build());
+    let error = pop_bus_error(&self.pipeline);
+    let _ = self.pipeline.set_state(gst::State::Null);
+    match error {
+      Some(error) => Err(error),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Drop for VirtualCamera {
+  fn drop(&mut self) {
+    let _ = self.stop();
+  }
+}
+
+/// Publishes the frames of an AppSink to a virtual camera device
+///
+/// The sink's callbacks are taken over until the camera is stopped. Frames
+/// are converted to the device format and written as they arrive.
+///
+/// # Arguments
+/// * `kit` - The kit running the pipeline
+/// * `sink_name` - The name of the AppSink element
+/// * `device_path_or_name` - Device path ("/dev/video10") or card label
+///   ("Node Cam") of a v4l2loopback device
+/// * `options` - Optional format and sink
+///
+/// # Returns
+/// * `Result<VirtualCamera>` - The running publisher
+///
+/// # Example
+/// ```javascript
+/// kit.setPipeline("v4l2src ! videoconvert ! facedetect ! videoconvert ! appsink name=sink");
+/// const camera = publishVirtualCamera(kit, "sink", "Node Cam");
+/// kit.play();
+/// ```
+#[napi]
+pub fn publish_virtual_camera(
+  kit: &GstKit,
+  sink_name: String,
+  device_path_or_name: String,
+  options: Option<VirtualCameraOptions>,
+) -> Result<VirtualCamera> {
+  let options = options.unwrap_or(VirtualCameraOptions {
+    format: None,
+    sink: None,
+  });
+  let format = options.format.unwrap_or_else(|| "YUY2".to_string());
+  if gst_video::VideoFormat::from_string(&format) == gst_video::VideoFormat::Unknown {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Unknown video format: {}", format),
+    ));
+  }
+  let appsink = kit.app_sink(&sink_name)?;
+  let (device, sink) = match options.sink {
+    Some(sink) => (device_path_or_name, sink),
+    None => {
+      let device = resolve_device(&device_path_or_name)?;
+      let sink = format!("v4l2sink device=\"{}\"", device);
+      (device, sink)
+    }
+  };
+
+  // Consumers read the latest frame, so nothing waits on the clock
+  let pipeline = launch(&format!(
+    "appsrc name=src format=time ! videoconvert ! videoscale ! video/x-raw,format={} ! {} sync=false",
+    format, sink
+  ))?;
+  let appsrc = pipeline
+    .by_name("src")
+    .and_then(|el| el.downcast::<AppSrc>().ok())
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Virtual camera source not found"))?;
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    let reason = pop_bus_error(&pipeline)
+      .map(|error| error.reason)
+      .unwrap_or_else(|| e.to_string());
+    let _ = pipeline.set_state(gst::State::Null);
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to open {}: {}", device, reason),
+    )
+  })?;
+
+  let frames = Arc::new(AtomicU32::new(0));
+  let published = frames.clone();
+  appsink.set_callbacks(
+    gst_app::AppSinkCallbacks::builder()
+      .new_sample(move |appsink| {
+        let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+        appsrc.push_sample(&sample)?;
+        published.fetch_add(1, Ordering::SeqCst);
+        Ok(gst::FlowSuccess::Ok)
+      })
+      .build(),
+  );
+  Ok(VirtualCamera {
+    appsink,
+    pipeline,
+    device,
+    frames,
+  })
+}
@@ -0,0 +1,165 @@
+//! # Waveform Videos
+//!
+//! Turns an audio file into a video of its animated waveform or spectrum,
+//! with the audio kept as the soundtrack: the usual way to share a podcast
+//! clip on platforms that only take video. The pictures come from
+//! GStreamer's audio visualizers and are encoded like any other transcode
+//! output.
+
+use crate::benchmark::parse_resolution;
+use crate::kit::to_uri;
+use crate::probe::probe_with_gstreamer;
+use crate::transcode::{audio_codec_spec, container_spec, launch, video_codec_spec, wait_for_eos};
+use gst::prelude::*;
+use gstreamer as gst;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+
+/// Options for `renderWaveformVideo`
+#[napi(object)]
+pub struct WaveformVideoOptions {
+  /// Visualization: "wave" (waveform, default), "dots" (waveform as dots),
+  /// "spectrum" (frequency bars), "stereo" (left against right channel),
+  /// "synaesthesia" or "goom"
+  pub style: Option<String>,
+  /// Frame size as "WIDTHxHEIGHT" (default: "1280x720")
+  pub resolution: Option<String>,
+  /// Frames per second (default: 30)
+  pub fps: Option<u32>,
+  /// Keep the audio as the soundtrack of the video (default: true)
+  pub audio: Option<bool>,
+  /// Output container; defaults to the output file extension
+  pub container: Option<String>,
+  /// Video codec, defaults to the usual codec for the container
+  pub video_codec: Option<String>,
+}
+
+/// Visualizer element and properties for a style
+fn visualizer(style: &str) -> Result<&'static str> {
+  Ok(match style {
+    "wave" => "wavescope style=color-lines",
+    "dots" => "wavescope style=color-dots",
+    "spectrum" => "spectrascope",
+    "stereo" => "spacescope style=color-lines",
+    "synaesthesia" => "synaescope",
+    "goom" => "goom",
+    style => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Unsupported waveform style: {}", style),
+      ))
+    }
+  })
+}
+
+/// Renders a video of the animated waveform or spectrum of an audio file
+///
+/// # Arguments
+/// * `audio_path` - Path or URI of the audio (or of any media with an audio track)
+/// * `output_path` - Where to write the video
+/// * `options` - Visualization style, frame size and output format
+///
+/// # Example
+/// ```javascript
+/// renderWaveformVideo("episode-clip.mp3", "clip.mp4", { style: "spectrum", resolution: "1080x1080" });
+/// ```
+#[napi]
+pub fn render_waveform_video(
+  audio_path: String,
+  output_path: String,
+  options: Option<WaveformVideoOptions>,
+) -> Result<()> {
+  gst::init().map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to initialize GStreamer: {}", e),
+    )
+  })?;
+
+  let options = options.unwrap_or(WaveformVideoOptions {
+    style: None,
+    resolution: None,
+    fps: None,
+    audio: None,
+    container: None,
+    video_codec: None,
+  });
+  let visualizer = visualizer(options.style.as_deref().unwrap_or("wave"))?;
+  let (width, height) = parse_resolution(options.resolution.as_deref().unwrap_or("1280x720"))?;
+  let fps = options.fps.unwrap_or(30).max(1);
+  let container = options
+    .container
+    .or_else(|| {
+      Path::new(&output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+    })
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Cannot infer a container for {}", output_path),
+      )
+    })?;
+  let container = container_spec(&container)?;
+  let codec = video_codec_spec(
+    options
+      .video_codec
+      .as_deref()
+      .unwrap_or(container.video_codec),
+  )?;
+
+  let info = probe_with_gstreamer(audio_path.clone(), None)?;
+  if info.audio.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{} has no audio stream", audio_path),
+    ));
+  }
+
+  let parser = codec
+    .parser
+    .map(|p| format!(" ! {}", p))
+    .unwrap_or_default();
+  let mut description = format!(
+    "uridecodebin uri=\"{}\" caps=audio/x-raw expose-all-streams=false ! audioconvert ! tee name=t \
+     t. ! queue ! audioconvert ! {} ! video/x-raw,width={},height={},framerate={}/1 ! \
+     videoconvert ! {}{} ! queue ! mux. \
+     {} name=mux ! filesink name=sink",
+    to_uri(&audio_path)?,
+    visualizer,
+    width,
+    height,
+    fps,
+    codec.encoder,
+    parser,
+    container.muxer
+  );
+  if options.audio.unwrap_or(true) {
+    let audio = audio_codec_spec(container.audio_codec)?;
+    let parser = audio
+      .parser
+      .map(|p| format!(" ! {}", p))
+      .unwrap_or_default();
+    description.push_str(&format!(
+      " t. ! queue ! audioconvert ! audioresample ! {}{} ! queue ! mux.",
+      audio.encoder, parser
+    ));
+  }
+  let pipeline = launch(&description)?;
+  let sink = pipeline
+    .by_name("sink")
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Output sink not found"))?;
+  sink.set_property("location", &output_path);
+
+  pipeline.set_state(gst::State::Playing).map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to set state to Playing: {}", e),
+    )
+  })?;
+  let result = wait_for_eos(&pipeline);
+  let _ = pipeline.set_state(gst::State::Null);
+  result
+}
@@ -0,0 +1,168 @@
+//! # WebM Cues
+//!
+//! Byte-range seeking of WebM/Matroska files over HTTP. The Cues element of
+//! a file lists the cluster holding the keyframe at each cue time, so a
+//! server can answer a seek to any time with the byte range of one cluster,
+//! and a Media Source Extensions client can append the header followed by
+//! that cluster. Files written by this crate (`recordFromPipeline` and
+//! transcodes to the "webm" container) carry cues for video keyframes.
+
+use crate::container_dump::{matroska_cues, MatroskaCues};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::fs::File;
+use std::io::BufReader;
+
+/// A cue point of a WebM file
+#[napi(object)]
+pub struct WebmCue {
+  /// Time of the cue in milliseconds
+  pub time_ms: f64,
+  /// Number of the track the cue is for
+  pub track: u32,
+  /// File offset of the cluster holding the cued frame
+  pub cluster_offset: i64,
+  /// Offset of the cued frame within the cluster payload, if given
+  pub relative_position: Option<i64>,
+}
+
+/// The cue index of a WebM file
+#[napi(object)]
+pub struct WebmCues {
+  /// Duration of the file in milliseconds, if set
+  pub duration_ms: Option<f64>,
+  /// Size of the file in bytes
+  pub file_size: i64,
+  /// Size of everything before the first cluster (EBML header, segment
+  /// header, tracks), which a decoder needs before any cluster
+  pub header_size: i64,
+  /// Cue points in file order
+  pub cues: Vec<WebmCue>,
+}
+
+/// A byte range of a WebM file, for an HTTP `Range` request
+#[napi(object)]
+pub struct WebmByteRange {
+  /// First byte of the cluster holding the keyframe at or before the time
+  pub start: i64,
+  /// Last byte before the next cued cluster, or of the last cluster
+  /// (inclusive, as in a `Content-Range` header)
+  pub end: i64,
+  /// Time of the keyframe the range starts with, in milliseconds
+  pub time_ms: f64,
+}
+
+/// Reads the cue index of a file, failing if it has none
+fn read_cues(path: &str) -> Result<(MatroskaCues, u64)> {
+  let read_error = |e: std::io::Error| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read {}: {}", path, e),
+    )
+  };
+  let file = File::open(path).map_err(read_error)?;
+  let len = file.metadata().map_err(read_error)?.len();
+  let cues = matroska_cues(BufReader::new(file), len).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("{} is not a WebM/Matroska file", path),
+    )
+  })?;
+  if cues.points.is_empty() {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!("{} has no cues", path),
+    ));
+  }
+  Ok((cues, len))
+}
+
+/// Milliseconds of a time in timestamp units
+fn to_ms(cues: &MatroskaCues, time: f64) -> f64 {
+  time * cues.timecode_scale as f64 / 1_000_000.0
+}
+
+/// Reads the cue points of a WebM file
+///
+/// # Arguments
+/// * `path` - Path of the WebM or Matroska file
+///
+/// # Returns
+/// * `Result<WebmCues>` - The cues, header size and duration
+///
+/// # Example
+/// ```javascript
+/// const { headerSize, cues } = getWebmCues("recording.webm");
+/// res.setHeader("X-Keyframes", cues.map((cue) => cue.timeMs).join(","));
+/// ```
+#[napi]
+pub fn get_webm_cues(path: String) -> Result<WebmCues> {
+  let (cues, len) = read_cues(&path)?;
+  Ok(WebmCues {
+    duration_ms: cues.duration.map(|duration| to_ms(&cues, duration)),
+    file_size: len as i64,
+    header_size: cues.first_cluster.unwrap_or(len) as i64,
+    cues: cues
+      .points
+      .iter()
+      .map(|&(time, track, cluster, relative)| WebmCue {
+        time_ms: to_ms(&cues, time as f64),
+        track: track as u32,
+        cluster_offset: (cues.segment_data + cluster) as i64,
+        relative_position: relative.map(|relative| relative as i64),
+      })
+      .collect(),
+  })
+}
+
+/// Finds the bytes to serve for a seek: from the cluster holding the last
+/// keyframe at or before a time up to the next cued cluster
+///
+/// # Arguments
+/// * `path` - Path of the WebM or Matroska file
+/// * `ms` - The time seeked to, in milliseconds
+///
+/// # Returns
+/// * `Result<WebmByteRange>` - The byte range of the cluster
+///
+/// # Example
+/// ```javascript
+/// const { start, end } = byteRangeForTime("recording.webm", 90_000);
+/// res.writeHead(206, { "Content-Range": `bytes ${start}-${end}/${size}` });
+/// fs.createReadStream("recording.webm", { start, end }).pipe(res);
+/// ```
+#[napi]
+pub fn byte_range_for_time(path: String, ms: f64) -> Result<WebmByteRange> {
+  if !ms.is_finite() || ms < 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid time: {}", ms),
+    ));
+  }
+  let (cues, len) = read_cues(&path)?;
+  let mut points: Vec<(f64, u64)> = cues
+    .points
+    .iter()
+    .map(|&(time, _, cluster, _)| (to_ms(&cues, time as f64), cues.segment_data + cluster))
+    .collect();
+  points.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.total_cmp(&b.0)));
+  points.dedup_by_key(|point| point.1);
+
+  let index = points
+    .iter()
+    .rposition(|&(time, _)| time <= ms)
+    .unwrap_or(0);
+  let (time_ms, start) = points[index];
+  // The cluster ends where the next cued one starts, or at the Cues when
+  // they follow the last cluster
+  let end = points
+    .get(index + 1)
+    .map(|&(_, cluster)| cluster)
+    .or(cues.cues_offset.filter(|&offset| offset > start))
+    .unwrap_or(len);
+  Ok(WebmByteRange {
+    start: start as i64,
+    end: end as i64 - 1,
+    time_ms,
+  })
+}